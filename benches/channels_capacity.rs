@@ -0,0 +1,36 @@
+//! Compare adding 1000 channels to a default `Channels` vs one created with
+//! `Channels::with_capacity`.
+//!
+//! Run with: cargo bench --bench channels_capacity
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use sockudo_client::channels::Channels;
+
+const CHANNELS: usize = 1000;
+
+fn fill(channels: &Channels, names: &[String]) -> usize {
+    for name in names {
+        channels.add(name).unwrap();
+        channels.queue_pending_subscription(name);
+    }
+    channels.len()
+}
+
+fn bench_capacity(c: &mut Criterion) {
+    let names: Vec<String> = (0..CHANNELS).map(|i| format!("channel-{}", i)).collect();
+
+    let mut group = c.benchmark_group("channels_1000");
+
+    group.bench_function("default", |b| {
+        b.iter(|| fill(&Channels::new(), black_box(&names)))
+    });
+
+    group.bench_function("with_capacity", |b| {
+        b.iter(|| fill(&Channels::with_capacity(CHANNELS), black_box(&names)))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_capacity);
+criterion_main!(benches);
@@ -0,0 +1,85 @@
+//! Compare `FilterOp::evaluate` with a compiled filter over 100K payloads.
+//!
+//! Run with: cargo bench --bench filter_compile
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use serde_json::{json, Value};
+use sockudo_client::{CompareOp, FilterOp};
+
+const INVOCATIONS: usize = 100_000;
+
+/// A filter shaped like a typical sports feed subscription
+fn realistic_filter() -> FilterOp {
+    FilterOp::or(vec![
+        FilterOp::and(vec![
+            FilterOp::eq("sport", "football"),
+            FilterOp::in_set(
+                "league",
+                vec![
+                    "premier".to_string(),
+                    "laliga".to_string(),
+                    "seriea".to_string(),
+                    "bundesliga".to_string(),
+                ],
+            ),
+            FilterOp::gte("minute", "45"),
+        ]),
+        FilterOp::and(vec![
+            FilterOp::eq("type", "goal"),
+            FilterOp::json_path("match.score.home", CompareOp::Gt, "2"),
+            FilterOp::not_exists("var_review"),
+        ]),
+    ])
+}
+
+fn payloads() -> Vec<Value> {
+    (0..100)
+        .map(|i| {
+            json!({
+                "sport": if i % 3 == 0 { "tennis" } else { "football" },
+                "league": ["premier", "laliga", "mls", "bundesliga"][i % 4],
+                "minute": (i * 7) % 90,
+                "type": if i % 5 == 0 { "goal" } else { "card" },
+                "match": { "score": { "home": i % 4, "away": i % 3 } },
+            })
+        })
+        .collect()
+}
+
+fn bench_filters(c: &mut Criterion) {
+    let filter = realistic_filter();
+    let compiled = filter.compile();
+    let payloads = payloads();
+
+    let mut group = c.benchmark_group("filter_100k");
+    group.sample_size(20);
+
+    group.bench_function("evaluate", |b| {
+        b.iter(|| {
+            let mut matched = 0;
+            for i in 0..INVOCATIONS {
+                if filter.evaluate(black_box(&payloads[i % payloads.len()])) {
+                    matched += 1;
+                }
+            }
+            matched
+        })
+    });
+
+    group.bench_function("compiled", |b| {
+        b.iter(|| {
+            let mut matched = 0;
+            for i in 0..INVOCATIONS {
+                if compiled.matches(black_box(&payloads[i % payloads.len()])) {
+                    matched += 1;
+                }
+            }
+            matched
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_filters);
+criterion_main!(benches);
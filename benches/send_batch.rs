@@ -0,0 +1,109 @@
+//! Compare resubscribing 100 channels with individual sends vs `send_batch`.
+//!
+//! Run with: cargo bench --bench send_batch
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use futures_util::{SinkExt, StreamExt};
+use sockudo_client::{ConnectionManager, PusherOptions};
+use tokio::runtime::Runtime;
+use tokio_tungstenite::tungstenite::Message;
+
+const CHANNELS: usize = 100;
+
+/// Start a server that counts every text frame it receives
+fn start_server(rt: &Runtime) -> (u16, Arc<AtomicUsize>) {
+    let received = Arc::new(AtomicUsize::new(0));
+    let counter = received.clone();
+
+    let listener = rt
+        .block_on(tokio::net::TcpListener::bind("127.0.0.1:0"))
+        .unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    rt.spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+        let established = serde_json::json!({
+            "event": "pusher:connection_established",
+            "data": r#"{"socket_id":"1.1","activity_timeout":120}"#,
+        });
+        ws.send(Message::Text(established.to_string()))
+            .await
+            .unwrap();
+
+        while let Some(Ok(message)) = ws.next().await {
+            if message.is_text() {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    });
+
+    (port, received)
+}
+
+fn wait_for(received: &AtomicUsize, target: usize) {
+    while received.load(Ordering::SeqCst) < target {
+        std::thread::yield_now();
+    }
+}
+
+fn resubscribe(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let (port, received) = start_server(&rt);
+
+    let options = PusherOptions::new("bench-key")
+        .ws_host("127.0.0.1")
+        .ws_port(port)
+        .use_tls(false);
+    let manager = ConnectionManager::new(options.into());
+    rt.block_on(async {
+        manager.connect().await.unwrap();
+        while !manager.is_connected() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    });
+
+    let messages: Vec<String> = (0..CHANNELS)
+        .map(|i| {
+            serde_json::json!({
+                "event": "pusher:subscribe",
+                "data": { "channel": format!("channel-{}", i) },
+            })
+            .to_string()
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("resubscribe_100_channels");
+
+    group.bench_function("individual_send", |b| {
+        b.iter(|| {
+            let target = received.load(Ordering::SeqCst) + CHANNELS;
+            for message in &messages {
+                // The command queue is bounded, so retry until accepted
+                while !manager.send(message) {
+                    std::thread::yield_now();
+                }
+            }
+            wait_for(&received, target);
+        })
+    });
+
+    group.bench_function("send_batch", |b| {
+        b.iter(|| {
+            let target = received.load(Ordering::SeqCst) + CHANNELS;
+            while manager.send_batch(messages.clone()) == 0 {
+                std::thread::yield_now();
+            }
+            wait_for(&received, target);
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, resubscribe);
+criterion_main!(benches);
@@ -38,13 +38,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let public_channel = client.subscribe("chat-room")?;
     public_channel.bind("message", |event| {
         println!("[Chat] Message: {:?}", event.data);
-    });
+    })?;
 
     // Subscribe to a private channel (requires auth endpoint)
     let private_channel = client.subscribe("private-user-123")?;
     private_channel.bind("notification", |event| {
         println!("[Private] Notification: {:?}", event.data);
-    });
+    })?;
 
     // Subscribe with tag filtering
     let filter = FilterOp::and(vec![
@@ -57,7 +57,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let sports_channel = client.subscribe_with_filter("sports-updates", Some(filter))?;
     sports_channel.bind("score-update", |event| {
         println!("[Sports] Score: {:?}", event.data);
-    });
+    })?;
 
     // Check delta compression stats periodically
     if let Some(stats) = client.get_delta_stats() {
@@ -0,0 +1,34 @@
+//! Benchmark delta compression on synthetic JSON messages.
+//!
+//! Run with: cargo run --example delta_bench --features bench-utils
+
+use sockudo_client::delta::bench::{benchmark_sequence, DeltaBenchReport};
+use sockudo_client::DeltaAlgorithm;
+
+fn main() {
+    // Simulate a price feed where only a few fields change per message
+    let messages: Vec<Vec<u8>> = (0..100)
+        .map(|i| {
+            serde_json::json!({
+                "symbol": "BTC-USD",
+                "exchange": "example",
+                "price": 42_000.0 + (i as f64) * 0.5,
+                "volume": 1_000 + i * 3,
+                "bids": [[41_999.5, 1.2], [41_999.0, 3.4], [41_998.5, 0.7]],
+                "asks": [[42_000.5, 0.9], [42_001.0, 2.1], [42_001.5, 4.0]],
+                "sequence": i,
+            })
+            .to_string()
+            .into_bytes()
+        })
+        .collect();
+    let messages: Vec<&[u8]> = messages.iter().map(|m| m.as_slice()).collect();
+
+    let mut report = DeltaBenchReport::new();
+    for algorithm in [DeltaAlgorithm::Fossil, DeltaAlgorithm::Xdelta3] {
+        report.add(benchmark_sequence(algorithm, &messages));
+    }
+
+    println!("Delta compression over {} messages:", messages.len());
+    report.print_table();
+}
@@ -30,7 +30,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         if let Some(ref data) = event.data {
             println!("  Internal data: {}", data);
         }
-    });
+    })?;
 
     // Handle subscription succeeded - get initial member list
     channel.bind("pusher:subscription_succeeded", |event| {
@@ -50,7 +50,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
             }
         }
-    });
+    })?;
 
     // Handle new members joining
     channel.bind("pusher:member_added", |event| {
@@ -69,7 +69,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 println!("Member joined: {} ({:?})", user_id, user_info);
             }
         }
-    });
+    })?;
 
     // Handle members leaving
     channel.bind("pusher:member_removed", |event| {
@@ -87,7 +87,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 println!("Member left: {}", user_id);
             }
         }
-    });
+    })?;
 
     // Handle chat messages
     channel.bind("message", |event| {
@@ -103,7 +103,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 println!("[{}]: {}", from, text);
             }
         }
-    });
+    })?;
 
     // Now connect - event handlers are ready
     client.connect().await?;
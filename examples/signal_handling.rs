@@ -46,7 +46,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     channel.bind("demo-event", move |event| {
         println!("📨 Received event: {:?}", event.data);
         msg_count_clone.store(true, Ordering::SeqCst);
-    });
+    })?;
 
     println!("✓ Subscribed to 'private-demo-channel'\n");
 
@@ -0,0 +1,79 @@
+import 'dart:async';
+import 'dart:convert';
+import 'dart:io';
+
+import 'package:flutter_test/flutter_test.dart';
+import 'package:integration_test/integration_test.dart';
+import 'package:sockudo_client/src/bridge_generated.dart/flutter_api.dart';
+import 'package:sockudo_client/src/bridge_generated.dart/frb_generated.dart';
+import 'package:sockudo_client/src/sockudo_client.dart';
+
+/// Pusher-compatible server that confirms subscriptions and then sends
+/// `greeting` on the subscribed channel
+Future<HttpServer> startMockServer() async {
+  final server = await HttpServer.bind(InternetAddress.loopbackIPv4, 0);
+  server.transform(WebSocketTransformer()).listen((socket) {
+    socket.add(jsonEncode({
+      'event': 'pusher:connection_established',
+      'data': jsonEncode({'socket_id': '1.1', 'activity_timeout': 120}),
+    }));
+    socket.listen((raw) {
+      final message = jsonDecode(raw as String) as Map<String, dynamic>;
+      if (message['event'] != 'pusher:subscribe') {
+        return;
+      }
+      // The client sends subscribe data as a JSON-encoded string
+      final data =
+          jsonDecode(message['data'] as String) as Map<String, dynamic>;
+      final channel = data['channel'];
+      socket.add(jsonEncode({
+        'event': 'pusher_internal:subscription_succeeded',
+        'channel': channel,
+        'data': '{}',
+      }));
+      socket.add(jsonEncode({
+        'event': 'greeting',
+        'channel': channel,
+        'data': jsonEncode({'text': 'hello'}),
+      }));
+    });
+  });
+  return server;
+}
+
+void main() {
+  IntegrationTestWidgetsFlutterBinding.ensureInitialized();
+
+  setUpAll(() async => await RustLib.init());
+
+  testWidgets('events reach Dart through the event stream', (tester) async {
+    final server = await startMockServer();
+    final handle = sockudoCreateClient(
+      options: SockudoOptions(
+        appKey: 'app-key',
+        wsHost: '127.0.0.1',
+        wsPort: server.port,
+        useTls: false,
+      ),
+    );
+
+    final greeting = sockudoEvents(handle)
+        .firstWhere((event) => event.eventName == 'greeting')
+        .timeout(const Duration(seconds: 10));
+
+    await sockudoConnect(handle: handle);
+    sockudoSubscribe(handle: handle, channelName: 'news');
+
+    final event = await greeting;
+    expect(event.channel, 'news');
+    expect(jsonDecode(event.data!), {'text': 'hello'});
+    expect(event.timestampMs, greaterThan(0));
+
+    // Destroying the client ends its stream
+    final remaining = sockudoEvents(handle).toList();
+    await sockudoDestroyClient(handle: handle);
+    await remaining.timeout(const Duration(seconds: 10));
+
+    await server.close(force: true);
+  });
+}
@@ -1,4 +1,5 @@
 import 'bridge_generated.dart';
+import 'bridge_generated.dart/flutter_api.dart';
 
 /// Re-export main classes with primary Sockudo names
 export 'bridge_generated.dart' show
@@ -55,3 +56,18 @@ extension PusherOptionsBuilder on PusherOptions {
     );
   }
 }
+
+/// Every event of the client behind [handle], as created by
+/// `sockudoCreateClient`.
+///
+/// The stream ends once the client is destroyed with
+/// `sockudoDestroyClient`.
+Stream<SockudoEvent> sockudoEvents(int handle) async* {
+  while (true) {
+    final event = await sockudoNextEvent(handle: handle);
+    if (event == null) {
+      return;
+    }
+    yield event;
+  }
+}
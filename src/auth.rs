@@ -5,8 +5,15 @@
 
 use crate::channels::ChannelAuthData;
 use crate::error::{Result, SockudoError};
+use crate::options::{AuthSignatureMode, SensitiveString, SockudoOptions};
+use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
 use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use tracing::warn;
 
 /// Request body for channel authorization
 #[derive(Debug, Serialize)]
@@ -51,6 +58,14 @@ pub struct AuthClient {
     auth_headers: HashMap<String, String>,
     user_auth_endpoint: Option<String>,
     user_auth_headers: HashMap<String, String>,
+    /// How requests are signed for the auth endpoint
+    signature_mode: AuthSignatureMode,
+    /// Shared HTTP client, reused across requests for connection pooling
+    #[cfg(not(target_arch = "wasm32"))]
+    http: reqwest::Client,
+    /// Extra `fetch` options (`credentials`, `mode`, `cache`, `headers`)
+    #[cfg(target_arch = "wasm32")]
+    fetch_options: Option<js_sys::Object>,
 }
 
 impl AuthClient {
@@ -61,12 +76,141 @@ impl AuthClient {
         user_auth_endpoint: Option<String>,
         user_auth_headers: Option<HashMap<String, String>>,
     ) -> Self {
-        Self {
+        let mut builder = Self::builder()
+            .auth_headers(auth_headers.unwrap_or_default())
+            .user_auth_headers(user_auth_headers.unwrap_or_default());
+        builder.auth_endpoint = auth_endpoint;
+        builder.user_auth_endpoint = user_auth_endpoint;
+        builder.build()
+    }
+
+    /// Create an authorization client for `channel_name`, using the endpoint
+    /// from its prefix config in `global_opts` if set, and the global one otherwise
+    pub fn for_channel(channel_name: &str, global_opts: &SockudoOptions) -> Self {
+        let auth_endpoint = global_opts
+            .channel_config(channel_name)
+            .and_then(|config| config.auth_endpoint.clone())
+            .or_else(|| global_opts.auth_endpoint.clone());
+        Self::new(
             auth_endpoint,
-            auth_headers: auth_headers.unwrap_or_default(),
-            user_auth_endpoint,
-            user_auth_headers: user_auth_headers.unwrap_or_default(),
+            global_opts.auth_headers.clone(),
+            global_opts.user_auth_endpoint.clone(),
+            global_opts.user_auth_headers.clone(),
+        )
+        .with_signature_mode(global_opts.auth_signature_mode.clone())
+    }
+
+    /// Use `endpoint` for channel authorization instead of the configured one
+    pub fn with_auth_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.auth_endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// Create a builder for configuring endpoints and the HTTP client
+    pub fn builder() -> AuthClientBuilder {
+        AuthClientBuilder::default()
+    }
+
+    /// Sign every auth request with HMAC-SHA256 using a secret shared with
+    /// the auth endpoint (see `AuthSignatureMode`)
+    pub fn with_hmac_auth(self, app_id: &str, app_secret: &str) -> Self {
+        self.with_signature_mode(AuthSignatureMode::Hmac {
+            app_id: app_id.to_string(),
+            app_secret: SensitiveString::new(app_secret),
+        })
+    }
+
+    /// Set how auth requests are signed
+    pub fn with_signature_mode(mut self, mode: AuthSignatureMode) -> Self {
+        self.signature_mode = mode;
+        self
+    }
+
+    /// Headers signing a request with form `body`, if signing is enabled
+    fn signature_headers(&self, body: &str) -> Vec<(&'static str, String)> {
+        signature_headers(&self.signature_mode, body)
+    }
+
+    /// Use a preconfigured `reqwest::Client` for all requests
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_custom_http_client(mut self, client: reqwest::Client) -> Self {
+        self.http = client;
+        self
+    }
+
+    /// Pass extra options to `fetch`.
+    ///
+    /// Supports `credentials`, `mode` and `cache` string values and a
+    /// `headers` object; other keys are ignored.
+    #[cfg(target_arch = "wasm32")]
+    pub fn with_custom_fetch_options(mut self, options: js_sys::Object) -> Self {
+        self.fetch_options = Some(options);
+        self
+    }
+
+    /// Apply `fetch_options` to a request
+    #[cfg(target_arch = "wasm32")]
+    fn apply_fetch_options(
+        &self,
+        mut request: gloo_net::http::RequestBuilder,
+    ) -> gloo_net::http::RequestBuilder {
+        use wasm_bindgen::JsCast;
+
+        let Some(ref options) = self.fetch_options else {
+            return request;
+        };
+        let get = |key: &str| js_sys::Reflect::get(options, &key.into()).ok();
+
+        if let Some(v) =
+            get("credentials").and_then(|v| web_sys::RequestCredentials::from_js_value(&v))
+        {
+            request = request.credentials(v);
+        }
+        if let Some(v) = get("mode").and_then(|v| web_sys::RequestMode::from_js_value(&v)) {
+            request = request.mode(v);
+        }
+        if let Some(v) = get("cache").and_then(|v| web_sys::RequestCache::from_js_value(&v)) {
+            request = request.cache(v);
+        }
+        if let Some(headers) = get("headers").filter(|h| h.is_object()) {
+            for entry in js_sys::Object::entries(headers.unchecked_ref()).iter() {
+                let entry: js_sys::Array = entry.unchecked_into();
+                if let (Some(key), Some(value)) =
+                    (entry.get(0).as_string(), entry.get(1).as_string())
+                {
+                    request = request.header(&key, &value);
+                }
+            }
         }
+        request
+    }
+
+    /// Attach `params` as the request body: multipart form data, or a signed
+    /// URL-encoded form when signing is enabled
+    #[cfg(target_arch = "wasm32")]
+    fn with_form_body(
+        &self,
+        mut request: gloo_net::http::RequestBuilder,
+        params: &[(&str, &str)],
+    ) -> Result<gloo_net::http::Request> {
+        let request = if self.signature_mode == AuthSignatureMode::None {
+            let form_data = web_sys::FormData::new()
+                .map_err(|_| SockudoError::authorization("Failed to create form data"))?;
+            for (key, value) in params {
+                form_data.append_with_str(key, value).map_err(|_| {
+                    SockudoError::authorization(format!("Failed to append {}", key))
+                })?;
+            }
+            request.body(form_data)
+        } else {
+            let body = form_body(params);
+            request = request.header("Content-Type", FORM_CONTENT_TYPE);
+            for (key, value) in self.signature_headers(&body) {
+                request = request.header(key, &value);
+            }
+            request.body(body)
+        };
+        request.map_err(|e| SockudoError::authorization(format!("Failed to build request: {}", e)))
     }
 
     /// Authorize a channel subscription (async)
@@ -81,27 +225,34 @@ impl AuthClient {
         })?;
 
         // Build request body as form data
-        let params = [("socket_id", socket_id), ("channel_name", channel_name)];
+        let body = form_body(&[("socket_id", socket_id), ("channel_name", channel_name)]);
 
         // Make async HTTP POST request
-        let client = reqwest::Client::new();
-        let mut request = client.post(endpoint).form(&params);
+        let mut request = self
+            .http
+            .post(endpoint)
+            .header(reqwest::header::CONTENT_TYPE, FORM_CONTENT_TYPE);
 
         // Add custom headers
         for (key, value) in &self.auth_headers {
             request = request.header(key, value);
         }
+        for (key, value) in self.signature_headers(&body) {
+            request = request.header(key, value);
+        }
+        let request = request.body(body);
 
         // Send request and parse response
         let response = request.send().await.map_err(|e| {
-            SockudoError::authorization(format!("Failed to send authorization request: {}", e))
+            if e.is_timeout() {
+                SockudoError::authorization(format!("Authorization request timed out: {}", e))
+            } else {
+                SockudoError::authorization(format!("Failed to send authorization request: {}", e))
+            }
         })?;
 
         if !response.status().is_success() {
-            return Err(SockudoError::authorization(format!(
-                "Authorization failed with status: {}",
-                response.status()
-            )));
+            return Err(failed_response("Authorization", &response));
         }
 
         let auth_response: AuthResponse = response.json().await.map_err(|e| {
@@ -126,18 +277,6 @@ impl AuthClient {
             SockudoError::authorization("No auth_endpoint configured for private/presence channels")
         })?;
 
-        // Build request body as form data
-        let form_data = web_sys::FormData::new()
-            .map_err(|_| SockudoError::authorization("Failed to create form data"))?;
-
-        form_data
-            .append_with_str("socket_id", socket_id)
-            .map_err(|_| SockudoError::authorization("Failed to append socket_id"))?;
-
-        form_data
-            .append_with_str("channel_name", channel_name)
-            .map_err(|_| SockudoError::authorization("Failed to append channel_name"))?;
-
         // Make HTTP POST request using gloo-net
         let mut request = gloo_net::http::Request::post(endpoint);
 
@@ -146,9 +285,10 @@ impl AuthClient {
             request = request.header(key, value);
         }
 
-        let request = request
-            .body(form_data)
-            .map_err(|e| SockudoError::authorization(format!("Failed to build request: {}", e)))?;
+        let request = self.with_form_body(
+            self.apply_fetch_options(request),
+            &[("socket_id", socket_id), ("channel_name", channel_name)],
+        )?;
 
         // Send request
         let response = request
@@ -184,27 +324,34 @@ impl AuthClient {
             .ok_or_else(|| SockudoError::authorization("No user_auth_endpoint configured"))?;
 
         // Build request body as form data
-        let params = [("socket_id", socket_id)];
+        let body = form_body(&[("socket_id", socket_id)]);
 
         // Make async HTTP POST request
-        let client = reqwest::Client::new();
-        let mut request = client.post(endpoint).form(&params);
+        let mut request = self
+            .http
+            .post(endpoint)
+            .header(reqwest::header::CONTENT_TYPE, FORM_CONTENT_TYPE);
 
         // Add custom headers
         for (key, value) in &self.user_auth_headers {
             request = request.header(key, value);
         }
+        for (key, value) in self.signature_headers(&body) {
+            request = request.header(key, value);
+        }
+        let request = request.body(body);
 
         // Send request and parse response
         let response = request.send().await.map_err(|e| {
-            SockudoError::authorization(format!("Failed to send user auth request: {}", e))
+            if e.is_timeout() {
+                SockudoError::authorization(format!("User auth request timed out: {}", e))
+            } else {
+                SockudoError::authorization(format!("Failed to send user auth request: {}", e))
+            }
         })?;
 
         if !response.status().is_success() {
-            return Err(SockudoError::authorization(format!(
-                "User authentication failed with status: {}",
-                response.status()
-            )));
+            return Err(failed_response("User authentication", &response));
         }
 
         let auth_response: UserAuthResponse = response.json().await.map_err(|e| {
@@ -225,14 +372,6 @@ impl AuthClient {
             .as_ref()
             .ok_or_else(|| SockudoError::authorization("No user_auth_endpoint configured"))?;
 
-        // Build request body as form data
-        let form_data = web_sys::FormData::new()
-            .map_err(|_| SockudoError::authorization("Failed to create form data"))?;
-
-        form_data
-            .append_with_str("socket_id", socket_id)
-            .map_err(|_| SockudoError::authorization("Failed to append socket_id"))?;
-
         // Make HTTP POST request
         let mut request = gloo_net::http::Request::post(endpoint);
 
@@ -241,9 +380,10 @@ impl AuthClient {
             request = request.header(key, value);
         }
 
-        let request = request
-            .body(form_data)
-            .map_err(|e| SockudoError::authorization(format!("Failed to build request: {}", e)))?;
+        let request = self.with_form_body(
+            self.apply_fetch_options(request),
+            &[("socket_id", socket_id)],
+        )?;
 
         // Send request
         let response = request
@@ -270,6 +410,239 @@ impl AuthClient {
     }
 }
 
+/// Builder for [`AuthClient`]
+#[derive(Default)]
+pub struct AuthClientBuilder {
+    auth_endpoint: Option<String>,
+    auth_headers: HashMap<String, String>,
+    user_auth_endpoint: Option<String>,
+    user_auth_headers: HashMap<String, String>,
+    #[cfg(not(target_arch = "wasm32"))]
+    http: HttpSettings,
+}
+
+/// HTTP client settings collected by [`AuthClientBuilder`]
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default)]
+struct HttpSettings {
+    client: Option<reqwest::Client>,
+    timeout: Option<Duration>,
+    pool_max_idle_per_host: Option<usize>,
+    user_agent: Option<String>,
+    headers: Vec<(String, String)>,
+    redirect_policy: Option<reqwest::redirect::Policy>,
+}
+
+impl AuthClientBuilder {
+    /// Set the channel authorization endpoint
+    pub fn auth_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.auth_endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// Set headers sent with channel authorization requests
+    pub fn auth_headers(mut self, headers: HashMap<String, String>) -> Self {
+        self.auth_headers = headers;
+        self
+    }
+
+    /// Set the user authentication endpoint
+    pub fn user_auth_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.user_auth_endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// Set headers sent with user authentication requests
+    pub fn user_auth_headers(mut self, headers: HashMap<String, String>) -> Self {
+        self.user_auth_headers = headers;
+        self
+    }
+
+    /// Use a preconfigured client; the HTTP options below are then ignored
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn http_client(mut self, client: reqwest::Client) -> Self {
+        self.http.client = Some(client);
+        self
+    }
+
+    /// Set the total timeout for each request
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.http.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the maximum number of idle pooled connections per host
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.http.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Set the `User-Agent` header
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.http.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Add a header sent with every request
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn add_header(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.http.headers.push((key.into(), value.into()));
+        self
+    }
+
+    /// Set how redirects are followed
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn redirect_policy(mut self, policy: reqwest::redirect::Policy) -> Self {
+        self.http.redirect_policy = Some(policy);
+        self
+    }
+
+    /// Build the client
+    pub fn build(self) -> AuthClient {
+        AuthClient {
+            auth_endpoint: self.auth_endpoint,
+            auth_headers: self.auth_headers,
+            user_auth_endpoint: self.user_auth_endpoint,
+            user_auth_headers: self.user_auth_headers,
+            signature_mode: AuthSignatureMode::None,
+            #[cfg(not(target_arch = "wasm32"))]
+            http: self.http.build(),
+            #[cfg(target_arch = "wasm32")]
+            fetch_options: None,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl HttpSettings {
+    fn build(self) -> reqwest::Client {
+        if let Some(client) = self.client {
+            return client;
+        }
+
+        let mut builder = reqwest::Client::builder();
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(user_agent) = self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        if let Some(policy) = self.redirect_policy {
+            builder = builder.redirect(policy);
+        }
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        for (key, value) in &self.headers {
+            match (
+                reqwest::header::HeaderName::from_bytes(key.as_bytes()),
+                reqwest::header::HeaderValue::from_str(value),
+            ) {
+                (Ok(name), Ok(value)) => {
+                    headers.append(name, value);
+                }
+                _ => warn!("Ignoring invalid auth header: {}", key),
+            }
+        }
+        builder = builder.default_headers(headers);
+
+        builder.build().unwrap_or_else(|e| {
+            warn!("Failed to build auth HTTP client, using defaults: {}", e);
+            reqwest::Client::new()
+        })
+    }
+}
+
+/// Header carrying the request time for signed auth requests
+const TIMESTAMP_HEADER: &str = "X-Sockudo-Timestamp";
+/// Header carrying the HMAC signature for signed auth requests
+const SIGNATURE_HEADER: &str = "X-Sockudo-Signature";
+const FORM_CONTENT_TYPE: &str = "application/x-www-form-urlencoded";
+
+/// Encode `params` as an `application/x-www-form-urlencoded` body
+fn form_body(params: &[(&str, &str)]) -> String {
+    params
+        .iter()
+        .map(|(key, value)| {
+            format!(
+                "{}={}",
+                urlencoding::encode(key),
+                urlencoding::encode(value)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Headers signing an auth request with form `body` under `mode`; empty
+/// when `mode` is `None`
+pub(crate) fn signature_headers(
+    mode: &AuthSignatureMode,
+    body: &str,
+) -> Vec<(&'static str, String)> {
+    match mode {
+        AuthSignatureMode::None => Vec::new(),
+        AuthSignatureMode::Hmac { app_id, app_secret } => {
+            let timestamp = unix_millis();
+            let signature = sign_auth_request(app_secret.expose(), timestamp, body);
+            vec![
+                (TIMESTAMP_HEADER, timestamp.to_string()),
+                (SIGNATURE_HEADER, format!("{}:sha256={}", app_id, signature)),
+            ]
+        }
+    }
+}
+
+/// Hex HMAC-SHA256 of `"POST\n/auth\n{timestamp}\n{body}"` keyed with `secret`
+fn sign_auth_request(secret: &str, timestamp: u64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(format!("POST\n/auth\n{}\n{}", timestamp, body).as_bytes());
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Current Unix time in milliseconds
+#[cfg(not(target_arch = "wasm32"))]
+fn unix_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Current Unix time in milliseconds; `SystemTime::now` is unavailable in the browser
+#[cfg(target_arch = "wasm32")]
+fn unix_millis() -> u64 {
+    js_sys::Date::now() as u64
+}
+
+/// Build an `AuthorizationFailed` error from an unsuccessful `action`
+/// response, e.g. "User authentication"
+#[cfg(not(target_arch = "wasm32"))]
+fn failed_response(action: &str, response: &reqwest::Response) -> SockudoError {
+    let headers = response
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| {
+            Some((name.as_str().to_string(), value.to_str().ok()?.to_string()))
+        })
+        .collect();
+    SockudoError::authorization_failed(
+        format!("{} failed with status: {}", action, response.status()),
+        response.status().as_u16(),
+        headers,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -286,4 +659,194 @@ mod tests {
         assert!(client.auth_endpoint.is_some());
         assert!(client.user_auth_endpoint.is_some());
     }
+
+    #[test]
+    fn test_for_channel_uses_prefix_endpoint() {
+        use crate::options::ChannelConfig;
+
+        let options = SockudoOptions::new("key")
+            .auth_endpoint("https://example.com/auth")
+            .channel_prefix_config(
+                "private-",
+                ChannelConfig {
+                    auth_endpoint: Some("https://private.example.com/auth".to_string()),
+                    ..Default::default()
+                },
+            )
+            .channel_prefix_config(
+                "presence-",
+                ChannelConfig {
+                    auth_endpoint: Some("https://presence.example.com/auth".to_string()),
+                    ..Default::default()
+                },
+            );
+
+        let endpoint = |channel: &str| AuthClient::for_channel(channel, &options).auth_endpoint;
+        assert_eq!(
+            endpoint("private-room-1").as_deref(),
+            Some("https://private.example.com/auth")
+        );
+        assert_eq!(
+            endpoint("presence-lobby").as_deref(),
+            Some("https://presence.example.com/auth")
+        );
+        assert_eq!(
+            endpoint("private-encrypted-notes").as_deref(),
+            Some("https://private.example.com/auth")
+        );
+        assert_eq!(
+            endpoint("news").as_deref(),
+            Some("https://example.com/auth")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_custom_http_client_timeout() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"{"auth":"key:sig"}"#)
+                    .set_delay(Duration::from_millis(200)),
+            )
+            .mount(&server)
+            .await;
+
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_millis(1))
+            .build()
+            .unwrap();
+        let client = AuthClient::builder()
+            .auth_endpoint(format!("{}/pusher/auth", server.uri()))
+            .build()
+            .with_custom_http_client(http);
+
+        let err = client
+            .authorize_channel("private-test", "123.456")
+            .await
+            .unwrap_err();
+        match err {
+            SockudoError::AuthorizationError { message } => {
+                assert!(message.contains("timed out"), "{}", message)
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hmac_signed_auth_requests() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+        /// Accepts requests signed with `secret` within `max_age` of now
+        struct VerifySignature {
+            secret: &'static str,
+            max_age: Duration,
+        }
+
+        impl Respond for VerifySignature {
+            fn respond(&self, request: &Request) -> ResponseTemplate {
+                let header = |name: &str| {
+                    request
+                        .headers
+                        .iter()
+                        .find(|(key, _)| key.as_str().eq_ignore_ascii_case(name))
+                        .map(|(_, values)| values.last().as_str().to_string())
+                };
+                let (Some(timestamp), Some(signature)) =
+                    (header(TIMESTAMP_HEADER), header(SIGNATURE_HEADER))
+                else {
+                    return ResponseTemplate::new(401);
+                };
+                let timestamp: u64 = timestamp.parse().unwrap();
+                if unix_millis().saturating_sub(timestamp) > self.max_age.as_millis() as u64 {
+                    return ResponseTemplate::new(401);
+                }
+                let body = String::from_utf8(request.body.clone()).unwrap();
+                let expected = format!(
+                    "app-1:sha256={}",
+                    sign_auth_request(self.secret, timestamp, &body)
+                );
+                if signature != expected {
+                    return ResponseTemplate::new(403);
+                }
+                ResponseTemplate::new(200).set_body_string(r#"{"auth":"key:sig"}"#)
+            }
+        }
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(VerifySignature {
+                secret: "s3cret",
+                max_age: Duration::from_millis(500),
+            })
+            .mount(&server)
+            .await;
+        let endpoint = format!("{}/pusher/auth", server.uri());
+
+        let client = AuthClient::builder()
+            .auth_endpoint(endpoint.clone())
+            .build()
+            .with_hmac_auth("app-1", "s3cret");
+        let auth = client
+            .authorize_channel("private-room one", "123.456")
+            .await
+            .unwrap();
+        assert_eq!(auth.auth, "key:sig");
+
+        let wrong_secret = AuthClient::builder()
+            .auth_endpoint(endpoint.clone())
+            .build()
+            .with_hmac_auth("app-1", "guess");
+        let unsigned = AuthClient::builder()
+            .auth_endpoint(endpoint.clone())
+            .build();
+        for (client, status) in [(wrong_secret, 403), (unsigned, 401)] {
+            match client.authorize_channel("private-room", "123.456").await {
+                Err(SockudoError::AuthorizationFailed {
+                    message,
+                    status: actual,
+                    ..
+                }) => {
+                    assert_eq!(actual, status);
+                    assert!(message.starts_with("Authorization failed with status: "));
+                }
+                other => panic!("expected AuthorizationFailed, got {:?}", other),
+            }
+        }
+
+        // Replaying the signed request once its timestamp is stale fails
+        let signed = server.received_requests().await.unwrap().remove(0);
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        let mut replay = reqwest::Client::new()
+            .post(&endpoint)
+            .body(signed.body.clone());
+        for (name, value) in signed.headers.iter() {
+            replay = replay.header(name.as_str(), value.last().as_str());
+        }
+        assert_eq!(replay.send().await.unwrap().status().as_u16(), 401);
+    }
+
+    #[test]
+    fn test_signature_headers_follow_config() {
+        assert!(signature_headers(&AuthSignatureMode::None, "socket_id=1.1").is_empty());
+
+        // The WASM subscribe path signs with the mode carried by `Config`
+        let options = SockudoOptions::new("key").hmac_auth("app-1", "s3cret");
+        let mode = crate::options::Config::from(options).auth_signature_mode;
+        let headers: HashMap<_, _> = signature_headers(&mode, "socket_id=1.1")
+            .into_iter()
+            .collect();
+        let timestamp: u64 = headers[TIMESTAMP_HEADER].parse().unwrap();
+        assert_eq!(
+            headers[SIGNATURE_HEADER],
+            format!(
+                "app-1:sha256={}",
+                sign_auth_request("s3cret", timestamp, "socket_id=1.1")
+            )
+        );
+    }
 }
@@ -2,12 +2,22 @@
 
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::future::Future;
+#[cfg(any(test, feature = "offline-mode"))]
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 use tracing::{debug, warn};
 
+use super::history::EventHistory;
+use super::state::ChannelStateCell;
+use crate::connection::ReconnectStrategy;
 use crate::error::{Result, SockudoError};
-use crate::events::EventDispatcher;
-use crate::protocol::{FilterOp, PusherEvent};
+use crate::events::{EventDispatcher, EventNameValidator, EventWaiter, RegistryMemoryStats};
+use crate::options::{SensitiveString, TriggerBatchMode};
+use crate::protocol::{CompiledFilter, FilterOp, Protocol, PusherEvent};
 
 /// Channel type enumeration
 #[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
@@ -52,7 +62,7 @@ impl ChannelType {
 }
 
 /// Channel subscription state
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ChannelState {
     /// Initial state
     Unsubscribed,
@@ -64,6 +74,27 @@ pub enum ChannelState {
     Failed,
 }
 
+/// Point-in-time copy of a channel's state, for tests and debugging
+#[derive(Debug, Clone, Serialize)]
+pub struct ChannelSnapshot {
+    /// Channel name
+    pub name: String,
+    /// Channel type
+    pub channel_type: ChannelType,
+    /// Subscription state
+    pub state: ChannelState,
+    /// Subscriber count last reported by the server
+    pub subscription_count: Option<u32>,
+    /// Tags filter sent with the subscription
+    pub tags_filter: Option<FilterOp>,
+    /// Callbacks bound to the channel
+    pub callback_stats: RegistryMemoryStats,
+    /// Data sent with `subscription_succeeded`
+    pub subscription_metadata: Option<serde_json::Value>,
+    /// When the current subscription succeeded
+    pub subscribed_at: Option<SystemTime>,
+}
+
 /// Callback for sending events
 #[cfg(feature = "wasm")]
 pub type SendEventFn = Arc<dyn Fn(&str, &serde_json::Value, Option<&str>) -> bool + Send + Sync>;
@@ -72,9 +103,44 @@ pub type SendEventFn = Arc<dyn Fn(&str, &serde_json::Value, Option<&str>) -> boo
 #[cfg(not(feature = "wasm"))]
 pub type SendEventFn = Arc<dyn Fn(&str, &str, Option<&str>) -> bool + Send + Sync>;
 
+/// Send an event built by `Protocol` through a `SendEventFn`
+pub(crate) fn send_protocol_event(send: &SendEventFn, event: &PusherEvent) -> bool {
+    match event.data {
+        Some(ref data) => send(&event.event, data, event.channel.as_deref()),
+        None => false,
+    }
+}
+
+/// Callback for sending a raw, already encoded frame
+pub type SendRawFn = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// Callback for sending events as one array frame, returning whether each
+/// event was sent
+pub type SendBatchFn = Arc<dyn Fn(Vec<PusherEvent>) -> Vec<bool> + Send + Sync>;
+
 /// Callback for channel authorization
 pub type AuthorizeFn = Arc<dyn Fn(&str, &str) -> Result<ChannelAuthData> + Send + Sync>;
 
+/// Confirmation timeout for resubscriptions retried with a channel's own
+/// reconnect strategy, when no timeout is set
+pub const DEFAULT_RESUBSCRIPTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How a channel is resubscribed after the connection is re-established
+#[derive(Clone, Default)]
+pub(crate) struct ResubscribePolicy {
+    /// Backoff between failed resubscriptions
+    pub(crate) strategy: Option<Arc<dyn ReconnectStrategy>>,
+    /// How long to wait for the server to confirm a resubscription
+    pub(crate) timeout: Option<Duration>,
+}
+
+impl ResubscribePolicy {
+    /// Whether the channel overrides the default resubscription handling
+    pub(crate) fn is_custom(&self) -> bool {
+        self.strategy.is_some() || self.timeout.is_some()
+    }
+}
+
 /// Channel authorization data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChannelAuthData {
@@ -88,16 +154,18 @@ pub struct ChannelAuthData {
 /// Base channel implementation
 #[cfg_attr(feature = "uniffi", derive(uniffi::Object))]
 pub struct Channel {
-    /// Channel name
-    name: String,
+    /// Channel name, following any `migrate_channel`
+    name: RwLock<Arc<str>>,
     /// Channel type
     channel_type: ChannelType,
     /// Current state (shared)
-    state: Arc<RwLock<ChannelState>>,
+    state: Arc<ChannelStateCell>,
     /// Event dispatcher for this channel
     dispatcher: EventDispatcher,
     /// Optional tags filter for subscription
     tags_filter: RwLock<Option<FilterOp>>,
+    /// Client-side filter applied to incoming user events
+    compiled_filter: RwLock<Option<Arc<CompiledFilter>>>,
     /// Callback for sending events
     send_event: Option<SendEventFn>,
     /// Callback for authorization
@@ -106,6 +174,29 @@ pub struct Channel {
     socket_id: RwLock<Option<String>>,
     /// Subscription count (if available)
     subscription_count: RwLock<Option<u32>>,
+    /// Callback for sending raw frames (used by signed client events)
+    send_raw: Option<SendRawFn>,
+    /// Callback for sending batched triggers
+    send_batch: Option<SendBatchFn>,
+    /// How `trigger_batch` submits events
+    trigger_batch_mode: TriggerBatchMode,
+    /// Maximum number of events per batch frame
+    max_trigger_batch_size: usize,
+    /// Shared secret for signing client events
+    client_event_secret: Option<SensitiveString>,
+    /// Data from the last `subscription_succeeded` event (shared)
+    subscription_metadata: Arc<RwLock<Option<serde_json::Value>>>,
+    /// When the current subscription succeeded (shared)
+    subscribed_at: Arc<RwLock<Option<SystemTime>>>,
+    /// Per-channel resubscription settings (shared)
+    resubscribe_policy: Arc<RwLock<ResubscribePolicy>>,
+    /// Metadata sent with the subscribe request (shared)
+    request_metadata: Arc<RwLock<HashMap<String, String>>>,
+    /// Most recent events received, if enabled (shared)
+    event_history: Arc<RwLock<EventHistory>>,
+    /// Whether the client is in offline mode (shared with `Channels`)
+    #[cfg(any(test, feature = "offline-mode"))]
+    offline: Arc<AtomicBool>,
 }
 
 impl Channel {
@@ -115,17 +206,30 @@ impl Channel {
         let channel_type = ChannelType::from_name(&name);
 
         Self {
-            name: name.clone(),
+            name: RwLock::new(name.as_str().into()),
             channel_type,
-            state: Arc::new(RwLock::new(ChannelState::Unsubscribed)),
+            state: Arc::new(ChannelStateCell::new(ChannelState::Unsubscribed)),
             dispatcher: EventDispatcher::with_fail_through(move |event, _| {
                 debug!("No callbacks on {} for {}", name, event);
             }),
             tags_filter: RwLock::new(None),
+            compiled_filter: RwLock::new(None),
             send_event: None,
             authorize_fn: None,
             socket_id: RwLock::new(None),
             subscription_count: RwLock::new(None),
+            send_raw: None,
+            send_batch: None,
+            trigger_batch_mode: TriggerBatchMode::Single,
+            max_trigger_batch_size: 10,
+            client_event_secret: None,
+            subscription_metadata: Arc::new(RwLock::new(None)),
+            subscribed_at: Arc::new(RwLock::new(None)),
+            resubscribe_policy: Arc::new(RwLock::new(ResubscribePolicy::default())),
+            request_metadata: Arc::new(RwLock::new(HashMap::new())),
+            event_history: Arc::new(RwLock::new(EventHistory::default())),
+            #[cfg(any(test, feature = "offline-mode"))]
+            offline: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -134,20 +238,47 @@ impl Channel {
         name: impl Into<String>,
         dispatcher: EventDispatcher,
         state: Arc<RwLock<ChannelState>>,
+    ) -> Self {
+        Self::with_state_cell(
+            name,
+            dispatcher,
+            Arc::new(ChannelStateCell::from_shared(state)),
+        )
+    }
+
+    /// Create a new channel sharing a dispatcher and state cell, so state
+    /// watchers see changes made through either view
+    pub(crate) fn with_state_cell(
+        name: impl Into<String>,
+        dispatcher: EventDispatcher,
+        state: Arc<ChannelStateCell>,
     ) -> Self {
         let name = name.into();
         let channel_type = ChannelType::from_name(&name);
 
         Self {
-            name,
+            name: RwLock::new(name.into()),
             channel_type,
             state,
             dispatcher,
             tags_filter: RwLock::new(None),
+            compiled_filter: RwLock::new(None),
             send_event: None,
             authorize_fn: None,
             socket_id: RwLock::new(None),
             subscription_count: RwLock::new(None),
+            send_raw: None,
+            send_batch: None,
+            trigger_batch_mode: TriggerBatchMode::Single,
+            max_trigger_batch_size: 10,
+            client_event_secret: None,
+            subscription_metadata: Arc::new(RwLock::new(None)),
+            subscribed_at: Arc::new(RwLock::new(None)),
+            resubscribe_policy: Arc::new(RwLock::new(ResubscribePolicy::default())),
+            request_metadata: Arc::new(RwLock::new(HashMap::new())),
+            event_history: Arc::new(RwLock::new(EventHistory::default())),
+            #[cfg(any(test, feature = "offline-mode"))]
+            offline: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -156,6 +287,28 @@ impl Channel {
         self.send_event = Some(callback);
     }
 
+    /// Set the raw frame callback used for signed client events, and by
+    /// `trigger_batch` in array mode when no batch callback is set
+    pub fn set_raw_send_callback(&mut self, callback: SendRawFn) {
+        self.send_raw = Some(callback);
+    }
+
+    /// Set the callback used by `trigger_batch` in array mode
+    pub fn set_batch_send_callback(&mut self, callback: SendBatchFn) {
+        self.send_batch = Some(callback);
+    }
+
+    /// Configure how `trigger_batch` sends events
+    pub fn set_trigger_batch_options(&mut self, mode: TriggerBatchMode, max_batch_size: usize) {
+        self.trigger_batch_mode = mode;
+        self.max_trigger_batch_size = max_batch_size.max(1);
+    }
+
+    /// Set the secret used to sign client events (`None` disables signing)
+    pub fn set_client_event_secret(&mut self, secret: Option<SensitiveString>) {
+        self.client_event_secret = secret;
+    }
+
     /// Set the authorization callback
     pub fn set_authorize_callback(&mut self, callback: AuthorizeFn) {
         self.authorize_fn = Some(callback);
@@ -166,9 +319,56 @@ impl Channel {
         *self.tags_filter.write() = filter;
     }
 
+    /// Drop incoming user events whose payload does not match `filter`
+    pub fn set_compiled_filter(&self, filter: CompiledFilter) {
+        *self.compiled_filter.write() = Some(Arc::new(filter));
+    }
+
+    /// Remove the client-side filter set with `set_compiled_filter`
+    pub fn clear_compiled_filter(&self) {
+        *self.compiled_filter.write() = None;
+    }
+
+    /// Number of events emitted on this channel, for one name or in total
+    #[cfg(feature = "event-stats")]
+    pub fn event_count(&self, event_name: Option<&str>) -> u64 {
+        match event_name {
+            Some(name) => self.dispatcher.event_count(name),
+            None => self.dispatcher.total_event_count(),
+        }
+    }
+
     /// Get channel name
-    pub fn name(&self) -> &str {
-        &self.name
+    ///
+    /// After [`SockudoClient::migrate_channel`](crate::SockudoClient::migrate_channel)
+    /// this is the name the channel was migrated to.
+    pub fn name(&self) -> String {
+        self.current_name()
+    }
+
+    /// Get the channel name as an owned `String`
+    pub fn current_name(&self) -> String {
+        self.name.read().to_string()
+    }
+
+    /// Rename the channel, unsubscribing the old name from the server.
+    ///
+    /// Callbacks, filter and metadata are kept; subscribe again to join
+    /// under the new name.
+    pub(crate) fn rename(&self, new_name: impl Into<String>) {
+        let new_name: String = new_name.into();
+        let old_name = std::mem::replace(&mut *self.name.write(), new_name.into());
+        if !self.is_subscribed() && !self.is_subscription_pending() {
+            return;
+        }
+
+        *self.state.write() = ChannelState::Unsubscribed;
+        *self.subscription_metadata.write() = None;
+        *self.subscribed_at.write() = None;
+
+        if let Some(ref send) = self.send_event {
+            send_protocol_event(send, &Protocol::create_unsubscribe_event(&old_name));
+        }
     }
 
     /// Get channel type
@@ -191,20 +391,214 @@ impl Channel {
         *self.state.read()
     }
 
+    /// Receiver of every subscription state change, starting from the
+    /// current state
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn watch_state(&self) -> tokio::sync::watch::Receiver<ChannelState> {
+        self.state.subscribe()
+    }
+
+    /// Dispatcher shared by every view of the channel
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn dispatcher(&self) -> &EventDispatcher {
+        &self.dispatcher
+    }
+
     /// Get subscription count
     pub fn subscription_count(&self) -> Option<u32> {
         *self.subscription_count.read()
     }
 
+    /// Data sent with `subscription_succeeded`, if subscribed
+    pub fn subscription_metadata(&self) -> Option<serde_json::Value> {
+        self.subscription_metadata.read().clone()
+    }
+
+    /// Read the subscription metadata in place, without cloning it
+    pub fn with_subscription_metadata<F, R>(&self, f: F) -> Option<R>
+    where
+        F: Fn(&serde_json::Value) -> R,
+    {
+        self.subscription_metadata.read().as_ref().map(f)
+    }
+
+    /// Share subscription metadata storage with a wrapping channel
+    pub(crate) fn share_subscription_metadata(
+        &mut self,
+        metadata: Arc<RwLock<Option<serde_json::Value>>>,
+    ) {
+        self.subscription_metadata = metadata;
+    }
+
+    /// When the current subscription succeeded, if subscribed
+    pub fn subscribed_at(&self) -> Option<SystemTime> {
+        *self.subscribed_at.read()
+    }
+
+    /// Share subscription time storage with a wrapping channel
+    pub(crate) fn share_subscribed_at(&mut self, subscribed_at: Arc<RwLock<Option<SystemTime>>>) {
+        self.subscribed_at = subscribed_at;
+    }
+
+    /// Keep the last `size` events of each event name for
+    /// `get_event_history`. 0 turns history off and drops what was kept.
+    pub fn set_history_size(&self, size: usize) {
+        self.event_history.write().set_capacity(size);
+    }
+
+    /// Number of events kept per event name (0 when history is off)
+    pub fn history_size(&self) -> usize {
+        self.event_history.read().capacity()
+    }
+
+    /// Up to `limit` of the most recent events received, newest first.
+    ///
+    /// Only events of `event_name` are returned when it is given.
+    pub fn get_event_history(&self, event_name: Option<&str>, limit: usize) -> Vec<PusherEvent> {
+        self.event_history.read().newest(event_name, limit)
+    }
+
+    /// Share event history storage with a wrapping channel
+    pub(crate) fn share_event_history(&mut self, history: Arc<RwLock<EventHistory>>) {
+        self.event_history = history;
+    }
+
+    /// Capture the channel's state at this moment.
+    ///
+    /// All fields are read under their locks at once, so the snapshot is
+    /// consistent even while events are being handled.
+    pub fn snapshot(&self) -> ChannelSnapshot {
+        // Always taken in this order, so concurrent snapshots cannot deadlock
+        let state = self.state.read();
+        let subscription_count = self.subscription_count.read();
+        let tags_filter = self.tags_filter.read();
+        let subscription_metadata = self.subscription_metadata.read();
+        let subscribed_at = self.subscribed_at.read();
+
+        ChannelSnapshot {
+            name: self.current_name(),
+            channel_type: self.channel_type,
+            state: *state,
+            subscription_count: *subscription_count,
+            tags_filter: tags_filter.clone(),
+            callback_stats: self.dispatcher.memory_usage(),
+            subscription_metadata: subscription_metadata.clone(),
+            subscribed_at: *subscribed_at,
+        }
+    }
+
+    /// Custom metadata sent in the `pusher:subscribe` request
+    pub fn request_metadata(&self) -> HashMap<String, String> {
+        self.request_metadata.read().clone()
+    }
+
+    /// Set the custom metadata sent in the `pusher:subscribe` request
+    pub fn set_request_metadata(&self, metadata: HashMap<String, String>) {
+        *self.request_metadata.write() = metadata;
+    }
+
+    /// Share the offline mode flag of the owning `Channels`
+    #[cfg(any(test, feature = "offline-mode"))]
+    pub(crate) fn share_offline_flag(&mut self, offline: Arc<AtomicBool>) {
+        self.offline = offline;
+    }
+
+    /// Share subscribe request metadata with a wrapping channel
+    pub(crate) fn share_request_metadata(
+        &mut self,
+        metadata: Arc<RwLock<HashMap<String, String>>>,
+    ) {
+        self.request_metadata = metadata;
+    }
+
+    /// Use the resubscription settings of another view of the same channel
+    pub(crate) fn share_resubscribe_policy(&mut self, policy: Arc<RwLock<ResubscribePolicy>>) {
+        self.resubscribe_policy = policy;
+    }
+
+    /// Retry failed resubscriptions after a reconnect with `strategy`
+    /// instead of giving up after the first attempt
+    pub fn set_reconnect_strategy(&self, strategy: Box<dyn ReconnectStrategy>) {
+        self.resubscribe_policy.write().strategy = Some(Arc::from(strategy));
+    }
+
+    /// Set how long a resubscription may wait for the server's confirmation
+    /// before it counts as failed (default: 10 seconds)
+    pub fn set_resubscription_timeout(&self, timeout: Duration) {
+        self.resubscribe_policy.write().timeout = Some(timeout);
+    }
+
+    /// Confirmation timeout for resubscriptions, if one was set
+    pub fn resubscription_timeout(&self) -> Option<Duration> {
+        self.resubscribe_policy.read().timeout
+    }
+
+    /// Current resubscription settings
+    pub(crate) fn resubscribe_policy(&self) -> ResubscribePolicy {
+        self.resubscribe_policy.read().clone()
+    }
+
+    /// Socket ID used by the last subscription attempt
+    pub(crate) fn subscribed_socket_id(&self) -> Option<String> {
+        self.socket_id.read().clone()
+    }
+
+    /// Move an `Unsubscribed` or `Failed` channel to `Subscribing`,
+    /// returning whether this call made the transition
+    pub(crate) fn begin_subscribing(&self) -> bool {
+        let mut state = self.state.write();
+        match *state {
+            ChannelState::Unsubscribed | ChannelState::Failed => {
+                *state = ChannelState::Subscribing;
+                true
+            }
+            ChannelState::Subscribing | ChannelState::Subscribed => false,
+        }
+    }
+
+    /// Mark a subscription the client has given up on
+    pub(crate) fn mark_failed(&self) {
+        *self.state.write() = ChannelState::Failed;
+    }
+
     /// Bind a callback to an event
     pub fn bind(
         &self,
         event_name: impl Into<String>,
         callback: impl Fn(&PusherEvent) + Send + Sync + 'static,
-    ) -> u64 {
+    ) -> Result<u64> {
         self.dispatcher.bind(event_name, callback)
     }
 
+    /// Bind a callback that is removed the first time it fires
+    pub fn bind_once(
+        &self,
+        event_name: impl Into<String>,
+        callback: impl Fn(&PusherEvent) + Send + Sync + 'static,
+    ) -> Result<u64> {
+        self.dispatcher.bind_once(event_name, callback)
+    }
+
+    /// Replace the validator applied to event names in `bind` and `trigger`
+    pub fn set_event_name_validator(&self, validator: EventNameValidator) {
+        self.dispatcher.set_name_validator(validator);
+    }
+
+    /// Limit how long event dispatch waits for each callback (`None` for no limit)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_callback_timeout(&self, timeout: Option<std::time::Duration>) {
+        self.dispatcher.set_callback_timeout(timeout);
+    }
+
+    /// Bind a callback to every event whose name matches a regular expression
+    pub fn bind_regex(
+        &self,
+        pattern: &str,
+        callback: impl Fn(&PusherEvent) + Send + Sync + 'static,
+    ) -> Result<u64> {
+        self.dispatcher.bind_regex(pattern, callback)
+    }
+
     /// Unbind callbacks
     pub fn unbind(&self, event_name: Option<&str>, callback_id: Option<u64>) {
         self.dispatcher.unbind(event_name, callback_id);
@@ -215,6 +609,155 @@ impl Channel {
         self.dispatcher.unbind_all();
     }
 
+    /// Check whether any callback would fire for `event_name`
+    pub fn has_listeners(&self, event_name: &str) -> bool {
+        self.dispatcher.has_listeners(event_name)
+    }
+
+    /// Count the callbacks that would fire for `event_name`, including
+    /// global and pattern bindings. With `None`, count every bound callback.
+    pub fn listener_count(&self, event_name: Option<&str>) -> usize {
+        self.dispatcher.listener_count(event_name)
+    }
+
+    /// Count the callbacks bound to the channel, by kind
+    pub fn callback_memory_usage(&self) -> RegistryMemoryStats {
+        self.dispatcher.memory_usage()
+    }
+
+    /// Warn when more than `limit` callbacks are bound to one event name
+    /// (default 10, 0 disables the warning)
+    pub fn set_max_listeners(&self, limit: usize) {
+        self.dispatcher.set_max_listeners(limit);
+    }
+
+    /// Get the listener leak warning threshold
+    pub fn max_listeners(&self) -> usize {
+        self.dispatcher.max_listeners()
+    }
+
+    /// Wait for the subscription to succeed.
+    ///
+    /// Resolves immediately if the channel is already subscribed, and with an
+    /// error if the server reports `pusher:subscription_error`. Dropping the
+    /// returned future removes its internal bindings.
+    pub fn on_subscribed(&self) -> EventWaiter<()> {
+        let waiter = EventWaiter::new(
+            &self.dispatcher,
+            &["pusher:subscription_succeeded", "pusher:subscription_error"],
+            subscription_result,
+        );
+
+        // Checked after binding so a concurrent success cannot be missed
+        if self.is_subscribed() {
+            return EventWaiter::ready(Ok(()));
+        }
+        waiter
+    }
+
+    /// Wait for the next `event_name` event on this channel, up to `timeout`.
+    ///
+    /// Concurrent callers for the same event are served in call order, one
+    /// event each. Pass `"*"` to wait for any event.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn await_event(
+        &self,
+        event_name: &str,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<PusherEvent>> + Send + 'static {
+        self.dispatcher.await_event(event_name, timeout)
+    }
+
+    /// Wait for the first `event_name` event for which `predicate` returns
+    /// `true`, up to `timeout`.
+    ///
+    /// Unlike `await_event`, events that do not match are skipped. The
+    /// temporary binding is removed once the future completes or is dropped.
+    ///
+    /// ```no_run
+    /// # use sockudo_client::Channel;
+    /// # use std::time::Duration;
+    /// # #[derive(serde::Deserialize)]
+    /// # struct Score { goals: u32 }
+    /// # async fn example(channel: &Channel) {
+    /// let event = channel
+    ///     .bind_and_wait(
+    ///         "score",
+    ///         |e| e.parse_data::<Score>().map(|s| s.goals > 3).unwrap_or(false),
+    ///         Duration::from_secs(60),
+    ///     )
+    ///     .await;
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn bind_and_wait(
+        &self,
+        event_name: &str,
+        predicate: impl Fn(&PusherEvent) -> bool + Send + Sync + 'static,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<PusherEvent>> + Send + 'static {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        let sender = parking_lot::Mutex::new(Some(sender));
+        let binding = self.bind(event_name, move |event| {
+            if predicate(event) {
+                if let Some(sender) = sender.lock().take() {
+                    let _ = sender.send(event.clone());
+                }
+            }
+        });
+        let event_name = event_name.to_string();
+        let unbind = binding.map(|binding| UnbindOnDrop {
+            dispatcher: self.dispatcher.clone(),
+            event_name: event_name.clone(),
+            binding,
+        });
+
+        async move {
+            let _unbind = unbind?;
+            match tokio::time::timeout(timeout, receiver).await {
+                Ok(Ok(event)) => Ok(event),
+                Ok(Err(_)) => Err(SockudoError::invalid_state("Event waiter was cancelled")),
+                Err(_) => Err(SockudoError::timeout(format!(
+                    "Timed out waiting for a matching '{}' event",
+                    event_name
+                ))),
+            }
+        }
+    }
+
+    /// Collect the next `count` `event_name` events on this channel, with
+    /// `timeout` covering all of them
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn await_events(
+        &self,
+        event_name: &str,
+        count: usize,
+        timeout: Duration,
+    ) -> impl Future<Output = Vec<Result<PusherEvent>>> + Send + 'static {
+        self.dispatcher.await_events(event_name, count, timeout)
+    }
+
+    /// Bind a callback for when the channel is unsubscribed, returning the binding ID
+    pub fn on_unsubscribed(&self, callback: impl Fn() + Send + Sync + 'static) -> u64 {
+        self.dispatcher
+            .bind_unchecked("pusher:unsubscription_succeeded", move |_| callback())
+    }
+
+    /// Bind a callback for a rejected subscription, returning the binding ID.
+    ///
+    /// The callback receives the error message from `pusher:subscription_error`.
+    pub fn on_subscription_failed(&self, callback: impl Fn(String) + Send + Sync + 'static) -> u64 {
+        self.dispatcher
+            .bind_unchecked("pusher:subscription_error", move |event| {
+                let message = event
+                    .data_as_value()
+                    .map(|data| subscription_error_message(&data))
+                    .or_else(|| event.data_as_string())
+                    .unwrap_or_default();
+                callback(message);
+            })
+    }
+
     /// Authorize the subscription (public channels skip authorization)
     pub fn authorize(&self, socket_id: &str) -> Result<ChannelAuthData> {
         if !self.channel_type.requires_auth() {
@@ -227,7 +770,7 @@ impl Channel {
         }
 
         if let Some(ref auth_fn) = self.authorize_fn {
-            auth_fn(&self.name, socket_id)
+            auth_fn(&self.current_name(), socket_id)
         } else {
             Err(SockudoError::authorization(
                 "No authorization callback configured",
@@ -237,6 +780,12 @@ impl Channel {
 
     /// Subscribe to the channel
     pub fn subscribe(&self, socket_id: &str) -> Result<()> {
+        self.subscribe_with(socket_id, None)
+    }
+
+    /// Subscribe, sending the request through `send` instead of the
+    /// channel's own callback when given
+    pub(crate) fn subscribe_with(&self, socket_id: &str, send: Option<&SendEventFn>) -> Result<()> {
         if self.is_subscribed() {
             return Ok(());
         }
@@ -244,33 +793,39 @@ impl Channel {
         *self.state.write() = ChannelState::Subscribing;
         *self.socket_id.write() = Some(socket_id.to_string());
 
+        // Offline there is no server to authorize against or to answer
+        #[cfg(any(test, feature = "offline-mode"))]
+        if self.offline.load(Ordering::SeqCst) {
+            // A presence channel has no members offline
+            let data = match self.channel_type {
+                ChannelType::Presence => {
+                    serde_json::json!({ "members": [], "count": 0, "myID": null })
+                }
+                _ => serde_json::json!({}),
+            };
+            self.handle_event(
+                &PusherEvent::new("pusher_internal:subscription_succeeded")
+                    .with_channel(self.current_name())
+                    .with_json_data(data),
+            );
+            return Ok(());
+        }
+
         // Authorize
         let auth_data = self.authorize(socket_id)?;
 
         // Build subscription data
-        let mut sub_data = serde_json::json!({
-            "channel": self.name,
-        });
-
-        if !auth_data.auth.is_empty() {
-            sub_data["auth"] = serde_json::Value::String(auth_data.auth);
-        }
-
-        if let Some(ref cd) = auth_data.channel_data {
-            sub_data["channel_data"] = serde_json::Value::String(cd.clone());
-        }
-
-        // Add tags filter if present
-        if let Some(ref filter) = *self.tags_filter.read() {
-            sub_data["tags_filter"] = filter.to_json();
-        }
+        let event = Protocol::create_subscribe_event_with_metadata(
+            &self.current_name(),
+            Some(auth_data.auth.as_str()).filter(|auth| !auth.is_empty()),
+            auth_data.channel_data.as_deref(),
+            self.tags_filter.read().as_ref(),
+            &self.request_metadata.read(),
+        )?;
 
         // Send subscribe event
-        if let Some(ref send) = self.send_event {
-            #[cfg(feature = "wasm")]
-            send("pusher:subscribe", &sub_data, None);
-            #[cfg(not(feature = "wasm"))]
-            send("pusher:subscribe", &sub_data.to_string(), None);
+        if let Some(send) = send.or(self.send_event.as_ref()) {
+            send_protocol_event(send, &event);
         }
 
         Ok(())
@@ -282,6 +837,7 @@ impl Channel {
         &self,
         socket_id: &str,
         auth_endpoint: Option<&str>,
+        signature_mode: &crate::options::AuthSignatureMode,
     ) -> Result<()> {
         if self.is_subscribed() {
             return Ok(());
@@ -294,8 +850,11 @@ impl Channel {
         let auth_data = if self.channel_type.requires_auth() {
             if let Some(endpoint) = auth_endpoint {
                 use crate::auth::AuthClient;
-                let auth_client = AuthClient::new(Some(endpoint.to_string()), None, None, None);
-                auth_client.authorize_channel(&self.name, socket_id).await?
+                let auth_client = AuthClient::new(Some(endpoint.to_string()), None, None, None)
+                    .with_signature_mode(signature_mode.clone());
+                auth_client
+                    .authorize_channel(&self.current_name(), socket_id)
+                    .await?
             } else {
                 return Err(SockudoError::authorization(
                     "No auth_endpoint provided for private/presence channel",
@@ -310,26 +869,17 @@ impl Channel {
         };
 
         // Build subscription data
-        let mut sub_data = serde_json::json!({
-            "channel": self.name,
-        });
-
-        if !auth_data.auth.is_empty() {
-            sub_data["auth"] = serde_json::Value::String(auth_data.auth);
-        }
-
-        if let Some(ref cd) = auth_data.channel_data {
-            sub_data["channel_data"] = serde_json::Value::String(cd.clone());
-        }
-
-        // Add tags filter if present
-        if let Some(ref filter) = *self.tags_filter.read() {
-            sub_data["tags_filter"] = filter.to_json();
-        }
+        let event = Protocol::create_subscribe_event_with_metadata(
+            &self.current_name(),
+            Some(auth_data.auth.as_str()).filter(|auth| !auth.is_empty()),
+            auth_data.channel_data.as_deref(),
+            self.tags_filter.read().as_ref(),
+            &self.request_metadata.read(),
+        )?;
 
         // Send subscribe event
         if let Some(ref send) = self.send_event {
-            send("pusher:subscribe", &sub_data, None);
+            send_protocol_event(send, &event);
         }
 
         Ok(())
@@ -342,22 +892,26 @@ impl Channel {
         }
 
         *self.state.write() = ChannelState::Unsubscribed;
-
-        let data = serde_json::json!({
-            "channel": self.name,
-        });
+        *self.subscription_metadata.write() = None;
+        *self.subscribed_at.write() = None;
 
         if let Some(ref send) = self.send_event {
-            #[cfg(feature = "wasm")]
-            send("pusher:unsubscribe", &data, None);
-            #[cfg(not(feature = "wasm"))]
-            send("pusher:unsubscribe", &data.to_string(), None);
+            send_protocol_event(
+                send,
+                &Protocol::create_unsubscribe_event(&self.current_name()),
+            );
         }
+
+        self.dispatcher.emit(
+            &PusherEvent::new("pusher:unsubscription_succeeded").with_channel(self.current_name()),
+        );
     }
 
     /// Handle disconnection
     pub fn disconnect(&self) {
         *self.state.write() = ChannelState::Unsubscribed;
+        *self.subscription_metadata.write() = None;
+        *self.subscribed_at.write() = None;
     }
 
     /// Trigger a client event (WASM version)
@@ -375,13 +929,20 @@ impl Channel {
                 event_name
             )));
         }
+        self.dispatcher.validate_event_name(event_name)?;
 
         if !self.is_subscribed() {
             warn!("Client event triggered before subscription succeeded");
         }
 
+        if let Some(ref secret) = self.client_event_secret {
+            let mut event = PusherEvent::new(event_name).with_channel(self.current_name());
+            event.data = Some(data);
+            return self.send_signed(&event, secret);
+        }
+
         if let Some(ref send) = self.send_event {
-            Ok(send(event_name, &data, Some(&self.name)))
+            Ok(send(event_name, &data, Some(&self.current_name())))
         } else {
             Err(SockudoError::invalid_state("No send callback configured"))
         }
@@ -408,18 +969,109 @@ impl Channel {
                 event_name
             )));
         }
+        self.dispatcher.validate_event_name(event_name)?;
 
         if !self.is_subscribed() {
             warn!("Client event triggered before subscription succeeded");
         }
 
+        if let Some(ref secret) = self.client_event_secret {
+            let mut event = PusherEvent::new(event_name).with_channel(self.current_name());
+            event.data = Some(data);
+            return self.send_signed(&event, secret);
+        }
+
         if let Some(ref send) = self.send_event {
-            Ok(send(event_name, &data, Some(&self.name)))
+            Ok(send(event_name, &data, Some(&self.current_name())))
         } else {
             Err(SockudoError::invalid_state("No send callback configured"))
         }
     }
 
+    /// Trigger several client events, returning one result per event.
+    ///
+    /// In [`TriggerBatchMode::Array`] events are sent as JSON array frames of
+    /// at most `max_trigger_batch_size` events; every event in a frame shares
+    /// that frame's result, except events dropped by a middleware, which
+    /// are left out of the frame and report `false`. In [`TriggerBatchMode::Single`] each event is sent
+    /// in its own frame, exactly like calling `trigger` in a loop.
+    pub fn trigger_batch(&self, events: Vec<(String, serde_json::Value)>) -> Vec<Result<bool>> {
+        if let Err(e) = self.validate_batch(&events) {
+            return events.iter().map(|_| Err(e.clone())).collect();
+        }
+
+        match self.trigger_batch_mode {
+            TriggerBatchMode::Single => events
+                .into_iter()
+                .map(|(event_name, data)| {
+                    #[cfg(feature = "wasm")]
+                    return self.trigger(&event_name, data);
+                    #[cfg(not(feature = "wasm"))]
+                    return self.trigger_value(&event_name, data);
+                })
+                .collect(),
+            TriggerBatchMode::Array => {
+                let mut results = Vec::with_capacity(events.len());
+                for chunk in events.chunks(self.max_trigger_batch_size) {
+                    results.extend(self.send_batch_frame(chunk));
+                }
+                results
+            }
+        }
+    }
+
+    fn send_signed(&self, event: &PusherEvent, secret: &SensitiveString) -> Result<bool> {
+        let send = self
+            .send_raw
+            .as_ref()
+            .ok_or_else(|| SockudoError::invalid_state("No raw send callback configured"))?;
+
+        let mut frame = serde_json::to_value(event)?;
+        frame["signature"] = Protocol::sign_message(event, secret.expose()).into();
+        Ok(send(&frame.to_string()))
+    }
+
+    fn validate_batch(&self, events: &[(String, serde_json::Value)]) -> Result<()> {
+        if !self.channel_type.supports_client_events() {
+            return Err(SockudoError::invalid_event(
+                "Client events are only supported on private and presence channels",
+            ));
+        }
+
+        if let Some((event_name, _)) = events.iter().find(|(name, _)| !name.starts_with("client-"))
+        {
+            return Err(SockudoError::invalid_event(format!(
+                "Client events must start with 'client-', got: {}",
+                event_name
+            )));
+        }
+
+        for (event_name, _) in events {
+            self.dispatcher.validate_event_name(event_name)?;
+        }
+
+        if !self.is_subscribed() {
+            warn!("Client events triggered before subscription succeeded");
+        }
+
+        Ok(())
+    }
+
+    fn send_batch_frame(&self, events: &[(String, serde_json::Value)]) -> Vec<Result<bool>> {
+        let batch = batch_events(&self.current_name(), events);
+        if let Some(ref send) = self.send_batch {
+            return send(batch).into_iter().map(Ok).collect();
+        }
+
+        let result = match self.send_raw {
+            Some(ref send) => Protocol::encode_batch(&batch).map(|frame| send(&frame)),
+            None => Err(SockudoError::invalid_state(
+                "No raw send callback configured",
+            )),
+        };
+        events.iter().map(|_| result.clone()).collect()
+    }
+
     /// Handle an incoming event
     pub fn handle_event(&self, event: &PusherEvent) {
         let event_name = &event.event;
@@ -428,15 +1080,45 @@ impl Channel {
             self.handle_subscription_succeeded(event);
         } else if event_name == "pusher_internal:subscription_count" {
             self.handle_subscription_count(event);
-        } else if !event_name.starts_with("pusher_internal:") {
+        } else if !event_name.starts_with("pusher_internal:") && self.passes_filter(event) {
             // User event - emit to callbacks
             self.dispatcher.emit(event);
+            self.event_history.write().record(event);
         }
     }
 
+    /// Deliver an event straight to this channel's callbacks.
+    ///
+    /// Skips the subscription filter and internal event handling.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn emit_local(&self, event: PusherEvent) {
+        self.dispatcher.emit_local(event);
+    }
+
+    /// Check a user event against the compiled filter, if any
+    fn passes_filter(&self, event: &PusherEvent) -> bool {
+        // Clone the Arc so evaluation runs without holding the lock
+        let Some(filter) = self.compiled_filter.read().clone() else {
+            return true;
+        };
+
+        #[cfg(feature = "wasm")]
+        let data = event.data.clone().unwrap_or(serde_json::Value::Null);
+        #[cfg(not(feature = "wasm"))]
+        let data = event
+            .data
+            .as_deref()
+            .and_then(|d| serde_json::from_str(d).ok())
+            .unwrap_or(serde_json::Value::Null);
+
+        filter.matches(&data)
+    }
+
     /// Handle subscription succeeded
     fn handle_subscription_succeeded(&self, event: &PusherEvent) {
         *self.state.write() = ChannelState::Subscribed;
+        *self.subscription_metadata.write() = event.data_as_value();
+        *self.subscribed_at.write() = Some(SystemTime::now());
 
         // Emit as pusher:subscription_succeeded
         let mut success_event = event.clone();
@@ -467,10 +1149,81 @@ impl Channel {
     }
 }
 
+/// Build the client events of a batch for `channel`
+fn batch_events(channel: &str, events: &[(String, serde_json::Value)]) -> Vec<PusherEvent> {
+    events
+        .iter()
+        .map(|(event_name, data)| {
+            let mut event = PusherEvent::new(event_name);
+            #[cfg(feature = "wasm")]
+            {
+                event.data = Some(data.clone());
+            }
+            #[cfg(not(feature = "wasm"))]
+            {
+                event.data = Some(data.to_string());
+            }
+            event.channel = Some(channel.to_string());
+            event
+        })
+        .collect()
+}
+
+/// Map a subscription outcome event to a result
+pub(crate) fn subscription_result(event: &PusherEvent) -> Result<()> {
+    if event.event == "pusher:subscription_error" {
+        #[cfg(feature = "wasm")]
+        let detail = event.data.as_ref().map(|d| d.to_string());
+        #[cfg(not(feature = "wasm"))]
+        let detail = event.data.clone();
+
+        return Err(SockudoError::channel(format!(
+            "Subscription to {} failed: {}",
+            event.channel.as_deref().unwrap_or("channel"),
+            detail.unwrap_or_default()
+        )));
+    }
+    Ok(())
+}
+
+/// Extract the error message from `pusher:subscription_error` data
+pub(crate) fn subscription_error_message(data: &serde_json::Value) -> String {
+    match data {
+        serde_json::Value::String(s) => serde_json::from_str::<serde_json::Value>(s)
+            .ok()
+            .filter(|parsed| parsed.is_object())
+            .map(|parsed| subscription_error_message(&parsed))
+            .unwrap_or_else(|| s.clone()),
+        serde_json::Value::Object(fields) => fields
+            .get("error")
+            .or_else(|| fields.get("message"))
+            .and_then(|message| message.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| data.to_string()),
+        _ => data.to_string(),
+    }
+}
+
+/// Removes a temporary binding when dropped
+#[cfg(not(target_arch = "wasm32"))]
+struct UnbindOnDrop {
+    dispatcher: EventDispatcher,
+    event_name: String,
+    binding: u64,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for UnbindOnDrop {
+    fn drop(&mut self) {
+        self.dispatcher
+            .unbind(Some(&self.event_name), Some(self.binding));
+    }
+}
+
 impl std::fmt::Debug for Channel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Channel")
-            .field("name", &self.name)
+            .field("name", &*self.name.read())
             .field("type", &self.channel_type)
             .field("state", &*self.state.read())
             .finish()
@@ -483,7 +1236,7 @@ impl std::fmt::Debug for Channel {
 impl Channel {
     /// Get the channel name (FFI wrapper)
     pub fn get_name(&self) -> String {
-        self.name.clone()
+        self.current_name()
     }
 
     /// Get the channel type (FFI wrapper)
@@ -512,7 +1265,7 @@ impl Channel {
         &self,
         event_name: String,
         callback: Box<dyn crate::ffi_callbacks::EventCallback>,
-    ) -> u64 {
+    ) -> Result<u64> {
         self.bind(event_name, move |event| {
             callback.on_event(crate::UniffiPusherEvent {
                 event: event.event.clone(),
@@ -523,6 +1276,34 @@ impl Channel {
         })
     }
 
+    /// Bind channel callbacks for subscription changes and user events (FFI
+    /// wrapper). Returns a binding ID that can be passed to `unbind`.
+    #[uniffi::method(name = "bindChannel")]
+    pub fn ffi_bind_channel(
+        &self,
+        callback: Box<dyn crate::ffi_callbacks::ChannelCallback>,
+    ) -> u64 {
+        self.dispatcher
+            .bind_global(move |event| match event.event.as_str() {
+                "pusher:subscription_succeeded" => {
+                    callback.on_subscription_succeeded(event.data.clone())
+                }
+                "pusher:subscription_error" => {
+                    let data = event.data_as_value().unwrap_or_default();
+                    let error_type = data["type"]
+                        .as_str()
+                        .unwrap_or("SubscriptionError")
+                        .to_string();
+                    callback.on_subscription_error(error_type, subscription_error_message(&data));
+                }
+                "pusher:unsubscription_succeeded" => callback.on_unsubscribed(),
+                name if !event.is_internal() => {
+                    callback.on_event(name.to_string(), event.data.clone(), event.user_id.clone())
+                }
+                _ => {}
+            })
+    }
+
     /// Unbind event callback(s) (FFI wrapper)
     #[uniffi::method(name = "unbind")]
     pub fn ffi_unbind(&self, event_name: Option<String>, callback_id: Option<u64>) {
@@ -561,6 +1342,161 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_event_history() {
+        let channel = Channel::new("prices");
+        channel.handle_event(&PusherEvent::new("tick"));
+        // Off by default
+        assert!(channel.get_event_history(None, 10).is_empty());
+
+        channel.set_history_size(3);
+        for i in 0..5 {
+            channel.handle_event(
+                &PusherEvent::new("tick").with_json_data(serde_json::json!({ "i": i })),
+            );
+            if i == 2 {
+                channel.handle_event(&PusherEvent::new("close"));
+            }
+        }
+        channel.handle_event(&PusherEvent::new("pusher_internal:subscription_count"));
+
+        let data = |events: Vec<PusherEvent>| -> Vec<serde_json::Value> {
+            events
+                .iter()
+                .map(|e| e.data_as_value().unwrap_or(serde_json::Value::Null))
+                .collect()
+        };
+        // Capped at 3 per event name, newest first
+        assert_eq!(
+            data(channel.get_event_history(Some("tick"), 10)),
+            [
+                serde_json::json!({"i": 4}),
+                serde_json::json!({"i": 3}),
+                serde_json::json!({"i": 2})
+            ]
+        );
+        assert_eq!(channel.get_event_history(Some("tick"), 1).len(), 1);
+
+        // All event types, merged in arrival order
+        let names: Vec<_> = channel
+            .get_event_history(None, 10)
+            .into_iter()
+            .map(|e| e.event)
+            .collect();
+        assert_eq!(names, ["tick", "tick", "close", "tick"]);
+
+        channel.set_history_size(1);
+        assert_eq!(channel.get_event_history(None, 10).len(), 2);
+        channel.set_history_size(0);
+        assert!(channel.get_event_history(None, 10).is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_is_independent_of_later_changes() {
+        let channel = Channel::new("scores");
+        channel.set_tags_filter(Some(FilterOp::eq("sport", "football")));
+        channel.bind("goal", |_| {}).unwrap();
+        channel.handle_event(
+            &PusherEvent::new("pusher_internal:subscription_succeeded")
+                .with_json_data(serde_json::json!({"region": "eu"})),
+        );
+        channel.handle_event(
+            &PusherEvent::new("pusher_internal:subscription_count")
+                .with_json_data(serde_json::json!({"subscription_count": 4})),
+        );
+
+        let snapshot = channel.snapshot();
+        assert_eq!(snapshot.name, "scores");
+        assert_eq!(snapshot.channel_type, ChannelType::Public);
+        assert_eq!(snapshot.state, ChannelState::Subscribed);
+        assert_eq!(snapshot.subscription_count, Some(4));
+        assert_eq!(
+            snapshot.tags_filter,
+            Some(FilterOp::eq("sport", "football"))
+        );
+        assert_eq!(snapshot.callback_stats.total_callbacks, 1);
+        assert_eq!(
+            snapshot.subscription_metadata,
+            Some(serde_json::json!({"region": "eu"}))
+        );
+        assert!(snapshot.subscribed_at.is_some());
+
+        channel.bind("card", |_| {}).unwrap();
+        channel.set_tags_filter(None);
+        channel.disconnect();
+
+        assert_eq!(snapshot.state, ChannelState::Subscribed);
+        assert_eq!(snapshot.callback_stats.total_callbacks, 1);
+        assert!(snapshot.tags_filter.is_some());
+        assert!(snapshot.subscribed_at.is_some());
+        let current = channel.snapshot();
+        assert_eq!(current.state, ChannelState::Unsubscribed);
+        assert_eq!(current.subscribed_at, None);
+        assert_eq!(current.callback_stats.total_callbacks, 2);
+
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(json["state"], "Subscribed");
+        assert_eq!(json["subscription_count"], 4);
+    }
+
+    #[cfg(all(not(feature = "wasm"), feature = "uniffi"))]
+    #[test]
+    fn test_ffi_channel_callback_lifecycle() {
+        use parking_lot::Mutex;
+
+        #[derive(Default)]
+        struct Recorder(Arc<Mutex<Vec<String>>>);
+
+        impl crate::ffi_callbacks::ChannelCallback for Recorder {
+            fn on_subscription_succeeded(&self, _data: Option<String>) {
+                self.0.lock().push("subscribed".to_string());
+            }
+
+            fn on_subscription_error(&self, error_type: String, message: String) {
+                self.0
+                    .lock()
+                    .push(format!("error {}: {}", error_type, message));
+            }
+
+            fn on_unsubscribed(&self) {
+                self.0.lock().push("unsubscribed".to_string());
+            }
+
+            fn on_event(
+                &self,
+                event_name: String,
+                _data: Option<String>,
+                _user_id: Option<String>,
+            ) {
+                self.0.lock().push(event_name);
+            }
+        }
+
+        let channel = Channel::new("news");
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        channel.ffi_bind_channel(Box::new(Recorder(calls.clone())));
+
+        channel.handle_event(
+            &PusherEvent::new("pusher:subscription_error").with_json_data(serde_json::json!({
+                "type": "AuthError",
+                "error": "Invalid signature",
+            })),
+        );
+        channel.handle_event(&PusherEvent::new("pusher_internal:subscription_succeeded"));
+        channel.handle_event(&PusherEvent::new("breaking"));
+        channel.unsubscribe();
+
+        assert_eq!(
+            *calls.lock(),
+            [
+                "error AuthError: Invalid signature",
+                "subscribed",
+                "breaking",
+                "unsubscribed",
+            ]
+        );
+    }
+
     #[test]
     fn test_channel_creation() {
         let channel = Channel::new("test-channel");
@@ -575,13 +1511,260 @@ mod tests {
         let counter = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
         let counter_clone = counter.clone();
 
-        channel.bind("test-event", move |_| {
-            counter_clone.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
-        });
+        channel
+            .bind("test-event", move |_| {
+                counter_clone.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            })
+            .unwrap();
 
         let event = PusherEvent::new("test-event");
         channel.handle_event(&event);
 
         assert_eq!(counter.load(std::sync::atomic::Ordering::SeqCst), 1);
     }
+
+    #[test]
+    fn test_on_subscribed_resolves() {
+        let channel = Channel::new("test-channel");
+        let first = channel.on_subscribed();
+        let second = channel.on_subscribed();
+
+        let mut event = PusherEvent::new("pusher_internal:subscription_succeeded");
+        event.channel = Some("test-channel".to_string());
+        channel.handle_event(&event);
+
+        assert!(futures::executor::block_on(first).is_ok());
+        assert!(futures::executor::block_on(second).is_ok());
+        assert!(!channel
+            .dispatcher
+            .has_callbacks("pusher:subscription_succeeded"));
+
+        // Already subscribed resolves immediately
+        assert!(futures::executor::block_on(channel.on_subscribed()).is_ok());
+    }
+
+    #[test]
+    fn test_on_subscribed_drop_cleans_up() {
+        let channel = Channel::new("test-channel");
+        let pending = channel.on_subscribed();
+        assert!(channel
+            .dispatcher
+            .has_callbacks("pusher:subscription_succeeded"));
+
+        drop(pending);
+        assert!(!channel
+            .dispatcher
+            .has_callbacks("pusher:subscription_succeeded"));
+        assert!(!channel
+            .dispatcher
+            .has_callbacks("pusher:subscription_error"));
+    }
+
+    fn batch_channel(mode: TriggerBatchMode, sent: Arc<RwLock<Vec<String>>>) -> Channel {
+        let mut channel = Channel::new("private-orders");
+        channel.set_trigger_batch_options(mode, 2);
+        channel.set_raw_send_callback(Arc::new(move |frame| {
+            sent.write().push(frame.to_string());
+            true
+        }));
+        channel
+    }
+
+    #[test]
+    fn test_compiled_filter_drops_non_matching_events() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let channel = Channel::new("scores");
+        channel.set_compiled_filter(FilterOp::eq("team", "home").compile());
+
+        let received = Arc::new(AtomicUsize::new(0));
+        let counter = received.clone();
+        channel
+            .bind("goal", move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })
+            .unwrap();
+
+        let home = PusherEvent::new("goal").with_json_data(serde_json::json!({"team": "home"}));
+        let away = PusherEvent::new("goal").with_json_data(serde_json::json!({"team": "away"}));
+        channel.handle_event(&home);
+        channel.handle_event(&away);
+        assert_eq!(received.load(Ordering::SeqCst), 1);
+
+        channel.clear_compiled_filter();
+        channel.handle_event(&away);
+        assert_eq!(received.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_subscription_metadata() {
+        let channel = Channel::new("test-channel");
+        assert_eq!(channel.subscription_metadata(), None);
+
+        let mut event = PusherEvent::new("pusher_internal:subscription_succeeded");
+        #[cfg(feature = "wasm")]
+        {
+            event.data = Some(serde_json::json!({"foo": "bar"}));
+        }
+        #[cfg(not(feature = "wasm"))]
+        {
+            event.data = Some(r#"{"foo":"bar"}"#.to_string());
+        }
+        channel.handle_event(&event);
+
+        assert_eq!(
+            channel.subscription_metadata(),
+            Some(serde_json::json!({"foo": "bar"}))
+        );
+        assert_eq!(
+            channel.with_subscription_metadata(|m| m["foo"].as_str().map(str::len)),
+            Some(Some(3))
+        );
+
+        channel.disconnect();
+        assert_eq!(channel.subscription_metadata(), None);
+    }
+
+    #[test]
+    fn test_trigger_signs_client_events() {
+        let sent = Arc::new(RwLock::new(Vec::new()));
+        let mut channel = batch_channel(TriggerBatchMode::Single, sent.clone());
+        channel.set_client_event_secret(Some("s3cret".into()));
+
+        #[cfg(feature = "wasm")]
+        let result = channel.trigger("client-ping", serde_json::json!({"n": 1}));
+        #[cfg(not(feature = "wasm"))]
+        let result = channel.trigger_value("client-ping", serde_json::json!({"n": 1}));
+        assert!(result.unwrap());
+
+        let frame: serde_json::Value = serde_json::from_str(&sent.read()[0]).unwrap();
+        let signature = frame["signature"].as_str().unwrap().to_string();
+        let event: PusherEvent = serde_json::from_value(frame).unwrap();
+        assert_eq!(event.channel.as_deref(), Some("private-orders"));
+        assert!(Protocol::verify_signature(&event, "s3cret", &signature));
+    }
+
+    #[test]
+    fn test_trigger_batch_array_frames() {
+        let sent = Arc::new(RwLock::new(Vec::new()));
+        let channel = batch_channel(TriggerBatchMode::Array, sent.clone());
+
+        let results = channel.trigger_batch(vec![
+            ("client-a".to_string(), serde_json::json!({"n": 1})),
+            ("client-b".to_string(), serde_json::json!({"n": 2})),
+            ("client-c".to_string(), serde_json::json!({"n": 3})),
+        ]);
+
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|r| matches!(r, Ok(true))));
+
+        // Capped at two events per frame
+        let sent = sent.read();
+        assert_eq!(sent.len(), 2);
+        let frame: Vec<serde_json::Value> = serde_json::from_str(&sent[0]).unwrap();
+        assert_eq!(frame.len(), 2);
+        assert_eq!(frame[0]["event"], "client-a");
+        assert_eq!(frame[0]["channel"], "private-orders");
+        assert_eq!(frame[1]["event"], "client-b");
+        let frame: Vec<serde_json::Value> = serde_json::from_str(&sent[1]).unwrap();
+        assert_eq!(frame.len(), 1);
+    }
+
+    #[test]
+    fn test_trigger_batch_rejects_invalid_event_names() {
+        let sent = Arc::new(RwLock::new(Vec::new()));
+        let channel = batch_channel(TriggerBatchMode::Array, sent.clone());
+
+        let results = channel.trigger_batch(vec![
+            ("client-a".to_string(), serde_json::json!({})),
+            ("server-b".to_string(), serde_json::json!({})),
+        ]);
+
+        assert!(results
+            .iter()
+            .all(|r| matches!(r, Err(SockudoError::InvalidEvent { .. }))));
+        assert!(sent.read().is_empty());
+    }
+
+    #[test]
+    fn test_trigger_validates_event_name() {
+        let sent = Arc::new(RwLock::new(Vec::new()));
+        let channel = batch_channel(TriggerBatchMode::Single, sent.clone());
+
+        #[cfg(feature = "wasm")]
+        let result = channel.trigger("client-bad name", serde_json::json!({}));
+        #[cfg(not(feature = "wasm"))]
+        let result = channel.trigger_value("client-bad name", serde_json::json!({}));
+        assert!(matches!(result, Err(SockudoError::InvalidEventName { .. })));
+
+        let results = channel.trigger_batch(vec![(
+            format!("client-{}", "x".repeat(200)),
+            serde_json::json!({}),
+        )]);
+        assert!(matches!(
+            results[0],
+            Err(SockudoError::InvalidEventName { .. })
+        ));
+        assert!(sent.read().is_empty());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_await_event_serves_callers_in_order() {
+        let channel = Channel::new("prices");
+        let first = channel.await_event("price-update", Duration::from_secs(5));
+        let second = channel.await_event("price-update", Duration::from_secs(5));
+        let any = channel.await_event("*", Duration::from_secs(5));
+
+        let first = tokio::spawn(first);
+        let second = tokio::spawn(second);
+        for price in ["100", "101"] {
+            channel.handle_event(&PusherEvent::new("price-update").with_string_data(price));
+        }
+
+        let first = first.await.unwrap().unwrap();
+        let second = second.await.unwrap().unwrap();
+        assert_eq!(first.data_as_string().as_deref(), Some("100"));
+        assert_eq!(second.data_as_string().as_deref(), Some("101"));
+        assert_eq!(any.await.unwrap().data_as_string().as_deref(), Some("100"));
+
+        let results = channel
+            .await_events("price-update", 2, Duration::from_millis(50))
+            .await;
+        assert_eq!(results.len(), 2);
+        assert!(results
+            .iter()
+            .all(|r| matches!(r, Err(SockudoError::TimeoutError { .. }))));
+    }
+
+    #[tokio::test]
+    async fn test_bind_and_wait() {
+        let channel = Channel::new("scores");
+        let waiter = tokio::spawn(channel.bind_and_wait(
+            "score",
+            |event| {
+                event
+                    .parse_data::<serde_json::Value>()
+                    .map(|score| score["goals"].as_u64() > Some(3))
+                    .unwrap_or(false)
+            },
+            Duration::from_secs(5),
+        ));
+        assert_eq!(channel.listener_count(Some("score")), 1);
+
+        for goals in [1, 2, 4, 5] {
+            channel.handle_event(
+                &PusherEvent::new("score").with_json_data(serde_json::json!({ "goals": goals })),
+            );
+        }
+        let event = waiter.await.unwrap().unwrap();
+        assert_eq!(event.data_as_value().unwrap()["goals"], 4);
+        assert_eq!(channel.listener_count(Some("score")), 0);
+
+        let timed_out = channel
+            .bind_and_wait("score", |_| false, Duration::from_millis(20))
+            .await;
+        assert!(matches!(timed_out, Err(SockudoError::TimeoutError { .. })));
+        assert_eq!(channel.listener_count(Some("score")), 0);
+    }
 }
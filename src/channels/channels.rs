@@ -1,11 +1,27 @@
 //! Channel collection management.
 
+use std::collections::HashMap;
+#[cfg(any(test, feature = "offline-mode"))]
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use dashmap::mapref::entry::Entry;
 use dashmap::DashMap;
-use tracing::debug;
+use parking_lot::{Mutex, RwLock};
+use tracing::{debug, info, warn};
 
 use crate::error::{Result, SockudoError};
-use super::channel::{Channel, ChannelType, SendEventFn, AuthorizeFn};
+use crate::events::EventNameValidator;
+use crate::protocol::validate_channel_name;
+use super::channel::{
+    AuthorizeFn, Channel, ChannelState, ChannelType, SendBatchFn, SendEventFn, SendRawFn,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use super::channel::{ResubscribePolicy, DEFAULT_RESUBSCRIPTION_TIMEOUT};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::events::EventWaiter;
+use crate::options::{
+    match_channel_prefix, ChannelConfig, SensitiveBytes, SensitiveString, TriggerBatchMode,
+};
 use super::presence_channel::PresenceChannel;
 use super::encrypted_channel::EncryptedChannel;
 
@@ -19,6 +35,41 @@ pub struct Channels {
     authorize_fn: Option<AuthorizeFn>,
     /// Encryption key callback for encrypted channels
     encryption_callback: Option<Arc<dyn Fn() -> Option<[u8; 32]> + Send + Sync>>,
+    /// Raw frame callback for signed client events
+    send_raw: Option<SendRawFn>,
+    /// Send callback for batched triggers
+    send_batch: Option<SendBatchFn>,
+    /// Send callback for bulk resubscription after a reconnect
+    send_bulk: Option<SendEventFn>,
+    /// Batch mode and maximum batch size for `trigger_batch`
+    trigger_batch_options: (TriggerBatchMode, usize),
+    /// Channels whose subscription is deferred until the connection is ready
+    pending_subscriptions: RwLock<Vec<String>>,
+    /// Shared secret for signing client events
+    client_event_secret: Option<SensitiveString>,
+    /// Maximum number of channels, if limited
+    max_subscriptions: Option<usize>,
+    /// Held while checking the subscription limit and inserting a channel,
+    /// and while migrating one
+    add_lock: Mutex<()>,
+    /// Custom event name validator applied to every channel's bindings
+    event_name_validator: Option<EventNameValidator>,
+    /// Whether channel names are checked with the event name validator
+    strict_channel_names: bool,
+    /// Per-prefix settings applied to new channels
+    channel_prefix_map: HashMap<String, ChannelConfig>,
+    /// Decryption keys for encrypted channels, by channel name
+    channel_shared_secrets: HashMap<String, SensitiveBytes>,
+    /// Metadata sent with every subscribe request
+    global_subscription_metadata: HashMap<String, String>,
+    /// Time each callback may run during dispatch, if limited
+    #[cfg(not(target_arch = "wasm32"))]
+    callback_timeout: Option<std::time::Duration>,
+    /// Events of each name kept by every new channel
+    event_history_size: usize,
+    /// Whether subscriptions succeed locally without authorization
+    #[cfg(any(test, feature = "offline-mode"))]
+    offline: Arc<AtomicBool>,
 }
 
 /// Entry that can hold different channel types
@@ -30,19 +81,147 @@ enum ChannelEntry {
 
 impl Channels {
     pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Create a collection with room for `initial_capacity` channels before
+    /// the channel map needs to grow
+    pub fn with_capacity(initial_capacity: usize) -> Self {
         Self {
-            channels: DashMap::new(),
+            channels: DashMap::with_capacity(initial_capacity),
             send_event: None,
             authorize_fn: None,
             encryption_callback: None,
+            send_raw: None,
+            send_batch: None,
+            send_bulk: None,
+            trigger_batch_options: (TriggerBatchMode::Single, 10),
+            pending_subscriptions: RwLock::new(Vec::with_capacity(initial_capacity)),
+            client_event_secret: None,
+            max_subscriptions: None,
+            add_lock: Mutex::new(()),
+            event_name_validator: None,
+            strict_channel_names: false,
+            channel_prefix_map: HashMap::new(),
+            channel_shared_secrets: HashMap::new(),
+            global_subscription_metadata: HashMap::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            callback_timeout: None,
+            event_history_size: 0,
+            #[cfg(any(test, feature = "offline-mode"))]
+            offline: Arc::new(AtomicBool::new(false)),
         }
     }
     
+    /// Make room for at least `additional` more channels, spread evenly
+    /// over the channel map's shards
+    pub fn reserve(&self, additional: usize) {
+        let shards = self.channels.shards();
+        let per_shard = additional.div_ceil(shards.len());
+        for shard in shards {
+            shard.write().reserve(per_shard);
+        }
+        self.pending_subscriptions.write().reserve(additional);
+    }
+
     /// Set the send event callback
     pub fn set_send_callback(&mut self, callback: SendEventFn) {
         self.send_event = Some(callback);
     }
     
+    /// Set the raw frame callback used by signed client events
+    pub fn set_raw_send_callback(&mut self, callback: SendRawFn) {
+        self.send_raw = Some(callback);
+    }
+
+    /// Set the send callback used by batched triggers
+    pub fn set_batch_send_callback(&mut self, callback: SendBatchFn) {
+        self.send_batch = Some(callback);
+    }
+    
+    /// Set the callback used to resubscribe channels after a reconnect, so
+    /// the bulk subscriptions can be sent at a lower priority
+    pub fn set_bulk_send_callback(&mut self, callback: SendEventFn) {
+        self.send_bulk = Some(callback);
+    }
+    
+    /// Configure how `Channel::trigger_batch` sends events
+    pub fn set_trigger_batch_options(&mut self, mode: TriggerBatchMode, max_batch_size: usize) {
+        self.trigger_batch_options = (mode, max_batch_size);
+    }
+    
+    /// Set the secret used to sign client events
+    pub fn set_client_event_secret(&mut self, secret: Option<SensitiveString>) {
+        self.client_event_secret = secret;
+    }
+    
+    /// Limit the number of channels that can be added (`None` for no limit)
+    pub fn set_max_subscriptions(&mut self, limit: Option<usize>) {
+        self.max_subscriptions = limit;
+    }
+    
+    /// Set the event name validator used by new channels (`None` for the default)
+    pub fn set_event_name_validator(&mut self, validator: Option<EventNameValidator>) {
+        self.event_name_validator = validator;
+    }
+    
+    /// Check channel names with the event name validator in `add`
+    pub fn set_strict_channel_names(&mut self, strict: bool) {
+        self.strict_channel_names = strict;
+    }
+    
+    /// Set the per-prefix settings applied to new channels
+    pub fn set_channel_prefix_map(&mut self, prefix_map: HashMap<String, ChannelConfig>) {
+        self.channel_prefix_map = prefix_map;
+    }
+    
+    /// Set the decryption keys used by new encrypted channels
+    pub fn set_channel_shared_secrets(&mut self, secrets: HashMap<String, SensitiveBytes>) {
+        self.channel_shared_secrets = secrets;
+    }
+    
+    /// Set the metadata sent with every subscribe request
+    pub fn set_global_subscription_metadata(&mut self, metadata: HashMap<String, String>) {
+        self.global_subscription_metadata = metadata;
+    }
+    
+    /// Metadata sent with every subscribe request
+    pub fn global_subscription_metadata(&self) -> &HashMap<String, String> {
+        &self.global_subscription_metadata
+    }
+    
+    /// Set the callback timeout applied to new channels (`None` for no limit)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_callback_timeout(&mut self, timeout: Option<std::time::Duration>) {
+        self.callback_timeout = timeout;
+    }
+    
+    /// Set the number of events of each name new channels keep for
+    /// `Channel::get_event_history` (0 for none)
+    pub fn set_event_history_size(&mut self, size: usize) {
+        self.event_history_size = size;
+    }
+    
+    /// Turn offline mode on or off for every channel, existing or added
+    /// later. Offline subscriptions skip authorization and succeed
+    /// immediately, presence channels with no members.
+    #[cfg(any(test, feature = "offline-mode"))]
+    pub(crate) fn set_offline(&self, offline: bool) {
+        self.offline.store(offline, Ordering::SeqCst);
+    }
+    
+    /// Get the prefix config that applies to `name`, if any
+    pub fn channel_config(&self, name: &str) -> Option<&ChannelConfig> {
+        match_channel_prefix(&self.channel_prefix_map, name)
+    }
+    
+    /// Whether full messages on `name` should be cached for delta decoding
+    pub fn delta_enabled_for(&self, name: &str) -> bool {
+        self.channel_config(name)
+            .and_then(|config| config.use_delta)
+            .unwrap_or(true)
+    }
+    
     /// Set the authorization callback
     pub fn set_authorize_callback(&mut self, callback: AuthorizeFn) {
         self.authorize_fn = Some(callback);
@@ -63,16 +242,42 @@ impl Channels {
             };
         }
         
+        if self.strict_channel_names {
+            match self.event_name_validator {
+                Some(ref validator) => {
+                    if let Err(SockudoError::InvalidEventName { reason, .. }) =
+                        validator.validate(name)
+                    {
+                        return Err(SockudoError::invalid_channel(format!(
+                            "Invalid channel name '{}': {}",
+                            name, reason
+                        )));
+                    }
+                }
+                None => validate_channel_name(name)?,
+            }
+        }
+        
         let channel_type = ChannelType::from_name(name);
         
         let entry = match channel_type {
             ChannelType::PrivateEncrypted => {
-                if self.encryption_callback.is_none() {
-                    return Err(SockudoError::config(
-                        "Encrypted channels require nacl/encryption support"
-                    ));
+                let secret = match self.channel_shared_secrets.get(name) {
+                    Some(secret) => Some(secret.clone()),
+                    None => self.encryption_callback.as_ref().and_then(|cb| cb()).map(SensitiveBytes::new),
+                };
+                if secret.is_none() && self.authorize_fn.is_none() {
+                    return Err(SockudoError::config(format!(
+                        "Encrypted channel '{}' requires a shared secret or an authorizer",
+                        name
+                    )));
                 }
                 let mut channel = EncryptedChannel::new(name);
+                #[cfg(any(test, feature = "offline-mode"))]
+                channel.share_offline_flag(self.offline.clone());
+                if let Some(secret) = secret {
+                    channel.set_shared_secret(secret.expose())?;
+                }
                 if let Some(ref cb) = self.send_event {
                     channel.set_send_callback(cb.clone());
                 }
@@ -83,9 +288,20 @@ impl Channels {
             }
             ChannelType::Presence => {
                 let mut channel = PresenceChannel::new(name);
+                #[cfg(any(test, feature = "offline-mode"))]
+                channel.share_offline_flag(self.offline.clone());
                 if let Some(ref cb) = self.send_event {
                     channel.set_send_callback(cb.clone());
                 }
+                if let Some(ref cb) = self.send_raw {
+                    channel.set_raw_send_callback(cb.clone());
+                }
+                if let Some(ref cb) = self.send_batch {
+                    channel.set_batch_send_callback(cb.clone());
+                }
+                let (mode, max_batch_size) = self.trigger_batch_options;
+                channel.set_trigger_batch_options(mode, max_batch_size);
+                channel.set_client_event_secret(self.client_event_secret.clone());
                 if let Some(ref cb) = self.authorize_fn {
                     channel.set_authorize_callback(cb.clone());
                 }
@@ -93,9 +309,20 @@ impl Channels {
             }
             _ => {
                 let mut channel = Channel::new(name);
+                #[cfg(any(test, feature = "offline-mode"))]
+                channel.share_offline_flag(self.offline.clone());
                 if let Some(ref cb) = self.send_event {
                     channel.set_send_callback(cb.clone());
                 }
+                if let Some(ref cb) = self.send_raw {
+                    channel.set_raw_send_callback(cb.clone());
+                }
+                if let Some(ref cb) = self.send_batch {
+                    channel.set_batch_send_callback(cb.clone());
+                }
+                let (mode, max_batch_size) = self.trigger_batch_options;
+                channel.set_trigger_batch_options(mode, max_batch_size);
+                channel.set_client_event_secret(self.client_event_secret.clone());
                 if let Some(ref cb) = self.authorize_fn {
                     channel.set_authorize_callback(cb.clone());
                 }
@@ -110,6 +337,36 @@ impl Channels {
             ChannelEntry::Encrypted(ch) => ch.as_channel(),
         };
         
+        if let Some(ref validator) = self.event_name_validator {
+            channel.set_event_name_validator(validator.clone());
+        }
+        if let Some(limit) = self.channel_config(name).and_then(|config| config.max_listeners) {
+            channel.set_max_listeners(limit);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        channel.set_callback_timeout(self.callback_timeout);
+        if !self.global_subscription_metadata.is_empty() {
+            channel.set_request_metadata(self.global_subscription_metadata.clone());
+        }
+        if self.event_history_size > 0 {
+            channel.set_history_size(self.event_history_size);
+        }
+        
+        let _guard = self.add_lock.lock();
+        // Another caller may have added the channel in the meantime
+        if let Some(existing) = self.find(name) {
+            return Ok(existing);
+        }
+        let current = self.len();
+        if let Some(limit) = self.max_subscriptions {
+            if current >= limit {
+                return Err(SockudoError::SubscriptionLimitExceeded { limit, current });
+            }
+            // Warn once, as the count reaches 80% of the limit
+            if current * 5 < limit * 4 && (current + 1) * 5 >= limit * 4 {
+                warn!("{} of {} allowed channel subscriptions in use", current + 1, limit);
+            }
+        }
         self.channels.insert(name.to_string(), entry);
         debug!("Created channel: {}", name);
         
@@ -127,6 +384,19 @@ impl Channels {
         })
     }
     
+    /// Find all channels whose name starts with `prefix`
+    pub fn find_by_prefix(&self, prefix: &str) -> Vec<Arc<Channel>> {
+        self.channels
+            .iter()
+            .filter(|entry| entry.key().starts_with(prefix))
+            .map(|entry| match entry.value() {
+                ChannelEntry::Basic(ch) => ch.clone(),
+                ChannelEntry::Presence(ch) => ch.as_channel(),
+                ChannelEntry::Encrypted(ch) => ch.as_channel(),
+            })
+            .collect()
+    }
+    
     /// Find a presence channel by name
     pub fn find_presence(&self, name: &str) -> Option<Arc<PresenceChannel>> {
         self.channels.get(name).and_then(|entry| {
@@ -147,8 +417,108 @@ impl Channels {
         })
     }
     
+    /// Defer the subscription of a channel until the connection is ready
+    pub fn queue_pending_subscription(&self, name: &str) {
+        let mut pending = self.pending_subscriptions.write();
+        if !pending.iter().any(|n| n == name) {
+            pending.push(name.to_string());
+        }
+    }
+    
+    /// Names of channels waiting for the connection, in subscription order
+    pub fn pending_subscriptions(&self) -> Vec<String> {
+        self.pending_subscriptions.read().clone()
+    }
+    
+    /// Take the deferred channels that still exist, in subscription order
+    pub fn take_pending_subscriptions(&self) -> Vec<Arc<Channel>> {
+        let names = std::mem::take(&mut *self.pending_subscriptions.write());
+        names.iter().filter_map(|name| self.find(name)).collect()
+    }
+    
+    /// Claim every channel that needs a subscription request, deferred
+    /// lazy subscriptions first.
+    ///
+    /// Channels in `Unsubscribed` or `Failed` are moved to `Subscribing` and
+    /// returned; subscribed and in-flight channels are left alone. The
+    /// transition is atomic per channel, so concurrent callers never get
+    /// the same channel twice.
+    pub fn drain_pending(&self) -> Vec<Arc<Channel>> {
+        let mut channels = self.take_pending_subscriptions();
+        for channel in self.all() {
+            if !channels.iter().any(|c| c.current_name() == channel.current_name()) {
+                channels.push(channel);
+            }
+        }
+        channels.retain(|channel| channel.begin_subscribing());
+        channels
+    }
+    
+    /// Subscribe every channel that is not already subscribed after the
+    /// connection is established, deferred lazy subscriptions first.
+    ///
+    /// Channels with their own reconnect strategy or resubscription timeout
+    /// are retried in the background until the server confirms them; the
+    /// others get a single attempt.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn resubscribe_all_after_connect(&self, socket_id: &str) {
+        let pending = self.drain_pending();
+        info!(
+            "Resubscribing {} channels after connection established",
+            pending.len()
+        );
+        
+        for channel in pending {
+            let policy = channel.resubscribe_policy();
+            if policy.is_custom() {
+                match tokio::runtime::Handle::try_current() {
+                    Ok(handle) => {
+                        handle.spawn(resubscribe_with_policy(channel, socket_id.to_string(), policy));
+                        continue;
+                    }
+                    Err(_) => warn!(
+                        "No tokio runtime to retry {}, subscribing once",
+                        channel.current_name()
+                    ),
+                }
+            }
+            
+            info!("Attempting to subscribe to channel: {}", channel.current_name());
+            if let Err(e) = channel.subscribe_with(socket_id, self.send_bulk.as_ref()) {
+                warn!("Failed to resubscribe to channel {}: {}", channel.current_name(), e);
+            } else {
+                info!("Successfully sent subscription for channel: {}", channel.current_name());
+            }
+        }
+    }
+    
+    /// Subscribe `channel` now and wait in the background for the server's
+    /// confirmation, up to the channel's subscription timeout.
+    ///
+    /// An unconfirmed subscription is retried with the channel's reconnect
+    /// strategy, then marked failed, as for resubscriptions. Without a tokio
+    /// runtime the request is sent without waiting.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn subscribe_bounded(&self, channel: Arc<Channel>, socket_id: &str) -> Result<()> {
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            return channel.subscribe(socket_id);
+        };
+        let policy = channel.resubscribe_policy();
+        let confirmed = channel.on_subscribed();
+        channel.subscribe(socket_id)?;
+        handle.spawn(confirm_with_policy(
+            channel,
+            socket_id.to_string(),
+            policy,
+            confirmed,
+            Ok(()),
+        ));
+        Ok(())
+    }
+    
     /// Remove a channel
     pub fn remove(&self, name: &str) -> Option<Arc<Channel>> {
+        self.pending_subscriptions.write().retain(|n| n != name);
         self.channels.remove(name).map(|(_, entry)| {
             debug!("Removed channel: {}", name);
             match entry {
@@ -159,6 +529,56 @@ impl Channels {
         })
     }
     
+    /// Move a channel to a new name, keeping its callbacks and settings.
+    ///
+    /// The old name is unsubscribed from the server; the caller subscribes
+    /// the returned channel under `new_name`. Only public and private
+    /// channels can be moved, and only to a name of the same type.
+    pub fn migrate(&self, old_name: &str, new_name: &str) -> Result<Arc<Channel>> {
+        let _guard = self.add_lock.lock();
+        if self.channels.contains_key(new_name) {
+            return Err(SockudoError::channel_already_exists(new_name));
+        }
+        let channel = match self.channels.get(old_name).as_deref() {
+            Some(ChannelEntry::Basic(ch)) => ch.clone(),
+            Some(_) => {
+                return Err(SockudoError::invalid_channel(format!(
+                    "Cannot migrate presence or encrypted channel '{}'",
+                    old_name
+                )))
+            }
+            None => {
+                return Err(SockudoError::channel(format!(
+                    "Channel '{}' not found",
+                    old_name
+                )))
+            }
+        };
+        if ChannelType::from_name(new_name) != channel.channel_type() {
+            return Err(SockudoError::invalid_channel(format!(
+                "Cannot migrate '{}' to '{}': channel types differ",
+                old_name, new_name
+            )));
+        }
+
+        match self.channels.entry(new_name.to_string()) {
+            Entry::Occupied(_) => return Err(SockudoError::channel_already_exists(new_name)),
+            Entry::Vacant(entry) => {
+                entry.insert(ChannelEntry::Basic(channel.clone()));
+            }
+        }
+        self.channels.remove(old_name);
+        channel.rename(new_name);
+
+        for name in self.pending_subscriptions.write().iter_mut() {
+            if name == old_name {
+                *name = new_name.to_string();
+            }
+        }
+        debug!("Migrated channel {} to {}", old_name, new_name);
+        Ok(channel)
+    }
+    
     /// Get all channels
     pub fn all(&self) -> Vec<Arc<Channel>> {
         self.channels.iter().map(|entry| {
@@ -170,6 +590,11 @@ impl Channels {
         }).collect()
     }
     
+    /// Get the names of all channels
+    pub fn names(&self) -> Vec<String> {
+        self.channels.iter().map(|entry| entry.key().clone()).collect()
+    }
+    
     /// Get channel count
     pub fn len(&self) -> usize {
         self.channels.len()
@@ -180,8 +605,51 @@ impl Channels {
         self.channels.is_empty()
     }
     
+    /// Count channels per type, omitting types with no channels
+    pub fn count_by_type(&self) -> HashMap<ChannelType, usize> {
+        let mut counts = HashMap::new();
+        for entry in self.channels.iter() {
+            *counts.entry(ChannelType::from_name(entry.key())).or_insert(0) += 1;
+        }
+        counts
+    }
+    
+    /// Count channels whose subscription has succeeded
+    pub fn subscribed_count(&self) -> usize {
+        self.all()
+            .iter()
+            .filter(|ch| ch.state() == ChannelState::Subscribed)
+            .count()
+    }
+    
+    /// Count channels whose subscription is in flight or deferred until
+    /// the connection is ready
+    pub fn pending_count(&self) -> usize {
+        self.pending_names().len()
+    }
+    
+    /// Names of channels whose subscription is in flight or deferred
+    pub fn pending_names(&self) -> Vec<String> {
+        let pending = self.pending_subscriptions.read();
+        self.all()
+            .iter()
+            .filter(|ch| {
+                ch.state() == ChannelState::Subscribing
+                    || pending.iter().any(|name| *name == ch.current_name())
+            })
+            .map(|ch| ch.current_name())
+            .collect()
+    }
+    
     /// Disconnect all channels
     pub fn disconnect(&self) {
+        self.reset_subscriptions();
+    }
+    
+    /// Move every channel back to `Unsubscribed`, so the next
+    /// `drain_pending` returns all of them (e.g. for a hard reconnect where
+    /// the server has forgotten the old subscriptions)
+    pub fn reset_subscriptions(&self) {
         for entry in self.channels.iter() {
             match &*entry {
                 ChannelEntry::Basic(ch) => ch.disconnect(),
@@ -193,10 +661,77 @@ impl Channels {
     
     /// Clear all channels
     pub fn clear(&self) {
+        self.pending_subscriptions.write().clear();
         self.channels.clear();
     }
 }
 
+/// Subscribe `channel` until the server confirms it, backing off between
+/// attempts with the channel's own strategy
+#[cfg(not(target_arch = "wasm32"))]
+async fn resubscribe_with_policy(channel: Arc<Channel>, socket_id: String, policy: ResubscribePolicy) {
+    let confirmed = channel.on_subscribed();
+    let sent = channel.subscribe(&socket_id);
+    confirm_with_policy(channel, socket_id, policy, confirmed, sent).await;
+}
+
+/// Wait for the server to confirm a subscription request that was `sent`,
+/// resubscribing with the channel's own strategy until it does
+#[cfg(not(target_arch = "wasm32"))]
+async fn confirm_with_policy(
+    channel: Arc<Channel>,
+    socket_id: String,
+    policy: ResubscribePolicy,
+    mut confirmed: EventWaiter<()>,
+    mut sent: Result<()>,
+) {
+    let timeout = policy.timeout.unwrap_or(DEFAULT_RESUBSCRIPTION_TIMEOUT);
+    let mut attempt = 0;
+    loop {
+        let result = match sent {
+            Ok(()) => tokio::time::timeout(timeout, confirmed)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(SockudoError::timeout(format!(
+                        "No confirmation within {:?}",
+                        timeout
+                    )))
+                }),
+            Err(e) => Err(e),
+        };
+        let Err(e) = result else {
+            return;
+        };
+        
+        attempt += 1;
+        let delay = policy
+            .strategy
+            .as_ref()
+            .and_then(|strategy| strategy.next_delay(attempt));
+        let Some(delay) = delay else {
+            warn!("Giving up resubscribing to channel {}: {}", channel.current_name(), e);
+            channel.mark_failed();
+            return;
+        };
+        warn!(
+            "Resubscribing to channel {} failed: {}; retrying in {:?}",
+            channel.current_name(),
+            e,
+            delay
+        );
+        tokio::time::sleep(delay).await;
+        
+        // A disconnect, unsubscribe or newer connection supersedes this retry
+        if channel.state() == ChannelState::Unsubscribed
+            || channel.subscribed_socket_id().as_deref() != Some(socket_id.as_str())
+        {
+            return;
+        }
+        confirmed = channel.on_subscribed();
+        sent = channel.subscribe(&socket_id);
+    }
+}
+
 impl Default for Channels {
     fn default() -> Self {
         Self::new()
@@ -237,6 +772,222 @@ mod tests {
         assert_eq!(private.channel_type(), ChannelType::Private);
     }
 
+    #[test]
+    fn test_count_by_type() {
+        let channels = Channels::new();
+        
+        channels.add("news").unwrap();
+        channels.add("sports").unwrap();
+        channels.add("private-inbox").unwrap();
+        channels.add("presence-lobby").unwrap();
+        channels.queue_pending_subscription("sports");
+        
+        let counts = channels.count_by_type();
+        assert_eq!(counts.get(&ChannelType::Public), Some(&2));
+        assert_eq!(counts.get(&ChannelType::Private), Some(&1));
+        assert_eq!(counts.get(&ChannelType::Presence), Some(&1));
+        assert_eq!(counts.get(&ChannelType::PrivateEncrypted), None);
+        
+        assert_eq!(channels.subscribed_count(), 0);
+        assert_eq!(channels.pending_count(), 1);
+    }
+
+    #[test]
+    fn test_drain_pending() {
+        let channels = Arc::new(Channels::new());
+        for i in 0..50 {
+            channels.add(&format!("channel-{}", i)).unwrap();
+        }
+        let subscribed = channels.add("subscribed").unwrap();
+        subscribed.handle_event(&crate::protocol::PusherEvent::new("pusher_internal:subscription_succeeded"));
+        channels.queue_pending_subscription("channel-7");
+        
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let channels = channels.clone();
+                std::thread::spawn(move || channels.drain_pending())
+            })
+            .collect();
+        let mut drained: Vec<String> = handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .map(|channel| channel.name().to_string())
+            .collect();
+        assert_eq!(drained.len(), 50);
+        drained.sort();
+        drained.dedup();
+        assert_eq!(drained.len(), 50);
+        assert!(!drained.contains(&"subscribed".to_string()));
+        assert!(channels.drain_pending().is_empty());
+        assert_eq!(channels.all().len(), 51);
+        assert_eq!(channels.subscribed_count(), 1);
+        
+        // A hard reconnect makes every channel eligible again
+        channels.reset_subscriptions();
+        assert_eq!(channels.drain_pending().len(), 51);
+    }
+    
+    #[test]
+    fn test_event_and_channel_name_validation() {
+        let mut channels = Channels::new();
+        let channel = channels.add("lenient channel").unwrap();
+        assert!(channel.bind("a".repeat(201), |_| {}).is_err());
+        assert!(matches!(
+            channel.bind("bad name", |_| {}),
+            Err(SockudoError::InvalidEventName { .. })
+        ));
+        assert!(channel.bind("good-name", |_| {}).is_ok());
+        
+        channels.set_strict_channel_names(true);
+        assert!(matches!(
+            channels.add("strict channel"),
+            Err(SockudoError::InvalidChannel { .. })
+        ));
+        
+        channels.set_event_name_validator(Some(EventNameValidator::new(|name| {
+            name.chars().all(|c| c.is_ascii_uppercase())
+        })));
+        let channel = channels.add("UPPER").unwrap();
+        assert!(channel.bind("PRICES", |_| {}).is_ok());
+        assert!(channel.bind("prices", |_| {}).is_err());
+        assert!(channels.add("lower").is_err());
+    }
+    
+    #[test]
+    fn test_channel_prefix_config() {
+        let mut channels = Channels::new();
+        let mut prefix_map = HashMap::new();
+        prefix_map.insert("private-".to_string(), ChannelConfig {
+            max_listeners: Some(50),
+            ..Default::default()
+        });
+        prefix_map.insert("private-feed-".to_string(), ChannelConfig {
+            use_delta: Some(false),
+            ..Default::default()
+        });
+        channels.set_channel_prefix_map(prefix_map);
+        
+        assert_eq!(channels.add("private-room-1").unwrap().max_listeners(), 50);
+        // The longest prefix wins and replaces the shorter one entirely
+        assert_eq!(
+            channels.add("private-feed-prices").unwrap().max_listeners(),
+            crate::events::DEFAULT_MAX_LISTENERS
+        );
+        assert!(!channels.delta_enabled_for("private-feed-prices"));
+        assert!(channels.delta_enabled_for("private-room-1"));
+        assert!(channels.channel_config("news").is_none());
+    }
+    
+    #[test]
+    fn test_max_subscriptions() {
+        let mut channels = Channels::new();
+        channels.set_max_subscriptions(Some(2));
+        
+        channels.add("one").unwrap();
+        channels.add("two").unwrap();
+        // Existing channels are returned even at the limit
+        assert!(channels.add("two").is_ok());
+        
+        let err = channels.add("three").unwrap_err();
+        assert!(matches!(
+            err,
+            SockudoError::SubscriptionLimitExceeded { limit: 2, current: 2 }
+        ));
+        assert_eq!(channels.len(), 2);
+        assert!(channels.find("three").is_none());
+    }
+    
+    #[test]
+    fn test_max_subscriptions_concurrent_adds() {
+        let mut channels = Channels::new();
+        channels.set_max_subscriptions(Some(5));
+        let channels = Arc::new(channels);
+        
+        let handles: Vec<_> = (0..8)
+            .map(|thread| {
+                let channels = channels.clone();
+                std::thread::spawn(move || {
+                    (0..50)
+                        .filter(|i| channels.add(&format!("feed-{}-{}", thread, i)).is_ok())
+                        .count()
+                })
+            })
+            .collect();
+        let added: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+        
+        assert_eq!(added, 5);
+        assert_eq!(channels.len(), 5);
+    }
+    
+    #[test]
+    fn test_encrypted_channel_shared_secrets() {
+        let sent = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let sent_clone = sent.clone();
+
+        let mut channels = Channels::new();
+        channels.set_send_callback(Arc::new(move |_event, data, _channel| {
+            sent_clone.lock().push(data.to_string());
+            true
+        }));
+        assert!(channels.add("private-encrypted-room").is_err());
+        
+        let mut secrets = HashMap::new();
+        secrets.insert("private-encrypted-presence-room".to_string(), vec![7u8; 32].into());
+        secrets.insert("private-encrypted-short".to_string(), vec![7u8; 16].into());
+        channels.set_channel_shared_secrets(secrets);
+        
+        // A preset secret is enough to subscribe without an authorizer
+        let channel = channels.add("private-encrypted-presence-room").unwrap();
+        assert!(channels.find_encrypted("private-encrypted-presence-room").is_some());
+        channel.subscribe("1.1").unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&sent.lock()[0]).unwrap();
+        assert_eq!(payload["channel"], "private-encrypted-presence-room");
+        assert!(payload.get("auth").is_none());
+        assert!(matches!(
+            channels.add("private-encrypted-short"),
+            Err(SockudoError::EncryptionError { .. })
+        ));
+    }
+    
+    #[test]
+    fn test_subscription_metadata() {
+        let sent = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let sent_clone = sent.clone();
+
+        let mut channels = Channels::new();
+        channels.set_send_callback(Arc::new(move |_event, data, _channel| {
+            sent_clone.lock().push(data.to_string());
+            true
+        }));
+        let mut global = HashMap::new();
+        global.insert("device_id".to_string(), "abc".to_string());
+        channels.set_global_subscription_metadata(global);
+
+        channels.add("plain").unwrap().subscribe("1.1").unwrap();
+        let custom = channels.add("custom").unwrap();
+        let mut metadata = HashMap::new();
+        metadata.insert("device_id".to_string(), "xyz".to_string());
+        metadata.insert("region".to_string(), "eu".to_string());
+        custom.set_request_metadata(metadata.clone());
+        assert_eq!(custom.request_metadata(), metadata);
+        channels.find("custom").unwrap().subscribe("1.1").unwrap();
+
+        let payloads: Vec<serde_json::Value> = sent
+            .lock()
+            .iter()
+            .map(|data| serde_json::from_str(data).unwrap())
+            .collect();
+        assert_eq!(payloads[0]["metadata"], serde_json::json!({"device_id": "abc"}));
+        assert_eq!(
+            payloads[1]["metadata"],
+            serde_json::json!({"device_id": "xyz", "region": "eu"})
+        );
+
+        use crate::protocol::Protocol;
+        let event = Protocol::create_subscribe_event("bare", None, None, None).unwrap();
+        assert!(!Protocol::encode_message(&event).unwrap().contains("metadata"));
+    }
+
     #[test]
     fn test_presence_channel() {
         let channels = Channels::new();
@@ -256,4 +1007,51 @@ mod tests {
         channels.remove("test-channel");
         assert_eq!(channels.len(), 0);
     }
+
+    #[test]
+    fn test_with_capacity() {
+        let empty = Channels::with_capacity(0);
+        assert!(empty.is_empty());
+
+        let sized = Channels::with_capacity(4);
+        for i in 0..10 {
+            sized.add(&format!("channel-{}", i)).unwrap();
+        }
+        assert_eq!(sized.len(), 10);
+
+        sized.reserve(100);
+        assert_eq!(sized.len(), 10);
+        assert!(sized.find("channel-9").is_some());
+
+        empty.reserve(0);
+        empty.add("late").unwrap();
+        assert_eq!(empty.len(), 1);
+    }
+
+    #[test]
+    fn test_migrate() {
+        let channels = Channels::new();
+        let channel = channels.add("feed-a").unwrap();
+        channels.queue_pending_subscription("feed-a");
+        channels.add("presence-room").unwrap();
+
+        let migrated = channels.migrate("feed-a", "feed-b").unwrap();
+        assert!(Arc::ptr_eq(&migrated, &channel));
+        assert_eq!(channel.current_name(), "feed-b");
+        assert_eq!(channel.name(), "feed-b");
+        assert!(channels.find("feed-a").is_none());
+        assert_eq!(channels.pending_subscriptions(), vec!["feed-b".to_string()]);
+
+        assert!(matches!(
+            channels.migrate("feed-b", "presence-room"),
+            Err(SockudoError::ChannelAlreadyExists { .. })
+        ));
+        assert!(channels.migrate("feed-b", "private-feed").is_err());
+        assert!(matches!(
+            channels.migrate("presence-room", "presence-lobby"),
+            Err(SockudoError::InvalidChannel { .. })
+        ));
+        assert!(channels.migrate("missing", "feed-c").is_err());
+        assert_eq!(channels.len(), 2);
+    }
 }
@@ -2,15 +2,22 @@
 
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use parking_lot::RwLock;
+use std::collections::HashMap;
+#[cfg(any(test, feature = "offline-mode"))]
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::SystemTime;
 use tracing::{debug, error, warn};
 
 use super::channel::{
-    AuthorizeFn, Channel, ChannelAuthData, ChannelState, ChannelType, SendEventFn,
+    send_protocol_event, AuthorizeFn, Channel, ChannelAuthData, ChannelState, ChannelType,
+    SendEventFn,
 };
+use super::history::EventHistory;
+use super::state::ChannelStateCell;
 use crate::error::{Result, SockudoError};
 use crate::events::EventDispatcher;
-use crate::protocol::PusherEvent;
+use crate::protocol::{Protocol, PusherEvent};
 
 /// Size constants for NaCl secretbox
 const NONCE_LENGTH: usize = 24;
@@ -21,17 +28,28 @@ pub struct EncryptedChannel {
     /// Channel name
     name: String,
     /// Channel state (shared)
-    state: Arc<RwLock<ChannelState>>,
+    state: Arc<ChannelStateCell>,
     /// Event dispatcher
     dispatcher: EventDispatcher,
     /// Encryption key (from auth endpoint)
     key: RwLock<Option<[u8; KEY_LENGTH]>>,
+    /// Key set with `set_shared_secret`, used when the auth response has none
+    shared_secret: RwLock<Option<[u8; KEY_LENGTH]>>,
     /// Send event callback
     send_event: Option<SendEventFn>,
     /// Authorization callback
     authorize_fn: Option<AuthorizeFn>,
     /// Socket ID
     socket_id: RwLock<Option<String>>,
+    /// Metadata sent with the subscribe request (shared)
+    request_metadata: Arc<RwLock<HashMap<String, String>>>,
+    /// When the current subscription succeeded (shared)
+    subscribed_at: Arc<RwLock<Option<SystemTime>>>,
+    /// Most recent decrypted events, if enabled (shared)
+    event_history: Arc<RwLock<EventHistory>>,
+    /// Whether the owning `Channels` is in offline mode (shared)
+    #[cfg(any(test, feature = "offline-mode"))]
+    offline: Arc<AtomicBool>,
 }
 
 impl EncryptedChannel {
@@ -44,17 +62,50 @@ impl EncryptedChannel {
 
         Self {
             name: name.clone(),
-            state: Arc::new(RwLock::new(ChannelState::Unsubscribed)),
+            state: Arc::new(ChannelStateCell::new(ChannelState::Unsubscribed)),
             dispatcher: EventDispatcher::with_fail_through(move |event, _| {
                 debug!("No callbacks on {} for {}", name, event);
             }),
             key: RwLock::new(None),
+            shared_secret: RwLock::new(None),
             send_event: None,
             authorize_fn: None,
             socket_id: RwLock::new(None),
+            request_metadata: Arc::new(RwLock::new(HashMap::new())),
+            subscribed_at: Arc::new(RwLock::new(None)),
+            event_history: Arc::new(RwLock::new(EventHistory::default())),
+            #[cfg(any(test, feature = "offline-mode"))]
+            offline: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    /// Share the offline mode flag of the owning `Channels`
+    #[cfg(any(test, feature = "offline-mode"))]
+    pub(crate) fn share_offline_flag(&mut self, offline: Arc<AtomicBool>) {
+        self.offline = offline;
+    }
+
+    /// Set the 32-byte key used to decrypt events.
+    ///
+    /// A `shared_secret` returned by the auth endpoint takes precedence, but
+    /// with this set the auth response no longer needs to include one.
+    pub fn set_shared_secret(&self, secret: &[u8]) -> Result<()> {
+        let key: [u8; KEY_LENGTH] = secret.try_into().map_err(|_| {
+            SockudoError::encryption(format!(
+                "shared_secret must be {} bytes, got {}",
+                KEY_LENGTH,
+                secret.len()
+            ))
+        })?;
+        *self.shared_secret.write() = Some(key);
+        Ok(())
+    }
+
+    /// Key from the auth response, falling back to the configured secret
+    fn current_key(&self) -> Option<[u8; KEY_LENGTH]> {
+        (*self.key.read()).or(*self.shared_secret.read())
+    }
+
     /// Set the send event callback
     pub fn set_send_callback(&mut self, callback: SendEventFn) {
         self.send_event = Some(callback);
@@ -95,7 +146,7 @@ impl EncryptedChannel {
         &self,
         event_name: impl Into<String>,
         callback: impl Fn(&PusherEvent) + Send + Sync + 'static,
-    ) -> u64 {
+    ) -> Result<u64> {
         self.dispatcher.bind(event_name, callback)
     }
 
@@ -131,13 +182,16 @@ impl EncryptedChannel {
                 let mut key = [0u8; KEY_LENGTH];
                 key.copy_from_slice(&secret_bytes);
                 *self.key.write() = Some(key);
-            } else {
+            } else if self.shared_secret.read().is_none() {
                 return Err(SockudoError::encryption(
                     "No shared_secret in auth response for encrypted channel",
                 ));
             }
 
             Ok(auth_data)
+        } else if self.shared_secret.read().is_some() {
+            // The key is already known, so subscribe without a signature
+            Ok(unsigned_auth())
         } else {
             Err(SockudoError::authorization(
                 "No authorization callback configured",
@@ -154,22 +208,32 @@ impl EncryptedChannel {
         *self.state.write() = ChannelState::Subscribing;
         *self.socket_id.write() = Some(socket_id.to_string());
 
+        // Offline there is no server to authorize against
+        #[cfg(any(test, feature = "offline-mode"))]
+        if self.offline.load(Ordering::SeqCst) {
+            self.handle_event(
+                &PusherEvent::new("pusher_internal:subscription_succeeded")
+                    .with_channel(&self.name)
+                    .with_json_data(serde_json::json!({})),
+            );
+            return Ok(());
+        }
+
         // Authorize (this will set the encryption key)
         let auth_data = self.authorize(socket_id)?;
 
         // Build subscription data (don't send shared_secret to server)
-        let sub_data = serde_json::json!({
-            "channel": self.name,
-            "auth": auth_data.auth,
-            "channel_data": auth_data.channel_data,
-        });
+        let event = Protocol::create_subscribe_event_with_metadata(
+            &self.name,
+            Some(auth_data.auth.as_str()).filter(|auth| !auth.is_empty()),
+            auth_data.channel_data.as_deref(),
+            None,
+            &self.request_metadata.read(),
+        )?;
 
         // Send subscribe event
         if let Some(ref send) = self.send_event {
-            #[cfg(feature = "wasm")]
-            send("pusher:subscribe", &sub_data, None);
-            #[cfg(not(feature = "wasm"))]
-            send("pusher:subscribe", &sub_data.to_string(), None);
+            send_protocol_event(send, &event);
         }
 
         Ok(())
@@ -182,23 +246,21 @@ impl EncryptedChannel {
         }
 
         *self.state.write() = ChannelState::Unsubscribed;
+        *self.subscribed_at.write() = None;
         *self.key.write() = None;
 
-        let data = serde_json::json!({
-            "channel": self.name,
-        });
-
         if let Some(ref send) = self.send_event {
-            #[cfg(feature = "wasm")]
-            send("pusher:unsubscribe", &data, None);
-            #[cfg(not(feature = "wasm"))]
-            send("pusher:unsubscribe", &data.to_string(), None);
+            send_protocol_event(send, &Protocol::create_unsubscribe_event(&self.name));
         }
+
+        self.dispatcher
+            .emit(&PusherEvent::new("pusher:unsubscription_succeeded").with_channel(&self.name));
     }
 
     /// Handle disconnection
     pub fn disconnect(&self) {
         *self.state.write() = ChannelState::Unsubscribed;
+        *self.subscribed_at.write() = None;
         // Keep the key for reconnection
     }
 
@@ -235,6 +297,7 @@ impl EncryptedChannel {
         match event.event.as_str() {
             "pusher_internal:subscription_succeeded" => {
                 *self.state.write() = ChannelState::Subscribed;
+                *self.subscribed_at.write() = Some(SystemTime::now());
 
                 let mut success_event = event.clone();
                 success_event.event = "pusher:subscription_succeeded".to_string();
@@ -251,134 +314,55 @@ impl EncryptedChannel {
 
     /// Handle encrypted events
     fn handle_encrypted_event(&self, event: &PusherEvent) {
-        let key = match *self.key.read() {
-            Some(k) => k,
-            None => {
-                warn!("Received encrypted event before key was retrieved");
-                return;
-            }
-        };
-
-        let data = match &event.data {
-            Some(d) => d,
-            None => {
-                error!("Encrypted event has no data");
-                return;
-            }
-        };
-
-        // Parse data as JSON and extract ciphertext and nonce
-        #[cfg(feature = "wasm")]
-        let (ciphertext_b64, nonce_b64) = {
-            let ciphertext = match data.get("ciphertext").and_then(|v| v.as_str()) {
-                Some(c) => c,
-                None => {
-                    error!("Encrypted event missing ciphertext");
-                    return;
-                }
-            };
-
-            let nonce = match data.get("nonce").and_then(|v| v.as_str()) {
-                Some(n) => n,
-                None => {
-                    error!("Encrypted event missing nonce");
-                    return;
-                }
-            };
-
-            (ciphertext, nonce)
-        };
-
-        #[cfg(not(feature = "wasm"))]
-        let (ciphertext_b64, nonce_b64) = {
-            let parsed: serde_json::Value = match serde_json::from_str(data) {
-                Ok(v) => v,
-                Err(e) => {
-                    error!("Failed to parse encrypted event data: {}", e);
-                    return;
-                }
-            };
-
-            let ciphertext = match parsed.get("ciphertext").and_then(|v| v.as_str()) {
-                Some(c) => c.to_string(),
-                None => {
-                    error!("Encrypted event missing ciphertext");
-                    return;
-                }
-            };
-
-            let nonce = match parsed.get("nonce").and_then(|v| v.as_str()) {
-                Some(n) => n.to_string(),
-                None => {
-                    error!("Encrypted event missing nonce");
-                    return;
-                }
-            };
-
-            (ciphertext, nonce)
-        };
-
-        // Decode from base64
-        let ciphertext = match BASE64.decode(ciphertext_b64) {
-            Ok(c) => c,
-            Err(e) => {
-                error!("Failed to decode ciphertext: {}", e);
-                return;
-            }
-        };
-
-        let nonce_bytes = match BASE64.decode(nonce_b64) {
-            Ok(n) => n,
-            Err(e) => {
-                error!("Failed to decode nonce: {}", e);
-                return;
-            }
-        };
-
-        if nonce_bytes.len() != NONCE_LENGTH {
-            error!(
-                "Invalid nonce length: {} (expected {})",
-                nonce_bytes.len(),
-                NONCE_LENGTH
-            );
+        if self.current_key().is_none() {
+            warn!("Received encrypted event before key was retrieved");
             return;
         }
 
-        // Decrypt using NaCl secretbox
-        let plaintext = match decrypt_secretbox(&ciphertext, &nonce_bytes, &key) {
-            Ok(p) => p,
-            Err(e) => {
+        let result = match self.decrypt_event(event) {
+            Err(SockudoError::DecryptionFailed { .. }) => {
                 warn!(
-                    "Failed to decrypt event: {}. Attempting to refresh key...",
-                    e
+                    "Failed to decrypt event on {}. Attempting to refresh key...",
+                    self.name
                 );
 
-                // Try to get a new key
-                if let Some(ref socket_id) = *self.socket_id.read() {
-                    if let Ok(_) = self.authorize(socket_id) {
-                        // Try decryption again with new key
-                        let new_key = match *self.key.read() {
-                            Some(k) => k,
-                            None => return,
-                        };
-
-                        match decrypt_secretbox(&ciphertext, &nonce_bytes, &new_key) {
-                            Ok(p) => p,
-                            Err(_) => {
-                                error!("Failed to decrypt event even with new key");
-                                return;
-                            }
-                        }
-                    } else {
+                // Try again with a fresh key from the auth endpoint
+                let socket_id = self.socket_id.read().clone();
+                match socket_id.map(|socket_id| self.authorize(&socket_id)) {
+                    Some(Ok(_)) => self.decrypt_event(event),
+                    _ => {
                         error!("Failed to refresh encryption key");
                         return;
                     }
-                } else {
-                    return;
                 }
             }
+            result => result,
         };
 
+        match result {
+            Ok(decrypted_event) => {
+                self.dispatcher.emit(&decrypted_event);
+                self.event_history.write().record(&decrypted_event);
+            }
+            Err(e) => error!("Dropping encrypted event on {}: {}", self.name, e),
+        }
+    }
+
+    /// Decrypt an event whose data holds base64 `ciphertext` and `nonce`
+    /// fields, returning the event with its plaintext data.
+    ///
+    /// Fails with `SockudoError::DecryptionFailed` if the message does not
+    /// authenticate with the channel's key.
+    pub fn decrypt_event(&self, event: &PusherEvent) -> Result<PusherEvent> {
+        let key = self
+            .current_key()
+            .ok_or_else(|| SockudoError::encryption("No key for encrypted channel"))?;
+        let (ciphertext, nonce) = encrypted_payload(event)?;
+
+        // Decrypt using NaCl secretbox
+        let plaintext = decrypt_secretbox(&ciphertext, &nonce, &key)
+            .map_err(|_| SockudoError::decryption_failed(&self.name))?;
+
         // Parse decrypted data
         let decrypted_data = match String::from_utf8(plaintext) {
             Ok(s) => {
@@ -386,12 +370,12 @@ impl EncryptedChannel {
                 serde_json::from_str(&s).unwrap_or(serde_json::Value::String(s))
             }
             Err(_) => {
-                error!("Decrypted data is not valid UTF-8");
-                return;
+                return Err(SockudoError::encryption(
+                    "Decrypted data is not valid UTF-8",
+                ));
             }
         };
 
-        // Emit decrypted event
         let mut decrypted_event = PusherEvent::new(&event.event);
         decrypted_event.channel = event.channel.clone();
 
@@ -406,14 +390,16 @@ impl EncryptedChannel {
 
         decrypted_event.user_id = event.user_id.clone();
 
-        self.dispatcher.emit(&decrypted_event);
+        Ok(decrypted_event)
     }
 
     /// Get as base Channel reference
     pub fn as_channel(&self) -> Arc<Channel> {
         // Create a channel that shares the same dispatcher and state
         let mut channel =
-            Channel::with_dispatcher(&self.name, self.dispatcher.clone(), self.state.clone());
+            Channel::with_state_cell(&self.name, self.dispatcher.clone(), self.state.clone());
+        #[cfg(any(test, feature = "offline-mode"))]
+        channel.share_offline_flag(self.offline.clone());
 
         // Copy callbacks from encrypted channel
         if let Some(ref send_cb) = self.send_event {
@@ -421,12 +407,68 @@ impl EncryptedChannel {
         }
         if let Some(ref auth_cb) = self.authorize_fn {
             channel.set_authorize_callback(auth_cb.clone());
+        } else if self.shared_secret.read().is_some() {
+            channel.set_authorize_callback(Arc::new(|_, _| Ok(unsigned_auth())));
         }
+        channel.share_request_metadata(self.request_metadata.clone());
+        channel.share_subscribed_at(self.subscribed_at.clone());
+        channel.share_event_history(self.event_history.clone());
 
         Arc::new(channel)
     }
 }
 
+/// Auth data for subscribing with a preset shared secret and no authorizer
+fn unsigned_auth() -> ChannelAuthData {
+    ChannelAuthData {
+        auth: String::new(),
+        channel_data: None,
+        shared_secret: None,
+    }
+}
+
+/// Extract and decode the ciphertext and nonce from an encrypted event
+fn encrypted_payload(event: &PusherEvent) -> Result<(Vec<u8>, Vec<u8>)> {
+    #[cfg(feature = "wasm")]
+    let parsed = event
+        .data
+        .clone()
+        .ok_or_else(|| SockudoError::encryption("Encrypted event has no data"))?;
+
+    #[cfg(not(feature = "wasm"))]
+    let parsed: serde_json::Value = {
+        let data = event
+            .data
+            .as_deref()
+            .ok_or_else(|| SockudoError::encryption("Encrypted event has no data"))?;
+        serde_json::from_str(data).map_err(|e| {
+            SockudoError::encryption(format!("Failed to parse encrypted event data: {}", e))
+        })?
+    };
+
+    let field = |name: &str| -> Result<Vec<u8>> {
+        let value = parsed
+            .get(name)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| SockudoError::encryption(format!("Encrypted event missing {}", name)))?;
+        BASE64
+            .decode(value)
+            .map_err(|e| SockudoError::encryption(format!("Failed to decode {}: {}", name, e)))
+    };
+
+    let ciphertext = field("ciphertext")?;
+    let nonce = field("nonce")?;
+    if nonce.len() != NONCE_LENGTH {
+        return Err(SockudoError::encryption(format!(
+            "Invalid nonce length: {} (expected {})",
+            nonce.len(),
+            NONCE_LENGTH
+        )));
+    }
+
+    Ok((ciphertext, nonce))
+}
+
 /// Decrypt using NaCl secretbox (XSalsa20-Poly1305)
 fn decrypt_secretbox(ciphertext: &[u8], nonce: &[u8], key: &[u8; KEY_LENGTH]) -> Result<Vec<u8>> {
     use nacl::aead::generic_array::GenericArray;
@@ -447,7 +489,7 @@ impl std::fmt::Debug for EncryptedChannel {
         f.debug_struct("EncryptedChannel")
             .field("name", &self.name)
             .field("state", &*self.state.read())
-            .field("has_key", &self.key.read().is_some())
+            .field("has_key", &self.current_key().is_some())
             .finish()
     }
 }
@@ -478,4 +520,98 @@ mod tests {
         let result = channel.trigger("client-event", serde_json::json!({}).to_string());
         assert!(result.is_err());
     }
+
+    // Known-answer vector from the NaCl `tests/secretbox.c` suite
+    const NACL_KEY: [u8; 32] = [
+        0x1b, 0x27, 0x55, 0x64, 0x73, 0xe9, 0x85, 0xd4, 0x62, 0xcd, 0x51, 0x19, 0x7a, 0x9a, 0x46,
+        0xc7, 0x60, 0x09, 0x54, 0x9e, 0xac, 0x64, 0x74, 0xf2, 0x06, 0xc4, 0xee, 0x08, 0x44, 0xf6,
+        0x83, 0x89,
+    ];
+    const NACL_NONCE: [u8; 24] = [
+        0x69, 0x69, 0x6e, 0xe9, 0x55, 0xb6, 0x2b, 0x73, 0xcd, 0x62, 0xbd, 0xa8, 0x75, 0xfc, 0x73,
+        0xd6, 0x82, 0x19, 0xe0, 0x03, 0x6b, 0x7a, 0x0b, 0x37,
+    ];
+    const NACL_PLAINTEXT: [u8; 131] = [
+        0xbe, 0x07, 0x5f, 0xc5, 0x3c, 0x81, 0xf2, 0xd5, 0xcf, 0x14, 0x13, 0x16, 0xeb, 0xeb, 0x0c,
+        0x7b, 0x52, 0x28, 0xc5, 0x2a, 0x4c, 0x62, 0xcb, 0xd4, 0x4b, 0x66, 0x84, 0x9b, 0x64, 0x24,
+        0x4f, 0xfc, 0xe5, 0xec, 0xba, 0xaf, 0x33, 0xbd, 0x75, 0x1a, 0x1a, 0xc7, 0x28, 0xd4, 0x5e,
+        0x6c, 0x61, 0x29, 0x6c, 0xdc, 0x3c, 0x01, 0x23, 0x35, 0x61, 0xf4, 0x1d, 0xb6, 0x6c, 0xce,
+        0x31, 0x4a, 0xdb, 0x31, 0x0e, 0x3b, 0xe8, 0x25, 0x0c, 0x46, 0xf0, 0x6d, 0xce, 0xea, 0x3a,
+        0x7f, 0xa1, 0x34, 0x80, 0x57, 0xe2, 0xf6, 0x55, 0x6a, 0xd6, 0xb1, 0x31, 0x8a, 0x02, 0x4a,
+        0x83, 0x8f, 0x21, 0xaf, 0x1f, 0xde, 0x04, 0x89, 0x77, 0xeb, 0x48, 0xf5, 0x9f, 0xfd, 0x49,
+        0x24, 0xca, 0x1c, 0x60, 0x90, 0x2e, 0x52, 0xf0, 0xa0, 0x89, 0xbc, 0x76, 0x89, 0x70, 0x40,
+        0xe0, 0x82, 0xf9, 0x37, 0x76, 0x38, 0x48, 0x64, 0x5e, 0x07, 0x05,
+    ];
+    const NACL_BOX: [u8; 147] = [
+        0xf3, 0xff, 0xc7, 0x70, 0x3f, 0x94, 0x00, 0xe5, 0x2a, 0x7d, 0xfb, 0x4b, 0x3d, 0x33, 0x05,
+        0xd9, 0x8e, 0x99, 0x3b, 0x9f, 0x48, 0x68, 0x12, 0x73, 0xc2, 0x96, 0x50, 0xba, 0x32, 0xfc,
+        0x76, 0xce, 0x48, 0x33, 0x2e, 0xa7, 0x16, 0x4d, 0x96, 0xa4, 0x47, 0x6f, 0xb8, 0xc5, 0x31,
+        0xa1, 0x18, 0x6a, 0xc0, 0xdf, 0xc1, 0x7c, 0x98, 0xdc, 0xe8, 0x7b, 0x4d, 0xa7, 0xf0, 0x11,
+        0xec, 0x48, 0xc9, 0x72, 0x71, 0xd2, 0xc2, 0x0f, 0x9b, 0x92, 0x8f, 0xe2, 0x27, 0x0d, 0x6f,
+        0xb8, 0x63, 0xd5, 0x17, 0x38, 0xb4, 0x8e, 0xee, 0xe3, 0x14, 0xa7, 0xcc, 0x8a, 0xb9, 0x32,
+        0x16, 0x45, 0x48, 0xe5, 0x26, 0xae, 0x90, 0x22, 0x43, 0x68, 0x51, 0x7a, 0xcf, 0xea, 0xbd,
+        0x6b, 0xb3, 0x73, 0x2b, 0xc0, 0xe9, 0xda, 0x99, 0x83, 0x2b, 0x61, 0xca, 0x01, 0xb6, 0xde,
+        0x56, 0x24, 0x4a, 0x9e, 0x88, 0xd5, 0xf9, 0xb3, 0x79, 0x73, 0xf6, 0x22, 0xa4, 0x3d, 0x14,
+        0xa6, 0x59, 0x9b, 0x1f, 0x65, 0x4c, 0xb4, 0x5a, 0x74, 0xe3, 0x55, 0xa5,
+    ];
+
+    #[test]
+    fn test_secretbox_known_answer() {
+        let plaintext = decrypt_secretbox(&NACL_BOX, &NACL_NONCE, &NACL_KEY).unwrap();
+        assert_eq!(plaintext, NACL_PLAINTEXT);
+
+        let mut tampered = NACL_BOX;
+        tampered[20] ^= 1;
+        assert!(decrypt_secretbox(&tampered, &NACL_NONCE, &NACL_KEY).is_err());
+    }
+
+    /// Message `{"message":"hello world"}` sealed with a key of 32 `0x07`
+    /// bytes and nonce `00 01 .. 17`
+    const TEST_CIPHERTEXT: &str = "O85sjdf1Oii1CbPgWqCBx+E8hCr4BgOigLJ5KVmzLfua1CWYQIMYmKw=";
+    const TEST_NONCE: &str = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYX";
+
+    fn encrypted_event(channel: &str) -> PusherEvent {
+        let data = serde_json::json!({ "ciphertext": TEST_CIPHERTEXT, "nonce": TEST_NONCE });
+        let mut event = PusherEvent::new("secret-message").with_channel(channel);
+        #[cfg(feature = "wasm")]
+        {
+            event.data = Some(data);
+        }
+        #[cfg(not(feature = "wasm"))]
+        {
+            event.data = Some(data.to_string());
+        }
+        event
+    }
+
+    #[test]
+    fn test_decrypt_with_shared_secret() {
+        let channel = EncryptedChannel::new("private-encrypted-presence-room");
+        assert!(channel.set_shared_secret(&[7u8; 16]).is_err());
+        channel.set_shared_secret(&[7u8; 32]).unwrap();
+
+        let received = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let sink = received.clone();
+        channel
+            .bind("secret-message", move |event| {
+                sink.lock().push(event.data.clone())
+            })
+            .unwrap();
+        channel.handle_event(&encrypted_event("private-encrypted-presence-room"));
+
+        #[cfg(feature = "wasm")]
+        let expected = serde_json::json!({ "message": "hello world" });
+        #[cfg(not(feature = "wasm"))]
+        let expected = serde_json::json!({ "message": "hello world" }).to_string();
+        assert_eq!(*received.lock(), vec![Some(expected)]);
+
+        let wrong_key = EncryptedChannel::new("private-encrypted-test");
+        wrong_key.set_shared_secret(&[8u8; 32]).unwrap();
+        match wrong_key.decrypt_event(&encrypted_event("private-encrypted-test")) {
+            Err(SockudoError::DecryptionFailed { channel }) => {
+                assert_eq!(channel, "private-encrypted-test");
+            }
+            other => panic!("expected DecryptionFailed, got {:?}", other),
+        }
+    }
 }
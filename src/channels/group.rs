@@ -0,0 +1,189 @@
+//! Batch operations on a family of related channels.
+
+use std::sync::Arc;
+
+use super::channel::Channel;
+use super::channels::Channels;
+use crate::error::Result;
+use crate::protocol::PusherEvent;
+
+/// Snapshot of the subscribed channels whose names share a prefix.
+///
+/// Channels subscribed after the group was created are not added to it;
+/// call `SockudoClient::channel_group` again for a fresh group.
+#[derive(Debug, Clone, Default)]
+pub struct ChannelGroup {
+    channels: Vec<Arc<Channel>>,
+}
+
+impl ChannelGroup {
+    /// Group the given channels
+    pub fn new(channels: Vec<Arc<Channel>>) -> Self {
+        Self { channels }
+    }
+
+    /// Group the subscribed channels of `channels` whose name starts with
+    /// `prefix`
+    pub(crate) fn from_prefix(channels: &Channels, prefix: &str) -> Self {
+        let mut channels: Vec<_> = channels
+            .find_by_prefix(prefix)
+            .into_iter()
+            .filter(|channel| channel.is_subscribed())
+            .collect();
+        channels.sort_by_key(|channel| channel.current_name());
+        Self::new(channels)
+    }
+
+    /// Bind `callback` to `event_name` on every channel, returning the
+    /// callback ID of each binding in channel order.
+    ///
+    /// If any binding fails, the ones already made are removed.
+    pub fn bind(
+        &self,
+        event_name: &str,
+        callback: impl Fn(&PusherEvent) + Send + Sync + 'static,
+    ) -> Result<Vec<u64>> {
+        let callback = Arc::new(callback);
+        let mut ids = Vec::with_capacity(self.channels.len());
+        for channel in &self.channels {
+            let callback = callback.clone();
+            match channel.bind(event_name, move |event| callback(event)) {
+                Ok(id) => ids.push(id),
+                Err(e) => {
+                    for (channel, id) in self.channels.iter().zip(&ids) {
+                        channel.unbind(Some(event_name), Some(*id));
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Remove every callback bound to the channels
+    pub fn unbind_all(&self) {
+        for channel in &self.channels {
+            channel.unbind_all();
+        }
+    }
+
+    /// Trigger a client event on every channel, returning one result per
+    /// channel
+    pub fn trigger(&self, event_name: &str, data: serde_json::Value) -> Vec<Result<bool>> {
+        self.channels
+            .iter()
+            .map(|channel| {
+                #[cfg(feature = "wasm")]
+                {
+                    channel.trigger(event_name, data.clone())
+                }
+                #[cfg(not(feature = "wasm"))]
+                {
+                    channel.trigger_value(event_name, data.clone())
+                }
+            })
+            .collect()
+    }
+
+    /// Channels in the group, sorted by name
+    pub fn channels(&self) -> &[Arc<Channel>] {
+        &self.channels
+    }
+
+    /// Names of the channels in the group
+    pub fn names(&self) -> Vec<String> {
+        self.channels
+            .iter()
+            .map(|channel| channel.current_name())
+            .collect()
+    }
+
+    /// Number of channels in the group
+    pub fn len(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Whether the group has no channels
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+}
+
+#[cfg(all(test, not(feature = "wasm")))]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_channel_group_prefix_snapshot() {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let sent_clone = sent.clone();
+        let mut channels = Channels::new();
+        channels.set_send_callback(Arc::new(move |event, data, channel| {
+            sent_clone.lock().push((
+                event.to_string(),
+                data.to_string(),
+                channel.map(String::from),
+            ));
+            true
+        }));
+
+        let subscribed = PusherEvent::new("pusher_internal:subscription_succeeded");
+        for name in [
+            "private-room-1",
+            "private-room-2",
+            "private-room-3",
+            "private-lobby",
+            "private-hall",
+        ] {
+            channels.add(name).unwrap().handle_event(&subscribed);
+        }
+        // Pending channels are left out
+        channels.add("private-room-pending").unwrap();
+
+        let group = ChannelGroup::from_prefix(&channels, "private-room-");
+        assert_eq!(group.len(), 3);
+        assert_eq!(
+            group.names(),
+            vec!["private-room-1", "private-room-2", "private-room-3"]
+        );
+
+        // Later subscriptions do not change the snapshot
+        channels
+            .add("private-room-4")
+            .unwrap()
+            .handle_event(&subscribed);
+        assert_eq!(group.len(), 3);
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let calls_clone = calls.clone();
+        let ids = group
+            .bind("update", move |_| {
+                calls_clone.fetch_add(1, Ordering::SeqCst);
+            })
+            .unwrap();
+        assert_eq!(ids.len(), 3);
+        for channel in channels.all() {
+            channel.handle_event(&PusherEvent::new("update").with_channel(channel.current_name()));
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let results = group.trigger("client-typing", serde_json::json!({"user": "ana"}));
+        assert!(results.iter().all(|r| matches!(r, Ok(true))));
+        let mut targets: Vec<_> = sent
+            .lock()
+            .iter()
+            .filter(|(event, _, _)| event == "client-typing")
+            .filter_map(|(_, _, channel)| channel.clone())
+            .collect();
+        targets.sort();
+        assert_eq!(targets, group.names());
+
+        group.unbind_all();
+        for channel in group.channels() {
+            channel.handle_event(&PusherEvent::new("update").with_channel(channel.current_name()));
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,64 @@
+//! Opt-in buffer of the most recent events a channel received.
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
+
+use crate::protocol::PusherEvent;
+
+/// The last `capacity` events of each event name, in arrival order.
+///
+/// A capacity of 0 (the default) records nothing.
+#[derive(Debug, Default)]
+pub(crate) struct EventHistory {
+    capacity: usize,
+    /// Arrival number of the next recorded event, used to order events
+    /// of different names
+    next_seq: u64,
+    events: HashMap<String, VecDeque<(u64, PusherEvent)>>,
+}
+
+impl EventHistory {
+    /// Number of events kept per event name
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Keep `capacity` events per event name, dropping the oldest ones
+    /// beyond it
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.events.retain(|_, events| {
+            let excess = events.len().saturating_sub(capacity);
+            events.drain(..excess);
+            !events.is_empty()
+        });
+    }
+
+    /// Append `event`, evicting the oldest event of the same name when full
+    pub fn record(&mut self, event: &PusherEvent) {
+        if self.capacity == 0 {
+            return;
+        }
+        let events = self.events.entry(event.event.clone()).or_default();
+        if events.len() == self.capacity {
+            events.pop_front();
+        }
+        events.push_back((self.next_seq, event.clone()));
+        self.next_seq += 1;
+    }
+
+    /// Up to `limit` recorded events, newest first, of `event_name` or of
+    /// every name when `None`
+    pub fn newest(&self, event_name: Option<&str>, limit: usize) -> Vec<PusherEvent> {
+        let mut events: Vec<_> = match event_name {
+            Some(name) => self.events.get(name).into_iter().flatten().collect(),
+            None => self.events.values().flatten().collect(),
+        };
+        events.sort_unstable_by_key(|(seq, _)| Reverse(*seq));
+        events
+            .into_iter()
+            .take(limit)
+            .map(|(_, event)| event.clone())
+            .collect()
+    }
+}
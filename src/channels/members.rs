@@ -51,6 +51,110 @@ impl MemberInfo {
         }
         self
     }
+
+    /// Get the user info as a JSON value
+    pub fn user_info_value(&self) -> Option<Value> {
+        #[cfg(feature = "wasm")]
+        {
+            self.user_info.clone()
+        }
+        #[cfg(not(feature = "wasm"))]
+        {
+            self.user_info
+                .as_deref()
+                .and_then(|info| serde_json::from_str(info).ok())
+        }
+    }
+}
+
+/// Batch of member changes, applied with `Members::apply_patch`.
+///
+/// Removals are applied first, then additions, then updates, so a member
+/// can be removed and re-added, or added and updated, in one patch.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MembersPatch {
+    /// Members that joined
+    #[serde(default)]
+    pub added: Vec<MemberInfo>,
+    /// User IDs of members that left
+    #[serde(default)]
+    pub removed: Vec<String>,
+    /// Members whose user info changed
+    #[serde(default)]
+    pub updated: Vec<MemberInfo>,
+}
+
+impl MembersPatch {
+    /// Parse a patch as sent in `pusher_internal:presence_patch`, where
+    /// `user_info` is a JSON value
+    pub fn from_value(data: &Value) -> Option<Self> {
+        let members = |key: &str| -> Option<Vec<MemberInfo>> {
+            match data.get(key) {
+                Some(list) => list.as_array()?.iter().map(member_from_value).collect(),
+                None => Some(Vec::new()),
+            }
+        };
+        let removed = match data.get("removed") {
+            Some(list) => list
+                .as_array()?
+                .iter()
+                .map(|id| id.as_str().map(String::from))
+                .collect::<Option<_>>()?,
+            None => Vec::new(),
+        };
+
+        Some(Self {
+            added: members("added")?,
+            removed,
+            updated: members("updated")?,
+        })
+    }
+
+    /// Whether the patch changes nothing
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.updated.is_empty()
+    }
+}
+
+/// Changes actually made by `Members::apply_patch`
+#[derive(Debug, Clone, Default)]
+pub struct AppliedPatch {
+    /// Members added
+    pub added: Vec<MemberInfo>,
+    /// Members removed, with their last known info
+    pub removed: Vec<MemberInfo>,
+    /// Members updated, as (previous, current)
+    pub updated: Vec<(MemberInfo, MemberInfo)>,
+}
+
+/// Copy of the members of a presence channel at one point in time
+#[derive(Debug, Clone, Default)]
+pub struct MemberSnapshot {
+    /// Members by user ID
+    pub members: HashMap<String, MemberInfo>,
+}
+
+impl MemberSnapshot {
+    /// Number of members in the snapshot
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Whether the snapshot has no members
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+}
+
+impl FromIterator<MemberInfo> for MemberSnapshot {
+    fn from_iter<I: IntoIterator<Item = MemberInfo>>(iter: I) -> Self {
+        Self {
+            members: iter
+                .into_iter()
+                .map(|member| (member.user_id.clone(), member))
+                .collect(),
+        }
+    }
 }
 
 /// Manages members of a presence channel
@@ -95,11 +199,80 @@ impl Members {
         self.members.read().get(user_id).cloned()
     }
 
+    /// Current user's ID and all members sorted by user ID, read together
+    pub(crate) fn snapshot_with_my_id(&self) -> (Option<String>, Vec<MemberInfo>) {
+        // Same lock order as `me`
+        let my_id = self.my_id.read();
+        let members = self.members.read();
+        let mut all: Vec<MemberInfo> = members.values().cloned().collect();
+        all.sort_by(|a, b| a.user_id.cmp(&b.user_id));
+        (my_id.clone(), all)
+    }
+
     /// Get all members
     pub fn all(&self) -> Vec<MemberInfo> {
         self.members.read().values().cloned().collect()
     }
 
+    /// Get all members sorted by a `user_info` field, ties broken by user ID.
+    ///
+    /// Values are compared as strings: string fields by their content and
+    /// anything else by its JSON text, so numbers sort lexicographically
+    /// (`"10"` before `"9"`). Members without the field come last. Use
+    /// `sorted_by_key` for numeric or descending order.
+    pub fn sorted_by(&self, field: &str) -> Vec<MemberInfo> {
+        self.sorted_by_key(|member| {
+            let value = member
+                .user_info_value()
+                .and_then(|info| info.get(field).map(sort_text));
+            (value.is_none(), value)
+        })
+    }
+
+    /// Get all members sorted alphabetically by user ID
+    pub fn sorted_by_id(&self) -> Vec<MemberInfo> {
+        self.sorted_by_key(|member| member.user_id.clone())
+    }
+
+    /// Get all members sorted by a custom key, ties broken by user ID
+    pub fn sorted_by_key<K: Ord>(&self, key_fn: impl Fn(&MemberInfo) -> K) -> Vec<MemberInfo> {
+        let mut members = self.all();
+        members.sort_by_cached_key(|member| (key_fn(member), member.user_id.clone()));
+        members
+    }
+
+    /// Map each member whose `user_info` has a string `field` to its value
+    pub fn by_info_field(&self, field: &str) -> HashMap<String, String> {
+        self.members
+            .read()
+            .values()
+            .filter_map(|member| {
+                let info = member.user_info_value()?;
+                let value = info.get(field)?.as_str()?;
+                Some((member.user_id.clone(), value.to_string()))
+            })
+            .collect()
+    }
+
+    /// Get the members matching `predicate`
+    pub fn filter(&self, predicate: impl Fn(&MemberInfo) -> bool) -> Vec<MemberInfo> {
+        self.members
+            .read()
+            .values()
+            .filter(|member| predicate(member))
+            .cloned()
+            .collect()
+    }
+
+    /// Get the members whose `user_info` has string `field` equal to `value`
+    pub fn with_info_field(&self, field: &str, value: &str) -> Vec<MemberInfo> {
+        self.filter(|member| {
+            member
+                .user_info_value()
+                .is_some_and(|info| info.get(field).and_then(Value::as_str) == Some(value))
+        })
+    }
+
     /// Get member count
     pub fn count(&self) -> usize {
         self.members.read().len()
@@ -123,6 +296,95 @@ impl Members {
         self.members.write().remove(user_id)
     }
 
+    /// Replace a member's user info, returning `true` if the member was found
+    pub fn update_info(&self, user_id: &str, new_info: Value) -> bool {
+        self.replace_info(user_id, new_info).is_some()
+    }
+
+    /// Replace a member's user info, returning the previous member state
+    fn replace_info(&self, user_id: &str, new_info: Value) -> Option<MemberInfo> {
+        let mut members = self.members.write();
+        let member = members.get_mut(user_id)?;
+        let previous = member.clone();
+
+        #[cfg(feature = "wasm")]
+        {
+            member.user_info = Some(new_info);
+        }
+        #[cfg(not(feature = "wasm"))]
+        {
+            member.user_info = Some(new_info.to_string());
+        }
+
+        Some(previous)
+    }
+
+    /// Copy the current members
+    pub fn snapshot(&self) -> MemberSnapshot {
+        MemberSnapshot {
+            members: self.members.read().clone(),
+        }
+    }
+
+    /// Apply all changes in `patch` at once, returning the changes made.
+    ///
+    /// Other readers see either none or all of the patch. Removing an
+    /// unknown member, adding an existing one or updating an unknown one is
+    /// skipped, as with `remove`, `add` and `update_info`.
+    pub fn apply_patch(&self, patch: MembersPatch) -> AppliedPatch {
+        let mut applied = AppliedPatch::default();
+        let mut members = self.members.write();
+
+        for user_id in patch.removed {
+            if let Some(member) = members.remove(&user_id) {
+                applied.removed.push(member);
+            }
+        }
+        for member in patch.added {
+            if !members.contains_key(&member.user_id) {
+                members.insert(member.user_id.clone(), member.clone());
+                applied.added.push(member);
+            }
+        }
+        for update in patch.updated {
+            if let Some(member) = members.get_mut(&update.user_id) {
+                let previous = member.clone();
+                member.user_info = update.user_info;
+                applied.updated.push((previous, member.clone()));
+            }
+        }
+
+        applied
+    }
+
+    /// Compute the patch that turns `old` into `new`.
+    ///
+    /// Members are compared by their parsed `user_info`, so formatting
+    /// differences in the JSON text are not reported as updates.
+    pub fn diff_to_patch(old: &MemberSnapshot, new: &MemberSnapshot) -> MembersPatch {
+        let mut patch = MembersPatch::default();
+        for (user_id, member) in &new.members {
+            match old.members.get(user_id) {
+                None => patch.added.push(member.clone()),
+                Some(previous) if previous.user_info_value() != member.user_info_value() => {
+                    patch.updated.push(member.clone())
+                }
+                Some(_) => {}
+            }
+        }
+        patch.removed = old
+            .members
+            .keys()
+            .filter(|user_id| !new.members.contains_key(*user_id))
+            .cloned()
+            .collect();
+
+        patch.added.sort_by(|a, b| a.user_id.cmp(&b.user_id));
+        patch.updated.sort_by(|a, b| a.user_id.cmp(&b.user_id));
+        patch.removed.sort();
+        patch
+    }
+
     /// Initialize from subscription data
     pub fn on_subscription(&self, data: &Value) {
         let mut members = self.members.write();
@@ -154,18 +416,7 @@ impl Members {
 
     /// Handle member added event
     pub fn add_member(&self, data: &Value) -> Option<MemberInfo> {
-        let user_id = data.get("user_id")?.as_str()?;
-        #[cfg(feature = "wasm")]
-        let user_info = data.get("user_info").cloned();
-        #[cfg(not(feature = "wasm"))]
-        let user_info = data.get("user_info").map(|v| v.to_string());
-
-        let member = MemberInfo {
-            user_id: user_id.to_string(),
-            user_info,
-        };
-
-        self.add(member)
+        self.add(member_from_value(data)?)
     }
 
     /// Handle member removed event
@@ -174,6 +425,16 @@ impl Members {
         self.remove(user_id)
     }
 
+    /// Handle member updated event, returning the old and new member info
+    pub fn update_member(&self, data: &Value) -> Option<(MemberInfo, MemberInfo)> {
+        let user_id = data.get("user_id")?.as_str()?;
+        let new_info = data.get("user_info").cloned().unwrap_or(Value::Null);
+
+        let previous = self.replace_info(user_id, new_info)?;
+        let current = self.get(user_id)?;
+        Some((previous, current))
+    }
+
     /// Reset members
     pub fn reset(&self) {
         self.members.write().clear();
@@ -197,6 +458,28 @@ impl Default for Members {
     }
 }
 
+/// Parse `{"user_id": ..., "user_info": ...}` as sent by the server
+fn member_from_value(data: &Value) -> Option<MemberInfo> {
+    let user_id = data.get("user_id")?.as_str()?;
+    #[cfg(feature = "wasm")]
+    let user_info = data.get("user_info").cloned();
+    #[cfg(not(feature = "wasm"))]
+    let user_info = data.get("user_info").map(|v| v.to_string());
+
+    Some(MemberInfo {
+        user_id: user_id.to_string(),
+        user_info,
+    })
+}
+
+/// Text used to compare a `user_info` value in `Members::sorted_by`
+fn sort_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -252,4 +535,102 @@ mod tests {
         assert!(members.get("user1").is_some());
         assert!(members.get("user2").is_some());
     }
+
+    #[test]
+    fn test_sorted_members() {
+        let members = Members::new();
+        for (id, name, score) in [("u1", "Carol", 9), ("u2", "Alice", 10), ("u3", "Bob", 42)] {
+            members.add(
+                MemberInfo::new(id)
+                    .with_info_value(serde_json::json!({ "name": name, "score": score })),
+            );
+        }
+        let ids = |sorted: Vec<MemberInfo>| -> Vec<String> {
+            sorted.into_iter().map(|m| m.user_id).collect()
+        };
+
+        assert_eq!(ids(members.sorted_by_id()), ["u1", "u2", "u3"]);
+        assert_eq!(ids(members.sorted_by("name")), ["u2", "u3", "u1"]);
+        // Numbers compare as strings: "10" < "42" < "9"
+        assert_eq!(ids(members.sorted_by("score")), ["u2", "u3", "u1"]);
+
+        let descending = members.sorted_by_key(|m| {
+            std::cmp::Reverse(m.user_info_value().and_then(|info| info["score"].as_i64()))
+        });
+        assert_eq!(ids(descending), ["u3", "u2", "u1"]);
+
+        // Members without the field sort last; the map itself is untouched
+        members.add(MemberInfo::new("u0"));
+        assert_eq!(ids(members.sorted_by("name")), ["u2", "u3", "u1", "u0"]);
+        assert_eq!(members.count(), 4);
+    }
+
+    #[test]
+    fn test_apply_patch_overlapping_operations() {
+        let members = Members::new();
+        for id in ["u1", "u2", "u3"] {
+            members.add(MemberInfo::new(id).with_info_value(serde_json::json!({"v": 1})));
+        }
+        let before = members.snapshot();
+
+        let applied = members.apply_patch(MembersPatch {
+            added: vec![
+                // Removed and re-added in the same patch
+                MemberInfo::new("u1").with_info_value(serde_json::json!({"v": 2})),
+                // Already present: skipped
+                MemberInfo::new("u2").with_info_value(serde_json::json!({"v": 9})),
+                MemberInfo::new("u4"),
+            ],
+            removed: vec!["u1".into(), "u3".into(), "missing".into()],
+            updated: vec![
+                // Added earlier in the same patch
+                MemberInfo::new("u4").with_info_value(serde_json::json!({"v": 4})),
+                MemberInfo::new("u2").with_info_value(serde_json::json!({"v": 3})),
+                // Removed earlier in the same patch: skipped
+                MemberInfo::new("u3").with_info_value(serde_json::json!({"v": 5})),
+            ],
+        });
+
+        let ids = |members: &[MemberInfo]| -> Vec<String> {
+            let mut ids: Vec<_> = members.iter().map(|m| m.user_id.clone()).collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(ids(&applied.removed), ["u1", "u3"]);
+        assert_eq!(ids(&applied.added), ["u1", "u4"]);
+        assert_eq!(applied.updated.len(), 2);
+        assert_eq!(applied.updated[1].0.user_info_value().unwrap()["v"], 1);
+
+        let info = |id: &str| members.get(id).and_then(|m| m.user_info_value());
+        assert_eq!(members.count(), 3);
+        assert_eq!(info("u1"), Some(serde_json::json!({"v": 2})));
+        assert_eq!(info("u2"), Some(serde_json::json!({"v": 3})));
+        assert_eq!(info("u4"), Some(serde_json::json!({"v": 4})));
+        assert!(members.get("u3").is_none());
+
+        // Diffing the snapshots gives a patch with the same end result
+        let patch = Members::diff_to_patch(&before, &members.snapshot());
+        assert_eq!(ids(&patch.added), ["u4"]);
+        assert_eq!(patch.removed, ["u3"]);
+        assert_eq!(ids(&patch.updated), ["u1", "u2"]);
+
+        let replayed = Members::new();
+        for member in before.members.into_values() {
+            replayed.add(member);
+        }
+        replayed.apply_patch(patch);
+        assert!(Members::diff_to_patch(&replayed.snapshot(), &members.snapshot()).is_empty());
+    }
+
+    #[test]
+    fn test_update_info() {
+        let members = Members::new();
+        members.add(MemberInfo::new("user1").with_info_value(serde_json::json!({"name": "Old"})));
+
+        assert!(members.update_info("user1", serde_json::json!({"name": "New"})));
+        assert!(!members.update_info("missing", serde_json::json!({})));
+
+        let info = members.get("user1").unwrap().user_info_value().unwrap();
+        assert_eq!(info["name"], "New");
+    }
 }
@@ -3,13 +3,33 @@
 mod channel;
 mod channels;
 mod encrypted_channel;
+mod group;
+mod history;
 mod members;
 mod presence_channel;
 mod private_channel;
+mod state;
+mod typed_presence;
+#[cfg(not(target_arch = "wasm32"))]
+mod watcher;
 
-pub use channel::{Channel, ChannelAuthData, ChannelState, ChannelType};
+pub use channel::{
+    Channel, ChannelAuthData, ChannelSnapshot, ChannelState, ChannelType,
+    DEFAULT_RESUBSCRIPTION_TIMEOUT,
+};
+pub(crate) use channel::SendEventFn;
+#[cfg(feature = "wasm")]
+pub(crate) use history::EventHistory;
+#[cfg(feature = "wasm")]
+pub(crate) use channel::subscription_error_message;
 pub use channels::Channels;
 pub use encrypted_channel::EncryptedChannel;
-pub use members::{MemberInfo, Members};
-pub use presence_channel::PresenceChannel;
+pub use group::ChannelGroup;
+pub use members::{AppliedPatch, MemberInfo, MemberSnapshot, Members, MembersPatch};
+pub use presence_channel::{
+    MemberChangeEvent, PresenceChannel, PresenceChannelSnapshot, PresenceSubscriptionData,
+};
 pub use private_channel::PrivateChannel;
+pub use typed_presence::{TypedErrorHandler, TypedMember, TypedPresenceChannel};
+#[cfg(not(target_arch = "wasm32"))]
+pub use watcher::ChannelWatcher;
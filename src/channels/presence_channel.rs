@@ -1,16 +1,56 @@
 //! Presence channel implementation with member tracking.
 
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+#[cfg(any(test, feature = "offline-mode"))]
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tracing::debug;
+use std::time::{Duration, SystemTime};
+use tracing::{debug, warn};
 
 use super::channel::{
-    AuthorizeFn, Channel, ChannelAuthData, ChannelState, ChannelType, SendEventFn,
+    send_protocol_event, subscription_result, AuthorizeFn, Channel, ChannelAuthData,
+    ChannelSnapshot, ChannelState, ChannelType, ResubscribePolicy, SendBatchFn, SendEventFn,
+    SendRawFn,
 };
-use super::members::{MemberInfo, Members};
+use super::history::EventHistory;
+use super::members::{MemberInfo, Members, MembersPatch};
+use super::state::ChannelStateCell;
+use crate::connection::ReconnectStrategy;
 use crate::error::Result;
-use crate::events::EventDispatcher;
-use crate::protocol::PusherEvent;
+use crate::events::{EventDispatcher, EventWaiter};
+use crate::options::{SensitiveString, TriggerBatchMode};
+use crate::protocol::{Protocol, PusherEvent};
+
+/// Member list delivered when a presence subscription succeeds
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PresenceSubscriptionData {
+    /// Members present at subscription time
+    pub members: Vec<MemberInfo>,
+    /// The current user's ID
+    #[serde(rename = "myID")]
+    pub my_id: String,
+}
+
+/// Point-in-time copy of a presence channel's state and members
+#[derive(Debug, Clone, Serialize)]
+pub struct PresenceChannelSnapshot {
+    /// State shared with every channel type
+    #[serde(flatten)]
+    pub channel: ChannelSnapshot,
+    /// Members present, sorted by user ID
+    pub members: Vec<MemberInfo>,
+    /// The current user's ID
+    pub my_id: Option<String>,
+}
+
+/// A member joining or leaving a presence channel
+#[derive(Debug, Clone)]
+pub enum MemberChangeEvent {
+    Added(MemberInfo),
+    Removed(MemberInfo),
+}
 
 /// Presence channel - private channel with member tracking
 #[cfg_attr(feature = "uniffi", derive(uniffi::Object))]
@@ -18,7 +58,7 @@ pub struct PresenceChannel {
     /// Channel name
     name: String,
     /// Channel state (shared)
-    state: Arc<RwLock<ChannelState>>,
+    state: Arc<ChannelStateCell>,
     /// Event dispatcher
     dispatcher: EventDispatcher,
     /// Members management
@@ -29,6 +69,27 @@ pub struct PresenceChannel {
     authorize_fn: Option<AuthorizeFn>,
     /// Socket ID
     socket_id: RwLock<Option<String>>,
+    /// Raw frame callback for signed client events
+    send_raw: Option<SendRawFn>,
+    /// Send callback for batched triggers
+    send_batch: Option<SendBatchFn>,
+    /// Batch mode and maximum batch size for `trigger_batch`
+    trigger_batch_options: (TriggerBatchMode, usize),
+    /// Shared secret for signing client events
+    client_event_secret: Option<SensitiveString>,
+    /// Data from the last `subscription_succeeded` event (shared)
+    subscription_metadata: Arc<RwLock<Option<serde_json::Value>>>,
+    /// When the current subscription succeeded (shared)
+    subscribed_at: Arc<RwLock<Option<SystemTime>>>,
+    /// Per-channel resubscription settings (shared)
+    resubscribe_policy: Arc<RwLock<ResubscribePolicy>>,
+    /// Metadata sent with the subscribe request (shared)
+    request_metadata: Arc<RwLock<HashMap<String, String>>>,
+    /// Most recent user and member events, if enabled (shared)
+    event_history: Arc<RwLock<EventHistory>>,
+    /// Whether the owning `Channels` is in offline mode (shared)
+    #[cfg(any(test, feature = "offline-mode"))]
+    offline: Arc<AtomicBool>,
 }
 
 impl PresenceChannel {
@@ -41,7 +102,7 @@ impl PresenceChannel {
 
         Self {
             name: name.clone(),
-            state: Arc::new(RwLock::new(ChannelState::Unsubscribed)),
+            state: Arc::new(ChannelStateCell::new(ChannelState::Unsubscribed)),
             dispatcher: EventDispatcher::with_fail_through(move |event, _| {
                 debug!("No callbacks on {} for {}", name, event);
             }),
@@ -49,14 +110,56 @@ impl PresenceChannel {
             send_event: None,
             authorize_fn: None,
             socket_id: RwLock::new(None),
+            send_raw: None,
+            send_batch: None,
+            trigger_batch_options: (TriggerBatchMode::Single, 10),
+            client_event_secret: None,
+            subscription_metadata: Arc::new(RwLock::new(None)),
+            subscribed_at: Arc::new(RwLock::new(None)),
+            resubscribe_policy: Arc::new(RwLock::new(ResubscribePolicy::default())),
+            request_metadata: Arc::new(RwLock::new(HashMap::new())),
+            event_history: Arc::new(RwLock::new(EventHistory::default())),
+            #[cfg(any(test, feature = "offline-mode"))]
+            offline: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    /// Share the offline mode flag of the owning `Channels`
+    #[cfg(any(test, feature = "offline-mode"))]
+    pub(crate) fn share_offline_flag(&mut self, offline: Arc<AtomicBool>) {
+        self.offline = offline;
+    }
+
     /// Set the send event callback
     pub fn set_send_callback(&mut self, callback: SendEventFn) {
         self.send_event = Some(callback);
     }
 
+    /// Set the raw frame callback used by signed client events
+    pub fn set_raw_send_callback(&mut self, callback: SendRawFn) {
+        self.send_raw = Some(callback);
+    }
+
+    /// Set the send callback used by batched triggers
+    pub fn set_batch_send_callback(&mut self, callback: SendBatchFn) {
+        self.send_batch = Some(callback);
+    }
+
+    /// Configure how `trigger_batch` sends events
+    pub fn set_trigger_batch_options(&mut self, mode: TriggerBatchMode, max_batch_size: usize) {
+        self.trigger_batch_options = (mode, max_batch_size);
+    }
+
+    /// Trigger several client events, returning one result per event
+    pub fn trigger_batch(&self, events: Vec<(String, serde_json::Value)>) -> Vec<Result<bool>> {
+        self.as_channel().trigger_batch(events)
+    }
+
+    /// Set the secret used to sign client events (`None` disables signing)
+    pub fn set_client_event_secret(&mut self, secret: Option<SensitiveString>) {
+        self.client_event_secret = secret;
+    }
+
     /// Set the authorization callback
     pub fn set_authorize_callback(&mut self, callback: AuthorizeFn) {
         self.authorize_fn = Some(callback);
@@ -87,6 +190,23 @@ impl PresenceChannel {
         *self.state.read()
     }
 
+    /// Retry failed resubscriptions after a reconnect with `strategy`
+    /// instead of giving up after the first attempt
+    pub fn set_reconnect_strategy(&self, strategy: Box<dyn ReconnectStrategy>) {
+        self.resubscribe_policy.write().strategy = Some(Arc::from(strategy));
+    }
+
+    /// Set how long a resubscription may wait for the server's confirmation
+    /// before it counts as failed (default: 10 seconds)
+    pub fn set_resubscription_timeout(&self, timeout: Duration) {
+        self.resubscribe_policy.write().timeout = Some(timeout);
+    }
+
+    /// Confirmation timeout for resubscriptions, if one was set
+    pub fn resubscription_timeout(&self) -> Option<Duration> {
+        self.resubscribe_policy.read().timeout
+    }
+
     /// Get all members
     pub fn get_members(&self) -> Vec<MemberInfo> {
         self.members.all()
@@ -107,15 +227,43 @@ impl PresenceChannel {
         self.members.get(user_id)
     }
 
+    /// Map user ID to the value of `field` in `user_info`, for members
+    /// where that field is a string
+    pub fn members_by_info_field(&self, field: &str) -> HashMap<String, String> {
+        self.members.by_info_field(field)
+    }
+
+    /// Get the members matching `predicate`.
+    ///
+    /// The predicate runs under the member list's read lock, so it must not
+    /// modify the channel's members.
+    pub fn members_where(&self, predicate: impl Fn(&MemberInfo) -> bool) -> Vec<MemberInfo> {
+        self.members.filter(predicate)
+    }
+
+    /// Get the members whose `user_info` has string `field` equal to `value`
+    pub fn members_with_info_field_matching(&self, field: &str, value: &str) -> Vec<MemberInfo> {
+        self.members.with_info_field(field, value)
+    }
+
     /// Bind a callback to an event
     pub fn bind(
         &self,
         event_name: impl Into<String>,
         callback: impl Fn(&PusherEvent) + Send + Sync + 'static,
-    ) -> u64 {
+    ) -> Result<u64> {
         self.dispatcher.bind(event_name, callback)
     }
 
+    /// Bind without validating the name, for protocol events
+    pub(crate) fn bind_unchecked(
+        &self,
+        event_name: impl Into<String>,
+        callback: impl Fn(&PusherEvent) + Send + Sync + 'static,
+    ) -> u64 {
+        self.dispatcher.bind_unchecked(event_name, callback)
+    }
+
     /// Unbind callbacks
     pub fn unbind(&self, event_name: Option<&str>, callback_id: Option<u64>) {
         self.dispatcher.unbind(event_name, callback_id);
@@ -126,6 +274,87 @@ impl PresenceChannel {
         self.dispatcher.unbind_all();
     }
 
+    /// Bind a callback for members joining, returning the binding ID
+    pub fn on_member_added(&self, callback: impl Fn(MemberInfo) + Send + Sync + 'static) -> u64 {
+        self.dispatcher
+            .bind_unchecked("pusher:member_added", move |event| {
+                if let Some(member) = parse_member(event) {
+                    callback(member);
+                }
+            })
+    }
+
+    /// Bind a callback for members leaving, returning the binding ID
+    pub fn on_member_removed(&self, callback: impl Fn(MemberInfo) + Send + Sync + 'static) -> u64 {
+        self.dispatcher
+            .bind_unchecked("pusher:member_removed", move |event| {
+                if let Some(member) = parse_member(event) {
+                    callback(member);
+                }
+            })
+    }
+
+    /// Bind a single callback for members joining and leaving.
+    ///
+    /// The returned ID removes the binding with `unbind(None, Some(id))`.
+    pub fn on_member_changed(
+        &self,
+        callback: impl Fn(MemberChangeEvent) + Send + Sync + 'static,
+    ) -> u64 {
+        self.dispatcher.bind_global(move |event| {
+            let change: fn(MemberInfo) -> MemberChangeEvent = match event.event.as_str() {
+                "pusher:member_added" => MemberChangeEvent::Added,
+                "pusher:member_removed" => MemberChangeEvent::Removed,
+                _ => return,
+            };
+            if let Some(member) = parse_member(event) {
+                callback(change(member));
+            }
+        })
+    }
+
+    /// Wait for the subscription to succeed and return the initial members.
+    ///
+    /// Resolves immediately with the current members if already subscribed.
+    /// Dropping the returned future removes its internal bindings.
+    pub fn on_subscribed(&self) -> EventWaiter<PresenceSubscriptionData> {
+        let waiter = EventWaiter::new(
+            &self.dispatcher,
+            &["pusher:subscription_succeeded", "pusher:subscription_error"],
+            |event| {
+                subscription_result(event)?;
+
+                #[cfg(feature = "wasm")]
+                let value = event.data.clone().unwrap_or_default();
+                #[cfg(not(feature = "wasm"))]
+                let value = match event.data {
+                    Some(ref data) => serde_json::from_str::<serde_json::Value>(data)?,
+                    None => serde_json::Value::Null,
+                };
+
+                let members = match value.get("members") {
+                    Some(members) => serde_json::from_value(members.clone())?,
+                    None => Vec::new(),
+                };
+                let my_id = value
+                    .get("myID")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string();
+
+                Ok(PresenceSubscriptionData { members, my_id })
+            },
+        );
+
+        if self.is_subscribed() {
+            return EventWaiter::ready(Ok(PresenceSubscriptionData {
+                members: self.members.all(),
+                my_id: self.members.my_id().unwrap_or_default(),
+            }));
+        }
+        waiter
+    }
+
     /// Authorize the subscription
     pub fn authorize(&self, socket_id: &str) -> Result<ChannelAuthData> {
         if let Some(ref auth_fn) = self.authorize_fn {
@@ -157,22 +386,35 @@ impl PresenceChannel {
         *self.state.write() = ChannelState::Subscribing;
         *self.socket_id.write() = Some(socket_id.to_string());
 
+        // Offline there is no server to authorize against or to supply
+        // members, so the channel is joined with none
+        #[cfg(any(test, feature = "offline-mode"))]
+        if self.offline.load(Ordering::SeqCst) {
+            self.handle_event(
+                &PusherEvent::new("pusher_internal:subscription_succeeded")
+                    .with_channel(&self.name)
+                    .with_json_data(serde_json::json!({
+                        "presence": { "ids": [], "hash": {}, "count": 0 }
+                    })),
+            );
+            return Ok(());
+        }
+
         // Authorize
         let auth_data = self.authorize(socket_id)?;
 
         // Build subscription data
-        let sub_data = serde_json::json!({
-            "channel": self.name,
-            "auth": auth_data.auth,
-            "channel_data": auth_data.channel_data,
-        });
+        let event = Protocol::create_subscribe_event_with_metadata(
+            &self.name,
+            Some(auth_data.auth.as_str()),
+            auth_data.channel_data.as_deref(),
+            None,
+            &self.request_metadata.read(),
+        )?;
 
         // Send subscribe event
         if let Some(ref send) = self.send_event {
-            #[cfg(feature = "wasm")]
-            send("pusher:subscribe", &sub_data, None);
-            #[cfg(not(feature = "wasm"))]
-            send("pusher:subscribe", &sub_data.to_string(), None);
+            send_protocol_event(send, &event);
         }
 
         Ok(())
@@ -185,25 +427,75 @@ impl PresenceChannel {
         }
 
         *self.state.write() = ChannelState::Unsubscribed;
-
-        let data = serde_json::json!({
-            "channel": self.name,
-        });
+        *self.subscription_metadata.write() = None;
+        *self.subscribed_at.write() = None;
 
         if let Some(ref send) = self.send_event {
-            #[cfg(feature = "wasm")]
-            send("pusher:unsubscribe", &data, None);
-            #[cfg(not(feature = "wasm"))]
-            send("pusher:unsubscribe", &data.to_string(), None);
+            send_protocol_event(send, &Protocol::create_unsubscribe_event(&self.name));
         }
+
+        self.dispatcher
+            .emit(&PusherEvent::new("pusher:unsubscription_succeeded").with_channel(&self.name));
     }
 
     /// Handle disconnection
     pub fn disconnect(&self) {
         *self.state.write() = ChannelState::Unsubscribed;
+        *self.subscription_metadata.write() = None;
+        *self.subscribed_at.write() = None;
         self.members.reset();
     }
 
+    /// Keep the last `size` events of each event name, including
+    /// `pusher:member_added` and `pusher:member_removed`. 0 turns history
+    /// off.
+    pub fn set_history_size(&self, size: usize) {
+        self.event_history.write().set_capacity(size);
+    }
+
+    /// Up to `limit` of the most recent user and member events, newest
+    /// first, only of `event_name` when it is given
+    pub fn get_event_history(&self, event_name: Option<&str>, limit: usize) -> Vec<PusherEvent> {
+        self.event_history.read().newest(event_name, limit)
+    }
+
+    /// Capture the channel's state and members at this moment
+    pub fn snapshot(&self) -> PresenceChannelSnapshot {
+        // Always taken in this order, so concurrent snapshots cannot deadlock
+        let state = self.state.read();
+        let subscription_metadata = self.subscription_metadata.read();
+        let subscribed_at = self.subscribed_at.read();
+        let (my_id, members) = self.members.snapshot_with_my_id();
+
+        PresenceChannelSnapshot {
+            channel: ChannelSnapshot {
+                name: self.name.clone(),
+                channel_type: ChannelType::Presence,
+                state: *state,
+                subscription_count: None,
+                tags_filter: None,
+                callback_stats: self.dispatcher.memory_usage(),
+                subscription_metadata: subscription_metadata.clone(),
+                subscribed_at: *subscribed_at,
+            },
+            members,
+            my_id,
+        }
+    }
+
+    /// Data sent with `subscription_succeeded`, if subscribed
+    pub fn subscription_metadata(&self) -> Option<serde_json::Value> {
+        self.subscription_metadata.read().clone()
+    }
+
+    /// Read the subscription metadata in place, without cloning it
+    pub fn with_subscription_metadata<F, R>(&self, f: F) -> Option<R>
+    where
+        F: Fn(&serde_json::Value) -> R,
+    {
+        self.subscription_metadata.read().as_ref().map(f)
+    }
+
     /// Trigger a client event (WASM version)
     #[cfg(feature = "wasm")]
     pub fn trigger(&self, event_name: &str, data: serde_json::Value) -> Result<bool> {
@@ -213,6 +505,11 @@ impl PresenceChannel {
                 event_name
             )));
         }
+        self.dispatcher.validate_event_name(event_name)?;
+
+        if self.client_event_secret.is_some() {
+            return self.as_channel().trigger(event_name, data);
+        }
 
         if let Some(ref send) = self.send_event {
             Ok(send(event_name, &data, Some(&self.name)))
@@ -238,6 +535,11 @@ impl PresenceChannel {
                 event_name
             )));
         }
+        self.dispatcher.validate_event_name(event_name)?;
+
+        if self.client_event_secret.is_some() {
+            return self.as_channel().trigger(event_name, data);
+        }
 
         if let Some(ref send) = self.send_event {
             Ok(send(event_name, &data, Some(&self.name)))
@@ -248,6 +550,44 @@ impl PresenceChannel {
         }
     }
 
+    /// Ask the server to evict a member by sending a `client-kick` event.
+    ///
+    /// The member is removed locally once the server re-broadcasts the kick
+    /// as `pusher_internal:member_kicked`.
+    pub fn kick(&self, user_id: &str, reason: Option<&str>) -> Result<()> {
+        if self.channel_type() != ChannelType::Presence {
+            return Err(crate::error::SockudoError::invalid_channel(
+                "Members can only be kicked from presence channels",
+            ));
+        }
+        if !self.is_subscribed() {
+            return Err(crate::error::SockudoError::invalid_state(
+                "Channel must be subscribed to kick members",
+            ));
+        }
+        if self.members.get(user_id).is_none() {
+            return Err(crate::error::SockudoError::member_not_found(user_id));
+        }
+
+        let payload = serde_json::json!({
+            "user_id": user_id,
+            "reason": reason,
+        });
+
+        #[cfg(feature = "wasm")]
+        let sent = self.trigger("client-kick", payload)?;
+        #[cfg(not(feature = "wasm"))]
+        let sent = self.trigger_value("client-kick", payload)?;
+
+        if sent {
+            Ok(())
+        } else {
+            Err(crate::error::SockudoError::connection(
+                "Failed to send client-kick event",
+            ))
+        }
+    }
+
     /// Handle an incoming event
     pub fn handle_event(&self, event: &PusherEvent) {
         let event_name = &event.event;
@@ -257,6 +597,7 @@ impl PresenceChannel {
         } else {
             // User event - emit with user_id metadata
             self.dispatcher.emit(event);
+            self.event_history.write().record(event);
         }
     }
 
@@ -278,6 +619,15 @@ impl PresenceChannel {
             "pusher_internal:member_removed" => {
                 self.handle_member_removed(event);
             }
+            "pusher_internal:member_updated" => {
+                self.handle_member_updated(event);
+            }
+            "pusher_internal:member_kicked" => {
+                self.handle_member_kicked(event);
+            }
+            "pusher_internal:presence_patch" => {
+                self.handle_presence_patch(event);
+            }
             _ => {}
         }
     }
@@ -285,6 +635,8 @@ impl PresenceChannel {
     /// Handle subscription succeeded
     fn handle_subscription_succeeded(&self, event: &PusherEvent) {
         *self.state.write() = ChannelState::Subscribed;
+        *self.subscription_metadata.write() = event.data_as_value();
+        *self.subscribed_at.write() = Some(SystemTime::now());
 
         // Initialize members from presence data
         if let Some(ref data) = event.data {
@@ -349,6 +701,7 @@ impl PresenceChannel {
                 }
 
                 self.dispatcher.emit(&added_event);
+                self.event_history.write().record(&added_event);
             }
         }
     }
@@ -379,20 +732,177 @@ impl PresenceChannel {
                 }
 
                 self.dispatcher.emit(&removed_event);
+                self.event_history.write().record(&removed_event);
             }
         }
     }
 
+    /// Handle a kick re-broadcast by the server.
+    ///
+    /// Emits `pusher:member_removed` followed by `pusher:member_kicked`,
+    /// whose data carries the `user_id`, `user_info` and `reason`.
+    fn handle_member_kicked(&self, event: &PusherEvent) {
+        #[cfg(feature = "wasm")]
+        let value = event.data.clone();
+        #[cfg(not(feature = "wasm"))]
+        let value = event
+            .data
+            .as_deref()
+            .and_then(|data| serde_json::from_str::<serde_json::Value>(data).ok());
+
+        let Some(value) = value else {
+            warn!("Invalid member_kicked data on {}", self.name);
+            return;
+        };
+        let Some(member) = self.members.remove_member(&value) else {
+            return;
+        };
+
+        let kicked_data = serde_json::json!({
+            "user_id": member.user_id,
+            "user_info": member.user_info_value(),
+            "reason": value.get("reason").cloned().unwrap_or(serde_json::Value::Null),
+        });
+
+        let mut removed_event = PusherEvent::new("pusher:member_removed");
+        removed_event.channel = Some(self.name.clone());
+        let mut kicked_event = PusherEvent::new("pusher:member_kicked");
+        kicked_event.channel = Some(self.name.clone());
+
+        #[cfg(feature = "wasm")]
+        {
+            removed_event.data = Some(serde_json::to_value(&member).unwrap());
+            kicked_event.data = Some(kicked_data);
+        }
+        #[cfg(not(feature = "wasm"))]
+        {
+            removed_event.data = Some(serde_json::to_string(&member).unwrap());
+            kicked_event.data = Some(kicked_data.to_string());
+        }
+
+        self.dispatcher.emit(&removed_event);
+        self.event_history.write().record(&removed_event);
+        self.dispatcher.emit(&kicked_event);
+    }
+
+    /// Handle member updated
+    fn handle_member_updated(&self, event: &PusherEvent) {
+        if let Some(ref data) = event.data {
+            #[cfg(feature = "wasm")]
+            let update_opt = self.members.update_member(data);
+            #[cfg(not(feature = "wasm"))]
+            let update_opt = if let Ok(value) = serde_json::from_str::<serde_json::Value>(data) {
+                self.members.update_member(&value)
+            } else {
+                None
+            };
+
+            if let Some((previous, current)) = update_opt {
+                let mut updated_event = PusherEvent::new("pusher:member_updated");
+                updated_event.channel = Some(self.name.clone());
+
+                let update_data = serde_json::json!({
+                    "user_id": current.user_id,
+                    "old_info": previous.user_info_value(),
+                    "new_info": current.user_info_value(),
+                });
+
+                #[cfg(feature = "wasm")]
+                {
+                    updated_event.data = Some(update_data);
+                }
+                #[cfg(not(feature = "wasm"))]
+                {
+                    updated_event.data = Some(update_data.to_string());
+                }
+
+                self.dispatcher.emit(&updated_event);
+            }
+        }
+    }
+
+    /// Handle a batch of member changes sent in place of a full member list.
+    ///
+    /// Emits `pusher:member_removed`, `pusher:member_added` and
+    /// `pusher:member_updated` for each member actually changed, in that
+    /// order.
+    fn handle_presence_patch(&self, event: &PusherEvent) {
+        let Some(value) = event.data_as_value() else {
+            warn!("Invalid presence_patch data on {}", self.name);
+            return;
+        };
+        let Some(patch) = MembersPatch::from_value(&value) else {
+            warn!("Invalid presence_patch data on {}", self.name);
+            return;
+        };
+
+        let applied = self.members.apply_patch(patch);
+        for member in &applied.removed {
+            self.emit_member_event(
+                "pusher:member_removed",
+                serde_json::to_value(member).unwrap(),
+            );
+        }
+        for member in &applied.added {
+            self.emit_member_event("pusher:member_added", serde_json::to_value(member).unwrap());
+        }
+        for (previous, current) in &applied.updated {
+            self.emit_member_event(
+                "pusher:member_updated",
+                serde_json::json!({
+                    "user_id": current.user_id,
+                    "old_info": previous.user_info_value(),
+                    "new_info": current.user_info_value(),
+                }),
+            );
+        }
+    }
+
+    fn emit_member_event(&self, event_name: &str, data: serde_json::Value) {
+        let mut member_event = PusherEvent::new(event_name);
+        member_event.channel = Some(self.name.clone());
+
+        #[cfg(feature = "wasm")]
+        {
+            member_event.data = Some(data);
+        }
+        #[cfg(not(feature = "wasm"))]
+        {
+            member_event.data = Some(data.to_string());
+        }
+
+        self.dispatcher.emit(&member_event);
+        if event_name != "pusher:member_updated" {
+            self.event_history.write().record(&member_event);
+        }
+    }
+
     /// Get as base Channel reference (for unified handling)
     pub fn as_channel(&self) -> Arc<Channel> {
         // Create a channel that shares the same dispatcher and state
         let mut channel =
-            Channel::with_dispatcher(&self.name, self.dispatcher.clone(), self.state.clone());
+            Channel::with_state_cell(&self.name, self.dispatcher.clone(), self.state.clone());
+        #[cfg(any(test, feature = "offline-mode"))]
+        channel.share_offline_flag(self.offline.clone());
+        channel.share_subscription_metadata(self.subscription_metadata.clone());
+        channel.share_subscribed_at(self.subscribed_at.clone());
+        channel.share_event_history(self.event_history.clone());
+        channel.share_resubscribe_policy(self.resubscribe_policy.clone());
+        channel.share_request_metadata(self.request_metadata.clone());
 
         // Copy callbacks from presence channel
         if let Some(ref send_cb) = self.send_event {
             channel.set_send_callback(send_cb.clone());
         }
+        if let Some(ref raw_cb) = self.send_raw {
+            channel.set_raw_send_callback(raw_cb.clone());
+        }
+        if let Some(ref batch_cb) = self.send_batch {
+            channel.set_batch_send_callback(batch_cb.clone());
+        }
+        let (mode, max_batch_size) = self.trigger_batch_options;
+        channel.set_trigger_batch_options(mode, max_batch_size);
+        channel.set_client_event_secret(self.client_event_secret.clone());
         if let Some(ref auth_cb) = self.authorize_fn {
             channel.set_authorize_callback(auth_cb.clone());
         }
@@ -401,6 +911,16 @@ impl PresenceChannel {
     }
 }
 
+fn parse_member(event: &PusherEvent) -> Option<MemberInfo> {
+    match event.parse_data::<MemberInfo>() {
+        Ok(member) => Some(member),
+        Err(e) => {
+            warn!("Invalid member data in {}: {}", event.event, e);
+            None
+        }
+    }
+}
+
 impl std::fmt::Debug for PresenceChannel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("PresenceChannel")
@@ -482,7 +1002,7 @@ impl PresenceChannel {
         &self,
         event_name: String,
         callback: Box<dyn crate::ffi_callbacks::EventCallback>,
-    ) -> u64 {
+    ) -> Result<u64> {
         self.bind(event_name, move |event| {
             callback.on_event(crate::UniffiPusherEvent {
                 event: event.event.clone(),
@@ -493,6 +1013,43 @@ impl PresenceChannel {
         })
     }
 
+    /// Bind presence callbacks for subscription and member changes (FFI wrapper).
+    /// Returns a binding ID that can be passed to `unbind`.
+    #[uniffi::method(name = "bindPresence")]
+    pub fn ffi_bind_presence(
+        &self,
+        callback: Box<dyn crate::ffi_callbacks::PresenceCallback>,
+    ) -> u64 {
+        self.dispatcher
+            .bind_global(move |event| match event.event.as_str() {
+                "pusher:subscription_succeeded" => {
+                    let data = event.parse_data::<serde_json::Value>().unwrap_or_default();
+                    let my_id = data["myID"].as_str().unwrap_or_default().to_string();
+                    let member_ids = data["members"]
+                        .as_array()
+                        .map(|members| {
+                            members
+                                .iter()
+                                .filter_map(|m| m["user_id"].as_str().map(String::from))
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    callback.on_subscription_succeeded(my_id, member_ids);
+                }
+                "pusher:member_added" => {
+                    if let Some(member) = parse_member(event) {
+                        callback.on_member_added(to_uniffi_member(&member));
+                    }
+                }
+                "pusher:member_removed" => {
+                    if let Some(member) = parse_member(event) {
+                        callback.on_member_removed(to_uniffi_member(&member));
+                    }
+                }
+                _ => {}
+            })
+    }
+
     /// Unbind event callback(s) (FFI wrapper)
     #[uniffi::method(name = "unbind")]
     pub fn ffi_unbind(&self, event_name: Option<String>, callback_id: Option<u64>) {
@@ -513,6 +1070,14 @@ impl PresenceChannel {
     }
 }
 
+#[cfg(all(not(feature = "wasm"), feature = "uniffi"))]
+fn to_uniffi_member(member: &MemberInfo) -> crate::UniffiMemberInfo {
+    crate::UniffiMemberInfo {
+        user_id: member.user_id.clone(),
+        user_info_json: member.user_info.clone(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -525,6 +1090,204 @@ mod tests {
         assert_eq!(channel.member_count(), 0);
     }
 
+    #[test]
+    fn test_offline_subscribe_has_no_members() {
+        let mut channel = PresenceChannel::new("presence-room");
+        channel.share_offline_flag(Arc::new(AtomicBool::new(true)));
+        channel.handle_event(
+            &PusherEvent::new("pusher_internal:member_added")
+                .with_json_data(serde_json::json!({"user_id": "ana"})),
+        );
+
+        channel.subscribe("offline.1").unwrap();
+        assert!(channel.is_subscribed());
+        assert_eq!(channel.member_count(), 0);
+    }
+
+    #[test]
+    fn test_presence_event_history_includes_member_changes() {
+        let channel = PresenceChannel::new("presence-room");
+        channel.as_channel().set_history_size(5);
+        channel.handle_event(
+            &PusherEvent::new("pusher_internal:member_added")
+                .with_json_data(serde_json::json!({"user_id": "ana"})),
+        );
+        channel.handle_event(&PusherEvent::new("chat"));
+        channel.handle_event(
+            &PusherEvent::new("pusher_internal:member_removed")
+                .with_json_data(serde_json::json!({"user_id": "ana"})),
+        );
+
+        let names: Vec<_> = channel
+            .get_event_history(None, 10)
+            .into_iter()
+            .map(|e| e.event)
+            .collect();
+        assert_eq!(
+            names,
+            ["pusher:member_removed", "chat", "pusher:member_added"]
+        );
+        // Views returned by `as_channel` share the history
+        assert_eq!(
+            channel
+                .as_channel()
+                .get_event_history(Some("pusher:member_added"), 10)
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_presence_snapshot() {
+        let channel = PresenceChannel::new("presence-room");
+        channel.handle_event(
+            &PusherEvent::new("pusher_internal:subscription_succeeded").with_json_data(
+                serde_json::json!({
+                    "presence": {
+                        "ids": ["user2", "user1"],
+                        "hash": { "user1": {"name": "Ana"}, "user2": {} }
+                    }
+                }),
+            ),
+        );
+        channel.members.set_my_id("user1");
+
+        let snapshot = channel.snapshot();
+        assert_eq!(snapshot.channel.state, ChannelState::Subscribed);
+        assert_eq!(snapshot.channel.channel_type, ChannelType::Presence);
+        assert!(snapshot.channel.subscribed_at.is_some());
+        assert_eq!(snapshot.my_id.as_deref(), Some("user1"));
+        let ids: Vec<_> = snapshot
+            .members
+            .iter()
+            .map(|m| m.user_id.as_str())
+            .collect();
+        assert_eq!(ids, ["user1", "user2"]);
+
+        channel.members.remove("user2");
+        assert_eq!(snapshot.members.len(), 2);
+
+        // The shared fields are flattened into the same object
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(json["name"], "presence-room");
+        assert_eq!(json["my_id"], "user1");
+
+        // Wrappers returned by `as_channel` share the subscription time
+        assert_eq!(
+            channel.as_channel().snapshot().subscribed_at,
+            snapshot.channel.subscribed_at
+        );
+    }
+
+    #[test]
+    fn test_members_by_info_field() {
+        let channel = PresenceChannel::new("presence-office");
+        for (id, info) in [
+            (
+                "u1",
+                serde_json::json!({"role": "admin", "department": "eng"}),
+            ),
+            (
+                "u2",
+                serde_json::json!({"role": "member", "department": "eng"}),
+            ),
+            (
+                "u3",
+                serde_json::json!({"role": "member", "department": "sales"}),
+            ),
+            ("u4", serde_json::json!({"role": 7})),
+        ] {
+            channel
+                .members
+                .add(MemberInfo::new(id).with_info_value(info));
+        }
+        channel.members.add(MemberInfo::new("u5"));
+        let ids = |members: Vec<MemberInfo>| {
+            let mut ids: Vec<String> = members.into_iter().map(|m| m.user_id).collect();
+            ids.sort();
+            ids
+        };
+
+        let roles = channel.members_by_info_field("role");
+        assert_eq!(roles.len(), 3);
+        assert_eq!(roles["u1"], "admin");
+        assert_eq!(roles["u3"], "member");
+        assert!(!roles.contains_key("u4"));
+        assert_eq!(channel.members_by_info_field("department")["u3"], "sales");
+        assert!(channel.members_by_info_field("missing").is_empty());
+
+        assert_eq!(
+            ids(channel.members_with_info_field_matching("department", "eng")),
+            ["u1", "u2"]
+        );
+        assert!(channel
+            .members_with_info_field_matching("role", "7")
+            .is_empty());
+        assert_eq!(
+            ids(channel.members_where(|m| m.user_info.is_none() || m.user_id == "u4")),
+            ["u4", "u5"]
+        );
+    }
+
+    #[test]
+    fn test_kick_round_trip() {
+        let sent = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let sent_clone = sent.clone();
+
+        let mut channel = PresenceChannel::new("presence-room");
+        channel.set_send_callback(Arc::new(move |event, data, channel| {
+            sent_clone.lock().push((
+                event.to_string(),
+                data.to_string(),
+                channel.map(str::to_string),
+            ));
+            true
+        }));
+
+        let data = serde_json::json!({
+            "presence": {
+                "count": 2,
+                "ids": ["user1", "user2"],
+                "hash": {"user1": {}, "user2": {}}
+            }
+        });
+        assert!(channel.kick("user2", None).is_err());
+        channel.handle_event(
+            &PusherEvent::new("pusher_internal:subscription_succeeded").with_json_data(data),
+        );
+
+        assert!(matches!(
+            channel.kick("user3", None),
+            Err(crate::error::SockudoError::MemberNotFound { .. })
+        ));
+
+        channel.kick("user2", Some("spam")).unwrap();
+        let (event, payload, target) = sent.lock().pop().unwrap();
+        assert_eq!(event, "client-kick");
+        assert_eq!(target.as_deref(), Some("presence-room"));
+        let payload: serde_json::Value = serde_json::from_str(&payload).unwrap();
+        assert_eq!(
+            payload,
+            serde_json::json!({"user_id": "user2", "reason": "spam"})
+        );
+
+        let kicked = Arc::new(parking_lot::Mutex::new(None));
+        let kicked_clone = kicked.clone();
+        channel
+            .bind("pusher:member_kicked", move |event| {
+                *kicked_clone.lock() = event.parse_data::<serde_json::Value>().ok();
+            })
+            .unwrap();
+
+        // Server re-broadcasts the kick
+        channel.handle_event(
+            &PusherEvent::new("pusher_internal:member_kicked").with_json_data(payload),
+        );
+        assert_eq!(channel.member_count(), 1);
+        assert!(channel.get_member("user2").is_none());
+        assert_eq!(kicked.lock().as_ref().unwrap()["reason"], "spam");
+    }
+
     #[test]
     fn test_member_tracking() {
         let channel = PresenceChannel::new("presence-room");
@@ -547,6 +1310,179 @@ mod tests {
         assert!(channel.get_member("user1").is_some());
     }
 
+    #[test]
+    fn test_on_subscribed_returns_members() {
+        let channel = PresenceChannel::new("presence-room");
+        channel.members.set_my_id("user1");
+        let waiter = channel.on_subscribed();
+
+        let data = serde_json::json!({
+            "presence": {
+                "count": 1,
+                "ids": ["user1"],
+                "hash": { "user1": {"name": "User One"} }
+            }
+        });
+        let mut event = PusherEvent::new("pusher_internal:subscription_succeeded");
+        #[cfg(feature = "wasm")]
+        {
+            event.data = Some(data);
+        }
+        #[cfg(not(feature = "wasm"))]
+        {
+            event.data = Some(data.to_string());
+        }
+        channel.handle_event(&event);
+
+        let result = futures::executor::block_on(waiter).unwrap();
+        assert_eq!(result.my_id, "user1");
+        assert_eq!(result.members.len(), 1);
+        assert_eq!(channel.dispatcher.callback_count(), 0);
+
+        // The raw subscription data is kept and visible through as_channel
+        let metadata = channel.as_channel().subscription_metadata().unwrap();
+        assert_eq!(metadata["presence"]["count"], 1);
+    }
+
+    #[test]
+    fn test_member_updated_event() {
+        let channel = PresenceChannel::new("presence-room");
+        channel.members.on_subscription(&serde_json::json!({
+            "presence": {
+                "ids": ["user1"],
+                "hash": { "user1": {"status": "away"} }
+            }
+        }));
+
+        let received = Arc::new(RwLock::new(None));
+        let received_clone = received.clone();
+        channel
+            .bind("pusher:member_updated", move |event| {
+                *received_clone.write() = event.data.clone();
+            })
+            .unwrap();
+
+        let data = serde_json::json!({"user_id": "user1", "user_info": {"status": "online"}});
+        let mut event = PusherEvent::new("pusher_internal:member_updated");
+        #[cfg(feature = "wasm")]
+        {
+            event.data = Some(data);
+        }
+        #[cfg(not(feature = "wasm"))]
+        {
+            event.data = Some(data.to_string());
+        }
+        channel.handle_event(&event);
+
+        let emitted = received.read().clone().expect("member_updated not emitted");
+        #[cfg(not(feature = "wasm"))]
+        let emitted: serde_json::Value = serde_json::from_str(&emitted).unwrap();
+        assert_eq!(emitted["user_id"], "user1");
+        assert_eq!(emitted["old_info"]["status"], "away");
+        assert_eq!(emitted["new_info"]["status"], "online");
+    }
+
+    #[test]
+    fn test_member_change_callbacks() {
+        let channel = PresenceChannel::new("presence-room");
+        let member_event = |name: &str, user_id: &str| {
+            let data = serde_json::json!({"user_id": user_id, "user_info": {"name": user_id}});
+            let mut event = PusherEvent::new(name);
+            #[cfg(feature = "wasm")]
+            {
+                event.data = Some(data);
+            }
+            #[cfg(not(feature = "wasm"))]
+            {
+                event.data = Some(data.to_string());
+            }
+            event
+        };
+
+        let added = Arc::new(RwLock::new(Vec::new()));
+        let added_clone = added.clone();
+        channel.on_member_added(move |member| added_clone.write().push(member.user_id));
+
+        let changes = Arc::new(RwLock::new(Vec::new()));
+        let changes_clone = changes.clone();
+        let changed_id = channel.on_member_changed(move |change| {
+            changes_clone.write().push(match change {
+                MemberChangeEvent::Added(m) => format!("+{}", m.user_id),
+                MemberChangeEvent::Removed(m) => format!("-{}", m.user_id),
+            })
+        });
+
+        channel.handle_event(&member_event("pusher_internal:member_added", "user1"));
+        channel.handle_event(&member_event("pusher_internal:member_removed", "user1"));
+        assert_eq!(*added.read(), vec!["user1".to_string()]);
+        assert_eq!(*changes.read(), vec!["+user1", "-user1"]);
+
+        channel.unbind(None, Some(changed_id));
+        channel.handle_event(&member_event("pusher_internal:member_added", "user2"));
+        assert_eq!(added.read().len(), 2);
+        assert_eq!(changes.read().len(), 2);
+    }
+
+    #[test]
+    fn test_presence_patch_event() {
+        let channel = PresenceChannel::new("presence-room");
+        channel.members.on_subscription(&serde_json::json!({
+            "presence": {
+                "ids": ["user1", "user2"],
+                "hash": { "user1": {"status": "away"}, "user2": {} }
+            }
+        }));
+
+        let events = Arc::new(RwLock::new(Vec::new()));
+        for name in [
+            "pusher:member_added",
+            "pusher:member_removed",
+            "pusher:member_updated",
+        ] {
+            let events = events.clone();
+            channel
+                .bind(name, move |event| {
+                    let data = event.data_as_value().unwrap();
+                    events.write().push(format!(
+                        "{} {}",
+                        event.event,
+                        data["user_id"].as_str().unwrap()
+                    ));
+                })
+                .unwrap();
+        }
+
+        let data = serde_json::json!({
+            "added": [{"user_id": "user3", "user_info": {"status": "online"}}],
+            "removed": ["user2"],
+            "updated": [{"user_id": "user1", "user_info": {"status": "online"}}],
+        });
+        let mut event = PusherEvent::new("pusher_internal:presence_patch");
+        #[cfg(feature = "wasm")]
+        {
+            event.data = Some(data);
+        }
+        #[cfg(not(feature = "wasm"))]
+        {
+            event.data = Some(data.to_string());
+        }
+        channel.handle_event(&event);
+
+        assert_eq!(
+            *events.read(),
+            vec![
+                "pusher:member_removed user2",
+                "pusher:member_added user3",
+                "pusher:member_updated user1",
+            ]
+        );
+        assert_eq!(channel.member_count(), 2);
+        let status =
+            |id: &str| channel.get_member(id).unwrap().user_info_value().unwrap()["status"].clone();
+        assert_eq!(status("user1"), "online");
+        assert_eq!(status("user3"), "online");
+    }
+
     #[test]
     #[should_panic]
     fn test_invalid_name() {
@@ -34,8 +34,8 @@ impl PrivateChannel {
     }
 
     /// Get channel name
-    pub fn name(&self) -> &str {
-        self.inner.name()
+    pub fn name(&self) -> String {
+        self.inner.current_name()
     }
 
     /// Get channel type
@@ -58,7 +58,7 @@ impl PrivateChannel {
         &self,
         event_name: impl Into<String>,
         callback: impl Fn(&PusherEvent) + Send + Sync + 'static,
-    ) -> u64 {
+    ) -> Result<u64> {
         self.inner.bind(event_name, callback)
     }
 
@@ -0,0 +1,142 @@
+//! Subscription state shared by every view of a channel.
+
+use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::sync::watch;
+
+use super::channel::ChannelState;
+
+/// A channel's subscription state, publishing every change to watchers.
+///
+/// Presence and encrypted channels share one cell with the `Channel`
+/// views returned by `as_channel`, so a change made through any of them
+/// is seen by all.
+pub(crate) struct ChannelStateCell {
+    state: Arc<RwLock<ChannelState>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    changes: watch::Sender<ChannelState>,
+}
+
+impl ChannelStateCell {
+    /// Create a cell holding `state`
+    pub(crate) fn new(state: ChannelState) -> Self {
+        Self::from_shared(Arc::new(RwLock::new(state)))
+    }
+
+    /// Create a cell around a lock shared with other code. Watchers only
+    /// see changes made through the cell.
+    pub(crate) fn from_shared(state: Arc<RwLock<ChannelState>>) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        let changes = watch::channel(*state.read()).0;
+        Self {
+            state,
+            #[cfg(not(target_arch = "wasm32"))]
+            changes,
+        }
+    }
+
+    /// Lock the state for reading
+    pub(crate) fn read(&self) -> RwLockReadGuard<'_, ChannelState> {
+        self.state.read()
+    }
+
+    /// Lock the state for writing; watchers are notified when the guard
+    /// is dropped, if the state changed
+    pub(crate) fn write(&self) -> StateWriteGuard<'_> {
+        StateWriteGuard {
+            guard: self.state.write(),
+            #[cfg(not(target_arch = "wasm32"))]
+            changes: &self.changes,
+        }
+    }
+
+    /// Receiver of every state change, starting from the current state
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn subscribe(&self) -> watch::Receiver<ChannelState> {
+        self.changes.subscribe()
+    }
+}
+
+impl std::fmt::Debug for ChannelStateCell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ChannelStateCell")
+            .field(&*self.state.read())
+            .finish()
+    }
+}
+
+/// Write access to a [`ChannelStateCell`]
+pub(crate) struct StateWriteGuard<'a> {
+    guard: RwLockWriteGuard<'a, ChannelState>,
+    #[cfg(not(target_arch = "wasm32"))]
+    changes: &'a watch::Sender<ChannelState>,
+}
+
+impl std::ops::Deref for StateWriteGuard<'_> {
+    type Target = ChannelState;
+
+    fn deref(&self) -> &ChannelState {
+        &self.guard
+    }
+}
+
+impl std::ops::DerefMut for StateWriteGuard<'_> {
+    fn deref_mut(&mut self) -> &mut ChannelState {
+        &mut self.guard
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for StateWriteGuard<'_> {
+    fn drop(&mut self) {
+        // Published while the lock is still held, so watchers see changes
+        // in the order they were made
+        let state = *self.guard;
+        self.changes.send_if_modified(|current| {
+            let modified = *current != state;
+            *current = state;
+            modified
+        });
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watchers_see_changes_only() {
+        let cell = ChannelStateCell::new(ChannelState::Unsubscribed);
+        let mut watcher = cell.subscribe();
+
+        *cell.write() = ChannelState::Unsubscribed;
+        assert!(!watcher.has_changed().unwrap());
+
+        *cell.write() = ChannelState::Subscribing;
+        assert!(watcher.has_changed().unwrap());
+        assert_eq!(*watcher.borrow_and_update(), ChannelState::Subscribing);
+
+        let mut state = cell.write();
+        *state = ChannelState::Subscribed;
+        assert!(!watcher.has_changed().unwrap());
+        drop(state);
+        assert_eq!(*watcher.borrow_and_update(), ChannelState::Subscribed);
+        assert_eq!(*cell.read(), ChannelState::Subscribed);
+    }
+
+    #[test]
+    fn test_shared_lock() {
+        let shared = Arc::new(RwLock::new(ChannelState::Subscribing));
+        let cell = ChannelStateCell::from_shared(shared.clone());
+        let mut watcher = cell.subscribe();
+        assert_eq!(*watcher.borrow_and_update(), ChannelState::Subscribing);
+
+        *cell.write() = ChannelState::Subscribed;
+        assert_eq!(*shared.read(), ChannelState::Subscribed);
+        assert!(watcher.has_changed().unwrap());
+
+        *shared.write() = ChannelState::Unsubscribed;
+        assert_eq!(*cell.read(), ChannelState::Unsubscribed);
+    }
+}
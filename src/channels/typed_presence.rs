@@ -0,0 +1,212 @@
+//! Presence channel wrapper with strongly typed member info.
+
+use parking_lot::RwLock;
+use serde::de::DeserializeOwned;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use tracing::warn;
+
+use super::members::MemberInfo;
+use super::presence_channel::PresenceChannel;
+use crate::error::{Result, SockudoError};
+use crate::protocol::PusherEvent;
+
+/// Handler for member info that fails to deserialize
+pub type TypedErrorHandler = Arc<dyn Fn(&SockudoError) + Send + Sync>;
+
+/// A presence member with deserialized user info
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedMember<T> {
+    pub user_id: String,
+    pub info: T,
+}
+
+/// A presence channel whose member info is deserialized into `T`.
+///
+/// Member info is deserialized on access. Members whose info does not match
+/// `T` are skipped and the error is passed to the handler set with
+/// `on_error` (or logged if none is set).
+pub struct TypedPresenceChannel<T> {
+    inner: Arc<PresenceChannel>,
+    error_handler: Arc<RwLock<Option<TypedErrorHandler>>>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned + 'static> TypedPresenceChannel<T> {
+    /// Wrap an existing presence channel
+    pub fn new(inner: Arc<PresenceChannel>) -> Self {
+        Self {
+            inner,
+            error_handler: Arc::new(RwLock::new(None)),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get the underlying presence channel
+    pub fn inner(&self) -> &Arc<PresenceChannel> {
+        &self.inner
+    }
+
+    /// Set the handler for member info that fails to deserialize
+    pub fn on_error(&self, handler: impl Fn(&SockudoError) + Send + Sync + 'static) {
+        *self.error_handler.write() = Some(Arc::new(handler));
+    }
+
+    /// Get all members with typed info
+    pub fn get_members_typed(&self) -> Vec<TypedMember<T>> {
+        self.inner
+            .get_members()
+            .iter()
+            .filter_map(|member| report(&self.error_handler, to_typed(member)))
+            .collect()
+    }
+
+    /// Get the current user's member with typed info
+    pub fn get_me_typed(&self) -> Option<TypedMember<T>> {
+        let me = self.inner.get_me()?;
+        report(&self.error_handler, to_typed(&me))
+    }
+
+    /// Bind a callback for members joining, returning the binding ID
+    pub fn on_member_added_typed(
+        &self,
+        callback: impl Fn(TypedMember<T>) + Send + Sync + 'static,
+    ) -> u64 {
+        self.bind_member_event("pusher:member_added", callback)
+    }
+
+    /// Bind a callback for members leaving, returning the binding ID
+    pub fn on_member_removed_typed(
+        &self,
+        callback: impl Fn(TypedMember<T>) + Send + Sync + 'static,
+    ) -> u64 {
+        self.bind_member_event("pusher:member_removed", callback)
+    }
+
+    fn bind_member_event(
+        &self,
+        event_name: &str,
+        callback: impl Fn(TypedMember<T>) + Send + Sync + 'static,
+    ) -> u64 {
+        let error_handler = self.error_handler.clone();
+        self.inner
+            .bind_unchecked(event_name, move |event: &PusherEvent| {
+                let typed = event
+                    .parse_data::<MemberInfo>()
+                    .and_then(|member| to_typed(&member));
+                if let Some(member) = report(&error_handler, typed) {
+                    callback(member);
+                }
+            })
+    }
+}
+
+fn to_typed<T: DeserializeOwned>(member: &MemberInfo) -> Result<TypedMember<T>> {
+    let value = member.user_info_value().unwrap_or(serde_json::Value::Null);
+    let info = serde_json::from_value(value).map_err(|e| {
+        SockudoError::serialization(format!(
+            "Invalid user info for member {}: {}",
+            member.user_id, e
+        ))
+    })?;
+
+    Ok(TypedMember {
+        user_id: member.user_id.clone(),
+        info,
+    })
+}
+
+fn report<T>(
+    error_handler: &RwLock<Option<TypedErrorHandler>>,
+    result: Result<TypedMember<T>>,
+) -> Option<TypedMember<T>> {
+    match result {
+        Ok(member) => Some(member),
+        Err(e) => {
+            match error_handler.read().as_ref() {
+                Some(handler) => handler(&e),
+                None => warn!("{}", e),
+            }
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Clone, PartialEq, Deserialize)]
+    struct UserInfo {
+        name: String,
+        avatar_url: Option<String>,
+    }
+
+    fn event_with_data(name: &str, data: serde_json::Value) -> PusherEvent {
+        let mut event = PusherEvent::new(name);
+        #[cfg(feature = "wasm")]
+        {
+            event.data = Some(data);
+        }
+        #[cfg(not(feature = "wasm"))]
+        {
+            event.data = Some(data.to_string());
+        }
+        event
+    }
+
+    #[test]
+    fn test_typed_members_round_trip() {
+        let channel = Arc::new(PresenceChannel::new("presence-room"));
+        channel.members.set_my_id("u1");
+        let typed = TypedPresenceChannel::<UserInfo>::new(channel.clone());
+
+        let errors = Arc::new(RwLock::new(Vec::new()));
+        let errors_clone = errors.clone();
+        typed.on_error(move |e| errors_clone.write().push(e.to_string()));
+
+        channel.handle_event(&event_with_data(
+            "pusher_internal:subscription_succeeded",
+            serde_json::json!({
+                "presence": {
+                    "count": 2,
+                    "ids": ["u1", "u2"],
+                    "hash": {
+                        "u1": {"name": "Ada", "avatar_url": "https://example.com/ada.png"},
+                        "u2": {"nickname": "no name field"}
+                    }
+                }
+            }),
+        ));
+
+        let members = typed.get_members_typed();
+        assert_eq!(members.len(), 1);
+        assert_eq!(
+            typed.get_me_typed(),
+            Some(TypedMember {
+                user_id: "u1".to_string(),
+                info: UserInfo {
+                    name: "Ada".to_string(),
+                    avatar_url: Some("https://example.com/ada.png".to_string()),
+                },
+            })
+        );
+        // The malformed member is reported, not returned
+        assert_eq!(errors.read().len(), 1);
+
+        let added = Arc::new(RwLock::new(None));
+        let added_clone = added.clone();
+        typed.on_member_added_typed(move |member| *added_clone.write() = Some(member));
+
+        channel.handle_event(&event_with_data(
+            "pusher_internal:member_added",
+            serde_json::json!({"user_id": "u3", "user_info": {"name": "Grace"}}),
+        ));
+
+        let added = added.read().clone().expect("member_added not delivered");
+        assert_eq!(added.user_id, "u3");
+        assert_eq!(added.info.name, "Grace");
+        assert_eq!(added.info.avatar_url, None);
+    }
+}
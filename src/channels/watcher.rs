@@ -0,0 +1,146 @@
+//! Async observation of a single channel's state, members and events.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{mpsc, watch};
+use tracing::warn;
+
+use super::channel::{Channel, ChannelState};
+use super::presence_channel::PresenceChannel;
+use crate::error::{Result, SockudoError};
+use crate::events::EventDispatcher;
+use crate::protocol::PusherEvent;
+
+/// Number of unread events a watcher buffers before dropping new ones
+const EVENT_BUFFER: usize = 1024;
+
+/// Waits for changes on one channel, created by
+/// `SockudoClient::watch_channel`.
+///
+/// Events are buffered from the moment the watcher is created. Dropping
+/// the watcher removes its callback from the channel.
+pub struct ChannelWatcher {
+    channel: Arc<Channel>,
+    presence: Option<Arc<PresenceChannel>>,
+    state: watch::Receiver<ChannelState>,
+    events: mpsc::Receiver<PusherEvent>,
+    /// Bumped on every event, so member counts are rechecked
+    emitted: watch::Receiver<()>,
+    dispatcher: EventDispatcher,
+    binding: u64,
+}
+
+impl ChannelWatcher {
+    /// Watch `channel`; `presence` gives access to its members when it is
+    /// a presence channel
+    pub(crate) fn new(channel: Arc<Channel>, presence: Option<Arc<PresenceChannel>>) -> Self {
+        let (event_tx, events) = mpsc::channel(EVENT_BUFFER);
+        let (emitted_tx, emitted) = watch::channel(());
+        let dispatcher = channel.dispatcher().clone();
+        let name = channel.current_name();
+        let binding = dispatcher.bind_global(move |event| {
+            emitted_tx.send_replace(());
+            if event.event.starts_with("pusher") {
+                return;
+            }
+            if event_tx.try_send(event.clone()).is_err() {
+                warn!("Watcher of {} is full, dropping {}", name, event.event);
+            }
+        });
+
+        Self {
+            state: channel.watch_state(),
+            channel,
+            presence,
+            events,
+            emitted,
+            dispatcher,
+            binding,
+        }
+    }
+
+    /// The watched channel
+    pub fn channel(&self) -> &Arc<Channel> {
+        &self.channel
+    }
+
+    /// Wait until the channel is in `target` state, resolving at once if
+    /// it already is
+    pub async fn wait_for_state(&self, target: ChannelState, timeout: Duration) -> Result<()> {
+        let mut state = self.state.clone();
+        let reached = async { state.wait_for(|state| *state == target).await.map(|_| ()) };
+        match tokio::time::timeout(timeout, reached).await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(_)) => Err(SockudoError::invalid_state("Channel was dropped")),
+            Err(_) => Err(SockudoError::timeout(format!(
+                "Timed out waiting for {} to be {:?}",
+                self.channel.current_name(),
+                target
+            ))),
+        }
+    }
+
+    /// Wait until the presence channel has exactly `n` members, resolving
+    /// at once if it already has.
+    ///
+    /// Fails on channels other than presence channels.
+    pub async fn wait_for_member_count(&self, n: usize, timeout: Duration) -> Result<()> {
+        let Some(ref presence) = self.presence else {
+            return Err(SockudoError::invalid_channel(format!(
+                "{} is not a presence channel",
+                self.channel.current_name()
+            )));
+        };
+        let mut emitted = self.emitted.clone();
+        let wait = async {
+            // The count is read after each event, so no change is missed
+            // between the check and the wait
+            while presence.member_count() != n {
+                if emitted.changed().await.is_err() {
+                    return Err(SockudoError::invalid_state("Watcher callback was removed"));
+                }
+            }
+            Ok(())
+        };
+        tokio::time::timeout(timeout, wait)
+            .await
+            .unwrap_or_else(|_| {
+                Err(SockudoError::timeout(format!(
+                    "Timed out waiting for {} members on {}",
+                    n,
+                    self.channel.current_name()
+                )))
+            })
+    }
+
+    /// Take the next user event received on the channel, in arrival order.
+    ///
+    /// `pusher:` events such as subscription and member changes are not
+    /// delivered here; use `wait_for_state` and `wait_for_member_count`.
+    pub async fn next_event(&mut self, timeout: Duration) -> Result<PusherEvent> {
+        match tokio::time::timeout(timeout, self.events.recv()).await {
+            Ok(Some(event)) => Ok(event),
+            Ok(None) => Err(SockudoError::invalid_state("Watcher callback was removed")),
+            Err(_) => Err(SockudoError::timeout(format!(
+                "Timed out waiting for an event on {}",
+                self.channel.current_name()
+            ))),
+        }
+    }
+}
+
+impl Drop for ChannelWatcher {
+    fn drop(&mut self) {
+        self.dispatcher.unbind_global(Some(self.binding));
+    }
+}
+
+impl std::fmt::Debug for ChannelWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChannelWatcher")
+            .field("channel", &self.channel.current_name())
+            .field("state", &*self.state.borrow())
+            .finish()
+    }
+}
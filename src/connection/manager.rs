@@ -1,33 +1,202 @@
 //! Connection manager for WebSocket lifecycle management.
 
 use parking_lot::RwLock;
+use std::collections::VecDeque;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 #[cfg(not(target_arch = "wasm32"))]
 use tokio::sync::mpsc;
 use tracing::{debug, error, info};
 
 use super::state::ConnectionState;
+use super::stats::{
+    push_reconnect_attempt, ConnectionCounters, ConnectionStats, ReconnectAttempt, SessionClock,
+    StatisticsWindowGuard,
+};
 use crate::error::{Result, SockudoError};
-use crate::events::EventDispatcher;
-use crate::options::Config;
-use crate::protocol::{Protocol, PusherEvent};
+use crate::events::{ClientMiddleware, EventDispatcher, EventFilter, MiddlewareChain};
+#[cfg(feature = "uniffi")]
+use crate::ffi_callbacks::ConnectionCallback;
+use crate::options::{sanitize_client_id, Config, SensitiveString};
+use crate::protocol::{MessageTransformer, Protocol, PusherEvent};
 #[cfg(not(target_arch = "wasm32"))]
-use crate::transports::{NativeTransport, Transport};
+use crate::transports::{ConnectionInfo, NativeTransport, Transport};
 #[cfg(feature = "wasm")]
 use serde_json::Value;
 
+/// Receiver of outgoing messages while the client is in offline mode
+#[cfg(any(test, feature = "offline-mode"))]
+pub(crate) type OfflineSink = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Order in which queued outgoing messages are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessagePriority {
+    /// Protocol control messages such as subscribe, ping and pong
+    High,
+    /// Client events
+    Normal,
+    /// Bulk traffic such as resubscribing after a reconnect
+    Low,
+}
+
+impl MessagePriority {
+    /// Default priority of a frame carrying `event_name`
+    pub fn for_event(event_name: &str) -> Self {
+        match event_name {
+            "pusher:subscribe" | "pusher:ping" | "pusher:pong" => Self::High,
+            _ => Self::Normal,
+        }
+    }
+}
+
+/// `pusher:ping` frames sent by probes on the current connection and the
+/// `pusher:pong` frames answering them, so each probe is woken only by the
+/// pong to its own ping
+#[derive(Default)]
+struct PingLedger {
+    /// Probe pings sent
+    sent: u64,
+    /// Pongs received while a probe ping was outstanding
+    answered: u64,
+    /// Probes waiting, with the number of their ping
+    waiters: Vec<(u64, futures::channel::oneshot::Sender<()>)>,
+}
+
+impl PingLedger {
+    /// Number the next probe ping, returning a receiver its pong resolves
+    fn next_ping(&mut self) -> futures::channel::oneshot::Receiver<()> {
+        self.waiters.retain(|(_, waiter)| !waiter.is_canceled());
+        self.sent += 1;
+        let (tx, rx) = futures::channel::oneshot::channel();
+        self.waiters.push((self.sent, tx));
+        rx
+    }
+
+    /// Count a pong against the oldest unanswered ping; pongs arriving with
+    /// no ping outstanding are ignored
+    fn answer(&mut self) {
+        if self.answered == self.sent {
+            return;
+        }
+        self.answered += 1;
+        let answered = self.answered;
+        if let Some(index) = self.waiters.iter().position(|(ping, _)| *ping == answered) {
+            let (_, waiter) = self.waiters.swap_remove(index);
+            let _ = waiter.send(());
+        }
+    }
+}
+
+/// Outgoing messages waiting for the connection task, one queue per priority
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default)]
+struct Outbox {
+    high: VecDeque<String>,
+    normal: VecDeque<String>,
+    low: VecDeque<String>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Outbox {
+    fn push(&mut self, message: String, priority: MessagePriority) {
+        match priority {
+            MessagePriority::High => self.high.push_back(message),
+            MessagePriority::Normal => self.normal.push_back(message),
+            MessagePriority::Low => self.low.push_back(message),
+        }
+    }
+
+    /// Next message to write, highest priority first
+    fn pop(&mut self) -> Option<String> {
+        self.high
+            .pop_front()
+            .or_else(|| self.normal.pop_front())
+            .or_else(|| self.low.pop_front())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.high.is_empty() && self.normal.is_empty() && self.low.is_empty()
+    }
+}
+
 /// Commands that can be sent to the connection task
 #[derive(Debug)]
 enum ConnectionCommand {
-    Connect,
+    Connect(Option<SensitiveString>),
     Disconnect,
-    Send(String),
+    DisconnectWithCode(u16, String),
+    Send {
+        message: String,
+        priority: MessagePriority,
+    },
+    SendBatch(Vec<String>),
     Ping,
     SendPong,
+    Pause,
+    #[cfg(not(target_arch = "wasm32"))]
+    Resume(tokio::sync::oneshot::Sender<bool>),
+    #[cfg(feature = "io-adapter")]
+    TakeTransport(tokio::sync::oneshot::Sender<Box<dyn Transport>>),
     Shutdown,
 }
 
+/// Forwards lifecycle changes to the registered `ConnectionCallback`
+#[derive(Clone, Default)]
+struct ConnectionObserver {
+    #[cfg(feature = "uniffi")]
+    callback: Arc<RwLock<Option<Arc<dyn ConnectionCallback>>>>,
+}
+
+impl ConnectionObserver {
+    #[cfg(feature = "uniffi")]
+    fn callback(&self) -> Option<Arc<dyn ConnectionCallback>> {
+        self.callback.read().clone()
+    }
+
+    fn state_changed(&self, previous: ConnectionState, current: ConnectionState) {
+        #[cfg(feature = "uniffi")]
+        if let Some(callback) = self.callback() {
+            callback.on_state_changed(previous, current);
+        }
+        #[cfg(not(feature = "uniffi"))]
+        let _ = (previous, current);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn error(&self, error: &str) {
+        #[cfg(feature = "uniffi")]
+        if let Some(callback) = self.callback() {
+            callback.on_error(error.to_string());
+        }
+        #[cfg(not(feature = "uniffi"))]
+        let _ = error;
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn socket_id_assigned(&self, socket_id: &str) {
+        #[cfg(feature = "uniffi")]
+        if let Some(callback) = self.callback() {
+            callback.on_socket_id_assigned(socket_id.to_string());
+        }
+        #[cfg(not(feature = "uniffi"))]
+        let _ = socket_id;
+    }
+}
+
+/// Move `state` to `new_state`, telling `observer` if it changed, and return
+/// the previous state
+fn set_state(
+    state: &RwLock<ConnectionState>,
+    new_state: ConnectionState,
+    observer: &ConnectionObserver,
+) -> ConnectionState {
+    let previous = std::mem::replace(&mut *state.write(), new_state);
+    if previous != new_state {
+        observer.state_changed(previous, new_state);
+    }
+    previous
+}
+
 /// Connection manager handles the WebSocket connection lifecycle
 pub struct ConnectionManager {
     /// Configuration
@@ -50,13 +219,52 @@ pub struct ConnectionManager {
     reconnect_attempts: Arc<RwLock<u32>>,
     /// Whether TLS is required
     using_tls: Arc<RwLock<bool>>,
+    /// Global event whitelist applied before dispatch
+    event_filter: Arc<RwLock<Option<EventFilter>>>,
+    /// Middlewares applied to every event sent and received
+    middleware: MiddlewareChain,
+    /// Transformers applied to every event as it is encoded and decoded
+    transformers: Arc<RwLock<Vec<Arc<dyn MessageTransformer>>>>,
+    /// Close code and reason sent by the last `disconnect_with_reason`
+    last_disconnect_reason: Arc<RwLock<Option<(u16, String)>>>,
+    /// Token appended to the URL on the next connect, cleared once used
+    connection_token: Arc<RwLock<Option<SensitiveString>>>,
+    /// URL used instead of the configured one on the next connect, cleared
+    /// once used
+    override_url: Arc<RwLock<Option<String>>>,
+    /// Sent as the `client_id` URL parameter on every connect
+    client_id: Arc<RwLock<Option<String>>>,
+    /// Transport details of the current connection
+    #[cfg(not(target_arch = "wasm32"))]
+    connection_info: Arc<RwLock<Option<ConnectionInfo>>>,
+    /// Traffic counters
+    counters: Arc<ConnectionCounters>,
+    /// When the current connection was established and total time connected
+    session_clock: Arc<SessionClock>,
+    /// Most recent connection or protocol error
+    last_error: Arc<RwLock<Option<String>>>,
+    /// Most recent reconnect attempts, oldest first
+    reconnect_history: Arc<RwLock<VecDeque<ReconnectAttempt>>>,
+    /// Round-trip time of the most recent answered ping
+    last_ping_rtt: Arc<RwLock<Option<Duration>>>,
+    /// Probe pings on the current connection and their pongs
+    pings: Arc<RwLock<PingLedger>>,
+    /// Receiver of state changes, errors and socket IDs
+    observer: ConnectionObserver,
+    /// Receives every outgoing message instead of the socket while offline
+    #[cfg(any(test, feature = "offline-mode"))]
+    offline_sink: Arc<RwLock<Option<OfflineSink>>>,
 }
 
 impl ConnectionManager {
     /// Create a new connection manager
-    pub fn new(config: Config) -> Self {
+    pub fn new(mut config: Config) -> Self {
         let activity_timeout = config.activity_timeout;
         let using_tls = config.use_tls;
+        let event_filter = config.event_filter.clone();
+        let connection_token = config.connection_token.take();
+        let client_id = config.client_id.clone();
+        let transformers = std::mem::take(&mut config.message_transformers);
 
         Self {
             config: Arc::new(config),
@@ -70,6 +278,24 @@ impl ConnectionManager {
             message_rx: Arc::new(RwLock::new(None)),
             reconnect_attempts: Arc::new(RwLock::new(0)),
             using_tls: Arc::new(RwLock::new(using_tls)),
+            event_filter: Arc::new(RwLock::new(event_filter)),
+            middleware: MiddlewareChain::default(),
+            transformers: Arc::new(RwLock::new(transformers)),
+            last_disconnect_reason: Arc::new(RwLock::new(None)),
+            connection_token: Arc::new(RwLock::new(connection_token)),
+            override_url: Arc::new(RwLock::new(None)),
+            client_id: Arc::new(RwLock::new(client_id)),
+            #[cfg(not(target_arch = "wasm32"))]
+            connection_info: Arc::new(RwLock::new(None)),
+            counters: Arc::new(ConnectionCounters::default()),
+            session_clock: Arc::new(SessionClock::default()),
+            last_error: Arc::new(RwLock::new(None)),
+            reconnect_history: Arc::new(RwLock::new(VecDeque::new())),
+            last_ping_rtt: Arc::new(RwLock::new(None)),
+            pings: Arc::new(RwLock::new(PingLedger::default())),
+            observer: ConnectionObserver::default(),
+            #[cfg(any(test, feature = "offline-mode"))]
+            offline_sink: Arc::new(RwLock::new(None)),
         }
     }
 
@@ -93,12 +319,178 @@ impl ConnectionManager {
         *self.using_tls.read()
     }
 
+    /// Replace the global event filter (`None` disables filtering)
+    pub fn set_event_filter(&self, filter: Option<EventFilter>) {
+        *self.event_filter.write() = filter;
+    }
+
+    /// Add `middleware` after those already registered
+    pub fn add_middleware(&self, middleware: Arc<dyn ClientMiddleware>) {
+        self.middleware.push(middleware);
+    }
+
+    /// Add `transformer` after those already registered
+    pub fn add_message_transformer(&self, transformer: Arc<dyn MessageTransformer>) {
+        self.transformers.write().push(transformer);
+    }
+
+    /// Transport details of the current connection
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn connection_info(&self) -> Option<ConnectionInfo> {
+        self.connection_info.read().clone()
+    }
+
+    /// Traffic counters since the manager was created
+    pub fn connection_stats(&self) -> ConnectionStats {
+        self.counters.snapshot(*self.reconnect_attempts.read())
+    }
+
+    /// Zero every counter returned by `connection_stats`, including the
+    /// reconnect attempts
+    pub fn statistics_reset(&self) {
+        self.counters.reset();
+        *self.reconnect_attempts.write() = 0;
+    }
+
+    /// Start measuring message and byte rates, e.g. once per `interval` in
+    /// a periodic logging task
+    pub fn start_statistics_window(&self, interval: Duration) -> StatisticsWindowGuard {
+        StatisticsWindowGuard::new(self.counters.clone(), interval)
+    }
+
+    /// How long the current connection has been established
+    pub fn uptime(&self) -> Option<Duration> {
+        self.session_clock.uptime()
+    }
+
+    /// Time spent connected across all connections since the manager was
+    /// created, including the current one
+    pub fn total_connected_duration(&self) -> Duration {
+        self.session_clock.total()
+    }
+
+    /// Most recent connection or protocol error
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error.read().clone()
+    }
+
+    /// Get the most recent reconnect attempts, oldest first.
+    ///
+    /// At most `MAX_RECONNECT_HISTORY` attempts are kept.
+    pub fn reconnect_history(&self) -> Vec<ReconnectAttempt> {
+        self.reconnect_history.read().iter().cloned().collect()
+    }
+
+    /// Round-trip time of the most recent `pusher:ping` answered by the
+    /// server
+    pub fn ping_rtt(&self) -> Option<Duration> {
+        *self.last_ping_rtt.read()
+    }
+
+    /// Record a ping round-trip time measured on another connection
+    pub(crate) fn record_ping_rtt(&self, rtt: Duration) {
+        *self.last_ping_rtt.write() = Some(rtt);
+    }
+
+    /// Send a `pusher:ping` and measure how long the server takes to answer
+    /// with `pusher:pong`.
+    ///
+    /// Returns `SockudoError::ProbeTimeout` if no pong arrives within
+    /// `timeout`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn probe(&self, timeout: Duration) -> Result<Duration> {
+        if !self.is_connected() {
+            return Err(SockudoError::invalid_state("Cannot probe: not connected"));
+        }
+
+        let ping = Protocol::encode_message(&Protocol::create_ping_event())?;
+        // Wait for the pong before sending, so a fast answer is not missed
+        let pong = self.pings.write().next_ping();
+        let started = std::time::Instant::now();
+        if !self.send_priority(ping, MessagePriority::High) {
+            return Err(SockudoError::connection("Failed to send ping"));
+        }
+        match tokio::time::timeout(timeout, pong).await {
+            Ok(Ok(())) => {}
+            _ => return Err(SockudoError::probe_timeout(timeout)),
+        }
+
+        let rtt = started.elapsed();
+        self.record_ping_rtt(rtt);
+        Ok(rtt)
+    }
+
+    /// Record the start of a reconnect attempt
+    pub(crate) fn begin_reconnect_attempt(&self, attempt_number: u32, delay_before: Duration) {
+        let attempt = ReconnectAttempt::started(
+            attempt_number,
+            SystemTime::now(),
+            delay_before.as_millis() as u64,
+        );
+        push_reconnect_attempt(&mut self.reconnect_history.write(), attempt);
+    }
+
+    /// Record the outcome of the attempt started last
+    pub(crate) fn finish_reconnect_attempt(&self, error: Option<String>) {
+        if let Some(attempt) = self.reconnect_history.write().back_mut() {
+            match error {
+                Some(error) => attempt.error = Some(error),
+                None => attempt.connected_at = Some(SystemTime::now()),
+            }
+        }
+    }
+
+    /// Register the callback told about state changes, connection errors and
+    /// socket IDs (`None` removes it)
+    #[cfg(feature = "uniffi")]
+    pub fn set_connection_callback(&self, callback: Option<Arc<dyn ConnectionCallback>>) {
+        *self.observer.callback.write() = callback;
+    }
+
+    /// Set the token appended to the WebSocket URL on the next connect
+    pub fn set_connection_token(&self, token: impl Into<String>) {
+        *self.connection_token.write() = Some(SensitiveString::new(token));
+    }
+
+    /// Connect to `url` instead of the configured URL on the next connect.
+    ///
+    /// The URL must use `ws://` or `wss://` and contain the app key.
+    pub fn set_override_url(&self, url: &str) -> Result<()> {
+        let parsed = url::Url::parse(url)
+            .map_err(|e| SockudoError::config(format!("Invalid URL {}: {}", url, e)))?;
+        if !matches!(parsed.scheme(), "ws" | "wss") {
+            return Err(SockudoError::config(format!(
+                "URL must use ws:// or wss://, got: {}",
+                url
+            )));
+        }
+        if !url.contains(&self.config.app_key) {
+            return Err(SockudoError::config(format!(
+                "URL must contain the app key {}",
+                self.config.app_key
+            )));
+        }
+        *self.override_url.write() = Some(url.to_string());
+        Ok(())
+    }
+
+    /// Send `id` as the `client_id` URL parameter from the next connect
+    /// on, replacing characters other than ASCII letters and digits with `-`
+    pub fn set_client_id(&self, id: &str) {
+        *self.client_id.write() = Some(sanitize_client_id(id));
+    }
+
+    /// ID sent as the `client_id` URL parameter, if any
+    pub fn client_id(&self) -> Option<String> {
+        self.client_id.read().clone()
+    }
+
     /// Bind to connection events
     pub fn bind(
         &self,
         event_name: impl Into<String>,
         callback: impl Fn(&PusherEvent) + Send + Sync + 'static,
-    ) -> u64 {
+    ) -> Result<u64> {
         self.dispatcher.bind(event_name, callback)
     }
 
@@ -112,6 +504,32 @@ impl ConnectionManager {
         self.dispatcher.bind_global(callback)
     }
 
+    /// Bind a callback for each time the connection reaches `Connected`,
+    /// returning the binding ID
+    pub fn on_connected_to_server(&self, callback: impl Fn() + Send + Sync + 'static) -> u64 {
+        self.dispatcher
+            .bind_unchecked("connected", move |_| callback())
+    }
+
+    /// Decode a raw frame and dispatch it as if the socket had received it.
+    ///
+    /// Only the event routing runs; socket ID and state are left untouched.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn inject_message(&self, raw_json: &str) -> Result<()> {
+        let mut event = Protocol::decode_message(raw_json)?;
+        event.mark_received();
+        route_incoming(
+            &self.dispatcher,
+            &self.event_filter,
+            &self.transformers,
+            &self.middleware,
+            &self.last_error,
+            &self.pings,
+            event,
+        );
+        Ok(())
+    }
+
     /// Connect to the server
     #[cfg(not(target_arch = "wasm32"))]
     pub async fn connect(&self) -> Result<()> {
@@ -122,7 +540,9 @@ impl ConnectionManager {
         self.update_state(ConnectionState::Connecting);
 
         // Create channels for communication
-        let (cmd_tx, cmd_rx) = mpsc::channel(32);
+        // Roomy enough that a burst of sends is queued by priority rather
+        // than rejected
+        let (cmd_tx, cmd_rx) = mpsc::channel(256);
         let (msg_tx, msg_rx) = mpsc::channel(64);
 
         *self.command_tx.write() = Some(cmd_tx.clone());
@@ -135,6 +555,13 @@ impl ConnectionManager {
         let activity_timeout = self.activity_timeout.clone();
         let reconnect_attempts = self.reconnect_attempts.clone();
         let using_tls = self.using_tls.clone();
+        let connection_info = self.connection_info.clone();
+        let counters = self.counters.clone();
+        let session_clock = self.session_clock.clone();
+        let last_error = self.last_error.clone();
+        let observer = self.observer.clone();
+        let override_url = self.override_url.clone();
+        let client_id = self.client_id.clone();
 
         // Clone cmd_tx for the connection task
         let cmd_tx_for_task = cmd_tx.clone();
@@ -148,6 +575,13 @@ impl ConnectionManager {
                 activity_timeout,
                 reconnect_attempts,
                 using_tls,
+                connection_info,
+                counters,
+                session_clock,
+                last_error,
+                observer,
+                override_url,
+                client_id,
                 cmd_rx,
                 cmd_tx_for_task,
                 msg_tx,
@@ -157,6 +591,11 @@ impl ConnectionManager {
 
         // Spawn message processing task
         let dispatcher = self.dispatcher.clone();
+        let event_filter = self.event_filter.clone();
+        let transformers = self.transformers.clone();
+        let middleware = self.middleware.clone();
+        let last_error = self.last_error.clone();
+        let pings = self.pings.clone();
         let msg_rx_arc = self.message_rx.clone();
         tokio::spawn(async move {
             loop {
@@ -167,19 +606,15 @@ impl ConnectionManager {
                     // Now we can await without holding the lock
                     match rx.recv().await {
                         Some(event) => {
-                            // Emit connection-specific events
-                            if event.event == "pusher:connection_established" {
-                                let mut connected_event = event.clone();
-                                connected_event.event = "connected".to_string();
-                                dispatcher.emit(&connected_event);
-                            } else if event.event == "pusher:error" {
-                                let mut error_event = event.clone();
-                                error_event.event = "error".to_string();
-                                dispatcher.emit(&error_event);
-                            }
-
-                            // Also emit the raw event
-                            dispatcher.emit(&event);
+                            route_incoming(
+                                &dispatcher,
+                                &event_filter,
+                                &transformers,
+                                &middleware,
+                                &last_error,
+                                &pings,
+                                event,
+                            );
 
                             // Put the receiver back
                             *msg_rx_arc.write() = Some(rx);
@@ -200,8 +635,9 @@ impl ConnectionManager {
         #[cfg(not(target_arch = "wasm32"))]
         {
             let tx = self.command_tx.read().clone();
+            let token = self.connection_token.write().take();
             if let Some(tx) = tx {
-                tx.send(ConnectionCommand::Connect)
+                tx.send(ConnectionCommand::Connect(token))
                     .await
                     .map_err(|_| SockudoError::connection("Failed to send connect command"))?;
             }
@@ -210,6 +646,94 @@ impl ConnectionManager {
         Ok(())
     }
 
+    /// Stop reading from the socket while keeping it open.
+    ///
+    /// Pings are suspended and the state becomes `Paused`. Use this when a
+    /// mobile app goes to the background instead of disconnecting.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn pause(&self) -> Result<()> {
+        if self.state() != ConnectionState::Connected {
+            return Err(SockudoError::invalid_state("Cannot pause: not connected"));
+        }
+
+        let tx = self.command_tx.read().clone();
+        let tx = tx.ok_or_else(|| SockudoError::invalid_state("Connection task not running"))?;
+        tx.send(ConnectionCommand::Pause)
+            .await
+            .map_err(|_| SockudoError::connection("Failed to send pause command"))?;
+
+        self.update_state(ConnectionState::Paused);
+        Ok(())
+    }
+
+    /// Resume reading after `pause`.
+    ///
+    /// Returns `Ok(true)` when the paused socket is still alive and the state
+    /// is back to `Connected`. Returns `Ok(false)` when the server closed it
+    /// in the meantime; the connection is then torn down, the state is
+    /// `Disconnected` and the caller should connect again.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn resume(&self) -> Result<bool> {
+        match self.state() {
+            ConnectionState::Paused => {}
+            ConnectionState::Connected => return Ok(true),
+            other => {
+                return Err(SockudoError::invalid_state(format!(
+                    "Cannot resume: connection is {}",
+                    other
+                )))
+            }
+        }
+
+        let tx = self.command_tx.read().clone();
+        let tx = tx.ok_or_else(|| SockudoError::invalid_state("Connection task not running"))?;
+        let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+        tx.send(ConnectionCommand::Resume(reply_tx))
+            .await
+            .map_err(|_| SockudoError::connection("Failed to send resume command"))?;
+        let alive = reply_rx.await.unwrap_or(false);
+
+        if alive {
+            self.update_state(ConnectionState::Connected);
+        } else {
+            info!("Paused connection was closed by the server");
+            let _ = tx.send(ConnectionCommand::Shutdown).await;
+            *self.command_tx.write() = None;
+            self.update_state(ConnectionState::Disconnected);
+            *self.socket_id.write() = None;
+            self.session_clock.stop();
+        }
+        Ok(alive)
+    }
+
+    /// Stop the connection task and hand over its connected transport.
+    ///
+    /// The manager is left `Disconnected` and no longer reads from the
+    /// socket; the caller owns the WebSocket from then on.
+    #[cfg(feature = "io-adapter")]
+    pub async fn take_transport(&self) -> Result<Box<dyn Transport>> {
+        if !self.is_connected() {
+            return Err(SockudoError::invalid_state(format!(
+                "Cannot take transport: connection is {}",
+                self.state()
+            )));
+        }
+
+        let tx = self.command_tx.write().take();
+        let tx = tx.ok_or_else(|| SockudoError::invalid_state("Connection task not running"))?;
+        let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+        tx.send(ConnectionCommand::TakeTransport(reply_tx))
+            .await
+            .map_err(|_| SockudoError::connection("Failed to send take transport command"))?;
+        let transport = reply_rx
+            .await
+            .map_err(|_| SockudoError::connection("Connection task stopped"))?;
+
+        self.update_state(ConnectionState::Disconnected);
+        *self.socket_id.write() = None;
+        Ok(transport)
+    }
+
     /// Disconnect from the server
     pub async fn disconnect(&self) {
         #[cfg(not(target_arch = "wasm32"))]
@@ -222,26 +746,188 @@ impl ConnectionManager {
 
         self.update_state(ConnectionState::Disconnected);
         *self.socket_id.write() = None;
+        self.session_clock.stop();
+    }
+
+    /// Disconnect from the server with a WebSocket close code and reason.
+    ///
+    /// Use 1000 for a normal closure, 1001 when the client is going away,
+    /// and 4000-4999 for application-specific reasons.
+    pub async fn disconnect_with_reason(&self, code: u16, reason: &str) {
+        *self.last_disconnect_reason.write() = Some((code, reason.to_string()));
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let tx = self.command_tx.read().clone();
+            if let Some(tx) = tx {
+                let _ = tx
+                    .send(ConnectionCommand::DisconnectWithCode(
+                        code,
+                        reason.to_string(),
+                    ))
+                    .await;
+            }
+        }
+
+        self.update_state(ConnectionState::Disconnected);
+        *self.socket_id.write() = None;
+        self.session_clock.stop();
+    }
+
+    /// Appear connected as `socket_id` without a socket, handing every
+    /// outgoing message to `sink`
+    #[cfg(any(test, feature = "offline-mode"))]
+    pub(crate) fn enter_offline_mode(&self, socket_id: String, sink: OfflineSink) {
+        *self.offline_sink.write() = Some(sink);
+        *self.socket_id.write() = Some(socket_id);
+        self.session_clock.start();
+        self.update_state(ConnectionState::Connected);
+    }
+
+    /// Drop the offline sink and move to `Disconnected`
+    #[cfg(any(test, feature = "offline-mode"))]
+    pub(crate) fn leave_offline_mode(&self) {
+        *self.offline_sink.write() = None;
+        self.update_state(ConnectionState::Disconnected);
+        *self.socket_id.write() = None;
     }
 
-    /// Send a raw message
+    /// Whether outgoing messages go to an offline sink instead of a socket
+    #[cfg(any(test, feature = "offline-mode"))]
+    pub fn is_offline(&self) -> bool {
+        self.offline_sink.read().is_some()
+    }
+
+    #[cfg(any(test, feature = "offline-mode"))]
+    fn offline_sink(&self) -> Option<OfflineSink> {
+        self.offline_sink.read().clone()
+    }
+
+    /// Get the close code and reason sent by the last `disconnect_with_reason`
+    pub fn last_disconnect_reason(&self) -> Option<(u16, String)> {
+        self.last_disconnect_reason.read().clone()
+    }
+
+    /// Send a raw message at `Normal` priority
     pub fn send(&self, message: &str) -> bool {
-        if !self.is_connected() {
+        self.send_priority(message.to_string(), MessagePriority::Normal)
+    }
+
+    /// Send a raw message, written ahead of any queued messages of lower
+    /// priority
+    pub fn send_priority(&self, message: String, priority: MessagePriority) -> bool {
+        if !self.state().can_send() {
             return false;
         }
 
+        #[cfg(any(test, feature = "offline-mode"))]
+        if let Some(sink) = self.offline_sink() {
+            sink(&message);
+            return true;
+        }
+
         #[cfg(not(target_arch = "wasm32"))]
         {
             if let Some(ref tx) = self.command_tx.read().as_ref() {
                 return tx
-                    .try_send(ConnectionCommand::Send(message.to_string()))
+                    .try_send(ConnectionCommand::Send { message, priority })
                     .is_ok();
             }
         }
 
+        #[cfg(target_arch = "wasm32")]
+        let _ = (message, priority);
+
         false
     }
 
+    /// Send several raw messages with a single command, returning how many
+    /// were enqueued.
+    ///
+    /// The messages are queued together at `Normal` priority, behind any
+    /// already queued, which is cheaper than one `send` per message when
+    /// many frames go out at once.
+    pub fn send_batch(&self, messages: Vec<String>) -> usize {
+        if messages.is_empty() || !self.state().can_send() {
+            return 0;
+        }
+
+        #[cfg(any(test, feature = "offline-mode"))]
+        if let Some(sink) = self.offline_sink() {
+            messages.iter().for_each(|message| sink(message));
+            return messages.len();
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(tx) = self.command_tx.read().as_ref() {
+                let count = messages.len();
+                return match tx.try_send(ConnectionCommand::SendBatch(messages)) {
+                    Ok(()) => count,
+                    Err(_) => 0,
+                };
+            }
+        }
+
+        0
+    }
+
+    /// Send `event` unless a middleware drops it, written ahead of any
+    /// queued messages of lower priority
+    pub fn send_event_with_priority(
+        &self,
+        mut event: PusherEvent,
+        priority: MessagePriority,
+    ) -> bool {
+        if !self.middleware.before_send(&mut event) {
+            debug!("Event '{}' dropped by middleware", event.event);
+            return false;
+        }
+
+        let transformers = self.transformers.read().clone();
+        match Protocol::encode_transformed(&event, &transformers) {
+            Ok(msg) => self.send_priority(msg, priority),
+            Err(e) => {
+                error!("Failed to encode event: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Send `events` as one JSON array frame, returning whether each was
+    /// sent.
+    ///
+    /// Every event passes through the middleware first; dropped events are
+    /// left out of the frame and reported as `false`.
+    pub fn send_event_batch(&self, mut events: Vec<PusherEvent>) -> Vec<bool> {
+        let kept: Vec<bool> = events
+            .iter_mut()
+            .map(|event| {
+                let keep = self.middleware.before_send(event);
+                if !keep {
+                    debug!("Event '{}' dropped by middleware", event.event);
+                }
+                keep
+            })
+            .collect();
+
+        let mut keep = kept.iter();
+        events.retain(|_| *keep.next().unwrap_or(&false));
+        if events.is_empty() {
+            return kept;
+        }
+
+        let transformers = self.transformers.read().clone();
+        let sent = match Protocol::encode_batch_transformed(&events, &transformers) {
+            Ok(frame) => self.send(&frame),
+            Err(e) => {
+                error!("Failed to encode event batch: {}", e);
+                false
+            }
+        };
+        kept.into_iter().map(|keep| keep && sent).collect()
+    }
+
     /// Send an event (WASM version)
     #[cfg(feature = "wasm")]
     pub fn send_event(&self, event_name: &str, data: &Value, channel: Option<&str>) -> bool {
@@ -250,14 +936,7 @@ impl ConnectionManager {
         if let Some(ch) = channel {
             event.channel = Some(ch.to_string());
         }
-
-        match Protocol::encode_message(&event) {
-            Ok(msg) => self.send(&msg),
-            Err(e) => {
-                error!("Failed to encode event: {}", e);
-                false
-            }
-        }
+        self.send_event_with_priority(event, MessagePriority::Normal)
     }
 
     /// Send an event (FFI version)
@@ -268,20 +947,18 @@ impl ConnectionManager {
         if let Some(ch) = channel {
             event.channel = Some(ch.to_string());
         }
-
-        match Protocol::encode_message(&event) {
-            Ok(msg) => self.send(&msg),
-            Err(e) => {
-                error!("Failed to encode event: {}", e);
-                false
-            }
-        }
+        self.send_event_with_priority(event, MessagePriority::Normal)
     }
 
     /// Update connection state and emit events
     fn update_state(&self, new_state: ConnectionState) {
-        let previous = *self.state.read();
-        *self.state.write() = new_state;
+        let previous = set_state(&self.state, new_state, &self.observer);
+        if matches!(
+            new_state,
+            ConnectionState::Disconnected | ConnectionState::Unavailable
+        ) {
+            self.session_clock.stop();
+        }
 
         if previous != new_state {
             debug!("State changed: {} -> {}", previous, new_state);
@@ -318,10 +995,160 @@ impl ConnectionManager {
             None => return,
         };
 
-        while let Some(event) = rx.recv().await {
-            self.dispatcher.emit(&event);
+        while let Some(mut event) = rx.recv().await {
+            transform_incoming(&self.transformers, &mut event);
+            if self.middleware.after_receive(&mut event) {
+                dispatch_filtered(&self.dispatcher, &self.event_filter, &event);
+            }
+        }
+    }
+}
+
+/// Pass a received event through every transformer, in order
+fn transform_incoming(
+    transformers: &RwLock<Vec<Arc<dyn MessageTransformer>>>,
+    event: &mut PusherEvent,
+) {
+    let transformers = transformers.read().clone();
+    for transformer in &transformers {
+        transformer.transform_incoming(event);
+    }
+}
+
+/// Route a received event: run the incoming transformers and middleware,
+/// emit the connection-level aliases (`connected`, `error`,
+/// `sockudo:server_error`) and then the event itself, unless the
+/// middleware drops it or the global event filter rejects it
+fn route_incoming(
+    dispatcher: &EventDispatcher,
+    event_filter: &RwLock<Option<EventFilter>>,
+    transformers: &RwLock<Vec<Arc<dyn MessageTransformer>>>,
+    middleware: &MiddlewareChain,
+    last_error: &RwLock<Option<String>>,
+    pings: &RwLock<PingLedger>,
+    mut event: PusherEvent,
+) {
+    // Pings sent on an earlier socket are never answered
+    match event.event.as_str() {
+        "pusher:connection_established" => *pings.write() = PingLedger::default(),
+        "pusher:pong" => pings.write().answer(),
+        _ => {}
+    }
+    transform_incoming(transformers, &mut event);
+    if !middleware.after_receive(&mut event) {
+        debug!("Event '{}' dropped by middleware", event.event);
+        return;
+    }
+
+    // Emit connection-specific events
+    if event.event == "pusher:connection_established" {
+        let mut connected_event = event.clone();
+        connected_event.event = "connected".to_string();
+        dispatcher.emit(&connected_event);
+    } else if event.event == "pusher:error" {
+        *last_error.write() = Some(describe_pusher_error(&event));
+        let mut error_event = event.clone();
+        error_event.event = "error".to_string();
+        dispatcher.emit(&error_event);
+
+        // Fatal errors move the connection to `Failed` instead
+        if let Some(server_error) = Protocol::pusher_server_error(&event) {
+            if !server_error.is_fatal {
+                dispatcher.emit(&PusherEvent::new("sockudo:server_error").with_json_data(
+                    serde_json::json!({
+                        "code": server_error.code,
+                        "message": server_error.message,
+                    }),
+                ));
+            }
+        }
+    }
+
+    // Also emit the raw event
+    dispatch_filtered(dispatcher, event_filter, &event);
+}
+
+/// Emit an incoming event unless the global event filter rejects it
+fn dispatch_filtered(
+    dispatcher: &EventDispatcher,
+    event_filter: &RwLock<Option<EventFilter>>,
+    event: &PusherEvent,
+) {
+    if let Some(ref filter) = *event_filter.read() {
+        if !filter.allows(event) {
+            debug!("Event '{}' discarded by event filter", event.event);
+            return;
+        }
+    }
+    dispatcher.emit(event);
+}
+
+/// Render a `pusher:error` event as "message (code)"
+fn describe_pusher_error(event: &PusherEvent) -> String {
+    #[cfg(feature = "wasm")]
+    let data = event.data.clone();
+    #[cfg(not(feature = "wasm"))]
+    let data = event
+        .data
+        .as_deref()
+        .and_then(|d| serde_json::from_str::<serde_json::Value>(d).ok());
+
+    let message = data
+        .as_ref()
+        .and_then(|d| d.get("message"))
+        .and_then(|m| m.as_str())
+        .unwrap_or("Unknown error");
+    match data
+        .as_ref()
+        .and_then(|d| d.get("code"))
+        .and_then(|c| c.as_u64())
+    {
+        Some(code) => format!("{} ({})", message, code),
+        None => message.to_string(),
+    }
+}
+
+/// Attach a debug checksum to an outgoing message when debug mode is on
+#[cfg(not(target_arch = "wasm32"))]
+fn prepare_outgoing(config: &Config, message: String) -> String {
+    #[cfg(debug_assertions)]
+    if config.debug {
+        if let Ok(checked) = Protocol::attach_checksum(&message) {
+            return checked;
         }
     }
+    #[cfg(not(debug_assertions))]
+    let _ = config;
+    message
+}
+
+/// Write one message taken from the outbox
+#[cfg(not(target_arch = "wasm32"))]
+async fn write_queued(
+    transport: &mut NativeTransport,
+    config: &Config,
+    counters: &ConnectionCounters,
+    message: String,
+) {
+    let message = prepare_outgoing(config, message);
+    match transport.send(&message).await {
+        Ok(()) => counters.record_sent(message.len()),
+        Err(e) => error!("Failed to send message: {:?}", e),
+    }
+}
+
+/// Write every queued message, highest priority first, so nothing accepted
+/// by `send` is lost when the socket is closed or handed over
+#[cfg(not(target_arch = "wasm32"))]
+async fn flush_outbox(
+    transport: &mut NativeTransport,
+    config: &Config,
+    counters: &ConnectionCounters,
+    outbox: &mut Outbox,
+) {
+    while let Some(message) = outbox.pop() {
+        write_queued(transport, config, counters, message).await;
+    }
 }
 
 /// Connection task that manages the actual WebSocket connection
@@ -333,34 +1160,127 @@ async fn connection_task(
     activity_timeout: Arc<RwLock<Duration>>,
     reconnect_attempts: Arc<RwLock<u32>>,
     using_tls: Arc<RwLock<bool>>,
+    connection_info: Arc<RwLock<Option<ConnectionInfo>>>,
+    counters: Arc<ConnectionCounters>,
+    session_clock: Arc<SessionClock>,
+    last_error: Arc<RwLock<Option<String>>>,
+    observer: ConnectionObserver,
+    override_url: Arc<RwLock<Option<String>>>,
+    client_id: Arc<RwLock<Option<String>>>,
     mut cmd_rx: mpsc::Receiver<ConnectionCommand>,
     cmd_tx: mpsc::Sender<ConnectionCommand>,
     msg_tx: mpsc::Sender<PusherEvent>,
 ) {
     use tokio::time::interval;
+    use zeroize::Zeroize;
 
-    let mut transport = NativeTransport::new();
+    let mut transport = NativeTransport::with_proxy(config.proxy.clone())
+        .with_tls_config(config.tls_config.clone())
+        .with_connection_info_slot(connection_info);
+    if let Some(timeout) = config.write_timeout {
+        transport.set_write_timeout(timeout);
+    }
+    if let Some(timeout) = config.read_timeout {
+        transport.set_read_timeout(timeout);
+    }
     let mut ping_interval = interval(Duration::from_secs(30));
+    let mut outbox = Outbox::default();
 
     loop {
         tokio::select! {
+            // Read every pending command before writing queued messages, so
+            // a high priority send overtakes earlier ones still queued
+            biased;
+
             // Handle commands
             Some(cmd) = cmd_rx.recv() => {
                 match cmd {
-                    ConnectionCommand::Connect => {
-                        info!("Connecting to {}", config.ws_url);
+                    ConnectionCommand::Connect(token) => {
+                        // A one-shot override wins over the provider, which
+                        // wins over the configured URL
+                        let override_url = override_url.write().take();
+                        let base_url = match (override_url, &config.url_provider) {
+                            (Some(url), _) => url,
+                            (None, Some(provider)) => provider.url().await,
+                            (None, None) => config.ws_url.clone(),
+                        };
+                        let base_url = match client_id.read().as_deref() {
+                            Some(id) => crate::options::url_with_client_id(&base_url, id),
+                            None => base_url,
+                        };
+                        info!("Connecting to {}", base_url);
 
                         // Set up message callback
                         let msg_tx_clone = msg_tx.clone();
                         let state_clone = state.clone();
                         let socket_id_clone = socket_id.clone();
                         let cmd_tx_clone = cmd_tx.clone();
+                        let counters_clone = counters.clone();
+                        let session_clock_clone = session_clock.clone();
+                        let config_clone = config.clone();
+                        let observer_clone = observer.clone();
+                        #[cfg(debug_assertions)]
+                        let verify_checksums = config.debug;
 
                         transport.on_message(Box::new(move |message| {
-                            if let Ok(event) = Protocol::decode_message(message) {
-                                // Handle pusher:ping - respond with pusher:pong immediately
-                                if event.event == "pusher:ping" {
-                                    debug!("Received pusher:ping, sending pusher:pong");
+                            counters_clone.record_received(message.len());
+
+                            #[cfg(debug_assertions)]
+                            if verify_checksums {
+                                if let Err(e @ SockudoError::ChecksumMismatch { expected, actual }) =
+                                    Protocol::verify_checksum(message)
+                                {
+                                    error!("Dropping corrupted message: {}", e);
+                                    let event = PusherEvent::new("sockudo:checksum_error")
+                                        .with_json_data(serde_json::json!({
+                                            "expected": expected,
+                                            "actual": actual,
+                                            "message": e.to_string(),
+                                        }));
+                                    let _ = msg_tx_clone.try_send(event);
+                                    return;
+                                }
+                            }
+
+                            // A fatal server error ends the connection for good.
+                            // Its data may be an object, which `decode_message`
+                            // rejects, so decode the raw frame
+                            if message.contains("pusher:error") {
+                                if let Some(server_error) = Protocol::decode_pusher_error(message) {
+                                    if server_error.is_fatal {
+                                        error!(
+                                            "Fatal server error {}: {}",
+                                            server_error.code, server_error.message
+                                        );
+                                        session_clock_clone.stop();
+                                        set_state(&state_clone, ConnectionState::Failed, &observer_clone);
+                                        if let Some(ref handler) = config_clone.on_fatal_error {
+                                            handler.call(&SockudoError::server_error(
+                                                server_error.code,
+                                                server_error.message,
+                                            ));
+                                        }
+                                    }
+                                }
+                            }
+
+                            if let Ok(mut event) = Protocol::decode_message(message) {
+                                event.mark_received();
+                                if event.event == "pusher:delta" {
+                                    counters_clone.record_delta();
+                                }
+
+                                // Answer the server heartbeat (pusher:ping by default) immediately
+                                if event.event == config_clone.heartbeat_event {
+                                    debug!(
+                                        "Received {}, sending {}",
+                                        config_clone.heartbeat_event,
+                                        config_clone.heartbeat_response_event
+                                    );
+                                    counters_clone.record_heartbeat();
+                                    if let Some(ref handler) = config_clone.heartbeat_handler {
+                                        handler.call();
+                                    }
                                     // Send command to send pong
                                     let _ = cmd_tx_clone.try_send(ConnectionCommand::SendPong);
                                 }
@@ -376,10 +1296,12 @@ async fn connection_task(
                                         if let Some(parsed) = parsed_data {
                                             if let Some(sid) = parsed.get("socket_id").and_then(|v| v.as_str()) {
                                                 *socket_id_clone.write() = Some(sid.to_string());
+                                                observer_clone.socket_id_assigned(sid);
                                             }
                                         }
                                     }
-                                    *state_clone.write() = ConnectionState::Connected;
+                                    session_clock_clone.start();
+                                    set_state(&state_clone, ConnectionState::Connected, &observer_clone);
                                 }
 
                                 let _ = msg_tx_clone.try_send(event);
@@ -388,35 +1310,75 @@ async fn connection_task(
 
                         // Set up close callback
                         let state_clone = state.clone();
+                        let session_clock_clone = session_clock.clone();
+                        let observer_clone = observer.clone();
                         transport.on_close(Box::new(move |_code, _reason| {
-                            *state_clone.write() = ConnectionState::Disconnected;
+                            session_clock_clone.stop();
+                            // The server closes the socket after a fatal error
+                            if *state_clone.read() != ConnectionState::Failed {
+                                set_state(&state_clone, ConnectionState::Disconnected, &observer_clone);
+                            }
                         }));
 
                         // Set up error callback
                         let state_clone = state.clone();
-                        transport.on_error(Box::new(move |_error| {
-                            *state_clone.write() = ConnectionState::Unavailable;
+                        let session_clock_clone = session_clock.clone();
+                        let last_error_clone = last_error.clone();
+                        let observer_clone = observer.clone();
+                        transport.on_error(Box::new(move |error| {
+                            observer_clone.error(&error);
+                            *last_error_clone.write() = Some(error);
+                            session_clock_clone.stop();
+                            // A fatal server error already ended the connection
+                            if *state_clone.read() != ConnectionState::Failed {
+                                set_state(&state_clone, ConnectionState::Unavailable, &observer_clone);
+                            }
                         }));
 
                         // Connect
-                        match transport.connect(&config.ws_url).await {
+                        let mut url = match token {
+                            Some(ref token) => config.url_with_token(&base_url, token.expose()),
+                            None => base_url,
+                        };
+                        let result = transport.connect(&url).await;
+                        url.zeroize();
+                        drop(token);
+
+                        match result {
                             Ok(_) => {
                                 *reconnect_attempts.write() = 0;
                             }
                             Err(e) => {
                                 error!("Failed to connect: {:?}", e);
-                                *state.write() = ConnectionState::Unavailable;
+                                observer.error(&e.to_string());
+                                *last_error.write() = Some(e.to_string());
+                                set_state(&state, ConnectionState::Unavailable, &observer);
                             }
                         }
                     }
                     ConnectionCommand::Disconnect => {
+                        flush_outbox(&mut transport, &config, &counters, &mut outbox).await;
                         transport.disconnect().await;
-                        *state.write() = ConnectionState::Disconnected;
+                        session_clock.stop();
+                        set_state(&state, ConnectionState::Disconnected, &observer);
+                        break;
+                    }
+                    ConnectionCommand::DisconnectWithCode(code, reason) => {
+                        flush_outbox(&mut transport, &config, &counters, &mut outbox).await;
+                        if let Err(e) = transport.close_with_code(code, &reason).await {
+                            error!("Failed to close with code {}: {:?}", code, e);
+                            transport.disconnect().await;
+                        }
+                        session_clock.stop();
+                        set_state(&state, ConnectionState::Disconnected, &observer);
                         break;
                     }
-                    ConnectionCommand::Send(msg) => {
-                        if let Err(e) = transport.send(&msg).await {
-                            error!("Failed to send message: {:?}", e);
+                    ConnectionCommand::Send { message, priority } => {
+                        outbox.push(message, priority);
+                    }
+                    ConnectionCommand::SendBatch(messages) => {
+                        for message in messages {
+                            outbox.push(message, MessagePriority::Normal);
                         }
                     }
                     ConnectionCommand::Ping => {
@@ -425,15 +1387,38 @@ async fn connection_task(
                         }
                     }
                     ConnectionCommand::SendPong => {
-                        let pong_event = Protocol::create_pong_event();
+                        let pong_event = PusherEvent::new(config.heartbeat_response_event.as_str())
+                            .with_json_data(serde_json::json!({}));
                         if let Ok(pong_msg) = Protocol::encode_message(&pong_event) {
-                            debug!("Sending pusher:pong");
-                            if let Err(e) = transport.send(&pong_msg).await {
-                                error!("Failed to send pong: {:?}", e);
+                            let pong_msg = prepare_outgoing(&config, pong_msg);
+                            debug!("Sending {}", config.heartbeat_response_event);
+                            match transport.send(&pong_msg).await {
+                                Ok(()) => counters.record_sent(pong_msg.len()),
+                                Err(e) => error!("Failed to send pong: {:?}", e),
                             }
                         }
                     }
+                    ConnectionCommand::Pause => {
+                        transport.pause();
+                    }
+                    ConnectionCommand::Resume(reply) => {
+                        let alive = transport.resume().await;
+                        if !alive {
+                            session_clock.stop();
+                        }
+                        let _ = reply.send(alive);
+                    }
+                    #[cfg(feature = "io-adapter")]
+                    ConnectionCommand::TakeTransport(reply) => {
+                        flush_outbox(&mut transport, &config, &counters, &mut outbox).await;
+                        let taken = std::mem::take(&mut transport);
+                        session_clock.stop();
+                        set_state(&state, ConnectionState::Disconnected, &observer);
+                        let _ = reply.send(Box::new(taken));
+                        break;
+                    }
                     ConnectionCommand::Shutdown => {
+                        flush_outbox(&mut transport, &config, &counters, &mut outbox).await;
                         transport.disconnect().await;
                         break;
                     }
@@ -446,6 +1431,13 @@ async fn connection_task(
                     let _ = transport.ping().await;
                 }
             }
+
+            // Write the next queued message
+            _ = std::future::ready(()), if !outbox.is_empty() => {
+                if let Some(msg) = outbox.pop() {
+                    write_queued(&mut transport, &config, &counters, msg).await;
+                }
+            }
         }
     }
 }
@@ -464,6 +1456,7 @@ impl std::fmt::Debug for ConnectionManager {
 mod tests {
     use super::*;
     use crate::options::PusherOptions;
+    use crate::transports::mock_server::MockServer;
 
     #[test]
     fn test_connection_manager_creation() {
@@ -474,4 +1467,460 @@ mod tests {
         assert_eq!(manager.state(), ConnectionState::Initialized);
         assert!(manager.socket_id().is_none());
     }
+
+    #[tokio::test]
+    async fn test_disconnect_with_reason_records_reason() {
+        let manager = ConnectionManager::new(Config::from(PusherOptions::new("test-key")));
+        assert!(manager.last_disconnect_reason().is_none());
+
+        manager.disconnect_with_reason(1001, "going away").await;
+
+        assert_eq!(
+            manager.last_disconnect_reason(),
+            Some((1001, "going away".to_string()))
+        );
+        assert_eq!(manager.state(), ConnectionState::Disconnected);
+    }
+
+    #[tokio::test]
+    async fn test_send_batch_writes_all_messages_in_order() {
+        let mut server = MockServer::new().spawn().await;
+
+        let options = PusherOptions::new("test-key")
+            .ws_host("127.0.0.1")
+            .ws_port(server.port)
+            .use_tls(false);
+        let manager = ConnectionManager::new(Config::from(options));
+        assert_eq!(manager.send_batch(vec!["early".to_string()]), 0);
+
+        manager.connect().await.unwrap();
+        while !manager.is_connected() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let messages: Vec<String> = (0..3).map(|i| format!("message-{}", i)).collect();
+        assert_eq!(manager.send_batch(messages.clone()), 3);
+
+        for message in messages {
+            let received = tokio::time::timeout(Duration::from_secs(5), server.received.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(received, message);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_writes_queued_messages() {
+        let mut server = MockServer::new().spawn().await;
+
+        let options = PusherOptions::new("test-key")
+            .ws_host("127.0.0.1")
+            .ws_port(server.port)
+            .use_tls(false);
+        let manager = ConnectionManager::new(Config::from(options));
+        manager.connect().await.unwrap();
+        while !manager.is_connected() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let message = |name: &str| serde_json::json!({ "event": name }).to_string();
+        assert!(manager.send_priority(message("low"), MessagePriority::Low));
+        assert_eq!(
+            manager.send_batch(vec![message("batch-1"), message("batch-2")]),
+            2
+        );
+        assert!(manager.send_priority(message("high"), MessagePriority::High));
+        manager.disconnect().await;
+
+        for expected in ["high", "batch-1", "batch-2", "low"] {
+            let received = tokio::time::timeout(Duration::from_secs(5), server.received.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(received["event"], expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_heartbeat_event() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let heartbeat = serde_json::json!({ "event": "app:heartbeat", "data": "{}" });
+        let mut server = MockServer::new()
+            .then_send(heartbeat.to_string())
+            .spawn()
+            .await;
+
+        let heartbeats = Arc::new(AtomicUsize::new(0));
+        let heartbeats_clone = heartbeats.clone();
+        let options = PusherOptions::new("test-key")
+            .ws_host("127.0.0.1")
+            .ws_port(server.port)
+            .use_tls(false)
+            .heartbeat_events("app:heartbeat", "app:heartbeat_ack")
+            .heartbeat_handler(move || {
+                heartbeats_clone.fetch_add(1, Ordering::SeqCst);
+            });
+        let manager = ConnectionManager::new(Config::from(options));
+        manager.connect().await.unwrap();
+
+        let response = tokio::time::timeout(Duration::from_secs(5), server.received.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(response["event"], "app:heartbeat_ack");
+        assert_eq!(manager.connection_stats().heartbeats_received, 1);
+        assert_eq!(heartbeats.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fatal_server_error_fails_connection() {
+        use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+        use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+        use tokio_tungstenite::tungstenite::Message;
+
+        let error = serde_json::json!({
+            "event": "pusher:error",
+            "data": {"code": 4004, "message": "Over capacity"},
+        });
+        let server = MockServer::new()
+            .without_established()
+            .then_send(error.to_string())
+            .then_send(Message::Close(Some(CloseFrame {
+                code: CloseCode::from(4004),
+                reason: "Over capacity".into(),
+            })))
+            .spawn()
+            .await;
+
+        let (error_tx, error_rx) = tokio::sync::oneshot::channel();
+        let error_tx = parking_lot::Mutex::new(Some(error_tx));
+        let options = PusherOptions::new("test-key")
+            .ws_host("127.0.0.1")
+            .ws_port(server.port)
+            .use_tls(false)
+            .on_fatal_error(move |error| {
+                if let Some(tx) = error_tx.lock().take() {
+                    let _ = tx.send(error.to_string());
+                }
+            });
+        let manager = ConnectionManager::new(Config::from(options));
+        manager.connect().await.unwrap();
+
+        let error = tokio::time::timeout(Duration::from_secs(5), error_rx)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(error, "Server error 4004: Over capacity");
+        assert_eq!(manager.state(), ConnectionState::Failed);
+
+        // The close that follows the error leaves the state alone
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(manager.state(), ConnectionState::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_url_provider_called_on_every_connect() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let mut server = MockServer::new().spawn().await;
+        let port = server.port;
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let calls_clone = calls.clone();
+        // The configured host is unreachable, so only the provider's URL works
+        let options = PusherOptions::new("test-key")
+            .ws_host("127.0.0.1")
+            .ws_port(1)
+            .use_tls(false)
+            .url_provider(move || {
+                let attempt = calls_clone.fetch_add(1, Ordering::SeqCst) + 1;
+                async move { format!("ws://127.0.0.1:{}/app/test-key?attempt={}", port, attempt) }
+            });
+        let manager = ConnectionManager::new(Config::from(options));
+
+        for attempt in 1..=2 {
+            manager.connect().await.unwrap();
+            let uri = tokio::time::timeout(Duration::from_secs(5), server.uris.recv())
+                .await
+                .expect("provider URL not used")
+                .unwrap();
+            assert_eq!(uri, format!("/app/test-key?attempt={}", attempt));
+            manager.disconnect().await;
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_send_priority_overtakes_queued_messages() {
+        let mut server = MockServer::new().spawn().await;
+
+        let options = PusherOptions::new("test-key")
+            .ws_host("127.0.0.1")
+            .ws_port(server.port)
+            .use_tls(false);
+        let manager = ConnectionManager::new(Config::from(options));
+        manager.connect().await.unwrap();
+        while !manager.is_connected() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        assert!(manager.send_priority("bulk".to_string(), MessagePriority::Low));
+        for i in 0..100 {
+            assert!(manager.send(&format!("normal-{}", i)));
+        }
+        assert!(manager.send_priority("urgent".to_string(), MessagePriority::High));
+
+        let mut received = Vec::new();
+        while received.len() < 102 {
+            let message = tokio::time::timeout(Duration::from_secs(5), server.received.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            received.push(message);
+        }
+        assert_eq!(received[0], "urgent");
+        assert_eq!(received[1], "normal-0");
+        assert_eq!(received[100], "normal-99");
+        assert_eq!(received[101], "bulk");
+    }
+
+    #[tokio::test]
+    async fn test_received_events_are_timestamped() {
+        use std::time::UNIX_EPOCH;
+
+        let event = serde_json::json!({ "event": "stamped-event", "data": "{}" });
+        let server = MockServer::new().then_send(event.to_string()).spawn().await;
+
+        let start = SystemTime::now();
+        let options = PusherOptions::new("test-key")
+            .ws_host("127.0.0.1")
+            .ws_port(server.port)
+            .use_tls(false);
+        let manager = ConnectionManager::new(Config::from(options));
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        manager
+            .bind("stamped-event", move |event| {
+                let _ = tx.send(event.clone());
+            })
+            .unwrap();
+        manager.connect().await.unwrap();
+
+        let event = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        let timestamp = event.timestamp.expect("received event should be stamped");
+        let offset = timestamp.duration_since(start).unwrap();
+        assert!(
+            offset < Duration::from_millis(100),
+            "offset was {:?}",
+            offset
+        );
+
+        let age = event.age().unwrap();
+        assert!(age < Duration::from_secs(1));
+        let millis = timestamp.duration_since(UNIX_EPOCH).unwrap().as_secs_f64() * 1000.0;
+        assert_eq!(event.received_at_ms(), Some(millis));
+        assert!(!serde_json::to_string(&event).unwrap().contains("timestamp"));
+    }
+
+    #[tokio::test]
+    async fn test_probe_waits_for_its_own_pong() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        const PONG: &str = r#"{"event":"pusher:pong","data":"{}"}"#;
+
+        // Offline, every ping reaches the sink, which answers it right away
+        // while `answer` is set
+        let manager = Arc::new(ConnectionManager::new(Config::from(PusherOptions::new(
+            "test-key",
+        ))));
+        let answer = Arc::new(AtomicBool::new(true));
+        let weak = Arc::downgrade(&manager);
+        let answer_clone = answer.clone();
+        manager.enter_offline_mode(
+            "1.1".to_string(),
+            Arc::new(move |message| {
+                if message.contains("pusher:ping") && answer_clone.load(Ordering::SeqCst) {
+                    if let Some(manager) = weak.upgrade() {
+                        manager.inject_message(PONG).unwrap();
+                    }
+                }
+            }),
+        );
+
+        // A pong nobody asked for is ignored
+        manager.inject_message(PONG).unwrap();
+        let rtt = manager.probe(Duration::from_secs(5)).await.unwrap();
+        assert!(rtt > Duration::ZERO);
+        assert!(rtt < Duration::from_millis(1), "rtt was {:?}", rtt);
+        assert_eq!(manager.ping_rtt(), Some(rtt));
+
+        // The late pong to a timed-out probe doesn't answer the next one
+        answer.store(false, Ordering::SeqCst);
+        let timeout = Duration::from_millis(50);
+        assert!(matches!(
+            manager.probe(timeout).await,
+            Err(SockudoError::ProbeTimeout { .. })
+        ));
+        let (result, _) = tokio::join!(manager.probe(timeout), async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            manager.inject_message(PONG).unwrap();
+        });
+        assert!(matches!(result, Err(SockudoError::ProbeTimeout { .. })));
+
+        // Once the earlier pings are answered, the next pong is the probe's
+        manager.inject_message(PONG).unwrap();
+        answer.store(true, Ordering::SeqCst);
+        assert!(manager.probe(Duration::from_secs(5)).await.is_ok());
+    }
+
+    #[test]
+    fn test_send_event_batch_runs_middleware() {
+        let manager = ConnectionManager::new(Config::from(PusherOptions::new("test-key")));
+        let frames = Arc::new(RwLock::new(Vec::new()));
+        let frames_clone = frames.clone();
+        manager.enter_offline_mode(
+            "1.1".to_string(),
+            Arc::new(move |message| frames_clone.write().push(message.to_string())),
+        );
+        manager.add_middleware(Arc::new(crate::RateLimitMiddleware::new(2)));
+
+        let events = (0..3)
+            .map(|i| {
+                let mut event = PusherEvent::new(format!("client-{}", i));
+                event.channel = Some("private-test".to_string());
+                event
+            })
+            .collect();
+        assert_eq!(manager.send_event_batch(events), vec![true, true, false]);
+
+        // The dropped event is left out of the single frame
+        let frames = frames.read();
+        assert_eq!(frames.len(), 1);
+        let sent: Vec<PusherEvent> = serde_json::from_str(&frames[0]).unwrap();
+        let names: Vec<_> = sent.iter().map(|e| e.event.as_str()).collect();
+        assert_eq!(names, ["client-0", "client-1"]);
+    }
+
+    #[test]
+    fn test_inject_message() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let manager = ConnectionManager::new(Config::from(PusherOptions::new("test-key")));
+        let errors = Arc::new(AtomicUsize::new(0));
+        let errors_clone = errors.clone();
+        manager
+            .bind("error", move |_| {
+                errors_clone.fetch_add(1, Ordering::SeqCst);
+            })
+            .unwrap();
+
+        manager
+            .inject_message(
+                r#"{"event":"pusher:error","data":"{\"message\":\"Over quota\",\"code\":4004}"}"#,
+            )
+            .unwrap();
+        assert_eq!(errors.load(Ordering::SeqCst), 1);
+        assert_eq!(manager.last_error().as_deref(), Some("Over quota (4004)"));
+
+        let server_errors = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let server_errors_clone = server_errors.clone();
+        manager
+            .bind("sockudo:server_error", move |event| {
+                server_errors_clone
+                    .lock()
+                    .push(event.parse_data::<serde_json::Value>().unwrap());
+            })
+            .unwrap();
+        manager
+            .inject_message(
+                r#"{"event":"pusher:error","data":"{\"message\":\"Pong reply not received\",\"code\":4201}"}"#,
+            )
+            .unwrap();
+        assert_eq!(errors.load(Ordering::SeqCst), 2);
+        // Only non-fatal errors are reported as server errors
+        assert_eq!(
+            *server_errors.lock(),
+            vec![serde_json::json!({"code": 4201, "message": "Pong reply not received"})]
+        );
+        assert!(manager.inject_message("not json").is_err());
+    }
+
+    #[test]
+    fn test_statistics_window_and_reset() {
+        let manager = ConnectionManager::new(Config::from(PusherOptions::new("test-key")));
+        manager.counters.record_sent(50);
+
+        let window = manager.start_statistics_window(Duration::from_millis(100));
+        assert_eq!(window.interval(), Duration::from_millis(100));
+        for n in 0..100 {
+            manager.counters.record_received(10);
+            if n % 4 == 0 {
+                manager.counters.record_delta();
+            }
+        }
+
+        // 100 messages over 100ms; the frame sent before the window is left out
+        let delta = window.delta_over(Duration::from_millis(100));
+        assert!((delta.messages_per_sec - 1000.0).abs() < 1e-6);
+        assert!((delta.bytes_per_sec - 10_000.0).abs() < 1e-6);
+        assert!((delta.delta_ratio - 0.25).abs() < 1e-9);
+        drop(window);
+
+        *manager.reconnect_attempts.write() = 3;
+        manager.statistics_reset();
+        assert_eq!(manager.connection_stats(), ConnectionStats::default());
+        let window = manager.start_statistics_window(Duration::from_secs(1));
+        assert_eq!(
+            window.delta(),
+            crate::connection::ConnectionStatsDelta::default()
+        );
+    }
+
+    #[test]
+    fn test_event_filter_discards_rejected_events() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let options = PusherOptions::new("test-key")
+            .event_filter(EventFilter::new(|event| event.event != "noise-event"));
+        let manager = ConnectionManager::new(Config::from(options));
+
+        let noise = Arc::new(AtomicUsize::new(0));
+        let noise_clone = noise.clone();
+        manager
+            .bind("noise-event", move |_| {
+                noise_clone.fetch_add(1, Ordering::SeqCst);
+            })
+            .unwrap();
+        let wanted = Arc::new(AtomicUsize::new(0));
+        let wanted_clone = wanted.clone();
+        manager
+            .bind("wanted-event", move |_| {
+                wanted_clone.fetch_add(1, Ordering::SeqCst);
+            })
+            .unwrap();
+
+        for name in ["noise-event", "wanted-event", "noise-event"] {
+            dispatch_filtered(
+                &manager.dispatcher,
+                &manager.event_filter,
+                &PusherEvent::new(name),
+            );
+        }
+
+        assert_eq!(noise.load(Ordering::SeqCst), 0);
+        assert_eq!(wanted.load(Ordering::SeqCst), 1);
+
+        // Clearing the filter lets everything through again
+        manager.set_event_filter(None);
+        dispatch_filtered(
+            &manager.dispatcher,
+            &manager.event_filter,
+            &PusherEvent::new("noise-event"),
+        );
+        assert_eq!(noise.load(Ordering::SeqCst), 1);
+    }
 }
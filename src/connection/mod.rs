@@ -2,6 +2,15 @@
 
 mod state;
 mod manager;
+mod stats;
+mod strategy;
 
-pub use state::ConnectionState;
-pub use manager::ConnectionManager;
+pub use state::{transition_is_valid, ConnectionState};
+pub use manager::{ConnectionManager, MessagePriority};
+pub use stats::{
+    ConnectionStats, ConnectionStatsDelta, ReconnectAttempt, StatisticsWindowGuard,
+    MAX_RECONNECT_HISTORY,
+};
+pub use strategy::{ExponentialBackoff, ReconnectStrategy};
+#[cfg(feature = "wasm")]
+pub(crate) use stats::push_reconnect_attempt;
@@ -18,20 +18,34 @@ pub enum ConnectionState {
     Unavailable,
     /// Connection strategy not supported
     Failed,
+    /// Connection kept open but not read from (e.g. app in background)
+    Paused,
 }
 
 impl ConnectionState {
-    /// Check if currently connecting or connected
+    /// Check if currently connecting, connected or paused
     pub fn is_active(&self) -> bool {
-        matches!(self, Self::Connecting | Self::Connected)
+        matches!(self, Self::Connecting | Self::Connected | Self::Paused)
     }
 
-    /// Check if connected
+    /// Check if connected. Alias for `is_healthy`.
     pub fn is_connected(&self) -> bool {
+        self.is_healthy()
+    }
+
+    /// Check if the connection is established and being read from
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, Self::Connected)
+    }
+
+    /// Check if messages can be sent in this state
+    pub fn can_send(&self) -> bool {
         matches!(self, Self::Connected)
     }
 
-    /// Check if in a terminal state
+    /// Check if in a terminal state, from which no reconnection happens
+    /// without user action: a requested disconnect, or `Failed` once the
+    /// reconnect attempts are exhausted
     pub fn is_terminal(&self) -> bool {
         matches!(self, Self::Disconnected | Self::Failed)
     }
@@ -42,6 +56,29 @@ impl ConnectionState {
     }
 }
 
+/// Whether the connection state machine may move from `from` to `to`.
+///
+/// Staying in the same state is always valid.
+pub fn transition_is_valid(from: ConnectionState, to: ConnectionState) -> bool {
+    use ConnectionState::*;
+
+    if from == to {
+        return true;
+    }
+    match (from, to) {
+        (_, Initialized) => false,
+        (Initialized | Disconnected | Failed, Connecting) => true,
+        (Connecting | Unavailable | Paused, Connected) => true,
+        (Connected, Paused) => true,
+        (Connecting | Connected | Paused, Unavailable) => true,
+        (Unavailable, Connecting) => true,
+        // A fatal server error fails an established connection outright
+        (Connecting | Connected | Unavailable, Failed) => true,
+        (_, Disconnected) => true,
+        _ => false,
+    }
+}
+
 impl Default for ConnectionState {
     fn default() -> Self {
         Self::Initialized
@@ -57,6 +94,71 @@ impl std::fmt::Display for ConnectionState {
             Self::Disconnected => write!(f, "disconnected"),
             Self::Unavailable => write!(f, "unavailable"),
             Self::Failed => write!(f, "failed"),
+            Self::Paused => write!(f, "paused"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_predicates() {
+        use ConnectionState::*;
+
+        let states = [
+            Initialized,
+            Connecting,
+            Connected,
+            Disconnected,
+            Unavailable,
+            Failed,
+            Paused,
+        ];
+        for state in states {
+            assert_eq!(state.can_send(), state == Connected, "{}", state);
+            assert_eq!(state.is_healthy(), state.is_connected(), "{}", state);
+        }
+        assert!(Failed.is_terminal());
+        assert!(!Unavailable.is_terminal());
+        assert!(Paused.is_active());
+        assert!(!Paused.is_healthy());
+    }
+
+    #[test]
+    fn test_transition_matrix() {
+        use ConnectionState::*;
+
+        let states = [
+            Initialized,
+            Connecting,
+            Connected,
+            Disconnected,
+            Unavailable,
+            Failed,
+            Paused,
+        ];
+        // Rows are `from`, columns are `to`, both in the order of `states`
+        let expected = [
+            [1, 1, 0, 1, 0, 0, 0], // Initialized
+            [0, 1, 1, 1, 1, 1, 0], // Connecting
+            [0, 0, 1, 1, 1, 1, 1], // Connected
+            [0, 1, 0, 1, 0, 0, 0], // Disconnected
+            [0, 1, 1, 1, 1, 1, 0], // Unavailable
+            [0, 1, 0, 1, 0, 1, 0], // Failed
+            [0, 0, 1, 1, 1, 0, 1], // Paused
+        ];
+        for (from, row) in states.iter().zip(expected) {
+            for (to, valid) in states.iter().zip(row) {
+                assert_eq!(
+                    transition_is_valid(*from, *to),
+                    valid == 1,
+                    "{} -> {}",
+                    from,
+                    to
+                );
+            }
         }
     }
 }
@@ -0,0 +1,314 @@
+//! Connection traffic statistics and reconnect diagnostics.
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use tracing::debug;
+
+/// Number of reconnect attempts kept for `reconnect_history`
+pub const MAX_RECONNECT_HISTORY: usize = 50;
+
+/// Snapshot of connection traffic counters
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConnectionStats {
+    /// Frames written to the socket
+    pub messages_sent: u64,
+    /// Frames received from the socket
+    pub messages_received: u64,
+    /// Bytes written to the socket
+    pub bytes_sent: u64,
+    /// Bytes received from the socket
+    pub bytes_received: u64,
+    /// Reconnect attempts since the last successful connection
+    pub reconnect_attempts: u32,
+    /// Heartbeat events received from the server
+    #[serde(default)]
+    pub heartbeats_received: u64,
+    /// `pusher:delta` messages received, included in `messages_received`
+    #[serde(default)]
+    pub delta_messages_received: u64,
+}
+
+/// Lock-free counters shared with the connection task
+#[derive(Debug, Default)]
+pub(crate) struct ConnectionCounters {
+    messages_sent: AtomicU64,
+    messages_received: AtomicU64,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    heartbeats_received: AtomicU64,
+    delta_messages_received: AtomicU64,
+}
+
+impl ConnectionCounters {
+    /// Record an outgoing frame
+    pub(crate) fn record_sent(&self, bytes: usize) {
+        self.messages_sent.fetch_add(1, Ordering::Relaxed);
+        self.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Record an incoming frame
+    pub(crate) fn record_received(&self, bytes: usize) {
+        self.messages_received.fetch_add(1, Ordering::Relaxed);
+        self.bytes_received
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Record a heartbeat from the server
+    pub(crate) fn record_heartbeat(&self) {
+        self.heartbeats_received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a `pusher:delta` message, already counted by `record_received`
+    pub(crate) fn record_delta(&self) {
+        self.delta_messages_received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Zero every counter
+    pub(crate) fn reset(&self) {
+        for counter in [
+            &self.messages_sent,
+            &self.messages_received,
+            &self.bytes_sent,
+            &self.bytes_received,
+            &self.heartbeats_received,
+            &self.delta_messages_received,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Take a snapshot of the counters
+    pub(crate) fn snapshot(&self, reconnect_attempts: u32) -> ConnectionStats {
+        ConnectionStats {
+            messages_sent: self.messages_sent.load(Ordering::Relaxed),
+            messages_received: self.messages_received.load(Ordering::Relaxed),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            reconnect_attempts,
+            heartbeats_received: self.heartbeats_received.load(Ordering::Relaxed),
+            delta_messages_received: self.delta_messages_received.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Traffic rates over a statistics window, returned by
+/// [`StatisticsWindowGuard::delta`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct ConnectionStatsDelta {
+    /// Frames sent and received per second
+    pub messages_per_sec: f64,
+    /// Bytes sent and received per second
+    pub bytes_per_sec: f64,
+    /// Share of received frames that were `pusher:delta` messages, from 0
+    /// to 1
+    pub delta_ratio: f64,
+}
+
+impl std::fmt::Display for ConnectionStatsDelta {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.1} messages/sec, {:.1} bytes/sec, {:.0}% deltas",
+            self.messages_per_sec,
+            self.bytes_per_sec,
+            self.delta_ratio * 100.0
+        )
+    }
+}
+
+/// Measures traffic rates from its creation, returned by
+/// `ConnectionManager::start_statistics_window`.
+///
+/// A periodic task can sleep for [`remaining`](Self::remaining), then log
+/// [`delta`](Self::delta) and start the next window. The final rates are
+/// logged at debug level when the guard is dropped.
+pub struct StatisticsWindowGuard {
+    counters: Arc<ConnectionCounters>,
+    start: ConnectionStats,
+    started_at: Instant,
+    interval: Duration,
+}
+
+impl StatisticsWindowGuard {
+    /// Start a window of `interval` over `counters`
+    pub(crate) fn new(counters: Arc<ConnectionCounters>, interval: Duration) -> Self {
+        Self {
+            start: counters.snapshot(0),
+            counters,
+            started_at: Instant::now(),
+            interval,
+        }
+    }
+
+    /// Intended length of the window
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Time since the window started
+    pub fn elapsed(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// Time left until the window has lasted `interval`
+    pub fn remaining(&self) -> Duration {
+        self.interval.saturating_sub(self.elapsed())
+    }
+
+    /// Rates since the window started.
+    ///
+    /// Counters reset during the window count from 0 again.
+    pub fn delta(&self) -> ConnectionStatsDelta {
+        self.delta_over(self.elapsed())
+    }
+
+    /// Rates of the traffic since the window started, spread over `elapsed`
+    pub(crate) fn delta_over(&self, elapsed: Duration) -> ConnectionStatsDelta {
+        let secs = elapsed.as_secs_f64();
+        let now = self.counters.snapshot(0);
+        let since = |counter: fn(&ConnectionStats) -> u64| {
+            counter(&now).saturating_sub(counter(&self.start))
+        };
+        let per_sec = |count: u64| {
+            if secs > 0.0 {
+                count as f64 / secs
+            } else {
+                0.0
+            }
+        };
+
+        let received = since(|stats| stats.messages_received);
+        ConnectionStatsDelta {
+            messages_per_sec: per_sec(since(|stats| stats.messages_sent) + received),
+            bytes_per_sec: per_sec(
+                since(|stats| stats.bytes_sent) + since(|stats| stats.bytes_received),
+            ),
+            delta_ratio: if received > 0 {
+                since(|stats| stats.delta_messages_received) as f64 / received as f64
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+impl Drop for StatisticsWindowGuard {
+    fn drop(&mut self) {
+        debug!(
+            "Statistics over {:.1}s: {}",
+            self.elapsed().as_secs_f64(),
+            self.delta()
+        );
+    }
+}
+
+impl std::fmt::Debug for StatisticsWindowGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StatisticsWindowGuard")
+            .field("interval", &self.interval)
+            .field("elapsed", &self.elapsed())
+            .finish()
+    }
+}
+
+/// Time spent connected, shared with the connection task
+#[derive(Debug, Default)]
+pub(crate) struct SessionClock {
+    /// When the current connection was established
+    connected_at: RwLock<Option<Instant>>,
+    /// Length of all earlier connections, in milliseconds
+    total_connected_ms: AtomicU64,
+}
+
+impl SessionClock {
+    /// Mark a connection as established now, ending any current one
+    pub(crate) fn start(&self) {
+        self.stop();
+        *self.connected_at.write() = Some(Instant::now());
+    }
+
+    /// Mark the connection as lost, adding its length to the total
+    pub(crate) fn stop(&self) {
+        if let Some(start) = self.connected_at.write().take() {
+            self.total_connected_ms
+                .fetch_add(start.elapsed().as_millis() as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// How long the current connection has been established
+    pub(crate) fn uptime(&self) -> Option<Duration> {
+        self.connected_at.read().map(|t| t.elapsed())
+    }
+
+    /// Time spent connected across all connections, including the current one
+    pub(crate) fn total(&self) -> Duration {
+        Duration::from_millis(self.total_connected_ms.load(Ordering::Relaxed))
+            + self.uptime().unwrap_or_default()
+    }
+}
+
+/// One connection attempt made while (re)connecting
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReconnectAttempt {
+    /// 1-based attempt number within the reconnect cycle
+    pub attempt_number: u32,
+    /// When the attempt started
+    pub started_at: SystemTime,
+    /// When the connection was established, if the attempt succeeded
+    pub connected_at: Option<SystemTime>,
+    /// Why the attempt failed, if it did
+    pub error: Option<String>,
+    /// Backoff delay waited before the attempt, in milliseconds
+    pub delay_before_ms: u64,
+}
+
+impl ReconnectAttempt {
+    /// Start a new, unfinished attempt
+    pub(crate) fn started(
+        attempt_number: u32,
+        started_at: SystemTime,
+        delay_before_ms: u64,
+    ) -> Self {
+        Self {
+            attempt_number,
+            started_at,
+            connected_at: None,
+            error: None,
+            delay_before_ms,
+        }
+    }
+}
+
+/// Append `attempt`, dropping the oldest entries beyond `MAX_RECONNECT_HISTORY`
+pub(crate) fn push_reconnect_attempt(
+    history: &mut VecDeque<ReconnectAttempt>,
+    attempt: ReconnectAttempt,
+) {
+    history.push_back(attempt);
+    while history.len() > MAX_RECONNECT_HISTORY {
+        history.pop_front();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconnect_history_is_bounded() {
+        let mut history = VecDeque::new();
+        for n in 1..=60 {
+            push_reconnect_attempt(
+                &mut history,
+                ReconnectAttempt::started(n, SystemTime::now(), 0),
+            );
+        }
+        assert_eq!(history.len(), MAX_RECONNECT_HISTORY);
+        assert_eq!(history.front().map(|a| a.attempt_number), Some(11));
+        assert_eq!(history.back().map(|a| a.attempt_number), Some(60));
+    }
+}
@@ -0,0 +1,84 @@
+//! Backoff strategies for retrying after a failure.
+
+use std::time::Duration;
+
+/// Decides how long to wait before each retry.
+pub trait ReconnectStrategy: Send + Sync {
+    /// Delay before retry number `attempt` (starting at 1), or `None` to
+    /// stop retrying
+    fn next_delay(&self, attempt: u32) -> Option<Duration>;
+}
+
+/// Backoff starting at `initial` and doubling up to `max`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExponentialBackoff {
+    /// Delay before the first retry
+    pub initial: Duration,
+    /// Upper bound on the delay
+    pub max: Duration,
+    /// Number of retries before giving up (0 = unlimited)
+    pub max_attempts: u32,
+}
+
+impl ExponentialBackoff {
+    /// Backoff from `initial` to `max` with unlimited retries
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            max_attempts: 0,
+        }
+    }
+
+    /// Retry after the same `delay` every time
+    pub fn fixed(delay: Duration) -> Self {
+        Self::new(delay, delay)
+    }
+
+    /// Give up after `max_attempts` retries
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+}
+
+impl Default for ExponentialBackoff {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(200), Duration::from_secs(30))
+    }
+}
+
+impl ReconnectStrategy for ExponentialBackoff {
+    fn next_delay(&self, attempt: u32) -> Option<Duration> {
+        if self.max_attempts > 0 && attempt > self.max_attempts {
+            return None;
+        }
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        Some(self.initial.saturating_mul(factor).min(self.max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exponential_backoff() {
+        let backoff =
+            ExponentialBackoff::new(Duration::from_millis(100), Duration::from_millis(500))
+                .max_attempts(5);
+        let delays: Vec<_> = (1..=6).map(|n| backoff.next_delay(n)).collect();
+        assert_eq!(
+            delays,
+            [100, 200, 400, 500, 500]
+                .into_iter()
+                .map(|ms| Some(Duration::from_millis(ms)))
+                .chain([None])
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            ExponentialBackoff::fixed(Duration::ZERO).next_delay(1000),
+            Some(Duration::ZERO)
+        );
+    }
+}
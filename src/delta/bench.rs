@@ -0,0 +1,209 @@
+//! Benchmark helpers for evaluating delta compression on real payloads.
+//!
+//! These run the same codecs the client uses, so you can check whether your
+//! message shapes benefit from delta compression before enabling it.
+
+use std::time::Instant;
+
+use super::decoders::{DeltaDecoder, FossilDeltaDecoder, Xdelta3Decoder};
+use super::types::DeltaAlgorithm;
+
+/// Result of encoding and decoding a single base/target pair
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CodecBenchResult {
+    /// Time spent creating the delta
+    pub encode_ns: u64,
+    /// Time spent applying the delta to the base
+    pub decode_ns: u64,
+    /// Size of the encoded delta
+    pub delta_bytes: usize,
+    /// Size of the full target message
+    pub target_bytes: usize,
+    /// `delta_bytes / target_bytes`; lower is better
+    pub compression_ratio: f64,
+}
+
+/// Aggregated results for a sequence of messages
+#[derive(Debug, Clone, PartialEq)]
+pub struct SequenceBenchResult {
+    /// Algorithm that was benchmarked
+    pub algorithm: DeltaAlgorithm,
+    /// Results for each successive message pair
+    pub pairs: Vec<CodecBenchResult>,
+}
+
+impl SequenceBenchResult {
+    /// Total bytes of the full messages
+    pub fn total_target_bytes(&self) -> usize {
+        self.pairs.iter().map(|r| r.target_bytes).sum()
+    }
+
+    /// Total bytes of the deltas
+    pub fn total_delta_bytes(&self) -> usize {
+        self.pairs.iter().map(|r| r.delta_bytes).sum()
+    }
+
+    /// Overall `delta_bytes / target_bytes` across the sequence
+    pub fn compression_ratio(&self) -> f64 {
+        ratio(self.total_delta_bytes(), self.total_target_bytes())
+    }
+
+    /// Mean encode time per pair
+    pub fn mean_encode_ns(&self) -> u64 {
+        mean(self.pairs.iter().map(|r| r.encode_ns), self.pairs.len())
+    }
+
+    /// Mean decode time per pair
+    pub fn mean_decode_ns(&self) -> u64 {
+        mean(self.pairs.iter().map(|r| r.decode_ns), self.pairs.len())
+    }
+}
+
+/// A collection of sequence results that can be printed as a table
+#[derive(Debug, Clone, Default)]
+pub struct DeltaBenchReport {
+    pub sequences: Vec<SequenceBenchResult>,
+}
+
+impl DeltaBenchReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a sequence result to the report
+    pub fn add(&mut self, result: SequenceBenchResult) {
+        self.sequences.push(result);
+    }
+
+    /// Render the report as an ASCII table
+    pub fn format_table(&self) -> String {
+        let mut out = String::new();
+        let separator = format!(
+            "+{:-<11}+{:-<8}+{:-<14}+{:-<14}+{:-<14}+{:-<14}+{:-<9}+\n",
+            "", "", "", "", "", "", ""
+        );
+
+        out.push_str(&separator);
+        out.push_str(&format!(
+            "| {:<9} | {:>6} | {:>12} | {:>12} | {:>12} | {:>12} | {:>7} |\n",
+            "algorithm",
+            "pairs",
+            "encode (ns)",
+            "decode (ns)",
+            "full bytes",
+            "delta bytes",
+            "ratio"
+        ));
+        out.push_str(&separator);
+
+        for seq in &self.sequences {
+            out.push_str(&format!(
+                "| {:<9} | {:>6} | {:>12} | {:>12} | {:>12} | {:>12} | {:>7.3} |\n",
+                seq.algorithm.to_string(),
+                seq.pairs.len(),
+                seq.mean_encode_ns(),
+                seq.mean_decode_ns(),
+                seq.total_target_bytes(),
+                seq.total_delta_bytes(),
+                seq.compression_ratio()
+            ));
+        }
+
+        out.push_str(&separator);
+        out
+    }
+
+    /// Print the report as an ASCII table to stdout
+    pub fn print_table(&self) {
+        print!("{}", self.format_table());
+    }
+}
+
+/// Encode `target` against `base` and decode it again, timing both steps.
+///
+/// # Panics
+///
+/// Panics if the codec fails or the round trip does not reproduce `target`,
+/// since the timings would be meaningless.
+pub fn benchmark_codec(algorithm: DeltaAlgorithm, base: &[u8], target: &[u8]) -> CodecBenchResult {
+    let start = Instant::now();
+    let delta = encode(algorithm, base, target);
+    let encode_ns = start.elapsed().as_nanos() as u64;
+
+    let start = Instant::now();
+    let decoded = match algorithm {
+        DeltaAlgorithm::Fossil => FossilDeltaDecoder::new().decode(base, &delta),
+        DeltaAlgorithm::Xdelta3 => Xdelta3Decoder::new().decode(base, &delta),
+    }
+    .expect("delta decoding failed");
+    let decode_ns = start.elapsed().as_nanos() as u64;
+
+    assert_eq!(decoded, target, "{} round trip mismatch", algorithm);
+
+    CodecBenchResult {
+        encode_ns,
+        decode_ns,
+        delta_bytes: delta.len(),
+        target_bytes: target.len(),
+        compression_ratio: ratio(delta.len(), target.len()),
+    }
+}
+
+/// Benchmark each message against its predecessor, as the server would send them.
+///
+/// # Panics
+///
+/// Panics under the same conditions as [`benchmark_codec`].
+pub fn benchmark_sequence(algorithm: DeltaAlgorithm, messages: &[&[u8]]) -> SequenceBenchResult {
+    let pairs = messages
+        .windows(2)
+        .map(|pair| benchmark_codec(algorithm, pair[0], pair[1]))
+        .collect();
+
+    SequenceBenchResult { algorithm, pairs }
+}
+
+fn encode(algorithm: DeltaAlgorithm, base: &[u8], target: &[u8]) -> Vec<u8> {
+    match algorithm {
+        DeltaAlgorithm::Fossil => fossil_delta::delta(target, base),
+        DeltaAlgorithm::Xdelta3 => xdelta3::encode(target, base).expect("xdelta3 encoding failed"),
+    }
+}
+
+fn ratio(delta_bytes: usize, target_bytes: usize) -> f64 {
+    if target_bytes == 0 {
+        return 1.0;
+    }
+    delta_bytes as f64 / target_bytes as f64
+}
+
+fn mean(values: impl Iterator<Item = u64>, count: usize) -> u64 {
+    if count == 0 {
+        return 0;
+    }
+    values.sum::<u64>() / count as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_benchmark_sequence_fossil() {
+        let messages: [&[u8]; 3] = [
+            br#"{"symbol":"BTC","price":100}"#,
+            br#"{"symbol":"BTC","price":101}"#,
+            br#"{"symbol":"BTC","price":102}"#,
+        ];
+
+        let result = benchmark_sequence(DeltaAlgorithm::Fossil, &messages);
+        assert_eq!(result.pairs.len(), 2);
+        assert_eq!(result.total_target_bytes(), messages[1].len() * 2);
+
+        let mut report = DeltaBenchReport::new();
+        report.add(result);
+        let table = report.format_table();
+        assert!(table.contains("fossil"));
+        assert_eq!(table.lines().count(), 5);
+    }
+}
@@ -1,5 +1,6 @@
 //! Delta compression decoders.
 
+use super::types::DeltaAlgorithm;
 use crate::error::{Result, SockudoError};
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 
@@ -11,23 +12,81 @@ pub trait DeltaDecoder: Send + Sync {
     /// Get the algorithm name
     fn algorithm(&self) -> &'static str;
 
+    /// Get the algorithm name as negotiated with the server.
+    ///
+    /// Prefer this over `algorithm`, which remains for existing implementors.
+    fn algorithm_name(&self) -> &str {
+        self.algorithm()
+    }
+
     /// Check if the decoder is available
     fn is_available(&self) -> bool {
         true
     }
 }
 
+/// Trait for decoders that accept a delta in chunks
+///
+/// Call `begin` with the base message, `feed` each chunk as it arrives and
+/// `finish` once the delta is complete. The output of every `feed` call and
+/// of `finish`, concatenated, equals the result of a single `decode` call.
+pub trait StreamingDeltaDecoder {
+    /// Start decoding a new delta against `base`
+    fn begin(&mut self, base: &[u8]);
+
+    /// Feed the next chunk of the delta, returning any output produced so far
+    fn feed(&mut self, chunk: &[u8]) -> Result<Vec<u8>>;
+
+    /// Complete decoding and return the remaining output
+    fn finish(&mut self) -> Result<Vec<u8>>;
+}
+
+/// Buffered state for an in-progress streaming decode
+#[derive(Debug, Clone, Default)]
+struct StreamState {
+    base: Vec<u8>,
+    delta: Vec<u8>,
+    active: bool,
+}
+
+impl StreamState {
+    fn begin(&mut self, base: &[u8]) {
+        self.base = base.to_vec();
+        self.delta.clear();
+        self.active = true;
+    }
+
+    fn push(&mut self, chunk: &[u8]) -> Result<()> {
+        if !self.active {
+            return Err(SockudoError::delta("Streaming decode not started"));
+        }
+        self.delta.extend_from_slice(chunk);
+        Ok(())
+    }
+
+    fn take(&mut self) -> Result<(Vec<u8>, Vec<u8>)> {
+        if !self.active {
+            return Err(SockudoError::delta("Streaming decode not started"));
+        }
+        self.active = false;
+        Ok((
+            std::mem::take(&mut self.base),
+            std::mem::take(&mut self.delta),
+        ))
+    }
+}
+
 /// Fossil Delta decoder
 ///
 /// Uses the fossil-delta crate for efficient binary delta decoding.
 #[derive(Debug, Clone, Default)]
 pub struct FossilDeltaDecoder {
-    _private: (),
+    stream: StreamState,
 }
 
 impl FossilDeltaDecoder {
     pub fn new() -> Self {
-        Self { _private: () }
+        Self::default()
     }
 }
 
@@ -42,15 +101,33 @@ impl DeltaDecoder for FossilDeltaDecoder {
     }
 }
 
+impl StreamingDeltaDecoder for FossilDeltaDecoder {
+    fn begin(&mut self, base: &[u8]) {
+        self.stream.begin(base);
+    }
+
+    fn feed(&mut self, chunk: &[u8]) -> Result<Vec<u8>> {
+        // Fossil deltas can only be applied once complete
+        self.stream.push(chunk)?;
+        Ok(Vec::new())
+    }
+
+    fn finish(&mut self) -> Result<Vec<u8>> {
+        let (base, delta) = self.stream.take()?;
+        self.decode(&base, &delta)
+    }
+}
+
 /// Xdelta3/VCDIFF decoder
 #[derive(Debug, Clone, Default)]
 pub struct Xdelta3Decoder {
-    _private: (),
+    stream: StreamState,
+    windows: VcdiffWindows,
 }
 
 impl Xdelta3Decoder {
     pub fn new() -> Self {
-        Self { _private: () }
+        Self::default()
     }
 }
 
@@ -70,6 +147,162 @@ impl DeltaDecoder for Xdelta3Decoder {
     }
 }
 
+impl StreamingDeltaDecoder for Xdelta3Decoder {
+    fn begin(&mut self, base: &[u8]) {
+        self.stream.begin(base);
+        self.windows = VcdiffWindows::default();
+    }
+
+    fn feed(&mut self, chunk: &[u8]) -> Result<Vec<u8>> {
+        self.stream.push(chunk)?;
+
+        // Each complete window that only copies from the base is decoded on
+        // its own, behind a copy of the file header
+        let mut output = Vec::new();
+        while let Some(window) = self.windows.next_window(&self.stream.delta)? {
+            let header = &self.stream.delta[..self.windows.header_len];
+            let encoded = [header, &self.stream.delta[window]].concat();
+            let decoded = self.decode(&self.stream.base, &encoded)?;
+            self.windows.emitted += decoded.len();
+            output.extend(decoded);
+        }
+        Ok(output)
+    }
+
+    fn finish(&mut self) -> Result<Vec<u8>> {
+        let (base, delta) = self.stream.take()?;
+        let windows = std::mem::take(&mut self.windows);
+        if windows.header_len > 0 && windows.consumed == delta.len() {
+            return Ok(Vec::new());
+        }
+
+        // Windows that copy from earlier target output, and truncated or
+        // malformed deltas, are decoded in one call; only the output not
+        // already returned by `feed` is returned here
+        let decoded = self.decode(&base, &delta)?;
+        decoded
+            .get(windows.emitted..)
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| SockudoError::delta("VCDIFF output shorter than streamed windows"))
+    }
+}
+
+/// VCDIFF file header magic (RFC 3284)
+const VCDIFF_MAGIC: [u8; 4] = [0xd6, 0xc3, 0xc4, 0x00];
+
+/// Progress through the windows of a VCDIFF delta being streamed
+#[derive(Debug, Clone, Default)]
+struct VcdiffWindows {
+    /// Length of the file header, or 0 until it has been read
+    header_len: usize,
+    /// Bytes of the delta taken up by the header and windows decoded so far
+    consumed: usize,
+    /// Bytes of output returned so far
+    emitted: usize,
+    /// Set once a window copies from the target, after which windows can no
+    /// longer be decoded independently
+    buffering: bool,
+}
+
+impl VcdiffWindows {
+    /// Return the byte range of the next complete window that can be decoded
+    /// on its own, or `None` if more input is needed
+    fn next_window(&mut self, delta: &[u8]) -> Result<Option<std::ops::Range<usize>>> {
+        if self.buffering {
+            return Ok(None);
+        }
+        if self.header_len == 0 {
+            let Some(len) = vcdiff_header_len(delta)? else {
+                return Ok(None);
+            };
+            self.header_len = len;
+            self.consumed = len;
+        }
+
+        let start = self.consumed;
+        let mut pos = start;
+        let Some(&indicator) = delta.get(pos) else {
+            return Ok(None);
+        };
+        pos += 1;
+        // VCD_TARGET: the window copies from target data already produced
+        if indicator & 0x02 != 0 {
+            self.buffering = true;
+            return Ok(None);
+        }
+        // VCD_SOURCE: source segment size and position
+        if indicator & 0x01 != 0 {
+            for _ in 0..2 {
+                if read_varint(delta, &mut pos)?.is_none() {
+                    return Ok(None);
+                }
+            }
+        }
+        let Some(len) = read_varint(delta, &mut pos)? else {
+            return Ok(None);
+        };
+        let end = pos
+            .checked_add(len)
+            .ok_or_else(|| SockudoError::delta("VCDIFF window too large"))?;
+        if end > delta.len() {
+            return Ok(None);
+        }
+        self.consumed = end;
+        Ok(Some(start..end))
+    }
+}
+
+/// Length of the VCDIFF file header at the start of `delta`, or `None` if
+/// it is not complete yet
+fn vcdiff_header_len(delta: &[u8]) -> Result<Option<usize>> {
+    let magic_len = VCDIFF_MAGIC.len().min(delta.len());
+    if delta[..magic_len] != VCDIFF_MAGIC[..magic_len] {
+        return Err(SockudoError::delta("Invalid VCDIFF header"));
+    }
+    let mut pos = VCDIFF_MAGIC.len();
+    let Some(&indicator) = delta.get(pos) else {
+        return Ok(None);
+    };
+    pos += 1;
+    // VCD_DECOMPRESS: secondary compressor ID
+    if indicator & 0x01 != 0 {
+        pos += 1;
+    }
+    // VCD_CODETABLE, then the xdelta3 application header
+    for flag in [0x02, 0x04] {
+        if indicator & flag != 0 {
+            let Some(len) = read_varint(delta, &mut pos)? else {
+                return Ok(None);
+            };
+            pos = pos
+                .checked_add(len)
+                .ok_or_else(|| SockudoError::delta("VCDIFF header too large"))?;
+        }
+    }
+    Ok((pos <= delta.len()).then_some(pos))
+}
+
+/// Read a VCDIFF variable-length integer at `pos`, advancing past it, or
+/// return `None` if it is not complete yet
+fn read_varint(buf: &[u8], pos: &mut usize) -> Result<Option<usize>> {
+    let mut value: usize = 0;
+    let mut end = *pos;
+    loop {
+        let Some(&byte) = buf.get(end) else {
+            return Ok(None);
+        };
+        end += 1;
+        value = value
+            .checked_mul(128)
+            .map(|value| value | usize::from(byte & 0x7f))
+            .ok_or_else(|| SockudoError::delta("VCDIFF integer overflow"))?;
+        if byte & 0x80 == 0 {
+            *pos = end;
+            return Ok(Some(value));
+        }
+    }
+}
+
 /// Utility functions for base64 encoding/decoding
 pub fn decode_base64(input: &str) -> Result<Vec<u8>> {
     BASE64
@@ -81,19 +314,41 @@ pub fn encode_base64(input: &[u8]) -> String {
     BASE64.encode(input)
 }
 
-/// Get a decoder for the specified algorithm
+/// Get a decoder for the specified algorithm, if it is available in this build
 pub fn get_decoder(algorithm: &str) -> Option<Box<dyn DeltaDecoder>> {
-    match algorithm.to_lowercase().as_str() {
-        "fossil" => Some(Box::new(FossilDeltaDecoder::new())),
-        "xdelta3" | "vcdiff" => {
-            let decoder = Xdelta3Decoder::new();
-            if decoder.is_available() {
-                Some(Box::new(decoder))
-            } else {
-                None
-            }
-        }
-        _ => None,
+    let decoder: Box<dyn DeltaDecoder> = match algorithm.parse().ok()? {
+        DeltaAlgorithm::Fossil => Box::new(FossilDeltaDecoder::new()),
+        DeltaAlgorithm::Xdelta3 => Box::new(Xdelta3Decoder::new()),
+    };
+    decoder.is_available().then_some(decoder)
+}
+
+/// Names of the decoders compiled into this build
+pub fn list_available() -> Vec<&'static str> {
+    [DeltaAlgorithm::Fossil, DeltaAlgorithm::Xdelta3]
+        .iter()
+        .filter_map(|algorithm| get_decoder(&algorithm.to_string()))
+        .map(|decoder| decoder.algorithm())
+        .collect()
+}
+
+/// Fail with a configuration error naming any of `algorithms` that
+/// `list_available` does not include
+pub fn check_available(algorithms: &[DeltaAlgorithm]) -> Result<()> {
+    let available = list_available();
+    let missing: Vec<String> = algorithms
+        .iter()
+        .map(|algorithm| algorithm.to_string())
+        .filter(|name| !available.contains(&name.as_str()))
+        .collect();
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(SockudoError::config(format!(
+            "Delta algorithms not available in this build: {}",
+            missing.join(", ")
+        )))
     }
 }
 
@@ -145,9 +400,100 @@ mod tests {
         assert_eq!(result, target);
     }
 
+    #[test]
+    fn test_streaming_matches_single_call() {
+        let base = b"The quick brown fox jumps over the lazy dog";
+        let target = b"The quick brown fox leaps over the very lazy dog!";
+        let delta = fossil_delta::delta(target, base);
+
+        let mut decoder = FossilDeltaDecoder::new();
+        let expected = decoder.decode(base, &delta).unwrap();
+
+        decoder.begin(base);
+        let mut output = Vec::new();
+        for chunk in delta.chunks(10) {
+            output.extend(decoder.feed(chunk).unwrap());
+        }
+        output.extend(decoder.finish().unwrap());
+
+        assert_eq!(output, expected);
+    }
+
+    /// A VCDIFF delta turning "Hello, World!" into "Hello, Rust World!" in
+    /// two windows that each copy from the base
+    const TWO_WINDOW_VCDIFF: &[u8] = &[
+        0xd6, 0xc3, 0xc4, 0x00, 0x00, // header
+        0x01, 13, 0, 13, // VCD_SOURCE window over base[0..13]
+        12, 0, 5, 2, 1, b'R', b'u', b's', b't', b' ', 23, 6, 0, // COPY 7 @0, ADD "Rust "
+        0x01, 6, 7, 7, // VCD_SOURCE window over base[7..13]
+        6, 0, 0, 1, 1, 22, 0, // COPY 6 @0
+    ];
+
+    #[test]
+    fn test_vcdiff_window_boundaries() {
+        let mut windows = VcdiffWindows::default();
+        assert_eq!(windows.next_window(&TWO_WINDOW_VCDIFF[..8]).unwrap(), None);
+        assert_eq!(windows.header_len, 5);
+        assert_eq!(windows.next_window(TWO_WINDOW_VCDIFF).unwrap(), Some(5..22));
+        assert_eq!(
+            windows.next_window(TWO_WINDOW_VCDIFF).unwrap(),
+            Some(22..33)
+        );
+        assert_eq!(windows.next_window(TWO_WINDOW_VCDIFF).unwrap(), None);
+        assert_eq!(windows.consumed, TWO_WINDOW_VCDIFF.len());
+
+        // Windows copying from the target are left for `finish`
+        let mut windows = VcdiffWindows::default();
+        let target_window = [&TWO_WINDOW_VCDIFF[..5], &[0x02, 6, 0, 7]].concat();
+        assert_eq!(windows.next_window(&target_window).unwrap(), None);
+        assert!(windows.buffering);
+
+        assert!(VcdiffWindows::default().next_window(b"{\"x\"").is_err());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_xdelta3_streams_each_window() {
+        let base = b"Hello, World!";
+        let mut decoder = Xdelta3Decoder::new();
+        let expected = decoder.decode(base, TWO_WINDOW_VCDIFF).unwrap();
+        assert_eq!(expected, b"Hello, Rust World!");
+
+        decoder.begin(base);
+        let mut chunks = Vec::new();
+        for chunk in TWO_WINDOW_VCDIFF.chunks(10) {
+            chunks.push(decoder.feed(chunk).unwrap());
+        }
+        chunks.push(decoder.finish().unwrap());
+
+        // The first window is output as soon as its last byte arrives
+        assert_eq!(chunks[2], b"Hello, Rust ");
+        assert_eq!(chunks.concat(), expected);
+    }
+
+    #[test]
+    fn test_streaming_requires_begin() {
+        let mut decoder = Xdelta3Decoder::new();
+        assert!(decoder.feed(b"chunk").is_err());
+        assert!(decoder.finish().is_err());
+    }
+
     #[test]
     fn test_xdelta3_decoder_availability() {
         let decoder = Xdelta3Decoder::new();
         assert!(decoder.is_available());
     }
+
+    #[test]
+    fn test_list_available() {
+        let available = list_available();
+        assert!(available.contains(&"fossil"));
+        // Every listed decoder can be retrieved and reports the same name
+        for name in &available {
+            assert_eq!(get_decoder(name).unwrap().algorithm_name(), *name);
+        }
+        assert!(get_decoder("vcdiff").is_some());
+        assert!(get_decoder("brotli").is_none());
+        assert!(check_available(&[DeltaAlgorithm::Fossil]).is_ok());
+    }
 }
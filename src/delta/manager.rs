@@ -21,6 +21,10 @@ pub struct DeltaManager {
     options: DeltaOptions,
     /// Whether delta compression is enabled
     enabled: RwLock<bool>,
+    /// Algorithm the server chose in `pusher:delta_compression_enabled`
+    active_algorithm: RwLock<Option<DeltaAlgorithm>>,
+    /// Algorithms used for a channel's deltas whatever the message says
+    channel_algorithms: RwLock<HashMap<String, DeltaAlgorithm>>,
     /// Per-channel state
     channel_states: RwLock<HashMap<String, Arc<ChannelState>>>,
     /// Global statistics
@@ -29,6 +33,9 @@ pub struct DeltaManager {
     decoders: HashMap<String, Box<dyn DeltaDecoder>>,
     /// Callback for sending events
     send_event: Option<SendEventFn>,
+    /// Saves full-message bases to `DeltaOptions::cache_persistence`
+    #[cfg(feature = "delta-persist")]
+    base_saver: Option<super::persistence::BaseSaver>,
 }
 
 impl DeltaManager {
@@ -41,14 +48,31 @@ impl DeltaManager {
             decoders.insert("fossil".to_string(), Box::new(FossilDeltaDecoder::new()));
         }
 
-        Self {
+        #[cfg(feature = "delta-persist")]
+        let base_saver = options.cache_persistence.clone().map(|persistence| {
+            super::persistence::BaseSaver::new(persistence, options.on_error.clone())
+        });
+
+        let manager = Self {
             options,
             enabled: RwLock::new(false),
+            active_algorithm: RwLock::new(None),
+            channel_algorithms: RwLock::new(HashMap::new()),
             channel_states: RwLock::new(HashMap::new()),
             stats: RwLock::new(DeltaStats::default()),
             decoders,
             send_event: None,
+            #[cfg(feature = "delta-persist")]
+            base_saver,
+        };
+
+        // Restore the bases persisted by a previous session
+        #[cfg(feature = "delta-persist")]
+        if manager.options.cache_persistence.is_some() {
+            manager.cache_warmup(HashMap::new());
         }
+
+        manager
     }
 
     /// Set the send event callback
@@ -56,6 +80,16 @@ impl DeltaManager {
         self.send_event = Some(callback);
     }
 
+    /// Replace the callback called for each delta message decoded
+    pub fn set_on_delta_decoded(&mut self, callback: Option<DeltaDecodedFn>) {
+        self.options.on_delta_decoded = callback;
+    }
+
+    /// Replace the callback called for each full message stored as a base
+    pub fn set_on_full_message(&mut self, callback: Option<FullMessageFn>) {
+        self.options.on_full_message = callback;
+    }
+
     /// Get available algorithms
     pub fn available_algorithms(&self) -> Vec<DeltaAlgorithm> {
         self.decoders
@@ -87,10 +121,19 @@ impl DeltaManager {
             return;
         }
 
-        // Filter to only algorithms we want
-        let supported: Vec<_> = available
+        // Filter to only algorithms we want, preferred algorithm first
+        let preferred = self
+            .options
+            .preferred_algorithm
+            .filter(|a| available.contains(a) && self.options.algorithms.contains(a));
+        let supported: Vec<_> = preferred
             .iter()
-            .filter(|a| self.options.algorithms.contains(a))
+            .chain(
+                self.options
+                    .algorithms
+                    .iter()
+                    .filter(|a| available.contains(a) && Some(**a) != preferred),
+            )
             .map(|a| a.to_string())
             .collect();
 
@@ -105,7 +148,10 @@ impl DeltaManager {
         );
 
         if let Some(ref send) = self.send_event {
-            let data = serde_json::json!({ "algorithms": supported });
+            let mut data = serde_json::json!({ "algorithms": supported });
+            if let Some(preferred) = preferred {
+                data["preferred_algorithm"] = Value::String(preferred.to_string());
+            }
             send("pusher:enable_delta_compression", &data);
         }
     }
@@ -113,6 +159,7 @@ impl DeltaManager {
     /// Disable delta compression
     pub fn disable(&self) {
         *self.enabled.write() = false;
+        *self.active_algorithm.write() = None;
         self.channel_states.write().clear();
     }
 
@@ -122,9 +169,40 @@ impl DeltaManager {
     /// Note: Some servers use per-channel delta config and may not send this event.
     pub fn handle_enabled(&self, data: &Value) {
         *self.enabled.write() = true;
+
+        let algorithm = data
+            .get("algorithm")
+            .and_then(|v| v.as_str())
+            .and_then(|a| match a.parse::<DeltaAlgorithm>() {
+                Ok(algorithm) => Some(algorithm),
+                Err(e) => {
+                    warn!("Server chose an unsupported delta algorithm: {}", e);
+                    None
+                }
+            });
+        *self.active_algorithm.write() = algorithm;
+
         debug!("Delta compression enabled: {:?}", data);
     }
 
+    /// Algorithm the server chose for this connection, if it reported one
+    pub fn active_algorithm(&self) -> Option<DeltaAlgorithm> {
+        *self.active_algorithm.read()
+    }
+
+    /// Decode every delta on `channel` with `algorithm`, ignoring the
+    /// algorithm named in the delta message
+    pub fn set_channel_algorithm(&self, channel: &str, algorithm: DeltaAlgorithm) {
+        self.channel_algorithms
+            .write()
+            .insert(channel.to_string(), algorithm);
+    }
+
+    /// Algorithm set for `channel` with `set_channel_algorithm`, if any
+    pub fn get_channel_algorithm(&self, channel: &str) -> Option<DeltaAlgorithm> {
+        self.channel_algorithms.read().get(channel).copied()
+    }
+
     /// Handle cache sync message
     pub fn handle_cache_sync(&self, channel: &str, data: CacheSyncData) {
         debug!(
@@ -150,6 +228,93 @@ impl DeltaManager {
         debug!("Cache sync complete. Groups: {}", state.group_count());
     }
 
+    /// Pre-populate the base-message cache, e.g. after a process restart.
+    ///
+    /// Keys are channel names and values the last full message received on
+    /// that channel, as returned by `cache_snapshot`. Entries that are not
+    /// valid UTF-8 are skipped. With `DeltaOptions::cache_persistence`,
+    /// channels that have neither state nor an entry are also warmed up
+    /// from their persisted base.
+    pub fn cache_warmup(&self, entries: HashMap<String, Vec<u8>>) {
+        #[cfg(feature = "delta-persist")]
+        let entries = self.with_persisted_bases(entries);
+
+        let mut states = self.channel_states.write();
+        for (channel, bytes) in entries {
+            let content = match String::from_utf8(bytes) {
+                Ok(content) => content,
+                Err(e) => {
+                    warn!("Skipping cache warmup for channel {}: {}", channel, e);
+                    continue;
+                }
+            };
+            let state = ChannelState::new(&channel);
+            state.set_base(content, 0);
+            states.insert(channel, Arc::new(state));
+        }
+        debug!("Delta cache warmed up for {} channels", states.len());
+    }
+
+    /// Warm up the cache from base64-encoded entries, as stored in
+    /// `SockudoOptions::delta_cache_warmup`.
+    pub fn cache_warmup_base64(&self, entries: &HashMap<String, String>) -> Result<()> {
+        let decoded = entries
+            .iter()
+            .map(|(channel, encoded)| Ok((channel.clone(), decode_base64(encoded)?)))
+            .collect::<Result<HashMap<_, _>>>()?;
+        self.cache_warmup(decoded);
+        Ok(())
+    }
+
+    /// Export the latest base message of every channel for persistence.
+    ///
+    /// The map can be stored with `serde` and handed back to `cache_warmup`.
+    pub fn cache_snapshot(&self) -> HashMap<String, Vec<u8>> {
+        self.channel_states
+            .read()
+            .iter()
+            .filter_map(|(channel, state)| {
+                state
+                    .get_base(None, None)
+                    .map(|base| (channel.clone(), base.into_bytes()))
+            })
+            .collect()
+    }
+
+    /// Add the persisted base of every channel that has neither state nor
+    /// an entry in `entries`
+    #[cfg(feature = "delta-persist")]
+    fn with_persisted_bases(
+        &self,
+        mut entries: HashMap<String, Vec<u8>>,
+    ) -> HashMap<String, Vec<u8>> {
+        let Some(ref persistence) = self.options.cache_persistence else {
+            return entries;
+        };
+        let channels = match persistence.channels() {
+            Ok(channels) => channels,
+            Err(e) => {
+                warn!("{}", e);
+                return entries;
+            }
+        };
+        let states = self.channel_states.read();
+        for channel in channels {
+            if states.contains_key(&channel) || entries.contains_key(&channel) {
+                continue;
+            }
+            match persistence.load(&channel) {
+                Ok(Some(base)) => {
+                    debug!("Restoring persisted delta base for channel {}", channel);
+                    entries.insert(channel, base);
+                }
+                Ok(None) => {}
+                Err(e) => warn!("{}", e),
+            }
+        }
+        entries
+    }
+
     /// Handle a delta message
     pub fn handle_delta(&self, channel: &str, delta_msg: DeltaMessage) -> Result<PusherEvent> {
         let states = self.channel_states.read();
@@ -169,15 +334,6 @@ impl DeltaManager {
                 SockudoError::delta(err)
             })?;
 
-        // Determine algorithm
-        let algo = delta_msg.algorithm.as_deref().unwrap_or("fossil");
-        let decoder = self.decoders.get(algo).ok_or_else(|| {
-            let err = format!("Unknown algorithm: {}", algo);
-            self.emit_error(&err);
-            self.stats.write().errors += 1;
-            SockudoError::delta(err)
-        })?;
-
         // Decode the delta
         let delta_bytes = decode_base64(&delta_msg.delta).map_err(|e| {
             let err = format!("Base64 decode error: {}", e);
@@ -185,20 +341,13 @@ impl DeltaManager {
             self.stats.write().errors += 1;
             e
         })?;
-        let base_bytes = base.as_bytes();
-
-        let decoded = decoder.decode(base_bytes, &delta_bytes).map_err(|e| {
-            let err = format!("Delta decode error: {}", e);
-            self.emit_error(&err);
-            self.stats.write().errors += 1;
-            e
-        })?;
-        let content = String::from_utf8(decoded).map_err(|e| {
-            let err = format!("Invalid UTF-8: {}", e);
-            self.emit_error(&err);
-            self.stats.write().errors += 1;
-            SockudoError::delta(err)
-        })?;
+        let channel_algorithm = self.get_channel_algorithm(channel).map(|a| a.to_string());
+        let algorithm = channel_algorithm
+            .as_deref()
+            .or(delta_msg.algorithm.as_deref())
+            .unwrap_or("fossil");
+        let (algo, content) =
+            self.decode_with_fallback(algorithm, base.as_bytes(), &delta_bytes)?;
 
         // Update statistics
         let compressed_size = delta_msg.delta.len();
@@ -220,6 +369,9 @@ impl DeltaManager {
 
         // Emit stats update
         self.emit_stats();
+        if let Some(ref callback) = self.options.on_delta_decoded {
+            callback(channel, &algo, compressed_size, decompressed_size);
+        }
 
         // Parse content as JSON and create event
         let data: Value = serde_json::from_str(&content).unwrap_or(Value::String(content.clone()));
@@ -239,6 +391,69 @@ impl DeltaManager {
         Ok(event)
     }
 
+    /// Decode `delta` with `algorithm`, returning the algorithm that succeeded
+    /// and the decoded content.
+    ///
+    /// With `algorithm_switch_on_error`, a failed decode is retried with the
+    /// other configured algorithms in `DeltaOptions::algorithms` order, up to
+    /// `max_fallback_attempts` times. The error is only reported once every
+    /// attempt has failed.
+    fn decode_with_fallback(
+        &self,
+        algorithm: &str,
+        base: &[u8],
+        delta: &[u8],
+    ) -> Result<(String, String)> {
+        let mut last_error = match self.decode_as(algorithm, base, delta) {
+            Ok(content) => return Ok((algorithm.to_string(), content)),
+            Err(e) => e,
+        };
+
+        if self.options.algorithm_switch_on_error {
+            let fallbacks = self
+                .options
+                .algorithms
+                .iter()
+                .map(|a| a.to_string())
+                .filter(|a| a != algorithm)
+                .take(self.options.max_fallback_attempts as usize);
+            for fallback in fallbacks {
+                debug!("Retrying {} delta as {}", algorithm, fallback);
+                self.stats.write().algorithm_fallbacks += 1;
+                match self.decode_as(&fallback, base, delta) {
+                    Ok(content) => return Ok((fallback, content)),
+                    Err(e) => last_error = e,
+                }
+            }
+        }
+
+        let (message, error) = last_error;
+        self.emit_error(&message);
+        self.stats.write().errors += 1;
+        Err(error)
+    }
+
+    /// Decode `delta` against `base` with a single algorithm, returning the
+    /// message to report along with the error on failure
+    fn decode_as(
+        &self,
+        algorithm: &str,
+        base: &[u8],
+        delta: &[u8],
+    ) -> std::result::Result<String, (String, SockudoError)> {
+        let decoder = self.decoders.get(algorithm).ok_or_else(|| {
+            let err = format!("Unknown algorithm: {}", algorithm);
+            (err.clone(), SockudoError::delta(err))
+        })?;
+        let decoded = decoder
+            .decode(base, delta)
+            .map_err(|e| (format!("Delta decode error: {}", e), e))?;
+        String::from_utf8(decoded).map_err(|e| {
+            let err = format!("Invalid UTF-8: {}", e);
+            (err.clone(), SockudoError::delta(err))
+        })
+    }
+
     /// Handle a full message (for tracking and caching)
     pub fn handle_full_message(&self, channel: &str, event: &PusherEvent, sequence: u64) {
         let mut states = self.channel_states.write();
@@ -298,7 +513,16 @@ impl DeltaManager {
             stats.total_bytes_with_compression += message_size as u64;
         }
 
+        drop(states);
+        #[cfg(feature = "delta-persist")]
+        if let Some(ref saver) = self.base_saver {
+            saver.save(channel, &content);
+        }
+
         self.emit_stats();
+        if let Some(ref callback) = self.options.on_full_message {
+            callback(channel, message_size);
+        }
     }
 
     /// Request resync for a channel
@@ -314,6 +538,28 @@ impl DeltaManager {
         self.channel_states.write().remove(channel);
     }
 
+    /// Discard the base messages for a channel and ask the server to resend
+    /// a full message.
+    ///
+    /// Use this when application code detects inconsistent data. The server
+    /// must support the `pusher:delta_resync_request` event.
+    pub fn force_resync(&self, channel: &str) {
+        debug!("Forcing resync for channel: {}", channel);
+
+        self.channel_states.write().remove(channel);
+        self.stats.write().resyncs_requested += 1;
+
+        if let Some(ref send) = self.send_event {
+            let data = serde_json::json!({ "channel": channel });
+            send("pusher:delta_resync_request", &data);
+        }
+    }
+
+    /// Count a message dropped because its debug checksum did not match
+    pub fn record_checksum_error(&self) {
+        self.stats.write().checksum_errors += 1;
+    }
+
     /// Get current statistics
     pub fn get_stats(&self) -> DeltaStats {
         let mut stats = self.stats.read().clone();
@@ -328,6 +574,7 @@ impl DeltaManager {
 
         stats.channel_count = channel_stats.len() as u64;
         stats.channels = channel_stats;
+        stats.active_algorithm = self.active_algorithm().map(|a| a.to_string());
 
         stats
     }
@@ -337,6 +584,12 @@ impl DeltaManager {
         self.stats.write().reset();
     }
 
+    /// Write the CSV header and one row with the current statistics
+    pub fn export_stats_csv(&self, writer: &mut impl std::io::Write) -> std::io::Result<()> {
+        writeln!(writer, "{}", DeltaStats::csv_header())?;
+        writeln!(writer, "{}", self.get_stats().to_csv_row())
+    }
+
     /// Clear state for a specific channel
     pub fn clear_channel(&self, channel: &str) {
         self.channel_states.write().remove(channel);
@@ -372,6 +625,7 @@ impl std::fmt::Debug for DeltaManager {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("DeltaManager")
             .field("enabled", &*self.enabled.read())
+            .field("active_algorithm", &self.active_algorithm())
             .field("channel_count", &self.channel_states.read().len())
             .field("algorithms", &self.available_algorithms())
             .finish()
@@ -406,6 +660,389 @@ mod tests {
         assert_eq!(stats.total_messages, 1);
     }
 
+    #[test]
+    fn test_stats_json_and_csv_export() {
+        let manager = DeltaManager::new(DeltaOptions::default());
+        let event =
+            PusherEvent::new("test-event").with_json_data(serde_json::json!({"price": 100}));
+        manager.handle_full_message("test-channel", &event, 1);
+
+        let json = manager.get_stats().as_json();
+        assert_eq!(json["total_messages"].as_u64(), Some(1));
+        assert_eq!(json["full_messages"].as_u64(), Some(1));
+        assert!(json["bandwidth_saved_percent"].is_f64());
+        assert!(json["channels"].is_array());
+        assert!(json["active_algorithm"].is_null());
+
+        let columns = DeltaStats::csv_header().split(',').count();
+        assert_eq!(columns, 9);
+        assert_eq!(manager.get_stats().to_csv_row().split(',').count(), columns);
+
+        let mut out = Vec::new();
+        manager.export_stats_csv(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines,
+            vec![DeltaStats::csv_header(), "1,0,1,13,13,0,0.00,0,1"]
+        );
+    }
+
+    #[test]
+    fn test_stats_merge() {
+        let stats = |messages: u64, without: u64, with: u64, channels: u64| {
+            let mut stats = DeltaStats {
+                total_messages: messages,
+                delta_messages: messages / 2,
+                full_messages: messages - messages / 2,
+                total_bytes_without_compression: without,
+                total_bytes_with_compression: with,
+                errors: 1,
+                channel_count: channels,
+                ..Default::default()
+            };
+            stats.calculate_savings();
+            stats
+        };
+        let a = stats(10, 1000, 500, 3);
+        let b = stats(4, 3000, 2700, 5);
+
+        let identity = DeltaStats::merged(&DeltaStats::default(), &a);
+        assert_eq!(identity.as_json(), a.as_json());
+
+        let merged = DeltaStats::merged(&a, &b);
+        assert_eq!(merged.total_messages, 14);
+        assert_eq!(merged.delta_messages, 7);
+        assert_eq!(merged.full_messages, 7);
+        assert_eq!(merged.total_bytes_without_compression, 4000);
+        assert_eq!(merged.total_bytes_with_compression, 3200);
+        assert_eq!(merged.bandwidth_saved, 800);
+        assert_eq!(merged.errors, 2);
+        assert_eq!(merged.channel_count, 5);
+        // 800 of 4000 bytes, not the average of 50% and 10%
+        assert!((merged.bandwidth_saved_percent - 20.0).abs() < f64::EPSILON);
+
+        let all = DeltaStats::merge_all([a.clone(), b.clone(), a.clone()]);
+        assert_eq!(all.total_messages, 24);
+        assert_eq!(all.as_json(), merged.merged(&a).as_json());
+
+        // In-place merge accumulates into the receiver
+        let mut total = a.clone();
+        total.merge(&b);
+        assert_eq!(total.total_messages, 14);
+        assert_eq!(total.bandwidth_saved, 800);
+        assert!((total.bandwidth_saved_percent - 20.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_preferred_algorithm_negotiation() {
+        let options = DeltaOptions {
+            algorithms: vec![DeltaAlgorithm::Fossil],
+            ..Default::default()
+        }
+        .priority_algorithm(DeltaAlgorithm::Fossil);
+        let mut manager = DeltaManager::new(options);
+        let sent = Arc::new(RwLock::new(Vec::new()));
+        let sent_clone = sent.clone();
+        manager.set_send_callback(Arc::new(move |event, data| {
+            sent_clone.write().push((event.to_string(), data.clone()));
+            true
+        }));
+
+        manager.enable();
+        let (event, data) = sent.read()[0].clone();
+        assert_eq!(event, "pusher:enable_delta_compression");
+        assert_eq!(data["preferred_algorithm"], "fossil");
+        assert_eq!(data["algorithms"][0], "fossil");
+
+        // Mock server response choosing the algorithm
+        manager.handle_enabled(&serde_json::json!({ "enabled": true, "algorithm": "fossil" }));
+        assert_eq!(manager.active_algorithm(), Some(DeltaAlgorithm::Fossil));
+        assert_eq!(
+            manager.get_stats().active_algorithm.as_deref(),
+            Some("fossil")
+        );
+
+        manager.disable();
+        assert_eq!(manager.active_algorithm(), None);
+    }
+
+    #[test]
+    fn test_force_resync() {
+        let mut manager = DeltaManager::new(DeltaOptions::default());
+        let sent = Arc::new(RwLock::new(Vec::new()));
+        let sent_clone = sent.clone();
+        manager.set_send_callback(Arc::new(move |event, data| {
+            sent_clone.write().push((event.to_string(), data.clone()));
+            true
+        }));
+
+        let event =
+            PusherEvent::new("test-event").with_json_data(serde_json::json!({"price": 100}));
+        manager.handle_full_message("test-channel", &event, 1);
+        assert_eq!(manager.get_stats().channel_count, 1);
+
+        manager.force_resync("test-channel");
+
+        let stats = manager.get_stats();
+        assert_eq!(stats.channel_count, 0);
+        assert_eq!(stats.resyncs_requested, 1);
+        assert_eq!(
+            *sent.read(),
+            vec![(
+                "pusher:delta_resync_request".to_string(),
+                serde_json::json!({"channel": "test-channel"})
+            )]
+        );
+    }
+
+    #[test]
+    fn test_cache_warmup_decodes_first_delta() {
+        let base = r#"{"price":100,"symbol":"BTC"}"#;
+        let target = r#"{"price":101,"symbol":"BTC"}"#;
+
+        let source = DeltaManager::new(DeltaOptions::default());
+        let event = PusherEvent::new("price")
+            .with_json_data(serde_json::json!({"price": 100, "symbol": "BTC"}));
+        source.handle_full_message("market-data", &event, 1);
+        let snapshot = source.cache_snapshot();
+        assert_eq!(snapshot["market-data"], base.as_bytes());
+
+        // Round-trip through serde as a persisted cache would
+        let persisted = serde_json::to_string(&snapshot).unwrap();
+        let restored: HashMap<String, Vec<u8>> = serde_json::from_str(&persisted).unwrap();
+
+        let mut manager = DeltaManager::new(DeltaOptions::default());
+        let sent = Arc::new(RwLock::new(Vec::new()));
+        let sent_clone = sent.clone();
+        manager.set_send_callback(Arc::new(move |event, _data| {
+            sent_clone.write().push(event.to_string());
+            true
+        }));
+        manager.cache_warmup(restored);
+
+        let delta = fossil_delta::delta(target.as_bytes(), base.as_bytes());
+        let decoded = manager
+            .handle_delta(
+                "market-data",
+                DeltaMessage {
+                    event: "price".to_string(),
+                    delta: crate::delta::encode_base64(&delta),
+                    seq: 2,
+                    algorithm: Some("fossil".to_string()),
+                    conflation_key: None,
+                    base_index: None,
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            decoded.data_as_value(),
+            Some(serde_json::json!({"price": 101, "symbol": "BTC"}))
+        );
+        assert!(sent.read().is_empty());
+    }
+
+    #[cfg(feature = "delta-persist")]
+    #[test]
+    fn test_file_cache_persistence_survives_restart() {
+        use crate::delta::FileDeltaCache;
+
+        let base = r#"{"price":100,"symbol":"BTC"}"#;
+        let target = r#"{"price":101,"symbol":"BTC"}"#;
+        let dir = std::env::temp_dir().join(format!("sockudo-delta-{}", uuid::Uuid::new_v4()));
+
+        let manager = DeltaManager::new(
+            DeltaOptions::builder().cache_persistence(FileDeltaCache::new(dir.clone())),
+        );
+        let event = PusherEvent::new("price")
+            .with_json_data(serde_json::json!({"price": 100, "symbol": "BTC"}));
+        manager.handle_full_message("market-data", &event, 1);
+        drop(manager);
+
+        let mut manager = DeltaManager::new(
+            DeltaOptions::builder().cache_persistence(FileDeltaCache::new(dir.clone())),
+        );
+        let sent = Arc::new(RwLock::new(Vec::new()));
+        let sent_clone = sent.clone();
+        manager.set_send_callback(Arc::new(move |event, _data| {
+            sent_clone.write().push(event.to_string());
+            true
+        }));
+
+        let delta = fossil_delta::delta(target.as_bytes(), base.as_bytes());
+        let decoded = manager
+            .handle_delta(
+                "market-data",
+                DeltaMessage {
+                    event: "price".to_string(),
+                    delta: crate::delta::encode_base64(&delta),
+                    seq: 2,
+                    algorithm: Some("fossil".to_string()),
+                    conflation_key: None,
+                    base_index: None,
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            decoded.data_as_value(),
+            Some(serde_json::json!({"price": 101, "symbol": "BTC"}))
+        );
+        assert!(sent.read().is_empty());
+        assert_eq!(manager.get_stats().errors, 0);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "delta-persist")]
+    #[test]
+    fn test_memory_cache_saves_full_messages_only() {
+        use crate::delta::DeltaCachePersistence;
+
+        let cache = Arc::new(crate::delta::MemoryDeltaCache::new());
+        let manager = DeltaManager::new(DeltaOptions {
+            cache_persistence: Some(cache.clone()),
+            ..Default::default()
+        });
+
+        let event = PusherEvent::new("price").with_json_data(serde_json::json!({"price": 100}));
+        manager.handle_full_message("ticker", &event, 1);
+        let delta = fossil_delta::delta(br#"{"price":101}"#, br#"{"price":100}"#);
+        manager
+            .handle_delta(
+                "ticker",
+                DeltaMessage {
+                    event: "price".to_string(),
+                    delta: crate::delta::encode_base64(&delta),
+                    seq: 2,
+                    algorithm: Some("fossil".to_string()),
+                    conflation_key: None,
+                    base_index: None,
+                },
+            )
+            .unwrap();
+        // Dropping the manager waits for the background saves
+        drop(manager);
+
+        assert_eq!(cache.len(), 1);
+        assert_eq!(
+            cache.load("ticker").unwrap(),
+            Some(br#"{"price":100}"#.to_vec())
+        );
+
+        // A new manager restores the saved base up front
+        let manager = DeltaManager::new(DeltaOptions {
+            cache_persistence: Some(cache.clone()),
+            ..Default::default()
+        });
+        assert_eq!(
+            manager.cache_snapshot(),
+            HashMap::from([("ticker".to_string(), br#"{"price":100}"#.to_vec())])
+        );
+    }
+
+    #[test]
+    fn test_algorithm_switch_on_error() {
+        let base = r#"{"price":100}"#;
+        let target = r#"{"price":101}"#;
+        let delta = fossil_delta::delta(target.as_bytes(), base.as_bytes());
+        let mislabeled = || DeltaMessage {
+            event: "price".to_string(),
+            delta: crate::delta::encode_base64(&delta),
+            seq: 2,
+            algorithm: Some("xdelta3".to_string()),
+            conflation_key: None,
+            base_index: None,
+        };
+        let manager_with = |options: DeltaOptions| {
+            let manager = DeltaManager::new(DeltaOptions {
+                algorithms: vec![DeltaAlgorithm::Xdelta3, DeltaAlgorithm::Fossil],
+                ..options
+            });
+            let event = PusherEvent::new("price").with_json_data(serde_json::json!({"price": 100}));
+            manager.handle_full_message("ticker", &event, 1);
+            manager
+        };
+
+        let strict = manager_with(DeltaOptions::default());
+        assert!(strict.handle_delta("ticker", mislabeled()).is_err());
+        assert_eq!(strict.get_stats().algorithm_fallbacks, 0);
+
+        assert_eq!(strict.get_stats().errors, 1);
+
+        let decoded_with = Arc::new(RwLock::new(None));
+        let decoded_clone = decoded_with.clone();
+        let reported = Arc::new(RwLock::new(Vec::new()));
+        let reported_clone = reported.clone();
+        let lenient = manager_with(DeltaOptions {
+            on_error: Some(Arc::new(move |err: &str| {
+                reported_clone.write().push(err.to_string())
+            })),
+            ..DeltaOptions::default()
+                .algorithm_fallback(1)
+                .on_delta_decoded(move |_, algorithm, _, _| {
+                    *decoded_clone.write() = Some(algorithm.to_string())
+                })
+        });
+        let event = lenient.handle_delta("ticker", mislabeled()).unwrap();
+        assert_eq!(
+            event.data_as_value(),
+            Some(serde_json::json!({"price": 101}))
+        );
+        assert_eq!(decoded_with.read().as_deref(), Some("fossil"));
+        let stats = lenient.get_stats();
+        assert_eq!(stats.algorithm_fallbacks, 1);
+        assert_eq!(stats.delta_messages, 1);
+        // The failed first attempt is not an error once the fallback succeeds
+        assert_eq!(stats.errors, 0);
+        assert!(reported.read().is_empty());
+
+        let exhausted = manager_with(DeltaOptions::default().algorithm_fallback(0));
+        assert!(exhausted.handle_delta("ticker", mislabeled()).is_err());
+        assert_eq!(exhausted.get_stats().errors, 1);
+    }
+
+    #[test]
+    fn test_channel_algorithm_override() {
+        let base = r#"{"price":100}"#;
+        let target = r#"{"price":101}"#;
+        let delta = fossil_delta::delta(target.as_bytes(), base.as_bytes());
+        let mislabeled = || DeltaMessage {
+            event: "price".to_string(),
+            delta: crate::delta::encode_base64(&delta),
+            seq: 2,
+            algorithm: Some("xdelta3".to_string()),
+            conflation_key: None,
+            base_index: None,
+        };
+        let manager = DeltaManager::new(DeltaOptions {
+            algorithms: vec![DeltaAlgorithm::Xdelta3, DeltaAlgorithm::Fossil],
+            ..DeltaOptions::default()
+        });
+        let event = PusherEvent::new("price").with_json_data(serde_json::json!({"price": 100}));
+        manager.handle_full_message("ticker", &event, 1);
+        manager.handle_full_message("other", &event, 1);
+
+        assert_eq!(manager.get_channel_algorithm("ticker"), None);
+        manager.set_channel_algorithm("ticker", DeltaAlgorithm::Fossil);
+        assert_eq!(
+            manager.get_channel_algorithm("ticker"),
+            Some(DeltaAlgorithm::Fossil)
+        );
+        assert_eq!(manager.get_channel_algorithm("other"), None);
+
+        let event = manager.handle_delta("ticker", mislabeled()).unwrap();
+        assert_eq!(
+            event.data_as_value(),
+            Some(serde_json::json!({"price": 101}))
+        );
+        assert_eq!(manager.get_stats().algorithm_fallbacks, 0);
+
+        // Channels without an override still trust the message
+        assert!(manager.handle_delta("other", mislabeled()).is_err());
+    }
+
     #[test]
     fn test_cache_sync() {
         let options = DeltaOptions::default();
@@ -1,13 +1,22 @@
 //! Delta compression for bandwidth optimization.
 
+#[cfg(all(feature = "bench-utils", not(target_arch = "wasm32")))]
+pub mod bench;
 mod channel_state;
 pub mod decoders;
 mod manager;
+#[cfg(feature = "delta-persist")]
+mod persistence;
 mod types;
 
 pub use channel_state::ChannelState;
 pub use decoders::{
-    decode_base64, encode_base64, DeltaDecoder, FossilDeltaDecoder, Xdelta3Decoder,
+    check_available, decode_base64, encode_base64, list_available, DeltaDecoder,
+    FossilDeltaDecoder, StreamingDeltaDecoder, Xdelta3Decoder,
 };
 pub use manager::DeltaManager;
+#[cfg(all(feature = "delta-persist", not(target_arch = "wasm32")))]
+pub use persistence::FileDeltaCache;
+#[cfg(feature = "delta-persist")]
+pub use persistence::{DeltaCachePersistence, MemoryDeltaCache};
 pub use types::*;
@@ -0,0 +1,256 @@
+//! Persistent storage for delta base messages, so a restarted client can
+//! decode deltas against the bases it had before shutting down.
+
+use parking_lot::RwLock;
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+use tracing::warn;
+
+use super::types::{DeltaCachePersistenceRef, ErrorCallback};
+use crate::error::Result;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::error::SockudoError;
+
+/// Storage for the latest base message of each channel.
+///
+/// `DeltaManager` saves a channel's base after every full message it
+/// receives and loads the stored bases back in `cache_warmup`.
+pub trait DeltaCachePersistence: Send + Sync {
+    /// Store `base` as the latest base message of `channel`
+    fn save(&self, channel: &str, base: &[u8]) -> Result<()>;
+
+    /// Load the stored base message of `channel`, if any
+    fn load(&self, channel: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Names of the channels with a stored base message
+    fn channels(&self) -> Result<Vec<String>>;
+}
+
+/// Keeps base messages in memory, mainly for tests
+#[derive(Debug, Default)]
+pub struct MemoryDeltaCache {
+    entries: RwLock<HashMap<String, Vec<u8>>>,
+}
+
+impl MemoryDeltaCache {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of channels with a stored base message
+    pub fn len(&self) -> usize {
+        self.entries.read().len()
+    }
+
+    /// Whether no base messages are stored
+    pub fn is_empty(&self) -> bool {
+        self.entries.read().is_empty()
+    }
+}
+
+impl DeltaCachePersistence for MemoryDeltaCache {
+    fn save(&self, channel: &str, base: &[u8]) -> Result<()> {
+        self.entries
+            .write()
+            .insert(channel.to_string(), base.to_vec());
+        Ok(())
+    }
+
+    fn load(&self, channel: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.entries.read().get(channel).cloned())
+    }
+
+    fn channels(&self) -> Result<Vec<String>> {
+        Ok(self.entries.read().keys().cloned().collect())
+    }
+}
+
+/// Writes each channel's base message to its own binary file in a directory.
+///
+/// File names are the percent-encoded channel name with a `.bin` extension.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+pub struct FileDeltaCache {
+    dir: PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileDeltaCache {
+    /// Store base messages in `dir`, which is created on the first save
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Directory the base messages are stored in
+    pub fn dir(&self) -> &std::path::Path {
+        &self.dir
+    }
+
+    fn path_for(&self, channel: &str) -> PathBuf {
+        self.dir
+            .join(format!("{}.bin", urlencoding::encode(channel)))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DeltaCachePersistence for FileDeltaCache {
+    fn save(&self, channel: &str, base: &[u8]) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)
+            .and_then(|_| std::fs::write(self.path_for(channel), base))
+            .map_err(|e| {
+                SockudoError::delta(format!(
+                    "Failed to save delta base for channel {}: {}",
+                    channel, e
+                ))
+            })
+    }
+
+    fn load(&self, channel: &str) -> Result<Option<Vec<u8>>> {
+        match std::fs::read(self.path_for(channel)) {
+            Ok(base) => Ok(Some(base)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(SockudoError::delta(format!(
+                "Failed to load delta base for channel {}: {}",
+                channel, e
+            ))),
+        }
+    }
+
+    fn channels(&self) -> Result<Vec<String>> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(SockudoError::delta(format!(
+                    "Failed to list delta bases in {}: {}",
+                    self.dir.display(),
+                    e
+                )))
+            }
+        };
+        Ok(entries
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name();
+                let encoded = name.to_str()?.strip_suffix(".bin")?;
+                urlencoding::decode(encoded)
+                    .ok()
+                    .map(|name| name.into_owned())
+            })
+            .collect())
+    }
+}
+
+/// Saves base messages away from the message path.
+///
+/// On native targets a worker thread performs the saves, so slow storage
+/// does not hold up message handling, and dropping the saver waits for
+/// the saves still queued. WASM has no threads, so saves happen inline.
+pub(crate) struct BaseSaver {
+    #[cfg(not(target_arch = "wasm32"))]
+    queue: Option<std::sync::mpsc::Sender<(String, Vec<u8>)>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    worker: Option<std::thread::JoinHandle<()>>,
+    #[cfg(target_arch = "wasm32")]
+    persistence: DeltaCachePersistenceRef,
+    #[cfg(target_arch = "wasm32")]
+    on_error: Option<ErrorCallback>,
+}
+
+impl BaseSaver {
+    /// Save to `persistence`, reporting failures to `on_error`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn new(
+        persistence: DeltaCachePersistenceRef,
+        on_error: Option<ErrorCallback>,
+    ) -> Self {
+        let (queue, saves) = std::sync::mpsc::channel::<(String, Vec<u8>)>();
+        let worker = std::thread::Builder::new()
+            .name("sockudo-delta-persist".to_string())
+            .spawn(move || {
+                for (channel, base) in saves {
+                    save_base(&*persistence, on_error.as_ref(), &channel, &base);
+                }
+            })
+            .map_err(|e| warn!("Failed to start delta persistence thread: {}", e))
+            .ok();
+        Self {
+            queue: worker.is_some().then_some(queue),
+            worker,
+        }
+    }
+
+    /// Save to `persistence`, reporting failures to `on_error`
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn new(
+        persistence: DeltaCachePersistenceRef,
+        on_error: Option<ErrorCallback>,
+    ) -> Self {
+        Self {
+            persistence,
+            on_error,
+        }
+    }
+
+    /// Queue `base` to be saved as the latest base message of `channel`
+    pub(crate) fn save(&self, channel: &str, base: &str) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(ref queue) = self.queue {
+            let _ = queue.send((channel.to_string(), base.as_bytes().to_vec()));
+        }
+        #[cfg(target_arch = "wasm32")]
+        save_base(
+            &*self.persistence,
+            self.on_error.as_ref(),
+            channel,
+            base.as_bytes(),
+        );
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for BaseSaver {
+    fn drop(&mut self) {
+        // Closing the queue ends the worker once it has saved everything
+        self.queue.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn save_base(
+    persistence: &dyn DeltaCachePersistence,
+    on_error: Option<&ErrorCallback>,
+    channel: &str,
+    base: &[u8],
+) {
+    if let Err(e) = persistence.save(channel, base) {
+        warn!("{}", e);
+        if let Some(callback) = on_error {
+            callback(&e.to_string());
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_cache_round_trip() {
+        let dir = std::env::temp_dir().join(format!("sockudo-delta-{}", uuid::Uuid::new_v4()));
+        let cache = FileDeltaCache::new(dir.clone());
+
+        assert_eq!(cache.load("private-a/b").unwrap(), None);
+        assert!(cache.channels().unwrap().is_empty());
+        cache.save("private-a/b", b"base").unwrap();
+        assert_eq!(cache.load("private-a/b").unwrap(), Some(b"base".to_vec()));
+        // Channel names never escape the cache directory
+        assert!(dir.join("private-a%2Fb.bin").exists());
+        assert_eq!(cache.channels().unwrap(), vec!["private-a/b".to_string()]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
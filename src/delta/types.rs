@@ -47,6 +47,17 @@ pub type StatsCallback = Arc<dyn Fn(&DeltaStats) + Send + Sync>;
 /// Callback type for error notifications
 pub type ErrorCallback = Arc<dyn Fn(&str) + Send + Sync>;
 
+/// Callback type for decoded deltas: channel, algorithm, delta size and
+/// decoded size in bytes
+pub type DeltaDecodedFn = Arc<dyn Fn(&str, &str, usize, usize) + Send + Sync>;
+
+/// Callback type for full (non-delta) messages: channel and size in bytes
+pub type FullMessageFn = Arc<dyn Fn(&str, usize) + Send + Sync>;
+
+/// Shared storage for delta base messages
+#[cfg(feature = "delta-persist")]
+pub type DeltaCachePersistenceRef = Arc<dyn super::persistence::DeltaCachePersistence>;
+
 /// Delta compression configuration options
 #[derive(Clone, Serialize, Deserialize)]
 pub struct DeltaOptions {
@@ -58,24 +69,53 @@ pub struct DeltaOptions {
     pub debug: bool,
     /// Maximum messages per conflation key (default: 10)
     pub max_messages_per_key: usize,
+    /// Algorithm the server should pick when it supports several
+    #[serde(default)]
+    pub preferred_algorithm: Option<DeltaAlgorithm>,
+    /// Retry a delta that fails to decode with the other `algorithms`, in
+    /// order, before giving up (default: false)
+    #[serde(default)]
+    pub algorithm_switch_on_error: bool,
+    /// Maximum number of other algorithms tried per delta (default: 1)
+    #[serde(default = "default_max_fallback_attempts")]
+    pub max_fallback_attempts: u32,
     /// Callback for stats updates (optional)
     #[serde(skip)]
     pub on_stats: Option<StatsCallback>,
     /// Callback for error notifications (optional)
     #[serde(skip)]
     pub on_error: Option<ErrorCallback>,
+    /// Callback for each delta message decoded (optional)
+    #[serde(skip)]
+    pub on_delta_decoded: Option<DeltaDecodedFn>,
+    /// Callback for each full message stored as a delta base (optional)
+    #[serde(skip)]
+    pub on_full_message: Option<FullMessageFn>,
+    /// Storage for base messages, so deltas still decode after a restart
+    /// (optional)
+    #[cfg(feature = "delta-persist")]
+    #[serde(skip)]
+    pub cache_persistence: Option<DeltaCachePersistenceRef>,
 }
 
 impl std::fmt::Debug for DeltaOptions {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("DeltaOptions")
+        let mut debug = f.debug_struct("DeltaOptions");
+        debug
             .field("enabled", &self.enabled)
             .field("algorithms", &self.algorithms)
             .field("debug", &self.debug)
             .field("max_messages_per_key", &self.max_messages_per_key)
+            .field("preferred_algorithm", &self.preferred_algorithm)
+            .field("algorithm_switch_on_error", &self.algorithm_switch_on_error)
+            .field("max_fallback_attempts", &self.max_fallback_attempts)
             .field("on_stats", &self.on_stats.is_some())
             .field("on_error", &self.on_error.is_some())
-            .finish()
+            .field("on_delta_decoded", &self.on_delta_decoded.is_some())
+            .field("on_full_message", &self.on_full_message.is_some());
+        #[cfg(feature = "delta-persist")]
+        debug.field("cache_persistence", &self.cache_persistence.is_some());
+        debug.finish()
     }
 }
 
@@ -86,12 +126,79 @@ impl Default for DeltaOptions {
             algorithms: vec![DeltaAlgorithm::Fossil, DeltaAlgorithm::Xdelta3],
             debug: false,
             max_messages_per_key: 10,
+            preferred_algorithm: None,
+            algorithm_switch_on_error: false,
+            max_fallback_attempts: default_max_fallback_attempts(),
             on_stats: None,
             on_error: None,
+            on_delta_decoded: None,
+            on_full_message: None,
+            #[cfg(feature = "delta-persist")]
+            cache_persistence: None,
         }
     }
 }
 
+fn default_max_fallback_attempts() -> u32 {
+    1
+}
+
+impl DeltaOptions {
+    /// Start from the default options and chain the builder methods below
+    pub fn builder() -> Self {
+        Self::default()
+    }
+
+    /// Builder pattern: call `callback` with the channel, algorithm, delta
+    /// size and decoded size in bytes for each delta message decoded
+    pub fn on_delta_decoded(
+        mut self,
+        callback: impl Fn(&str, &str, usize, usize) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_delta_decoded = Some(Arc::new(callback));
+        self
+    }
+
+    /// Builder pattern: call `callback` with the channel and size in bytes
+    /// for each full message stored as a delta base
+    pub fn on_full_message(
+        mut self,
+        callback: impl Fn(&str, usize) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_full_message = Some(Arc::new(callback));
+        self
+    }
+
+    /// Builder pattern: save the base of each full message to
+    /// `persistence` in the background and restore the saved bases in
+    /// `DeltaManager::cache_warmup`
+    #[cfg(feature = "delta-persist")]
+    pub fn cache_persistence(
+        mut self,
+        persistence: impl super::persistence::DeltaCachePersistence + 'static,
+    ) -> Self {
+        self.cache_persistence = Some(Arc::new(persistence));
+        self
+    }
+
+    /// Builder pattern: ask the server to prefer `algorithm`.
+    ///
+    /// The server only honors the preference if it supports the algorithm;
+    /// check `DeltaManager::active_algorithm` for its final choice.
+    pub fn priority_algorithm(mut self, algorithm: DeltaAlgorithm) -> Self {
+        self.preferred_algorithm = Some(algorithm);
+        self
+    }
+
+    /// Builder pattern: retry failed decodes with up to `max_attempts` of
+    /// the other configured algorithms before requesting a resync
+    pub fn algorithm_fallback(mut self, max_attempts: u32) -> Self {
+        self.algorithm_switch_on_error = true;
+        self.max_fallback_attempts = max_attempts;
+        self
+    }
+}
+
 /// Statistics for delta compression performance
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DeltaStats {
@@ -113,6 +220,19 @@ pub struct DeltaStats {
     pub errors: u64,
     /// Number of channels using delta compression
     pub channel_count: u64,
+    /// Number of resyncs requested with `force_resync`
+    #[serde(default)]
+    pub resyncs_requested: u64,
+    /// Algorithm the server chose during negotiation
+    #[serde(default)]
+    pub active_algorithm: Option<String>,
+    /// Messages dropped because their debug checksum did not match
+    #[serde(default)]
+    pub checksum_errors: u64,
+    /// Decode attempts made with a fallback algorithm after the announced
+    /// one failed
+    #[serde(default)]
+    pub algorithm_fallbacks: u64,
     /// Per-channel statistics
     pub channels: Vec<ChannelDeltaStats>,
 }
@@ -141,13 +261,87 @@ impl DeltaStats {
         self.total_bytes_without_compression += other.total_bytes_without_compression;
         self.total_bytes_with_compression += other.total_bytes_with_compression;
         self.errors += other.errors;
+        self.resyncs_requested += other.resyncs_requested;
+        self.checksum_errors += other.checksum_errors;
+        if self.active_algorithm.is_none() {
+            self.active_algorithm = other.active_algorithm.clone();
+        }
         self.calculate_savings();
     }
 
+    /// Combine two sets of stats into new ones, e.g. from several clients.
+    ///
+    /// Counters are summed, `channel_count` is the larger of the two and
+    /// `bandwidth_saved_percent` is recomputed from the summed byte counts.
+    #[must_use]
+    pub fn merged(&self, other: &DeltaStats) -> DeltaStats {
+        let mut merged = DeltaStats {
+            total_messages: self.total_messages + other.total_messages,
+            delta_messages: self.delta_messages + other.delta_messages,
+            full_messages: self.full_messages + other.full_messages,
+            total_bytes_without_compression: self.total_bytes_without_compression
+                + other.total_bytes_without_compression,
+            total_bytes_with_compression: self.total_bytes_with_compression
+                + other.total_bytes_with_compression,
+            bandwidth_saved: self.bandwidth_saved + other.bandwidth_saved,
+            bandwidth_saved_percent: 0.0,
+            errors: self.errors + other.errors,
+            channel_count: self.channel_count.max(other.channel_count),
+            resyncs_requested: self.resyncs_requested + other.resyncs_requested,
+            active_algorithm: self
+                .active_algorithm
+                .clone()
+                .or_else(|| other.active_algorithm.clone()),
+            checksum_errors: self.checksum_errors + other.checksum_errors,
+            algorithm_fallbacks: self.algorithm_fallbacks + other.algorithm_fallbacks,
+            channels: self
+                .channels
+                .iter()
+                .chain(&other.channels)
+                .cloned()
+                .collect(),
+        };
+        merged.calculate_savings();
+        merged
+    }
+
+    /// Combine any number of stats with [`merged`](Self::merged)
+    pub fn merge_all(stats: impl IntoIterator<Item = DeltaStats>) -> DeltaStats {
+        stats
+            .into_iter()
+            .fold(DeltaStats::default(), |acc, next| acc.merged(&next))
+    }
+
     /// Reset statistics
     pub fn reset(&mut self) {
         *self = Self::default();
     }
+
+    /// Serialize all fields to a JSON object with snake_case keys
+    pub fn as_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    /// Header line matching the columns of [`to_csv_row`](Self::to_csv_row)
+    pub fn csv_header() -> &'static str {
+        "total_messages,delta_messages,full_messages,bytes_without,bytes_with,bandwidth_saved,pct_saved,errors,channels"
+    }
+
+    /// Format the totals as one CSV line, without a header or trailing newline
+    pub fn to_csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{:.2},{},{}",
+            self.total_messages,
+            self.delta_messages,
+            self.full_messages,
+            self.total_bytes_without_compression,
+            self.total_bytes_with_compression,
+            self.bandwidth_saved,
+            self.bandwidth_saved_percent,
+            self.errors,
+            self.channel_count
+        )
+    }
 }
 
 /// Per-channel delta statistics
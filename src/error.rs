@@ -1,5 +1,7 @@
 //! Error types for the Sockudo client library.
 
+use std::collections::HashMap;
+use std::time::Duration;
 use thiserror::Error;
 
 /// Result type alias for Sockudo operations
@@ -16,18 +18,37 @@ pub enum SockudoError {
     #[error("Authorization error: {message}")]
     AuthorizationError { message: String },
 
+    /// An auth endpoint answered with an error status. These were reported
+    /// as `AuthorizationError` before, with the same message.
+    #[error("Authorization error: {message}")]
+    AuthorizationFailed {
+        message: String,
+        status: u16,
+        /// Response headers, with lowercase names
+        headers: HashMap<String, String>,
+    },
+
     #[error("Channel error: {message}")]
     ChannelError { message: String },
 
     #[error("Protocol error: {message}")]
     ProtocolError { message: String },
 
+    #[error("Server error {code}: {message}")]
+    ServerError { code: u32, message: String },
+
     #[error("Encryption error: {message}")]
     EncryptionError { message: String },
 
     #[error("Timeout error: {message}")]
     TimeoutError { message: String },
 
+    #[error("Write timed out after {timeout:?}")]
+    WriteTimeout { timeout: Duration },
+
+    #[error("No pong received within {timeout:?}")]
+    ProbeTimeout { timeout: Duration },
+
     #[error("Invalid state: {message}")]
     InvalidState { message: String },
 
@@ -48,6 +69,39 @@ pub enum SockudoError {
 
     #[error("Delta compression error: {message}")]
     DeltaError { message: String },
+
+    #[error("Proxy error: {message}")]
+    ProxyError { message: String },
+
+    #[error("Channel already exists: {channel}")]
+    ChannelAlreadyExists { channel: String },
+
+    #[error("Subscription limit exceeded: {current} of {limit} channels subscribed")]
+    SubscriptionLimitExceeded { limit: usize, current: usize },
+
+    #[error("Member not found: {user_id}")]
+    MemberNotFound { user_id: String },
+
+    #[error("Invalid pattern: {message}")]
+    InvalidPattern { message: String },
+
+    #[error("Invalid event name '{name}': {reason}")]
+    InvalidEventName { name: String, reason: String },
+
+    #[error("Failed to connect after {attempts} attempts")]
+    MaxReconnectAttemptsExceeded { attempts: u32 },
+
+    #[error("Message checksum mismatch: expected {expected}, got {actual}")]
+    ChecksumMismatch { expected: u32, actual: u32 },
+
+    #[error("Failed to decrypt event on channel {channel}")]
+    DecryptionFailed { channel: String },
+
+    #[error("{error}\nDebug dump: {dump}")]
+    WithContext {
+        error: Box<SockudoError>,
+        dump: String,
+    },
 }
 
 impl SockudoError {
@@ -63,6 +117,18 @@ impl SockudoError {
         }
     }
 
+    pub fn authorization_failed(
+        msg: impl Into<String>,
+        status: u16,
+        headers: HashMap<String, String>,
+    ) -> Self {
+        Self::AuthorizationFailed {
+            message: msg.into(),
+            status,
+            headers,
+        }
+    }
+
     pub fn channel(msg: impl Into<String>) -> Self {
         Self::ChannelError {
             message: msg.into(),
@@ -87,6 +153,21 @@ impl SockudoError {
         }
     }
 
+    pub fn write_timeout(timeout: Duration) -> Self {
+        Self::WriteTimeout { timeout }
+    }
+
+    pub fn probe_timeout(timeout: Duration) -> Self {
+        Self::ProbeTimeout { timeout }
+    }
+
+    pub fn server_error(code: u32, msg: impl Into<String>) -> Self {
+        Self::ServerError {
+            code,
+            message: msg.into(),
+        }
+    }
+
     pub fn invalid_state(msg: impl Into<String>) -> Self {
         Self::InvalidState {
             message: msg.into(),
@@ -128,6 +209,99 @@ impl SockudoError {
             message: msg.into(),
         }
     }
+
+    pub fn proxy(msg: impl Into<String>) -> Self {
+        Self::ProxyError {
+            message: msg.into(),
+        }
+    }
+
+    pub fn channel_already_exists(channel: impl Into<String>) -> Self {
+        Self::ChannelAlreadyExists {
+            channel: channel.into(),
+        }
+    }
+
+    pub fn member_not_found(user_id: impl Into<String>) -> Self {
+        Self::MemberNotFound {
+            user_id: user_id.into(),
+        }
+    }
+
+    pub fn invalid_pattern(msg: impl Into<String>) -> Self {
+        Self::InvalidPattern {
+            message: msg.into(),
+        }
+    }
+
+    pub fn invalid_event_name(name: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self::InvalidEventName {
+            name: name.into(),
+            reason: reason.into(),
+        }
+    }
+
+    pub fn max_reconnect_attempts_exceeded(attempts: u32) -> Self {
+        Self::MaxReconnectAttemptsExceeded { attempts }
+    }
+
+    pub fn checksum_mismatch(expected: u32, actual: u32) -> Self {
+        Self::ChecksumMismatch { expected, actual }
+    }
+
+    pub fn decryption_failed(channel: impl Into<String>) -> Self {
+        Self::DecryptionFailed {
+            channel: channel.into(),
+        }
+    }
+
+    /// Whether the operation may succeed if retried.
+    ///
+    /// Dropped connections, timeouts, delta decode failures, server-side
+    /// (5xx or 429) authorization failures and server errors outside
+    /// 4000-4099 are transient; invalid configuration, names and state are
+    /// not.
+    pub fn is_retriable(&self) -> bool {
+        match self {
+            Self::ConnectionError { .. }
+            | Self::WebSocketError { .. }
+            | Self::TimeoutError { .. }
+            | Self::WriteTimeout { .. }
+            | Self::ProbeTimeout { .. }
+            | Self::ProxyError { .. }
+            | Self::DeltaError { .. }
+            | Self::ChecksumMismatch { .. } => true,
+            Self::AuthorizationFailed { status, .. } => *status >= 500 || *status == 429,
+            Self::ServerError { code, .. } => !(4000..=4099).contains(code),
+            Self::WithContext { error, .. } => error.is_retriable(),
+            _ => false,
+        }
+    }
+
+    /// Delay requested by the server's `Retry-After` header, in seconds form
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::AuthorizationFailed { headers, .. } => headers
+                .get("retry-after")
+                .and_then(|value| value.trim().parse().ok())
+                .map(Duration::from_secs),
+            Self::WithContext { error, .. } => error.retry_after(),
+            _ => None,
+        }
+    }
+
+    /// Attach a serialized snapshot of the client, such as a `DebugDump`.
+    ///
+    /// Errors that already carry a snapshot are returned unchanged.
+    pub fn context(self, dump: &impl serde::Serialize) -> Self {
+        if matches!(self, Self::WithContext { .. }) {
+            return self;
+        }
+        Self::WithContext {
+            error: Box::new(self),
+            dump: serde_json::to_string_pretty(dump).unwrap_or_default(),
+        }
+    }
 }
 
 impl From<serde_json::Error> for SockudoError {
@@ -136,6 +310,12 @@ impl From<serde_json::Error> for SockudoError {
     }
 }
 
+impl From<regex::Error> for SockudoError {
+    fn from(err: regex::Error) -> Self {
+        Self::invalid_pattern(err.to_string())
+    }
+}
+
 impl From<url::ParseError> for SockudoError {
     fn from(err: url::ParseError) -> Self {
         Self::config(format!("Invalid URL: {}", err))
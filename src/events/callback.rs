@@ -1,8 +1,11 @@
 //! Callback registry for managing event callbacks.
 
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use parking_lot::RwLock;
 use dashmap::DashMap;
+use regex::Regex;
 use crate::protocol::PusherEvent;
 
 /// Type alias for callback function
@@ -36,6 +39,62 @@ impl std::fmt::Debug for Callback {
     }
 }
 
+/// A callback bound to an event name on channels matching a pattern
+#[derive(Debug, Clone)]
+struct ChannelScopedCallback {
+    /// Channel name, or glob pattern where `*` matches any characters
+    channel_pattern: String,
+    /// Event name
+    event_name: String,
+    callback: Callback,
+}
+
+impl ChannelScopedCallback {
+    /// Whether this binding fires for `event_name` on `channel`
+    fn matches(&self, channel: &str, event_name: &str) -> bool {
+        self.event_name == event_name && glob_match(&self.channel_pattern, channel)
+    }
+}
+
+/// Match `text` against `pattern`, where `*` matches any run of characters
+/// (including none) and every other character matches itself
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // split always yields at least one part
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else {
+        // No `*`: the whole text must match
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// Number of callbacks held by a registry, for spotting bindings that are
+/// never removed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct RegistryMemoryStats {
+    /// Every registered callback, of any kind
+    pub total_callbacks: usize,
+    /// Event names with at least one callback bound to exactly that name
+    pub total_events: usize,
+    /// Callbacks that receive all events
+    pub global_callbacks: usize,
+    /// Callbacks bound to an event name pattern
+    pub regex_callbacks: usize,
+    /// Callbacks removed after they first fire that have not fired yet
+    pub expiry_callbacks: usize,
+}
+
 /// Registry for storing callbacks per event name
 #[derive(Debug, Default)]
 pub struct CallbackRegistry {
@@ -43,15 +102,29 @@ pub struct CallbackRegistry {
     callbacks: DashMap<String, Vec<Callback>>,
     /// Global callbacks that receive all events
     global_callbacks: RwLock<Vec<Callback>>,
+    /// Callbacks for event names matching a pattern, in registration order
+    regex_callbacks: RwLock<Vec<(Regex, Callback)>>,
+    /// Callbacks for an event name on channels matching a glob pattern
+    channel_callbacks: RwLock<Vec<ChannelScopedCallback>>,
+    /// IDs of the callbacks removed after they first fire
+    expiring: RwLock<HashSet<u64>>,
     /// Counter for generating unique callback IDs
     next_id: std::sync::atomic::AtomicU64,
 }
 
 impl CallbackRegistry {
     pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Create a registry with room for callbacks on `capacity` event names
+    pub fn with_capacity(capacity: usize) -> Self {
         Self {
-            callbacks: DashMap::new(),
+            callbacks: DashMap::with_capacity(capacity),
             global_callbacks: RwLock::new(Vec::new()),
+            regex_callbacks: RwLock::new(Vec::new()),
+            channel_callbacks: RwLock::new(Vec::new()),
+            expiring: RwLock::new(HashSet::new()),
             next_id: std::sync::atomic::AtomicU64::new(1),
         }
     }
@@ -74,6 +147,31 @@ impl CallbackRegistry {
         id
     }
     
+    /// Add a callback for a specific event that removes itself the first
+    /// time it fires
+    pub fn add_once(self: &Arc<Self>, event_name: impl Into<String>, callback: impl Fn(&PusherEvent) + Send + Sync + 'static) -> u64 {
+        let id = self.next_id();
+        let event_name = event_name.into();
+        let registry = Arc::downgrade(self);
+        let bound_name = event_name.clone();
+        let fired = AtomicBool::new(false);
+        let cb = Callback::new(id, move |event| {
+            // Concurrent emits may both hold a copy of the callback
+            if fired.swap(true, Ordering::SeqCst) {
+                return;
+            }
+            if let Some(registry) = registry.upgrade() {
+                registry.remove(Some(&bound_name), Some(id));
+            }
+            callback(event);
+        });
+        
+        self.expiring.write().insert(id);
+        self.callbacks.entry(event_name).or_default().push(cb);
+        
+        id
+    }
+    
     /// Add a global callback that receives all events
     pub fn add_global(&self, callback: impl Fn(&PusherEvent) + Send + Sync + 'static) -> u64 {
         let id = self.next_id();
@@ -84,6 +182,32 @@ impl CallbackRegistry {
         id
     }
     
+    /// Add a callback for every event whose name matches `pattern`
+    pub fn add_regex(&self, pattern: Regex, callback: impl Fn(&PusherEvent) + Send + Sync + 'static) -> u64 {
+        let id = self.next_id();
+        let cb = Callback::new(id, callback);
+        
+        self.regex_callbacks.write().push((pattern, cb));
+        
+        id
+    }
+    
+    /// Add a callback for `event_name` on channels matching `channel_pattern`
+    pub fn add_channel_scoped(
+        &self,
+        channel_pattern: impl Into<String>,
+        event_name: impl Into<String>,
+        callback: impl Fn(&PusherEvent) + Send + Sync + 'static,
+    ) -> u64 {
+        let id = self.next_id();
+        self.channel_callbacks.write().push(ChannelScopedCallback {
+            channel_pattern: channel_pattern.into(),
+            event_name: event_name.into(),
+            callback: Callback::new(id, callback),
+        });
+        id
+    }
+    
     /// Get callbacks for a specific event
     pub fn get(&self, event_name: &str) -> Vec<Callback> {
         self.callbacks
@@ -97,6 +221,29 @@ impl CallbackRegistry {
         self.global_callbacks.read().clone()
     }
     
+    /// Get pattern callbacks whose pattern matches an event name
+    pub fn get_matching(&self, event_name: &str) -> Vec<Callback> {
+        self.regex_callbacks
+            .read()
+            .iter()
+            .filter(|(pattern, _)| pattern.is_match(event_name))
+            .map(|(_, cb)| cb.clone())
+            .collect()
+    }
+    
+    /// Get channel-scoped callbacks for `event_name` on `channel`
+    pub fn get_channel_scoped(&self, channel: Option<&str>, event_name: &str) -> Vec<Callback> {
+        let Some(channel) = channel else {
+            return Vec::new();
+        };
+        self.channel_callbacks
+            .read()
+            .iter()
+            .filter(|scoped| scoped.matches(channel, event_name))
+            .map(|scoped| scoped.callback.clone())
+            .collect()
+    }
+    
     /// Remove a specific callback by ID
     pub fn remove(&self, event_name: Option<&str>, callback_id: Option<u64>) {
         match (event_name, callback_id) {
@@ -105,22 +252,38 @@ impl CallbackRegistry {
                 if let Some(mut callbacks) = self.callbacks.get_mut(name) {
                     callbacks.retain(|cb| cb.id != id);
                 }
+                self.expiring.write().remove(&id);
+                self.channel_callbacks
+                    .write()
+                    .retain(|scoped| scoped.event_name != name || scoped.callback.id != id);
             }
             (Some(name), None) => {
                 // Remove all callbacks for specific event
-                self.callbacks.remove(name);
+                if let Some((_, removed)) = self.callbacks.remove(name) {
+                    let mut expiring = self.expiring.write();
+                    for cb in removed {
+                        expiring.remove(&cb.id);
+                    }
+                }
+                self.channel_callbacks
+                    .write()
+                    .retain(|scoped| scoped.event_name != name);
             }
             (None, Some(id)) => {
                 // Remove callback with ID from all events
                 for mut entry in self.callbacks.iter_mut() {
                     entry.retain(|cb| cb.id != id);
                 }
+                self.expiring.write().remove(&id);
                 self.global_callbacks.write().retain(|cb| cb.id != id);
+                self.regex_callbacks.write().retain(|(_, cb)| cb.id != id);
+                self.channel_callbacks
+                    .write()
+                    .retain(|scoped| scoped.callback.id != id);
             }
             (None, None) => {
                 // Remove all callbacks
-                self.callbacks.clear();
-                self.global_callbacks.write().clear();
+                self.clear();
             }
         }
     }
@@ -138,6 +301,9 @@ impl CallbackRegistry {
     pub fn clear(&self) {
         self.callbacks.clear();
         self.global_callbacks.write().clear();
+        self.regex_callbacks.write().clear();
+        self.channel_callbacks.write().clear();
+        self.expiring.write().clear();
     }
     
     /// Check if there are any callbacks for an event
@@ -148,11 +314,49 @@ impl CallbackRegistry {
             .unwrap_or(false)
     }
     
+    /// Count the callbacks bound to exactly this event name
+    pub fn binding_count(&self, event_name: &str) -> usize {
+        self.callbacks.get(event_name).map(|v| v.len()).unwrap_or(0)
+    }
+    
+    /// Count the callbacks that would fire for an event (exact, pattern,
+    /// global and channel-scoped on any channel)
+    pub fn listener_count(&self, event_name: &str) -> usize {
+        let exact = self.callbacks.get(event_name).map(|v| v.len()).unwrap_or(0);
+        let matching = self
+            .regex_callbacks
+            .read()
+            .iter()
+            .filter(|(pattern, _)| pattern.is_match(event_name))
+            .count();
+        let scoped = self
+            .channel_callbacks
+            .read()
+            .iter()
+            .filter(|scoped| scoped.event_name == event_name)
+            .count();
+        exact + matching + scoped + self.global_callbacks.read().len()
+    }
+    
     /// Get number of registered callbacks
     pub fn callback_count(&self) -> usize {
         let event_count: usize = self.callbacks.iter().map(|v| v.len()).sum();
         let global_count = self.global_callbacks.read().len();
-        event_count + global_count
+        let regex_count = self.regex_callbacks.read().len();
+        let channel_count = self.channel_callbacks.read().len();
+        event_count + global_count + regex_count + channel_count
+    }
+    
+    /// Count the callbacks held by the registry, by kind
+    pub fn memory_usage(&self) -> RegistryMemoryStats {
+        let total_events = self.callbacks.iter().filter(|v| !v.is_empty()).count();
+        RegistryMemoryStats {
+            total_callbacks: self.callback_count(),
+            total_events,
+            global_callbacks: self.global_callbacks.read().len(),
+            regex_callbacks: self.regex_callbacks.read().len(),
+            expiry_callbacks: self.expiring.read().len(),
+        }
     }
 }
 
@@ -205,6 +409,19 @@ mod tests {
         assert!(!registry.has_callbacks("test-event"));
     }
 
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("room-*", "room-1"));
+        assert!(glob_match("room-*", "room-"));
+        assert!(glob_match("*-room-*", "private-room-9"));
+        assert!(glob_match("a*b*c", "abbbc"));
+        assert!(glob_match("lobby", "lobby"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("room-*", "lobby"));
+        assert!(!glob_match("lobby", "lobby-2"));
+        assert!(!glob_match("ab*ba", "aba"));
+    }
+
     #[test]
     fn test_clear() {
         let registry = CallbackRegistry::new();
@@ -219,4 +436,53 @@ mod tests {
         
         assert_eq!(registry.callback_count(), 0);
     }
+
+    #[test]
+    fn test_memory_usage() {
+        let registry = CallbackRegistry::new();
+        let ids: Vec<u64> = (0..100)
+            .map(|i| registry.add(format!("event-{}", i % 10), |_| {}))
+            .collect();
+        
+        let stats = registry.memory_usage();
+        assert_eq!(stats.total_callbacks, 100);
+        assert_eq!(stats.total_events, 10);
+        assert_eq!(stats.global_callbacks, 0);
+        
+        for id in ids {
+            registry.remove(None, Some(id));
+        }
+        
+        let stats = registry.memory_usage();
+        assert_eq!(stats.total_callbacks, 0);
+        assert_eq!(stats.total_events, 0);
+    }
+
+    #[test]
+    fn test_once_callback_counted_until_fired() {
+        let registry = Arc::new(CallbackRegistry::new());
+        let counter = Arc::new(AtomicUsize::new(0));
+        let counter_clone = counter.clone();
+        
+        registry.add_once("event", move |_| {
+            counter_clone.fetch_add(1, Ordering::SeqCst);
+        });
+        registry.add_global(|_| {});
+        
+        let stats = registry.memory_usage();
+        assert_eq!(stats.total_callbacks, 2);
+        assert_eq!(stats.expiry_callbacks, 1);
+        
+        let callbacks = registry.get("event");
+        let event = PusherEvent::new("event");
+        for cb in callbacks.iter().chain(&callbacks) {
+            cb.invoke(&event);
+        }
+        
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+        let stats = registry.memory_usage();
+        assert_eq!(stats.total_callbacks, 1);
+        assert_eq!(stats.expiry_callbacks, 0);
+        assert_eq!(stats.global_callbacks, 1);
+    }
 }
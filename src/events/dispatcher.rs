@@ -1,11 +1,87 @@
 //! Event dispatcher for managing and emitting events.
 
-use super::callback::CallbackRegistry;
+use super::callback::{Callback, CallbackRegistry, RegistryMemoryStats};
+use super::validator::EventNameValidator;
+use crate::error::Result;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::error::SockudoError;
 use crate::protocol::PusherEvent;
-use parking_lot::RwLock;
+use futures::channel::oneshot;
+use parking_lot::{Mutex, RwLock};
+#[cfg(feature = "event-stats")]
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::future::Future;
+#[cfg(feature = "dispatch-metrics")]
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicUsize;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::atomic::{AtomicI64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::{debug, warn};
 
+/// Bindings per event name above which `bind` warns about a possible leak
+pub const DEFAULT_MAX_LISTENERS: usize = 10;
+
+/// Callbacks that overran the callback timeout and may still be running in
+/// the background before `emit` stops moving callbacks off the emitting
+/// thread
+#[cfg(not(target_arch = "wasm32"))]
+pub const MAX_DETACHED_CALLBACKS: usize = 8;
+
+/// Snapshot of callback timing collected by an `EventDispatcher`.
+///
+/// Counters only advance when the `dispatch-metrics` feature is enabled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct DispatchMetrics {
+    /// Number of calls to `emit`
+    pub total_dispatches: u64,
+    /// Number of callbacks invoked across all dispatches
+    pub total_callback_invocations: u64,
+    /// Duration of the slowest callback, in nanoseconds
+    pub slowest_callback_ns: u64,
+    /// Mean callback duration, in nanoseconds
+    pub average_callback_ns: u64,
+    /// Number of callbacks that took longer than one millisecond
+    pub callbacks_over_1ms: u64,
+    /// Number of callbacks abandoned for exceeding the callback timeout
+    pub timed_out_callbacks: u64,
+}
+
+/// Atomic counters behind `DispatchMetrics`
+#[cfg(feature = "dispatch-metrics")]
+#[derive(Default)]
+struct MetricCounters {
+    dispatches: AtomicU64,
+    invocations: AtomicU64,
+    total_ns: AtomicU64,
+    slowest_ns: AtomicU64,
+    over_1ms: AtomicU64,
+    timed_out: AtomicU64,
+}
+
+#[cfg(feature = "dispatch-metrics")]
+impl MetricCounters {
+    fn record(&self, elapsed: std::time::Duration) {
+        use std::sync::atomic::Ordering::Relaxed;
+        let ns = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+        self.invocations.fetch_add(1, Relaxed);
+        self.total_ns.fetch_add(ns, Relaxed);
+        self.slowest_ns.fetch_max(ns, Relaxed);
+        if ns > 1_000_000 {
+            self.over_1ms.fetch_add(1, Relaxed);
+        }
+    }
+}
+
+/// Event name an `await_event` waiter accepts in place of a specific name
+/// to receive the next event of any kind
+pub const ANY_EVENT: &str = "*";
+
+/// Event name and sender of one pending `await_event` waiter
+type EventWaiterEntry = (String, oneshot::Sender<PusherEvent>);
+
 /// Callback for when no handlers are registered for an event
 pub type FailThroughFn = Arc<dyn Fn(&str, &PusherEvent) + Send + Sync + 'static>;
 
@@ -18,6 +94,29 @@ pub struct EventDispatcher {
     callbacks: Arc<CallbackRegistry>,
     /// Optional callback when no listeners are bound
     fail_through: Arc<RwLock<Option<FailThroughFn>>>,
+    /// Number of async callbacks currently running
+    #[cfg(not(target_arch = "wasm32"))]
+    active_async: Arc<AtomicI64>,
+    /// Bindings per event name before warning (0 disables the check)
+    max_listeners: Arc<AtomicUsize>,
+    /// Callbacks in total before warning (0 disables the check)
+    max_callbacks: Arc<AtomicUsize>,
+    /// Number of emitted events per event name
+    #[cfg(feature = "event-stats")]
+    event_counts: Arc<RwLock<HashMap<String, u64>>>,
+    /// Callback timing counters
+    #[cfg(feature = "dispatch-metrics")]
+    metrics: Arc<MetricCounters>,
+    /// Validator applied to event names passed to `bind`
+    name_validator: Arc<RwLock<EventNameValidator>>,
+    /// Pending `await_event` waiters, oldest first
+    event_waiters: Arc<Mutex<Vec<EventWaiterEntry>>>,
+    /// Time each callback may run before `emit` moves on without it
+    #[cfg(not(target_arch = "wasm32"))]
+    callback_timeout: Arc<RwLock<Option<Duration>>>,
+    /// Number of timed-out callbacks still running in the background
+    #[cfg(not(target_arch = "wasm32"))]
+    detached: Arc<AtomicUsize>,
 }
 
 impl Default for EventDispatcher {
@@ -29,9 +128,28 @@ impl Default for EventDispatcher {
 impl EventDispatcher {
     /// Create a new event dispatcher
     pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Create a dispatcher with room for callbacks on `capacity` event names
+    pub fn with_capacity(capacity: usize) -> Self {
         Self {
-            callbacks: Arc::new(CallbackRegistry::new()),
+            callbacks: Arc::new(CallbackRegistry::with_capacity(capacity)),
             fail_through: Arc::new(RwLock::new(None)),
+            #[cfg(not(target_arch = "wasm32"))]
+            active_async: Arc::new(AtomicI64::new(0)),
+            max_listeners: Arc::new(AtomicUsize::new(DEFAULT_MAX_LISTENERS)),
+            max_callbacks: Arc::new(AtomicUsize::new(0)),
+            #[cfg(feature = "event-stats")]
+            event_counts: Arc::new(RwLock::new(HashMap::new())),
+            #[cfg(feature = "dispatch-metrics")]
+            metrics: Arc::new(MetricCounters::default()),
+            name_validator: Arc::new(RwLock::new(EventNameValidator::default())),
+            event_waiters: Arc::new(Mutex::new(Vec::new())),
+            #[cfg(not(target_arch = "wasm32"))]
+            callback_timeout: Arc::new(RwLock::new(None)),
+            #[cfg(not(target_arch = "wasm32"))]
+            detached: Arc::new(AtomicUsize::new(0)),
         }
     }
 
@@ -44,21 +162,289 @@ impl EventDispatcher {
         dispatcher
     }
 
-    /// Bind a callback to a specific event
+    /// Bind a callback to a specific event.
+    ///
+    /// Returns `SockudoError::InvalidEventName` if the name fails the
+    /// dispatcher's event name validator.
     pub fn bind(
         &self,
         event_name: impl Into<String>,
         callback: impl Fn(&PusherEvent) + Send + Sync + 'static,
+    ) -> Result<u64> {
+        let name = event_name.into();
+        self.validate_event_name(&name)?;
+        Ok(self.bind_unchecked(name, callback))
+    }
+
+    /// Bind without validating the name, for protocol events bound internally
+    pub(crate) fn bind_unchecked(
+        &self,
+        event_name: impl Into<String>,
+        callback: impl Fn(&PusherEvent) + Send + Sync + 'static,
     ) -> u64 {
         let name = event_name.into();
         debug!("Binding callback for event: {}", name);
-        self.callbacks.add(name, callback)
+        let id = self.callbacks.add(name.clone(), callback);
+        self.check_listener_limit(&name);
+        self.check_callback_limit();
+        id
+    }
+
+    /// Bind a callback that is removed the first time it fires.
+    ///
+    /// It counts towards `callback_count` until then.
+    pub fn bind_once(
+        &self,
+        event_name: impl Into<String>,
+        callback: impl Fn(&PusherEvent) + Send + Sync + 'static,
+    ) -> Result<u64> {
+        let name = event_name.into();
+        self.validate_event_name(&name)?;
+        debug!("Binding one-time callback for event: {}", name);
+        let id = self.callbacks.add_once(name.clone(), callback);
+        self.check_listener_limit(&name);
+        self.check_callback_limit();
+        Ok(id)
+    }
+
+    /// Replace the validator applied to event names passed to `bind`
+    pub fn set_name_validator(&self, validator: EventNameValidator) {
+        *self.name_validator.write() = validator;
+    }
+
+    /// Check `event_name` against the dispatcher's validator
+    pub fn validate_event_name(&self, event_name: &str) -> Result<()> {
+        self.name_validator.read().validate(event_name)
+    }
+
+    /// Bind a callback to every event whose name matches a regular expression.
+    ///
+    /// The pattern is compiled once here; an invalid pattern returns
+    /// `SockudoError::InvalidPattern`. Pattern bindings fire in registration
+    /// order after the exact bindings for the event, and in addition to them.
+    /// Use `^...$` anchors to match whole event names.
+    pub fn bind_regex(
+        &self,
+        pattern: &str,
+        callback: impl Fn(&PusherEvent) + Send + Sync + 'static,
+    ) -> Result<u64> {
+        let regex = regex::Regex::new(pattern)?;
+        debug!("Binding callback for pattern: {}", pattern);
+        let id = self.callbacks.add_regex(regex, callback);
+        self.check_callback_limit();
+        Ok(id)
+    }
+
+    /// Bind a callback to `event_name` on channels matching `channel_pattern`.
+    ///
+    /// The pattern is an exact channel name or a glob where `*` matches any
+    /// characters, e.g. `"room-*"`. Events without a channel never match.
+    /// Channel-scoped bindings fire after the exact and pattern bindings for
+    /// the event.
+    ///
+    /// Returns `SockudoError::InvalidEventName` if the name fails the
+    /// dispatcher's event name validator.
+    pub fn bind_channel_scoped(
+        &self,
+        channel_pattern: &str,
+        event_name: &str,
+        callback: impl Fn(&PusherEvent) + Send + Sync + 'static,
+    ) -> Result<u64> {
+        self.validate_event_name(event_name)?;
+        debug!(
+            "Binding callback for event {} on channels {}",
+            event_name, channel_pattern
+        );
+        let id = self
+            .callbacks
+            .add_channel_scoped(channel_pattern, event_name, callback);
+        self.check_callback_limit();
+        Ok(id)
+    }
+
+    /// Set the number of bindings per event name above which `bind` warns
+    /// about a possible listener leak (0 disables the warning)
+    pub fn set_max_listeners(&self, limit: usize) {
+        self.max_listeners
+            .store(limit, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Get the listener leak warning threshold
+    pub fn max_listeners(&self) -> usize {
+        self.max_listeners
+            .load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// Set the total number of callbacks above which binding another warns
+    /// about a possible leak (0, the default, disables the warning)
+    pub fn set_max_callbacks(&self, limit: usize) {
+        self.max_callbacks
+            .store(limit, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Get the total callback leak warning threshold
+    pub fn max_callbacks(&self) -> usize {
+        self.max_callbacks
+            .load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// Warn once when the total number of callbacks first exceeds the limit
+    fn check_callback_limit(&self) {
+        let limit = self.max_callbacks();
+        if limit > 0 && self.callbacks.callback_count() == limit + 1 {
+            warn!(
+                "Possible callback leak: {} callbacks bound (limit {}). \
+                 Unbind callbacks that are no longer needed.",
+                limit + 1,
+                limit
+            );
+        }
+    }
+
+    /// Warn once when the bindings for `event_name` first exceed the limit
+    fn check_listener_limit(&self, event_name: &str) {
+        let limit = self.max_listeners();
+        if limit > 0 && self.callbacks.binding_count(event_name) == limit + 1 {
+            warn!(
+                "Possible listener leak: {} listeners bound for '{}' (limit {}). \
+                 Use set_max_listeners to raise the limit.",
+                limit + 1,
+                event_name,
+                limit
+            );
+        }
+    }
+
+    /// Bind an async callback to a specific event.
+    ///
+    /// Each invocation runs as a separate tokio task, so it must be emitted
+    /// from within a tokio runtime. Use `drain` to wait for running tasks.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn bind_async<F, Fut>(&self, event_name: impl Into<String>, callback: F) -> Result<u64>
+    where
+        F: Fn(PusherEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let active = self.active_async.clone();
+        self.bind(event_name, move |event| {
+            let handle = match tokio::runtime::Handle::try_current() {
+                Ok(handle) => handle,
+                Err(_) => {
+                    warn!(
+                        "Async callback for '{}' skipped: no tokio runtime",
+                        event.event
+                    );
+                    return;
+                }
+            };
+
+            let guard = ActiveGuard::new(active.clone());
+            let future = callback(event.clone());
+            handle.spawn(async move {
+                let _guard = guard;
+                future.await;
+            });
+        })
+    }
+
+    /// Wait for running async callbacks to finish, up to `timeout`.
+    ///
+    /// Returns the number of callbacks still running when the timeout
+    /// expired, or 0 if all completed. Sync callbacks run inline during
+    /// `emit`, so they never count. Call this before disconnecting to avoid
+    /// losing in-flight work.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn drain(&self, timeout: Duration) -> usize {
+        let active = self.active_async.clone();
+        let wait = async {
+            while active.load(Ordering::SeqCst) > 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+
+        match tokio::time::timeout(timeout, wait).await {
+            Ok(()) => 0,
+            Err(_) => self.active_async.load(Ordering::SeqCst).max(0) as usize,
+        }
+    }
+
+    /// Register a waiter for the next emission of `event_name`.
+    ///
+    /// Waiters for the same name are served oldest first, one per event.
+    /// Pass `ANY_EVENT` to receive the next event of any name.
+    pub fn next_event(&self, event_name: impl Into<String>) -> oneshot::Receiver<PusherEvent> {
+        let (sender, receiver) = oneshot::channel();
+        let mut waiters = self.event_waiters.lock();
+        waiters.retain(|(_, sender)| !sender.is_canceled());
+        waiters.push((event_name.into(), sender));
+        receiver
+    }
+
+    /// Wait for the next emission of `event_name`, up to `timeout`.
+    ///
+    /// The waiter is registered when this is called, not when the returned
+    /// future is first polled, so concurrent callers are served in call order.
+    /// Returns `SockudoError::TimeoutError` if no matching event arrives in time.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn await_event(
+        &self,
+        event_name: &str,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<PusherEvent>> + Send + 'static {
+        let receiver = self.next_event(event_name);
+        let event_name = event_name.to_string();
+        async move {
+            let deadline = tokio::time::Instant::now() + timeout;
+            receive_event(receiver, &event_name, deadline).await
+        }
+    }
+
+    /// Wait for the next `count` emissions of `event_name`, sharing one
+    /// `timeout` across all of them.
+    ///
+    /// Each slot holds the event received, or a timeout error for events
+    /// that did not arrive before the deadline.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn await_events(
+        &self,
+        event_name: &str,
+        count: usize,
+        timeout: Duration,
+    ) -> impl Future<Output = Vec<Result<PusherEvent>>> + Send + 'static {
+        let receivers: Vec<_> = (0..count).map(|_| self.next_event(event_name)).collect();
+        let event_name = event_name.to_string();
+        async move {
+            let deadline = tokio::time::Instant::now() + timeout;
+            let mut results = Vec::with_capacity(receivers.len());
+            for receiver in receivers {
+                results.push(receive_event(receiver, &event_name, deadline).await);
+            }
+            results
+        }
+    }
+
+    /// Hand `event` to the oldest waiter for its name and the oldest
+    /// `ANY_EVENT` waiter
+    fn notify_waiters(&self, event: &PusherEvent) {
+        let mut waiters = self.event_waiters.lock();
+        if waiters.is_empty() {
+            return;
+        }
+        waiters.retain(|(_, sender)| !sender.is_canceled());
+        for name in [event.event.as_str(), ANY_EVENT] {
+            if let Some(index) = waiters.iter().position(|(waiting, _)| waiting == name) {
+                let (_, sender) = waiters.remove(index);
+                let _ = sender.send(event.clone());
+            }
+        }
     }
 
     /// Bind a callback to all events (global binding)
     pub fn bind_global(&self, callback: impl Fn(&PusherEvent) + Send + Sync + 'static) -> u64 {
         debug!("Binding global callback");
-        self.callbacks.add_global(callback)
+        let id = self.callbacks.add_global(callback);
+        self.check_callback_limit();
+        id
     }
 
     /// Unbind callbacks from an event
@@ -82,26 +468,83 @@ impl EventDispatcher {
         self.callbacks.clear();
     }
 
-    /// Emit an event to all registered callbacks
+    /// Limit how long `emit` waits for each callback (`None` for no limit).
+    ///
+    /// See `emit_with_timeout` for how an overrunning callback is handled.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_callback_timeout(&self, timeout: Option<Duration>) {
+        *self.callback_timeout.write() = timeout;
+    }
+
+    /// Emit an event to all registered callbacks.
+    ///
+    /// With a callback timeout set, this behaves like `emit_with_timeout`.
     pub fn emit(&self, event: &PusherEvent) {
+        #[cfg(not(target_arch = "wasm32"))]
+        let timeout = *self.callback_timeout.read();
+        #[cfg(target_arch = "wasm32")]
+        let timeout = None;
+        self.dispatch(event, timeout);
+    }
+
+    /// Emit an event, waiting at most `timeout` for each callback.
+    ///
+    /// Callbacks run on tokio's blocking pool, or a new thread outside a
+    /// runtime. On a multi-threaded runtime the wait goes through
+    /// `block_in_place`, so other tasks move off the emitting worker.
+    ///
+    /// A sync callback is never cancelled: one that overruns is logged,
+    /// counted in `DispatchMetrics::timed_out_callbacks` and left to finish
+    /// in the background, keeping its thread, while the remaining callbacks
+    /// run. It may therefore still be running when later callbacks start.
+    /// Once `MAX_DETACHED_CALLBACKS` such callbacks are running, callbacks
+    /// are invoked on the emitting thread without a timeout until one of
+    /// them finishes.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn emit_with_timeout(&self, event: &PusherEvent, timeout: Duration) {
+        self.dispatch(event, Some(timeout));
+    }
+
+    /// Run every callback for `event`, with an optional per-callback timeout
+    fn dispatch(&self, event: &PusherEvent, timeout: Option<Duration>) {
         let event_name = &event.event;
 
+        #[cfg(feature = "event-stats")]
+        {
+            *self
+                .event_counts
+                .write()
+                .entry(event_name.clone())
+                .or_insert(0) += 1;
+        }
+
+        #[cfg(feature = "dispatch-metrics")]
+        self.metrics
+            .dispatches
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
         // Call global callbacks first
         for callback in self.callbacks.get_global() {
             if let Err(e) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                callback.invoke(event);
+                self.invoke(&callback, event, timeout);
             })) {
                 warn!("Global callback panicked: {:?}", e);
             }
         }
 
-        // Call event-specific callbacks
-        let callbacks = self.callbacks.get(event_name);
+        // Call event-specific callbacks, then pattern callbacks, then
+        // callbacks scoped to the event's channel
+        let mut callbacks = self.callbacks.get(event_name);
+        callbacks.extend(self.callbacks.get_matching(event_name));
+        callbacks.extend(
+            self.callbacks
+                .get_channel_scoped(event.channel.as_deref(), event_name),
+        );
 
         if !callbacks.is_empty() {
             for callback in callbacks {
                 if let Err(e) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                    callback.invoke(event);
+                    self.invoke(&callback, event, timeout);
                 })) {
                     warn!("Callback for '{}' panicked: {:?}", event_name, e);
                 }
@@ -113,6 +556,168 @@ impl EventDispatcher {
                 fail_through(event_name, event);
             }
         }
+
+        self.notify_waiters(event);
+    }
+
+    /// Invoke one callback, timing it when dispatch metrics are enabled
+    fn invoke(&self, callback: &Callback, event: &PusherEvent, timeout: Option<Duration>) {
+        #[cfg(feature = "dispatch-metrics")]
+        let started = std::time::Instant::now();
+        match timeout {
+            #[cfg(not(target_arch = "wasm32"))]
+            Some(timeout) => self.invoke_with_timeout(callback, event, timeout),
+            _ => callback.invoke(event),
+        }
+        #[cfg(feature = "dispatch-metrics")]
+        self.metrics.record(started.elapsed());
+    }
+
+    /// Run a callback off the current thread and wait up to `timeout` for it
+    #[cfg(not(target_arch = "wasm32"))]
+    fn invoke_with_timeout(&self, callback: &Callback, event: &PusherEvent, timeout: Duration) {
+        use std::sync::atomic::AtomicU8;
+        use std::sync::mpsc::{self, RecvTimeoutError};
+        use tokio::runtime::{Handle, RuntimeFlavor};
+
+        const RUNNING: u8 = 0;
+        const FINISHED: u8 = 1;
+        const DETACHED: u8 = 2;
+
+        if self.detached.load(Ordering::SeqCst) >= MAX_DETACHED_CALLBACKS {
+            warn!(
+                "{} timed-out callbacks still running; invoking '{}' callback without a timeout",
+                MAX_DETACHED_CALLBACKS, event.event
+            );
+            callback.invoke(event);
+            return;
+        }
+
+        let (done_tx, done_rx) = mpsc::sync_channel(1);
+        let status = Arc::new(AtomicU8::new(RUNNING));
+        let run = {
+            let callback = callback.clone();
+            let event = event.clone();
+            let status = status.clone();
+            let detached = self.detached.clone();
+            move || {
+                let invoke = std::panic::AssertUnwindSafe(|| callback.invoke(&event));
+                if std::panic::catch_unwind(invoke).is_ok() {
+                    let _ = done_tx.send(());
+                }
+                // A callback the emitter gave up on releases its slot
+                if status
+                    .compare_exchange(RUNNING, FINISHED, Ordering::SeqCst, Ordering::SeqCst)
+                    .is_err()
+                {
+                    detached.fetch_sub(1, Ordering::SeqCst);
+                }
+            }
+        };
+        let handle = Handle::try_current().ok();
+        match handle {
+            Some(ref handle) => {
+                handle.spawn_blocking(run);
+            }
+            None => {
+                std::thread::spawn(run);
+            }
+        }
+
+        let wait = || done_rx.recv_timeout(timeout);
+        let result = match handle {
+            Some(ref handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+                tokio::task::block_in_place(wait)
+            }
+            _ => wait(),
+        };
+        match result {
+            Ok(()) => {}
+            Err(RecvTimeoutError::Timeout) => {
+                self.detached.fetch_add(1, Ordering::SeqCst);
+                if status
+                    .compare_exchange(RUNNING, DETACHED, Ordering::SeqCst, Ordering::SeqCst)
+                    .is_err()
+                {
+                    // Finished just after the deadline
+                    self.detached.fetch_sub(1, Ordering::SeqCst);
+                    return;
+                }
+                warn!(
+                    "Callback for '{}' exceeded the {:?} callback timeout; continuing without it",
+                    event.event, timeout
+                );
+                #[cfg(feature = "dispatch-metrics")]
+                self.metrics
+                    .timed_out
+                    .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                warn!("Callback for '{}' panicked", event.event);
+            }
+        }
+    }
+
+    /// Emit an event built locally, as in tests
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn emit_local(&self, event: PusherEvent) {
+        self.emit(&event);
+    }
+
+    /// Number of times `event_name` has been emitted
+    #[cfg(feature = "event-stats")]
+    pub fn event_count(&self, event_name: &str) -> u64 {
+        self.event_counts
+            .read()
+            .get(event_name)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Snapshot of the emit count for every event name seen so far
+    #[cfg(feature = "event-stats")]
+    pub fn event_stats(&self) -> HashMap<String, u64> {
+        self.event_counts.read().clone()
+    }
+
+    /// Total number of events emitted
+    #[cfg(feature = "event-stats")]
+    pub fn total_event_count(&self) -> u64 {
+        self.event_counts.read().values().sum()
+    }
+
+    /// Reset all event counters
+    #[cfg(feature = "event-stats")]
+    pub fn reset_event_stats(&self) {
+        self.event_counts.write().clear();
+    }
+
+    /// Snapshot of callback timing since creation or the last reset
+    #[cfg(feature = "dispatch-metrics")]
+    pub fn metrics(&self) -> DispatchMetrics {
+        use std::sync::atomic::Ordering::Relaxed;
+        let invocations = self.metrics.invocations.load(Relaxed);
+        let total_ns = self.metrics.total_ns.load(Relaxed);
+        DispatchMetrics {
+            total_dispatches: self.metrics.dispatches.load(Relaxed),
+            total_callback_invocations: invocations,
+            slowest_callback_ns: self.metrics.slowest_ns.load(Relaxed),
+            average_callback_ns: total_ns.checked_div(invocations).unwrap_or(0),
+            callbacks_over_1ms: self.metrics.over_1ms.load(Relaxed),
+            timed_out_callbacks: self.metrics.timed_out.load(Relaxed),
+        }
+    }
+
+    /// Reset all callback timing counters
+    #[cfg(feature = "dispatch-metrics")]
+    pub fn reset_metrics(&self) {
+        use std::sync::atomic::Ordering::Relaxed;
+        self.metrics.dispatches.store(0, Relaxed);
+        self.metrics.invocations.store(0, Relaxed);
+        self.metrics.total_ns.store(0, Relaxed);
+        self.metrics.slowest_ns.store(0, Relaxed);
+        self.metrics.over_1ms.store(0, Relaxed);
+        self.metrics.timed_out.store(0, Relaxed);
     }
 
     /// Emit an event with a specific name and data (WASM version)
@@ -140,6 +745,27 @@ impl EventDispatcher {
     pub fn callback_count(&self) -> usize {
         self.callbacks.callback_count()
     }
+
+    /// Count the registered callbacks by kind
+    pub fn memory_usage(&self) -> RegistryMemoryStats {
+        self.callbacks.memory_usage()
+    }
+
+    /// Check whether emitting `event_name` would invoke any callback,
+    /// including global, pattern and channel-scoped bindings
+    pub fn has_listeners(&self, event_name: &str) -> bool {
+        self.callbacks.listener_count(event_name) > 0
+    }
+
+    /// Count the callbacks that emitting `event_name` would invoke,
+    /// including global, pattern and channel-scoped bindings (whatever their
+    /// channel). With `None`, count every registered callback.
+    pub fn listener_count(&self, event_name: Option<&str>) -> usize {
+        match event_name {
+            Some(name) => self.callbacks.listener_count(name),
+            None => self.callbacks.callback_count(),
+        }
+    }
 }
 
 impl std::fmt::Debug for EventDispatcher {
@@ -150,20 +776,101 @@ impl std::fmt::Debug for EventDispatcher {
     }
 }
 
+/// Resolve an `await_event` waiter, or fail once `deadline` passes
+#[cfg(not(target_arch = "wasm32"))]
+async fn receive_event(
+    receiver: oneshot::Receiver<PusherEvent>,
+    event_name: &str,
+    deadline: tokio::time::Instant,
+) -> Result<PusherEvent> {
+    match tokio::time::timeout_at(deadline, receiver).await {
+        Ok(Ok(event)) => Ok(event),
+        Ok(Err(_)) => Err(SockudoError::invalid_state("Event waiter was cancelled")),
+        Err(_) => Err(SockudoError::timeout(format!(
+            "Timed out waiting for event '{}'",
+            event_name
+        ))),
+    }
+}
+
+/// Keeps the active async callback count raised until dropped, including
+/// when the callback panics
+#[cfg(not(target_arch = "wasm32"))]
+struct ActiveGuard(Arc<AtomicI64>);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ActiveGuard {
+    fn new(active: Arc<AtomicI64>) -> Self {
+        active.fetch_add(1, Ordering::SeqCst);
+        Self(active)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for ActiveGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::sync::atomic::{AtomicUsize, Ordering};
 
+    #[cfg(feature = "event-stats")]
+    #[test]
+    fn test_event_stats() {
+        let dispatcher = EventDispatcher::new();
+        dispatcher.emit(&PusherEvent::new("a"));
+        dispatcher.emit(&PusherEvent::new("a"));
+        dispatcher.emit(&PusherEvent::new("b"));
+
+        assert_eq!(dispatcher.event_count("a"), 2);
+        assert_eq!(dispatcher.event_count("missing"), 0);
+        assert_eq!(dispatcher.total_event_count(), 3);
+        assert_eq!(dispatcher.event_stats().get("b"), Some(&1));
+
+        dispatcher.reset_event_stats();
+        assert!(dispatcher.event_stats().is_empty());
+    }
+
+    #[cfg(feature = "dispatch-metrics")]
+    #[test]
+    fn test_dispatch_metrics() {
+        let dispatcher = EventDispatcher::new();
+        dispatcher.bind("fast", |_| {}).unwrap();
+        dispatcher
+            .bind("slow", |_| {
+                std::thread::sleep(Duration::from_millis(2));
+            })
+            .unwrap();
+
+        dispatcher.emit(&PusherEvent::new("fast"));
+        dispatcher.emit(&PusherEvent::new("slow"));
+
+        let metrics = dispatcher.metrics();
+        assert_eq!(metrics.total_dispatches, 2);
+        assert_eq!(metrics.total_callback_invocations, 2);
+        assert_eq!(metrics.callbacks_over_1ms, 1);
+        assert!(metrics.slowest_callback_ns >= 2_000_000);
+        assert!(metrics.average_callback_ns >= 1_000_000);
+
+        dispatcher.reset_metrics();
+        assert_eq!(dispatcher.metrics(), DispatchMetrics::default());
+    }
+
     #[test]
     fn test_bind_and_emit() {
         let dispatcher = EventDispatcher::new();
         let counter = Arc::new(AtomicUsize::new(0));
         let counter_clone = counter.clone();
 
-        dispatcher.bind("test-event", move |_| {
-            counter_clone.fetch_add(1, Ordering::SeqCst);
-        });
+        dispatcher
+            .bind("test-event", move |_| {
+                counter_clone.fetch_add(1, Ordering::SeqCst);
+            })
+            .unwrap();
 
         let event = PusherEvent::new("test-event");
         dispatcher.emit(&event);
@@ -208,9 +915,11 @@ mod tests {
         let counter = Arc::new(AtomicUsize::new(0));
         let counter_clone = counter.clone();
 
-        let id = dispatcher.bind("test-event", move |_| {
-            counter_clone.fetch_add(1, Ordering::SeqCst);
-        });
+        let id = dispatcher
+            .bind("test-event", move |_| {
+                counter_clone.fetch_add(1, Ordering::SeqCst);
+            })
+            .unwrap();
 
         dispatcher.emit(&PusherEvent::new("test-event"));
         assert_eq!(counter.load(Ordering::SeqCst), 1);
@@ -220,4 +929,276 @@ mod tests {
         dispatcher.emit(&PusherEvent::new("test-event"));
         assert_eq!(counter.load(Ordering::SeqCst), 1); // Should not increment
     }
+
+    #[test]
+    fn test_max_listeners_warning() {
+        use std::io::Write;
+
+        #[derive(Clone, Default)]
+        struct Capture(Arc<parking_lot::Mutex<Vec<u8>>>);
+
+        impl Write for Capture {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let capture = Capture::default();
+        let writer = capture.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_max_level(tracing::Level::WARN)
+            .finish();
+        let warnings = || {
+            String::from_utf8_lossy(&capture.0.lock())
+                .matches("Possible listener leak")
+                .count()
+        };
+
+        tracing::subscriber::with_default(subscriber, || {
+            let dispatcher = EventDispatcher::new();
+            dispatcher.set_max_listeners(3);
+
+            let ids: Vec<u64> = (0..3)
+                .map(|_| dispatcher.bind("price", |_| {}).unwrap())
+                .collect();
+            assert_eq!(warnings(), 0);
+
+            dispatcher.bind("price", |_| {}).unwrap();
+            assert_eq!(warnings(), 1);
+            dispatcher.bind("price", |_| {}).unwrap();
+            assert_eq!(warnings(), 1);
+
+            dispatcher.unbind(Some("price"), Some(ids[0]));
+            dispatcher.unbind(Some("price"), Some(ids[1]));
+            assert_eq!(dispatcher.listener_count(Some("price")), 3);
+
+            // Crossing the limit again warns again
+            dispatcher.bind("price", |_| {}).unwrap();
+            assert_eq!(warnings(), 2);
+        });
+    }
+
+    #[test]
+    fn test_bind_once_memory_usage() {
+        let dispatcher = EventDispatcher::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..3 {
+            let calls = calls.clone();
+            dispatcher
+                .bind_once("ready", move |_| {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                })
+                .unwrap();
+        }
+        let usage = dispatcher.memory_usage();
+        assert_eq!(usage.total_callbacks, 3);
+        assert_eq!(usage.expiry_callbacks, 3);
+
+        dispatcher.emit(&PusherEvent::new("ready"));
+        dispatcher.emit(&PusherEvent::new("ready"));
+
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(dispatcher.memory_usage(), RegistryMemoryStats::default());
+    }
+
+    #[test]
+    fn test_listener_count_after_unbind() {
+        let dispatcher = EventDispatcher::new();
+        assert!(!dispatcher.has_listeners("price"));
+
+        let first = dispatcher.bind("price", |_| {}).unwrap();
+        let second = dispatcher.bind("price", |_| {}).unwrap();
+        let global = dispatcher.bind_global(|_| {});
+        dispatcher.bind("volume", |_| {}).unwrap();
+
+        assert!(dispatcher.has_listeners("price"));
+        assert_eq!(dispatcher.listener_count(Some("price")), 3);
+        assert_eq!(dispatcher.listener_count(Some("other")), 1);
+        assert_eq!(dispatcher.listener_count(None), 4);
+
+        dispatcher.unbind(Some("price"), Some(first));
+        assert_eq!(dispatcher.listener_count(Some("price")), 2);
+
+        dispatcher.unbind_global(Some(global));
+        assert_eq!(dispatcher.listener_count(Some("price")), 1);
+        assert!(!dispatcher.has_listeners("other"));
+
+        dispatcher.unbind(Some("price"), Some(second));
+        assert!(!dispatcher.has_listeners("price"));
+        assert_eq!(dispatcher.listener_count(None), 1);
+    }
+
+    #[test]
+    fn test_bind_regex_overlapping_patterns() {
+        let dispatcher = EventDispatcher::new();
+        let counter = |dispatcher: &EventDispatcher, pattern: &str| {
+            let count = Arc::new(AtomicUsize::new(0));
+            let count_clone = count.clone();
+            dispatcher
+                .bind_regex(pattern, move |_| {
+                    count_clone.fetch_add(1, Ordering::SeqCst);
+                })
+                .unwrap();
+            count
+        };
+
+        let rooms = counter(&dispatcher, r"^chat:message:\d+$");
+        let chat = counter(&dispatcher, r"^chat:");
+        let digits = counter(&dispatcher, r"\d+$");
+        let exact = Arc::new(AtomicUsize::new(0));
+        let exact_clone = exact.clone();
+        dispatcher
+            .bind("chat:message:123", move |_| {
+                exact_clone.fetch_add(1, Ordering::SeqCst);
+            })
+            .unwrap();
+
+        assert_eq!(dispatcher.listener_count(Some("chat:message:123")), 4);
+
+        dispatcher.emit(&PusherEvent::new("chat:message:123"));
+        dispatcher.emit(&PusherEvent::new("chat:typing"));
+        dispatcher.emit(&PusherEvent::new("order:42"));
+
+        assert_eq!(exact.load(Ordering::SeqCst), 1);
+        assert_eq!(rooms.load(Ordering::SeqCst), 1);
+        assert_eq!(chat.load(Ordering::SeqCst), 2);
+        assert_eq!(digits.load(Ordering::SeqCst), 2);
+
+        let err = dispatcher.bind_regex("chat:(", |_| {}).unwrap_err();
+        assert!(matches!(err, crate::SockudoError::InvalidPattern { .. }));
+    }
+
+    #[test]
+    fn test_bind_channel_scoped() {
+        let dispatcher = EventDispatcher::new();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let received_clone = received.clone();
+        let id = dispatcher
+            .bind_channel_scoped("room-*", "message", move |event| {
+                received_clone
+                    .lock()
+                    .push(event.channel.clone().unwrap_or_default());
+            })
+            .unwrap();
+        assert!(dispatcher.has_listeners("message"));
+        assert_eq!(dispatcher.listener_count(Some("message")), 1);
+        assert_eq!(dispatcher.listener_count(Some("ping")), 0);
+        assert!(matches!(
+            dispatcher.bind_channel_scoped("room-*", "", |_| {}),
+            Err(crate::SockudoError::InvalidEventName { .. })
+        ));
+
+        for (channel, event) in [
+            ("room-1", "message"),
+            ("room-99", "message"),
+            ("lobby", "message"),
+            ("room-1", "ping"),
+        ] {
+            dispatcher.emit(&PusherEvent::new(event).with_channel(channel));
+        }
+        dispatcher.emit(&PusherEvent::new("message"));
+
+        assert_eq!(*received.lock(), vec!["room-1", "room-99"]);
+
+        dispatcher.unbind(Some("message"), Some(id));
+        dispatcher.emit(&PusherEvent::new("message").with_channel("room-1"));
+        assert_eq!(received.lock().len(), 2);
+        assert_eq!(dispatcher.callback_count(), 0);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_drain_waits_for_async_callbacks() {
+        let dispatcher = EventDispatcher::new();
+        let done = Arc::new(AtomicUsize::new(0));
+        let done_clone = done.clone();
+
+        dispatcher
+            .bind_async("slow-event", move |_| {
+                let done = done_clone.clone();
+                async move {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    done.fetch_add(1, Ordering::SeqCst);
+                }
+            })
+            .unwrap();
+        dispatcher.bind("sync-event", |_| {}).unwrap();
+
+        dispatcher.emit(&PusherEvent::new("sync-event"));
+        assert_eq!(dispatcher.drain(Duration::from_millis(10)).await, 0);
+
+        dispatcher.emit(&PusherEvent::new("slow-event"));
+        assert_eq!(dispatcher.drain(Duration::from_millis(10)).await, 1);
+        assert_eq!(dispatcher.drain(Duration::from_secs(5)).await, 0);
+        assert_eq!(done.load(Ordering::SeqCst), 1);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_callback_timeout() {
+        let dispatcher = EventDispatcher::new();
+        dispatcher.set_callback_timeout(Some(Duration::from_millis(50)));
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let log = received.clone();
+        dispatcher
+            .bind("slow", move |_| {
+                std::thread::sleep(Duration::from_millis(200));
+                log.lock().push("slow");
+            })
+            .unwrap();
+        let log = received.clone();
+        dispatcher
+            .bind("fast", move |_| log.lock().push("fast"))
+            .unwrap();
+
+        let started = std::time::Instant::now();
+        dispatcher.emit(&PusherEvent::new("slow"));
+        assert!(started.elapsed() < Duration::from_millis(150));
+
+        dispatcher.emit(&PusherEvent::new("fast"));
+        assert_eq!(*received.lock(), vec!["fast"]);
+
+        #[cfg(feature = "dispatch-metrics")]
+        assert_eq!(dispatcher.metrics().timed_out_callbacks, 1);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_callback_timeout_caps_detached_callbacks() {
+        let dispatcher = EventDispatcher::new();
+        dispatcher.set_callback_timeout(Some(Duration::from_millis(10)));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let calls_clone = calls.clone();
+        dispatcher
+            .bind("slow", move |_| {
+                std::thread::sleep(Duration::from_millis(300));
+                calls_clone.fetch_add(1, Ordering::SeqCst);
+            })
+            .unwrap();
+
+        for _ in 0..MAX_DETACHED_CALLBACKS {
+            dispatcher.emit(&PusherEvent::new("slow"));
+        }
+        assert_eq!(
+            dispatcher.detached.load(Ordering::SeqCst),
+            MAX_DETACHED_CALLBACKS
+        );
+
+        // With every slot taken the callback runs to completion inline
+        let before = calls.load(Ordering::SeqCst);
+        dispatcher.emit(&PusherEvent::new("slow"));
+        assert!(calls.load(Ordering::SeqCst) > before);
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(dispatcher.detached.load(Ordering::SeqCst), 0);
+        assert_eq!(calls.load(Ordering::SeqCst), MAX_DETACHED_CALLBACKS + 1);
+    }
 }
@@ -0,0 +1,84 @@
+//! Client-side event filtering applied before dispatch.
+
+use crate::protocol::PusherEvent;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Predicate deciding whether an incoming event should be dispatched.
+pub type EventFilterFn = Arc<dyn Fn(&PusherEvent) -> bool + Send + Sync + 'static>;
+
+/// A global whitelist applied to incoming events before they reach any callbacks.
+///
+/// The predicate runs synchronously in the receive loop, so it must be cheap
+/// and must not block. Protocol events (`pusher:*` and `pusher_internal:*`)
+/// always bypass the filter so the connection and subscriptions keep working.
+#[derive(Clone)]
+pub struct EventFilter {
+    predicate: EventFilterFn,
+}
+
+impl EventFilter {
+    /// Create a filter from a predicate
+    pub fn new(predicate: impl Fn(&PusherEvent) -> bool + Send + Sync + 'static) -> Self {
+        Self {
+            predicate: Arc::new(predicate),
+        }
+    }
+
+    /// Only allow events whose name is in `names`
+    pub fn by_names(names: &[&str]) -> Self {
+        let names: HashSet<String> = names.iter().map(|n| n.to_string()).collect();
+        Self::new(move |event| names.contains(&event.event))
+    }
+
+    /// Only allow events sent on channels starting with `prefix`
+    pub fn by_channel_prefix(prefix: &str) -> Self {
+        let prefix = prefix.to_string();
+        Self::new(move |event| {
+            event
+                .channel
+                .as_deref()
+                .map(|channel| channel.starts_with(&prefix))
+                .unwrap_or(false)
+        })
+    }
+
+    /// Check whether an event passes the filter
+    pub fn allows(&self, event: &PusherEvent) -> bool {
+        if event.event.starts_with("pusher:") || event.event.starts_with("pusher_internal:") {
+            return true;
+        }
+        (self.predicate)(event)
+    }
+}
+
+impl std::fmt::Debug for EventFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventFilter").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_by_names() {
+        let filter = EventFilter::by_names(&["price-update"]);
+        assert!(filter.allows(&PusherEvent::new("price-update")));
+        assert!(!filter.allows(&PusherEvent::new("noise-event")));
+        // Protocol events always pass
+        assert!(filter.allows(&PusherEvent::new("pusher:connection_established")));
+    }
+
+    #[test]
+    fn test_by_channel_prefix() {
+        let filter = EventFilter::by_channel_prefix("private-");
+        let mut event = PusherEvent::new("update");
+        assert!(!filter.allows(&event));
+        event.channel = Some("private-orders".to_string());
+        assert!(filter.allows(&event));
+        event.channel = Some("public-orders".to_string());
+        assert!(!filter.allows(&event));
+    }
+}
@@ -0,0 +1,162 @@
+//! Bounded log of recently emitted events, for debugging.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use parking_lot::Mutex;
+use serde::Serialize;
+
+use super::dispatcher::EventDispatcher;
+use crate::protocol::PusherEvent;
+
+/// Maximum number of bytes of event data kept per logged event
+pub const MAX_LOGGED_DATA_BYTES: usize = 1024;
+
+/// An event recorded by an [`EventLogGuard`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LoggedEvent {
+    /// Event name
+    pub event: String,
+    /// Channel the event arrived on, if any
+    pub channel: Option<String>,
+    /// Event data, cut to at most 1 KB
+    pub data: Option<String>,
+    /// Whether `data` was cut
+    pub truncated: bool,
+    /// When the event was received
+    pub timestamp: SystemTime,
+}
+
+impl LoggedEvent {
+    fn from_event(event: &PusherEvent) -> Self {
+        let mut data = event.data.as_ref().map(|d| d.to_string());
+        let truncated = data
+            .as_mut()
+            .map(|data| truncate_to_char_boundary(data, MAX_LOGGED_DATA_BYTES))
+            .unwrap_or(false);
+
+        Self {
+            event: event.event.clone(),
+            channel: event.channel.clone(),
+            data,
+            truncated,
+            timestamp: event.timestamp.unwrap_or_else(SystemTime::now),
+        }
+    }
+}
+
+/// Cut `text` to at most `max_bytes` without splitting a character,
+/// returning whether anything was removed
+fn truncate_to_char_boundary(text: &mut String, max_bytes: usize) -> bool {
+    if text.len() <= max_bytes {
+        return false;
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    true
+}
+
+/// Records the most recent events emitted by a dispatcher while alive.
+///
+/// Holds at most `capacity` events, dropping the oldest first. Dropping
+/// the guard removes its global binding.
+pub struct EventLogGuard {
+    buffer: Arc<Mutex<VecDeque<LoggedEvent>>>,
+    dispatcher: EventDispatcher,
+    binding: u64,
+}
+
+impl EventLogGuard {
+    /// Start logging the events emitted by `dispatcher`
+    pub fn new(dispatcher: &EventDispatcher, capacity: usize) -> Self {
+        let buffer = Arc::new(Mutex::new(VecDeque::with_capacity(capacity)));
+        let log = buffer.clone();
+        let binding = dispatcher.bind_global(move |event| {
+            if capacity == 0 {
+                return;
+            }
+            let mut log = log.lock();
+            if log.len() == capacity {
+                log.pop_front();
+            }
+            log.push_back(LoggedEvent::from_event(event));
+        });
+
+        Self {
+            buffer,
+            dispatcher: dispatcher.clone(),
+            binding,
+        }
+    }
+
+    /// Copy of the logged events, oldest first
+    pub fn snapshot(&self) -> Vec<LoggedEvent> {
+        self.buffer.lock().iter().cloned().collect()
+    }
+
+    /// Remove and return the logged events, oldest first
+    pub fn drain(&self) -> Vec<LoggedEvent> {
+        self.buffer.lock().drain(..).collect()
+    }
+
+    /// Number of events currently logged
+    pub fn len(&self) -> usize {
+        self.buffer.lock().len()
+    }
+
+    /// Whether no events are logged
+    pub fn is_empty(&self) -> bool {
+        self.buffer.lock().is_empty()
+    }
+}
+
+impl Drop for EventLogGuard {
+    fn drop(&mut self) {
+        self.dispatcher.unbind_global(Some(self.binding));
+    }
+}
+
+impl std::fmt::Debug for EventLogGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventLogGuard")
+            .field("len", &self.len())
+            .field("binding", &self.binding)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_log_truncates_and_unbinds() {
+        let dispatcher = EventDispatcher::new();
+        let log = EventLogGuard::new(&dispatcher, 2);
+        assert_eq!(dispatcher.callback_count(), 1);
+
+        let long = "é".repeat(600);
+        dispatcher.emit(&PusherEvent::new("big").with_json_data(serde_json::json!(long)));
+        dispatcher.emit(&PusherEvent::new("small").with_channel("news"));
+
+        let logged = log.snapshot();
+        assert_eq!(logged.len(), 2);
+        let data = logged[0].data.as_deref().unwrap();
+        assert!(logged[0].truncated);
+        // Cut before the multi-byte character that would cross the limit
+        assert_eq!(data.len(), MAX_LOGGED_DATA_BYTES - 1);
+        assert_eq!(logged[1].channel.as_deref(), Some("news"));
+        assert!(!logged[1].truncated);
+        assert!(serde_json::to_value(&logged[1]).is_ok());
+
+        assert_eq!(log.drain().len(), 2);
+        assert!(log.is_empty());
+
+        drop(log);
+        assert_eq!(dispatcher.callback_count(), 0);
+    }
+}
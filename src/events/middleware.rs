@@ -0,0 +1,325 @@
+//! Hooks that inspect, rewrite or drop events on their way in and out.
+
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::{Mutex, RwLock};
+use tracing::{debug, warn};
+
+use crate::delta::{decode_base64, encode_base64};
+use crate::protocol::PusherEvent;
+
+/// Sees every event the client sends and receives.
+///
+/// Both hooks run synchronously on the sending or receiving thread, so
+/// they must be cheap and must not block.
+pub trait ClientMiddleware: Send + Sync {
+    /// Called before an event is sent; returning `false` drops it
+    fn before_send(&self, event: &mut PusherEvent) -> bool {
+        let _ = event;
+        true
+    }
+
+    /// Called before a received event is dispatched; returning `false`
+    /// drops it
+    fn after_receive(&self, event: &mut PusherEvent) -> bool {
+        let _ = event;
+        true
+    }
+}
+
+/// Middlewares applied in registration order.
+///
+/// The first middleware returning `false` drops the event; later ones do
+/// not see it.
+#[derive(Clone, Default)]
+pub(crate) struct MiddlewareChain {
+    middlewares: Arc<RwLock<Vec<Arc<dyn ClientMiddleware>>>>,
+}
+
+impl MiddlewareChain {
+    /// Add `middleware` after those already registered
+    pub fn push(&self, middleware: Arc<dyn ClientMiddleware>) {
+        self.middlewares.write().push(middleware);
+    }
+
+    /// Pass an outgoing event through every middleware
+    pub fn before_send(&self, event: &mut PusherEvent) -> bool {
+        self.snapshot().iter().all(|mw| mw.before_send(event))
+    }
+
+    /// Pass an incoming event through every middleware
+    pub fn after_receive(&self, event: &mut PusherEvent) -> bool {
+        self.snapshot().iter().all(|mw| mw.after_receive(event))
+    }
+
+    /// Copy of the chain, so middlewares run without holding the lock
+    fn snapshot(&self) -> Vec<Arc<dyn ClientMiddleware>> {
+        self.middlewares.read().clone()
+    }
+}
+
+impl std::fmt::Debug for MiddlewareChain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MiddlewareChain")
+            .field("len", &self.middlewares.read().len())
+            .finish()
+    }
+}
+
+/// Whether `name` is a protocol event the connection depends on
+fn is_protocol_event(name: &str) -> bool {
+    name.starts_with("pusher:") || name.starts_with("pusher_internal:")
+}
+
+/// Logs every event sent and received at debug level.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoggingMiddleware;
+
+impl ClientMiddleware for LoggingMiddleware {
+    fn before_send(&self, event: &mut PusherEvent) -> bool {
+        debug!(
+            "Sending {} on {}",
+            event.event,
+            event.channel.as_deref().unwrap_or("<connection>")
+        );
+        true
+    }
+
+    fn after_receive(&self, event: &mut PusherEvent) -> bool {
+        debug!(
+            "Received {} on {}",
+            event.event,
+            event.channel.as_deref().unwrap_or("<connection>")
+        );
+        true
+    }
+}
+
+/// Drops outgoing events beyond `max_per_second` in any one-second window.
+///
+/// Protocol events (`pusher:*` and `pusher_internal:*`) are never dropped
+/// nor counted, so subscriptions keep working while client events are
+/// limited.
+///
+/// The send window is private, so build it with
+/// [`RateLimitMiddleware::new`] rather than a struct literal.
+#[derive(Debug)]
+pub struct RateLimitMiddleware {
+    /// Events allowed in any one-second window
+    pub max_per_second: u32,
+    /// When each event of the last second was sent, oldest first
+    sent: Mutex<VecDeque<Instant>>,
+}
+
+impl RateLimitMiddleware {
+    /// Allow at most `max_per_second` events per second
+    pub fn new(max_per_second: u32) -> Self {
+        Self {
+            max_per_second,
+            sent: Mutex::new(VecDeque::new()),
+        }
+    }
+}
+
+impl ClientMiddleware for RateLimitMiddleware {
+    fn before_send(&self, event: &mut PusherEvent) -> bool {
+        if is_protocol_event(&event.event) {
+            return true;
+        }
+
+        let now = Instant::now();
+        let mut sent = self.sent.lock();
+        while sent
+            .front()
+            .is_some_and(|at| now.duration_since(*at) >= Duration::from_secs(1))
+        {
+            sent.pop_front();
+        }
+        if sent.len() >= self.max_per_second as usize {
+            warn!("Rate limit reached, dropping {}", event.event);
+            return false;
+        }
+        sent.push_back(now);
+        true
+    }
+}
+
+/// Key of the object replacing compressed event data
+const GZIP_KEY: &str = "gzip";
+
+/// Gzips the data of outgoing client events larger than `min_bytes`,
+/// sending `{"gzip": "<base64>"}` in its place when that is smaller, and
+/// restores such data on incoming events.
+///
+/// Every client reading the events needs the middleware as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionMiddleware {
+    /// Data of at most this many bytes is sent as is
+    pub min_bytes: usize,
+}
+
+impl CompressionMiddleware {
+    /// Compress data larger than `min_bytes`
+    pub fn new(min_bytes: usize) -> Self {
+        Self { min_bytes }
+    }
+}
+
+impl Default for CompressionMiddleware {
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
+
+impl ClientMiddleware for CompressionMiddleware {
+    fn before_send(&self, event: &mut PusherEvent) -> bool {
+        if is_protocol_event(&event.event) {
+            return true;
+        }
+        let Some(data) = data_text(event) else {
+            return true;
+        };
+        if data.len() <= self.min_bytes {
+            return true;
+        }
+
+        let compressed = serde_json::json!({ GZIP_KEY: encode_base64(&gzip(data.as_bytes())) });
+        if compressed.to_string().len() < data.len() {
+            set_data(event, compressed);
+        }
+        true
+    }
+
+    fn after_receive(&self, event: &mut PusherEvent) -> bool {
+        let Some(payload) = gzip_payload(event) else {
+            return true;
+        };
+        let restored = decode_base64(&payload)
+            .ok()
+            .and_then(|bytes| gunzip(&bytes))
+            .and_then(|bytes| String::from_utf8(bytes).ok());
+        match restored {
+            #[cfg(feature = "wasm")]
+            Some(data) => match serde_json::from_str(&data) {
+                Ok(value) => event.data = Some(value),
+                Err(e) => warn!("Decompressed data of {} is not JSON: {}", event.event, e),
+            },
+            #[cfg(not(feature = "wasm"))]
+            Some(data) => event.data = Some(data),
+            None => warn!("Failed to decompress data of {}", event.event),
+        }
+        true
+    }
+}
+
+/// Event data as JSON text
+fn data_text(event: &PusherEvent) -> Option<Cow<'_, str>> {
+    #[cfg(feature = "wasm")]
+    return event.data.as_ref().map(|data| Cow::Owned(data.to_string()));
+    #[cfg(not(feature = "wasm"))]
+    return event.data.as_deref().map(Cow::Borrowed);
+}
+
+fn set_data(event: &mut PusherEvent, data: serde_json::Value) {
+    #[cfg(feature = "wasm")]
+    {
+        event.data = Some(data);
+    }
+    #[cfg(not(feature = "wasm"))]
+    {
+        event.data = Some(data.to_string());
+    }
+}
+
+/// Base64 gzip stream of data written by `CompressionMiddleware`, if the
+/// event carries one
+fn gzip_payload(event: &PusherEvent) -> Option<String> {
+    #[cfg(feature = "wasm")]
+    let data = event.data.clone()?;
+    #[cfg(not(feature = "wasm"))]
+    let data: serde_json::Value = {
+        // Cheap check before parsing, since most events are not compressed
+        let text = event.data.as_deref()?;
+        if !text.starts_with("{\"gzip\":") {
+            return None;
+        }
+        serde_json::from_str(text).ok()?
+    };
+    let object = data.as_object().filter(|object| object.len() == 1)?;
+    object.get(GZIP_KEY)?.as_str().map(str::to_string)
+}
+
+/// Magic bytes, deflate method and no flags, mtime or OS
+const GZIP_HEADER: [u8; 10] = [0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff];
+
+/// Compress `data` into a gzip stream
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut out = GZIP_HEADER.to_vec();
+    out.extend(miniz_oxide::deflate::compress_to_vec(data, 6));
+    out.extend(crc32fast::hash(data).to_le_bytes());
+    out.extend((data.len() as u32).to_le_bytes());
+    out
+}
+
+/// Decompress a gzip stream without optional header fields, as written by
+/// `gzip`
+fn gunzip(stream: &[u8]) -> Option<Vec<u8>> {
+    if stream.len() < GZIP_HEADER.len() + 8 || stream[..4] != GZIP_HEADER[..4] {
+        return None;
+    }
+    let (body, trailer) =
+        stream[GZIP_HEADER.len()..].split_at(stream.len() - GZIP_HEADER.len() - 8);
+    let data = miniz_oxide::inflate::decompress_to_vec(body).ok()?;
+    let crc = u32::from_le_bytes(trailer[..4].try_into().ok()?);
+    (crc32fast::hash(&data) == crc).then_some(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_order_and_drop() {
+        struct Tag(&'static str, bool);
+        impl ClientMiddleware for Tag {
+            fn before_send(&self, event: &mut PusherEvent) -> bool {
+                event.event.push_str(self.0);
+                self.1
+            }
+        }
+
+        let chain = MiddlewareChain::default();
+        chain.push(Arc::new(Tag("-a", true)));
+        chain.push(Arc::new(Tag("-b", false)));
+        chain.push(Arc::new(Tag("-c", true)));
+
+        let mut event = PusherEvent::new("client-x");
+        assert!(!chain.before_send(&mut event));
+        assert_eq!(event.event, "client-x-a-b");
+        assert!(chain.after_receive(&mut event));
+    }
+
+    #[test]
+    fn test_compression_round_trip() {
+        let compression = CompressionMiddleware::new(64);
+        let data = serde_json::json!({ "text": "a".repeat(500) });
+
+        let mut small = PusherEvent::new("client-x").with_json_data(serde_json::json!({"n": 1}));
+        assert!(compression.before_send(&mut small));
+        assert_eq!(small.data_as_value(), Some(serde_json::json!({"n": 1})));
+
+        let mut event = PusherEvent::new("client-x").with_json_data(data.clone());
+        assert!(compression.before_send(&mut event));
+        let sent = event.data_as_value().unwrap();
+        assert!(sent.get(GZIP_KEY).is_some());
+        assert!(sent.to_string().len() < data.to_string().len());
+
+        assert!(compression.after_receive(&mut event));
+        assert_eq!(event.data_as_value(), Some(data));
+
+        assert!(gunzip(b"not a gzip stream").is_none());
+    }
+}
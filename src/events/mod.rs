@@ -2,7 +2,22 @@
 
 mod dispatcher;
 mod callback;
+mod filter;
+mod log;
+mod middleware;
+mod validator;
+mod waiter;
 
-pub use dispatcher::EventDispatcher;
-pub use callback::{Callback, CallbackRegistry};
+pub use dispatcher::{DispatchMetrics, EventDispatcher, ANY_EVENT, DEFAULT_MAX_LISTENERS};
+#[cfg(not(target_arch = "wasm32"))]
+pub use dispatcher::MAX_DETACHED_CALLBACKS;
+pub use callback::{Callback, CallbackRegistry, RegistryMemoryStats};
+pub use filter::{EventFilter, EventFilterFn};
+pub use log::{EventLogGuard, LoggedEvent, MAX_LOGGED_DATA_BYTES};
+pub(crate) use middleware::MiddlewareChain;
+pub use middleware::{
+    ClientMiddleware, CompressionMiddleware, LoggingMiddleware, RateLimitMiddleware,
+};
+pub use validator::{EventNameValidator, EventNameValidatorFn, MAX_EVENT_NAME_LENGTH};
+pub use waiter::EventWaiter;
 pub use crate::protocol::PusherEvent;
@@ -0,0 +1,92 @@
+//! Client-side validation of event and channel names.
+
+use crate::error::{Result, SockudoError};
+use crate::protocol::validate_event_name;
+use std::sync::Arc;
+
+pub use crate::protocol::MAX_EVENT_NAME_LENGTH;
+
+/// Predicate deciding whether an event name is acceptable.
+pub type EventNameValidatorFn = Arc<dyn Fn(&str) -> bool + Send + Sync + 'static>;
+
+/// Checks event names before they are bound or triggered.
+///
+/// The default validator applies `validate_event_name`: names of at most
+/// 200 characters made of ASCII alphanumerics and `-_:.`. Protocol events
+/// (`pusher:*` and `pusher_internal:*`) always pass so internal bindings
+/// keep working.
+#[derive(Clone, Default)]
+pub struct EventNameValidator {
+    predicate: Option<EventNameValidatorFn>,
+}
+
+impl EventNameValidator {
+    /// Create a validator from a custom predicate
+    pub fn new(predicate: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        Self {
+            predicate: Some(Arc::new(predicate)),
+        }
+    }
+
+    /// Check `name`, returning `SockudoError::InvalidEventName` on failure
+    pub fn validate(&self, name: &str) -> Result<()> {
+        if name.starts_with("pusher:") || name.starts_with("pusher_internal:") {
+            return Ok(());
+        }
+        match self.predicate {
+            Some(ref predicate) if predicate(name) => Ok(()),
+            Some(_) => Err(SockudoError::invalid_event_name(
+                name,
+                "rejected by custom validator",
+            )),
+            None => validate_event_name(name),
+        }
+    }
+
+    /// Whether `name` passes the validator
+    pub fn is_valid(&self, name: &str) -> bool {
+        self.validate(name).is_ok()
+    }
+}
+
+impl std::fmt::Debug for EventNameValidator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventNameValidator")
+            .field("custom", &self.predicate.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_validator() {
+        let validator = EventNameValidator::default();
+        assert!(validator.is_valid("client-message"));
+        assert!(validator.is_valid("chat:message.v2"));
+        assert!(validator.is_valid(&"a".repeat(200)));
+
+        assert!(!validator.is_valid(&"a".repeat(201)));
+        assert!(!validator.is_valid(""));
+        match validator.validate("my event") {
+            Err(SockudoError::InvalidEventName { name, reason }) => {
+                assert_eq!(name, "my event");
+                assert_eq!(reason, "invalid character ' '");
+            }
+            other => panic!("expected InvalidEventName, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_custom_validator() {
+        let validator =
+            EventNameValidator::new(|name| name.chars().all(|c| c.is_ascii_uppercase()));
+        assert!(validator.is_valid("PRICES"));
+        assert!(!validator.is_valid("prices"));
+        assert!(!validator.is_valid("PRICE-UPDATE"));
+        // Protocol events bypass custom rules
+        assert!(validator.is_valid("pusher:subscription_succeeded"));
+    }
+}
@@ -0,0 +1,142 @@
+//! One-shot futures that resolve when an event is emitted.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures::channel::oneshot;
+use parking_lot::Mutex;
+
+use super::dispatcher::EventDispatcher;
+use crate::error::{Result, SockudoError};
+use crate::protocol::PusherEvent;
+
+/// A future that resolves the first time one of a set of events is emitted.
+///
+/// The underlying bindings are removed when the waiter is dropped, so
+/// dropping it before the event arrives leaves no callbacks behind. Every
+/// waiter owns its own bindings, so concurrent waiters are independent.
+pub struct EventWaiter<T> {
+    receiver: oneshot::Receiver<Result<T>>,
+    dispatcher: Option<EventDispatcher>,
+    bindings: Vec<(String, u64)>,
+}
+
+impl<T: Send + 'static> EventWaiter<T> {
+    /// Wait for any of `event_names`, converting the first match with `map`
+    pub fn new(
+        dispatcher: &EventDispatcher,
+        event_names: &[&str],
+        map: impl Fn(&PusherEvent) -> Result<T> + Send + Sync + 'static,
+    ) -> Self {
+        let (sender, receiver) = oneshot::channel();
+        let sender = Arc::new(Mutex::new(Some(sender)));
+        let map = Arc::new(map);
+
+        let bindings = event_names
+            .iter()
+            .map(|name| {
+                let sender = sender.clone();
+                let map = map.clone();
+                let id = dispatcher.bind_unchecked(*name, move |event| {
+                    if let Some(sender) = sender.lock().take() {
+                        let _ = sender.send(map(event));
+                    }
+                });
+                (name.to_string(), id)
+            })
+            .collect();
+
+        Self {
+            receiver,
+            dispatcher: Some(dispatcher.clone()),
+            bindings,
+        }
+    }
+
+    /// A waiter that is already resolved with `value`
+    pub fn ready(value: Result<T>) -> Self {
+        let (sender, receiver) = oneshot::channel();
+        let _ = sender.send(value);
+
+        Self {
+            receiver,
+            dispatcher: None,
+            bindings: Vec::new(),
+        }
+    }
+}
+
+impl<T> EventWaiter<T> {
+    /// Check whether the waiter still has callbacks bound
+    pub fn is_bound(&self) -> bool {
+        !self.bindings.is_empty()
+    }
+
+    fn release(&mut self) {
+        if let Some(ref dispatcher) = self.dispatcher {
+            for (name, id) in self.bindings.drain(..) {
+                dispatcher.unbind(Some(&name), Some(id));
+            }
+        }
+    }
+}
+
+impl<T: Send + 'static> Future for EventWaiter<T> {
+    type Output = Result<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match Pin::new(&mut self.receiver).poll(cx) {
+            Poll::Ready(result) => {
+                self.release();
+                Poll::Ready(result.unwrap_or_else(|_| {
+                    Err(SockudoError::invalid_state("Event waiter was cancelled"))
+                }))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<T> Drop for EventWaiter<T> {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+impl<T> std::fmt::Debug for EventWaiter<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventWaiter")
+            .field("bindings", &self.bindings)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_waiter_resolves_on_event() {
+        let dispatcher = EventDispatcher::new();
+        let waiter = EventWaiter::new(&dispatcher, &["done"], |event| Ok(event.event.clone()));
+        assert!(dispatcher.has_callbacks("done"));
+
+        dispatcher.emit(&PusherEvent::new("done"));
+
+        let result = futures::executor::block_on(waiter).unwrap();
+        assert_eq!(result, "done");
+        assert!(!dispatcher.has_callbacks("done"));
+    }
+
+    #[test]
+    fn test_drop_unbinds() {
+        let dispatcher = EventDispatcher::new();
+        let waiter = EventWaiter::new(&dispatcher, &["done"], |_| Ok(()));
+        assert_eq!(dispatcher.callback_count(), 1);
+
+        drop(waiter);
+        assert_eq!(dispatcher.callback_count(), 0);
+    }
+}
@@ -3,7 +3,7 @@
 #![cfg(feature = "uniffi")]
 
 use crate::connection::ConnectionState;
-use crate::UniffiPusherEvent;
+use crate::{UniffiMemberInfo, UniffiPusherEvent};
 
 /// Callback for receiving events
 #[cfg(feature = "uniffi")]
@@ -18,10 +18,13 @@ pub trait EventCallback: Send + Sync {
 #[uniffi::export(callback_interface)]
 pub trait ConnectionCallback: Send + Sync {
     /// Called when connection state changes
-    fn on_state_change(&self, previous: ConnectionState, current: ConnectionState);
+    fn on_state_changed(&self, previous: ConnectionState, current: ConnectionState);
 
     /// Called when a connection error occurs
-    fn on_error(&self, error_type: String, message: String);
+    fn on_error(&self, error: String);
+
+    /// Called when the server assigns a socket ID
+    fn on_socket_id_assigned(&self, socket_id: String);
 }
 
 /// Callback for channel events
@@ -34,6 +37,9 @@ pub trait ChannelCallback: Send + Sync {
     /// Called when subscription fails
     fn on_subscription_error(&self, error_type: String, message: String);
 
+    /// Called when the channel is unsubscribed
+    fn on_unsubscribed(&self);
+
     /// Called when an event is received on the channel
     fn on_event(&self, event_name: String, data: Option<String>, user_id: Option<String>);
 }
@@ -46,8 +52,30 @@ pub trait PresenceCallback: Send + Sync {
     fn on_subscription_succeeded(&self, my_id: String, member_ids: Vec<String>);
 
     /// Called when a member is added
-    fn on_member_added(&self, user_id: String);
+    fn on_member_added(&self, member: UniffiMemberInfo);
 
     /// Called when a member is removed
-    fn on_member_removed(&self, user_id: String);
+    fn on_member_removed(&self, member: UniffiMemberInfo);
+}
+
+/// Callback for each delta-compressed message decoded
+#[cfg(feature = "uniffi")]
+#[uniffi::export(callback_interface)]
+pub trait DeltaDecodedCallback: Send + Sync {
+    /// Called with the delta size and decoded size in bytes
+    fn on_delta_decoded(
+        &self,
+        channel: String,
+        algorithm: String,
+        delta_bytes: u64,
+        decoded_bytes: u64,
+    );
+}
+
+/// Callback for each full message stored as a delta base
+#[cfg(feature = "uniffi")]
+#[uniffi::export(callback_interface)]
+pub trait FullMessageCallback: Send + Sync {
+    /// Called with the message size in bytes
+    fn on_full_message(&self, channel: String, bytes: u64);
 }
@@ -16,6 +16,9 @@ pub struct UniffiDeltaOptions {
     pub algorithms: Vec<DeltaAlgorithm>,
     pub debug: bool,
     pub max_messages_per_key: u32,
+    pub preferred_algorithm: Option<DeltaAlgorithm>,
+    pub algorithm_switch_on_error: bool,
+    pub max_fallback_attempts: u32,
 }
 
 impl From<UniffiDeltaOptions> for crate::delta::DeltaOptions {
@@ -25,8 +28,15 @@ impl From<UniffiDeltaOptions> for crate::delta::DeltaOptions {
             algorithms: opts.algorithms,
             debug: opts.debug,
             max_messages_per_key: opts.max_messages_per_key as usize,
+            preferred_algorithm: opts.preferred_algorithm,
+            algorithm_switch_on_error: opts.algorithm_switch_on_error,
+            max_fallback_attempts: opts.max_fallback_attempts,
             on_stats: None,
             on_error: None,
+            on_delta_decoded: None,
+            on_full_message: None,
+            #[cfg(feature = "delta-persist")]
+            cache_persistence: None,
         }
     }
 }
@@ -38,6 +48,9 @@ impl From<crate::delta::DeltaOptions> for UniffiDeltaOptions {
             algorithms: opts.algorithms,
             debug: opts.debug,
             max_messages_per_key: opts.max_messages_per_key as u32,
+            preferred_algorithm: opts.preferred_algorithm,
+            algorithm_switch_on_error: opts.algorithm_switch_on_error,
+            max_fallback_attempts: opts.max_fallback_attempts,
         }
     }
 }
@@ -64,6 +77,11 @@ pub struct SockudoOptions {
     pub max_reconnection_attempts: Option<u32>,
     pub reconnection_delay_ms: Option<u64>,
     pub max_reconnection_delay_ms: Option<u64>,
+    pub skip_sanitization: Option<bool>,
+    pub client_id: Option<String>,
+    pub offline_echo_events: Option<bool>,
+    pub channel_event_history_size: Option<u32>,
+    pub expected_channel_count: Option<u32>,
 }
 
 impl From<SockudoOptions> for crate::options::SockudoOptions {
@@ -72,6 +90,7 @@ impl From<SockudoOptions> for crate::options::SockudoOptions {
             app_key: opts.app_key,
             cluster: opts.cluster,
             ws_host: opts.ws_host,
+            cluster_url_resolver: None,
             ws_port: opts.ws_port,
             use_tls: opts.use_tls,
             auth_endpoint: opts.auth_endpoint,
@@ -80,6 +99,7 @@ impl From<SockudoOptions> for crate::options::SockudoOptions {
             pong_timeout_ms: opts.pong_timeout_ms,
             unavailable_timeout_ms: opts.unavailable_timeout_ms,
             delta_compression: opts.delta_compression.map(|d| d.into()),
+            delta_cache_warmup: None,
             enable_stats: opts.enable_stats,
             debug: opts.debug,
             user_auth_endpoint: opts.user_auth_endpoint,
@@ -88,6 +108,42 @@ impl From<SockudoOptions> for crate::options::SockudoOptions {
             max_reconnection_attempts: opts.max_reconnection_attempts,
             reconnection_delay_ms: opts.reconnection_delay_ms,
             max_reconnection_delay_ms: opts.max_reconnection_delay_ms,
+            initial_reconnect_delay: std::time::Duration::from_millis(200),
+            max_reconnect_delay: std::time::Duration::from_secs(30),
+            channel_prefix_map: std::collections::HashMap::new(),
+            dump_on_error: false,
+            event_filter: None,
+            event_name_validator: None,
+            strict_channel_names: false,
+            proxy: None,
+            trigger_batch_mode: crate::options::TriggerBatchMode::Single,
+            max_trigger_batch_size: 10,
+            tls_config: None,
+            tls_roots: None,
+            accepts_self_signed: false,
+            client_event_secret: None,
+            max_subscriptions: None,
+            connection_token: None,
+            token_query_param: "token".to_string(),
+            client_id: opts.client_id,
+            callback_timeout: None,
+            max_global_callbacks: None,
+            write_timeout: None,
+            read_timeout: None,
+            auth_signature_mode: crate::options::AuthSignatureMode::None,
+            channel_shared_secrets: std::collections::HashMap::new(),
+            global_subscription_metadata: std::collections::HashMap::new(),
+            skip_sanitization: opts.skip_sanitization.unwrap_or(false),
+            offline_echo_events: opts.offline_echo_events.unwrap_or(false),
+            channel_event_history_size: opts.channel_event_history_size.map_or(0, |n| n as usize),
+            expected_channel_count: opts.expected_channel_count.map(|n| n as usize),
+            heartbeat_event: "pusher:ping".to_string(),
+            heartbeat_response_event: "pusher:pong".to_string(),
+            heartbeat_handler: None,
+            on_fatal_error: None,
+            message_transformers: Vec::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            url_provider: None,
         }
     }
 }
@@ -112,6 +168,15 @@ impl From<crate::options::SockudoOptions> for SockudoOptions {
             max_reconnection_attempts: opts.max_reconnection_attempts,
             reconnection_delay_ms: opts.reconnection_delay_ms,
             max_reconnection_delay_ms: opts.max_reconnection_delay_ms,
+            skip_sanitization: Some(opts.skip_sanitization),
+            client_id: opts.client_id,
+            offline_echo_events: Some(opts.offline_echo_events),
+            channel_event_history_size: Some(
+                opts.channel_event_history_size.min(u32::MAX as usize) as u32,
+            ),
+            expected_channel_count: opts
+                .expected_channel_count
+                .map(|n| n.min(u32::MAX as usize) as u32),
         }
     }
 }
@@ -139,7 +204,7 @@ pub struct UniffiMemberInfo {
 /// UniFFI-friendly delta statistics
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 #[cfg_attr(feature = "uniffi", uniffi(name = "DeltaStats"))]
-#[derive(Clone, Default, Debug)]
+#[derive(Clone, Default, Debug, serde::Serialize)]
 pub struct UniffiDeltaStats {
     pub total_messages: u64,
     pub delta_messages: u64,
@@ -150,6 +215,10 @@ pub struct UniffiDeltaStats {
     pub bandwidth_saved_percent: f64,
     pub errors: u64,
     pub channel_count: u64,
+    pub resyncs_requested: u64,
+    pub active_algorithm: Option<String>,
+    pub checksum_errors: u64,
+    pub algorithm_fallbacks: u64,
 }
 
 impl From<crate::DeltaStats> for UniffiDeltaStats {
@@ -164,6 +233,75 @@ impl From<crate::DeltaStats> for UniffiDeltaStats {
             bandwidth_saved_percent: stats.bandwidth_saved_percent,
             errors: stats.errors,
             channel_count: stats.channel_count,
+            resyncs_requested: stats.resyncs_requested,
+            active_algorithm: stats.active_algorithm,
+            checksum_errors: stats.checksum_errors,
+            algorithm_fallbacks: stats.algorithm_fallbacks,
+        }
+    }
+}
+
+impl From<UniffiDeltaStats> for crate::DeltaStats {
+    fn from(stats: UniffiDeltaStats) -> Self {
+        Self {
+            total_messages: stats.total_messages,
+            delta_messages: stats.delta_messages,
+            full_messages: stats.full_messages,
+            total_bytes_without_compression: stats.total_bytes_without_compression,
+            total_bytes_with_compression: stats.total_bytes_with_compression,
+            bandwidth_saved: stats.bandwidth_saved,
+            bandwidth_saved_percent: stats.bandwidth_saved_percent,
+            errors: stats.errors,
+            channel_count: stats.channel_count,
+            resyncs_requested: stats.resyncs_requested,
+            active_algorithm: stats.active_algorithm,
+            checksum_errors: stats.checksum_errors,
+            algorithm_fallbacks: stats.algorithm_fallbacks,
+            channels: Vec::new(),
+        }
+    }
+}
+
+impl UniffiDeltaStats {
+    /// Serialize the statistics to a JSON string with snake_case keys
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Combine with `other` (see `DeltaStats::merged`)
+    pub fn merge(self, other: UniffiDeltaStats) -> UniffiDeltaStats {
+        crate::DeltaStats::from(self).merged(&other.into()).into()
+    }
+}
+
+/// Combine the delta stats of two clients (FFI wrapper for
+/// `UniffiDeltaStats::merge`)
+#[cfg(feature = "uniffi")]
+#[uniffi::export]
+pub fn merge_delta_stats(stats: UniffiDeltaStats, other: UniffiDeltaStats) -> UniffiDeltaStats {
+    stats.merge(other)
+}
+
+/// UniFFI-friendly reconnect attempt
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[cfg_attr(feature = "uniffi", uniffi(name = "ReconnectAttempt"))]
+#[derive(Clone, Debug)]
+pub struct UniffiReconnectAttempt {
+    pub attempt_number: u32,
+    pub started_at: std::time::SystemTime,
+    pub connected_at: Option<std::time::SystemTime>,
+    pub error: Option<String>,
+    pub delay_before_ms: u64,
+}
+
+impl From<crate::ReconnectAttempt> for UniffiReconnectAttempt {
+    fn from(attempt: crate::ReconnectAttempt) -> Self {
+        Self {
+            attempt_number: attempt.attempt_number,
+            started_at: attempt.started_at,
+            connected_at: attempt.connected_at,
+            error: attempt.error,
+            delay_before_ms: attempt.delay_before_ms,
         }
     }
 }
@@ -6,10 +6,15 @@
 #![allow(unexpected_cfgs)]
 
 use flutter_rust_bridge::frb;
-use std::sync::Arc;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
 
 use crate::delta::DeltaAlgorithm;
-use crate::{Result, SockudoClient as CoreClient};
+use crate::{PusherEvent, Result, SockudoClient as CoreClient, SockudoError};
 
 #[cfg(feature = "uniffi")]
 use crate::ffi_types::{SockudoOptions as CoreOptions, UniffiDeltaOptions as CoreDeltaOptions};
@@ -55,6 +60,9 @@ impl From<FlutterDeltaOptions> for CoreDeltaOptions {
                 algorithms,
                 debug: opts.debug,
                 max_messages_per_key: opts.max_messages_per_key,
+                preferred_algorithm: None,
+                algorithm_switch_on_error: false,
+                max_fallback_attempts: 1,
             }
         }
 
@@ -65,8 +73,13 @@ impl From<FlutterDeltaOptions> for CoreDeltaOptions {
                 algorithms,
                 debug: opts.debug,
                 max_messages_per_key: opts.max_messages_per_key as usize,
+                preferred_algorithm: None,
+                algorithm_switch_on_error: false,
+                max_fallback_attempts: 1,
                 on_stats: None,
                 on_error: None,
+                on_delta_decoded: None,
+                on_full_message: None,
             }
         }
     }
@@ -115,6 +128,11 @@ impl From<FlutterSockudoOptions> for CoreOptions {
             max_reconnection_attempts: opts.max_reconnection_attempts,
             reconnection_delay_ms: opts.reconnection_delay_ms,
             max_reconnection_delay_ms: opts.max_reconnection_delay_ms,
+            skip_sanitization: None,
+            expected_channel_count: None,
+            offline_echo_events: None,
+            channel_event_history_size: None,
+            client_id: None,
         }
     }
 }
@@ -126,6 +144,7 @@ impl From<FlutterSockudoOptions> for CoreOptions {
             app_key: opts.app_key,
             cluster: opts.cluster,
             ws_host: opts.ws_host,
+            cluster_url_resolver: None,
             ws_port: opts.ws_port,
             use_tls: opts.use_tls,
             auth_endpoint: opts.auth_endpoint,
@@ -134,6 +153,7 @@ impl From<FlutterSockudoOptions> for CoreOptions {
             pong_timeout_ms: opts.pong_timeout_ms,
             unavailable_timeout_ms: opts.unavailable_timeout_ms,
             delta_compression: opts.delta_compression.map(|d| d.into()),
+            delta_cache_warmup: None,
             enable_stats: opts.enable_stats,
             debug: opts.debug,
             user_auth_endpoint: opts.user_auth_endpoint,
@@ -142,6 +162,42 @@ impl From<FlutterSockudoOptions> for CoreOptions {
             max_reconnection_attempts: opts.max_reconnection_attempts,
             reconnection_delay_ms: opts.reconnection_delay_ms,
             max_reconnection_delay_ms: opts.max_reconnection_delay_ms,
+            initial_reconnect_delay: std::time::Duration::from_millis(200),
+            max_reconnect_delay: std::time::Duration::from_secs(30),
+            channel_prefix_map: std::collections::HashMap::new(),
+            dump_on_error: false,
+            event_filter: None,
+            event_name_validator: None,
+            strict_channel_names: false,
+            proxy: None,
+            trigger_batch_mode: crate::options::TriggerBatchMode::Single,
+            max_trigger_batch_size: 10,
+            tls_config: None,
+            tls_roots: None,
+            accepts_self_signed: false,
+            client_event_secret: None,
+            max_subscriptions: None,
+            connection_token: None,
+            token_query_param: "token".to_string(),
+            client_id: None,
+            callback_timeout: None,
+            max_global_callbacks: None,
+            write_timeout: None,
+            read_timeout: None,
+            auth_signature_mode: crate::options::AuthSignatureMode::None,
+            channel_shared_secrets: HashMap::new(),
+            global_subscription_metadata: HashMap::new(),
+            skip_sanitization: false,
+            offline_echo_events: false,
+            channel_event_history_size: 0,
+            expected_channel_count: None,
+            heartbeat_event: "pusher:ping".to_string(),
+            heartbeat_response_event: "pusher:pong".to_string(),
+            heartbeat_handler: None,
+            on_fatal_error: None,
+            message_transformers: Vec::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            url_provider: None,
         }
     }
 }
@@ -345,28 +401,138 @@ impl FlutterChannel {
 // ============================================================================
 // Stream API for Events
 // ============================================================================
-// Note: Stream functions are commented out as they require additional setup
-// and are not supported in the current flutter_rust_bridge version being used.
-// Uncomment and implement when upgrading to a version that supports #[frb(stream)]
-
-// /// Create a stream of connection state changes
-// pub async fn connection_state_stream(
-//     client: FlutterSockudoClient,
-// ) -> impl futures::Stream<Item = String> {
-//     // This is a simplified implementation
-//     // In a real implementation, you'd want to hook into the actual connection state changes
-//     futures::stream::iter(vec!["Connected".to_string()])
-// }
-//
-// /// Create a stream of events for a specific channel
-// pub async fn channel_event_stream(
-//     client: FlutterSockudoClient,
-//     channel_name: String,
-// ) -> impl futures::Stream<Item = FlutterPusherEvent> {
-//     // This is a simplified implementation
-//     // In a real implementation, you'd want to hook into the actual event dispatcher
-//     futures::stream::iter(vec![])
-// }
+
+/// Event forwarded to Dart through a [`FlutterEventSink`]
+#[frb(dart_metadata=("freezed"), dart_type = "SockudoEvent")]
+#[derive(Clone, Debug)]
+pub struct FlutterEvent {
+    pub event_name: String,
+    pub channel: Option<String>,
+    pub data: Option<String>,
+    /// Receive time in milliseconds since the Unix epoch
+    pub timestamp_ms: i64,
+}
+
+impl From<&PusherEvent> for FlutterEvent {
+    fn from(event: &PusherEvent) -> Self {
+        let received = event.timestamp.unwrap_or_else(SystemTime::now);
+        Self {
+            event_name: event.event.clone(),
+            channel: event.channel.clone(),
+            data: event.data.clone(),
+            timestamp_ms: received
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as i64)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Queue feeding every event of a client to Dart.
+///
+/// Dart drains it with [`sockudo_next_event`], which the Dart side wraps
+/// in a `Stream`.
+#[frb(ignore)]
+#[derive(Clone)]
+pub struct FlutterEventSink(mpsc::UnboundedSender<FlutterEvent>);
+
+impl FlutterEventSink {
+    /// Forward `event` to Dart, returning false once the queue is closed
+    pub fn add(&self, event: &PusherEvent) -> bool {
+        self.0.send(FlutterEvent::from(event)).is_ok()
+    }
+}
+
+type EventQueue = Arc<tokio::sync::Mutex<mpsc::UnboundedReceiver<FlutterEvent>>>;
+
+/// Clients created through [`sockudo_create_client`], by handle
+static CLIENTS: LazyLock<Mutex<HashMap<i64, Arc<CoreClient>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Receiving end of each client's [`FlutterEventSink`], with the id of the
+/// global callback feeding it, by handle
+static EVENT_QUEUES: LazyLock<Mutex<HashMap<i64, (u64, EventQueue)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+static NEXT_HANDLE: AtomicI64 = AtomicI64::new(1);
+
+fn client_for_handle(handle: i64) -> Result<Arc<CoreClient>> {
+    CLIENTS
+        .lock()
+        .get(&handle)
+        .cloned()
+        .ok_or_else(|| SockudoError::invalid_state(format!("Unknown client handle {}", handle)))
+}
+
+/// Create a client whose events are all queued for [`sockudo_next_event`].
+///
+/// Returns a handle for [`sockudo_connect`], [`sockudo_subscribe`],
+/// [`sockudo_unsubscribe`] and [`sockudo_destroy_client`].
+#[frb(sync)]
+pub fn sockudo_create_client(options: FlutterSockudoOptions) -> Result<i64> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let sink = FlutterEventSink(tx);
+
+    #[cfg(feature = "uniffi")]
+    let client = CoreClient::new(options.into())?;
+    #[cfg(not(feature = "uniffi"))]
+    let client = CoreClient::from_options(options.into())?;
+
+    let callback_id = client.bind_global(move |event| {
+        sink.add(event);
+    });
+
+    let handle = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
+    CLIENTS.lock().insert(handle, Arc::new(client));
+    EVENT_QUEUES
+        .lock()
+        .insert(handle, (callback_id, Arc::new(tokio::sync::Mutex::new(rx))));
+    Ok(handle)
+}
+
+/// Connect the client behind `handle`
+pub async fn sockudo_connect(handle: i64) -> Result<()> {
+    client_for_handle(handle)?.connect().await
+}
+
+/// Wait for the next event of the client behind `handle`.
+///
+/// Returns `None` once the client has been destroyed, ending the Dart
+/// stream.
+pub async fn sockudo_next_event(handle: i64) -> Option<FlutterEvent> {
+    let queue = EVENT_QUEUES.lock().get(&handle)?.1.clone();
+    let mut events = queue.lock().await;
+    events.recv().await
+}
+
+/// Subscribe the client behind `handle` to a channel
+#[frb(sync)]
+pub fn sockudo_subscribe(handle: i64, channel_name: String) -> Result<()> {
+    client_for_handle(handle)?.subscribe(&channel_name)?;
+    Ok(())
+}
+
+/// Unsubscribe the client behind `handle` from a channel
+#[frb(sync)]
+pub fn sockudo_unsubscribe(handle: i64, channel_name: String) {
+    if let Ok(client) = client_for_handle(handle) {
+        client.unsubscribe(&channel_name);
+    }
+}
+
+/// Disconnect the client behind `handle` and release it, closing its
+/// event stream
+pub async fn sockudo_destroy_client(handle: i64) {
+    let client = CLIENTS.lock().remove(&handle);
+    let queue = EVENT_QUEUES.lock().remove(&handle);
+    if let Some(client) = client {
+        if let Some((callback_id, _)) = queue {
+            // Drops the sink, so a pending `sockudo_next_event` returns None
+            client.unbind_global(Some(callback_id));
+        }
+        client.disconnect().await;
+    }
+}
 
 // ============================================================================
 // Utility Functions
@@ -35,7 +35,7 @@
 //!     let channel = client.subscribe("my-channel")?;
 //!     channel.bind("my-event", |data| {
 //!         println!("Received: {:?}", data);
-//!     });
+//!     })?;
 //!
 //!     Ok(())
 //! }
@@ -64,21 +64,51 @@ mod options;
 mod pusher;
 
 // Re-exports
-pub use channels::{Channel, ChannelType, MemberInfo, Members, PresenceChannel};
-pub use connection::{ConnectionManager, ConnectionState};
+pub use channels::{
+    Channel, ChannelGroup, ChannelSnapshot, ChannelType, MemberChangeEvent, MemberInfo,
+    MemberSnapshot, Members, MembersPatch, PresenceChannel, PresenceChannelSnapshot, TypedMember,
+    TypedPresenceChannel,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use channels::ChannelWatcher;
+pub use connection::{
+    ConnectionManager, ConnectionState, ConnectionStats, ConnectionStatsDelta, ExponentialBackoff,
+    MessagePriority, ReconnectAttempt, ReconnectStrategy, StatisticsWindowGuard,
+};
 pub use delta::{DeltaAlgorithm, DeltaManager, DeltaOptions, DeltaStats};
 pub use error::{Result, SockudoError};
-pub use events::{EventDispatcher, PusherEvent};
+pub use events::{
+    ClientMiddleware, CompressionMiddleware, DispatchMetrics, EventDispatcher, EventFilter,
+    EventLogGuard, EventNameValidator, LoggedEvent, LoggingMiddleware, PusherEvent,
+    RateLimitMiddleware, RegistryMemoryStats,
+};
 #[cfg(feature = "uniffi")]
-pub use ffi_callbacks::{ChannelCallback, ConnectionCallback, EventCallback, PresenceCallback};
+pub use ffi_callbacks::{
+    ChannelCallback, ConnectionCallback, DeltaDecodedCallback, EventCallback, FullMessageCallback,
+    PresenceCallback,
+};
 #[cfg(feature = "uniffi")]
 pub use ffi_types::SockudoOptions as UniffiSockudoOptions;
 #[cfg(feature = "uniffi")]
-pub use ffi_types::{UniffiDeltaStats, UniffiMemberInfo, UniffiPusherEvent};
-pub use options::{PusherOptions, SockudoOptions};
-pub use protocol::{FilterOp, Protocol};
+pub use ffi_types::{
+    merge_delta_stats, UniffiDeltaStats, UniffiMemberInfo, UniffiPusherEvent,
+    UniffiReconnectAttempt,
+};
+pub use options::{
+    AuthSignatureMode, ChannelConfig, ChannelSubscribeOptions, ClusterUrlResolver, ErrorHandler, HeartbeatHandler, ProxyConfig, PusherOptions,
+    SensitiveBytes, SensitiveString, SockudoOptions,
+    TriggerBatchMode,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use options::UrlProvider;
+pub use protocol::{
+    validate_channel_name, validate_event_name, CompareOp, CompiledFilter, FilterOp,
+    HeaderInjectionTransformer, MessageTransformer, Protocol, PusherServerError,
+};
 #[cfg(not(target_arch = "wasm32"))]
-pub use pusher::{Pusher, SockudoClient};
+pub use pusher::{
+    ClientStatus, DebugDump, Pusher, SockudoClient, StatisticsDiff, StatisticsSnapshot,
+};
 
 // UniFFI setup for Kotlin/Swift bindings
 #[cfg(feature = "uniffi")]
@@ -1,8 +1,15 @@
 //! Configuration options for the Sockudo client.
 
+use crate::connection::ReconnectStrategy;
 use crate::delta::DeltaOptions;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::error::{Result, SockudoError};
+use crate::events::{EventFilter, EventNameValidator};
+use crate::protocol::{FilterOp, MessageTransformer};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
+use zeroize::Zeroize;
 
 /// Configuration options for creating a Sockudo client
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +25,11 @@ pub struct SockudoOptions {
     #[serde(default)]
     pub ws_host: Option<String>,
 
+    /// Maps `cluster` to a WebSocket host when `ws_host` is not set
+    /// (default: `ws-{cluster}.pusher.com`)
+    #[serde(skip)]
+    pub cluster_url_resolver: Option<ClusterUrlResolver>,
+
     /// WebSocket port (default: 80 for ws, 443 for wss)
     #[serde(default)]
     pub ws_port: Option<u16>,
@@ -50,6 +62,11 @@ pub struct SockudoOptions {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub delta_compression: Option<DeltaOptions>,
 
+    /// Base messages to seed the delta cache with, keyed by channel name
+    /// with base64-encoded values (see `DeltaManager::cache_snapshot`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delta_cache_warmup: Option<HashMap<String, String>>,
+
     /// Enable timeline/stats collection
     #[serde(default)]
     pub enable_stats: Option<bool>,
@@ -81,6 +98,548 @@ pub struct SockudoOptions {
     /// Maximum reconnection delay in milliseconds
     #[serde(default)]
     pub max_reconnection_delay_ms: Option<u64>,
+
+    /// First backoff delay used by `SockudoClient::connect_until`
+    #[serde(default = "default_initial_reconnect_delay")]
+    pub initial_reconnect_delay: std::time::Duration,
+
+    /// Upper bound on the backoff delay used by `SockudoClient::connect_until`
+    #[serde(default = "default_max_reconnect_delay")]
+    pub max_reconnect_delay: std::time::Duration,
+
+    /// Per-prefix channel settings; the longest matching prefix wins
+    #[serde(default)]
+    pub channel_prefix_map: HashMap<String, ChannelConfig>,
+
+    /// Attach a `DebugDump` of the client to errors returned by
+    /// `connect`, `connect_until` and the subscribe methods
+    #[serde(default)]
+    pub dump_on_error: bool,
+
+    /// Global event whitelist applied before dispatching to callbacks
+    #[serde(skip)]
+    pub event_filter: Option<EventFilter>,
+
+    /// Custom event name check replacing the default length and charset rules
+    #[serde(skip)]
+    pub event_name_validator: Option<EventNameValidator>,
+
+    /// Apply the event name validator to channel names too (default: false)
+    #[serde(default)]
+    pub strict_channel_names: bool,
+
+    /// Proxy to tunnel the WebSocket connection through
+    #[serde(default)]
+    pub proxy: Option<ProxyConfig>,
+
+    /// How `Channel::trigger_batch` submits client events
+    #[serde(default)]
+    pub trigger_batch_mode: TriggerBatchMode,
+
+    /// Maximum number of client events per batch frame (default: 10)
+    #[serde(default = "default_max_trigger_batch_size")]
+    pub max_trigger_batch_size: usize,
+
+    /// Custom TLS configuration passed to the WebSocket TLS connector
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    pub tls_config: Option<Arc<rustls::ClientConfig>>,
+
+    /// Root certificates added with `add_root_certificate`
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    pub(crate) tls_roots: Option<rustls::RootCertStore>,
+
+    /// Whether `tls_config` was set by `accept_self_signed(true)`
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    pub(crate) accepts_self_signed: bool,
+
+    /// Shared secret used to sign client events with HMAC-SHA256
+    #[serde(default, skip_serializing)]
+    pub client_event_secret: Option<SensitiveString>,
+
+    /// Maximum number of channels that can be subscribed at once
+    #[serde(default)]
+    pub max_subscriptions: Option<usize>,
+
+    /// Token appended to the WebSocket URL query string when connecting
+    #[serde(default, skip_serializing)]
+    pub connection_token: Option<SensitiveString>,
+
+    /// Query parameter name used for `connection_token` (default: "token")
+    #[serde(default = "default_token_query_param")]
+    pub token_query_param: String,
+
+    /// ID sent as the `client_id` query parameter so the server can track
+    /// and resume the session (default: the session ID in hex).
+    /// Characters other than ASCII letters and digits are replaced with `-`
+    #[serde(default)]
+    pub client_id: Option<String>,
+
+    /// Time each sync callback may run before dispatch moves on without it
+    /// (default: unlimited). The callback is not cancelled; see
+    /// `EventDispatcher::emit_with_timeout`
+    #[serde(default)]
+    pub callback_timeout: Option<std::time::Duration>,
+
+    /// Number of client-wide callbacks above which binding another logs a
+    /// possible leak warning (default: unlimited)
+    #[serde(default)]
+    pub max_global_callbacks: Option<usize>,
+
+    /// Time a WebSocket write may take before the connection is dropped
+    /// (default: unlimited)
+    #[serde(default)]
+    pub write_timeout: Option<std::time::Duration>,
+
+    /// Time without receiving any data before the connection is treated as
+    /// unavailable, separate from the ping timeout (default: unlimited)
+    #[serde(default)]
+    pub read_timeout: Option<std::time::Duration>,
+
+    /// How auth requests are signed (default: unsigned)
+    #[serde(default, skip_serializing)]
+    pub auth_signature_mode: AuthSignatureMode,
+
+    /// 32-byte decryption keys for `private-encrypted-` channels, by channel
+    /// name, for servers whose auth response carries no `shared_secret`
+    #[serde(default, skip_serializing)]
+    pub channel_shared_secrets: HashMap<String, SensitiveBytes>,
+
+    /// Metadata sent with every `pusher:subscribe` request, for server
+    /// extensions such as device IDs (per-subscription metadata overrides it)
+    #[serde(default)]
+    pub global_subscription_metadata: HashMap<String, String>,
+
+    /// Deliver event data to callbacks as received, without stripping
+    /// server-internal fields (see `Protocol::sanitize_message`)
+    #[serde(default)]
+    pub skip_sanitization: bool,
+
+    /// In offline mode, deliver triggered client events back to the
+    /// client's own callbacks
+    #[serde(default)]
+    pub offline_echo_events: bool,
+
+    /// Number of events of each event name every channel keeps for
+    /// `Channel::get_event_history` (default: 0, history off)
+    #[serde(default)]
+    pub channel_event_history_size: usize,
+
+    /// Number of channels the application expects to subscribe to, used to
+    /// pre-size the channel map and event callback storage
+    #[serde(default)]
+    pub expected_channel_count: Option<usize>,
+
+    /// Server heartbeat event answered automatically (default: `pusher:ping`)
+    #[serde(default = "default_heartbeat_event")]
+    pub heartbeat_event: String,
+
+    /// Event sent in reply to `heartbeat_event` (default: `pusher:pong`)
+    #[serde(default = "default_heartbeat_response_event")]
+    pub heartbeat_response_event: String,
+
+    /// Callback invoked for each heartbeat received from the server
+    #[serde(skip)]
+    pub heartbeat_handler: Option<HeartbeatHandler>,
+
+    /// Callback invoked with `SockudoError::ServerError` when the server
+    /// sends a fatal `pusher:error` (codes 4000-4099)
+    #[serde(skip)]
+    pub on_fatal_error: Option<ErrorHandler>,
+
+    /// Transformers applied, in order, to every event sent and received
+    #[serde(skip)]
+    pub message_transformers: Vec<Arc<dyn MessageTransformer>>,
+
+    /// Async provider of the WebSocket URL, called on every connect attempt
+    /// in place of the URL built from the host options
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    pub url_provider: Option<UrlProvider>,
+}
+
+/// A string that is masked in `Debug` output, for secrets and credentials
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct SensitiveString(String);
+
+impl SensitiveString {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    /// Get the underlying secret
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Drop for SensitiveString {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl std::fmt::Debug for SensitiveString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("***")
+    }
+}
+
+impl From<String> for SensitiveString {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for SensitiveString {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+/// Bytes that are masked in `Debug` output, for keys
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct SensitiveBytes(Vec<u8>);
+
+impl SensitiveBytes {
+    pub fn new(value: impl Into<Vec<u8>>) -> Self {
+        Self(value.into())
+    }
+
+    /// Get the underlying secret
+    pub fn expose(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Drop for SensitiveBytes {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl std::fmt::Debug for SensitiveBytes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("***")
+    }
+}
+
+impl From<Vec<u8>> for SensitiveBytes {
+    fn from(value: Vec<u8>) -> Self {
+        Self(value)
+    }
+}
+
+/// Function mapping a cluster name to its WebSocket host.
+pub type ClusterUrlResolverFn = Arc<dyn Fn(&str) -> String + Send + Sync + 'static>;
+
+/// Resolves the WebSocket host for a cluster, for servers that do not use
+/// the `ws-{cluster}.pusher.com` naming scheme.
+#[derive(Clone)]
+pub struct ClusterUrlResolver {
+    resolve: ClusterUrlResolverFn,
+}
+
+impl ClusterUrlResolver {
+    /// Create a resolver from a function returning the host for a cluster
+    pub fn new(resolve: impl Fn(&str) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            resolve: Arc::new(resolve),
+        }
+    }
+
+    /// Resolve to `ws-{cluster}.pusher.com`
+    pub fn pusher() -> Self {
+        Self::new(|cluster| format!("ws-{}.pusher.com", cluster))
+    }
+
+    /// Resolve to `ws-{cluster}.sockudo.io`
+    pub fn sockudo() -> Self {
+        Self::new(|cluster| format!("ws-{}.sockudo.io", cluster))
+    }
+
+    /// Get the WebSocket host for `cluster`
+    pub fn resolve(&self, cluster: &str) -> String {
+        (self.resolve)(cluster)
+    }
+}
+
+impl std::fmt::Debug for ClusterUrlResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClusterUrlResolver").finish_non_exhaustive()
+    }
+}
+
+/// Callback invoked for each server heartbeat.
+#[derive(Clone)]
+pub struct HeartbeatHandler {
+    handler: Arc<dyn Fn() + Send + Sync + 'static>,
+}
+
+impl HeartbeatHandler {
+    /// Create a handler from a callback
+    pub fn new(handler: impl Fn() + Send + Sync + 'static) -> Self {
+        Self {
+            handler: Arc::new(handler),
+        }
+    }
+
+    /// Run the callback
+    pub fn call(&self) {
+        (self.handler)()
+    }
+}
+
+impl std::fmt::Debug for HeartbeatHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HeartbeatHandler").finish_non_exhaustive()
+    }
+}
+
+/// Callback invoked with errors that end the connection.
+#[derive(Clone)]
+pub struct ErrorHandler {
+    handler: Arc<dyn Fn(&SockudoError) + Send + Sync + 'static>,
+}
+
+impl ErrorHandler {
+    /// Create a handler from a callback
+    pub fn new(handler: impl Fn(&SockudoError) + Send + Sync + 'static) -> Self {
+        Self {
+            handler: Arc::new(handler),
+        }
+    }
+
+    /// Run the callback
+    pub fn call(&self, error: &SockudoError) {
+        (self.handler)(error)
+    }
+}
+
+impl std::fmt::Debug for ErrorHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ErrorHandler").finish_non_exhaustive()
+    }
+}
+
+/// Function returning the WebSocket URL to connect to
+#[cfg(not(target_arch = "wasm32"))]
+pub type UrlProviderFn = Arc<
+    dyn Fn() -> std::pin::Pin<Box<dyn std::future::Future<Output = String> + Send>> + Send + Sync,
+>;
+
+/// Async provider of the WebSocket URL, called on every connect attempt.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone)]
+pub struct UrlProvider {
+    provide: UrlProviderFn,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl UrlProvider {
+    /// Create a provider from an async function returning the URL
+    pub fn new<F, Fut>(provide: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = String> + Send + 'static,
+    {
+        Self {
+            provide: Arc::new(move || Box::pin(provide())),
+        }
+    }
+
+    /// Get the URL to connect to
+    pub async fn url(&self) -> String {
+        (self.provide)().await
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl std::fmt::Debug for UrlProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UrlProvider").finish_non_exhaustive()
+    }
+}
+
+/// Mask all but the last 4 characters of a socket ID, e.g. for debug dumps
+pub(crate) fn redact_socket_id(socket_id: &str) -> String {
+    let chars: Vec<char> = socket_id.chars().collect();
+    let visible: String = chars[chars.len().saturating_sub(4)..].iter().collect();
+    format!("***{}", visible)
+}
+
+/// Settings overriding the global options for channels with a given prefix
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelConfig {
+    /// Channel authorization endpoint
+    #[serde(default)]
+    pub auth_endpoint: Option<String>,
+
+    /// Whether full messages are cached for delta decoding (default: true)
+    #[serde(default)]
+    pub use_delta: Option<bool>,
+
+    /// Listener leak warning threshold for bindings on the channel
+    #[serde(default)]
+    pub max_listeners: Option<usize>,
+}
+
+/// Per-subscription settings for `SockudoClient::subscribe_with_options`.
+///
+/// Fields left unset keep the channel's current settings.
+#[derive(Default)]
+pub struct ChannelSubscribeOptions {
+    /// Tags filter sent with the subscribe request
+    pub filter: Option<FilterOp>,
+    /// Metadata sent with the subscribe request, merged over
+    /// `global_subscription_metadata`
+    pub metadata: HashMap<String, String>,
+    /// Backoff for retrying failed resubscriptions after a reconnect
+    pub reconnect_strategy: Option<Box<dyn ReconnectStrategy>>,
+    /// Listener leak warning threshold for bindings on the channel
+    pub max_listeners: Option<usize>,
+    /// Channel authorization endpoint used instead of the configured one
+    pub auth_endpoint_override: Option<String>,
+    /// How long the subscription, and each later resubscription, may wait
+    /// for the server's confirmation before it counts as failed
+    pub subscription_timeout: Option<std::time::Duration>,
+}
+
+impl ChannelSubscribeOptions {
+    /// Builder pattern: set the tags filter
+    pub fn filter(mut self, filter: FilterOp) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Builder pattern: add a key-value pair sent with the subscribe request
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Builder pattern: retry failed resubscriptions with `strategy`
+    pub fn reconnect_strategy(mut self, strategy: impl ReconnectStrategy + 'static) -> Self {
+        self.reconnect_strategy = Some(Box::new(strategy));
+        self
+    }
+
+    /// Builder pattern: set the listener leak warning threshold
+    pub fn max_listeners(mut self, limit: usize) -> Self {
+        self.max_listeners = Some(limit);
+        self
+    }
+
+    /// Builder pattern: authorize the channel against `endpoint`
+    pub fn auth_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.auth_endpoint_override = Some(endpoint.into());
+        self
+    }
+
+    /// Builder pattern: set the subscription confirmation timeout
+    pub fn subscription_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.subscription_timeout = Some(timeout);
+        self
+    }
+}
+
+impl std::fmt::Debug for ChannelSubscribeOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChannelSubscribeOptions")
+            .field("filter", &self.filter)
+            .field("metadata", &self.metadata)
+            .field("reconnect_strategy", &self.reconnect_strategy.is_some())
+            .field("max_listeners", &self.max_listeners)
+            .field("auth_endpoint_override", &self.auth_endpoint_override)
+            .field("subscription_timeout", &self.subscription_timeout)
+            .finish()
+    }
+}
+
+/// Find the config whose prefix is the longest match for `channel_name`
+pub(crate) fn match_channel_prefix<'a>(
+    prefix_map: &'a HashMap<String, ChannelConfig>,
+    channel_name: &str,
+) -> Option<&'a ChannelConfig> {
+    prefix_map
+        .iter()
+        .filter(|(prefix, _)| channel_name.starts_with(prefix.as_str()))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, config)| config)
+}
+
+/// Proxy configuration for the native transport
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ProxyConfig {
+    /// SOCKS5 proxy, optionally with username/password authentication
+    Socks5 {
+        host: String,
+        port: u16,
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
+    },
+}
+
+/// How channel and user auth requests are signed.
+///
+/// With `Hmac`, every auth request carries `X-Sockudo-Timestamp` (Unix
+/// milliseconds) and `X-Sockudo-Signature: {app_id}:sha256={hex}`, the
+/// HMAC-SHA256 of `"POST\n/auth\n{timestamp}\n{body}"` keyed with the app
+/// secret. The auth endpoint must be configured to verify the signature and
+/// reject stale timestamps; servers that do not check it ignore the headers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthSignatureMode {
+    /// Auth requests are not signed
+    #[default]
+    None,
+    /// Sign auth requests with an app secret shared with the auth endpoint
+    Hmac {
+        app_id: String,
+        app_secret: SensitiveString,
+    },
+}
+
+/// How batched client events are sent to the server
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TriggerBatchMode {
+    /// One frame per event, supported by every server
+    #[default]
+    Single,
+    /// One JSON array frame per batch
+    Array,
+}
+
+fn default_max_trigger_batch_size() -> usize {
+    10
+}
+
+fn default_initial_reconnect_delay() -> std::time::Duration {
+    std::time::Duration::from_millis(200)
+}
+
+fn default_max_reconnect_delay() -> std::time::Duration {
+    std::time::Duration::from_secs(30)
+}
+
+fn default_token_query_param() -> String {
+    "token".to_string()
+}
+
+fn default_heartbeat_event() -> String {
+    "pusher:ping".to_string()
+}
+
+fn default_heartbeat_response_event() -> String {
+    "pusher:pong".to_string()
 }
 
 impl Default for SockudoOptions {
@@ -89,6 +648,7 @@ impl Default for SockudoOptions {
             app_key: String::new(),
             cluster: None,
             ws_host: None,
+            cluster_url_resolver: None,
             ws_port: None,
             use_tls: None,
             auth_endpoint: Some("/pusher/auth".to_string()),
@@ -97,6 +657,7 @@ impl Default for SockudoOptions {
             pong_timeout_ms: Some(30_000),
             unavailable_timeout_ms: Some(10_000),
             delta_compression: None,
+            delta_cache_warmup: None,
             enable_stats: Some(false),
             debug: Some(false),
             user_auth_endpoint: Some("/pusher/user-auth".to_string()),
@@ -105,6 +666,45 @@ impl Default for SockudoOptions {
             max_reconnection_attempts: Some(0),
             reconnection_delay_ms: Some(1000),
             max_reconnection_delay_ms: Some(30_000),
+            initial_reconnect_delay: default_initial_reconnect_delay(),
+            max_reconnect_delay: default_max_reconnect_delay(),
+            channel_prefix_map: HashMap::new(),
+            dump_on_error: false,
+            event_filter: None,
+            event_name_validator: None,
+            strict_channel_names: false,
+            proxy: None,
+            trigger_batch_mode: TriggerBatchMode::Single,
+            max_trigger_batch_size: default_max_trigger_batch_size(),
+            #[cfg(not(target_arch = "wasm32"))]
+            tls_config: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            tls_roots: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            accepts_self_signed: false,
+            client_event_secret: None,
+            max_subscriptions: None,
+            connection_token: None,
+            token_query_param: default_token_query_param(),
+            client_id: None,
+            callback_timeout: None,
+            max_global_callbacks: None,
+            write_timeout: None,
+            read_timeout: None,
+            auth_signature_mode: AuthSignatureMode::None,
+            channel_shared_secrets: HashMap::new(),
+            global_subscription_metadata: HashMap::new(),
+            skip_sanitization: false,
+            offline_echo_events: false,
+            channel_event_history_size: 0,
+            expected_channel_count: None,
+            heartbeat_event: default_heartbeat_event(),
+            heartbeat_response_event: default_heartbeat_response_event(),
+            heartbeat_handler: None,
+            on_fatal_error: None,
+            message_transformers: Vec::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            url_provider: None,
         }
     }
 }
@@ -124,6 +724,32 @@ impl SockudoOptions {
         self
     }
 
+    /// Builder pattern: resolve the WebSocket host for `cluster` with a
+    /// custom function, used when `ws_host` is not set
+    pub fn with_cluster_url_resolver(
+        mut self,
+        resolver: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.cluster_url_resolver = Some(ClusterUrlResolver::new(resolver));
+        self
+    }
+
+    /// Builder pattern: connect to a Sockudo cloud cluster at
+    /// `ws-{cluster}.sockudo.io`
+    pub fn with_sockudo_cluster(mut self, cluster: &str) -> Self {
+        self.cluster = Some(cluster.to_string());
+        self.cluster_url_resolver = Some(ClusterUrlResolver::sockudo());
+        self
+    }
+
+    /// Builder pattern: connect to a Pusher cluster at
+    /// `ws-{cluster}.pusher.com`
+    pub fn with_pusher_cluster(mut self, cluster: &str) -> Self {
+        self.cluster = Some(cluster.to_string());
+        self.cluster_url_resolver = Some(ClusterUrlResolver::pusher());
+        self
+    }
+
     /// Builder pattern: set custom WebSocket host
     pub fn ws_host(mut self, host: impl Into<String>) -> Self {
         self.ws_host = Some(host.into());
@@ -167,6 +793,294 @@ impl SockudoOptions {
         self
     }
 
+    /// Builder pattern: seed the delta cache from a `DeltaManager::cache_snapshot`
+    pub fn delta_cache_warmup(mut self, snapshot: &HashMap<String, Vec<u8>>) -> Self {
+        self.delta_cache_warmup = Some(
+            snapshot
+                .iter()
+                .map(|(channel, bytes)| (channel.clone(), crate::delta::encode_base64(bytes)))
+                .collect(),
+        );
+        self
+    }
+
+    /// Builder pattern: override settings for channels starting with `prefix`
+    pub fn channel_prefix_config(
+        mut self,
+        prefix: impl Into<String>,
+        config: ChannelConfig,
+    ) -> Self {
+        self.channel_prefix_map.insert(prefix.into(), config);
+        self
+    }
+
+    /// Builder pattern: attach a debug dump to errors
+    pub fn dump_on_error(mut self, enabled: bool) -> Self {
+        self.dump_on_error = enabled;
+        self
+    }
+
+    /// Builder pattern: set a global event filter
+    pub fn event_filter(mut self, filter: EventFilter) -> Self {
+        self.event_filter = Some(filter);
+        self
+    }
+
+    /// Builder pattern: validate event names with a custom predicate
+    pub fn event_name_validator(
+        mut self,
+        validator: impl Fn(&str) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.event_name_validator = Some(EventNameValidator::new(validator));
+        self
+    }
+
+    /// Builder pattern: validate channel names like event names
+    pub fn strict_channel_names(mut self, strict: bool) -> Self {
+        self.strict_channel_names = strict;
+        self
+    }
+
+    /// Builder pattern: set the backoff bounds used by `connect_until`
+    pub fn reconnect_backoff(
+        mut self,
+        initial: std::time::Duration,
+        max: std::time::Duration,
+    ) -> Self {
+        self.initial_reconnect_delay = initial;
+        self.max_reconnect_delay = max;
+        self
+    }
+
+    /// Builder pattern: set proxy
+    pub fn proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Builder pattern: set how `Channel::trigger_batch` sends events
+    pub fn trigger_batch_mode(mut self, mode: TriggerBatchMode) -> Self {
+        self.trigger_batch_mode = mode;
+        self
+    }
+
+    /// Builder pattern: set the maximum number of events per batch frame
+    pub fn max_trigger_batch_size(mut self, size: usize) -> Self {
+        self.max_trigger_batch_size = size;
+        self
+    }
+
+    /// Builder pattern: sign client events with an HMAC-SHA256 shared secret
+    pub fn client_event_secret(mut self, secret: impl Into<String>) -> Self {
+        self.client_event_secret = Some(SensitiveString::new(secret));
+        self
+    }
+
+    /// Builder pattern: limit the number of channels subscribed at once
+    pub fn max_subscriptions(mut self, limit: usize) -> Self {
+        self.max_subscriptions = Some(limit);
+        self
+    }
+
+    /// Builder pattern: append a token to the WebSocket URL on connect
+    pub fn connection_token(mut self, token: impl Into<String>) -> Self {
+        self.connection_token = Some(SensitiveString::new(token));
+        self
+    }
+
+    /// Builder pattern: set the query parameter name used for the connection token
+    pub fn token_query_param(mut self, name: impl Into<String>) -> Self {
+        self.token_query_param = name.into();
+        self
+    }
+
+    /// Builder pattern: send `id` as the `client_id` URL parameter
+    pub fn client_id(mut self, id: impl Into<String>) -> Self {
+        self.client_id = Some(id.into());
+        self
+    }
+
+    /// Builder pattern: stop waiting for a callback after `timeout`
+    pub fn callback_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.callback_timeout = Some(timeout);
+        self
+    }
+
+    /// Builder pattern: warn when more than `limit` client-wide callbacks
+    /// are bound
+    pub fn max_global_callbacks(mut self, limit: usize) -> Self {
+        self.max_global_callbacks = Some(limit);
+        self
+    }
+
+    /// Builder pattern: fail sends that do not complete within `timeout`
+    pub fn write_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.write_timeout = Some(timeout);
+        self
+    }
+
+    /// Builder pattern: drop the connection after `timeout` without data
+    pub fn read_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Builder pattern: sign auth requests with HMAC-SHA256 (see `AuthSignatureMode`)
+    pub fn hmac_auth(mut self, app_id: impl Into<String>, app_secret: impl Into<String>) -> Self {
+        self.auth_signature_mode = AuthSignatureMode::Hmac {
+            app_id: app_id.into(),
+            app_secret: SensitiveString::new(app_secret),
+        };
+        self
+    }
+
+    /// Builder pattern: set the decryption key for an encrypted channel
+    pub fn channel_shared_secret(
+        mut self,
+        channel: impl Into<String>,
+        secret: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.channel_shared_secrets
+            .insert(channel.into(), SensitiveBytes::new(secret));
+        self
+    }
+
+    /// Builder pattern: add a key-value pair sent with every subscribe request
+    pub fn subscription_metadata(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.global_subscription_metadata
+            .insert(key.into(), value.into());
+        self
+    }
+
+    /// Builder pattern: keep server-internal fields in event data
+    pub fn skip_sanitization(mut self, skip: bool) -> Self {
+        self.skip_sanitization = skip;
+        self
+    }
+
+    /// Builder pattern: echo triggered client events locally in offline mode
+    pub fn offline_echo_events(mut self, echo: bool) -> Self {
+        self.offline_echo_events = echo;
+        self
+    }
+
+    /// Builder pattern: keep the last `size` events of each event name on
+    /// every channel
+    pub fn channel_event_history_size(mut self, size: usize) -> Self {
+        self.channel_event_history_size = size;
+        self
+    }
+
+    /// Builder pattern: pre-allocate room for `count` channels
+    pub fn expected_channel_count(mut self, count: usize) -> Self {
+        self.expected_channel_count = Some(count);
+        self
+    }
+
+    /// Builder pattern: answer the server heartbeat `event` with `response`
+    /// instead of `pusher:ping` / `pusher:pong`
+    pub fn heartbeat_events(
+        mut self,
+        event: impl Into<String>,
+        response: impl Into<String>,
+    ) -> Self {
+        self.heartbeat_event = event.into();
+        self.heartbeat_response_event = response.into();
+        self
+    }
+
+    /// Builder pattern: call `handler` for each heartbeat from the server
+    pub fn heartbeat_handler(mut self, handler: impl Fn() + Send + Sync + 'static) -> Self {
+        self.heartbeat_handler = Some(HeartbeatHandler::new(handler));
+        self
+    }
+
+    /// Builder pattern: call `handler` when a fatal server error ends the
+    /// connection
+    pub fn on_fatal_error(
+        mut self,
+        handler: impl Fn(&SockudoError) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_fatal_error = Some(ErrorHandler::new(handler));
+        self
+    }
+
+    /// Builder pattern: pass every event sent and received through
+    /// `transformer`, after the transformers already added
+    pub fn add_message_transformer(mut self, transformer: Box<dyn MessageTransformer>) -> Self {
+        self.message_transformers.push(Arc::from(transformer));
+        self
+    }
+
+    /// Builder pattern: get the WebSocket URL from `provider` on every
+    /// connect attempt, e.g. from service discovery
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn url_provider<F, Fut>(mut self, provider: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = String> + Send + 'static,
+    {
+        self.url_provider = Some(UrlProvider::new(provider));
+        self
+    }
+
+    /// Builder pattern: set a custom TLS client configuration
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn tls_config(mut self, config: Arc<rustls::ClientConfig>) -> Self {
+        self.tls_config = Some(config);
+        self.accepts_self_signed = false;
+        self
+    }
+
+    /// Builder pattern: accept self-signed (or otherwise invalid) server certificates
+    ///
+    /// **Development only.** This disables certificate verification entirely
+    /// and must never be used in production. Passing `false` undoes an
+    /// earlier `accept_self_signed(true)`, restoring the roots added with
+    /// `add_root_certificate` or the default web PKI roots; a configuration
+    /// set with `tls_config` is kept.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn accept_self_signed(mut self, accept: bool) -> Self {
+        if accept {
+            self.tls_config = Some(crate::transports::tls::insecure_client_config());
+        } else if self.accepts_self_signed {
+            self.tls_config = self
+                .tls_roots
+                .clone()
+                .map(crate::transports::tls::client_config_with_roots);
+        }
+        self.accepts_self_signed = accept;
+        self
+    }
+
+    /// Builder pattern: trust the certificates in a PEM document
+    ///
+    /// Certificates accumulate across calls. Once any are added, only those
+    /// roots are trusted, not the default web PKI roots.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn add_root_certificate(mut self, pem: &str) -> Result<Self> {
+        let certs = crate::transports::tls::parse_pem_certificates(pem)?;
+        let roots = self
+            .tls_roots
+            .get_or_insert_with(rustls::RootCertStore::empty);
+
+        for cert in certs {
+            roots
+                .add(cert)
+                .map_err(|e| SockudoError::config(format!("Invalid root certificate: {}", e)))?;
+        }
+
+        self.tls_config = Some(crate::transports::tls::client_config_with_roots(
+            roots.clone(),
+        ));
+        self.accepts_self_signed = false;
+        Ok(self)
+    }
+
     /// Builder pattern: enable debug mode
     pub fn debug(mut self, enabled: bool) -> Self {
         self.debug = Some(enabled);
@@ -181,7 +1095,10 @@ impl SockudoOptions {
         let host = if let Some(ref host) = self.ws_host {
             host.clone()
         } else if let Some(ref cluster) = self.cluster {
-            format!("ws-{}.pusher.com", cluster)
+            match self.cluster_url_resolver {
+                Some(ref resolver) => resolver.resolve(cluster),
+                None => ClusterUrlResolver::pusher().resolve(cluster),
+            }
         } else {
             "ws.pusherapp.com".to_string()
         };
@@ -206,6 +1123,11 @@ impl SockudoOptions {
         std::time::Duration::from_millis(self.activity_timeout_ms.unwrap_or(120_000))
     }
 
+    /// Get the prefix config that applies to `channel_name`, if any
+    pub fn channel_config(&self, channel_name: &str) -> Option<&ChannelConfig> {
+        match_channel_prefix(&self.channel_prefix_map, channel_name)
+    }
+
     /// Get pong timeout duration
     pub fn get_pong_timeout(&self) -> std::time::Duration {
         std::time::Duration::from_millis(self.pong_timeout_ms.unwrap_or(30_000))
@@ -245,11 +1167,13 @@ pub struct Config {
     pub ws_url: String,
     pub auth_endpoint: String,
     pub auth_headers: HashMap<String, String>,
+    pub auth_signature_mode: AuthSignatureMode,
     pub activity_timeout: std::time::Duration,
     pub pong_timeout: std::time::Duration,
     pub unavailable_timeout: std::time::Duration,
     pub use_tls: bool,
     pub delta_compression: Option<DeltaOptions>,
+    pub delta_cache_warmup: Option<HashMap<String, String>>,
     pub enable_stats: bool,
     pub debug: bool,
     pub user_auth_endpoint: String,
@@ -258,6 +1182,76 @@ pub struct Config {
     pub max_reconnection_attempts: u32,
     pub reconnection_delay: std::time::Duration,
     pub max_reconnection_delay: std::time::Duration,
+    pub initial_reconnect_delay: std::time::Duration,
+    pub max_reconnect_delay: std::time::Duration,
+    pub channel_prefix_map: HashMap<String, ChannelConfig>,
+    pub dump_on_error: bool,
+    pub event_filter: Option<EventFilter>,
+    pub event_name_validator: Option<EventNameValidator>,
+    pub strict_channel_names: bool,
+    pub proxy: Option<ProxyConfig>,
+    pub trigger_batch_mode: TriggerBatchMode,
+    pub max_trigger_batch_size: usize,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub tls_config: Option<Arc<rustls::ClientConfig>>,
+    pub client_event_secret: Option<SensitiveString>,
+    pub max_subscriptions: Option<usize>,
+    pub connection_token: Option<SensitiveString>,
+    pub token_query_param: String,
+    pub client_id: Option<String>,
+    pub channel_shared_secrets: HashMap<String, SensitiveBytes>,
+    pub callback_timeout: Option<std::time::Duration>,
+    pub max_global_callbacks: Option<usize>,
+    pub write_timeout: Option<std::time::Duration>,
+    pub read_timeout: Option<std::time::Duration>,
+    pub global_subscription_metadata: HashMap<String, String>,
+    pub skip_sanitization: bool,
+    pub offline_echo_events: bool,
+    pub channel_event_history_size: usize,
+    pub expected_channel_count: Option<usize>,
+    pub heartbeat_event: String,
+    pub heartbeat_response_event: String,
+    pub heartbeat_handler: Option<HeartbeatHandler>,
+    pub on_fatal_error: Option<ErrorHandler>,
+    pub message_transformers: Vec<Arc<dyn MessageTransformer>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub url_provider: Option<UrlProvider>,
+}
+
+impl Config {
+    /// WebSocket URL with the connection token appended, URL-encoded
+    pub fn ws_url_with_token(&self, token: &str) -> String {
+        self.url_with_token(&self.ws_url, token)
+    }
+
+    /// `url` with the connection token appended, URL-encoded
+    pub fn url_with_token(&self, url: &str, token: &str) -> String {
+        format!(
+            "{}{}{}={}",
+            url,
+            if url.contains('?') { "&" } else { "?" },
+            urlencoding::encode(&self.token_query_param),
+            urlencoding::encode(token)
+        )
+    }
+}
+
+/// `id` with every character other than an ASCII letter or digit replaced
+/// with `-`, so it is safe to send unencoded in a URL
+pub fn sanitize_client_id(id: &str) -> String {
+    id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// `url` with `client_id` appended as the `client_id` query parameter
+pub(crate) fn url_with_client_id(url: &str, client_id: &str) -> String {
+    format!(
+        "{}{}client_id={}",
+        url,
+        if url.contains('?') { "&" } else { "?" },
+        client_id
+    )
 }
 
 impl From<PusherOptions> for Config {
@@ -270,11 +1264,13 @@ impl From<PusherOptions> for Config {
                 .clone()
                 .unwrap_or_else(|| "/pusher/auth".to_string()),
             auth_headers: opts.auth_headers.clone().unwrap_or_default(),
+            auth_signature_mode: opts.auth_signature_mode.clone(),
             activity_timeout: opts.get_activity_timeout(),
             pong_timeout: opts.get_pong_timeout(),
             unavailable_timeout: opts.get_unavailable_timeout(),
             use_tls: opts.use_tls.unwrap_or(true),
             delta_compression: opts.delta_compression.clone(),
+            delta_cache_warmup: opts.delta_cache_warmup.clone(),
             enable_stats: opts.enable_stats.unwrap_or(false),
             debug: opts.is_debug(),
             user_auth_endpoint: opts
@@ -289,6 +1285,40 @@ impl From<PusherOptions> for Config {
             max_reconnection_delay: std::time::Duration::from_millis(
                 opts.max_reconnection_delay_ms.unwrap_or(30_000),
             ),
+            initial_reconnect_delay: opts.initial_reconnect_delay,
+            max_reconnect_delay: opts.max_reconnect_delay,
+            channel_prefix_map: opts.channel_prefix_map,
+            dump_on_error: opts.dump_on_error,
+            event_filter: opts.event_filter,
+            event_name_validator: opts.event_name_validator,
+            strict_channel_names: opts.strict_channel_names,
+            proxy: opts.proxy,
+            trigger_batch_mode: opts.trigger_batch_mode,
+            max_trigger_batch_size: opts.max_trigger_batch_size,
+            #[cfg(not(target_arch = "wasm32"))]
+            tls_config: opts.tls_config,
+            client_event_secret: opts.client_event_secret,
+            max_subscriptions: opts.max_subscriptions,
+            connection_token: opts.connection_token,
+            token_query_param: opts.token_query_param,
+            client_id: opts.client_id.as_deref().map(sanitize_client_id),
+            channel_shared_secrets: opts.channel_shared_secrets,
+            callback_timeout: opts.callback_timeout,
+            max_global_callbacks: opts.max_global_callbacks,
+            write_timeout: opts.write_timeout,
+            read_timeout: opts.read_timeout,
+            global_subscription_metadata: opts.global_subscription_metadata,
+            skip_sanitization: opts.skip_sanitization,
+            offline_echo_events: opts.offline_echo_events,
+            channel_event_history_size: opts.channel_event_history_size,
+            expected_channel_count: opts.expected_channel_count,
+            heartbeat_event: opts.heartbeat_event,
+            heartbeat_response_event: opts.heartbeat_response_event,
+            heartbeat_handler: opts.heartbeat_handler,
+            on_fatal_error: opts.on_fatal_error,
+            message_transformers: opts.message_transformers,
+            #[cfg(not(target_arch = "wasm32"))]
+            url_provider: opts.url_provider,
         }
     }
 }
@@ -297,6 +1327,42 @@ impl From<PusherOptions> for Config {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_client_event_secret_is_masked() {
+        let opts = PusherOptions::new("test-key").client_event_secret("s3cret");
+        assert!(!format!("{:?}", opts).contains("s3cret"));
+        assert!(!serde_json::to_string(&opts).unwrap().contains("s3cret"));
+        assert_eq!(opts.client_event_secret.unwrap().expose(), "s3cret");
+    }
+
+    #[test]
+    fn test_connection_token_url_is_encoded() {
+        let opts = PusherOptions::new("test-key")
+            .connection_token("a b&c=d")
+            .token_query_param("auth");
+        assert!(!format!("{:?}", opts).contains("a b&c=d"));
+
+        let config = Config::from(opts);
+        assert!(!format!("{:?}", config).contains("a b&c=d"));
+        assert!(config
+            .ws_url_with_token("a b&c=d")
+            .ends_with("&auth=a%20b%26c%3Dd"));
+    }
+
+    #[test]
+    fn test_sanitize_client_id() {
+        assert_eq!(sanitize_client_id("device_42/a b"), "device-42-a-b");
+        assert_eq!(sanitize_client_id("Ab09"), "Ab09");
+        assert_eq!(
+            url_with_client_id("ws://host/app/key?protocol=7", "c1"),
+            "ws://host/app/key?protocol=7&client_id=c1"
+        );
+        assert_eq!(
+            url_with_client_id("ws://host", "c1"),
+            "ws://host?client_id=c1"
+        );
+    }
+
     #[test]
     fn test_default_ws_url() {
         let opts = PusherOptions::new("test-key").cluster("mt1");
@@ -306,6 +1372,62 @@ mod tests {
         assert!(url.contains("test-key"));
     }
 
+    #[test]
+    fn test_cluster_url_resolver() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let calls_clone = calls.clone();
+        let opts = PusherOptions::new("test-key")
+            .cluster("eu-central")
+            .with_cluster_url_resolver(move |cluster| {
+                calls_clone.fetch_add(1, Ordering::SeqCst);
+                assert_eq!(cluster, "eu-central");
+                "realtime.example.com".to_string()
+            });
+
+        let config = Config::from(opts);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(config
+            .ws_url
+            .starts_with("wss://realtime.example.com/app/test-key?"));
+
+        let sockudo = PusherOptions::new("test-key").with_sockudo_cluster("us1");
+        assert!(sockudo.get_ws_url().contains("ws-us1.sockudo.io"));
+        let pusher = PusherOptions::new("test-key").with_pusher_cluster("mt1");
+        assert!(pusher.get_ws_url().contains("ws-mt1.pusher.com"));
+
+        // An explicit host still takes precedence
+        let hosted = PusherOptions::new("test-key")
+            .with_sockudo_cluster("us1")
+            .ws_host("localhost");
+        assert!(hosted.get_ws_url().contains("wss://localhost/"));
+    }
+
+    #[test]
+    fn test_add_root_certificate_rejects_invalid_pem() {
+        let result = PusherOptions::new("test-key").add_root_certificate("not a certificate");
+        assert!(matches!(
+            result,
+            Err(SockudoError::ConfigurationError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_accept_self_signed_keeps_custom_tls_config() {
+        let custom =
+            crate::transports::tls::client_config_with_roots(rustls::RootCertStore::empty());
+        let opts = PusherOptions::new("test-key")
+            .tls_config(custom.clone())
+            .accept_self_signed(false);
+        assert!(Arc::ptr_eq(opts.tls_config.as_ref().unwrap(), &custom));
+
+        // Only the verifier set by `accept_self_signed(true)` is undone
+        let opts = opts.accept_self_signed(true);
+        assert!(!Arc::ptr_eq(opts.tls_config.as_ref().unwrap(), &custom));
+        assert!(opts.accept_self_signed(false).tls_config.is_none());
+    }
+
     #[test]
     fn test_custom_host_url() {
         let opts = PusherOptions::new("test-key")
@@ -5,6 +5,7 @@
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashSet;
 
 /// Filter operation for tag filtering
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -58,6 +59,91 @@ pub enum FilterOp {
     /// Logical OR of multiple filters
     #[serde(rename = "$or")]
     Or { filters: Vec<FilterOp> },
+
+    /// Logical negation of a filter.
+    ///
+    /// Holds exactly one filter; it is a `Vec` only because UniFFI enums
+    /// cannot contain a `Box`. Serialized as a single object.
+    #[serde(rename = "$not", alias = "not")]
+    Not {
+        #[serde(with = "single_filter")]
+        filter: Vec<FilterOp>,
+    },
+
+    /// Unix timestamp after: field > value, in epoch seconds
+    #[serde(rename = "timestamp_gt")]
+    TimestampGt { field: String, value: String },
+
+    /// Unix timestamp before: field < value, in epoch seconds
+    #[serde(rename = "timestamp_lt")]
+    TimestampLt { field: String, value: String },
+
+    /// Compare a nested field addressed by a dot-separated path
+    #[serde(rename = "json_path")]
+    JsonPath {
+        path: String,
+        compare: CompareOp,
+        value: String,
+    },
+}
+
+/// Comparison operator used by [`FilterOp::JsonPath`]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum CompareOp {
+    Eq,
+    Neq,
+    Lt,
+    Gt,
+    Lte,
+    Gte,
+}
+
+impl CompareOp {
+    /// Compare `actual` against `expected`.
+    ///
+    /// Both sides are compared numerically when they parse as numbers,
+    /// otherwise lexicographically.
+    pub fn compare(&self, actual: &str, expected: &str) -> bool {
+        let ordering = match (actual.parse::<f64>(), expected.parse::<f64>()) {
+            (Ok(a), Ok(b)) => a.partial_cmp(&b),
+            _ => Some(actual.cmp(expected)),
+        };
+
+        self.matches_ordering(ordering)
+    }
+
+    fn matches_ordering(&self, ordering: Option<std::cmp::Ordering>) -> bool {
+        let Some(ordering) = ordering else {
+            return false;
+        };
+
+        match self {
+            Self::Eq => ordering.is_eq(),
+            Self::Neq => ordering.is_ne(),
+            Self::Lt => ordering.is_lt(),
+            Self::Gt => ordering.is_gt(),
+            Self::Lte => ordering.is_le(),
+            Self::Gte => ordering.is_ge(),
+        }
+    }
+}
+
+impl std::str::FromStr for CompareOp {
+    type Err = FilterValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "eq" => Ok(Self::Eq),
+            "neq" => Ok(Self::Neq),
+            "lt" => Ok(Self::Lt),
+            "gt" => Ok(Self::Gt),
+            "lte" => Ok(Self::Lte),
+            "gte" => Ok(Self::Gte),
+            other => Err(FilterValidationError::UnknownOperator(other.to_string())),
+        }
+    }
 }
 
 impl FilterOp {
@@ -149,11 +235,274 @@ impl FilterOp {
         Self::Or { filters }
     }
 
+    /// Create a NOT filter matching whenever `inner` does not
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(inner: FilterOp) -> Self {
+        Self::Not {
+            filter: vec![inner],
+        }
+    }
+
+    /// Create a NAND filter: `not(and(filters))`
+    pub fn nand(filters: Vec<FilterOp>) -> Self {
+        Self::not(Self::and(filters))
+    }
+
+    /// Create a NOR filter: `not(or(filters))`
+    pub fn nor(filters: Vec<FilterOp>) -> Self {
+        Self::not(Self::or(filters))
+    }
+
+    /// Layer `overlay` on top of `base`: both must match
+    pub fn merge(base: FilterOp, overlay: FilterOp) -> Self {
+        Self::and(vec![base, overlay])
+    }
+
+    /// Require every filter in `filters` to match
+    pub fn intersect(filters: Vec<FilterOp>) -> Self {
+        Self::and(filters)
+    }
+
+    /// Match events matched by both `self` and `other`
+    pub fn intersection(self, other: FilterOp) -> Self {
+        Self::and(vec![self, other])
+    }
+
+    /// Match events matched by `self`, `other` or both
+    pub fn union(self, other: FilterOp) -> Self {
+        Self::or(vec![self, other])
+    }
+
+    /// Match events matched by `base` but not by `exclude`
+    pub fn difference(base: FilterOp, exclude: FilterOp) -> Self {
+        Self::and(vec![base, Self::not(exclude)])
+    }
+
+    /// Match events matched by exactly one of `a` and `b`
+    pub fn xor(a: FilterOp, b: FilterOp) -> Self {
+        Self::or(vec![
+            Self::and(vec![a.clone(), Self::not(b.clone())]),
+            Self::and(vec![Self::not(a), b]),
+        ])
+    }
+
+    /// Create a filter comparing a nested field, e.g. `"user.role"` or `"items.0.sku"`
+    pub fn json_path(path: &str, op: CompareOp, value: &str) -> Self {
+        Self::JsonPath {
+            path: path.to_string(),
+            compare: op,
+            value: value.to_string(),
+        }
+    }
+
+    /// Create a filter matching events whose `field` is later than `ts`.
+    ///
+    /// Timestamps are Unix epoch seconds as `u64`; the field may hold a
+    /// number or a numeric string. Events where it is missing or not a
+    /// `u64` never match. The value is sent as a string for JSON
+    /// compatibility.
+    pub fn timestamp_after(field: &str, ts: u64) -> Self {
+        Self::TimestampGt {
+            field: field.to_string(),
+            value: ts.to_string(),
+        }
+    }
+
+    /// Create a filter matching events whose `field` is earlier than `ts`,
+    /// in Unix epoch seconds
+    pub fn timestamp_before(field: &str, ts: u64) -> Self {
+        Self::TimestampLt {
+            field: field.to_string(),
+            value: ts.to_string(),
+        }
+    }
+
+    /// Create a filter matching events whose `field` is strictly between
+    /// `start` and `end`, in Unix epoch seconds
+    pub fn timestamp_between(field: &str, start: u64, end: u64) -> Self {
+        Self::and(vec![
+            Self::timestamp_after(field, start),
+            Self::timestamp_before(field, end),
+        ])
+    }
+
+    /// Create a filter matching events whose `field` is within the last
+    /// `secs` seconds, in Unix epoch seconds.
+    ///
+    /// The cutoff is computed once, when the filter is created.
+    pub fn timestamp_within_last_secs(field: &str, secs: u64) -> Self {
+        Self::timestamp_after(field, unix_secs().saturating_sub(secs))
+    }
+
+    /// Evaluate the filter against an event payload
+    pub fn evaluate(&self, data: &Value) -> bool {
+        match self {
+            Self::Eq { field, value } => compare_field(data.get(field), CompareOp::Eq, value),
+            Self::Neq { field, value } => !compare_field(data.get(field), CompareOp::Eq, value),
+            Self::Lt { field, value } => compare_field(data.get(field), CompareOp::Lt, value),
+            Self::Lte { field, value } => compare_field(data.get(field), CompareOp::Lte, value),
+            Self::Gt { field, value } => compare_field(data.get(field), CompareOp::Gt, value),
+            Self::Gte { field, value } => compare_field(data.get(field), CompareOp::Gte, value),
+            Self::In { field, values } => data
+                .get(field)
+                .map(value_as_string)
+                .map(|actual| values.contains(&actual))
+                .unwrap_or(false),
+            Self::NotIn { field, values } => data
+                .get(field)
+                .map(value_as_string)
+                .map(|actual| !values.contains(&actual))
+                .unwrap_or(true),
+            Self::Exists { field } => data.get(field).is_some(),
+            Self::NotExists { field } => data.get(field).is_none(),
+            Self::TimestampGt { field, value } => {
+                compare_timestamp(data.get(field), CompareOp::Gt, value)
+            }
+            Self::TimestampLt { field, value } => {
+                compare_timestamp(data.get(field), CompareOp::Lt, value)
+            }
+            Self::And { filters } => filters.iter().all(|f| f.evaluate(data)),
+            Self::Or { filters } => filters.iter().any(|f| f.evaluate(data)),
+            Self::Not { filter } => !filter.iter().all(|f| f.evaluate(data)),
+            Self::JsonPath {
+                path,
+                compare,
+                value,
+            } => {
+                let actual = resolve_path(data, path);
+                match compare {
+                    CompareOp::Neq => !compare_field(actual, CompareOp::Eq, value),
+                    _ => compare_field(actual, *compare, value),
+                }
+            }
+        }
+    }
+
+    /// Compile the filter into a closure for repeated evaluation.
+    ///
+    /// Field paths are split, `$in` sets hashed and numeric operands parsed
+    /// once up front, so [`CompiledFilter::matches`] does no per-call setup.
+    pub fn compile(&self) -> CompiledFilter {
+        CompiledFilter {
+            source: self.clone(),
+            matcher: compile_node(self),
+        }
+    }
+
     /// Convert to JSON value for protocol
     pub fn to_json(&self) -> Value {
         serde_json::to_value(self).unwrap_or(Value::Null)
     }
 
+    /// Parse the compact URL form produced by
+    /// [`to_url_query_string`](Self::to_url_query_string).
+    ///
+    /// Leaves are `field:op:value` with `op` one of `eq`, `neq`, `lt`,
+    /// `lte`, `gt`, `gte`, `in`, `nin`, `tsgt`, `tslt`, `exists` or
+    /// `nexists` (the last two take no value), or `path:$op:value` for a
+    /// JSON path comparison.
+    /// `in`/`nin` values are separated by `|`. Comma-separated terms are
+    /// ANDed, `(a|b)` is an OR and `!` negates the following term.
+    /// Reserved characters in fields and values are percent-encoded. A
+    /// leading `?filter=` is ignored, so
+    /// `type:eq:goal,league:in:premier|champions` and
+    /// `?filter=type:eq:goal,league:in:premier,champions` are equivalent.
+    pub fn from_url_query_string(query: &str) -> Result<Self, FilterValidationError> {
+        let query = query.strip_prefix('?').unwrap_or(query);
+        let query = query.strip_prefix("filter=").unwrap_or(query);
+        let mut parser = QueryParser {
+            input: query,
+            pos: 0,
+        };
+        let filter = parser.and_list()?;
+        match parser.peek() {
+            None => Ok(filter),
+            Some(c) => Err(parser.error(format!("unexpected '{}'", c))),
+        }
+    }
+
+    /// Encode the filter in the compact URL form read by
+    /// [`from_url_query_string`](Self::from_url_query_string)
+    pub fn to_url_query_string(&self) -> String {
+        let mut out = String::new();
+        self.write_query(&mut out, true);
+        out
+    }
+
+    /// Write the filter as a comma list (`list`) or as a single term
+    fn write_query(&self, out: &mut String, list: bool) {
+        let mut leaf = |field: &str, op: &str, values: &[&String]| {
+            out.push_str(&encode_query_part(field));
+            out.push(':');
+            out.push_str(op);
+            for (i, value) in values.iter().enumerate() {
+                out.push(if i == 0 { ':' } else { '|' });
+                out.push_str(&encode_query_part(value));
+            }
+        };
+
+        match self {
+            Self::Eq { field, value } => leaf(field, "eq", &[value]),
+            Self::Neq { field, value } => leaf(field, "neq", &[value]),
+            Self::Lt { field, value } => leaf(field, "lt", &[value]),
+            Self::Lte { field, value } => leaf(field, "lte", &[value]),
+            Self::Gt { field, value } => leaf(field, "gt", &[value]),
+            Self::Gte { field, value } => leaf(field, "gte", &[value]),
+            Self::In { field, values } => leaf(field, "in", &values.iter().collect::<Vec<_>>()),
+            Self::NotIn { field, values } => leaf(field, "nin", &values.iter().collect::<Vec<_>>()),
+            Self::Exists { field } => leaf(field, "exists", &[]),
+            Self::NotExists { field } => leaf(field, "nexists", &[]),
+            Self::TimestampGt { field, value } => leaf(field, "tsgt", &[value]),
+            Self::TimestampLt { field, value } => leaf(field, "tslt", &[value]),
+            Self::JsonPath {
+                path,
+                compare,
+                value,
+            } => {
+                let op = match compare {
+                    CompareOp::Eq => "$eq",
+                    CompareOp::Neq => "$neq",
+                    CompareOp::Lt => "$lt",
+                    CompareOp::Gt => "$gt",
+                    CompareOp::Lte => "$lte",
+                    CompareOp::Gte => "$gte",
+                };
+                leaf(path, op, &[value]);
+            }
+            Self::And { filters } => {
+                if !list {
+                    out.push('(');
+                }
+                for (i, filter) in filters.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    filter.write_query(out, false);
+                }
+                if !list {
+                    out.push(')');
+                }
+            }
+            Self::Or { filters } => {
+                out.push('(');
+                for (i, filter) in filters.iter().enumerate() {
+                    if i > 0 {
+                        out.push('|');
+                    }
+                    filter.write_query(out, true);
+                }
+                out.push(')');
+            }
+            Self::Not { filter } => {
+                out.push('!');
+                match filter.as_slice() {
+                    [inner] => inner.write_query(out, false),
+                    filters => Self::and(filters.to_vec()).write_query(out, false),
+                }
+            }
+        }
+    }
+
     /// Validate the filter
     pub fn validate(&self) -> Result<(), FilterValidationError> {
         match self {
@@ -164,11 +513,20 @@ impl FilterOp {
             | Self::Gt { field, .. }
             | Self::Gte { field, .. }
             | Self::Exists { field }
-            | Self::NotExists { field } => {
+            | Self::NotExists { field }
+            | Self::JsonPath { path: field, .. } => {
                 if field.is_empty() {
                     return Err(FilterValidationError::EmptyField);
                 }
             }
+            Self::TimestampGt { field, value } | Self::TimestampLt { field, value } => {
+                if field.is_empty() {
+                    return Err(FilterValidationError::EmptyField);
+                }
+                if value.parse::<u64>().is_err() {
+                    return Err(FilterValidationError::InvalidTimestamp(value.clone()));
+                }
+            }
             Self::In { field, values } | Self::NotIn { field, values } => {
                 if field.is_empty() {
                     return Err(FilterValidationError::EmptyField);
@@ -185,17 +543,498 @@ impl FilterOp {
                     filter.validate()?;
                 }
             }
+            Self::Not { filter } => {
+                if filter.is_empty() {
+                    return Err(FilterValidationError::EmptyFilterList);
+                }
+                for filter in filter {
+                    filter.validate()?;
+                }
+            }
         }
         Ok(())
     }
 }
 
+impl std::fmt::Display for FilterOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let join = |filters: &[FilterOp], sep: &str| {
+            filters
+                .iter()
+                .map(|filter| filter.to_string())
+                .collect::<Vec<_>>()
+                .join(sep)
+        };
+
+        match self {
+            Self::Eq { field, value } => write!(f, "{} = {:?}", field, value),
+            Self::Neq { field, value } => write!(f, "{} != {:?}", field, value),
+            Self::Lt { field, value } => write!(f, "{} < {:?}", field, value),
+            Self::Lte { field, value } => write!(f, "{} <= {:?}", field, value),
+            Self::Gt { field, value } => write!(f, "{} > {:?}", field, value),
+            Self::Gte { field, value } => write!(f, "{} >= {:?}", field, value),
+            Self::In { field, values } => write!(f, "{} IN {:?}", field, values),
+            Self::NotIn { field, values } => write!(f, "{} NOT IN {:?}", field, values),
+            Self::Exists { field } => write!(f, "EXISTS {}", field),
+            Self::NotExists { field } => write!(f, "NOT EXISTS {}", field),
+            Self::TimestampGt { field, value } => write!(f, "{} AFTER {}", field, value),
+            Self::TimestampLt { field, value } => write!(f, "{} BEFORE {}", field, value),
+            Self::And { filters } => write!(f, "({})", join(filters, " AND ")),
+            Self::Or { filters } => write!(f, "({})", join(filters, " OR ")),
+            Self::Not { filter } => match filter.as_slice() {
+                [inner @ (Self::And { .. } | Self::Or { .. })] => write!(f, "NOT {}", inner),
+                [inner] => write!(f, "NOT ({})", inner),
+                filters => write!(f, "NOT ({})", join(filters, " AND ")),
+            },
+            Self::JsonPath {
+                path,
+                compare,
+                value,
+            } => {
+                let op = match compare {
+                    CompareOp::Eq => "=",
+                    CompareOp::Neq => "!=",
+                    CompareOp::Lt => "<",
+                    CompareOp::Gt => ">",
+                    CompareOp::Lte => "<=",
+                    CompareOp::Gte => ">=",
+                };
+                write!(f, "{} {} {:?}", path, op, value)
+            }
+        }
+    }
+}
+
+/// Percent-encode everything except unreserved URL characters
+fn encode_query_part(part: &str) -> String {
+    let mut out = String::with_capacity(part.len());
+    for byte in part.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    out
+}
+
+/// Recursive descent parser for [`FilterOp::from_url_query_string`]
+struct QueryParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl QueryParser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn error(&self, message: impl std::fmt::Display) -> FilterValidationError {
+        FilterValidationError::InvalidQuery(format!("{} at offset {}", message, self.pos))
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), FilterValidationError> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(c) => Err(self.error(format!("expected '{}', found '{}'", expected, c))),
+            None => Err(self.error(format!("expected '{}'", expected))),
+        }
+    }
+
+    /// Comma-separated terms, combined with AND when there are several
+    fn and_list(&mut self) -> Result<FilterOp, FilterValidationError> {
+        let mut filters = vec![self.term()?];
+        while self.peek() == Some(',') {
+            self.pos += 1;
+            filters.push(self.term()?);
+        }
+        Ok(match filters.len() {
+            1 => filters.remove(0),
+            _ => FilterOp::And { filters },
+        })
+    }
+
+    /// A negation, a parenthesized OR group or a leaf
+    fn term(&mut self) -> Result<FilterOp, FilterValidationError> {
+        match self.peek() {
+            Some('!') => {
+                self.pos += 1;
+                Ok(FilterOp::not(self.term()?))
+            }
+            Some('(') => {
+                self.pos += 1;
+                let mut filters = vec![self.and_list()?];
+                while self.peek() == Some('|') {
+                    self.pos += 1;
+                    filters.push(self.and_list()?);
+                }
+                self.expect(')')?;
+                Ok(match filters.len() {
+                    1 => filters.remove(0),
+                    _ => FilterOp::Or { filters },
+                })
+            }
+            _ => self.leaf(),
+        }
+    }
+
+    fn leaf(&mut self) -> Result<FilterOp, FilterValidationError> {
+        let field = self.part()?;
+        if field.is_empty() {
+            return Err(self.error("missing field"));
+        }
+        self.expect(':')?;
+        let op = self.part()?;
+
+        let value = |parser: &mut Self| -> Result<String, FilterValidationError> {
+            parser.expect(':')?;
+            parser.part()
+        };
+        Ok(match op.as_str() {
+            "eq" => FilterOp::Eq {
+                field,
+                value: value(self)?,
+            },
+            "neq" => FilterOp::Neq {
+                field,
+                value: value(self)?,
+            },
+            "lt" => FilterOp::Lt {
+                field,
+                value: value(self)?,
+            },
+            "lte" => FilterOp::Lte {
+                field,
+                value: value(self)?,
+            },
+            "gt" => FilterOp::Gt {
+                field,
+                value: value(self)?,
+            },
+            "gte" => FilterOp::Gte {
+                field,
+                value: value(self)?,
+            },
+            "in" => FilterOp::In {
+                field,
+                values: self.values()?,
+            },
+            "nin" => FilterOp::NotIn {
+                field,
+                values: self.values()?,
+            },
+            "exists" => FilterOp::Exists { field },
+            "nexists" => FilterOp::NotExists { field },
+            "tsgt" => FilterOp::TimestampGt {
+                field,
+                value: value(self)?,
+            },
+            "tslt" => FilterOp::TimestampLt {
+                field,
+                value: value(self)?,
+            },
+            other => match other.strip_prefix('$') {
+                Some(compare) => FilterOp::JsonPath {
+                    path: field,
+                    compare: compare.parse()?,
+                    value: value(self)?,
+                },
+                None => return Err(FilterValidationError::UnknownOperator(other.to_string())),
+            },
+        })
+    }
+
+    /// Set values separated by `|` or `,`. A piece containing `:` starts
+    /// the next leaf instead.
+    fn values(&mut self) -> Result<Vec<String>, FilterValidationError> {
+        self.expect(':')?;
+        let mut values = vec![self.part()?];
+        while matches!(self.peek(), Some('|' | ',')) && self.next_is_value() {
+            self.pos += 1;
+            values.push(self.part()?);
+        }
+        Ok(values)
+    }
+
+    /// Whether the piece after the current separator is a bare value
+    fn next_is_value(&self) -> bool {
+        let rest = &self.input[self.pos + 1..];
+        let end = rest.find([',', '|', '(', ')']).unwrap_or(rest.len());
+        !rest[..end].contains(':') && !rest.starts_with('!')
+    }
+
+    /// Read and percent-decode up to the next reserved character
+    fn part(&mut self) -> Result<String, FilterValidationError> {
+        let rest = &self.input[self.pos..];
+        let end = rest
+            .find([':', ',', '|', '(', ')', '!'])
+            .unwrap_or(rest.len());
+        let raw = &rest[..end];
+
+        let mut bytes = Vec::with_capacity(raw.len());
+        let mut iter = raw.bytes();
+        while let Some(byte) = iter.next() {
+            if byte != b'%' {
+                bytes.push(byte);
+                continue;
+            }
+            let hex = [iter.next(), iter.next()];
+            let decoded = match hex {
+                [Some(hi), Some(lo)] => std::str::from_utf8(&[hi, lo])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+                _ => None,
+            };
+            match decoded {
+                Some(byte) => bytes.push(byte),
+                None => return Err(self.error("invalid percent escape")),
+            }
+        }
+        let part = String::from_utf8(bytes).map_err(|_| self.error("invalid UTF-8"))?;
+        self.pos += end;
+        Ok(part)
+    }
+}
+
+type Matcher = Box<dyn Fn(&Value) -> bool + Send + Sync + 'static>;
+
+/// A [`FilterOp`] compiled into a closure by [`FilterOp::compile`].
+///
+/// Evaluates exactly like [`FilterOp::evaluate`]. It is not serializable;
+/// use [`CompiledFilter::source`] to get the filter it was built from.
+pub struct CompiledFilter {
+    source: FilterOp,
+    matcher: Matcher,
+}
+
+impl CompiledFilter {
+    /// Check whether an event payload matches the filter
+    pub fn matches(&self, data: &Value) -> bool {
+        (self.matcher)(data)
+    }
+
+    /// The filter this was compiled from
+    pub fn source(&self) -> &FilterOp {
+        &self.source
+    }
+}
+
+impl Clone for CompiledFilter {
+    fn clone(&self) -> Self {
+        self.source.compile()
+    }
+}
+
+impl std::fmt::Debug for CompiledFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompiledFilter")
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+/// Comparison operand with its numeric form parsed ahead of time
+struct Operand {
+    text: String,
+    number: Option<f64>,
+}
+
+impl Operand {
+    fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            number: text.parse().ok(),
+        }
+    }
+
+    /// Same semantics as `compare_field`, without re-parsing the operand
+    fn compare(&self, actual: Option<&Value>, op: CompareOp) -> bool {
+        match (actual, self.number) {
+            (None, _) => false,
+            (Some(Value::Number(n)), Some(expected)) => match n.as_f64() {
+                Some(actual) => op.matches_ordering(actual.partial_cmp(&expected)),
+                None => op.compare(&n.to_string(), &self.text),
+            },
+            (Some(Value::String(actual)), _) => op.compare(actual, &self.text),
+            (Some(actual), _) => op.compare(&value_as_string(actual), &self.text),
+        }
+    }
+}
+
+fn compile_node(filter: &FilterOp) -> Matcher {
+    match filter.clone() {
+        FilterOp::Eq { field, value } => compile_compare(field, CompareOp::Eq, &value, false),
+        FilterOp::Neq { field, value } => compile_compare(field, CompareOp::Eq, &value, true),
+        FilterOp::Lt { field, value } => compile_compare(field, CompareOp::Lt, &value, false),
+        FilterOp::Lte { field, value } => compile_compare(field, CompareOp::Lte, &value, false),
+        FilterOp::Gt { field, value } => compile_compare(field, CompareOp::Gt, &value, false),
+        FilterOp::Gte { field, value } => compile_compare(field, CompareOp::Gte, &value, false),
+        FilterOp::In { field, values } => {
+            let values: HashSet<String> = values.into_iter().collect();
+            Box::new(move |data| {
+                data.get(&field)
+                    .map(|actual| contains_value(&values, actual))
+                    .unwrap_or(false)
+            })
+        }
+        FilterOp::NotIn { field, values } => {
+            let values: HashSet<String> = values.into_iter().collect();
+            Box::new(move |data| {
+                data.get(&field)
+                    .map(|actual| !contains_value(&values, actual))
+                    .unwrap_or(true)
+            })
+        }
+        FilterOp::Exists { field } => Box::new(move |data| data.get(&field).is_some()),
+        FilterOp::NotExists { field } => Box::new(move |data| data.get(&field).is_none()),
+        FilterOp::TimestampGt { field, value } => compile_timestamp(field, CompareOp::Gt, &value),
+        FilterOp::TimestampLt { field, value } => compile_timestamp(field, CompareOp::Lt, &value),
+        FilterOp::And { filters } => {
+            let matchers: Vec<Matcher> = filters.iter().map(compile_node).collect();
+            Box::new(move |data| matchers.iter().all(|m| m(data)))
+        }
+        FilterOp::Or { filters } => {
+            let matchers: Vec<Matcher> = filters.iter().map(compile_node).collect();
+            Box::new(move |data| matchers.iter().any(|m| m(data)))
+        }
+        FilterOp::Not { filter } => {
+            let matchers: Vec<Matcher> = filter.iter().map(compile_node).collect();
+            Box::new(move |data| !matchers.iter().all(|m| m(data)))
+        }
+        FilterOp::JsonPath {
+            path,
+            compare,
+            value,
+        } => {
+            let segments: Vec<String> = path.split('.').map(str::to_string).collect();
+            let operand = Operand::new(&value);
+            let (op, negate) = match compare {
+                CompareOp::Neq => (CompareOp::Eq, true),
+                other => (other, false),
+            };
+            Box::new(move |data| {
+                let actual = segments
+                    .iter()
+                    .try_fold(data, |current, key| match current {
+                        Value::Object(map) => map.get(key),
+                        Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+                        _ => None,
+                    });
+                operand.compare(actual, op) != negate
+            })
+        }
+    }
+}
+
+/// Serialize the one-element `Not` filter list as a single filter object
+mod single_filter {
+    use super::FilterOp;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(filter: &[FilterOp], serializer: S) -> Result<S::Ok, S::Error> {
+        match filter {
+            [inner] => inner.serialize(serializer),
+            filters => FilterOp::and(filters.to_vec()).serialize(serializer),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<FilterOp>, D::Error> {
+        FilterOp::deserialize(deserializer).map(|inner| vec![inner])
+    }
+}
+
+fn compile_compare(field: String, op: CompareOp, value: &str, negate: bool) -> Matcher {
+    let operand = Operand::new(value);
+    Box::new(move |data| operand.compare(data.get(&field), op) != negate)
+}
+
+fn compile_timestamp(field: String, op: CompareOp, value: &str) -> Matcher {
+    match value.parse::<u64>() {
+        Ok(expected) => Box::new(move |data| {
+            let actual = data.get(&field).and_then(timestamp_value);
+            actual.is_some_and(|actual| op.matches_ordering(Some(actual.cmp(&expected))))
+        }),
+        Err(_) => Box::new(|_| false),
+    }
+}
+
+fn contains_value(values: &HashSet<String>, actual: &Value) -> bool {
+    match actual {
+        Value::String(s) => values.contains(s),
+        other => values.contains(&other.to_string()),
+    }
+}
+
+/// Walk a dot-separated path through nested objects and arrays
+fn resolve_path<'a>(data: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(data, |current, key| match current {
+            Value::Object(map) => map.get(key),
+            Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        })
+}
+
+fn compare_field(actual: Option<&Value>, op: CompareOp, expected: &str) -> bool {
+    actual
+        .map(|value| op.compare(&value_as_string(value), expected))
+        .unwrap_or(false)
+}
+
+/// Compare a Unix timestamp field against `expected`; anything that is not
+/// a `u64` on either side never matches
+fn compare_timestamp(actual: Option<&Value>, op: CompareOp, expected: &str) -> bool {
+    match (actual.and_then(timestamp_value), expected.parse::<u64>()) {
+        (Some(actual), Ok(expected)) => op.matches_ordering(Some(actual.cmp(&expected))),
+        _ => false,
+    }
+}
+
+/// Read a Unix timestamp stored as a number or a numeric string
+fn timestamp_value(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Current Unix time in seconds
+#[cfg(not(target_arch = "wasm32"))]
+fn unix_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Current Unix time in seconds; `SystemTime::now` is unavailable in the browser
+#[cfg(target_arch = "wasm32")]
+fn unix_secs() -> u64 {
+    (js_sys::Date::now() / 1000.0) as u64
+}
+
+fn value_as_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
 /// Filter validation error
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FilterValidationError {
     EmptyField,
     EmptyValueSet,
     EmptyFilterList,
+    UnknownOperator(String),
+    InvalidQuery(String),
+    InvalidTimestamp(String),
 }
 
 impl std::fmt::Display for FilterValidationError {
@@ -204,6 +1043,11 @@ impl std::fmt::Display for FilterValidationError {
             Self::EmptyField => write!(f, "Field name cannot be empty"),
             Self::EmptyValueSet => write!(f, "Value set cannot be empty"),
             Self::EmptyFilterList => write!(f, "Filter list cannot be empty"),
+            Self::UnknownOperator(op) => write!(f, "Unknown comparison operator: {}", op),
+            Self::InvalidQuery(message) => write!(f, "Invalid filter query: {}", message),
+            Self::InvalidTimestamp(value) => {
+                write!(f, "Timestamp must be Unix epoch seconds: {}", value)
+            }
         }
     }
 }
@@ -258,6 +1102,49 @@ impl Default for FilterBuilder {
     }
 }
 
+/// Builder for layering required and conditional filters.
+///
+/// ```ignore
+/// let filter = FilterChain::new()
+///     .require(FilterOp::eq("tenant_id", "abc"))
+///     .optional_if(is_admin, FilterOp::eq("user_role", "admin"))
+///     .build();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FilterChain {
+    filters: Vec<FilterOp>,
+}
+
+impl FilterChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a filter that must always match
+    pub fn require(mut self, filter: FilterOp) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Add `filter` only when `condition` is true
+    pub fn optional_if(self, condition: bool, filter: FilterOp) -> Self {
+        if condition {
+            self.require(filter)
+        } else {
+            self
+        }
+    }
+
+    /// Combine the collected filters with AND logic
+    pub fn build(self) -> FilterOp {
+        if self.filters.len() == 1 {
+            self.filters.into_iter().next().unwrap()
+        } else {
+            FilterOp::intersect(self.filters)
+        }
+    }
+}
+
 /// FFI-safe filter builder for use across language boundaries
 #[cfg(all(not(feature = "wasm"), feature = "uniffi"))]
 #[derive(uniffi::Object)]
@@ -325,6 +1212,145 @@ impl Clone for FilterBuilder {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_compiled_filter_matches_evaluate() {
+        let filter = FilterOp::or(vec![
+            FilterOp::and(vec![
+                FilterOp::eq("type", "goal"),
+                FilterOp::gte("minute", "45"),
+                FilterOp::in_set("team", vec!["home".to_string(), "away".to_string()]),
+            ]),
+            FilterOp::json_path("meta.score.0", CompareOp::Gt, "2"),
+            FilterOp::and(vec![
+                FilterOp::neq("type", "card"),
+                FilterOp::not_exists("var"),
+                FilterOp::not_in("league", vec!["1".to_string()]),
+            ]),
+        ]);
+        let compiled = filter.compile();
+
+        let payloads = [
+            serde_json::json!({"type": "goal", "minute": 50, "team": "home"}),
+            serde_json::json!({"type": "goal", "minute": "9", "team": "home", "var": true}),
+            serde_json::json!({"type": "card", "meta": {"score": [3, 1]}}),
+            serde_json::json!({"type": "card", "meta": {"score": [1, 1]}}),
+            serde_json::json!({"type": "shot", "league": 1}),
+            serde_json::json!({"type": "shot", "league": 2}),
+            serde_json::json!(null),
+        ];
+        for payload in &payloads {
+            assert_eq!(
+                compiled.matches(payload),
+                filter.evaluate(payload),
+                "{}",
+                payload
+            );
+        }
+
+        let cloned = compiled.clone();
+        assert_eq!(cloned.source(), &filter);
+        assert!(cloned.matches(&payloads[0]));
+    }
+
+    #[test]
+    fn test_not_de_morgan() {
+        let a = FilterOp::eq("a", "1");
+        let b = FilterOp::exists("b");
+        let payloads = [
+            serde_json::json!({}),
+            serde_json::json!({ "a": "1" }),
+            serde_json::json!({ "b": true }),
+            serde_json::json!({ "a": "1", "b": true }),
+        ];
+
+        let nand = FilterOp::not(FilterOp::and(vec![a.clone(), b.clone()]));
+        let or_nots = FilterOp::or(vec![FilterOp::not(a.clone()), FilterOp::not(b.clone())]);
+        let nor = FilterOp::nor(vec![a.clone(), b.clone()]);
+        let and_nots = FilterOp::and(vec![FilterOp::not(a.clone()), FilterOp::not(b.clone())]);
+
+        for data in &payloads {
+            assert_eq!(nand.evaluate(data), or_nots.evaluate(data), "{}", data);
+            assert_eq!(nor.evaluate(data), and_nots.evaluate(data), "{}", data);
+            assert_eq!(
+                FilterOp::nand(vec![a.clone(), b.clone()]).evaluate(data),
+                nand.evaluate(data)
+            );
+            assert_eq!(nand.compile().matches(data), nand.evaluate(data));
+        }
+
+        assert_eq!(
+            nand.to_json(),
+            serde_json::json!({ "op": "$not", "filter": { "op": "$and", "filters": [
+                { "op": "$eq", "field": "a", "value": "1" },
+                { "op": "$exists", "field": "b" },
+            ]}})
+        );
+        let parsed: FilterOp = serde_json::from_value(
+            serde_json::json!({ "op": "not", "filter": { "op": "$exists", "field": "b" } }),
+        )
+        .unwrap();
+        assert_eq!(parsed, FilterOp::not(b));
+        assert_eq!(nand.to_string(), r#"NOT (a = "1" AND EXISTS b)"#);
+    }
+
+    #[test]
+    fn test_set_operations_truth_tables() {
+        let a = FilterOp::eq("a", "1");
+        let b = FilterOp::exists("b");
+        // Expected results for: neither, only a, only b, both
+        let operations = [
+            (
+                "intersection",
+                a.clone().intersection(b.clone()),
+                [false, false, false, true],
+            ),
+            (
+                "union",
+                a.clone().union(b.clone()),
+                [false, true, true, true],
+            ),
+            (
+                "difference",
+                FilterOp::difference(a.clone(), b.clone()),
+                [false, true, false, false],
+            ),
+            (
+                "xor",
+                FilterOp::xor(a.clone(), b.clone()),
+                [false, true, true, false],
+            ),
+        ];
+        let payloads = [
+            serde_json::json!({}),
+            serde_json::json!({ "a": "1" }),
+            serde_json::json!({ "b": true }),
+            serde_json::json!({ "a": "1", "b": true }),
+        ];
+
+        for (name, filter, expected) in &operations {
+            for (data, expected) in payloads.iter().zip(expected) {
+                assert_eq!(filter.evaluate(data), *expected, "{} on {}", name, data);
+                assert_eq!(
+                    filter.compile().matches(data),
+                    *expected,
+                    "{} on {}",
+                    name,
+                    data
+                );
+            }
+            let parsed: FilterOp = serde_json::from_value(filter.to_json()).unwrap();
+            assert_eq!(&parsed, filter);
+        }
+
+        assert_eq!(
+            FilterOp::difference(a.clone(), b.clone()).to_json(),
+            serde_json::json!({ "op": "$and", "filters": [
+                { "op": "$eq", "field": "a", "value": "1" },
+                { "op": "$not", "filter": { "op": "$exists", "field": "b" } },
+            ]})
+        );
+    }
+
     #[test]
     fn test_simple_filter() {
         let filter = FilterOp::eq("type", "goal");
@@ -350,6 +1376,52 @@ mod tests {
         filter.validate().unwrap();
     }
 
+    #[test]
+    fn test_json_path_serialization() {
+        let filter = FilterOp::json_path("user.role", CompareOp::Eq, "admin");
+        assert_eq!(
+            filter.to_json(),
+            serde_json::json!({
+                "op": "json_path",
+                "path": "user.role",
+                "compare": "eq",
+                "value": "admin"
+            })
+        );
+        let parsed: FilterOp = serde_json::from_value(filter.to_json()).unwrap();
+        assert_eq!(parsed, filter);
+    }
+
+    #[test]
+    fn test_json_path_nested_evaluation() {
+        let data = serde_json::json!({
+            "order": {
+                "customer": { "tier": "gold", "age": 42 },
+                "items": [{ "sku": "A-1" }, { "sku": "B-2" }]
+            }
+        });
+
+        assert!(FilterOp::json_path("order.customer.tier", CompareOp::Eq, "gold").evaluate(&data));
+        assert!(
+            FilterOp::json_path("order.customer.tier", CompareOp::Neq, "silver").evaluate(&data)
+        );
+        assert!(FilterOp::json_path("order.customer.age", CompareOp::Gte, "42").evaluate(&data));
+        assert!(FilterOp::json_path("order.customer.age", CompareOp::Lt, "100").evaluate(&data));
+        assert!(!FilterOp::json_path("order.customer.age", CompareOp::Gt, "42").evaluate(&data));
+        assert!(FilterOp::json_path("order.items.1.sku", CompareOp::Eq, "B-2").evaluate(&data));
+        assert!(!FilterOp::json_path("order.items.5.sku", CompareOp::Eq, "B-2").evaluate(&data));
+        assert!(!FilterOp::json_path("order.missing.tier", CompareOp::Eq, "gold").evaluate(&data));
+    }
+
+    #[test]
+    fn test_compare_op_from_str() {
+        assert_eq!("lte".parse::<CompareOp>(), Ok(CompareOp::Lte));
+        assert!(matches!(
+            "between".parse::<CompareOp>(),
+            Err(FilterValidationError::UnknownOperator(_))
+        ));
+    }
+
     #[test]
     fn test_validation_empty_field() {
         let filter = FilterOp::eq("", "value");
@@ -358,4 +1430,170 @@ mod tests {
             Err(FilterValidationError::EmptyField)
         ));
     }
+
+    #[test]
+    fn test_merge_and_filter_chain() {
+        let merged = FilterOp::merge(FilterOp::eq("a", "1"), FilterOp::eq("b", "2"));
+        assert!(merged.evaluate(&serde_json::json!({"a": "1", "b": "2"})));
+        assert!(!merged.evaluate(&serde_json::json!({"a": "0", "b": "2"})));
+        assert!(!merged.evaluate(&serde_json::json!({"a": "1", "b": "0"})));
+        assert!(!merged.evaluate(&serde_json::json!({"a": "1"})));
+
+        let intersect = FilterOp::intersect(vec![
+            FilterOp::eq("a", "1"),
+            FilterOp::eq("b", "2"),
+            FilterOp::exists("c"),
+        ]);
+        assert!(intersect.evaluate(&serde_json::json!({"a": "1", "b": "2", "c": 0})));
+        assert!(!intersect.evaluate(&serde_json::json!({"a": "1", "b": "2"})));
+
+        let chain = FilterChain::new()
+            .require(FilterOp::eq("tenant_id", "abc"))
+            .optional_if(false, FilterOp::eq("user_role", "admin"))
+            .build();
+        assert!(chain.evaluate(&serde_json::json!({"tenant_id": "abc", "user_role": "guest"})));
+
+        let chain = FilterChain::new()
+            .require(FilterOp::eq("tenant_id", "abc"))
+            .optional_if(true, FilterOp::eq("user_role", "admin"))
+            .build();
+        assert!(!chain.evaluate(&serde_json::json!({"tenant_id": "abc", "user_role": "guest"})));
+        assert!(chain.evaluate(&serde_json::json!({"tenant_id": "abc", "user_role": "admin"})));
+    }
+
+    #[test]
+    fn test_url_query_string_round_trip() {
+        let values = |vs: &[&str]| vs.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        let filters = vec![
+            FilterOp::eq("type", "goal"),
+            FilterOp::neq("type", "foul"),
+            FilterOp::lt("minute", "45"),
+            FilterOp::lte("minute", "45"),
+            FilterOp::gt("score", "1.5"),
+            FilterOp::gte("score", "-2"),
+            FilterOp::in_set("league", values(&["premier", "champions"])),
+            FilterOp::not_in("league", values(&["friendly"])),
+            FilterOp::exists("var"),
+            FilterOp::not_exists("var"),
+            FilterOp::json_path("player.stats.goals", CompareOp::Gte, "3"),
+            FilterOp::eq("team name", "a:b,c|d(e)!f%g&h=i"),
+            FilterOp::and(vec![FilterOp::eq("a", "1"), FilterOp::eq("b", "2")]),
+            FilterOp::or(vec![
+                FilterOp::in_set("a", values(&["1", "2"])),
+                FilterOp::and(vec![FilterOp::eq("b", "2"), FilterOp::exists("c")]),
+                FilterOp::or(vec![FilterOp::eq("d", "4"), FilterOp::eq("e", "5")]),
+            ]),
+            FilterOp::and(vec![
+                FilterOp::and(vec![FilterOp::eq("a", "1"), FilterOp::eq("b", "2")]),
+                FilterOp::not(FilterOp::or(vec![
+                    FilterOp::eq("c", "3"),
+                    FilterOp::eq("d", "4"),
+                ])),
+                FilterOp::not(FilterOp::eq("e", "5")),
+            ]),
+        ];
+
+        for filter in filters {
+            let query = filter.to_url_query_string();
+            assert_eq!(
+                FilterOp::from_url_query_string(&query),
+                Ok(filter.clone()),
+                "query {}",
+                query
+            );
+        }
+    }
+
+    #[test]
+    fn test_url_query_string_parsing() {
+        let parsed =
+            FilterOp::from_url_query_string("?filter=type:eq:goal,league:in:premier,champions")
+                .unwrap();
+        assert_eq!(
+            parsed,
+            FilterOp::and(vec![
+                FilterOp::eq("type", "goal"),
+                FilterOp::in_set(
+                    "league",
+                    vec!["premier".to_string(), "champions".to_string()]
+                ),
+            ])
+        );
+        assert_eq!(
+            parsed.to_url_query_string(),
+            "type:eq:goal,league:in:premier|champions"
+        );
+
+        assert_eq!(
+            FilterOp::from_url_query_string("type:like:goal"),
+            Err(FilterValidationError::UnknownOperator("like".to_string()))
+        );
+        for malformed in [
+            "",
+            "type",
+            ":eq:goal",
+            "(a:eq:1|b:eq:2",
+            "a:eq:1)",
+            "a:eq:%zz",
+        ] {
+            assert!(
+                matches!(
+                    FilterOp::from_url_query_string(malformed),
+                    Err(FilterValidationError::InvalidQuery(_))
+                ),
+                "{:?} should be rejected",
+                malformed
+            );
+        }
+    }
+
+    #[test]
+    fn test_timestamp_filters() {
+        let filter = FilterOp::timestamp_after("created_at", 1_700_000_000);
+        assert_eq!(
+            filter.to_json(),
+            serde_json::json!({
+                "op": "timestamp_gt",
+                "field": "created_at",
+                "value": "1700000000",
+            })
+        );
+
+        let now = unix_secs();
+        let recent = FilterOp::timestamp_within_last_secs("created_at", 3600);
+        let half_hour_ago = serde_json::json!({ "created_at": now - 30 * 60 });
+        let ninety_minutes_ago = serde_json::json!({ "created_at": now - 90 * 60 });
+        assert!(recent.evaluate(&half_hour_ago));
+        assert!(!recent.evaluate(&ninety_minutes_ago));
+        assert!(recent.compile().matches(&half_hour_ago));
+        assert!(!recent.compile().matches(&ninety_minutes_ago));
+
+        let between = FilterOp::timestamp_between("ts", 100, 200);
+        for (ts, expected) in [
+            (serde_json::json!(150), true),
+            (serde_json::json!("150"), true),
+            (serde_json::json!(100), false),
+            (serde_json::json!(250), false),
+            (serde_json::json!(-5), false),
+            (serde_json::json!("soon"), false),
+        ] {
+            let data = serde_json::json!({ "ts": ts });
+            assert_eq!(between.evaluate(&data), expected, "{}", data);
+            assert_eq!(between.compile().matches(&data), expected, "{}", data);
+        }
+        assert!(!FilterOp::timestamp_before("ts", 200).evaluate(&serde_json::json!({})));
+
+        let parsed = FilterOp::from_url_query_string(&between.to_url_query_string()).unwrap();
+        assert_eq!(parsed, between);
+        assert_eq!(
+            FilterOp::TimestampGt {
+                field: "ts".to_string(),
+                value: "yesterday".to_string(),
+            }
+            .validate(),
+            Err(FilterValidationError::InvalidTimestamp(
+                "yesterday".to_string()
+            ))
+        );
+    }
 }
@@ -1,8 +1,14 @@
 //! Pusher protocol message types and encoding/decoding.
 
 use crate::error::{Result, SockudoError};
+use crate::protocol::{validate_channel_name, FilterOp};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
 
 /// Pusher event message structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +24,14 @@ pub struct PusherEvent {
     pub data: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user_id: Option<String>,
+    /// When the event was received from the socket (never serialized)
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    pub timestamp: Option<std::time::SystemTime>,
+    /// When the event was received, in JS milliseconds (never serialized)
+    #[cfg(target_arch = "wasm32")]
+    #[serde(skip)]
+    pub timestamp: Option<f64>,
 }
 
 impl PusherEvent {
@@ -27,9 +41,49 @@ impl PusherEvent {
             channel: None,
             data: None,
             user_id: None,
+            timestamp: None,
+        }
+    }
+
+    /// Stamp the event as received now
+    pub fn mark_received(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.timestamp = Some(std::time::SystemTime::now());
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            self.timestamp = Some(js_sys::Date::now());
         }
     }
 
+    /// When the event was received, in milliseconds since the Unix epoch,
+    /// if it was stamped
+    pub fn received_at_ms(&self) -> Option<f64> {
+        #[cfg(not(target_arch = "wasm32"))]
+        return self
+            .timestamp
+            .and_then(|ts| ts.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|since| since.as_secs_f64() * 1000.0);
+        #[cfg(target_arch = "wasm32")]
+        return self.timestamp;
+    }
+
+    /// Time elapsed since the event was received, if it was stamped
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn age(&self) -> Option<std::time::Duration> {
+        self.timestamp.and_then(|ts| ts.elapsed().ok())
+    }
+
+    /// Time elapsed since the event was received, if it was stamped
+    #[cfg(target_arch = "wasm32")]
+    pub fn age(&self) -> Option<std::time::Duration> {
+        self.timestamp.map(|ts| {
+            let elapsed_ms = (js_sys::Date::now() - ts).max(0.0);
+            std::time::Duration::from_secs_f64(elapsed_ms / 1000.0)
+        })
+    }
+
     /// Get data field as Value (for non-wasm builds)
     #[cfg(not(feature = "wasm"))]
     pub fn data_as_value(&self) -> Option<Value> {
@@ -182,6 +236,29 @@ pub struct ErrorData {
     pub code: Option<i32>,
 }
 
+/// A `pusher:error` sent by the server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PusherServerError {
+    pub code: u32,
+    pub message: String,
+    /// Codes 4000-4099: the connection must not be retried as is
+    pub is_fatal: bool,
+}
+
+impl PusherServerError {
+    /// Range of error codes after which the client must not reconnect
+    pub const FATAL_CODES: std::ops::RangeInclusive<u32> = 4000..=4099;
+
+    /// Build an error, deriving `is_fatal` from `code`
+    pub fn new(code: u32, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            is_fatal: Self::FATAL_CODES.contains(&code),
+        }
+    }
+}
+
 /// Channel data for subscription
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChannelData {
@@ -202,12 +279,11 @@ pub struct SubscribeData {
     pub auth: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub channel_data: Option<String>,
-    #[cfg(feature = "wasm")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tags_filter: Option<Value>,
-    #[cfg(not(feature = "wasm"))]
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub tags_filter: Option<String>,
+    /// Custom key-value pairs for server extensions (e.g. device ID)
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub metadata: std::collections::HashMap<String, String>,
 }
 
 /// Unsubscribe message data
@@ -216,6 +292,9 @@ pub struct UnsubscribeData {
     pub channel: String,
 }
 
+/// Field carrying a message checksum in debug mode
+pub const CHECKSUM_FIELD: &str = "__crc";
+
 /// Protocol encoder/decoder
 pub struct Protocol;
 
@@ -225,11 +304,140 @@ impl Protocol {
         serde_json::to_string(event).map_err(Into::into)
     }
 
+    /// Encode several events into a single JSON array frame
+    pub fn encode_batch(events: &[PusherEvent]) -> Result<String> {
+        serde_json::to_string(events).map_err(Into::into)
+    }
+
     /// Decode a message from JSON string
     pub fn decode_message(raw: &str) -> Result<PusherEvent> {
         serde_json::from_str(raw).map_err(Into::into)
     }
 
+    /// CRC32 of a raw message, used for debug integrity checks
+    pub fn message_checksum(message: &str) -> u32 {
+        crc32fast::hash(message.as_bytes())
+    }
+
+    /// Remove debug-only fields (`__crc`) from a decoded message
+    pub fn strip_debug_fields(message: &mut Value) {
+        if let Some(object) = message.as_object_mut() {
+            object.remove(CHECKSUM_FIELD);
+        }
+    }
+
+    /// Remove server-internal fields from the data of a user event before it
+    /// reaches callbacks.
+    ///
+    /// Strips top-level `__`-prefixed keys (e.g. `__delta_seq`), which the
+    /// protocol reserves for the server. Keys the publisher chose, including
+    /// `timestamp`, are kept. Data that is not a JSON object and
+    /// `pusher:`/`pusher_internal:` events are left untouched.
+    pub fn sanitize_message(event: &mut PusherEvent) {
+        if event.is_internal() {
+            return;
+        }
+
+        fn strip(object: &mut serde_json::Map<String, Value>) -> bool {
+            let before = object.len();
+            object.retain(|key, _| !key.starts_with("__"));
+            object.len() != before
+        }
+
+        #[cfg(feature = "wasm")]
+        if let Some(Value::Object(ref mut object)) = event.data {
+            strip(object);
+        }
+        #[cfg(not(feature = "wasm"))]
+        if let Some(ref mut data) = event.data {
+            if let Ok(Value::Object(mut object)) = serde_json::from_str(data) {
+                if strip(&mut object) {
+                    *data = Value::Object(object).to_string();
+                }
+            }
+        }
+    }
+
+    /// Add a `__crc` field holding the checksum of the message without it.
+    ///
+    /// The checksum covers the compact JSON encoding of the message, so
+    /// the receiver can recompute it after stripping the field. Messages
+    /// that are not JSON objects (e.g. batch frames) are returned as is.
+    #[cfg(debug_assertions)]
+    pub fn attach_checksum(message: &str) -> Result<String> {
+        let mut value: Value = serde_json::from_str(message)?;
+        if !value.is_object() {
+            return Ok(message.to_string());
+        }
+        Self::strip_debug_fields(&mut value);
+        let checksum = Self::message_checksum(&value.to_string());
+        if let Some(object) = value.as_object_mut() {
+            object.insert(CHECKSUM_FIELD.to_string(), checksum.into());
+        }
+        Ok(value.to_string())
+    }
+
+    /// Check the `__crc` field of a raw message against its contents.
+    ///
+    /// Messages without the field pass. Returns
+    /// `SockudoError::ChecksumMismatch` when the checksum does not match.
+    #[cfg(debug_assertions)]
+    pub fn verify_checksum(message: &str) -> Result<()> {
+        let mut value: Value = serde_json::from_str(message)?;
+        let Some(expected) = value.get(CHECKSUM_FIELD).and_then(Value::as_u64) else {
+            return Ok(());
+        };
+        Self::strip_debug_fields(&mut value);
+        let actual = Self::message_checksum(&value.to_string());
+        if expected != u64::from(actual) {
+            return Err(SockudoError::checksum_mismatch(expected as u32, actual));
+        }
+        Ok(())
+    }
+
+    /// Sign a client event with HMAC-SHA256, returning a base64 signature.
+    ///
+    /// The signed payload is `"{event}\n{channel}\n{data}"`, with missing
+    /// fields treated as empty strings.
+    pub fn sign_message(event: &PusherEvent, secret: &str) -> String {
+        let mac = Self::message_mac(event, secret);
+        BASE64.encode(mac.finalize().into_bytes())
+    }
+
+    /// Check a signature produced by `sign_message` in constant time
+    pub fn verify_signature(event: &PusherEvent, secret: &str, signature: &str) -> bool {
+        match BASE64.decode(signature) {
+            Ok(bytes) => Self::message_mac(event, secret)
+                .verify_slice(&bytes)
+                .is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    fn message_mac(event: &PusherEvent, secret: &str) -> HmacSha256 {
+        #[cfg(feature = "wasm")]
+        let data = event
+            .data
+            .as_ref()
+            .map(|d| d.to_string())
+            .unwrap_or_default();
+        #[cfg(not(feature = "wasm"))]
+        let data = event.data.clone().unwrap_or_default();
+
+        let mut mac =
+            HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+        mac.update(
+            format!(
+                "{}\n{}\n{}",
+                event.event,
+                event.channel.as_deref().unwrap_or_default(),
+                data
+            )
+            .as_bytes(),
+        );
+        mac
+    }
+
     /// Process handshake response
     pub fn process_handshake(event: &PusherEvent) -> Result<HandshakeResult> {
         match event.event.as_str() {
@@ -256,6 +464,39 @@ impl Protocol {
         }
     }
 
+    /// Decode a raw frame as a `pusher:error`.
+    ///
+    /// The error data may be a JSON object or a JSON-encoded string. Returns
+    /// `None` if the frame is not a `pusher:error` event or carries no
+    /// numeric error code.
+    pub fn decode_pusher_error(message: &str) -> Option<PusherServerError> {
+        let frame: Value = serde_json::from_str(message).ok()?;
+        if frame.get("event").and_then(Value::as_str) != Some("pusher:error") {
+            return None;
+        }
+        match frame.get("data")? {
+            Value::String(data) => Self::server_error_from_data(&serde_json::from_str(data).ok()?),
+            data => Self::server_error_from_data(data),
+        }
+    }
+
+    /// Read the error code and message of an already decoded `pusher:error`
+    pub fn pusher_server_error(event: &PusherEvent) -> Option<PusherServerError> {
+        if event.event != "pusher:error" {
+            return None;
+        }
+        Self::server_error_from_data(&event.parse_data().ok()?)
+    }
+
+    fn server_error_from_data(data: &Value) -> Option<PusherServerError> {
+        let code = u32::try_from(data.get("code").and_then(Value::as_u64)?).ok()?;
+        let message = data
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("Unknown error");
+        Some(PusherServerError::new(code, message))
+    }
+
     /// Get action based on close code
     pub fn get_close_action(code: Option<u16>) -> CloseAction {
         match code {
@@ -287,40 +528,45 @@ impl Protocol {
         }
     }
 
-    /// Create a subscribe event
-    #[cfg(feature = "wasm")]
+    /// Create a `pusher:subscribe` event.
+    ///
+    /// Returns `SockudoError::InvalidChannel` if the channel name fails
+    /// `validate_channel_name`.
     pub fn create_subscribe_event(
         channel: &str,
-        auth: Option<String>,
-        channel_data: Option<String>,
-        tags_filter: Option<Value>,
-    ) -> PusherEvent {
-        let data = SubscribeData {
-            channel: channel.to_string(),
+        auth: Option<&str>,
+        channel_data: Option<&str>,
+        filter: Option<&FilterOp>,
+    ) -> Result<PusherEvent> {
+        Self::create_subscribe_event_with_metadata(
+            channel,
             auth,
             channel_data,
-            tags_filter,
-        };
-
-        PusherEvent::new("pusher:subscribe").with_json_data(serde_json::to_value(data).unwrap())
+            filter,
+            &Default::default(),
+        )
     }
 
-    /// Create a subscribe event
-    #[cfg(not(feature = "wasm"))]
-    pub fn create_subscribe_event(
+    /// Create a `pusher:subscribe` event carrying custom `metadata`, which
+    /// is omitted from the message when empty
+    pub fn create_subscribe_event_with_metadata(
         channel: &str,
-        auth: Option<String>,
-        channel_data: Option<String>,
-        tags_filter: Option<String>,
-    ) -> PusherEvent {
+        auth: Option<&str>,
+        channel_data: Option<&str>,
+        filter: Option<&FilterOp>,
+        metadata: &std::collections::HashMap<String, String>,
+    ) -> Result<PusherEvent> {
+        validate_channel_name(channel)?;
+
         let data = SubscribeData {
             channel: channel.to_string(),
-            auth,
-            channel_data,
-            tags_filter,
+            auth: auth.map(str::to_string),
+            channel_data: channel_data.map(str::to_string),
+            tags_filter: filter.map(FilterOp::to_json),
+            metadata: metadata.clone(),
         };
 
-        PusherEvent::new("pusher:subscribe").with_json_data(serde_json::to_value(data).unwrap())
+        Ok(PusherEvent::new("pusher:subscribe").with_json_data(serde_json::to_value(data)?))
     }
 
     /// Create an unsubscribe event
@@ -402,6 +648,31 @@ pub const CLIENT_VERSION: &str = env!("CARGO_PKG_VERSION");
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::protocol::MAX_CHANNEL_NAME_LENGTH;
+
+    #[test]
+    fn test_decode_pusher_error() {
+        let fatal = Protocol::decode_pusher_error(
+            r#"{"event":"pusher:error","data":{"code":4004,"message":"Over quota"}}"#,
+        )
+        .unwrap();
+        assert_eq!(fatal, PusherServerError::new(4004, "Over quota"));
+        assert!(fatal.is_fatal);
+
+        let retriable = Protocol::decode_pusher_error(
+            r#"{"event":"pusher:error","data":"{\"code\":4201,\"message\":\"Pong reply not received\"}"}"#,
+        )
+        .unwrap();
+        assert_eq!(retriable.code, 4201);
+        assert!(!retriable.is_fatal);
+
+        assert!(Protocol::decode_pusher_error(
+            r#"{"event":"pusher:error","data":{"message":"No code"}}"#
+        )
+        .is_none());
+        assert!(Protocol::decode_pusher_error(r#"{"event":"pusher:ping","data":{}}"#).is_none());
+        assert!(Protocol::decode_pusher_error("not json").is_none());
+    }
 
     #[test]
     fn test_decode_connection_established() {
@@ -424,14 +695,163 @@ mod tests {
 
     #[test]
     fn test_encode_subscribe() {
-        let event = Protocol::create_subscribe_event(
-            "test-channel",
-            Some("auth-token".to_string()),
-            None,
-            None,
-        );
+        let event =
+            Protocol::create_subscribe_event("test-channel", Some("auth-token"), None, None)
+                .unwrap();
         let json = Protocol::encode_message(&event).unwrap();
         assert!(json.contains("pusher:subscribe"));
         assert!(json.contains("test-channel"));
     }
+
+    /// Encode `event` and return the frame with its `data` field parsed
+    fn encoded_frame(event: &PusherEvent) -> Value {
+        let mut frame: Value =
+            serde_json::from_str(&Protocol::encode_message(event).unwrap()).unwrap();
+        if let Some(data) = frame["data"].as_str() {
+            frame["data"] = serde_json::from_str(data).unwrap();
+        }
+        frame
+    }
+
+    #[test]
+    fn test_protocol_message_encoding() {
+        use serde_json::json;
+
+        assert_eq!(
+            encoded_frame(&Protocol::create_ping_event()),
+            json!({ "event": "pusher:ping", "data": {} })
+        );
+        assert_eq!(
+            encoded_frame(&Protocol::create_pong_event()),
+            json!({ "event": "pusher:pong", "data": {} })
+        );
+        assert_eq!(
+            encoded_frame(&Protocol::create_unsubscribe_event("my-channel")),
+            json!({ "event": "pusher:unsubscribe", "data": { "channel": "my-channel" } })
+        );
+        assert_eq!(
+            encoded_frame(
+                &Protocol::create_subscribe_event("my-channel", None, None, None).unwrap()
+            ),
+            json!({ "event": "pusher:subscribe", "data": { "channel": "my-channel" } })
+        );
+
+        let filter = FilterOp::eq("symbol".to_string(), "BTC".to_string());
+        let event = Protocol::create_subscribe_event(
+            "presence-room",
+            Some("key:signature"),
+            Some(r#"{"user_id":"1"}"#),
+            Some(&filter),
+        )
+        .unwrap();
+        assert_eq!(
+            encoded_frame(&event),
+            json!({
+                "event": "pusher:subscribe",
+                "data": {
+                    "channel": "presence-room",
+                    "auth": "key:signature",
+                    "channel_data": r#"{"user_id":"1"}"#,
+                    "tags_filter": filter.to_json(),
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_sanitize_message() {
+        use serde_json::json;
+
+        let data = json!({
+            "__delta_seq": 4,
+            "__internal_id": "abc",
+            "timestamp": 1700000000,
+            "price": 100,
+            "nested": {"__keep": true}
+        });
+        let mut event = PusherEvent::new("update")
+            .with_channel("prices")
+            .with_json_data(data.clone());
+        Protocol::sanitize_message(&mut event);
+        assert_eq!(
+            event.data_as_value(),
+            Some(json!({"timestamp": 1700000000, "price": 100, "nested": {"__keep": true}}))
+        );
+
+        let mut internal = PusherEvent::new("pusher:delta").with_json_data(data.clone());
+        Protocol::sanitize_message(&mut internal);
+        assert_eq!(internal.data_as_value(), Some(data));
+
+        let mut plain = PusherEvent::new("update").with_string_data("__not_json");
+        Protocol::sanitize_message(&mut plain);
+        assert_eq!(plain.data_as_string().as_deref(), Some("__not_json"));
+    }
+
+    #[test]
+    fn test_subscribe_rejects_invalid_channel_names() {
+        for name in [
+            "bad\0channel".to_string(),
+            "a".repeat(MAX_CHANNEL_NAME_LENGTH + 1),
+        ] {
+            assert!(matches!(
+                Protocol::create_subscribe_event(&name, None, None, None),
+                Err(SockudoError::InvalidChannel { .. })
+            ));
+        }
+        assert!(Protocol::create_subscribe_event(
+            &"a".repeat(MAX_CHANNEL_NAME_LENGTH),
+            None,
+            None,
+            None
+        )
+        .is_ok());
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_message_checksum() {
+        let raw = r#"{"event":"my-event","channel":"test-channel","data":"{\"price\":100}"}"#;
+        let checked = Protocol::attach_checksum(raw).unwrap();
+        assert!(Protocol::verify_checksum(&checked).is_ok());
+        // Messages without a checksum are accepted
+        assert!(Protocol::verify_checksum(raw).is_ok());
+
+        let corrupted = checked.replace("100", "900");
+        match Protocol::verify_checksum(&corrupted) {
+            Err(SockudoError::ChecksumMismatch { expected, actual }) => {
+                assert_ne!(expected, actual);
+                let mut value: Value = serde_json::from_str(&corrupted).unwrap();
+                Protocol::strip_debug_fields(&mut value);
+                assert_eq!(actual, Protocol::message_checksum(&value.to_string()));
+                assert!(value.get(CHECKSUM_FIELD).is_none());
+            }
+            other => panic!("expected ChecksumMismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_sign_message_detects_tampering() {
+        let event = Protocol::create_client_event(
+            "client-typing",
+            "private-chat",
+            serde_json::json!({"user": "ada"}),
+        )
+        .unwrap();
+        let signature = Protocol::sign_message(&event, "s3cret");
+        assert!(Protocol::verify_signature(&event, "s3cret", &signature));
+        assert!(!Protocol::verify_signature(&event, "other", &signature));
+        assert!(!Protocol::verify_signature(&event, "s3cret", "not base64!"));
+
+        let mut renamed = event.clone();
+        renamed.event = "client-stopped".to_string();
+        let mut moved = event.clone();
+        moved.channel = Some("private-other".to_string());
+        let edited = event
+            .clone()
+            .with_json_data(serde_json::json!({"user": "eve"}));
+
+        for tampered in [renamed, moved, edited] {
+            assert!(!Protocol::verify_signature(&tampered, "s3cret", &signature));
+        }
+    }
 }
@@ -2,6 +2,12 @@
 
 pub mod message_types;
 pub mod filter;
+pub mod transform;
+pub mod validation;
 
 pub use message_types::*;
 pub use filter::*;
+pub use transform::{HeaderInjectionTransformer, MessageTransformer};
+pub use validation::{
+    validate_channel_name, validate_event_name, MAX_CHANNEL_NAME_LENGTH, MAX_EVENT_NAME_LENGTH,
+};
@@ -0,0 +1,153 @@
+//! Hooks rewriting messages as they are encoded and decoded.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::message_types::{Protocol, PusherEvent};
+use crate::error::Result;
+
+/// Rewrites every event on its way to and from the wire, e.g. to add or
+/// strip envelope fields.
+///
+/// Transformers run synchronously in the send and receive paths, so they
+/// must be cheap and must not block. Heartbeat pings and pongs and signed
+/// client events are sent as they are, since rewriting them would break
+/// the heartbeat or the signature.
+pub trait MessageTransformer: Send + Sync {
+    /// Rewrite an event before it is serialized and sent
+    fn transform_outgoing(&self, event: &mut PusherEvent);
+
+    /// Rewrite a received event before it is emitted
+    fn transform_incoming(&self, event: &mut PusherEvent);
+}
+
+impl std::fmt::Debug for dyn MessageTransformer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MessageTransformer").finish_non_exhaustive()
+    }
+}
+
+impl Protocol {
+    /// Encode a message after passing it through `transformers`, in order
+    pub fn encode_transformed(
+        event: &PusherEvent,
+        transformers: &[Arc<dyn MessageTransformer>],
+    ) -> Result<String> {
+        if transformers.is_empty() {
+            return Self::encode_message(event);
+        }
+        let mut event = event.clone();
+        for transformer in transformers {
+            transformer.transform_outgoing(&mut event);
+        }
+        Self::encode_message(&event)
+    }
+
+    /// Encode events as one JSON array frame after passing each through
+    /// `transformers`, in order
+    pub fn encode_batch_transformed(
+        events: &[PusherEvent],
+        transformers: &[Arc<dyn MessageTransformer>],
+    ) -> Result<String> {
+        if transformers.is_empty() {
+            return Self::encode_batch(events);
+        }
+        let mut events = events.to_vec();
+        for event in &mut events {
+            for transformer in transformers {
+                transformer.transform_outgoing(event);
+            }
+        }
+        Self::encode_batch(&events)
+    }
+}
+
+/// Adds `headers` to the data object of every outgoing event.
+///
+/// Protocol events (`pusher:*` and `pusher_internal:*`) and events whose
+/// data is not a JSON object are sent unchanged, and fields already present
+/// in the data are kept.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeaderInjectionTransformer {
+    /// Field names and values to add
+    pub headers: HashMap<String, String>,
+}
+
+impl HeaderInjectionTransformer {
+    /// Inject `headers` into every outgoing event
+    pub fn new(headers: HashMap<String, String>) -> Self {
+        Self { headers }
+    }
+
+    /// Builder pattern: also inject `name` with `value`
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+}
+
+impl MessageTransformer for HeaderInjectionTransformer {
+    fn transform_outgoing(&self, event: &mut PusherEvent) {
+        if event.is_internal() {
+            return;
+        }
+        let Some(serde_json::Value::Object(mut data)) = event.data_as_value() else {
+            return;
+        };
+        for (name, value) in &self.headers {
+            data.entry(name.as_str())
+                .or_insert_with(|| value.clone().into());
+        }
+        let data = serde_json::Value::Object(data);
+        #[cfg(feature = "wasm")]
+        {
+            event.data = Some(data);
+        }
+        #[cfg(not(feature = "wasm"))]
+        {
+            event.data = Some(data.to_string());
+        }
+    }
+
+    fn transform_incoming(&self, _event: &mut PusherEvent) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_injection() {
+        let transformer: Arc<dyn MessageTransformer> = Arc::new(
+            HeaderInjectionTransformer::default()
+                .header("tenant", "acme")
+                .header("v", "2"),
+        );
+
+        let event = PusherEvent::new("client-x")
+            .with_channel("private-room")
+            .with_json_data(serde_json::json!({"text": "hi", "v": "1"}));
+        let encoded =
+            Protocol::encode_transformed(&event, std::slice::from_ref(&transformer)).unwrap();
+        let decoded = Protocol::decode_message(&encoded).unwrap();
+        assert_eq!(
+            decoded.data_as_value(),
+            Some(serde_json::json!({"text": "hi", "tenant": "acme", "v": "1"}))
+        );
+        assert_eq!(decoded.channel.as_deref(), Some("private-room"));
+
+        // Non-object data is left alone
+        let mut event = PusherEvent::new("client-x").with_json_data(serde_json::json!([1, 2]));
+        transformer.transform_outgoing(&mut event);
+        assert_eq!(event.data_as_value(), Some(serde_json::json!([1, 2])));
+
+        // So are protocol events
+        let mut event = PusherEvent::new("pusher:subscribe")
+            .with_json_data(serde_json::json!({"channel": "a"}));
+        transformer.transform_outgoing(&mut event);
+        assert_eq!(
+            event.data_as_value(),
+            Some(serde_json::json!({"channel": "a"}))
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Canonical rules for event and channel names.
+//!
+//! Both validators only inspect the name and allocate nothing unless the
+//! name is rejected.
+
+use crate::error::{Result, SockudoError};
+use std::fmt;
+
+/// Maximum length of an event name, in characters
+pub const MAX_EVENT_NAME_LENGTH: usize = 200;
+
+/// Maximum length of a channel name, in characters
+pub const MAX_CHANNEL_NAME_LENGTH: usize = 200;
+
+/// Why a name was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NameError {
+    Empty,
+    TooLong { length: usize, limit: usize },
+    ReservedPrefix,
+    Whitespace,
+    InvalidCharacter(char),
+}
+
+impl fmt::Display for NameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "name is empty"),
+            Self::TooLong { length, limit } => {
+                write!(f, "{} characters exceeds the limit of {}", length, limit)
+            }
+            Self::ReservedPrefix => write!(f, "names cannot start with '#'"),
+            Self::Whitespace => write!(f, "name contains whitespace"),
+            Self::InvalidCharacter(c) => write!(f, "invalid character {:?}", c),
+        }
+    }
+}
+
+/// Whether `c` is allowed in event names
+#[inline]
+fn is_event_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | ':' | '.')
+}
+
+/// Whether `c` is allowed in channel names: the event name characters plus
+/// the `=@,;` Pusher also permits
+#[inline]
+fn is_channel_name_char(c: char) -> bool {
+    is_event_name_char(c) || matches!(c, '=' | '@' | ',' | ';')
+}
+
+/// Check the emptiness, length and character rules shared by both validators
+fn check_name(
+    name: &str,
+    limit: usize,
+    is_name_char: fn(char) -> bool,
+) -> std::result::Result<(), NameError> {
+    if name.is_empty() {
+        return Err(NameError::Empty);
+    }
+    let length = name.chars().count();
+    if length > limit {
+        return Err(NameError::TooLong { length, limit });
+    }
+    match name.chars().find(|c| !is_name_char(*c)) {
+        Some(c) => Err(NameError::InvalidCharacter(c)),
+        None => Ok(()),
+    }
+}
+
+/// Check an event name: non-empty, at most 200 characters, and made only of
+/// ASCII alphanumerics and `_-:.`.
+///
+/// Returns `SockudoError::InvalidEventName` with the reason on failure.
+pub fn validate_event_name(name: &str) -> Result<()> {
+    check_name(name, MAX_EVENT_NAME_LENGTH, is_event_name_char)
+        .map_err(|reason| SockudoError::invalid_event_name(name, reason.to_string()))
+}
+
+/// Check a channel name with the event name rules, additionally allowing
+/// `=@,;` and rejecting names that start with `#` (reserved for server
+/// channels) or contain whitespace.
+///
+/// Returns `SockudoError::InvalidChannel` with the reason on failure.
+pub fn validate_channel_name(name: &str) -> Result<()> {
+    let checked = if name.starts_with('#') {
+        Err(NameError::ReservedPrefix)
+    } else if name.contains(char::is_whitespace) {
+        Err(NameError::Whitespace)
+    } else {
+        check_name(name, MAX_CHANNEL_NAME_LENGTH, is_channel_name_char)
+    };
+    checked.map_err(|reason| {
+        SockudoError::invalid_channel(format!("Invalid channel name '{}': {}", name, reason))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event_reason(name: &str) -> String {
+        match validate_event_name(name) {
+            Err(SockudoError::InvalidEventName { reason, .. }) => reason,
+            other => panic!("expected InvalidEventName, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate_event_name() {
+        assert!(validate_event_name("client-message").is_ok());
+        assert!(validate_event_name("chat:message.v2_final").is_ok());
+        assert!(validate_event_name("a").is_ok());
+        assert!(validate_event_name(&"a".repeat(200)).is_ok());
+
+        assert_eq!(event_reason(""), "name is empty");
+        assert_eq!(
+            event_reason(&"a".repeat(201)),
+            "201 characters exceeds the limit of 200"
+        );
+        assert_eq!(event_reason("my event"), "invalid character ' '");
+        assert_eq!(event_reason("price/update"), "invalid character '/'");
+        assert_eq!(event_reason("user@host"), "invalid character '@'");
+        assert_eq!(event_reason("café"), "invalid character 'é'");
+        // Leading '#' and whitespace are only special for channel names
+        assert_eq!(event_reason("#tag"), "invalid character '#'");
+    }
+
+    #[test]
+    fn test_validate_channel_name() {
+        assert!(validate_channel_name("my-channel").is_ok());
+        assert!(validate_channel_name("private-encrypted-room.1").is_ok());
+        assert!(validate_channel_name("presence-team:42").is_ok());
+        assert!(validate_channel_name(&"c".repeat(200)).is_ok());
+        assert!(validate_channel_name("private-user=alice@example.com;a,b").is_ok());
+
+        let reason = |name: &str| validate_channel_name(name).unwrap_err().to_string();
+        assert!(reason("").ends_with("name is empty"));
+        assert!(reason(&"c".repeat(201)).ends_with("201 characters exceeds the limit of 200"));
+        assert!(reason("#server-to-user-1").ends_with("names cannot start with '#'"));
+        assert!(reason("news feed").ends_with("name contains whitespace"));
+        assert!(reason("news\tfeed").ends_with("name contains whitespace"));
+        assert!(reason("news#feed").ends_with("invalid character '#'"));
+        assert!(reason("news/feed").ends_with("invalid character '/'"));
+        assert!(matches!(
+            validate_channel_name("#"),
+            Err(SockudoError::InvalidChannel { .. })
+        ));
+    }
+}
@@ -1,20 +1,31 @@
 //! Main Sockudo/Pusher client implementation.
 
 use parking_lot::RwLock;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::SystemTime;
 use tracing::{debug, info, warn};
 
-#[cfg(not(feature = "uniffi"))]
+#[cfg(not(target_arch = "wasm32"))]
 use crate::auth::AuthClient;
-use crate::channels::{Channel, Channels, PresenceChannel};
-use crate::connection::{ConnectionManager, ConnectionState};
-use crate::delta::DeltaManager;
+use crate::channels::{
+    Channel, ChannelAuthData, ChannelGroup, ChannelSnapshot, ChannelType, ChannelWatcher, Channels,
+    PresenceChannel, SendEventFn,
+};
+use crate::connection::{
+    ConnectionManager, ConnectionState, ConnectionStats, MessagePriority, ReconnectAttempt,
+};
+use crate::delta::{DeltaAlgorithm, DeltaManager, DeltaStats};
 use crate::error::{Result, SockudoError};
-use crate::events::EventDispatcher;
+use crate::events::{
+    ClientMiddleware, DispatchMetrics, EventDispatcher, EventLogGuard, RegistryMemoryStats,
+};
 #[cfg(feature = "uniffi")]
-use crate::ffi_callbacks::EventCallback;
-use crate::options::{Config, SockudoOptions};
-use crate::protocol::{FilterOp, Protocol};
+use crate::ffi_callbacks::{
+    ConnectionCallback, DeltaDecodedCallback, EventCallback, FullMessageCallback,
+};
+use crate::options::{ChannelSubscribeOptions, Config, SockudoOptions};
+use crate::protocol::{validate_channel_name, FilterOp, MessageTransformer, Protocol};
 use crate::PusherEvent;
 
 /// The main Sockudo client for connecting to Pusher-compatible servers.
@@ -61,6 +72,200 @@ pub struct SockudoClient {
     session_id: u32,
     /// Delta compression manager
     delta_manager: Option<Arc<RwLock<DeltaManager>>>,
+    /// Authorization endpoints set per channel by `subscribe_with_options`
+    auth_endpoint_overrides: Arc<RwLock<HashMap<String, String>>>,
+}
+
+/// Snapshot of the client's connection, subscriptions and statistics,
+/// returned by [`SockudoClient::status`].
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ClientStatus {
+    /// Connection state
+    pub state: ConnectionState,
+    /// Socket ID assigned by the server
+    pub socket_id: Option<String>,
+    /// Session ID of this client instance
+    pub session_id: u32,
+    /// Names of all subscribed channels
+    pub subscribed_channels: Vec<String>,
+    /// Traffic counters
+    pub connection_stats: ConnectionStats,
+    /// Delta compression statistics, if delta compression is configured
+    pub delta_stats: Option<DeltaStats>,
+    /// How long the current connection has been established
+    pub uptime: Option<std::time::Duration>,
+    /// Most recent connection or protocol error
+    pub last_error: Option<String>,
+}
+
+/// Client state for attaching to support requests, returned by
+/// [`SockudoClient::debug_dump`].
+///
+/// Serialize it with `serde_json::to_string_pretty`. The socket ID is
+/// masked to its last 4 characters.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DebugDump {
+    /// Library version
+    pub version: &'static str,
+    /// Connection state
+    pub state: ConnectionState,
+    /// Redacted socket ID
+    pub socket_id: Option<String>,
+    /// Number of channels registered with the client
+    pub channel_count: usize,
+    /// Names of the channels whose subscription has succeeded
+    pub subscribed_channels: Vec<String>,
+    /// Channels whose subscription is in flight or deferred
+    pub pending_channels: Vec<String>,
+    /// Traffic counters
+    pub connection_stats: ConnectionStats,
+    /// Delta compression statistics, if delta compression is configured
+    pub delta_stats: Option<DeltaStats>,
+    /// Most recent connection or protocol error
+    pub last_error: Option<String>,
+    /// Most recent reconnect attempts, oldest first
+    pub reconnect_history: Vec<ReconnectAttempt>,
+}
+
+/// Combined client metrics at one point in time, returned by
+/// [`SockudoClient::statistics_snapshot`].
+///
+/// Subtract two snapshots with [`diff`](Self::diff) to get rates.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StatisticsSnapshot {
+    /// When the snapshot was taken
+    pub timestamp: SystemTime,
+    /// Traffic counters
+    pub connection: ConnectionStats,
+    /// Delta compression statistics, if delta compression is configured
+    pub delta: Option<DeltaStats>,
+    /// Number of registered channels per type
+    pub channel_counts: HashMap<ChannelType, usize>,
+    /// Callback timing of the global dispatcher
+    pub dispatch: DispatchMetrics,
+    /// Most recent reconnect attempts, oldest first
+    pub recent_reconnects: Vec<ReconnectAttempt>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl StatisticsSnapshot {
+    /// Change in each counter since `previous`.
+    ///
+    /// Counters that went backwards (e.g. after a stats reset) count as 0.
+    pub fn diff(&self, previous: &Self) -> StatisticsDiff {
+        let delta_count = |f: fn(&DeltaStats) -> u64| {
+            let before = previous.delta.as_ref().map_or(0, f);
+            self.delta.as_ref().map_or(0, f).saturating_sub(before)
+        };
+        let channels = |snapshot: &Self| snapshot.channel_counts.values().sum::<usize>() as i64;
+
+        StatisticsDiff {
+            elapsed: self
+                .timestamp
+                .duration_since(previous.timestamp)
+                .unwrap_or_default(),
+            messages_sent: (self.connection.messages_sent)
+                .saturating_sub(previous.connection.messages_sent),
+            messages_received: (self.connection.messages_received)
+                .saturating_sub(previous.connection.messages_received),
+            bytes_sent: (self.connection.bytes_sent).saturating_sub(previous.connection.bytes_sent),
+            bytes_received: (self.connection.bytes_received)
+                .saturating_sub(previous.connection.bytes_received),
+            delta_messages: delta_count(|stats| stats.delta_messages),
+            full_messages: delta_count(|stats| stats.full_messages),
+            delta_errors: delta_count(|stats| stats.errors),
+            dispatches: (self.dispatch.total_dispatches)
+                .saturating_sub(previous.dispatch.total_dispatches),
+            callback_invocations: (self.dispatch.total_callback_invocations)
+                .saturating_sub(previous.dispatch.total_callback_invocations),
+            reconnect_attempts: self
+                .recent_reconnects
+                .iter()
+                .filter(|attempt| attempt.started_at > previous.timestamp)
+                .count(),
+            channel_change: channels(self) - channels(previous),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl std::fmt::Display for StatisticsSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "sent {} msgs ({} B), received {} msgs ({} B), {} channels, {} dispatches",
+            self.connection.messages_sent,
+            self.connection.bytes_sent,
+            self.connection.messages_received,
+            self.connection.bytes_received,
+            self.channel_counts.values().sum::<usize>(),
+            self.dispatch.total_dispatches
+        )?;
+        if let Some(ref delta) = self.delta {
+            write!(
+                f,
+                ", {} delta / {} full msgs",
+                delta.delta_messages, delta.full_messages
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Change between two [`StatisticsSnapshot`]s, returned by
+/// [`StatisticsSnapshot::diff`]
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct StatisticsDiff {
+    /// Time between the snapshots
+    pub elapsed: std::time::Duration,
+    pub messages_sent: u64,
+    pub messages_received: u64,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    pub delta_messages: u64,
+    pub full_messages: u64,
+    pub delta_errors: u64,
+    pub dispatches: u64,
+    pub callback_invocations: u64,
+    /// Reconnect attempts started between the snapshots
+    pub reconnect_attempts: usize,
+    /// Net number of channels added (negative if removed)
+    pub channel_change: i64,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl StatisticsDiff {
+    /// `count` spread over the elapsed time, per second
+    pub fn per_second(&self, count: u64) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            count as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl std::fmt::Display for StatisticsDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "over {:.1}s: sent {} msgs ({:.1}/s), received {} msgs ({:.1}/s), {} delta / {} full msgs, {} reconnects",
+            self.elapsed.as_secs_f64(),
+            self.messages_sent,
+            self.per_second(self.messages_sent),
+            self.messages_received,
+            self.per_second(self.messages_received),
+            self.delta_messages,
+            self.full_messages,
+            self.reconnect_attempts
+        )
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -80,12 +285,19 @@ impl SockudoClient {
         let config = Arc::new(config);
 
         // Create channels with callbacks
-        let mut channels = Channels::new();
+        let expected_channels = config.expected_channel_count.unwrap_or(0);
+        let mut channels = Channels::with_capacity(expected_channels);
 
         // Create delta manager if enabled
+        if let Some(ref delta_opts) = config.delta_compression {
+            crate::delta::check_available(&delta_opts.algorithms)?;
+        }
         let delta_manager = if let Some(delta_opts) = config.delta_compression.clone() {
             if delta_opts.enabled {
                 let dm = DeltaManager::new(delta_opts);
+                if let Some(ref entries) = config.delta_cache_warmup {
+                    dm.cache_warmup_base64(entries)?;
+                }
                 Some(Arc::new(RwLock::new(dm)))
             } else {
                 None
@@ -105,27 +317,40 @@ impl SockudoClient {
             app_key, session_id
         );
 
-        let connection = Arc::new(ConnectionManager::new(Config::from(options)));
+        let connection = Arc::new(ConnectionManager::new(Config::from(options.clone())));
+        if options.client_id.is_none() {
+            connection.set_client_id(&format!("{:x}", session_id));
+        }
 
-        // Set up send callback for channels
-        let connection_clone = connection.clone();
-        channels.set_send_callback(Arc::new(move |event_name, data, channel| {
-            let mut event = PusherEvent::new(event_name);
-            #[cfg(feature = "wasm")]
-            {
-                event.data = Some(data.clone());
-            }
-            #[cfg(not(feature = "wasm"))]
-            {
-                event.data = Some(data.to_string());
-            }
-            event.channel = channel.map(|s| s.to_string());
+        let global_emitter = EventDispatcher::with_capacity(expected_channels);
+        global_emitter.set_callback_timeout(config.callback_timeout);
+        global_emitter.set_max_callbacks(config.max_global_callbacks.unwrap_or(0));
 
-            match Protocol::encode_message(&event) {
-                Ok(msg) => connection_clone.send(&msg),
-                Err(_) => false,
-            }
+        // Set up raw and batch send callbacks and batch options for triggers
+        let connection_raw = connection.clone();
+        channels.set_raw_send_callback(Arc::new(move |frame| connection_raw.send(frame)));
+        let connection_batch = connection.clone();
+        channels.set_batch_send_callback(Arc::new(move |events| {
+            connection_batch.send_event_batch(events)
         }));
+        channels
+            .set_trigger_batch_options(config.trigger_batch_mode, config.max_trigger_batch_size);
+        channels.set_client_event_secret(config.client_event_secret.clone());
+        channels.set_max_subscriptions(config.max_subscriptions);
+        channels.set_event_name_validator(config.event_name_validator.clone());
+        channels.set_strict_channel_names(config.strict_channel_names);
+        channels.set_channel_prefix_map(config.channel_prefix_map.clone());
+        channels.set_channel_shared_secrets(config.channel_shared_secrets.clone());
+        channels.set_global_subscription_metadata(config.global_subscription_metadata.clone());
+        channels.set_event_history_size(config.channel_event_history_size);
+        channels.set_callback_timeout(config.callback_timeout);
+
+        // Set up send callbacks for channels
+        channels.set_send_callback(connection_send_fn(connection.clone(), None));
+        channels.set_bulk_send_callback(connection_send_fn(
+            connection.clone(),
+            Some(MessagePriority::Low),
+        ));
 
         // Set up send callback for delta manager
         if let Some(ref dm) = delta_manager {
@@ -142,48 +367,30 @@ impl SockudoClient {
                         event.data = Some(data.to_string());
                     }
 
-                    match Protocol::encode_message(&event) {
-                        Ok(msg) => connection_for_delta.send(&msg),
-                        Err(_) => false,
-                    }
+                    connection_for_delta.send_event_with_priority(event, MessagePriority::Normal)
                 }));
         }
 
         // Set up authorization callback for private/presence channels
         // Note: uniffi doesn't support async callbacks easily, so we use blocking
-        if !config.auth_endpoint.is_empty() {
-            let auth_endpoint = config.auth_endpoint.clone();
-            let auth_headers = config.auth_headers.clone();
-
-            channels.set_authorize_callback(Arc::new(move |channel_name, socket_id| {
-                use crate::auth::AuthClient;
-
-                let auth_client = AuthClient::new(
-                    Some(auth_endpoint.clone()),
-                    Some(auth_headers.clone()),
-                    None,
-                    None,
-                );
-
-                // Use block_in_place to allow blocking in async context
-                // Note: This is only called in non-WASM builds because WASM uses async subscribe
-                tokio::task::block_in_place(|| {
-                    tokio::runtime::Handle::current().block_on(async {
-                        auth_client.authorize_channel(channel_name, socket_id).await
-                    })
-                })
-            }));
-        }
+        let auth_endpoint_overrides = Arc::new(RwLock::new(HashMap::<String, String>::new()));
+        channels.set_authorize_callback(Arc::new(channel_authorizer(
+            &options,
+            auth_endpoint_overrides.clone(),
+        )));
 
-        Ok(Self {
+        let client = Self {
             key: app_key,
             config,
             channels: Arc::new(channels),
-            global_emitter: EventDispatcher::new(),
+            global_emitter,
             connection,
             session_id,
             delta_manager,
-        })
+            auth_endpoint_overrides,
+        };
+        client.bind_connection_handlers()?;
+        Ok(client)
     }
 
     /// Get the application key.
@@ -196,6 +403,11 @@ impl SockudoClient {
         self.session_id
     }
 
+    /// Get the ID sent as the `client_id` URL parameter (FFI version).
+    pub fn get_client_id(&self) -> String {
+        self.client_id()
+    }
+
     /// Get the current connection state.
     pub fn state(&self) -> ConnectionState {
         self.connection.state()
@@ -242,210 +454,137 @@ impl SockudoClient {
     }
 
     pub async fn connect(&self) -> Result<()> {
-        // Set up connection callbacks before connecting
-        let channels = self.channels.clone();
-        let global_emitter = self.global_emitter.clone();
-        let delta_manager = self.delta_manager.clone();
-
-        // Bind to all connection events globally and route them
-        let channels_for_events = self.channels.clone();
-        let global_emitter_for_events = self.global_emitter.clone();
-        let delta_manager_for_events = self.delta_manager.clone();
-
-        self.connection.bind_global(move |event| {
-            // Debug: log all events
-            debug!(
-                "Received event: '{}' on channel {:?}",
-                event.event, event.channel
-            );
+        self.connection
+            .connect()
+            .await
+            .map_err(|e| self.error_context(e))?;
 
-            // Handle delta compression protocol events first
-            if let Some(ref dm) = delta_manager_for_events {
-                match event.event.as_str() {
-                    "pusher:delta_compression_enabled" => {
-                        if let Some(ref data) = event.data {
-                            #[cfg(feature = "wasm")]
-                            {
-                                dm.write().handle_enabled(data);
-                            }
-                            #[cfg(not(feature = "wasm"))]
-                            {
-                                if let Ok(value) = serde_json::from_str::<serde_json::Value>(data) {
-                                    dm.write().handle_enabled(&value);
-                                    debug!("Delta compression enabled by server");
-                                }
-                            }
-                        }
-                        return;
-                    }
-                    "pusher:delta_cache_sync" => {
-                        if let (Some(ref channel), Some(ref data)) = (&event.channel, &event.data) {
-                            #[cfg(feature = "wasm")]
-                            let sync_result: std::result::Result<crate::delta::CacheSyncData, _> = serde_json::from_value(data.clone());
-                            #[cfg(not(feature = "wasm"))]
-                            let sync_result: std::result::Result<crate::delta::CacheSyncData, _> = serde_json::from_str(data);
-
-                            if let Ok(sync_data) = sync_result {
-                                dm.write().handle_cache_sync(channel, sync_data);
-                                debug!("Delta cache sync for channel: {}", channel);
-                            }
-                        }
-                        return;
-                    }
-                    "pusher:delta" => {
-                        if let Some(ref channel) = event.channel {
-                            if let Some(ref data) = event.data {
-                                #[cfg(feature = "wasm")]
-                                let delta_result: std::result::Result<crate::delta::DeltaMessage, _> = serde_json::from_value(data.clone());
-                                #[cfg(not(feature = "wasm"))]
-                                let delta_result: std::result::Result<crate::delta::DeltaMessage, _> = serde_json::from_str(data);
-
-                                if let Ok(delta_msg) = delta_result {
-                                    match dm.read().handle_delta(channel, delta_msg) {
-                                        Ok(decoded_event) => {
-                                            // Route the decoded event to the channel
-                                            if let Some(ch) = channels_for_events.find(channel) {
-                                                ch.handle_event(&decoded_event);
-                                            }
-                                            // Also emit globally
-                                            global_emitter_for_events.emit(&decoded_event);
-                                            debug!("Delta decoded and routed for channel: {}", channel);
-                                        }
-                                        Err(e) => {
-                                            warn!("Failed to handle delta: {}", e);
-                                            dm.read().request_resync(channel);
-                                        }
-                                    }
-                                    return;
-                                }
-                            }
-                        }
-                    }
-                    _ => {}
-                }
-            }
+        Ok(())
+    }
 
-            // Check if this is an internal event (like pusher-js does)
-            let is_internal = event.event.starts_with("pusher_internal:");
+    /// Connect with a token appended to the WebSocket URL.
+    ///
+    /// The token is URL-encoded under `token_query_param` and cleared from
+    /// memory once the connection attempt has been made.
+    pub async fn connect_with_token(&self, token: String) -> Result<()> {
+        self.connection.set_connection_token(token);
+        self.connection
+            .connect()
+            .await
+            .map_err(|e| self.error_context(e))
+    }
 
-            // Route to channel if specified
-            if let Some(ref channel_name) = event.channel {
-                debug!(
-                    "Routing event '{}' to channel '{}'",
-                    event.event, channel_name
-                );
+    /// Connect to `url` instead of the configured URL, e.g. one returned by
+    /// service discovery.
+    ///
+    /// The URL must use `ws://` or `wss://` and contain the app key. It is
+    /// used for this connection only; later connects use the configured URL
+    /// or `url_provider` again.
+    pub async fn connect_via(&self, url: &str) -> Result<()> {
+        self.connection
+            .set_override_url(url)
+            .map_err(|e| self.error_context(e))?;
+        self.connection
+            .connect()
+            .await
+            .map_err(|e| self.error_context(e))
+    }
 
-                // Route to channel - dispatchers are now shared so this works correctly
-                if let Some(channel) = channels_for_events.find(channel_name) {
-                    channel.handle_event(event);
-                    debug!("Event routed to channel '{}'", channel_name);
-                } else {
-                    warn!(
-                        "Channel '{}' not found for event '{}'",
-                        channel_name, event.event
-                    );
-                }
-            }
+    /// Connect, retrying with exponential backoff while the server is unavailable.
+    ///
+    /// Each attempt waits up to `activity_timeout` for the connection to be
+    /// established. Failed attempts are retried after `initial_reconnect_delay`,
+    /// doubling up to `max_reconnect_delay`. A `sockudo:reconnect_attempt`
+    /// event with `{attempt, delay_ms}` is emitted globally before each attempt.
+    pub async fn connect_until(&self, max_attempts: u32) -> Result<()> {
+        use tokio::time::{sleep, Duration, Instant};
+
+        let max_attempts = max_attempts.max(1);
+        let mut delay = Duration::ZERO;
+
+        for attempt in 1..=max_attempts {
+            let attempt_event = PusherEvent::new("sockudo:reconnect_attempt").with_json_data(
+                serde_json::json!({ "attempt": attempt, "delay_ms": delay.as_millis() as u64 }),
+            );
+            self.global_emitter.emit(&attempt_event);
 
-            // Emit globally (except internal events, like pusher-js does)
-            if !is_internal {
-                global_emitter_for_events.emit(event);
+            if attempt > 1 {
+                sleep(delay).await;
             }
-        });
-
-        let connection = self.connection.clone();
-        let config_for_resubscribe = self.config.clone();
-        self.connection.bind("connected", move |_event| {
-            info!("Connected to Pusher");
-
-            // Enable delta compression if configured
-            if let Some(ref dm) = delta_manager {
-                dm.read().enable();
+            self.connection.begin_reconnect_attempt(attempt, delay);
+            let started = if attempt == 1 {
+                self.connect().await
+            } else {
+                self.connection.disconnect().await;
+                self.connection.connect().await
+            };
+            if let Err(e) = started {
+                self.connection
+                    .finish_reconnect_attempt(Some(e.to_string()));
+                return Err(self.error_context(e));
             }
 
-            // Resubscribe to all channels
-            if let Some(socket_id) = connection.socket_id() {
-                let all_channels = channels.all();
-                info!(
-                    "Resubscribing {} channels after connection established",
-                    all_channels.len()
-                );
-
-                #[cfg(not(target_arch = "wasm32"))]
-                {
-                    // Native: Use synchronous subscribe with callback-based auth
-                    for channel in all_channels {
-                        info!(
-                            "Channel '{}' state: subscribed={}, pending={}",
-                            channel.name(),
-                            channel.is_subscribed(),
-                            channel.is_subscription_pending()
-                        );
-
-                        if !channel.is_subscribed() && !channel.is_subscription_pending() {
-                            info!("Attempting to subscribe to channel: {}", channel.name());
-                            if let Err(e) = channel.subscribe(&socket_id) {
-                                warn!("Failed to resubscribe to channel {}: {}", channel.name(), e);
-                            } else {
-                                info!(
-                                    "Successfully sent subscription for channel: {}",
-                                    channel.name()
-                                );
-                            }
-                        } else if channel.is_subscription_pending() {
-                            info!(
-                                "Channel {} already has subscription pending",
-                                channel.name()
-                            );
-                        } else {
-                            info!("Channel {} already subscribed", channel.name());
-                        }
+            let deadline = Instant::now() + self.config.activity_timeout;
+            let state = loop {
+                match self.connection.state() {
+                    ConnectionState::Connected => {
+                        self.connection.finish_reconnect_attempt(None);
+                        return Ok(());
                     }
-                }
-
-                #[cfg(target_arch = "wasm32")]
-                {
-                    // WASM: Spawn async tasks for subscription with async auth
-                    let config_clone = config_for_resubscribe.clone();
-                    for channel in all_channels {
-                        if !channel.is_subscribed() && !channel.is_subscription_pending() {
-                            let channel = channel.clone();
-                            let socket_id = socket_id.clone();
-                            let auth_endpoint = config_clone.auth_endpoint.clone();
-
-                            wasm_bindgen_futures::spawn_local(async move {
-                                let auth_ep = if !auth_endpoint.is_empty() {
-                                    Some(auth_endpoint.as_str())
-                                } else {
-                                    None
-                                };
-
-                                if let Err(e) = channel.subscribe_async(&socket_id, auth_ep).await {
-                                    warn!(
-                                        "Failed to resubscribe to channel {}: {}",
-                                        channel.name(),
-                                        e
-                                    );
-                                }
-                            });
-                        }
+                    ConnectionState::Connecting if Instant::now() < deadline => {
+                        sleep(Duration::from_millis(50)).await;
                     }
+                    state => break state,
                 }
+            };
+
+            warn!("Connection attempt {} of {} failed", attempt, max_attempts);
+            let error = self
+                .connection
+                .last_error()
+                .unwrap_or_else(|| format!("connection ended in state {}", state));
+            self.connection.finish_reconnect_attempt(Some(error));
+            delay = if attempt == 1 {
+                self.config.initial_reconnect_delay
             } else {
-                warn!("No socket_id available for resubscription");
-            }
-        });
+                (delay * 2).min(self.config.max_reconnect_delay)
+            };
+        }
+
+        Err(self.error_context(SockudoError::max_reconnect_attempts_exceeded(max_attempts)))
+    }
 
-        // Now connect
-        self.connection.connect().await?;
+    /// Pause the connection, e.g. when a mobile app goes to the background.
+    ///
+    /// The socket stays open but is no longer read from and pings stop, so
+    /// no events are delivered until `resume` is called.
+    pub async fn pause(&self) -> Result<()> {
+        info!("Pausing connection");
+        self.connection.pause().await
+    }
 
-        Ok(())
+    /// Resume a paused connection.
+    ///
+    /// If the server closed the socket while paused (e.g. idle timeout), a
+    /// new connection is made and channels are resubscribed.
+    pub async fn resume(&self) -> Result<()> {
+        info!("Resuming connection");
+        if self.connection.resume().await? {
+            return Ok(());
+        }
+
+        info!("Paused connection was lost, reconnecting");
+        self.channels.reset_subscriptions();
+        self.connection.connect().await
     }
 
     /// Disconnect from the server.
     pub async fn disconnect(&self) {
         info!("Disconnecting from Pusher");
 
+        #[cfg(any(test, feature = "offline-mode"))]
+        self.disable_offline_mode();
+
         // Call disconnect - no lock held across await since disconnect() uses &self
         self.connection.disconnect().await;
         self.channels.disconnect();
@@ -460,9 +599,9 @@ impl SockudoClient {
     /// # let client = SockudoClient::new(PusherOptions::new("key").into()).unwrap();
     /// client.bind("my-event", |event| {
     ///     println!("Received: {:?}", event);
-    /// });
+    /// })?;
     /// ```
-    pub fn bind(&self, event_name: String, callback: Box<dyn EventCallback>) {
+    pub fn bind(&self, event_name: String, callback: Box<dyn EventCallback>) -> Result<()> {
         let callback = Arc::new(callback);
         self.global_emitter.bind(event_name, move |event| {
             let ffi_event = crate::UniffiPusherEvent {
@@ -472,7 +611,8 @@ impl SockudoClient {
                 user_id: event.user_id.clone(),
             };
             callback.on_event(ffi_event);
-        });
+        })?;
+        Ok(())
     }
 
     /// Bind a callback to all events globally (FFI version).
@@ -521,7 +661,7 @@ impl SockudoClient {
     /// });
     /// ```
     pub fn subscribe(&self, channel_name: &str) -> Result<Arc<Channel>> {
-        self.subscribe_with_filter(channel_name, None)
+        self.subscribe_with_options(channel_name, ChannelSubscribeOptions::default())
     }
 
     /// Subscribe to a channel with a tags filter.
@@ -542,40 +682,142 @@ impl SockudoClient {
         channel_name: &str,
         filter: Option<FilterOp>,
     ) -> Result<Arc<Channel>> {
-        // Validate channel name
-        if channel_name.starts_with('#') {
-            return Err(SockudoError::invalid_channel(format!(
-                "Channel names cannot start with '#': {}",
-                channel_name
-            )));
-        }
+        self.subscribe_with_options(
+            channel_name,
+            ChannelSubscribeOptions {
+                filter,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Subscribe to a channel, sending `metadata` with the subscribe request.
+    ///
+    /// The metadata is merged over `global_subscription_metadata` (keys in
+    /// `metadata` win) and is sent again on every resubscription.
+    pub fn subscribe_with_metadata(
+        &self,
+        channel_name: &str,
+        metadata: HashMap<String, String>,
+    ) -> Result<Arc<Channel>> {
+        self.subscribe_with_options(
+            channel_name,
+            ChannelSubscribeOptions {
+                metadata,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Subscribe to a channel, deferring `pusher:subscribe` until connected.
+    ///
+    /// The channel is registered immediately so callbacks can be bound before
+    /// the connection is established. If the client is not connected yet, the
+    /// subscription is queued and sent once the connection is ready.
+    pub fn subscribe_lazy(&self, channel_name: &str) -> Result<Arc<Channel>> {
+        self.subscribe_lazy_with_filter(channel_name, None)
+    }
+
+    /// Subscribe lazily to a channel with a tags filter.
+    ///
+    /// See `subscribe_lazy` and `subscribe_with_filter`.
+    pub fn subscribe_lazy_with_filter(
+        &self,
+        channel_name: &str,
+        filter: Option<FilterOp>,
+    ) -> Result<Arc<Channel>> {
+        validate_channel_name(channel_name).map_err(|e| self.error_context(e))?;
 
-        // Get or create channel
-        let channel = self.channels.add(channel_name)?;
+        let channel = self
+            .channels
+            .add(channel_name)
+            .map_err(|e| self.error_context(e))?;
 
-        // Set filter if provided
         if let Some(f) = filter {
             channel.set_tags_filter(Some(f));
         }
 
-        // Subscribe if connected
-        if let Some(socket_id) = self.socket_id() {
-            channel.subscribe(&socket_id)?;
+        match self.socket_id() {
+            Some(socket_id) if self.is_connected() => {
+                channel
+                    .subscribe(&socket_id)
+                    .map_err(|e| self.error_context(e))?;
+                debug!("Subscribed to channel: {}", channel_name);
+            }
+            _ => {
+                self.channels.queue_pending_subscription(channel_name);
+                debug!("Deferred subscription to channel: {}", channel_name);
+            }
         }
 
-        debug!("Subscribed to channel: {}", channel_name);
         Ok(channel)
     }
 
+    /// Names of channels whose subscription is waiting for the connection.
+    pub fn pending_subscriptions(&self) -> Vec<String> {
+        self.channels.pending_subscriptions()
+    }
+
     /// Unsubscribe from a channel.
     pub fn unsubscribe(&self, channel_name: &str) {
         if let Some(channel) = self.channels.find(channel_name) {
             channel.unsubscribe();
         }
         self.channels.remove(channel_name);
+        self.auth_endpoint_overrides.write().remove(channel_name);
         debug!("Unsubscribed from channel: {}", channel_name);
     }
 
+    /// Move a subscribed channel to a new name.
+    ///
+    /// Unsubscribes `old_name` and subscribes `new_name` with the same
+    /// channel object, so bound callbacks, callback IDs, the tags filter and
+    /// subscription metadata all carry over, and [`Channel::name`] returns
+    /// `new_name` from then on. Fails with
+    /// `SockudoError::ChannelAlreadyExists` if `new_name` is already in use,
+    /// and with `SockudoError::InvalidChannel` for presence and encrypted
+    /// channels, whose member list and keys are tied to their name.
+    pub fn migrate_channel(&self, old_name: &str, new_name: &str) -> Result<()> {
+        validate_channel_name(new_name).map_err(|e| self.error_context(e))?;
+
+        let channel = self
+            .channels
+            .migrate(old_name, new_name)
+            .map_err(|e| self.error_context(e))?;
+        {
+            let mut overrides = self.auth_endpoint_overrides.write();
+            if let Some(endpoint) = overrides.remove(old_name) {
+                overrides.insert(new_name.to_string(), endpoint);
+            }
+        }
+
+        match self.socket_id() {
+            Some(socket_id) if self.is_connected() => {
+                channel
+                    .subscribe(&socket_id)
+                    .map_err(|e| self.error_context(e))?;
+            }
+            _ => self.channels.queue_pending_subscription(new_name),
+        }
+
+        debug!("Migrated channel {} to {}", old_name, new_name);
+        Ok(())
+    }
+
+    /// Unsubscribe from every channel.
+    pub fn unsubscribe_all(&self) {
+        for channel in self.channels.all() {
+            self.unsubscribe(&channel.current_name());
+        }
+    }
+
+    /// Unsubscribe from every channel whose name starts with `prefix`.
+    pub fn unsubscribe_by_prefix(&self, prefix: &str) {
+        for channel in self.channels.find_by_prefix(prefix) {
+            self.unsubscribe(&channel.current_name());
+        }
+    }
+
     /// Get a channel by name.
     pub fn channel(&self, name: &str) -> Option<Arc<Channel>> {
         self.channels.find(name)
@@ -597,28 +839,39 @@ impl SockudoClient {
     /// });
     /// ```
     pub fn subscribe_presence(&self, channel_name: &str) -> Result<Arc<PresenceChannel>> {
+        validate_channel_name(channel_name).map_err(|e| self.error_context(e))?;
+
         // Validate it's a presence channel
         if !channel_name.starts_with("presence-") {
-            return Err(SockudoError::invalid_channel(format!(
+            return Err(self.error_context(SockudoError::invalid_channel(format!(
                 "Channel name must start with 'presence-': {}",
                 channel_name
-            )));
+            ))));
         }
 
         // Create/get the channel
-        let _ = self.channels.add(channel_name)?;
+        let _ = self
+            .channels
+            .add(channel_name)
+            .map_err(|e| self.error_context(e))?;
 
         // Get the presence channel
-        let presence_channel = self.channels.find_presence(channel_name).ok_or_else(|| {
-            SockudoError::invalid_channel(format!(
-                "Failed to get presence channel: {}",
-                channel_name
-            ))
-        })?;
+        let presence_channel = self
+            .channels
+            .find_presence(channel_name)
+            .ok_or_else(|| {
+                SockudoError::invalid_channel(format!(
+                    "Failed to get presence channel: {}",
+                    channel_name
+                ))
+            })
+            .map_err(|e| self.error_context(e))?;
 
         // Subscribe if connected
         if let Some(socket_id) = self.socket_id() {
-            presence_channel.subscribe(&socket_id)?;
+            presence_channel
+                .subscribe(&socket_id)
+                .map_err(|e| self.error_context(e))?;
         }
 
         debug!("Subscribed to presence channel: {}", channel_name);
@@ -630,6 +883,11 @@ impl SockudoClient {
         self.channels.all()
     }
 
+    /// Get the names of all subscribed channels (FFI version).
+    pub fn get_channel_names(&self) -> Vec<String> {
+        self.channel_names()
+    }
+
     /// Send a custom event over the connection (FFI version).
     ///
     /// This is used for client events on private/presence channels.
@@ -648,6 +906,15 @@ impl SockudoClient {
         }
     }
 
+    /// Get the most recent reconnect attempts, oldest first (FFI version).
+    pub fn get_reconnect_history(&self) -> Vec<crate::UniffiReconnectAttempt> {
+        self.connection
+            .reconnect_history()
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
     /// Get delta compression statistics.
     pub fn get_delta_stats(&self) -> Option<crate::UniffiDeltaStats> {
         self.delta_manager
@@ -662,6 +929,35 @@ impl SockudoClient {
         }
     }
 
+    /// Discard the delta base message for a channel and request a resync.
+    ///
+    /// Sends `pusher:delta_resync_request` to the server, which must support
+    /// it, and emits `sockudo:delta_resync` on the channel.
+    pub fn force_delta_resync(&self, channel_name: &str) -> Result<()> {
+        let dm = self
+            .delta_manager
+            .as_ref()
+            .ok_or_else(|| SockudoError::invalid_state("Delta compression is not enabled"))?;
+        let channel = self.channels.find(channel_name).ok_or_else(|| {
+            SockudoError::invalid_channel(format!("Not subscribed to channel: {}", channel_name))
+        })?;
+
+        dm.read().force_resync(channel_name);
+
+        let mut event = PusherEvent::new("sockudo:delta_resync").with_channel(channel_name);
+        #[cfg(feature = "wasm")]
+        {
+            event.data = Some(serde_json::json!({ "channel": channel_name }));
+        }
+        #[cfg(not(feature = "wasm"))]
+        {
+            event.data = Some(serde_json::json!({ "channel": channel_name }).to_string());
+        }
+        channel.handle_event(&event);
+
+        Ok(())
+    }
+
     /// Check if delta compression is enabled and active.
     pub fn is_delta_compression_enabled(&self) -> bool {
         self.delta_manager
@@ -669,399 +965,2935 @@ impl SockudoClient {
             .map(|dm| dm.read().is_enabled())
             .unwrap_or(false)
     }
-}
 
-// Rust-native methods that accept closures (always available)
-#[cfg(not(target_arch = "wasm32"))]
-impl SockudoClient {
-    /// Bind a callback to all events globally.
-    ///
-    /// This is the primary method for Rust code to bind global event handlers.
-    /// It accepts closures for convenient event handling.
+    /// Get the delta algorithm the server chose, if it reported one.
+    pub fn active_delta_algorithm(&self) -> Option<DeltaAlgorithm> {
+        self.delta_manager
+            .as_ref()
+            .and_then(|dm| dm.read().active_algorithm())
+    }
+
+    /// Decode deltas on `channel_name` with `algorithm`, whatever algorithm
+    /// the server names in each delta message.
     ///
-    /// # Example
+    /// Has no effect unless delta compression is enabled.
+    pub fn set_channel_delta_algorithm(&self, channel_name: &str, algorithm: DeltaAlgorithm) {
+        if let Some(ref dm) = self.delta_manager {
+            dm.read().set_channel_algorithm(channel_name, algorithm);
+        }
+    }
+
+    /// Receive connection state changes, connection errors and assigned
+    /// socket IDs, replacing any callback set before.
+    pub fn set_connection_callback(&self, callback: Box<dyn ConnectionCallback>) {
+        self.connection
+            .set_connection_callback(Some(Arc::from(callback)));
+    }
+
+    /// Receive the channel, algorithm and sizes of each decoded delta
+    /// message, e.g. for bandwidth accounting.
     ///
-    /// ```ignore
-    /// # use sockudo_client::{SockudoClient, PusherOptions};
-    /// # let client = SockudoClient::new(PusherOptions::new("key").into()).unwrap();
-    /// client.bind_global(|event| {
-    ///     println!("Event: {} on {:?}", event.event, event.channel);
-    /// });
-    /// ```
-    #[cfg(feature = "uniffi")]
-    pub fn bind_global(&self, callback: impl Fn(&PusherEvent) + Send + Sync + 'static) -> u64 {
-        self.global_emitter.bind_global(callback)
+    /// Has no effect unless delta compression is enabled.
+    pub fn set_delta_decoded_callback(&self, callback: Box<dyn DeltaDecodedCallback>) {
+        if let Some(ref dm) = self.delta_manager {
+            let callback = Arc::new(callback);
+            dm.write().set_on_delta_decoded(Some(Arc::new(
+                move |channel, algorithm, delta_bytes, decoded_bytes| {
+                    callback.on_delta_decoded(
+                        channel.to_string(),
+                        algorithm.to_string(),
+                        delta_bytes as u64,
+                        decoded_bytes as u64,
+                    );
+                },
+            )));
+        }
     }
 
-    /// Bind a callback to all events globally (non-uniffi version).
-    #[cfg(not(feature = "uniffi"))]
-    pub fn bind_global(&self, callback: impl Fn(&PusherEvent) + Send + Sync + 'static) -> u64 {
-        self.global_emitter.bind_global(callback)
+    /// Receive the channel and size of each full message stored as a
+    /// delta base.
+    ///
+    /// Has no effect unless delta compression is enabled.
+    pub fn set_full_message_callback(&self, callback: Box<dyn FullMessageCallback>) {
+        if let Some(ref dm) = self.delta_manager {
+            let callback = Arc::new(callback);
+            dm.write()
+                .set_on_full_message(Some(Arc::new(move |channel, bytes| {
+                    callback.on_full_message(channel.to_string(), bytes as u64);
+                })));
+        }
     }
 }
 
-// Private methods (not exported via uniffi)
+// Rust-native methods that accept closures (always available)
 #[cfg(not(target_arch = "wasm32"))]
-#[cfg(feature = "uniffi")]
 impl SockudoClient {
-    /// Handle an incoming message from the connection.
-    fn handle_message(&self, event: &PusherEvent) {
-        let event_name = &event.event;
+    /// ID sent as the `client_id` URL parameter on every connect:
+    /// `SockudoOptions::client_id` if set, otherwise the session ID in hex
+    pub fn client_id(&self) -> String {
+        self.connection.client_id().unwrap_or_default()
+    }
 
-        // Handle delta compression protocol events
-        if let Some(ref dm) = self.delta_manager {
-            match event_name.as_str() {
-                "pusher:delta_compression_enabled" => {
-                    if let Some(ref data) = event.data {
-                        #[cfg(feature = "wasm")]
-                        {
-                            dm.write().handle_enabled(data);
-                        }
-                        #[cfg(not(feature = "wasm"))]
-                        {
-                            if let Ok(value) = serde_json::from_str::<serde_json::Value>(data) {
-                                dm.write().handle_enabled(&value);
+    /// Route connection events to channels and global listeners, and
+    /// resubscribe on every `connected`. Called once when the client is
+    /// built, so repeated connects don't stack handlers.
+    fn bind_connection_handlers(&self) -> Result<()> {
+        let channels = self.channels.clone();
+        let delta_manager = self.delta_manager.clone();
+
+        // Bind to all connection events globally and route them
+        let channels_for_events = self.channels.clone();
+        let global_emitter_for_events = self.global_emitter.clone();
+        let delta_manager_for_events = self.delta_manager.clone();
+        let skip_sanitization = self.config.skip_sanitization;
+
+        self.connection.bind_global(move |event| {
+            route_event(
+                &channels_for_events,
+                &global_emitter_for_events,
+                delta_manager_for_events.as_ref(),
+                skip_sanitization,
+                event,
+            );
+        });
+
+        let connection = self.connection.clone();
+        let config_for_resubscribe = self.config.clone();
+        self.connection.bind("connected", move |_event| {
+            info!("Connected to Pusher");
+
+            // Enable delta compression if configured
+            if let Some(ref dm) = delta_manager {
+                dm.read().enable();
+            }
+
+            // Resubscribe to all channels, deferred lazy subscriptions first
+            if let Some(socket_id) = connection.socket_id() {
+                #[cfg(not(target_arch = "wasm32"))]
+                channels.resubscribe_all_after_connect(&socket_id);
+
+                #[cfg(target_arch = "wasm32")]
+                {
+                    // WASM: Spawn async tasks for subscription with async auth
+                    let config_clone = config_for_resubscribe.clone();
+                    for channel in channels.drain_pending() {
+                        let socket_id = socket_id.clone();
+                        let auth_endpoint = config_clone.auth_endpoint.clone();
+                        let signature_mode = config_clone.auth_signature_mode.clone();
+
+                        wasm_bindgen_futures::spawn_local(async move {
+                            let auth_ep = if !auth_endpoint.is_empty() {
+                                Some(auth_endpoint.as_str())
+                            } else {
+                                None
+                            };
+
+                            if let Err(e) = channel
+                                .subscribe_async(&socket_id, auth_ep, &signature_mode)
+                                .await
+                            {
+                                warn!(
+                                    "Failed to resubscribe to channel {}: {}",
+                                    channel.current_name(),
+                                    e
+                                );
                             }
-                        }
+                        });
                     }
-                    return;
                 }
-                "pusher:delta_cache_sync" => {
-                    if let (Some(ref channel), Some(ref data)) = (&event.channel, &event.data) {
-                        #[cfg(feature = "wasm")]
-                        let sync_result = serde_json::from_value(data.clone());
-                        #[cfg(not(feature = "wasm"))]
-                        let sync_result = serde_json::from_str(data);
+            } else {
+                warn!("No socket_id available for resubscription");
+            }
+        })?;
 
-                        if let Ok(sync_data) = sync_result {
-                            dm.write().handle_cache_sync(channel, sync_data);
-                        }
-                    }
+        Ok(())
+    }
+
+    /// Add `middleware` to the chain every event passes through, returning
+    /// the client.
+    ///
+    /// ```no_run
+    /// # use sockudo_client::{LoggingMiddleware, RateLimitMiddleware, SockudoClient};
+    /// # fn example(client: SockudoClient) -> SockudoClient {
+    /// client
+    ///     .with_middleware(Box::new(LoggingMiddleware))
+    ///     .with_middleware(Box::new(RateLimitMiddleware::new(10)))
+    /// # }
+    /// ```
+    pub fn with_middleware(self, middleware: Box<dyn ClientMiddleware>) -> Self {
+        self.add_middleware(middleware);
+        self
+    }
+
+    /// Add `middleware` after those already registered.
+    ///
+    /// Outgoing events pass each middleware's `before_send` and incoming
+    /// events each `after_receive`, in registration order. The first
+    /// middleware returning `false` drops the event.
+    pub fn add_middleware(&self, middleware: Box<dyn ClientMiddleware>) {
+        self.connection.add_middleware(Arc::from(middleware));
+    }
+
+    /// Add `transformer` after the transformers given in
+    /// `SockudoOptions::message_transformers` and those added before.
+    ///
+    /// Applies to events sent and received from now on; outgoing events
+    /// are transformed after the middleware chain, incoming ones before it.
+    pub fn add_message_transformer_at_runtime(&self, transformer: Box<dyn MessageTransformer>) {
+        self.connection
+            .add_message_transformer(Arc::from(transformer));
+    }
+
+    /// Watch a channel for state changes, member count changes and
+    /// events.
+    ///
+    /// The channel must have been subscribed; the watcher can be created
+    /// before the subscription succeeds.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use sockudo_client::channels::ChannelState;
+    /// # async fn example(client: &sockudo_client::SockudoClient) -> sockudo_client::Result<()> {
+    /// client.subscribe("prices")?;
+    /// let mut watcher = client.watch_channel("prices")?;
+    /// watcher
+    ///     .wait_for_state(ChannelState::Subscribed, Duration::from_secs(10))
+    ///     .await?;
+    /// let event = watcher.next_event(Duration::from_secs(30)).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn watch_channel(&self, name: &str) -> Result<ChannelWatcher> {
+        let channel = self
+            .channels
+            .find(name)
+            .ok_or_else(|| SockudoError::invalid_channel(format!("Unknown channel: {}", name)))?;
+        Ok(ChannelWatcher::new(
+            channel,
+            self.channels.find_presence(name),
+        ))
+    }
+
+    /// Subscribe to a channel with per-subscription settings.
+    ///
+    /// Every option in `opts` is applied to the channel before the
+    /// `pusher:subscribe` request is sent, and applies again on
+    /// resubscription. Options left unset keep the channel's current
+    /// settings. Like `subscribe`, the request waits for the connection
+    /// when the client is not connected.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use sockudo_client::{ChannelSubscribeOptions, ExponentialBackoff, FilterOp};
+    /// # fn example(client: &sockudo_client::SockudoClient) {
+    /// let opts = ChannelSubscribeOptions::default()
+    ///     .filter(FilterOp::eq("type", "goal"))
+    ///     .metadata("device_id", "abc")
+    ///     .reconnect_strategy(ExponentialBackoff::default())
+    ///     .auth_endpoint("https://example.com/sports/auth");
+    /// let channel = client.subscribe_with_options("private-sports", opts).unwrap();
+    /// # }
+    /// ```
+    pub fn subscribe_with_options(
+        &self,
+        channel_name: &str,
+        opts: ChannelSubscribeOptions,
+    ) -> Result<Arc<Channel>> {
+        validate_channel_name(channel_name).map_err(|e| self.error_context(e))?;
+
+        let channel = self
+            .channels
+            .add(channel_name)
+            .map_err(|e| self.error_context(e))?;
+
+        if let Some(filter) = opts.filter {
+            channel.set_tags_filter(Some(filter));
+        }
+        if !opts.metadata.is_empty() {
+            let mut merged = self.channels.global_subscription_metadata().clone();
+            merged.extend(opts.metadata);
+            channel.set_request_metadata(merged);
+        }
+        if let Some(strategy) = opts.reconnect_strategy {
+            channel.set_reconnect_strategy(strategy);
+        }
+        if let Some(limit) = opts.max_listeners {
+            channel.set_max_listeners(limit);
+        }
+        if let Some(endpoint) = opts.auth_endpoint_override {
+            self.auth_endpoint_overrides
+                .write()
+                .insert(channel_name.to_string(), endpoint);
+        }
+        if let Some(timeout) = opts.subscription_timeout {
+            channel.set_resubscription_timeout(timeout);
+        }
+
+        match self.socket_id() {
+            Some(socket_id) if self.is_connected() => {
+                let subscribed = if opts.subscription_timeout.is_some() {
+                    self.channels.subscribe_bounded(channel.clone(), &socket_id)
+                } else {
+                    channel.subscribe(&socket_id)
+                };
+                subscribed.map_err(|e| self.error_context(e))?;
+                debug!("Subscribed to channel: {}", channel_name);
+            }
+            _ => {
+                self.channels.queue_pending_subscription(channel_name);
+                debug!("Deferred subscription to channel: {}", channel_name);
+            }
+        }
+
+        Ok(channel)
+    }
+
+    /// Total number of events emitted by the client.
+    #[cfg(feature = "event-stats")]
+    pub fn event_count(&self) -> u64 {
+        self.global_emitter.total_event_count()
+    }
+
+    /// Number of events emitted by the client, per event name.
+    #[cfg(feature = "event-stats")]
+    pub fn event_stats(&self) -> std::collections::HashMap<String, u64> {
+        self.global_emitter.event_stats()
+    }
+
+    /// Reset the client's event counters.
+    #[cfg(feature = "event-stats")]
+    pub fn reset_event_stats(&self) {
+        self.global_emitter.reset_event_stats();
+    }
+
+    /// Callback timing collected by the client's global dispatcher.
+    #[cfg(feature = "dispatch-metrics")]
+    pub fn dispatch_metrics(&self) -> crate::events::DispatchMetrics {
+        self.global_emitter.metrics()
+    }
+
+    /// Get the most recent reconnect attempts, oldest first.
+    pub fn reconnect_history(&self) -> Vec<ReconnectAttempt> {
+        self.connection.reconnect_history()
+    }
+
+    /// Take over the WebSocket as a byte stream, consuming the client.
+    ///
+    /// Channels are dropped and no further events are dispatched; received
+    /// text frames are read from the returned `TransportIO` instead.
+    #[cfg(feature = "io-adapter")]
+    pub async fn into_io(self) -> Result<crate::transports::TransportIO> {
+        let transport = self.connection.take_transport().await?;
+        self.channels.disconnect();
+        Ok(crate::transports::TransportIO::from_transport(transport))
+    }
+
+    /// Attach a debug dump to `error` when `dump_on_error` is set.
+    fn error_context(&self, error: SockudoError) -> SockudoError {
+        if self.config.dump_on_error {
+            error.context(&self.debug_dump())
+        } else {
+            error
+        }
+    }
+
+    /// Collect the client state for a support request.
+    ///
+    /// ```no_run
+    /// # fn example(client: &sockudo_client::SockudoClient) {
+    /// println!("{}", serde_json::to_string_pretty(&client.debug_dump()).unwrap());
+    /// # }
+    /// ```
+    pub fn debug_dump(&self) -> DebugDump {
+        let subscribed_channels = self
+            .channels
+            .all()
+            .iter()
+            .filter(|channel| channel.is_subscribed())
+            .map(|channel| channel.current_name())
+            .collect();
+        DebugDump {
+            version: crate::protocol::CLIENT_VERSION,
+            state: self.connection.state(),
+            socket_id: self
+                .connection
+                .socket_id()
+                .map(|id| crate::options::redact_socket_id(&id)),
+            channel_count: self.channels.len(),
+            subscribed_channels,
+            pending_channels: self.channels.pending_names(),
+            connection_stats: self.connection.connection_stats(),
+            delta_stats: self.delta_manager.as_ref().map(|dm| dm.read().get_stats()),
+            last_error: self.connection.last_error(),
+            reconnect_history: self.connection.reconnect_history(),
+        }
+    }
+
+    /// Get a snapshot of the connection, subscriptions and statistics.
+    ///
+    /// The fields are read one after another, so a concurrent state change
+    /// can leave them out of step (e.g. `socket_id` still set right after
+    /// `state` became `Disconnected`).
+    pub fn status(&self) -> ClientStatus {
+        ClientStatus {
+            state: self.connection.state(),
+            socket_id: self.connection.socket_id(),
+            session_id: self.session_id,
+            subscribed_channels: self.channels.names(),
+            connection_stats: self.connection.connection_stats(),
+            delta_stats: self.delta_manager.as_ref().map(|dm| dm.read().get_stats()),
+            uptime: self.connection.uptime(),
+            last_error: self.connection.last_error(),
+        }
+    }
+
+    /// How long the current connection has been established, or `None`
+    /// while not connected
+    pub fn uptime(&self) -> Option<std::time::Duration> {
+        self.connection.uptime()
+    }
+
+    /// Time spent connected across all connections of this client
+    pub fn total_connected_duration(&self) -> std::time::Duration {
+        self.connection.total_connected_duration()
+    }
+
+    /// Collect every statistic the client tracks in one snapshot.
+    ///
+    /// Like [`status`](Self::status), the parts are read one after another,
+    /// so traffic during the call can make them disagree slightly.
+    pub fn statistics_snapshot(&self) -> StatisticsSnapshot {
+        StatisticsSnapshot {
+            timestamp: SystemTime::now(),
+            connection: self.connection.connection_stats(),
+            delta: self.delta_manager.as_ref().map(|dm| dm.read().get_stats()),
+            channel_counts: self.channels.count_by_type(),
+            #[cfg(feature = "dispatch-metrics")]
+            dispatch: self.global_emitter.metrics(),
+            #[cfg(not(feature = "dispatch-metrics"))]
+            dispatch: DispatchMetrics::default(),
+            recent_reconnects: self.connection.reconnect_history(),
+        }
+    }
+
+    /// Start measuring message and byte rates over `interval`.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # async fn example(client: &sockudo_client::SockudoClient) {
+    /// loop {
+    ///     let window = client.start_statistics_window(Duration::from_secs(10));
+    ///     tokio::time::sleep(window.remaining()).await;
+    ///     println!("{}", window.delta());
+    /// }
+    /// # }
+    /// ```
+    pub fn start_statistics_window(
+        &self,
+        interval: std::time::Duration,
+    ) -> crate::connection::StatisticsWindowGuard {
+        self.connection.start_statistics_window(interval)
+    }
+
+    /// Get transport details of the current connection.
+    pub fn connection_info(&self) -> Option<crate::transports::ConnectionInfo> {
+        self.connection.connection_info()
+    }
+
+    /// Get the number of channels currently registered with the client.
+    pub fn subscription_count(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Get the names of all subscribed channels.
+    pub fn channel_names(&self) -> Vec<String> {
+        self.channels.names()
+    }
+
+    /// Count channels per type.
+    pub fn channel_counts_by_type(&self) -> std::collections::HashMap<ChannelType, usize> {
+        self.channels.count_by_type()
+    }
+
+    /// Count channels whose subscription has succeeded.
+    pub fn subscribed_channel_count(&self) -> usize {
+        self.channels.subscribed_count()
+    }
+
+    /// Make room for at least `additional` more channels, e.g. before
+    /// subscribing to a large batch that `expected_channel_count` didn't
+    /// account for.
+    pub fn reserve_channels(&self, additional: usize) {
+        self.channels.reserve(additional);
+    }
+
+    /// Count channels whose subscription is in flight or deferred.
+    pub fn pending_channel_count(&self) -> usize {
+        self.channels.pending_count()
+    }
+
+    /// Group the subscribed channels whose name starts with `prefix`, to
+    /// bind events or trigger client events on all of them at once.
+    ///
+    /// The group is a snapshot; channels subscribed later are not added.
+    pub fn channel_group(&self, prefix: &str) -> ChannelGroup {
+        ChannelGroup::from_prefix(&self.channels, prefix)
+    }
+
+    /// Capture the state of every channel, sorted by name.
+    ///
+    /// Use `PresenceChannel::snapshot` to include presence members.
+    pub fn channel_snapshots(&self) -> Vec<ChannelSnapshot> {
+        let mut snapshots: Vec<_> = self
+            .channels
+            .all()
+            .iter()
+            .map(|channel| channel.snapshot())
+            .collect();
+        snapshots.sort_by(|a, b| a.name.cmp(&b.name));
+        snapshots
+    }
+
+    /// Bind a callback to all events globally.
+    ///
+    /// This is the primary method for Rust code to bind global event handlers.
+    /// It accepts closures for convenient event handling.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// # use sockudo_client::{SockudoClient, PusherOptions};
+    /// # let client = SockudoClient::new(PusherOptions::new("key").into()).unwrap();
+    /// client.bind_global(|event| {
+    ///     println!("Event: {} on {:?}", event.event, event.channel);
+    /// });
+    /// ```
+    #[cfg(feature = "uniffi")]
+    pub fn bind_global(&self, callback: impl Fn(&PusherEvent) + Send + Sync + 'static) -> u64 {
+        self.global_emitter.bind_global(callback)
+    }
+
+    /// Bind a callback to all events globally (non-uniffi version).
+    #[cfg(not(feature = "uniffi"))]
+    pub fn bind_global(&self, callback: impl Fn(&PusherEvent) + Send + Sync + 'static) -> u64 {
+        self.global_emitter.bind_global(callback)
+    }
+
+    /// Bind a callback to `event_name` on channels matching `channel`, an
+    /// exact name or a glob such as `"room-*"`.
+    ///
+    /// Cheaper than a global callback that checks `event.channel` itself.
+    /// Remove it with `unbind`. Fails with `SockudoError::InvalidEventName`
+    /// if the event name does not pass the event name validator.
+    pub fn bind_on(
+        &self,
+        channel: &str,
+        event_name: &str,
+        callback: impl Fn(&PusherEvent) + Send + Sync + 'static,
+    ) -> Result<u64> {
+        self.global_emitter
+            .bind_channel_scoped(channel, event_name, callback)
+            .map_err(|e| self.error_context(e))
+    }
+
+    /// Count the client-wide callbacks (global, channel-scoped and
+    /// per-event bindings on the client), by kind
+    pub fn global_callback_memory_usage(&self) -> RegistryMemoryStats {
+        self.global_emitter.memory_usage()
+    }
+
+    /// Count every callback bound on the client and on all its channels.
+    ///
+    /// A number that keeps growing in a long-running process usually means
+    /// callbacks are bound without ever being unbound.
+    pub fn total_callback_count(&self) -> usize {
+        let channel_callbacks: usize = self
+            .channels
+            .all()
+            .iter()
+            .map(|channel| channel.callback_memory_usage().total_callbacks)
+            .sum();
+        channel_callbacks + self.global_emitter.callback_count()
+    }
+
+    /// Record the most recent `capacity` events received by the client
+    /// until the returned guard is dropped.
+    ///
+    /// Useful for inspecting what arrived before an error; see
+    /// [`EventLogGuard::snapshot`].
+    pub fn event_log(&self, capacity: usize) -> EventLogGuard {
+        EventLogGuard::new(&self.global_emitter, capacity)
+    }
+
+    /// Bind a callback for each time the client connects to the server.
+    ///
+    /// Fires on the initial connection and after every reconnect. The returned
+    /// ID removes the binding with the connection's `unbind`.
+    pub fn on_connected_to_server(&self, callback: impl Fn() + Send + Sync + 'static) -> u64 {
+        self.connection.on_connected_to_server(callback)
+    }
+
+    /// Measure the round-trip time of a `pusher:ping` to the server.
+    ///
+    /// When connected, the ping goes over the current connection. Otherwise a
+    /// separate connection is opened for the probe and closed afterwards, so
+    /// channels and their subscriptions are never touched. Returns
+    /// `SockudoError::ProbeTimeout` if no pong arrives within `timeout`.
+    pub async fn probe_connection(
+        &self,
+        timeout: std::time::Duration,
+    ) -> Result<std::time::Duration> {
+        if self.is_connected() {
+            return self.connection.probe(timeout).await;
+        }
+
+        // The connection token belongs to the client's own connection
+        let mut config = (*self.config).clone();
+        config.connection_token = None;
+        let probe = ConnectionManager::new(config);
+
+        let (connected_tx, connected_rx) = tokio::sync::oneshot::channel();
+        let connected_tx = parking_lot::Mutex::new(Some(connected_tx));
+        probe.on_connected_to_server(move || {
+            if let Some(tx) = connected_tx.lock().take() {
+                let _ = tx.send(());
+            }
+        });
+
+        let result = tokio::time::timeout(timeout, async {
+            probe.connect().await?;
+            let _ = connected_rx.await;
+            probe.probe(timeout).await
+        })
+        .await
+        .unwrap_or(Err(SockudoError::probe_timeout(timeout)));
+        probe.disconnect().await;
+
+        let rtt = result?;
+        self.connection.record_ping_rtt(rtt);
+        Ok(rtt)
+    }
+
+    /// Round-trip time measured by the most recent `probe_connection`
+    pub fn ping_rtt(&self) -> Option<std::time::Duration> {
+        self.connection.ping_rtt()
+    }
+
+    /// Appear connected without a server, for tests and offline demos.
+    ///
+    /// The client moves to `Connected` with a fake socket ID and outgoing
+    /// messages are dropped instead of sent. Channels, including those
+    /// already added, subscribe without authorization and succeed
+    /// immediately; presence channels have no members. With
+    /// `offline_echo_events` set, triggered client events are delivered back
+    /// to the client's own callbacks. Fails with `SockudoError::InvalidState`
+    /// while a real connection is active.
+    #[cfg(any(test, feature = "offline-mode"))]
+    pub fn enable_offline_mode(&self) -> Result<()> {
+        if self.connection.is_offline() {
+            return Ok(());
+        }
+        let state = self.state();
+        if state.is_active() {
+            return Err(SockudoError::invalid_state(format!(
+                "Cannot enter offline mode while {}",
+                state
+            )));
+        }
+
+        let channels = self.channels.clone();
+        let global_emitter = self.global_emitter.clone();
+        let echo = self.config.offline_echo_events;
+        let socket_id = format!("offline.{}", self.session_id);
+        self.connection.enter_offline_mode(
+            socket_id.clone(),
+            Arc::new(move |message| {
+                if !echo {
                     return;
                 }
-                "pusher:delta" => {
-                    if let Some(ref channel) = event.channel {
-                        if let Some(ref data) = event.data {
-                            #[cfg(feature = "wasm")]
-                            let delta_result = serde_json::from_value(data.clone());
-                            #[cfg(not(feature = "wasm"))]
-                            let delta_result = serde_json::from_str(data);
-
-                            if let Ok(delta_msg) = delta_result {
-                                match dm.read().handle_delta(channel, delta_msg) {
-                                    Ok(decoded_event) => {
-                                        // Route the decoded event to the channel
-                                        if let Some(ch) = self.channels.find(channel) {
-                                            ch.handle_event(&decoded_event);
-                                        }
-                                        // Also emit globally
-                                        self.global_emitter.emit(&decoded_event);
-                                    }
-                                    Err(e) => {
-                                        warn!("Failed to handle delta: {}", e);
-                                        dm.read().request_resync(channel);
-                                    }
-                                }
-                                return;
-                            }
-                        }
-                    }
+                let Ok(mut event) = Protocol::decode_message(message) else {
+                    return;
+                };
+                if !event.event.starts_with("client-") {
+                    return;
                 }
-                _ => {}
-            }
+                event.mark_received();
+                if let Some(channel) = event
+                    .channel
+                    .as_deref()
+                    .and_then(|name| channels.find(name))
+                {
+                    channel.handle_event(&event);
+                }
+                global_emitter.emit(&event);
+            }),
+        );
+        self.channels.set_offline(true);
+        self.channels.resubscribe_all_after_connect(&socket_id);
+        info!("Offline mode enabled");
+        Ok(())
+    }
+
+    /// Leave offline mode, disconnecting the client.
+    ///
+    /// Channels are kept and subscribe normally on the next `connect`.
+    #[cfg(any(test, feature = "offline-mode"))]
+    pub fn disable_offline_mode(&self) {
+        if !self.connection.is_offline() {
+            return;
         }
+        self.channels.set_offline(false);
+        self.connection.leave_offline_mode();
+        self.channels.disconnect();
+        info!("Offline mode disabled");
+    }
+
+    /// Replace the global event filter at runtime.
+    ///
+    /// Events for which the predicate returns `false` are discarded before
+    /// reaching any channel or global callbacks. The predicate runs in the
+    /// receive loop and must not block.
+    pub fn set_event_filter(&self, filter: impl Fn(&PusherEvent) -> bool + Send + Sync + 'static) {
+        self.connection
+            .set_event_filter(Some(crate::events::EventFilter::new(filter)));
+    }
+
+    /// Remove the global event filter.
+    pub fn clear_event_filter(&self) {
+        self.connection.set_event_filter(None);
+    }
+
+    /// Subscribe to a presence channel whose member info deserializes into `T`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// #[derive(serde::Deserialize)]
+    /// struct UserInfo { name: String }
+    ///
+    /// let channel = client.subscribe_presence_typed::<UserInfo>("presence-room")?;
+    /// channel.on_member_added_typed(|member| println!("{} joined", member.info.name));
+    /// ```
+    #[cfg(feature = "uniffi")]
+    pub fn subscribe_presence_typed<T: serde::de::DeserializeOwned + 'static>(
+        &self,
+        channel_name: &str,
+    ) -> Result<crate::channels::TypedPresenceChannel<T>> {
+        self.subscribe_presence(channel_name)
+            .map(crate::channels::TypedPresenceChannel::new)
+    }
 
-        // Route to channel if specified
-        if let Some(ref channel_name) = event.channel {
-            if let Some(channel) = self.channels.find(channel_name) {
-                channel.handle_event(event);
+    /// Route `event` to `channel_name` and the global listeners as if it
+    /// had arrived from the server, without touching the transport.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn replay_local(&self, channel_name: &str, mut event: PusherEvent) {
+        event.channel = Some(channel_name.to_string());
+        if event.timestamp.is_none() {
+            event.timestamp = Some(std::time::SystemTime::now());
+        }
+        route_event(
+            &self.channels,
+            &self.global_emitter,
+            self.delta_manager.as_ref(),
+            self.config.skip_sanitization,
+            &event,
+        );
+    }
+}
+
+/// Route an event received from the connection to its channel and the
+/// global listeners, handling the delta compression protocol events.
+///
+/// Full messages are tracked for delta compression as received; channel
+/// and global callbacks get a sanitized copy unless `skip_sanitization`
+/// is set.
+#[cfg(not(target_arch = "wasm32"))]
+fn route_event(
+    channels: &Channels,
+    global_emitter: &EventDispatcher,
+    delta_manager: Option<&Arc<RwLock<DeltaManager>>>,
+    skip_sanitization: bool,
+    event: &PusherEvent,
+) {
+    debug!(
+        "Received event: '{}' on channel {:?}",
+        event.event, event.channel
+    );
+
+    // Handle delta compression protocol events first
+    if let Some(dm) = delta_manager {
+        match event.event.as_str() {
+            "sockudo:checksum_error" => dm.read().record_checksum_error(),
+            "pusher:delta_compression_enabled" => {
+                if let Some(ref data) = event.data {
+                    #[cfg(feature = "wasm")]
+                    {
+                        dm.write().handle_enabled(data);
+                    }
+                    #[cfg(not(feature = "wasm"))]
+                    {
+                        if let Ok(value) = serde_json::from_str::<serde_json::Value>(data) {
+                            dm.write().handle_enabled(&value);
+                            debug!("Delta compression enabled by server");
+                        }
+                    }
+                }
+                return;
+            }
+            "pusher:delta_cache_sync" => {
+                if let (Some(ref channel), Some(ref data)) = (&event.channel, &event.data) {
+                    #[cfg(feature = "wasm")]
+                    let sync_result: std::result::Result<
+                        crate::delta::CacheSyncData,
+                        _,
+                    > = serde_json::from_value(data.clone());
+                    #[cfg(not(feature = "wasm"))]
+                    let sync_result: std::result::Result<
+                        crate::delta::CacheSyncData,
+                        _,
+                    > = serde_json::from_str(data);
+
+                    if let Ok(sync_data) = sync_result {
+                        dm.write().handle_cache_sync(channel, sync_data);
+                        debug!("Delta cache sync for channel: {}", channel);
+                    }
+                }
+                return;
+            }
+            "pusher:delta" => {
+                if let Some(ref channel) = event.channel {
+                    if let Some(ref data) = event.data {
+                        #[cfg(feature = "wasm")]
+                        let delta_result: std::result::Result<
+                            crate::delta::DeltaMessage,
+                            _,
+                        > = serde_json::from_value(data.clone());
+                        #[cfg(not(feature = "wasm"))]
+                        let delta_result: std::result::Result<
+                            crate::delta::DeltaMessage,
+                            _,
+                        > = serde_json::from_str(data);
+
+                        if let Ok(delta_msg) = delta_result {
+                            let decoded = dm.read().handle_delta(channel, delta_msg);
+                            match decoded {
+                                Ok(mut decoded_event) => {
+                                    if !skip_sanitization {
+                                        Protocol::sanitize_message(&mut decoded_event);
+                                    }
+                                    // Route the decoded event to the channel
+                                    if let Some(ch) = channels.find(channel) {
+                                        ch.handle_event(&decoded_event);
+                                    }
+                                    // Also emit globally
+                                    global_emitter.emit(&decoded_event);
+                                    debug!("Delta decoded and routed for channel: {}", channel);
+                                }
+                                Err(e) => {
+                                    warn!("Failed to handle delta: {}", e);
+                                    dm.read().request_resync(channel);
+                                }
+                            }
+                            return;
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Track full message for delta compression before sanitizing strips
+    // the sequence number
+    if let (Some(dm), Some(ref channel_name)) = (delta_manager, &event.channel) {
+        #[cfg(feature = "wasm")]
+        let seq_opt = event
+            .data
+            .as_ref()
+            .and_then(|d| d.get("__delta_seq"))
+            .and_then(|v| v.as_u64());
+
+        #[cfg(not(feature = "wasm"))]
+        let seq_opt = event.data.as_ref().and_then(|d| {
+            serde_json::from_str::<serde_json::Value>(d)
+                .ok()
+                .and_then(|v| v.get("__delta_seq").and_then(|s| s.as_u64()))
+        });
+
+        if let (Some(seq), true) = (seq_opt, channels.delta_enabled_for(channel_name)) {
+            dm.write().handle_full_message(channel_name, event, seq);
+        }
+    }
+
+    // Callbacks never see server-internal fields
+    let sanitized;
+    let event = if skip_sanitization {
+        event
+    } else {
+        let mut copy = event.clone();
+        Protocol::sanitize_message(&mut copy);
+        sanitized = copy;
+        &sanitized
+    };
+
+    // Check if this is an internal event (like pusher-js does)
+    let is_internal = event.event.starts_with("pusher_internal:");
+
+    // Route to channel if specified
+    if let Some(ref channel_name) = event.channel {
+        debug!(
+            "Routing event '{}' to channel '{}'",
+            event.event, channel_name
+        );
+
+        // Route to channel - dispatchers are now shared so this works correctly
+        if let Some(channel) = channels.find(channel_name) {
+            channel.handle_event(event);
+            debug!("Event routed to channel '{}'", channel_name);
+        } else {
+            warn!(
+                "Channel '{}' not found for event '{}'",
+                channel_name, event.event
+            );
+        }
+    }
+
+    // Emit globally (except internal events, like pusher-js does)
+    if !is_internal {
+        global_emitter.emit(event);
+    }
+}
+
+// WASM-specific methods (outside uniffi export)
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(all(feature = "wasm", not(feature = "uniffi")))]
+impl SockudoClient {
+    /// Send an event to the server (WASM version).
+    ///
+    /// This is used for client events on private/presence channels.
+    pub fn send_event(
+        &self,
+        event_name: &str,
+        data: &serde_json::Value,
+        channel: Option<&str>,
+    ) -> bool {
+        self.connection.send_event(event_name, data, channel)
+    }
+}
+
+// Non-uniffi methods (for WASM and other non-FFI builds)
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(not(feature = "uniffi"))]
+impl SockudoClient {
+    /// Create a new Sockudo client (Pusher-JS compatible API).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use sockudo::{SockudoClient, SockudoOptions};
+    ///
+    /// let client = SockudoClient::new("app-key", SockudoOptions {
+    ///     cluster: Some("mt1".to_string()),
+    ///     ..Default::default()
+    /// }).await.unwrap();
+    /// ```
+    pub async fn new(app_key: impl Into<String>, mut options: SockudoOptions) -> Result<Self> {
+        let app_key = app_key.into();
+        if app_key.is_empty() {
+            return Err(SockudoError::config("App key is required"));
+        }
+
+        // Set the app_key in options
+        options.app_key = app_key.clone();
+
+        // Create the client
+        let client = Self::from_options(options)?;
+
+        // Auto-connect (Pusher-JS behavior)
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            client.connect_until(3).await?;
+        }
+
+        Ok(client)
+    }
+
+    /// Create a new Sockudo client from options without auto-connecting.
+    ///
+    /// This is useful for testing or when you want manual control over the connection.
+    /// For normal use, prefer `SockudoClient::new()` which auto-connects like Pusher-JS.
+    pub fn from_options(options: SockudoOptions) -> Result<Self> {
+        if options.app_key.is_empty() {
+            return Err(SockudoError::config("App key is required"));
+        }
+
+        let config: Config = options.clone().into();
+        let config = Arc::new(config);
+
+        // Create channels with callbacks
+        let expected_channels = config.expected_channel_count.unwrap_or(0);
+        let mut channels = Channels::with_capacity(expected_channels);
+
+        // Create delta manager if enabled
+        if let Some(ref delta_opts) = config.delta_compression {
+            crate::delta::check_available(&delta_opts.algorithms)?;
+        }
+        let delta_manager = if let Some(delta_opts) = config.delta_compression.clone() {
+            if delta_opts.enabled {
+                let dm = DeltaManager::new(delta_opts);
+                if let Some(ref entries) = config.delta_cache_warmup {
+                    dm.cache_warmup_base64(entries)?;
+                }
+                Some(Arc::new(RwLock::new(dm)))
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        // Generate session ID
+        let session_id = rand::random::<u32>();
+
+        info!(
+            "Creating Sockudo client for app '{}' (session: {})",
+            options.app_key, session_id
+        );
+
+        // Create event dispatcher
+        let global_emitter = EventDispatcher::with_capacity(expected_channels);
+        global_emitter.set_callback_timeout(config.callback_timeout);
+        global_emitter.set_max_callbacks(config.max_global_callbacks.unwrap_or(0));
+
+        // Create connection manager
+        let connection = Arc::new(ConnectionManager::new((*config).clone()));
+        if config.client_id.is_none() {
+            connection.set_client_id(&format!("{:x}", session_id));
+        }
+
+        // Set up raw and batch send callbacks and batch options for triggers
+        let connection_raw = connection.clone();
+        channels.set_raw_send_callback(Arc::new(move |frame| connection_raw.send(frame)));
+        let connection_batch = connection.clone();
+        channels.set_batch_send_callback(Arc::new(move |events| {
+            connection_batch.send_event_batch(events)
+        }));
+        channels
+            .set_trigger_batch_options(config.trigger_batch_mode, config.max_trigger_batch_size);
+        channels.set_client_event_secret(config.client_event_secret.clone());
+        channels.set_max_subscriptions(config.max_subscriptions);
+        channels.set_event_name_validator(config.event_name_validator.clone());
+        channels.set_strict_channel_names(config.strict_channel_names);
+        channels.set_channel_prefix_map(config.channel_prefix_map.clone());
+        channels.set_channel_shared_secrets(config.channel_shared_secrets.clone());
+        channels.set_global_subscription_metadata(config.global_subscription_metadata.clone());
+        channels.set_event_history_size(config.channel_event_history_size);
+        channels.set_callback_timeout(config.callback_timeout);
+
+        // Set up send callbacks for channels
+        channels.set_send_callback(connection_send_fn(connection.clone(), None));
+        channels.set_bulk_send_callback(connection_send_fn(
+            connection.clone(),
+            Some(MessagePriority::Low),
+        ));
+
+        // Set up send callback for delta manager
+        if let Some(ref dm) = delta_manager {
+            let connection_for_delta = connection.clone();
+            dm.write()
+                .set_send_callback(Arc::new(move |event_name, data| {
+                    let mut event = PusherEvent::new(event_name);
+                    #[cfg(feature = "wasm")]
+                    {
+                        event.data = Some(data.clone());
+                    }
+                    #[cfg(not(feature = "wasm"))]
+                    {
+                        event.data = Some(data.to_string());
+                    }
+
+                    connection_for_delta.send_event_with_priority(event, MessagePriority::Normal)
+                }));
+        }
+
+        // Set up authorization callback for private/presence channels
+        // Authorization callback is only needed for native builds
+        // WASM uses async authorization directly in subscribe_async
+        let auth_endpoint_overrides = Arc::new(RwLock::new(HashMap::<String, String>::new()));
+        #[cfg(not(target_arch = "wasm32"))]
+        channels.set_authorize_callback(Arc::new(channel_authorizer(
+            &options,
+            auth_endpoint_overrides.clone(),
+        )));
+
+        let client = Self {
+            key: options.app_key,
+            config,
+            channels: Arc::new(channels),
+            connection,
+            delta_manager,
+            global_emitter,
+            session_id,
+            auth_endpoint_overrides,
+        };
+        client.bind_connection_handlers()?;
+        Ok(client)
+    }
+
+    /// Get the current connection state.
+    pub fn state(&self) -> ConnectionState {
+        self.connection.state()
+    }
+
+    /// Get the socket ID assigned by the server.
+    pub fn socket_id(&self) -> Option<String> {
+        self.connection.socket_id()
+    }
+}
+
+/// Channel authorization through the channel's auth endpoint: its
+/// per-subscription override, its prefix config or the global endpoint, in
+/// that order.
+///
+/// Channels without an endpoint fail to authorize, unless a shared secret is
+/// configured for them, in which case they subscribe unsigned.
+#[cfg(not(target_arch = "wasm32"))]
+fn channel_authorizer(
+    options: &SockudoOptions,
+    overrides: Arc<RwLock<HashMap<String, String>>>,
+) -> impl Fn(&str, &str) -> Result<ChannelAuthData> + Send + Sync + 'static {
+    let options = options.clone();
+    let global_endpoint = options
+        .auth_endpoint
+        .clone()
+        .filter(|endpoint| !endpoint.is_empty());
+    // Shared by every channel using the global endpoint
+    let global_client = global_endpoint.map(|endpoint| {
+        AuthClient::new(
+            Some(endpoint),
+            options.auth_headers.clone(),
+            options.user_auth_endpoint.clone(),
+            options.user_auth_headers.clone(),
+        )
+        .with_signature_mode(options.auth_signature_mode.clone())
+    });
+
+    move |channel_name, socket_id| {
+        // Channels with their own endpoint get a dedicated client
+        let channel_endpoint = overrides.read().get(channel_name).cloned().or_else(|| {
+            options
+                .channel_config(channel_name)
+                .and_then(|config| config.auth_endpoint.clone())
+        });
+        let channel_client = channel_endpoint.map(|endpoint| {
+            AuthClient::for_channel(channel_name, &options).with_auth_endpoint(endpoint)
+        });
+
+        let Some(auth_client) = channel_client.as_ref().or(global_client.as_ref()) else {
+            if options.channel_shared_secrets.contains_key(channel_name) {
+                return Ok(ChannelAuthData {
+                    auth: String::new(),
+                    channel_data: None,
+                    shared_secret: None,
+                });
+            }
+            return Err(SockudoError::authorization(
+                "No auth_endpoint configured for private/presence channels",
+            ));
+        };
+
+        tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current()
+                .block_on(async { auth_client.authorize_channel(channel_name, socket_id).await })
+        })
+    }
+}
+
+/// Channel send callback encoding events for `connection`, sent at
+/// `priority` or at each event's default priority
+#[cfg(not(target_arch = "wasm32"))]
+fn connection_send_fn(
+    connection: Arc<ConnectionManager>,
+    priority: Option<MessagePriority>,
+) -> SendEventFn {
+    Arc::new(move |event_name, data, channel| {
+        let mut event = PusherEvent::new(event_name);
+        #[cfg(feature = "wasm")]
+        {
+            event.data = Some(data.clone());
+        }
+        #[cfg(not(feature = "wasm"))]
+        {
+            event.data = Some(data.to_string());
+        }
+        event.channel = channel.map(|s| s.to_string());
+
+        connection.send_event_with_priority(
+            event,
+            priority.unwrap_or_else(|| MessagePriority::for_event(event_name)),
+        )
+    })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl std::fmt::Debug for SockudoClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SockudoClient")
+            .field("key", &self.key)
+            .field("session_id", &self.session_id)
+            .field("state", &self.state())
+            .field("socket_id", &self.socket_id())
+            .field("channel_count", &self.channels.len())
+            .finish()
+    }
+}
+
+// Make SockudoClient Send + Sync for use across threads
+#[cfg(not(target_arch = "wasm32"))]
+unsafe impl Send for SockudoClient {}
+#[cfg(not(target_arch = "wasm32"))]
+unsafe impl Sync for SockudoClient {}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use crate::transports::mock_server::MockServer;
+    use crate::ChannelType;
+
+    use super::*;
+
+    #[test]
+    fn test_client_creation() {
+        let options = SockudoOptions::new("test-key").cluster("mt1");
+        #[cfg(feature = "uniffi")]
+        let client = SockudoClient::new(options.into()).unwrap();
+        #[cfg(not(feature = "uniffi"))]
+        let client = SockudoClient::from_options(options).unwrap();
+
+        assert_eq!(client.key(), "test-key");
+        assert_eq!(client.state(), ConnectionState::Initialized);
+    }
+
+    #[test]
+    fn test_client_requires_key() {
+        let options = SockudoOptions::default();
+        #[cfg(feature = "uniffi")]
+        let result = SockudoClient::new(options.into());
+        #[cfg(not(feature = "uniffi"))]
+        let result = SockudoClient::new(options);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_subscribe() {
+        let options = SockudoOptions::new("test-key");
+        #[cfg(feature = "uniffi")]
+        let client = SockudoClient::new(options.into()).unwrap();
+        #[cfg(not(feature = "uniffi"))]
+        let client = SockudoClient::from_options(options).unwrap();
+
+        let channel = client.subscribe("test-channel").unwrap();
+        assert_eq!(channel.name(), "test-channel");
+        assert_eq!(channel.channel_type(), ChannelType::Public);
+    }
+
+    #[test]
+    fn test_invalid_channel_name() {
+        let options = SockudoOptions::new("test-key");
+        #[cfg(feature = "uniffi")]
+        let client = SockudoClient::new(options.into()).unwrap();
+        #[cfg(not(feature = "uniffi"))]
+        let client = SockudoClient::from_options(options).unwrap();
+
+        let result = client.subscribe("#invalid");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_subscribe_validates_channel_names() {
+        let options = SockudoOptions::new("test-key");
+        #[cfg(feature = "uniffi")]
+        let client = SockudoClient::new(options.into()).unwrap();
+        #[cfg(not(feature = "uniffi"))]
+        let client = SockudoClient::from_options(options).unwrap();
+
+        assert!(client.subscribe("news feed").is_err());
+        assert!(client.subscribe_lazy("").is_err());
+        assert!(client.subscribe_presence("presence-room\0").is_err());
+        assert!(client.subscribe_presence("room").is_err());
+        assert!(client.subscribe("news-feed").is_ok());
+    }
+
+    /// Start a [`MockServer`] with the default script, returning its port
+    /// and every message it receives
+    async fn spawn_mock_server() -> (u16, tokio::sync::mpsc::UnboundedReceiver<serde_json::Value>) {
+        let server = MockServer::new().spawn().await;
+        (server.port, server.received)
+    }
+
+    fn mock_client(port: u16) -> SockudoClient {
+        let options = SockudoOptions::new("test-key")
+            .ws_host("127.0.0.1")
+            .ws_port(port)
+            .use_tls(false);
+        #[cfg(feature = "uniffi")]
+        let client = SockudoClient::new(options.into()).unwrap();
+        #[cfg(not(feature = "uniffi"))]
+        let client = SockudoClient::from_options(options).unwrap();
+        client
+    }
+
+    #[tokio::test]
+    async fn test_watch_channel() {
+        use crate::channels::ChannelState;
+        use std::time::Duration;
+
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<ChannelWatcher>();
+
+        let client = mock_client(1);
+        assert!(client.watch_channel("prices").is_err());
+        let channel = client.subscribe("prices").unwrap();
+        let listeners = channel.listener_count(None);
+        let mut watcher = client.watch_channel("prices").unwrap();
+        assert_eq!(channel.listener_count(None), listeners + 1);
+
+        let short = Duration::from_millis(20);
+        assert!(matches!(
+            watcher
+                .wait_for_state(ChannelState::Subscribed, short)
+                .await,
+            Err(SockudoError::TimeoutError { .. })
+        ));
+        channel.handle_event(
+            &PusherEvent::new("pusher_internal:subscription_succeeded").with_channel("prices"),
+        );
+        watcher
+            .wait_for_state(ChannelState::Subscribed, Duration::from_secs(1))
+            .await
+            .unwrap();
+
+        for i in 0..3 {
+            channel.handle_event(
+                &PusherEvent::new("tick")
+                    .with_channel("prices")
+                    .with_json_data(serde_json::json!({ "i": i })),
+            );
+        }
+        for i in 0..3 {
+            let event = watcher.next_event(Duration::from_secs(1)).await.unwrap();
+            assert_eq!(event.data_as_value(), Some(serde_json::json!({ "i": i })));
+        }
+        // The subscription event is not delivered as an event
+        assert!(watcher.next_event(short).await.is_err());
+        assert!(watcher.wait_for_member_count(0, short).await.is_err());
+
+        drop(watcher);
+        assert_eq!(channel.listener_count(None), listeners);
+    }
+
+    #[tokio::test]
+    async fn test_watch_presence_member_count() {
+        use std::time::Duration;
+
+        let client = mock_client(1);
+        client.subscribe("presence-room").unwrap();
+        let channel = client.channels.find_presence("presence-room").unwrap();
+        let watcher = client.watch_channel("presence-room").unwrap();
+
+        let data = serde_json::json!({
+            "presence": {
+                "count": 1,
+                "ids": ["user1"],
+                "hash": {"user1": {}}
+            }
+        });
+        channel.handle_event(
+            &PusherEvent::new("pusher_internal:subscription_succeeded")
+                .with_channel("presence-room")
+                .with_json_data(data),
+        );
+        watcher
+            .wait_for_member_count(1, Duration::from_secs(1))
+            .await
+            .unwrap();
+
+        let waiter = tokio::spawn(async move {
+            watcher
+                .wait_for_member_count(2, Duration::from_secs(5))
+                .await
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        channel.handle_event(
+            &PusherEvent::new("pusher_internal:member_added")
+                .with_channel("presence-room")
+                .with_json_data(serde_json::json!({"user_id": "user2", "user_info": {}})),
+        );
+        waiter.await.unwrap().unwrap();
+    }
+
+    #[test]
+    fn test_replay_local() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let client = mock_client(1);
+        let channel = client.subscribe("test-channel").unwrap();
+
+        let on_channel = Arc::new(AtomicUsize::new(0));
+        let on_channel_clone = on_channel.clone();
+        channel
+            .bind("my-event", move |event| {
+                assert_eq!(event.channel.as_deref(), Some("test-channel"));
+                on_channel_clone.fetch_add(1, Ordering::SeqCst);
+            })
+            .unwrap();
+        let global = Arc::new(AtomicUsize::new(0));
+        let global_clone = global.clone();
+        client.bind_global(move |_| {
+            global_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        client.replay_local("test-channel", PusherEvent::new("my-event"));
+        client.replay_local("other-channel", PusherEvent::new("my-event"));
+
+        assert_eq!(on_channel.load(Ordering::SeqCst), 1);
+        assert_eq!(global.load(Ordering::SeqCst), 2);
+        #[cfg(feature = "event-stats")]
+        assert_eq!(client.event_count(), 2);
+
+        channel.emit_local(PusherEvent::new("my-event").with_channel("test-channel"));
+        assert_eq!(on_channel.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_event_sanitization() {
+        let raw = serde_json::json!({"__delta_seq": 3, "__internal_id": "x", "price": 100});
+        for skip in [false, true] {
+            let update = serde_json::json!({
+                "event": "update",
+                "channel": "prices",
+                "data": raw.to_string(),
+            });
+            let server = MockServer::new()
+                .then_send(update.to_string())
+                .spawn()
+                .await;
+            let options = SockudoOptions::new("test-key")
+                .ws_host("127.0.0.1")
+                .ws_port(server.port)
+                .use_tls(false)
+                .skip_sanitization(skip);
+            #[cfg(feature = "uniffi")]
+            let client = SockudoClient::new(options.into()).unwrap();
+            #[cfg(not(feature = "uniffi"))]
+            let client = SockudoClient::from_options(options).unwrap();
+
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+            client
+                .subscribe("prices")
+                .unwrap()
+                .bind("update", move |event| {
+                    let _ = tx.send(event.data_as_value().unwrap());
+                })
+                .unwrap();
+            client.connect_until(1).await.unwrap();
+
+            let received = tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            let expected = if skip {
+                raw.clone()
+            } else {
+                serde_json::json!({"price": 100})
+            };
+            assert_eq!(received, expected);
+            client.disconnect().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_uptime() {
+        use std::time::Duration;
+
+        let (port, _messages) = spawn_mock_server().await;
+        let client = mock_client(port);
+        assert_eq!(client.uptime(), None);
+        assert_eq!(client.total_connected_duration(), Duration::ZERO);
+
+        client.connect().await.unwrap();
+        client.wait_for_connection(5).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let uptime = client.uptime().unwrap();
+        assert!(uptime >= Duration::from_millis(200) && uptime < Duration::from_secs(2));
+
+        client.disconnect().await;
+        assert_eq!(client.uptime(), None);
+        let total = client.total_connected_duration();
+        assert!(total >= Duration::from_millis(200) && total < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_header_injection_transformer() {
+        use std::time::Duration;
+
+        let (port, mut messages) = spawn_mock_server().await;
+        let client = mock_client(port);
+        client.connect().await.unwrap();
+        client.wait_for_connection(5).await.unwrap();
+
+        async fn next_data(
+            messages: &mut tokio::sync::mpsc::UnboundedReceiver<serde_json::Value>,
+        ) -> serde_json::Value {
+            let message = tokio::time::timeout(Duration::from_secs(5), messages.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            serde_json::from_str(message["data"].as_str().unwrap()).unwrap()
+        }
+
+        client.subscribe("news").unwrap();
+        assert!(next_data(&mut messages).await.get("tenant").is_none());
+
+        client.add_message_transformer_at_runtime(Box::new(
+            crate::HeaderInjectionTransformer::default().header("tenant", "acme"),
+        ));
+        client.subscribe("sports").unwrap();
+        client
+            .connection
+            .send_event("client-note", r#"{"text":"hi"}"#, Some("news"));
+        client
+            .connection
+            .send_event("client-note", r#"{"tenant":"other"}"#, Some("news"));
+
+        // Protocol events are sent unchanged
+        let subscribe = next_data(&mut messages).await;
+        assert_eq!(subscribe["channel"], "sports");
+        assert!(subscribe.get("tenant").is_none());
+        assert_eq!(
+            next_data(&mut messages).await,
+            serde_json::json!({"text": "hi", "tenant": "acme"})
+        );
+        assert_eq!(
+            next_data(&mut messages).await,
+            serde_json::json!({"tenant": "other"})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_channel_lifecycle_callbacks() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let (port, _messages) = spawn_mock_server().await;
+        let client = mock_client(port);
+        let connected = Arc::new(AtomicUsize::new(0));
+        let connected_clone = connected.clone();
+        client.on_connected_to_server(move || {
+            connected_clone.fetch_add(1, Ordering::SeqCst);
+        });
+        client.connect().await.unwrap();
+        client.wait_for_connection(5).await.unwrap();
+        assert_eq!(connected.load(Ordering::SeqCst), 1);
+
+        let channel = client.subscribe("restricted-feed").unwrap();
+        let failures = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let failures_clone = failures.clone();
+        channel.on_subscription_failed(move |message| failures_clone.lock().push(message));
+        let unsubscribed = Arc::new(AtomicUsize::new(0));
+        let unsubscribed_clone = unsubscribed.clone();
+        channel.on_unsubscribed(move || {
+            unsubscribed_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        client.replay_local(
+            "restricted-feed",
+            PusherEvent::new("pusher:subscription_error").with_json_data(serde_json::json!({
+                "type": "AuthError",
+                "error": "Invalid signature",
+                "status": 401,
+            })),
+        );
+        assert_eq!(*failures.lock(), vec!["Invalid signature".to_string()]);
+
+        client.replay_local(
+            "restricted-feed",
+            PusherEvent::new("pusher_internal:subscription_succeeded")
+                .with_json_data(serde_json::json!({})),
+        );
+        client.unsubscribe("restricted-feed");
+        assert_eq!(unsubscribed.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_channel_names() {
+        let options = SockudoOptions::new("test-key");
+        #[cfg(feature = "uniffi")]
+        let client = SockudoClient::new(options.into()).unwrap();
+        #[cfg(not(feature = "uniffi"))]
+        let client = SockudoClient::from_options(options).unwrap();
+
+        for name in ["news", "sports", "presence-lobby", "weather"] {
+            client.subscribe(name).unwrap();
+        }
+        client.unsubscribe("sports");
+        client.subscribe("sports-live").unwrap();
+        client.unsubscribe("weather");
+
+        let mut names = client.channel_names();
+        names.sort();
+        assert_eq!(names, vec!["news", "presence-lobby", "sports-live"]);
+        assert_eq!(client.subscription_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_connection_info_after_connect() {
+        let (port, _messages) = spawn_mock_server().await;
+        let client = mock_client(port);
+        assert!(client.connection_info().is_none());
+
+        client.connect().await.unwrap();
+        client.wait_for_connection(5).await.unwrap();
+
+        let info = client.connection_info().unwrap();
+        assert_eq!(info.peer_addr.unwrap().port(), port);
+        assert!(info.http_version.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_status_after_connect() {
+        let (port, _messages) = spawn_mock_server().await;
+        let client = mock_client(port);
+        client.subscribe("status-a").unwrap();
+        client.subscribe("status-b").unwrap();
+
+        client.connect().await.unwrap();
+        let status = client.status();
+        assert!(matches!(
+            status.state,
+            ConnectionState::Connecting | ConnectionState::Connected
+        ));
+
+        let mut expected: Vec<String> = client
+            .all_channels()
+            .iter()
+            .map(|c| c.name().to_string())
+            .collect();
+        let mut subscribed = status.subscribed_channels.clone();
+        expected.sort();
+        subscribed.sort();
+        assert_eq!(subscribed, expected);
+        assert!(status.last_error.is_none());
+
+        let json = serde_json::to_value(&status).unwrap();
+        assert_eq!(json["session_id"], client.session_id());
+    }
+
+    #[cfg(feature = "io-adapter")]
+    #[tokio::test]
+    async fn test_into_io_reads_raw_frames() {
+        use tokio::io::AsyncReadExt;
+
+        let server = MockServer::new().spawn().await;
+        let client = mock_client(server.port);
+        client.connect_until(1).await.unwrap();
+        let mut io = client.into_io().await.unwrap();
+        server.send("custom framing");
+        server.close();
+
+        let mut received = String::new();
+        tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            io.read_to_string(&mut received),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(received, "custom framing");
+    }
+
+    #[tokio::test]
+    async fn test_debug_dump() {
+        let (port, _messages) = spawn_mock_server().await;
+        let client = mock_client(port);
+        client.subscribe_lazy("dump-channel").unwrap();
+
+        let dump = client.debug_dump();
+        assert_eq!(dump.version, crate::protocol::CLIENT_VERSION);
+        assert_eq!(dump.channel_count, 1);
+        assert!(dump.subscribed_channels.is_empty());
+        assert_eq!(dump.pending_channels, vec!["dump-channel".to_string()]);
+        assert!(dump.socket_id.is_none());
+
+        client.connect_until(1).await.unwrap();
+        let channel = client.channel("dump-channel").unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(5), channel.on_subscribed())
+            .await
+            .unwrap()
+            .unwrap();
+        let dump = client.debug_dump();
+        assert!(dump.pending_channels.is_empty());
+        assert_eq!(dump.socket_id.as_deref(), Some("***.456"));
+        assert_eq!(dump.reconnect_history.len(), 1);
+
+        let text = serde_json::to_string_pretty(&dump).unwrap();
+        assert!(!text.contains("123.456"));
+        let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(json["socket_id"], "***.456");
+        assert_eq!(json["channel_count"], 1);
+        assert_eq!(json["subscribed_channels"][0], "dump-channel");
+        assert_eq!(json["reconnect_history"][0]["attempt_number"], 1);
+
+        // The dump attached to an error is the same JSON, attached once
+        let error = SockudoError::invalid_channel("bad")
+            .context(&dump)
+            .context(&dump);
+        match error {
+            SockudoError::WithContext { error, dump } => {
+                assert!(matches!(*error, SockudoError::InvalidChannel { .. }));
+                assert_eq!(
+                    serde_json::from_str::<serde_json::Value>(&dump).unwrap(),
+                    json
+                );
+            }
+            other => panic!("expected WithContext, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pause_and_resume() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let server = MockServer::new().spawn().await;
+        let client = mock_client(server.port);
+        let late_events = Arc::new(AtomicUsize::new(0));
+        let late_clone = late_events.clone();
+        client.bind_global(move |event| {
+            if event.event == "late-event" {
+                late_clone.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        assert!(client.pause().await.is_err());
+        client.connect().await.unwrap();
+        client.wait_for_connection(5).await.unwrap();
+
+        client.pause().await.unwrap();
+        assert_eq!(client.state(), ConnectionState::Paused);
+        server.send(serde_json::json!({ "event": "late-event", "data": "{}" }).to_string());
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        assert_eq!(late_events.load(Ordering::SeqCst), 0);
+
+        client.resume().await.unwrap();
+        assert_eq!(client.state(), ConnectionState::Connected);
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while late_events.load(Ordering::SeqCst) == 0 {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("queued event not delivered after resume");
+    }
+
+    #[tokio::test]
+    async fn test_resume_reconnects_when_server_closed() {
+        let server = MockServer::new().socket_ids(&["1.1", "2.2"]).spawn().await;
+        let client = mock_client(server.port);
+
+        client.connect().await.unwrap();
+        client.wait_for_connection(5).await.unwrap();
+        assert_eq!(client.socket_id(), Some("1.1".to_string()));
+
+        client.pause().await.unwrap();
+        server.close();
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+        client.resume().await.unwrap();
+        client.wait_for_connection(5).await.unwrap();
+        assert_eq!(client.state(), ConnectionState::Connected);
+        assert_eq!(client.socket_id(), Some("2.2".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_lazy_before_connect() {
+        let (port, _messages) = spawn_mock_server().await;
+        let client = mock_client(port);
+
+        let channel = client.subscribe_lazy("lazy-channel").unwrap();
+        assert_eq!(
+            client.pending_subscriptions(),
+            vec!["lazy-channel".to_string()]
+        );
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let tx = std::sync::Mutex::new(Some(tx));
+        channel
+            .bind("pusher:subscription_succeeded", move |_| {
+                if let Some(tx) = tx.lock().unwrap().take() {
+                    let _ = tx.send(());
+                }
+            })
+            .unwrap();
+
+        client.connect().await.unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(5), rx)
+            .await
+            .expect("subscription_succeeded not delivered")
+            .unwrap();
+
+        assert!(channel.is_subscribed());
+        assert!(client.pending_subscriptions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_connect_with_token_appends_encoded_token() {
+        let mut server = MockServer::new().spawn().await;
+        let client = mock_client(server.port);
+        client
+            .connect_with_token("s3cret token/+".to_string())
+            .await
+            .unwrap();
+
+        let uri = tokio::time::timeout(std::time::Duration::from_secs(5), server.uris.recv())
+            .await
+            .expect("handshake not received")
+            .unwrap();
+        assert!(uri.starts_with("/app/test-key?"));
+        assert!(uri.ends_with("&token=s3cret%20token%2F%2B"));
+        assert!(!format!("{:?}", client.connection).contains("s3cret"));
+    }
+
+    #[tokio::test]
+    async fn test_connect_with_token_delivers_events_once() {
+        use std::time::Duration;
+
+        let tick = serde_json::json!({ "event": "tick", "channel": "ticker", "data": "{}" });
+        let server = MockServer::new().then_send(tick.to_string()).spawn().await;
+        let client = mock_client(server.port);
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        client.bind_global(move |event| {
+            if event.event == "tick" {
+                let _ = tx.send(());
+            }
+        });
+
+        client.connect().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        client.disconnect().await;
+        client
+            .connect_with_token("token".to_string())
+            .await
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_connect_via_overrides_url_once() {
+        use std::time::Duration;
+
+        let mut configured = MockServer::new().spawn().await;
+        let mut discovered = MockServer::new().spawn().await;
+        let client = mock_client(configured.port);
+
+        assert!(client
+            .connect_via("http://127.0.0.1/app/test-key")
+            .await
+            .is_err());
+        assert!(client
+            .connect_via("ws://127.0.0.1/app/other-key")
+            .await
+            .is_err());
+
+        let url = format!(
+            "ws://127.0.0.1:{}/app/test-key?protocol=7&via=discovery",
+            discovered.port
+        );
+        client.connect_via(&url).await.unwrap();
+        let uri = tokio::time::timeout(Duration::from_secs(5), discovered.uris.recv())
+            .await
+            .expect("override URL not used")
+            .unwrap();
+        assert_eq!(
+            uri,
+            format!(
+                "/app/test-key?protocol=7&via=discovery&client_id={}",
+                client.client_id()
+            )
+        );
+        assert!(configured.uris.try_recv().is_err());
+
+        // The override is single-use
+        client.disconnect().await;
+        client.connect().await.unwrap();
+        let uri = tokio::time::timeout(Duration::from_secs(5), configured.uris.recv())
+            .await
+            .expect("configured URL not used after the override")
+            .unwrap();
+        assert!(uri.starts_with("/app/test-key?"));
+        assert!(discovered.uris.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_connect_sends_client_id() {
+        use std::time::Duration;
+
+        let mut server = MockServer::new().spawn().await;
+        let port = server.port;
+
+        // Defaults to the session ID in hex
+        let client = mock_client(port);
+        assert_eq!(client.client_id(), format!("{:x}", client.session_id));
+        for _ in 0..2 {
+            client.connect().await.unwrap();
+            let uri = tokio::time::timeout(Duration::from_secs(5), server.uris.recv())
+                .await
+                .expect("handshake not received")
+                .unwrap();
+            assert!(uri.starts_with("/app/test-key?protocol=7&"));
+            assert!(uri.ends_with(&format!("&client_id={}", client.client_id())));
+            client.disconnect().await;
+        }
+
+        let options = SockudoOptions::new("test-key")
+            .ws_host("127.0.0.1")
+            .ws_port(port)
+            .use_tls(false)
+            .client_id("device 42/a");
+        #[cfg(feature = "uniffi")]
+        let client = SockudoClient::new(options.into()).unwrap();
+        #[cfg(not(feature = "uniffi"))]
+        let client = SockudoClient::from_options(options).unwrap();
+        assert_eq!(client.client_id(), "device-42-a");
+        client.connect().await.unwrap();
+        let uri = tokio::time::timeout(Duration::from_secs(5), server.uris.recv())
+            .await
+            .expect("handshake not received")
+            .unwrap();
+        assert!(uri.ends_with("&client_id=device-42-a"));
+        client.disconnect().await;
+    }
+
+    #[tokio::test]
+    async fn test_probe_connection_measures_rtt() {
+        use std::time::Duration;
+
+        let mut server = MockServer::new().spawn().await;
+        let client = mock_client(server.port);
+        assert_eq!(client.ping_rtt(), None);
+
+        // Not connected: the probe uses its own short-lived connection
+        let rtt = client
+            .probe_connection(Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(rtt > Duration::ZERO);
+        assert_eq!(client.ping_rtt(), Some(rtt));
+        assert!(!client.is_connected());
+        assert!(server.uris.try_recv().is_ok());
+
+        // Connected: the ping goes over the existing connection
+        let channel = client.subscribe("news").unwrap();
+        client.connect().await.unwrap();
+        client.wait_for_connection(5).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), channel.on_subscribed())
+            .await
+            .expect("subscription_succeeded not delivered")
+            .unwrap();
+        assert!(server.uris.try_recv().is_ok());
+
+        let rtt = client
+            .probe_connection(Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(rtt > Duration::ZERO);
+        assert!(client.is_connected());
+        assert!(channel.is_subscribed());
+        assert!(server.uris.try_recv().is_err());
+
+        client.disconnect().await;
+    }
+
+    #[tokio::test]
+    async fn test_probe_connection_times_out_without_pong() {
+        use std::time::Duration;
+
+        let server = MockServer::new().replies(|_| Vec::new()).spawn().await;
+        let client = mock_client(server.port);
+        client.connect().await.unwrap();
+        client.wait_for_connection(5).await.unwrap();
+
+        let result = client.probe_connection(Duration::from_millis(100)).await;
+        assert!(matches!(result, Err(SockudoError::ProbeTimeout { .. })));
+        assert_eq!(client.ping_rtt(), None);
+
+        client.disconnect().await;
+    }
+
+    #[tokio::test]
+    async fn test_unsubscribe_all_and_by_prefix() {
+        let (port, mut messages) = spawn_mock_server().await;
+        let client = mock_client(port);
+
+        let names = ["private-room-1", "private-room-2", "news", "updates"];
+        for name in &names[2..] {
+            client.subscribe(name).unwrap();
+        }
+        // Private channels need auth, so mark them subscribed directly
+        for name in &names[..2] {
+            client.subscribe(name).unwrap();
+            let mut event = PusherEvent::new("pusher_internal:subscription_succeeded");
+            event.data = Some("{}".to_string());
+            client.channel(name).unwrap().handle_event(&event);
+        }
+
+        client.connect().await.unwrap();
+        client.wait_for_connection(5).await.unwrap();
+
+        let mut subscribed = Vec::new();
+        while subscribed.len() < 2 {
+            let message = messages.recv().await.unwrap();
+            if message["event"] == "pusher:subscribe" {
+                subscribed.push(message);
+            }
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        client.unsubscribe_by_prefix("private-room-");
+        let mut remaining: Vec<String> = client
+            .all_channels()
+            .iter()
+            .map(|c| c.name().to_string())
+            .collect();
+        remaining.sort();
+        assert_eq!(remaining, vec!["news", "updates"]);
+
+        client.unsubscribe_all();
+        assert!(client.all_channels().is_empty());
+
+        let mut unsubscribed = Vec::new();
+        while unsubscribed.len() < names.len() {
+            let message = tokio::time::timeout(std::time::Duration::from_secs(5), messages.recv())
+                .await
+                .expect("missing pusher:unsubscribe")
+                .unwrap();
+            if message["event"] == "pusher:unsubscribe" {
+                let data: serde_json::Value =
+                    serde_json::from_str(message["data"].as_str().unwrap()).unwrap();
+                unsubscribed.push(data["channel"].as_str().unwrap().to_string());
+            }
+        }
+        unsubscribed[..2].sort();
+        unsubscribed[2..].sort();
+        assert_eq!(unsubscribed, names);
+    }
+
+    #[tokio::test]
+    async fn test_connect_until_retries_with_backoff() {
+        // Refuse the first two handshakes
+        let server = MockServer::new().refuse_first(2).spawn().await;
+        let client = mock_client(server.port);
+        let attempts = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let attempts_clone = attempts.clone();
+        client.bind_global(move |event| {
+            if event.event == "sockudo:reconnect_attempt" {
+                attempts_clone.lock().push(event.data_as_value().unwrap());
+            }
+        });
+
+        client.connect_until(5).await.unwrap();
+        assert!(client.is_connected());
+        assert_eq!(
+            *attempts.lock(),
+            vec![
+                serde_json::json!({ "attempt": 1, "delay_ms": 0 }),
+                serde_json::json!({ "attempt": 2, "delay_ms": 200 }),
+                serde_json::json!({ "attempt": 3, "delay_ms": 400 }),
+            ]
+        );
+
+        let history = client.reconnect_history();
+        let summary: Vec<_> = history
+            .iter()
+            .map(|a| {
+                (
+                    a.attempt_number,
+                    a.delay_before_ms,
+                    a.connected_at.is_some(),
+                    a.error.is_some(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, 0, false, true),
+                (2, 200, false, true),
+                (3, 400, true, false)
+            ]
+        );
+        assert!(history[2].connected_at.unwrap() >= history[2].started_at);
+        assert!(history[1].started_at >= history[0].started_at);
+    }
+
+    #[tokio::test]
+    async fn test_connect_until_gives_up() {
+        let server = MockServer::new().refuse_first(usize::MAX).spawn().await;
+        let client = mock_client(server.port);
+        match client.connect_until(2).await {
+            Err(SockudoError::MaxReconnectAttemptsExceeded { attempts }) => assert_eq!(attempts, 2),
+            other => panic!("expected MaxReconnectAttemptsExceeded, got {:?}", other),
+        }
+        assert!(!client.is_connected());
+    }
+
+    #[tokio::test]
+    async fn test_connect_until_waits_for_activity_timeout() {
+        use std::time::{Duration, Instant};
+
+        // Accepts the socket but never establishes the connection
+        let server = MockServer::new().without_established().spawn().await;
+        let mut options = SockudoOptions::new("test-key")
+            .ws_host("127.0.0.1")
+            .ws_port(server.port)
+            .use_tls(false);
+        options.activity_timeout_ms = Some(300);
+        options.pong_timeout_ms = Some(10_000);
+        #[cfg(feature = "uniffi")]
+        let client = SockudoClient::new(options.into()).unwrap();
+        #[cfg(not(feature = "uniffi"))]
+        let client = SockudoClient::from_options(options).unwrap();
+
+        let started = Instant::now();
+        assert!(matches!(
+            client.connect_until(1).await,
+            Err(SockudoError::MaxReconnectAttemptsExceeded { attempts: 1 })
+        ));
+        let waited = started.elapsed();
+        assert!(waited >= Duration::from_millis(300));
+        assert!(waited < Duration::from_secs(5), "waited {:?}", waited);
+    }
+
+    #[cfg(debug_assertions)]
+    #[tokio::test]
+    async fn test_corrupted_message_emits_checksum_error() {
+        let checked =
+            Protocol::attach_checksum(r#"{"event":"price-update","data":"100"}"#).unwrap();
+        let server = MockServer::new()
+            .then_send(checked.replace("100", "900"))
+            .spawn()
+            .await;
+
+        let options = SockudoOptions::new("test-key")
+            .ws_host("127.0.0.1")
+            .ws_port(server.port)
+            .use_tls(false)
+            .debug(true);
+        #[cfg(feature = "uniffi")]
+        let client = SockudoClient::new(options.into()).unwrap();
+        #[cfg(not(feature = "uniffi"))]
+        let client = SockudoClient::from_options(options).unwrap();
 
-                // Track full message for delta compression
-                if let Some(ref dm) = self.delta_manager {
-                    #[cfg(feature = "wasm")]
-                    let seq_opt = event
-                        .data
-                        .as_ref()
-                        .and_then(|d| d.get("__delta_seq"))
-                        .and_then(|v| v.as_u64());
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        client.bind_global(move |event| {
+            let _ = tx.send(event.clone());
+        });
+        client.connect_until(1).await.unwrap();
+
+        let event = loop {
+            let event = tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_ne!(event.event, "price-update");
+            if event.event == "sockudo:checksum_error" {
+                break event;
+            }
+        };
+        let data = event.data_as_value().unwrap();
+        assert_ne!(data["expected"], data["actual"]);
+        assert!(data["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Message checksum mismatch"));
+    }
 
-                    #[cfg(not(feature = "wasm"))]
-                    let seq_opt = event.data.as_ref().and_then(|d| {
-                        serde_json::from_str::<serde_json::Value>(d)
-                            .ok()
-                            .and_then(|v| v.get("__delta_seq").and_then(|s| s.as_u64()))
-                    });
-
-                    if let Some(seq) = seq_opt {
-                        dm.write().handle_full_message(channel_name, event, seq);
-                    }
+    #[tokio::test]
+    async fn test_per_channel_resubscribe_strategy() {
+        use crate::connection::ExponentialBackoff;
+        use std::time::Duration;
+
+        // Reject the first subscription to each channel, reporting when each
+        // subscribe arrived
+        let latency = Duration::from_millis(50);
+        let (arrived_tx, mut subscribes) = tokio::sync::mpsc::unbounded_channel();
+        let attempts = parking_lot::Mutex::new(std::collections::HashMap::new());
+        let server = MockServer::new()
+            .replies(move |message| {
+                if message["event"] != "pusher:subscribe" {
+                    return Vec::new();
                 }
-            }
+                let data: serde_json::Value =
+                    serde_json::from_str(message["data"].as_str().unwrap()).unwrap();
+                let channel = data["channel"].as_str().unwrap().to_string();
+                let _ = arrived_tx.send((channel.clone(), tokio::time::Instant::now()));
+
+                let mut attempts = attempts.lock();
+                let attempt = attempts.entry(channel.clone()).or_insert(0);
+                *attempt += 1;
+                if *attempt == 1 {
+                    vec![serde_json::json!({
+                        "event": "pusher:subscription_error",
+                        "channel": channel,
+                        "data": r#"{"type":"Unavailable","error":"Try again","status":503}"#,
+                    })]
+                } else {
+                    MockServer::default_replies(message)
+                }
+            })
+            .reply_delay(latency)
+            .spawn()
+            .await;
+        let client = mock_client(server.port);
+
+        let trading = client.subscribe("trading-feed").unwrap();
+        trading.set_reconnect_strategy(Box::new(ExponentialBackoff::fixed(Duration::ZERO)));
+        trading.set_resubscription_timeout(Duration::from_secs(2));
+        let notifications = client.subscribe("notifications").unwrap();
+        notifications
+            .set_reconnect_strategy(Box::new(ExponentialBackoff::fixed(Duration::from_secs(1))));
+        client.connect().await.unwrap();
+
+        let mut arrivals: std::collections::HashMap<String, Vec<tokio::time::Instant>> =
+            std::collections::HashMap::new();
+        while arrivals.values().map(Vec::len).sum::<usize>() < 4 {
+            let (channel, at) = tokio::time::timeout(Duration::from_secs(5), subscribes.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            arrivals.entry(channel).or_default().push(at);
         }
 
-        // Emit to global listeners (except internal events)
-        if !event_name.starts_with("pusher_internal:") {
-            self.global_emitter.emit(event);
-        }
+        let retry_gap = |name: &str| arrivals[name][1] - arrivals[name][0];
+        assert!(retry_gap("trading-feed") < Duration::from_millis(500));
+        assert!(retry_gap("notifications") >= Duration::from_secs(1) + latency);
+
+        tokio::time::timeout(Duration::from_secs(5), trading.on_subscribed())
+            .await
+            .unwrap()
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(5), notifications.on_subscribed())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            trading.resubscription_timeout(),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(notifications.resubscription_timeout(), None);
     }
-}
 
-// WASM-specific methods (outside uniffi export)
-#[cfg(not(target_arch = "wasm32"))]
-#[cfg(all(feature = "wasm", not(feature = "uniffi")))]
-impl SockudoClient {
-    /// Send an event to the server (WASM version).
-    ///
-    /// This is used for client events on private/presence channels.
-    pub fn send_event(
-        &self,
-        event_name: &str,
-        data: &serde_json::Value,
-        channel: Option<&str>,
-    ) -> bool {
-        self.connection.send_event(event_name, data, channel)
-    }
-}
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_subscribe_with_options() {
+        use crate::connection::ExponentialBackoff;
+        use std::time::Duration;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let auth_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/inbox-auth"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"auth":"key:inbox"}"#))
+            .expect(1)
+            .mount(&auth_server)
+            .await;
 
-// Non-uniffi methods (for WASM and other non-FFI builds)
-#[cfg(not(target_arch = "wasm32"))]
-#[cfg(not(feature = "uniffi"))]
-impl SockudoClient {
-    /// Create a new Sockudo client (Pusher-JS compatible API).
-    ///
-    /// # Example
-    /// ```no_run
-    /// use sockudo::{SockudoClient, SockudoOptions};
-    ///
-    /// let client = SockudoClient::new("app-key", SockudoOptions {
-    ///     cluster: Some("mt1".to_string()),
-    ///     ..Default::default()
-    /// }).await.unwrap();
-    /// ```
-    pub async fn new(app_key: impl Into<String>, mut options: SockudoOptions) -> Result<Self> {
-        let app_key = app_key.into();
-        if app_key.is_empty() {
-            return Err(SockudoError::config("App key is required"));
+        let (port, mut received) = spawn_mock_server().await;
+        let client = mock_client(port);
+        client.connect().await.unwrap();
+
+        client
+            .subscribe_with_options(
+                "sports",
+                ChannelSubscribeOptions::default().filter(FilterOp::eq("type", "goal")),
+            )
+            .unwrap();
+        client
+            .subscribe_with_options(
+                "devices",
+                ChannelSubscribeOptions::default().metadata("device_id", "abc"),
+            )
+            .unwrap();
+        client
+            .subscribe_with_options(
+                "private-inbox",
+                ChannelSubscribeOptions::default()
+                    .auth_endpoint(format!("{}/inbox-auth", auth_server.uri())),
+            )
+            .unwrap();
+
+        // Sends may be reordered, so key the requests by channel
+        let mut subscribes = HashMap::new();
+        while subscribes.len() < 3 {
+            let message = tokio::time::timeout(Duration::from_secs(5), received.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            let data: serde_json::Value =
+                serde_json::from_str(message["data"].as_str().unwrap()).unwrap();
+            subscribes.insert(data["channel"].as_str().unwrap().to_string(), data);
         }
+        let (sports, devices, inbox) = (
+            &subscribes["sports"],
+            &subscribes["devices"],
+            &subscribes["private-inbox"],
+        );
+        assert!(sports["tags_filter"].is_object());
+        assert!(sports.get("metadata").is_none());
+        assert_eq!(devices["metadata"], serde_json::json!({"device_id": "abc"}));
+        assert!(devices.get("tags_filter").is_none());
+        assert_eq!(inbox["auth"], "key:inbox");
+
+        let listeners = client
+            .subscribe_with_options("chat", ChannelSubscribeOptions::default().max_listeners(3))
+            .unwrap();
+        assert_eq!(listeners.max_listeners(), 3);
+
+        let retried = client
+            .subscribe_with_options(
+                "trading",
+                ChannelSubscribeOptions::default()
+                    .reconnect_strategy(ExponentialBackoff::default()),
+            )
+            .unwrap();
+        assert!(retried.resubscribe_policy().strategy.is_some());
+        assert_eq!(retried.resubscription_timeout(), None);
+
+        let timed = client
+            .subscribe_with_options(
+                "quotes",
+                ChannelSubscribeOptions::default().subscription_timeout(Duration::from_secs(3)),
+            )
+            .unwrap();
+        assert_eq!(timed.resubscription_timeout(), Some(Duration::from_secs(3)));
+        assert!(timed.resubscribe_policy().strategy.is_none());
+
+        // Re-subscribing without options keeps earlier settings
+        assert_eq!(client.subscribe("chat").unwrap().max_listeners(), 3);
+        client.unsubscribe("private-inbox");
+        assert!(client.auth_endpoint_overrides.read().is_empty());
+    }
 
-        // Set the app_key in options
-        options.app_key = app_key.clone();
-
-        // Create the client
-        let client = Self::from_options(options)?;
-
-        // Auto-connect (Pusher-JS behavior)
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            client.connect().await?;
+    #[tokio::test]
+    async fn test_subscribe_with_options_times_out_unconfirmed_subscription() {
+        use crate::channels::ChannelState;
+        use std::time::Duration;
 
-            // Wait for connection to be established (with timeout)
-            use tokio::time::{timeout, Duration};
-            let wait_result = timeout(Duration::from_secs(10), async {
-                while !client.is_connected() {
-                    tokio::time::sleep(Duration::from_millis(100)).await;
-                }
+        // Never confirms a subscription
+        let server = MockServer::new()
+            .replies(|message| match message["event"].as_str() {
+                Some("pusher:subscribe") => Vec::new(),
+                _ => MockServer::default_replies(message),
             })
+            .spawn()
             .await;
+        let client = mock_client(server.port);
+        let timeout =
+            || ChannelSubscribeOptions::default().subscription_timeout(Duration::from_millis(100));
+
+        // Deferred while disconnected, then bounded once connected
+        let deferred = client
+            .subscribe_with_options("deferred", timeout())
+            .unwrap();
+        assert_eq!(client.pending_subscriptions(), vec!["deferred".to_string()]);
+        client.connect_until(1).await.unwrap();
+
+        let direct = client.subscribe_with_options("direct", timeout()).unwrap();
+        assert_eq!(direct.state(), ChannelState::Subscribing);
+
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert_eq!(direct.state(), ChannelState::Failed);
+        assert_eq!(deferred.state(), ChannelState::Failed);
+    }
 
-            if wait_result.is_err() {
-                return Err(SockudoError::connection(
-                    "Connection timeout - failed to connect within 10 seconds",
-                ));
-            }
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_channel_auth_endpoints_without_global_endpoint() {
+        use crate::options::ChannelConfig;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let auth_server = MockServer::start().await;
+        for (endpoint, auth) in [("/team-auth", "key:team"), ("/inbox-auth", "key:inbox")] {
+            Mock::given(method("POST"))
+                .and(path(endpoint))
+                .respond_with(
+                    ResponseTemplate::new(200).set_body_string(format!(r#"{{"auth":"{}"}}"#, auth)),
+                )
+                .expect(1)
+                .mount(&auth_server)
+                .await;
         }
 
-        Ok(client)
+        let options = SockudoOptions::new("test-key")
+            .auth_endpoint("")
+            .channel_prefix_config(
+                "private-team-",
+                ChannelConfig {
+                    auth_endpoint: Some(format!("{}/team-auth", auth_server.uri())),
+                    ..ChannelConfig::default()
+                },
+            )
+            .channel_shared_secret("private-encrypted-known", vec![7; 32]);
+        let overrides = Arc::new(RwLock::new(HashMap::new()));
+        overrides.write().insert(
+            "private-inbox".to_string(),
+            format!("{}/inbox-auth", auth_server.uri()),
+        );
+        let authorize = channel_authorizer(&options, overrides);
+
+        // Prefix and per-subscription endpoints apply without a global one
+        assert_eq!(authorize("private-team-a", "1.1").unwrap().auth, "key:team");
+        assert_eq!(authorize("private-inbox", "1.1").unwrap().auth, "key:inbox");
+        assert!(matches!(
+            authorize("private-other", "1.1"),
+            Err(SockudoError::AuthorizationError { .. })
+        ));
+        // A known shared secret still subscribes without an endpoint
+        assert_eq!(
+            authorize("private-encrypted-known", "1.1").unwrap().auth,
+            ""
+        );
     }
 
-    /// Create a new Sockudo client from options without auto-connecting.
-    ///
-    /// This is useful for testing or when you want manual control over the connection.
-    /// For normal use, prefer `SockudoClient::new()` which auto-connects like Pusher-JS.
-    pub fn from_options(options: SockudoOptions) -> Result<Self> {
-        if options.app_key.is_empty() {
-            return Err(SockudoError::config("App key is required"));
+    #[tokio::test]
+    async fn test_migrate_channel() {
+        use std::time::Duration;
+
+        // Every confirmed subscription is followed by a price on that channel
+        let server = MockServer::new()
+            .replies(|message| {
+                let mut replies = MockServer::default_replies(message);
+                if message["event"] == "pusher:subscribe" {
+                    replies.push(serde_json::json!({
+                        "event": "price",
+                        "channel": replies[0]["channel"],
+                        "data": "100",
+                    }));
+                }
+                replies
+            })
+            .spawn()
+            .await;
+        let mut received = server.received;
+        let client = mock_client(server.port);
+        let channel = client.subscribe("feed-a").unwrap();
+        let (events_tx, mut events) = tokio::sync::mpsc::unbounded_channel();
+        let binding = channel
+            .bind("price", move |event| {
+                let _ = events_tx.send(event.channel.clone());
+            })
+            .unwrap();
+        client.connect().await.unwrap();
+
+        async fn next_event(
+            events: &mut tokio::sync::mpsc::UnboundedReceiver<Option<String>>,
+        ) -> Option<String> {
+            tokio::time::timeout(Duration::from_secs(5), events.recv())
+                .await
+                .unwrap()
+                .unwrap()
         }
+        assert_eq!(next_event(&mut events).await.as_deref(), Some("feed-a"));
 
-        let config: Config = options.clone().into();
-        let config = Arc::new(config);
+        client.subscribe("feed-c").unwrap();
+        match client.migrate_channel("feed-a", "feed-c") {
+            Err(SockudoError::ChannelAlreadyExists { channel }) => assert_eq!(channel, "feed-c"),
+            other => panic!("expected ChannelAlreadyExists, got {:?}", other),
+        }
 
-        // Create channels with callbacks
-        let mut channels = Channels::new();
+        client.migrate_channel("feed-a", "feed-b").unwrap();
+        assert!(client.channel("feed-a").is_none());
+        assert!(Arc::ptr_eq(&client.channel("feed-b").unwrap(), &channel));
+        assert_eq!(channel.current_name(), "feed-b");
+        assert_eq!(channel.name(), "feed-b");
 
-        // Create delta manager if enabled
-        let delta_manager = if let Some(delta_opts) = config.delta_compression.clone() {
-            if delta_opts.enabled {
-                Some(Arc::new(RwLock::new(DeltaManager::new(delta_opts))))
-            } else {
-                None
+        // Callbacks bound under the old name fire for the new one
+        assert_eq!(next_event(&mut events).await.as_deref(), Some("feed-b"));
+
+        let mut unsubscribed = Vec::new();
+        let mut subscribed = Vec::new();
+        while !subscribed.contains(&"feed-b".to_string())
+            || !unsubscribed.contains(&"feed-a".to_string())
+        {
+            let message = tokio::time::timeout(Duration::from_secs(5), received.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            let data: serde_json::Value =
+                serde_json::from_str(message["data"].as_str().unwrap()).unwrap();
+            let name = data["channel"].as_str().unwrap().to_string();
+            match message["event"].as_str().unwrap() {
+                "pusher:subscribe" => subscribed.push(name),
+                "pusher:unsubscribe" => unsubscribed.push(name),
+                _ => {}
             }
-        } else {
-            None
-        };
+        }
 
-        // Generate session ID
-        let session_id = rand::random::<u32>();
+        channel.unbind(Some("price"), Some(binding));
+        assert!(!channel.has_listeners("price"));
+    }
 
-        info!(
-            "Creating Sockudo client for app '{}' (session: {})",
-            options.app_key, session_id
-        );
+    #[cfg(feature = "uniffi")]
+    #[tokio::test]
+    async fn test_connection_callback_order() {
+        use crate::connection::ConnectionState::*;
+        use std::time::Duration;
 
-        // Create event dispatcher
-        let global_emitter = EventDispatcher::new();
+        struct Recorder(Arc<parking_lot::Mutex<Vec<String>>>);
 
-        // Create connection manager
-        let connection = Arc::new(ConnectionManager::new((*config).clone()));
+        impl ConnectionCallback for Recorder {
+            fn on_state_changed(&self, previous: ConnectionState, current: ConnectionState) {
+                self.0.lock().push(format!("{} -> {}", previous, current));
+            }
 
-        // Set up send callback for channels
-        let connection_clone = connection.clone();
-        channels.set_send_callback(Arc::new(move |event_name, data, channel| {
-            let mut event = PusherEvent::new(event_name);
-            event.data = Some(data.clone());
-            event.channel = channel.map(|s| s.to_string());
+            fn on_error(&self, error: String) {
+                self.0.lock().push(format!("error: {}", error));
+            }
 
-            match Protocol::encode_message(&event) {
-                Ok(msg) => connection_clone.send(&msg),
-                Err(_) => false,
+            fn on_socket_id_assigned(&self, socket_id: String) {
+                self.0.lock().push(format!("socket_id: {}", socket_id));
             }
-        }));
+        }
 
-        // Set up send callback for delta manager
-        if let Some(ref dm) = delta_manager {
-            let connection_for_delta = connection.clone();
-            dm.write()
-                .set_send_callback(Arc::new(move |event_name, data| {
-                    let mut event = PusherEvent::new(event_name);
-                    #[cfg(feature = "wasm")]
-                    {
-                        event.data = Some(data.clone());
-                    }
-                    #[cfg(not(feature = "wasm"))]
-                    {
-                        event.data = Some(data.to_string());
-                    }
+        let (port, _messages) = spawn_mock_server().await;
+        let client = mock_client(port);
+        let log = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        client.set_connection_callback(Box::new(Recorder(log.clone())));
+
+        client.connect_until(1).await.unwrap();
+        let channel = client.subscribe("updates").unwrap();
+        let subscribed_log = log.clone();
+        channel
+            .bind("pusher:subscription_succeeded", move |_| {
+                subscribed_log.lock().push("subscribed".to_string());
+            })
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(5), channel.on_subscribed())
+            .await
+            .unwrap()
+            .unwrap();
+        client.disconnect().await;
+
+        let expected = [
+            format!("{} -> {}", Initialized, Connecting),
+            "socket_id: 123.456".to_string(),
+            format!("{} -> {}", Connecting, Connected),
+            "subscribed".to_string(),
+            format!("{} -> {}", Connected, Disconnected),
+        ];
+        assert_eq!(*log.lock(), expected);
+    }
 
-                    match Protocol::encode_message(&event) {
-                        Ok(msg) => connection_for_delta.send(&msg),
-                        Err(_) => false,
-                    }
-                }));
-        }
+    #[test]
+    fn test_statistics_snapshot_diff() {
+        use std::time::Duration;
 
-        // Set up authorization callback for private/presence channels
-        // Authorization callback is only needed for native builds
-        // WASM uses async authorization directly in subscribe_async
-        #[cfg(not(target_arch = "wasm32"))]
-        if !config.auth_endpoint.is_empty() {
-            let auth_client = Arc::new(AuthClient::new(
-                Some(config.auth_endpoint.clone()),
-                Some(config.auth_headers.clone()),
-                None,
-                None,
-            ));
+        let options = SockudoOptions::new("test-key");
+        #[cfg(feature = "uniffi")]
+        let client = SockudoClient::new(options.into()).unwrap();
+        #[cfg(not(feature = "uniffi"))]
+        let client = SockudoClient::from_options(options).unwrap();
+        client.subscribe("feed").unwrap();
+        let live = client.statistics_snapshot();
+        assert_eq!(live.channel_counts.get(&ChannelType::Public), Some(&1));
+        assert!(serde_json::to_value(&live).is_ok());
+
+        let mut earlier = live.clone();
+        earlier.connection = ConnectionStats {
+            messages_sent: 10,
+            messages_received: 100,
+            bytes_sent: 1_000,
+            bytes_received: 20_000,
+            reconnect_attempts: 0,
+            heartbeats_received: 0,
+            delta_messages_received: 0,
+        };
+        let mut later = earlier.clone();
+        later.timestamp = earlier.timestamp + Duration::from_secs(1);
+        later.connection.messages_sent = 15;
+        later.connection.messages_received = 160;
+        later.connection.bytes_received = 26_000;
+        later.dispatch.total_dispatches = 60;
+        later.channel_counts.insert(ChannelType::Private, 2);
+        later
+            .recent_reconnects
+            .push(ReconnectAttempt::started(1, later.timestamp, 0));
+
+        let diff = later.diff(&earlier);
+        assert_eq!(diff.elapsed, Duration::from_secs(1));
+        assert_eq!(diff.messages_sent, 5);
+        assert_eq!(diff.messages_received, 60);
+        assert_eq!(diff.bytes_sent, 0);
+        assert_eq!(diff.bytes_received, 6_000);
+        assert_eq!(diff.dispatches, 60);
+        assert_eq!(diff.reconnect_attempts, 1);
+        assert_eq!(diff.channel_change, 2);
+        assert_eq!(diff.per_second(diff.messages_received), 60.0);
+        assert!(diff
+            .to_string()
+            .starts_with("over 1.0s: sent 5 msgs (5.0/s)"));
+
+        // Counters reset between snapshots never go negative
+        assert_eq!(earlier.diff(&later).messages_received, 0);
+    }
 
-            channels.set_authorize_callback(Arc::new(move |channel_name, socket_id| {
-                tokio::task::block_in_place(|| {
-                    tokio::runtime::Handle::current().block_on(async {
-                        auth_client.authorize_channel(channel_name, socket_id).await
-                    })
-                })
-            }));
+    #[test]
+    fn test_delta_telemetry_callbacks() {
+        let full = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let decoded = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let full_clone = full.clone();
+        let decoded_clone = decoded.clone();
+        let telemetry = crate::delta::DeltaOptions::builder()
+            .on_full_message(move |channel, bytes| {
+                full_clone.lock().push((channel.to_string(), bytes));
+            })
+            .on_delta_decoded(move |channel, algorithm, delta_bytes, decoded_bytes| {
+                decoded_clone.lock().push((
+                    channel.to_string(),
+                    algorithm.to_string(),
+                    delta_bytes,
+                    decoded_bytes,
+                ));
+            });
+
+        let options = SockudoOptions::new("test-key")
+            .ws_host("127.0.0.1")
+            .ws_port(1)
+            .use_tls(false)
+            .delta_compression(crate::delta::DeltaOptions {
+                algorithms: vec![DeltaAlgorithm::Fossil],
+                ..Default::default()
+            });
+        #[cfg(feature = "uniffi")]
+        let client = SockudoClient::new(options.into()).unwrap();
+        #[cfg(not(feature = "uniffi"))]
+        let client = SockudoClient::from_options(options).unwrap();
+        {
+            // FFI options drop closures, so install the hooks directly
+            let mut dm = client.delta_manager.as_ref().unwrap().write();
+            dm.set_on_full_message(telemetry.on_full_message.clone());
+            dm.set_on_delta_decoded(telemetry.on_delta_decoded.clone());
         }
+        client.subscribe("market-data").unwrap();
 
-        Ok(Self {
-            key: options.app_key,
-            config,
-            channels: Arc::new(channels),
-            connection,
-            delta_manager,
-            global_emitter,
-            session_id,
-        })
-    }
+        let base = r#"{"__delta_seq":1,"price":100}"#;
+        let target = r#"{"__delta_seq":2,"price":101}"#;
+        client.replay_local(
+            "market-data",
+            PusherEvent::new("price").with_string_data(base),
+        );
+        let delta =
+            crate::delta::encode_base64(&fossil_delta::delta(target.as_bytes(), base.as_bytes()));
+        client.replay_local(
+            "market-data",
+            PusherEvent::new("pusher:delta").with_json_data(serde_json::json!({
+                "event": "price",
+                "delta": delta,
+                "seq": 2,
+                "algorithm": "fossil",
+            })),
+        );
 
-    /// Get the current connection state.
-    pub fn state(&self) -> ConnectionState {
-        self.connection.state()
+        assert_eq!(*full.lock(), vec![("market-data".to_string(), base.len())]);
+        assert_eq!(
+            *decoded.lock(),
+            vec![(
+                "market-data".to_string(),
+                "fossil".to_string(),
+                delta.len(),
+                target.len()
+            )]
+        );
     }
 
-    /// Get the socket ID assigned by the server.
-    pub fn socket_id(&self) -> Option<String> {
-        self.connection.socket_id()
-    }
-}
+    #[tokio::test]
+    async fn test_event_log_keeps_most_recent_events() {
+        let mut server = MockServer::new();
+        for n in 0..20 {
+            let tick = serde_json::json!({
+                "event": "tick",
+                "channel": "ticker",
+                "data": format!("n={}", n),
+            });
+            server = server.then_send(tick.to_string());
+        }
+        let server = server.spawn().await;
+
+        let client = mock_client(server.port);
+        let log = client.event_log(10);
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        client.bind_global(move |event| {
+            if event.event == "tick" {
+                let _ = tx.send(event.clone());
+            }
+        });
+        client.connect_until(1).await.unwrap();
 
-#[cfg(not(target_arch = "wasm32"))]
-impl std::fmt::Debug for SockudoClient {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("SockudoClient")
-            .field("key", &self.key)
-            .field("session_id", &self.session_id)
-            .field("state", &self.state())
-            .field("socket_id", &self.socket_id())
-            .field("channel_count", &self.channels.len())
-            .finish()
+        for _ in 0..20 {
+            tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv())
+                .await
+                .unwrap()
+                .unwrap();
+        }
+
+        let logged = log.snapshot();
+        let data: Vec<_> = logged.iter().map(|e| e.data.clone().unwrap()).collect();
+        let expected: Vec<_> = (10..20).map(|n| format!("n={}", n)).collect();
+        assert_eq!(data, expected);
+        assert!(logged
+            .iter()
+            .all(|e| e.event == "tick" && e.channel.as_deref() == Some("ticker")));
+
+        let callbacks = client.global_emitter.callback_count();
+        drop(log);
+        assert_eq!(client.global_emitter.callback_count(), callbacks - 1);
     }
-}
 
-// Make SockudoClient Send + Sync for use across threads
-#[cfg(not(target_arch = "wasm32"))]
-unsafe impl Send for SockudoClient {}
-#[cfg(not(target_arch = "wasm32"))]
-unsafe impl Sync for SockudoClient {}
+    #[test]
+    fn test_callback_memory_usage() {
+        let client = mock_client(1);
+        let channel = client.subscribe("news").unwrap();
+        let baseline = client.total_callback_count();
+
+        let ids: Vec<u64> = (0..100)
+            .map(|i| channel.bind(format!("event-{}", i % 4), |_| {}).unwrap())
+            .collect();
+        let usage = channel.callback_memory_usage();
+        assert_eq!(usage.total_callbacks, 100);
+        assert_eq!(usage.total_events, 4);
+        assert_eq!(client.total_callback_count(), baseline + 100);
+
+        for id in ids {
+            channel.unbind(None, Some(id));
+        }
+        assert_eq!(channel.callback_memory_usage().total_callbacks, 0);
+        assert_eq!(client.total_callback_count(), baseline);
+
+        // One-time callbacks count until they fire
+        let global = client.global_callback_memory_usage();
+        channel.bind_once("ready", |_| {}).unwrap();
+        let usage = channel.callback_memory_usage();
+        assert_eq!(usage.total_callbacks, 1);
+        assert_eq!(usage.expiry_callbacks, 1);
+        assert_eq!(client.total_callback_count(), baseline + 1);
+
+        channel.handle_event(&PusherEvent::new("ready").with_channel("news"));
+        assert_eq!(channel.callback_memory_usage().total_callbacks, 0);
+        assert_eq!(channel.callback_memory_usage().expiry_callbacks, 0);
+        assert_eq!(client.total_callback_count(), baseline);
+        assert_eq!(client.global_callback_memory_usage(), global);
+    }
 
-#[cfg(all(test, not(target_arch = "wasm32")))]
-mod tests {
-    use crate::ChannelType;
+    #[test]
+    fn test_channel_snapshots() {
+        let client = mock_client(1);
+        client.subscribe("news").unwrap();
+        client.subscribe("alerts").unwrap();
+        client.channel("news").unwrap().handle_event(
+            &PusherEvent::new("pusher_internal:subscription_succeeded")
+                .with_json_data(serde_json::json!({})),
+        );
 
-    use super::*;
+        let snapshots = client.channel_snapshots();
+        let names: Vec<_> = snapshots.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["alerts", "news"]);
+        assert_ne!(
+            snapshots[0].state,
+            crate::channels::ChannelState::Subscribed
+        );
+        assert_eq!(
+            snapshots[1].state,
+            crate::channels::ChannelState::Subscribed
+        );
+        assert!(snapshots[1].subscribed_at.is_some());
+    }
 
     #[test]
-    fn test_client_creation() {
-        let options = SockudoOptions::new("test-key").cluster("mt1");
+    fn test_offline_mode_echoes_client_events() {
+        let options = SockudoOptions::new("test-key").offline_echo_events(true);
         #[cfg(feature = "uniffi")]
         let client = SockudoClient::new(options.into()).unwrap();
         #[cfg(not(feature = "uniffi"))]
         let client = SockudoClient::from_options(options).unwrap();
 
-        assert_eq!(client.key(), "test-key");
-        assert_eq!(client.state(), ConnectionState::Initialized);
+        // Added before going offline: subscribed once offline mode starts
+        let early = client.subscribe("private-early").unwrap();
+        assert!(!early.is_subscribed());
+
+        client.enable_offline_mode().unwrap();
+        assert_eq!(client.state(), ConnectionState::Connected);
+        assert!(client.socket_id().is_some());
+        assert!(early.is_subscribed());
+
+        // No auth endpoint is configured, yet the private channel subscribes
+        let channel = client.subscribe("private-room").unwrap();
+        assert!(channel.is_subscribed());
+
+        let received = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let received_clone = received.clone();
+        channel
+            .bind("client-message", move |event| {
+                received_clone.lock().push(event.data_as_value().unwrap());
+            })
+            .unwrap();
+
+        assert!(channel
+            .trigger("client-message", r#"{"text":"hello"}"#.to_string())
+            .unwrap());
+        client.replay_local(
+            "private-room",
+            PusherEvent::new("client-message")
+                .with_json_data(serde_json::json!({"text": "replayed"})),
+        );
+        assert_eq!(
+            *received.lock(),
+            vec![
+                serde_json::json!({"text": "hello"}),
+                serde_json::json!({"text": "replayed"}),
+            ]
+        );
+
+        client.disable_offline_mode();
+        assert_eq!(client.state(), ConnectionState::Disconnected);
+        assert!(client.socket_id().is_none());
+        assert!(!channel.is_subscribed());
+        assert!(!channel
+            .trigger("client-message", r#"{"text":"lost"}"#.to_string())
+            .unwrap());
+        assert_eq!(received.lock().len(), 2);
     }
 
     #[test]
-    fn test_client_requires_key() {
-        let options = SockudoOptions::default();
-        #[cfg(feature = "uniffi")]
-        let result = SockudoClient::new(options.into());
-        #[cfg(not(feature = "uniffi"))]
-        let result = SockudoClient::new(options);
-        assert!(result.is_err());
+    fn test_offline_mode_without_echo_drops_sends() {
+        let client = mock_client(1);
+        client.enable_offline_mode().unwrap();
+
+        let channel = client.subscribe("private-room").unwrap();
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let calls_clone = calls.clone();
+        channel
+            .bind("client-message", move |_| {
+                calls_clone.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            })
+            .unwrap();
+
+        assert!(channel.trigger("client-message", "{}".to_string()).unwrap());
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 0);
     }
 
     #[test]
-    fn test_subscribe() {
-        let options = SockudoOptions::new("test-key");
-        #[cfg(feature = "uniffi")]
-        let client = SockudoClient::new(options.into()).unwrap();
-        #[cfg(not(feature = "uniffi"))]
-        let client = SockudoClient::from_options(options).unwrap();
+    fn test_offline_mode_subscribes_presence_and_encrypted_channels() {
+        let client = mock_client(1);
+        client.enable_offline_mode().unwrap();
+
+        let presence = client.channels.add("presence-room").unwrap();
+        let succeeded = Arc::new(parking_lot::Mutex::new(None));
+        let succeeded_clone = succeeded.clone();
+        presence
+            .bind("pusher:subscription_succeeded", move |event| {
+                *succeeded_clone.lock() = event.data_as_value();
+            })
+            .unwrap();
+        client.subscribe("presence-room").unwrap();
+        assert!(presence.is_subscribed());
+        assert_eq!(
+            *succeeded.lock(),
+            Some(serde_json::json!({ "members": [], "count": 0, "myID": null }))
+        );
+        assert_eq!(
+            client
+                .channels
+                .find_presence("presence-room")
+                .unwrap()
+                .member_count(),
+            0
+        );
 
-        let channel = client.subscribe("test-channel").unwrap();
-        assert_eq!(channel.name(), "test-channel");
-        assert_eq!(channel.channel_type(), ChannelType::Public);
+        assert!(client
+            .subscribe("private-encrypted-room")
+            .unwrap()
+            .is_subscribed());
     }
 
     #[test]
-    fn test_invalid_channel_name() {
-        let options = SockudoOptions::new("test-key");
+    fn test_rate_limit_middleware_drops_excess_sends() {
+        let options = SockudoOptions::new("test-key").offline_echo_events(true);
         #[cfg(feature = "uniffi")]
         let client = SockudoClient::new(options.into()).unwrap();
         #[cfg(not(feature = "uniffi"))]
         let client = SockudoClient::from_options(options).unwrap();
+        let client = client.with_middleware(Box::new(crate::RateLimitMiddleware::new(10)));
+        client.enable_offline_mode().unwrap();
+
+        // The subscribe request is a protocol event and is not limited
+        let channel = client.subscribe("private-room").unwrap();
+        assert!(channel.is_subscribed());
+
+        let received = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let received_clone = received.clone();
+        channel
+            .bind("client-message", move |_| {
+                received_clone.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            })
+            .unwrap();
 
-        let result = client.subscribe("#invalid");
-        assert!(result.is_err());
+        let sent: Vec<bool> = (0..11)
+            .map(|i| {
+                channel
+                    .trigger("client-message", format!(r#"{{"n":{}}}"#, i))
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(sent[..10], [true; 10]);
+        assert!(!sent[10]);
+        assert_eq!(received.load(std::sync::atomic::Ordering::SeqCst), 10);
+    }
+
+    #[test]
+    fn test_middleware_rewrites_incoming_events() {
+        struct Upper;
+        impl ClientMiddleware for Upper {
+            fn after_receive(&self, event: &mut PusherEvent) -> bool {
+                if event.event == "noise" {
+                    return false;
+                }
+                event.event = event.event.to_uppercase();
+                true
+            }
+        }
+
+        let client = mock_client(1);
+        client.add_middleware(Box::new(Upper));
+        let received = Arc::new(RwLock::new(Vec::new()));
+        let received_clone = received.clone();
+        client.connection.bind_global(move |event| {
+            received_clone.write().push(event.event.clone());
+        });
+
+        client
+            .connection
+            .inject_message(r#"{"event":"noise","channel":"room","data":"{}"}"#)
+            .unwrap();
+        client
+            .connection
+            .inject_message(r#"{"event":"update","channel":"room","data":"{}"}"#)
+            .unwrap();
+        assert_eq!(*received.read(), vec!["UPDATE".to_string()]);
     }
 }
 
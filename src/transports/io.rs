@@ -0,0 +1,212 @@
+//! `AsyncRead`/`AsyncWrite` adapter over a `Transport`.
+//!
+//! Lets callers layer their own protocol (e.g. custom binary framing) over
+//! the WebSocket. Incoming text frames are appended to a read buffer; each
+//! write is sent as one text frame.
+
+use super::Transport;
+use bytes::{Buf, BytesMut};
+use parking_lot::Mutex;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+type SendFuture = Pin<Box<dyn Future<Output = crate::error::Result<usize>> + Send>>;
+
+/// Bytes received from the transport, waiting to be read
+#[derive(Default)]
+struct ReadState {
+    buffer: BytesMut,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+/// Byte stream over a connected transport.
+///
+/// Reads return the concatenated contents of received text frames and
+/// reach end of file when the transport closes. Writes must be valid
+/// UTF-8, since each is sent as a text frame.
+pub struct TransportIO {
+    transport: Arc<dyn Transport>,
+    read_state: Arc<Mutex<ReadState>>,
+    pending_send: Option<SendFuture>,
+}
+
+impl TransportIO {
+    /// Wrap `transport`, replacing its message and close callbacks
+    pub fn from_transport(mut transport: Box<dyn Transport>) -> Self {
+        let read_state = Arc::new(Mutex::new(ReadState::default()));
+
+        let state = read_state.clone();
+        transport.on_message(Box::new(move |message| {
+            let mut state = state.lock();
+            state.buffer.extend_from_slice(message.as_bytes());
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        }));
+
+        let state = read_state.clone();
+        transport.on_close(Box::new(move |_code, _reason| {
+            let mut state = state.lock();
+            state.closed = true;
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        }));
+
+        Self {
+            transport: Arc::from(transport),
+            read_state,
+            pending_send: None,
+        }
+    }
+}
+
+impl AsyncRead for TransportIO {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let mut state = self.read_state.lock();
+        if !state.buffer.is_empty() {
+            let n = state.buffer.len().min(buf.remaining());
+            buf.put_slice(&state.buffer[..n]);
+            state.buffer.advance(n);
+            return Poll::Ready(Ok(()));
+        }
+        if state.closed {
+            return Poll::Ready(Ok(()));
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl AsyncWrite for TransportIO {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        if self.pending_send.is_none() {
+            let text = std::str::from_utf8(buf)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+                .to_string();
+            let transport = self.transport.clone();
+            self.pending_send = Some(Box::pin(async move {
+                transport.send(&text).await.map(|_| text.len())
+            }));
+        }
+
+        let result = match self.pending_send.as_mut() {
+            Some(send) => std::task::ready!(send.as_mut().poll(cx)),
+            None => unreachable!("send future was just created"),
+        };
+        self.pending_send = None;
+        Poll::Ready(result.map_err(io::Error::other))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl std::fmt::Debug for TransportIO {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TransportIO")
+            .field("connected", &self.transport.is_connected())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transports::MessageCallback;
+    use async_trait::async_trait;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    type CloseCallback = Box<dyn Fn(Option<u16>, Option<String>) + Send + Sync>;
+
+    /// Transport whose callbacks the test drives directly
+    #[derive(Default)]
+    struct MockTransport {
+        sent: Arc<Mutex<Vec<String>>>,
+        on_message: Arc<Mutex<Option<MessageCallback>>>,
+        on_close: Arc<Mutex<Option<CloseCallback>>>,
+    }
+
+    #[async_trait]
+    impl Transport for MockTransport {
+        async fn connect(&mut self, _url: &str) -> crate::error::Result<()> {
+            Ok(())
+        }
+
+        async fn disconnect(&mut self) {}
+
+        async fn close_with_code(&mut self, _code: u16, _reason: &str) -> crate::error::Result<()> {
+            Ok(())
+        }
+
+        async fn send(&self, message: &str) -> crate::error::Result<()> {
+            self.sent.lock().push(message.to_string());
+            Ok(())
+        }
+
+        async fn ping(&self) -> crate::error::Result<()> {
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        fn on_message(&mut self, callback: MessageCallback) {
+            *self.on_message.lock() = Some(callback);
+        }
+
+        fn on_close(&mut self, callback: CloseCallback) {
+            *self.on_close.lock() = Some(callback);
+        }
+
+        fn on_error(&mut self, _callback: Box<dyn Fn(String) + Send + Sync>) {}
+    }
+
+    #[tokio::test]
+    async fn test_transport_io_reads_and_writes() {
+        let transport = MockTransport::default();
+        let sent = transport.sent.clone();
+        let on_message = transport.on_message.clone();
+        let on_close = transport.on_close.clone();
+        let mut io = TransportIO::from_transport(Box::new(transport));
+
+        let inject = tokio::spawn(async move {
+            tokio::task::yield_now().await;
+            if let Some(ref callback) = *on_message.lock() {
+                callback("hello ");
+                callback("world");
+            }
+            if let Some(ref callback) = *on_close.lock() {
+                callback(Some(1000), None);
+            }
+        });
+
+        let mut received = String::new();
+        io.read_to_string(&mut received).await.unwrap();
+        inject.await.unwrap();
+        assert_eq!(received, "hello world");
+
+        io.write_all(b"frame").await.unwrap();
+        assert_eq!(*sent.lock(), vec!["frame".to_string()]);
+        assert!(io.write_all(&[0xff, 0xfe]).await.is_err());
+    }
+}
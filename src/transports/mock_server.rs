@@ -0,0 +1,222 @@
+//! Scripted Pusher server for tests.
+//!
+//! Listens on a random local port and speaks just enough of the protocol to
+//! drive a real client: each handshake is answered with
+//! `pusher:connection_established`, subscribes are confirmed, pings answered,
+//! and every frame a client sends is handed back to the test.
+
+use futures_util::{SinkExt, StreamExt};
+use parking_lot::Mutex;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+use tokio_tungstenite::tungstenite::Message;
+
+type Replies = Arc<dyn Fn(&Value) -> Vec<Value> + Send + Sync>;
+
+/// What a [`MockServer`] does with each connection
+pub(crate) struct MockServer {
+    refuse: usize,
+    socket_ids: Vec<String>,
+    handshake: bool,
+    greeting: Vec<Message>,
+    replies: Replies,
+    reply_delay: Duration,
+}
+
+impl Default for MockServer {
+    fn default() -> Self {
+        Self {
+            refuse: 0,
+            socket_ids: vec!["123.456".to_string()],
+            handshake: true,
+            greeting: Vec::new(),
+            replies: Arc::new(Self::default_replies),
+            reply_delay: Duration::ZERO,
+        }
+    }
+}
+
+impl MockServer {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop the first `count` connections before the WebSocket handshake
+    pub(crate) fn refuse_first(mut self, count: usize) -> Self {
+        self.refuse = count;
+        self
+    }
+
+    /// Socket IDs handed out per connection, the last one repeating
+    pub(crate) fn socket_ids(mut self, ids: &[&str]) -> Self {
+        self.socket_ids = ids.iter().map(|id| id.to_string()).collect();
+        self
+    }
+
+    /// Don't send `pusher:connection_established` after the handshake
+    pub(crate) fn without_established(mut self) -> Self {
+        self.handshake = false;
+        self
+    }
+
+    /// Send `frame` on every connection once it is established
+    pub(crate) fn then_send(mut self, frame: impl Into<Message>) -> Self {
+        self.greeting.push(frame.into());
+        self
+    }
+
+    /// Answer each client message with `replies` instead of
+    /// [`MockServer::default_replies`]
+    pub(crate) fn replies(
+        mut self,
+        replies: impl Fn(&Value) -> Vec<Value> + Send + Sync + 'static,
+    ) -> Self {
+        self.replies = Arc::new(replies);
+        self
+    }
+
+    /// Wait `delay` before sending the replies to a client message
+    pub(crate) fn reply_delay(mut self, delay: Duration) -> Self {
+        self.reply_delay = delay;
+        self
+    }
+
+    /// Confirm `pusher:subscribe` and answer `pusher:ping`
+    pub(crate) fn default_replies(message: &Value) -> Vec<Value> {
+        match message["event"].as_str() {
+            Some("pusher:subscribe") => {
+                let data: Value = serde_json::from_str(message["data"].as_str().unwrap()).unwrap();
+                vec![serde_json::json!({
+                    "event": "pusher_internal:subscription_succeeded",
+                    "channel": data["channel"],
+                    "data": "{}",
+                })]
+            }
+            Some("pusher:ping") => vec![serde_json::json!({
+                "event": "pusher:pong",
+                "data": "{}",
+            })],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Start accepting connections in the background
+    pub(crate) async fn spawn(self) -> MockServerHandle {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (received_tx, received) = unbounded_channel();
+        let (uris_tx, uris) = unbounded_channel();
+        let current = Arc::new(Mutex::new(None));
+
+        let server = Arc::new(self);
+        let latest = current.clone();
+        tokio::spawn(async move {
+            let mut accepted = 0;
+            while let Ok((socket, _)) = listener.accept().await {
+                accepted += 1;
+                if accepted <= server.refuse {
+                    continue;
+                }
+                let index = (accepted - server.refuse - 1).min(server.socket_ids.len() - 1);
+                tokio::spawn(server.clone().serve(
+                    socket,
+                    server.socket_ids[index].clone(),
+                    uris_tx.clone(),
+                    received_tx.clone(),
+                    latest.clone(),
+                ));
+            }
+        });
+
+        MockServerHandle {
+            port,
+            received,
+            uris,
+            current,
+        }
+    }
+
+    async fn serve(
+        self: Arc<Self>,
+        socket: TcpStream,
+        socket_id: String,
+        uris: UnboundedSender<String>,
+        received: UnboundedSender<Value>,
+        current: Arc<Mutex<Option<UnboundedSender<Message>>>>,
+    ) {
+        #[allow(clippy::result_large_err)]
+        let callback = |req: &Request, res: Response| {
+            let _ = uris.send(req.uri().to_string());
+            Ok(res)
+        };
+        let Ok(ws) = tokio_tungstenite::accept_hdr_async(socket, callback).await else {
+            return;
+        };
+        let (mut sink, mut stream) = ws.split();
+        let (tx, mut outgoing) = unbounded_channel::<Message>();
+        *current.lock() = Some(tx.clone());
+        tokio::spawn(async move {
+            while let Some(frame) = outgoing.recv().await {
+                if sink.send(frame).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        if self.handshake {
+            let established = serde_json::json!({
+                "event": "pusher:connection_established",
+                "data": format!(r#"{{"socket_id":"{}","activity_timeout":120}}"#, socket_id),
+            });
+            let _ = tx.send(Message::Text(established.to_string()));
+        }
+        for frame in &self.greeting {
+            let _ = tx.send(frame.clone());
+        }
+
+        while let Some(Ok(frame)) = stream.next().await {
+            let Message::Text(text) = frame else {
+                continue;
+            };
+            let message = serde_json::from_str(&text).unwrap_or(Value::String(text));
+            let replies = (self.replies)(&message);
+            if !replies.is_empty() && !self.reply_delay.is_zero() {
+                tokio::time::sleep(self.reply_delay).await;
+            }
+            for reply in replies {
+                let _ = tx.send(Message::Text(reply.to_string()));
+            }
+            let _ = received.send(message);
+        }
+    }
+}
+
+/// A running [`MockServer`]
+pub(crate) struct MockServerHandle {
+    pub(crate) port: u16,
+    /// Every frame the clients sent; frames that aren't JSON arrive as strings
+    pub(crate) received: UnboundedReceiver<Value>,
+    /// Request URI of every handshake
+    pub(crate) uris: UnboundedReceiver<String>,
+    current: Arc<Mutex<Option<UnboundedSender<Message>>>>,
+}
+
+impl MockServerHandle {
+    /// Send `frame` on the most recent connection
+    pub(crate) fn send(&self, frame: impl Into<Message>) {
+        let current = self.current.lock();
+        let _ = current
+            .as_ref()
+            .expect("no connection yet")
+            .send(frame.into());
+    }
+
+    /// Close the most recent connection
+    pub(crate) fn close(&self) {
+        self.send(Message::Close(None));
+    }
+}
@@ -16,7 +16,21 @@ pub use transport::{MessageCallback, Transport};
 pub mod native;
 
 #[cfg(feature = "native")]
-pub use native::NativeTransport;
+pub use native::{ConnectionInfo, NativeTransport};
+
+/// `AsyncRead`/`AsyncWrite` adapter over a transport
+#[cfg(feature = "io-adapter")]
+pub mod io;
+
+#[cfg(feature = "io-adapter")]
+pub use io::TransportIO;
+
+/// TLS configuration helpers for native transports
+pub(crate) mod tls;
+
+/// Scripted Pusher server for tests
+#[cfg(all(test, feature = "native"))]
+pub(crate) mod mock_server;
 
 /// WASM WebSocket transport (web-sys)
 #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
@@ -5,19 +5,152 @@
 use async_trait::async_trait;
 use futures_util::{SinkExt, StreamExt};
 use parking_lot::RwLock;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::mpsc;
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::TcpStream;
+use tokio::sync::oneshot;
+use tokio::sync::{mpsc, watch, Notify};
+use tokio_socks::tcp::Socks5Stream;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::{CloseFrame, Message};
+use tokio_tungstenite::{client_async_tls_with_config, Connector, MaybeTlsStream};
 use tracing::{debug, error, info};
 
 use super::transport::{MessageCallback, Transport};
 use crate::error::{Result, SockudoError};
+use crate::options::ProxyConfig;
+
+/// How long `resume` waits for a pong before treating the socket as dead
+const RESUME_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Command to send to the WebSocket writer task
 enum WriteCommand {
-    SendText(String),
+    /// Text frame, with a slot for the result of the write
+    SendText(String, oneshot::Sender<Result<()>>),
     SendPing,
     Close,
+    CloseWithCode(u16, String),
+}
+
+/// Time of the last byte read from a socket
+struct ReadActivity {
+    started: Instant,
+    last_read_ms: AtomicU64,
+}
+
+impl ReadActivity {
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            last_read_ms: AtomicU64::new(0),
+        }
+    }
+
+    fn touch(&self) {
+        let now = self.started.elapsed().as_millis() as u64;
+        self.last_read_ms.store(now, Ordering::Relaxed);
+    }
+
+    /// Time since the last byte was read
+    fn idle(&self) -> Duration {
+        let last = Duration::from_millis(self.last_read_ms.load(Ordering::Relaxed));
+        self.started.elapsed().saturating_sub(last)
+    }
+}
+
+/// Stream wrapper that records every successful read in a `ReadActivity`
+struct ActivityStream<S> {
+    inner: S,
+    activity: Arc<ReadActivity>,
+}
+
+impl<S> ActivityStream<S> {
+    fn new(inner: S, activity: Arc<ReadActivity>) -> Self {
+        activity.touch();
+        Self { inner, activity }
+    }
+
+    fn get_ref(&self) -> &S {
+        &self.inner
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for ActivityStream<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let filled = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if matches!(poll, Poll::Ready(Ok(()))) && buf.filled().len() > filled {
+            self.activity.touch();
+        }
+        poll
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for ActivityStream<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+/// Low-level details of an established connection, for diagnostics
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    pub local_addr: Option<SocketAddr>,
+    pub peer_addr: Option<SocketAddr>,
+    /// Negotiated TLS version (e.g. "TLSv1_3"), `None` for `ws://`
+    pub tls_version: Option<String>,
+    /// Negotiated cipher suite, `None` for `ws://`
+    pub cipher_suite: Option<String>,
+    /// HTTP version of the upgrade response (e.g. "HTTP/1.1")
+    pub http_version: Option<String>,
+    /// Time taken by the TCP, TLS and WebSocket handshakes
+    pub handshake_duration_ms: u64,
+}
+
+impl ConnectionInfo {
+    fn from_stream(stream: &MaybeTlsStream<ActivityStream<TcpStream>>, started: Instant) -> Self {
+        let mut info = Self {
+            handshake_duration_ms: started.elapsed().as_millis() as u64,
+            ..Self::default()
+        };
+
+        let tcp = match stream {
+            MaybeTlsStream::Plain(tcp) => tcp.get_ref(),
+            MaybeTlsStream::Rustls(tls) => {
+                let (tcp, session) = tls.get_ref();
+                info.tls_version = session.protocol_version().map(|v| format!("{:?}", v));
+                info.cipher_suite = session
+                    .negotiated_cipher_suite()
+                    .map(|suite| format!("{:?}", suite.suite()));
+                tcp.get_ref()
+            }
+            _ => return info,
+        };
+        info.local_addr = tcp.local_addr().ok();
+        info.peer_addr = tcp.peer_addr().ok();
+        info
+    }
 }
 
 /// Native WebSocket transport
@@ -32,6 +165,20 @@ pub struct NativeTransport {
     on_close: Arc<RwLock<Option<Box<dyn Fn(Option<u16>, Option<String>) + Send + Sync>>>>,
     /// Error callback
     on_error: Arc<RwLock<Option<Box<dyn Fn(String) + Send + Sync>>>>,
+    /// Optional proxy to tunnel the connection through
+    proxy: Option<ProxyConfig>,
+    /// Custom TLS configuration for `wss://` connections
+    tls_config: Option<Arc<rustls::ClientConfig>>,
+    /// Details of the current connection, set once the handshake completes
+    connection_info: Arc<RwLock<Option<ConnectionInfo>>>,
+    /// Reader stops polling the socket while this is `true`
+    paused: Arc<watch::Sender<bool>>,
+    /// Notified whenever a pong frame arrives
+    pong: Arc<Notify>,
+    /// Time a single write may take, if limited
+    write_timeout: Option<Duration>,
+    /// Time the socket may stay silent, if limited
+    read_timeout: Option<Duration>,
 }
 
 impl NativeTransport {
@@ -43,21 +190,146 @@ impl NativeTransport {
             on_message: Arc::new(RwLock::new(None)),
             on_close: Arc::new(RwLock::new(None)),
             on_error: Arc::new(RwLock::new(None)),
+            proxy: None,
+            tls_config: None,
+            connection_info: Arc::new(RwLock::new(None)),
+            paused: Arc::new(watch::channel(false).0),
+            pong: Arc::new(Notify::new()),
+            write_timeout: None,
+            read_timeout: None,
+        }
+    }
+
+    /// Create a native transport that connects through a proxy
+    pub fn with_proxy(proxy: Option<ProxyConfig>) -> Self {
+        Self {
+            proxy,
+            ..Self::new()
+        }
+    }
+
+    /// Use a custom TLS configuration instead of the bundled web PKI roots
+    pub fn with_tls_config(mut self, tls_config: Option<Arc<rustls::ClientConfig>>) -> Self {
+        self.tls_config = tls_config;
+        self
+    }
+
+    /// Fail sends whose write does not complete within `timeout`.
+    ///
+    /// A timed out write leaves a partial frame on the socket, so the
+    /// connection is dropped and reported through the error callback.
+    pub fn set_write_timeout(&mut self, timeout: Duration) {
+        self.write_timeout = Some(timeout);
+    }
+
+    /// Drop the connection when no bytes arrive for `timeout`.
+    ///
+    /// Any incoming data resets the timer, including partial frames. Takes
+    /// effect on the next `connect`.
+    pub fn set_read_timeout(&mut self, timeout: Duration) {
+        self.read_timeout = Some(timeout);
+    }
+
+    /// Record connection details into a slot shared with the caller
+    pub(crate) fn with_connection_info_slot(
+        mut self,
+        slot: Arc<RwLock<Option<ConnectionInfo>>>,
+    ) -> Self {
+        self.connection_info = slot;
+        self
+    }
+
+    /// Details of the current connection, `None` until a handshake completes
+    pub fn connection_info(&self) -> Option<ConnectionInfo> {
+        self.connection_info.read().clone()
+    }
+
+    /// Stop reading from the socket without closing it.
+    ///
+    /// Incoming frames queue up in the OS buffers (and then on the server)
+    /// until `resume` is called.
+    pub fn pause(&self) {
+        debug!("Pausing reader");
+        self.paused.send_replace(true);
+    }
+
+    /// Resume reading and check that the socket is still alive.
+    ///
+    /// Sends a ping and waits for the pong; returns `false` if the server
+    /// closed the connection while paused or did not answer in time.
+    pub async fn resume(&self) -> bool {
+        debug!("Resuming reader");
+        let pong = self.pong.notified();
+        tokio::pin!(pong);
+        pong.as_mut().enable();
+        self.paused.send_replace(false);
+
+        if self.ping().await.is_err() {
+            return false;
         }
+
+        let probe = async {
+            loop {
+                tokio::select! {
+                    _ = &mut pong => return true,
+                    _ = tokio::time::sleep(Duration::from_millis(10)) => {
+                        if !self.is_connected() {
+                            return false;
+                        }
+                    }
+                }
+            }
+        };
+        tokio::time::timeout(RESUME_PROBE_TIMEOUT, probe)
+            .await
+            .unwrap_or(false)
     }
 
     /// Spawn reader and writer tasks
-    fn spawn_tasks(&self, url: String) -> Result<()> {
+    ///
+    /// When `stream` is provided (e.g. a proxy tunnel) the WebSocket handshake
+    /// runs over it instead of opening a direct connection.
+    fn spawn_tasks(&self, url: String, stream: Option<TcpStream>) -> Result<()> {
         let on_message = self.on_message.clone();
         let on_close = self.on_close.clone();
         let on_error = self.on_error.clone();
         let connected = self.connected.clone();
         let write_tx_arc = self.write_tx.clone();
+        let connector = self.tls_config.clone().map(Connector::Rustls);
+        let connection_info = self.connection_info.clone();
+        let mut paused = self.paused.subscribe();
+        let pong = self.pong.clone();
+        let write_timeout = self.write_timeout;
+        let read_timeout = self.read_timeout;
+        let activity = Arc::new(ReadActivity::new());
 
         tokio::spawn(async move {
             // Connect
-            let ws_stream = match connect_async(&url).await {
-                Ok((stream, _)) => stream,
+            let started = Instant::now();
+            let connect_result = async {
+                let stream = match stream {
+                    Some(stream) => stream,
+                    None => {
+                        let (host, port) = target_addr(&url)?;
+                        let host = host.trim_start_matches('[').trim_end_matches(']');
+                        TcpStream::connect((host, port))
+                            .await
+                            .map_err(|e| SockudoError::connection(e.to_string()))?
+                    }
+                };
+                let stream = ActivityStream::new(stream, activity.clone());
+                Ok::<_, SockudoError>(
+                    client_async_tls_with_config(url.as_str(), stream, None, connector).await?,
+                )
+            }
+            .await;
+            let ws_stream = match connect_result {
+                Ok((stream, response)) => {
+                    let mut info = ConnectionInfo::from_stream(stream.get_ref(), started);
+                    info.http_version = Some(format!("{:?}", response.version()));
+                    *connection_info.write() = Some(info);
+                    stream
+                }
                 Err(e) => {
                     error!("WebSocket connection failed: {:?}", e);
                     *connected.write() = false;
@@ -80,20 +352,38 @@ impl NativeTransport {
 
             // Spawn writer task
             let connected_clone = connected.clone();
+            let on_error_clone = on_error.clone();
             tokio::spawn(async move {
                 while let Some(cmd) = write_rx.recv().await {
                     let result = match cmd {
-                        WriteCommand::SendText(text) => {
+                        WriteCommand::SendText(text, done) => {
                             debug!("Sending text: {}", text);
-                            writer.send(Message::Text(text)).await
+                            let result =
+                                send_with_timeout(&mut writer, Message::Text(text), write_timeout)
+                                    .await;
+                            let _ = done.send(result.clone());
+                            result
                         }
                         WriteCommand::SendPing => {
                             debug!("Sending ping");
-                            writer.send(Message::Ping(vec![])).await
+                            send_with_timeout(&mut writer, Message::Ping(vec![]), write_timeout)
+                                .await
                         }
                         WriteCommand::Close => {
                             debug!("Closing connection");
-                            let _ = writer.send(Message::Close(None)).await;
+                            let _ =
+                                send_with_timeout(&mut writer, Message::Close(None), write_timeout)
+                                    .await;
+                            break;
+                        }
+                        WriteCommand::CloseWithCode(code, reason) => {
+                            debug!("Closing connection with code {}: {}", code, reason);
+                            let frame = CloseFrame {
+                                code: CloseCode::from(code),
+                                reason: reason.into(),
+                            };
+                            let close = Message::Close(Some(frame));
+                            let _ = send_with_timeout(&mut writer, close, write_timeout).await;
                             break;
                         }
                     };
@@ -101,6 +391,11 @@ impl NativeTransport {
                     if let Err(e) = result {
                         error!("Write error: {:?}", e);
                         *connected_clone.write() = false;
+                        if let SockudoError::WriteTimeout { .. } = e {
+                            if let Some(ref callback) = *on_error_clone.read() {
+                                callback(e.to_string());
+                            }
+                        }
                         break;
                     }
                 }
@@ -109,7 +404,47 @@ impl NativeTransport {
 
             // Reader task (runs in this task)
             loop {
-                match reader.next().await {
+                if *paused.borrow_and_update() {
+                    debug!("Reader paused");
+                    if paused.wait_for(|p| !*p).await.is_err() {
+                        break;
+                    }
+                    debug!("Reader resumed");
+                    activity.touch();
+                }
+
+                let silence = async {
+                    match read_timeout {
+                        Some(limit) => {
+                            tokio::time::sleep(limit.saturating_sub(activity.idle())).await
+                        }
+                        None => std::future::pending().await,
+                    }
+                };
+
+                let next = tokio::select! {
+                    next = reader.next() => next,
+                    changed = paused.changed() => {
+                        if changed.is_err() {
+                            break;
+                        }
+                        continue;
+                    }
+                    _ = silence => {
+                        let idle = activity.idle();
+                        if read_timeout.is_some_and(|limit| idle >= limit) {
+                            error!("No data received for {:?}", idle);
+                            *connected.write() = false;
+                            if let Some(ref callback) = *on_error.read() {
+                                callback(format!("Read timed out after {:?}", idle));
+                            }
+                            break;
+                        }
+                        continue;
+                    }
+                };
+
+                match next {
                     Some(Ok(message)) => match message {
                         Message::Text(text) => {
                             debug!("Received text message: {}", text);
@@ -140,6 +475,7 @@ impl NativeTransport {
                         }
                         Message::Pong(_) => {
                             debug!("Received pong");
+                            pong.notify_waiters();
                         }
                         Message::Frame(_) => {
                             debug!("Received raw frame");
@@ -177,6 +513,71 @@ impl NativeTransport {
     }
 }
 
+/// Write one frame, giving up after `limit`
+async fn send_with_timeout<S>(
+    writer: &mut S,
+    message: Message,
+    limit: Option<Duration>,
+) -> Result<()>
+where
+    S: futures_util::Sink<Message, Error = tokio_tungstenite::tungstenite::Error> + Unpin,
+{
+    match limit {
+        Some(limit) => tokio::time::timeout(limit, writer.send(message))
+            .await
+            .map_err(|_| SockudoError::write_timeout(limit))?
+            .map_err(SockudoError::from),
+        None => writer.send(message).await.map_err(SockudoError::from),
+    }
+}
+
+/// Host and port a WebSocket URL points at
+fn target_addr(url: &str) -> Result<(String, u16)> {
+    let target = url::Url::parse(url)?;
+    let host = target
+        .host_str()
+        .ok_or_else(|| SockudoError::config(format!("URL has no host: {}", url)))?
+        .to_string();
+    let port = target
+        .port_or_known_default()
+        .ok_or_else(|| SockudoError::config(format!("URL has no port: {}", url)))?;
+    Ok((host, port))
+}
+
+/// Open a TCP connection to the WebSocket host through a SOCKS5 proxy
+pub(crate) async fn connect_socks5(proxy: &ProxyConfig, url: &str) -> Result<TcpStream> {
+    let ProxyConfig::Socks5 {
+        host,
+        port,
+        username,
+        password,
+    } = proxy;
+
+    let (target_host, target_port) = target_addr(url)?;
+
+    let proxy_addr = (host.as_str(), *port);
+    let target_addr = (target_host.as_str(), target_port);
+
+    let stream = match (username, password) {
+        (Some(username), Some(password)) => {
+            Socks5Stream::connect_with_password(proxy_addr, target_addr, username, password).await
+        }
+        _ => Socks5Stream::connect(proxy_addr, target_addr).await,
+    }
+    .map_err(|e| {
+        SockudoError::proxy(format!(
+            "SOCKS5 handshake with {}:{} failed: {}",
+            host, port, e
+        ))
+    })?;
+
+    debug!(
+        "SOCKS5 tunnel to {}:{} established via {}:{}",
+        target_host, target_port, host, port
+    );
+    Ok(stream.into_inner())
+}
+
 impl Default for NativeTransport {
     fn default() -> Self {
         Self::new()
@@ -190,9 +591,21 @@ impl Transport for NativeTransport {
             return Err(SockudoError::invalid_state("Already connected"));
         }
 
-        info!("Connecting to WebSocket: {}", url);
+        *self.connection_info.write() = None;
+        self.paused.send_replace(false);
+
+        // Query parameters may carry credentials, so only log the endpoint
+        info!(
+            "Connecting to WebSocket: {}",
+            url.split('?').next().unwrap_or(url)
+        );
 
-        self.spawn_tasks(url.to_string())?;
+        let stream = match self.proxy {
+            Some(ref proxy) => Some(connect_socks5(proxy, url).await?),
+            None => None,
+        };
+
+        self.spawn_tasks(url.to_string(), stream)?;
 
         // Wait a bit for connection to establish
         for _ in 0..50 {
@@ -223,6 +636,30 @@ impl Transport for NativeTransport {
         info!("WebSocket disconnected");
     }
 
+    async fn close_with_code(&mut self, code: u16, reason: &str) -> Result<()> {
+        if reason.len() > 123 {
+            return Err(SockudoError::config(
+                "Close reason must not exceed 123 bytes",
+            ));
+        }
+
+        if !self.is_connected() {
+            return Err(SockudoError::invalid_state("Not connected"));
+        }
+
+        info!("Closing WebSocket with code {}: {}", code, reason);
+
+        let tx = self.write_tx.write().take();
+        if let Some(tx) = tx {
+            tx.send(WriteCommand::CloseWithCode(code, reason.to_string()))
+                .await
+                .map_err(|e| SockudoError::websocket(format!("Close failed: {:?}", e)))?;
+        }
+
+        *self.connected.write() = false;
+        Ok(())
+    }
+
     async fn send(&self, message: &str) -> Result<()> {
         if !self.is_connected() {
             return Err(SockudoError::invalid_state("Not connected"));
@@ -231,14 +668,30 @@ impl Transport for NativeTransport {
         debug!("Sending message: {}", message);
 
         let tx = self.write_tx.read().as_ref().cloned();
-        if let Some(tx) = tx {
-            tx.send(WriteCommand::SendText(message.to_string()))
+        let Some(tx) = tx else {
+            return Err(SockudoError::invalid_state("Writer not available"));
+        };
+
+        let (done_tx, done_rx) = oneshot::channel();
+        let queued = tx.send(WriteCommand::SendText(message.to_string(), done_tx));
+        let Some(limit) = self.write_timeout else {
+            return queued
+                .await
+                .map_err(|e| SockudoError::websocket(format!("Send failed: {:?}", e)));
+        };
+
+        // Wait for the frame to reach the socket so a stuck write surfaces here
+        let write = async {
+            queued
                 .await
                 .map_err(|e| SockudoError::websocket(format!("Send failed: {:?}", e)))?;
-            Ok(())
-        } else {
-            Err(SockudoError::invalid_state("Writer not available"))
-        }
+            done_rx
+                .await
+                .map_err(|_| SockudoError::websocket("Writer task ended"))?
+        };
+        tokio::time::timeout(limit, write)
+            .await
+            .map_err(|_| SockudoError::write_timeout(limit))?
     }
 
     async fn ping(&self) -> Result<()> {
@@ -275,3 +728,298 @@ impl Transport for NativeTransport {
         *self.on_error.write() = Some(callback);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Minimal SOCKS5 server that validates the CONNECT request and then
+    /// answers with `reply` (0x00 = succeeded)
+    async fn mini_socks5_server(reply: u8) -> (u16, tokio::task::JoinHandle<Vec<u8>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+
+            // Greeting: VER, NMETHODS, METHODS...
+            let mut header = [0u8; 2];
+            socket.read_exact(&mut header).await.unwrap();
+            assert_eq!(header[0], 0x05);
+            let mut methods = vec![0u8; header[1] as usize];
+            socket.read_exact(&mut methods).await.unwrap();
+            assert!(methods.contains(&0x00));
+            socket.write_all(&[0x05, 0x00]).await.unwrap();
+
+            // Request: VER, CMD, RSV, ATYP=domain, LEN, HOST, PORT
+            let mut request = [0u8; 5];
+            socket.read_exact(&mut request).await.unwrap();
+            let mut rest = vec![0u8; request[4] as usize + 2];
+            socket.read_exact(&mut rest).await.unwrap();
+
+            socket
+                .write_all(&[0x05, reply, 0x00, 0x01, 127, 0, 0, 1, 0, 0])
+                .await
+                .unwrap();
+
+            let mut received = request.to_vec();
+            received.extend_from_slice(&rest);
+            received
+        });
+
+        (port, handle)
+    }
+
+    #[tokio::test]
+    async fn test_socks5_connect_sends_connect_command() {
+        let (port, server) = mini_socks5_server(0x00).await;
+        let proxy = ProxyConfig::Socks5 {
+            host: "127.0.0.1".to_string(),
+            port,
+            username: None,
+            password: None,
+        };
+
+        let result = connect_socks5(&proxy, "ws://ws.example.com:6001/app/key").await;
+        assert!(result.is_ok());
+
+        let request = server.await.unwrap();
+        let mut expected = vec![0x05, 0x01, 0x00, 0x03, 14];
+        expected.extend_from_slice(b"ws.example.com");
+        expected.extend_from_slice(&6001u16.to_be_bytes());
+        assert_eq!(request, expected);
+    }
+
+    #[tokio::test]
+    async fn test_connection_info_after_connect() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+            while ws.next().await.is_some() {}
+        });
+
+        let mut transport = NativeTransport::new();
+        assert!(transport.connection_info().is_none());
+
+        transport
+            .connect(&format!("ws://127.0.0.1:{}", port))
+            .await
+            .unwrap();
+
+        let info = transport.connection_info().unwrap();
+        assert_eq!(info.peer_addr.unwrap().port(), port);
+        assert!(info.local_addr.is_some());
+        assert_eq!(info.http_version.as_deref(), Some("HTTP/1.1"));
+        // Plain ws:// has no TLS session
+        assert!(info.tls_version.is_none());
+        assert!(info.cipher_suite.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_close_with_code_sends_close_frame() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+            match ws.next().await {
+                Some(Ok(Message::Close(Some(frame)))) => {
+                    (u16::from(frame.code), frame.reason.to_string())
+                }
+                other => panic!("expected close frame, got {:?}", other),
+            }
+        });
+
+        let mut transport = NativeTransport::new();
+        transport
+            .connect(&format!("ws://127.0.0.1:{}", port))
+            .await
+            .unwrap();
+        transport
+            .close_with_code(4001, "client shutdown")
+            .await
+            .unwrap();
+
+        assert!(!transport.is_connected());
+        let (code, reason) = server.await.unwrap();
+        assert_eq!(code, 4001);
+        assert_eq!(reason, "client shutdown");
+    }
+
+    #[tokio::test]
+    async fn test_write_timeout_on_black_hole_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // Completes the handshake, then never reads again
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let _ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+            std::future::pending::<()>().await;
+        });
+
+        let errors = Arc::new(RwLock::new(Vec::new()));
+        let errors_clone = errors.clone();
+        let mut transport = NativeTransport::new();
+        transport.set_write_timeout(Duration::from_millis(200));
+        transport.on_error(Box::new(move |error| errors_clone.write().push(error)));
+        transport
+            .connect(&format!("ws://127.0.0.1:{}", port))
+            .await
+            .unwrap();
+
+        // Fill the socket buffers until a write can no longer complete
+        let chunk = "x".repeat(1024 * 1024);
+        let mut result = Ok(());
+        for _ in 0..256 {
+            result = transport.send(&chunk).await;
+            if result.is_err() {
+                break;
+            }
+        }
+
+        match result {
+            Err(SockudoError::WriteTimeout { timeout }) => {
+                assert_eq!(timeout, Duration::from_millis(200))
+            }
+            other => panic!("expected WriteTimeout, got {:?}", other),
+        }
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(!transport.is_connected());
+        assert!(errors
+            .read()
+            .iter()
+            .any(|e| e.starts_with("Write timed out")));
+    }
+
+    #[tokio::test]
+    async fn test_read_timeout_resets_on_partial_frames() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+            // Trickle one text frame out a byte at a time, slower in total
+            // than the read timeout but faster than it between bytes
+            let socket = ws.get_mut();
+            socket.write_all(&[0x81, 0x05]).await.unwrap();
+            for byte in b"hello" {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                socket.write_all(&[*byte]).await.unwrap();
+            }
+            std::future::pending::<()>().await;
+        });
+
+        let messages = Arc::new(RwLock::new(Vec::new()));
+        let messages_clone = messages.clone();
+        let errors = Arc::new(RwLock::new(Vec::new()));
+        let errors_clone = errors.clone();
+        let mut transport = NativeTransport::new();
+        transport.set_read_timeout(Duration::from_millis(400));
+        transport.on_message(Box::new(move |text| {
+            messages_clone.write().push(text.to_string())
+        }));
+        transport.on_error(Box::new(move |error| errors_clone.write().push(error)));
+        transport
+            .connect(&format!("ws://127.0.0.1:{}", port))
+            .await
+            .unwrap();
+
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        assert_eq!(*messages.read(), vec!["hello".to_string()]);
+        assert!(errors.read().is_empty());
+        assert!(transport.is_connected());
+
+        // Nothing more arrives, so the connection is dropped
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert!(!transport.is_connected());
+        assert!(errors
+            .read()
+            .iter()
+            .any(|e| e.starts_with("Read timed out")));
+    }
+
+    /// Spawn a single-connection `wss://` server with a self-signed certificate
+    async fn spawn_self_signed_server() -> (u16, String) {
+        use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert_pem = cert.serialize_pem().unwrap();
+        let server_config = rustls::ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![CertificateDer::from(cert.serialize_der().unwrap())],
+                PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(cert.serialize_private_key_der())),
+            )
+            .unwrap();
+        let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(server_config));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let tls = acceptor.accept(socket).await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tls).await.unwrap();
+            while let Some(Ok(message)) = ws.next().await {
+                if message.is_close() {
+                    break;
+                }
+            }
+        });
+
+        (port, cert_pem)
+    }
+
+    #[tokio::test]
+    async fn test_tls_accept_self_signed() {
+        let (port, _) = spawn_self_signed_server().await;
+        let options = crate::options::SockudoOptions::new("key").accept_self_signed(true);
+
+        let mut transport = NativeTransport::new().with_tls_config(options.tls_config);
+        transport
+            .connect(&format!("wss://localhost:{}", port))
+            .await
+            .unwrap();
+        assert!(transport.is_connected());
+        transport.disconnect().await;
+    }
+
+    #[tokio::test]
+    async fn test_tls_custom_root_certificate() {
+        let (port, cert_pem) = spawn_self_signed_server().await;
+        let options = crate::options::SockudoOptions::new("key")
+            .add_root_certificate(&cert_pem)
+            .unwrap();
+
+        let mut transport = NativeTransport::new().with_tls_config(options.tls_config);
+        transport
+            .connect(&format!("wss://localhost:{}", port))
+            .await
+            .unwrap();
+        assert!(transport.is_connected());
+        transport.disconnect().await;
+    }
+
+    #[tokio::test]
+    async fn test_socks5_handshake_failure_is_proxy_error() {
+        // 0x05 = connection refused
+        let (port, _server) = mini_socks5_server(0x05).await;
+        let proxy = ProxyConfig::Socks5 {
+            host: "127.0.0.1".to_string(),
+            port,
+            username: None,
+            password: None,
+        };
+
+        let result = connect_socks5(&proxy, "ws://ws.example.com/app/key").await;
+        assert!(matches!(result, Err(SockudoError::ProxyError { .. })));
+    }
+}
@@ -20,6 +20,13 @@ pub trait Transport: Send + Sync {
     /// Disconnect from the server
     async fn disconnect(&mut self);
 
+    /// Close the connection with a close code and reason.
+    ///
+    /// Standard codes: 1000 (normal closure), 1001 (going away).
+    /// Codes 4000-4999 are reserved for application use. The reason must
+    /// not exceed 123 bytes.
+    async fn close_with_code(&mut self, code: u16, reason: &str) -> Result<()>;
+
     /// Send a text message
     async fn send(&self, message: &str) -> Result<()>;
 
@@ -49,6 +56,13 @@ pub trait Transport {
     /// Disconnect from the server
     async fn disconnect(&mut self);
 
+    /// Close the connection with a close code and reason.
+    ///
+    /// Standard codes: 1000 (normal closure), 1001 (going away).
+    /// Codes 4000-4999 are reserved for application use. The reason must
+    /// not exceed 123 bytes.
+    async fn close_with_code(&mut self, code: u16, reason: &str) -> Result<()>;
+
     /// Send a text message
     async fn send(&self, message: &str) -> Result<()>;
 
@@ -67,3 +81,11 @@ pub trait Transport {
     /// Set error callback
     fn on_error(&mut self, callback: Box<dyn Fn(String)>);
 }
+
+impl std::fmt::Debug for dyn Transport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Transport")
+            .field("connected", &self.is_connected())
+            .finish()
+    }
+}
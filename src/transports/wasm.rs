@@ -206,6 +206,23 @@ impl Transport for WasmTransport {
         info!("WebSocket disconnected");
     }
 
+    async fn close_with_code(&mut self, code: u16, reason: &str) -> Result<()> {
+        if !self.is_connected() {
+            return Err(SockudoError::invalid_state("Not connected"));
+        }
+
+        info!("Closing WebSocket with code {}: {}", code, reason);
+
+        if let Some(ws) = self.ws.write().take() {
+            ws.close_with_code_and_reason(code, reason)
+                .map_err(|e| SockudoError::websocket(format!("Close failed: {:?}", e)))?;
+        }
+
+        *self.connected.write() = false;
+        self._closures.write().clear();
+        Ok(())
+    }
+
     async fn send(&self, message: &str) -> Result<()> {
         if !self.is_connected() {
             return Err(SockudoError::invalid_state("Not connected"));
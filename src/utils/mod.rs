@@ -2,11 +2,15 @@
 
 pub mod collections;
 #[cfg(all(not(target_arch = "wasm32"), feature = "native"))]
+pub mod retry;
+#[cfg(all(not(target_arch = "wasm32"), feature = "native"))]
 pub mod signals;
 pub mod timers;
 
 pub use collections::*;
 #[cfg(all(not(target_arch = "wasm32"), feature = "native"))]
+pub use retry::*;
+#[cfg(all(not(target_arch = "wasm32"), feature = "native"))]
 pub use signals::*;
 #[cfg(not(target_arch = "wasm32"))]
 pub use timers::*;
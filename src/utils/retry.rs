@@ -0,0 +1,97 @@
+//! Retrying fallible operations.
+
+use crate::connection::ReconnectStrategy;
+use crate::error::Result;
+use std::future::Future;
+use tokio::time::sleep;
+
+/// Run `f` until it succeeds, fails with a permanent error, or `policy`
+/// gives up.
+///
+/// Only errors whose [`is_retriable`](crate::SockudoError::is_retriable)
+/// is true are retried. The wait is the policy's delay, or the server's
+/// `Retry-After` when that is longer. The last error is returned when the
+/// policy runs out of attempts.
+pub async fn retry_with_policy<F, Fut, T>(mut f: F, policy: &dyn ReconnectStrategy) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        let error = match f().await {
+            Ok(value) => return Ok(value),
+            Err(error) if error.is_retriable() => error,
+            Err(error) => return Err(error),
+        };
+        attempt += 1;
+        let Some(delay) = policy.next_delay(attempt) else {
+            return Err(error);
+        };
+        sleep(error.retry_after().map_or(delay, |after| after.max(delay))).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::ExponentialBackoff;
+    use crate::error::SockudoError;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_retry_with_policy() {
+        let policy = ExponentialBackoff::fixed(Duration::from_millis(1)).max_attempts(3);
+        let calls = AtomicU32::new(0);
+        let result = retry_with_policy(
+            || async {
+                match calls.fetch_add(1, Ordering::SeqCst) {
+                    0 => Err(SockudoError::connection("dropped")),
+                    1 => Err(SockudoError::authorization_failed(
+                        "unavailable",
+                        503,
+                        HashMap::new(),
+                    )),
+                    n => Ok(n),
+                }
+            },
+            &policy,
+        )
+        .await;
+        assert_eq!(result.unwrap(), 2);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // Permanent errors are returned without retrying
+        calls.store(0, Ordering::SeqCst);
+        let result: Result<()> = retry_with_policy(
+            || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(SockudoError::invalid_channel("bad name"))
+            },
+            &policy,
+        )
+        .await;
+        assert!(matches!(result, Err(SockudoError::InvalidChannel { .. })));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_error_classification() {
+        let headers = HashMap::from([("retry-after".to_string(), "7".to_string())]);
+        let unavailable = SockudoError::authorization_failed("unavailable", 503, headers);
+        assert!(unavailable.is_retriable());
+        assert_eq!(unavailable.retry_after(), Some(Duration::from_secs(7)));
+
+        assert!(
+            !SockudoError::authorization_failed("forbidden", 403, HashMap::new()).is_retriable()
+        );
+        assert!(SockudoError::delta("bad base").is_retriable());
+        assert!(!SockudoError::config("missing key").is_retriable());
+        assert!(!SockudoError::invalid_event_name("a b", "space").is_retriable());
+        assert!(SockudoError::timeout("slow")
+            .context(&"dump")
+            .is_retriable());
+    }
+}
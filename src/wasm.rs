@@ -8,14 +8,88 @@
 use js_sys::{Array, Function};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use wasm_bindgen::JsValue;
 
 use crate::delta::{decoders, DeltaAlgorithm, DeltaOptions, DeltaStats};
-use crate::options::SockudoOptions;
+use crate::options::{AuthSignatureMode, SockudoOptions};
+use crate::protocol::filter::CompareOp;
 use crate::protocol::filter::FilterOp as InternalFilterOp;
+use crate::protocol::{validate_channel_name, Protocol};
+
+/// Console log level, stored as the `LogLevel` discriminant
+static LOG_LEVEL: std::sync::atomic::AtomicU8 =
+    std::sync::atomic::AtomicU8::new(LogLevel::Debug as u8);
+
+thread_local! {
+    /// Options applied to clients created after `WasmSockudo.setGlobalDefaults`
+    static GLOBAL_DEFAULTS: std::cell::RefCell<Option<WasmOptions>> = const { std::cell::RefCell::new(None) };
+}
+
+/// Severity of a console message, in increasing order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    fn parse(level: &str) -> Option<Self> {
+        match level {
+            "debug" => Some(Self::Debug),
+            "info" => Some(Self::Info),
+            "warn" => Some(Self::Warn),
+            "error" => Some(Self::Error),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Debug => "debug",
+            Self::Info => "info",
+            Self::Warn => "warn",
+            Self::Error => "error",
+        }
+    }
+
+    fn current() -> Self {
+        match LOG_LEVEL.load(std::sync::atomic::Ordering::Relaxed) {
+            0 => Self::Debug,
+            1 => Self::Info,
+            2 => Self::Warn,
+            _ => Self::Error,
+        }
+    }
+
+    fn enabled(self) -> bool {
+        self >= Self::current()
+    }
+
+    /// Write `message` with the console method matching this level
+    fn write(self, message: &str) {
+        let message = JsValue::from_str(message);
+        match self {
+            Self::Debug | Self::Info => web_sys::console::log_1(&message),
+            Self::Warn => web_sys::console::warn_1(&message),
+            Self::Error => web_sys::console::error_1(&message),
+        }
+    }
+}
+
+/// Log a formatted message to the console if `$level` is enabled by
+/// `WasmSockudo.setLogLevel`
+macro_rules! wasm_log {
+    ($level:ident, $($arg:tt)+) => {
+        if LogLevel::$level.enabled() {
+            LogLevel::$level.write(&format!($($arg)+));
+        }
+    };
+}
 
 /// Response from authorization endpoint
 #[derive(Debug, Deserialize)]
@@ -46,6 +120,9 @@ pub struct WasmDeltaOptions {
     pub debug: bool,
     #[wasm_bindgen(skip)]
     pub max_messages_per_key: u32,
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub preferred_algorithm: Option<String>,
 }
 
 #[wasm_bindgen]
@@ -57,6 +134,7 @@ impl WasmDeltaOptions {
             algorithms: vec!["fossil".to_string(), "xdelta3".to_string()],
             debug: false,
             max_messages_per_key: 10,
+            preferred_algorithm: None,
         }
     }
 
@@ -99,6 +177,25 @@ impl WasmDeltaOptions {
             .collect();
     }
 
+    /// Ask the server to prefer an algorithm (e.g., "fossil")
+    #[wasm_bindgen(js_name = setPreferredAlgorithm)]
+    pub fn set_preferred_algorithm(&mut self, algorithm: &str) {
+        self.preferred_algorithm = Some(algorithm.trim().to_string());
+    }
+
+    /// Serialize the options to JSON, e.g. to persist them across page loads
+    #[wasm_bindgen(js_name = toJSON)]
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Restore options saved with `toJSON`
+    #[wasm_bindgen(js_name = fromJSON)]
+    pub fn from_json_string(s: &str) -> Result<WasmDeltaOptions, JsValue> {
+        serde_json::from_str(s)
+            .map_err(|e| JsValue::from_str(&format!("Invalid delta options: {}", e)))
+    }
+
     /// Convert to internal DeltaOptions
     pub(crate) fn to_delta_options(&self) -> DeltaOptions {
         let algorithms: Vec<DeltaAlgorithm> = self
@@ -116,8 +213,18 @@ impl WasmDeltaOptions {
             },
             debug: self.debug,
             max_messages_per_key: self.max_messages_per_key as usize,
+            preferred_algorithm: self
+                .preferred_algorithm
+                .as_deref()
+                .and_then(|a| a.parse().ok()),
+            algorithm_switch_on_error: false,
+            max_fallback_attempts: 1,
             on_stats: None,
             on_error: None,
+            on_delta_decoded: None,
+            on_full_message: None,
+            #[cfg(feature = "delta-persist")]
+            cache_persistence: None,
         }
     }
 }
@@ -229,6 +336,162 @@ impl WasmFilterOp {
         }
     }
 
+    /// Layer `overlay` on top of `base`: both must match
+    #[wasm_bindgen(js_name = merge)]
+    pub fn merge(base: WasmFilterOp, overlay: WasmFilterOp) -> WasmFilterOp {
+        WasmFilterOp {
+            inner: InternalFilterOp::merge(base.inner, overlay.inner),
+        }
+    }
+
+    /// Create a filter matching whenever `inner` does not
+    #[wasm_bindgen(js_name = not)]
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(inner: WasmFilterOp) -> WasmFilterOp {
+        WasmFilterOp {
+            inner: InternalFilterOp::not(inner.inner),
+        }
+    }
+
+    /// Match events matched by both this filter and `other`
+    #[wasm_bindgen(js_name = intersection)]
+    pub fn intersection(&self, other: &WasmFilterOp) -> WasmFilterOp {
+        WasmFilterOp {
+            inner: self.inner.clone().intersection(other.inner.clone()),
+        }
+    }
+
+    /// Match events matched by this filter, `other` or both
+    #[wasm_bindgen(js_name = union)]
+    pub fn union(&self, other: &WasmFilterOp) -> WasmFilterOp {
+        WasmFilterOp {
+            inner: self.inner.clone().union(other.inner.clone()),
+        }
+    }
+
+    /// Match events matched by `base` but not by `exclude`
+    #[wasm_bindgen(js_name = difference)]
+    pub fn difference(base: WasmFilterOp, exclude: WasmFilterOp) -> WasmFilterOp {
+        WasmFilterOp {
+            inner: InternalFilterOp::difference(base.inner, exclude.inner),
+        }
+    }
+
+    /// Match events matched by exactly one of `a` and `b`
+    #[wasm_bindgen(js_name = xor)]
+    pub fn xor(a: WasmFilterOp, b: WasmFilterOp) -> WasmFilterOp {
+        WasmFilterOp {
+            inner: InternalFilterOp::xor(a.inner, b.inner),
+        }
+    }
+
+    /// Create a NAND filter: not(and(filters))
+    #[wasm_bindgen(js_name = nand)]
+    pub fn nand(filters: Vec<WasmFilterOp>) -> WasmFilterOp {
+        let inner_filters: Vec<InternalFilterOp> = filters.into_iter().map(|f| f.inner).collect();
+        WasmFilterOp {
+            inner: InternalFilterOp::nand(inner_filters),
+        }
+    }
+
+    /// Create a NOR filter: not(or(filters))
+    #[wasm_bindgen(js_name = nor)]
+    pub fn nor(filters: Vec<WasmFilterOp>) -> WasmFilterOp {
+        let inner_filters: Vec<InternalFilterOp> = filters.into_iter().map(|f| f.inner).collect();
+        WasmFilterOp {
+            inner: InternalFilterOp::nor(inner_filters),
+        }
+    }
+
+    /// Create a filter matching a Unix timestamp field later than `ts`.
+    ///
+    /// Timestamps are Unix epoch seconds; `ts` is a number for JS
+    /// compatibility and is truncated to a whole second.
+    #[wasm_bindgen(js_name = timestampAfter)]
+    pub fn timestamp_after(field: &str, ts: f64) -> WasmFilterOp {
+        WasmFilterOp {
+            inner: InternalFilterOp::timestamp_after(field, ts as u64),
+        }
+    }
+
+    /// Create a filter matching a Unix timestamp field earlier than `ts`
+    #[wasm_bindgen(js_name = timestampBefore)]
+    pub fn timestamp_before(field: &str, ts: f64) -> WasmFilterOp {
+        WasmFilterOp {
+            inner: InternalFilterOp::timestamp_before(field, ts as u64),
+        }
+    }
+
+    /// Create a filter matching a Unix timestamp field strictly between
+    /// `start` and `end`
+    #[wasm_bindgen(js_name = timestampBetween)]
+    pub fn timestamp_between(field: &str, start: f64, end: f64) -> WasmFilterOp {
+        WasmFilterOp {
+            inner: InternalFilterOp::timestamp_between(field, start as u64, end as u64),
+        }
+    }
+
+    /// Create a filter matching a Unix timestamp field within the last
+    /// `secs` seconds
+    #[wasm_bindgen(js_name = timestampWithinLastSecs)]
+    pub fn timestamp_within_last_secs(field: &str, secs: f64) -> WasmFilterOp {
+        WasmFilterOp {
+            inner: InternalFilterOp::timestamp_within_last_secs(field, secs as u64),
+        }
+    }
+
+    /// Create a filter on a nested field: path is dot-separated, e.g. "user.role"
+    ///
+    /// `op` is one of "eq", "neq", "lt", "gt", "lte", "gte".
+    #[wasm_bindgen(js_name = jsonPath)]
+    pub fn json_path(path: &str, op: &str, value: &str) -> Result<WasmFilterOp, JsValue> {
+        let op = op
+            .parse::<CompareOp>()
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(WasmFilterOp {
+            inner: InternalFilterOp::json_path(path, op, value),
+        })
+    }
+
+    /// Parse a filter from its compact URL form, e.g.
+    /// "type:eq:goal,league:in:premier|champions"
+    #[wasm_bindgen(js_name = fromQueryString)]
+    pub fn from_query_string(qs: &str) -> Result<WasmFilterOp, JsValue> {
+        let inner = InternalFilterOp::from_url_query_string(qs)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(WasmFilterOp { inner })
+    }
+
+    /// Encode the filter in the compact URL form read by `fromQueryString`
+    #[wasm_bindgen(js_name = toQueryString)]
+    pub fn to_query_string(&self) -> String {
+        self.inner.to_url_query_string()
+    }
+
+    /// Parse a filter from the JSON produced by `toJSON`, e.g. one stored
+    /// server-side
+    #[wasm_bindgen(js_name = fromJSON)]
+    pub fn from_json(json: &str) -> Result<WasmFilterOp, JsValue> {
+        let inner = serde_json::from_str::<InternalFilterOp>(json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid filter JSON: {}", e)))?;
+        Ok(WasmFilterOp { inner })
+    }
+
+    /// Build a filter from a plain JS object in the shape of `toJSON`
+    #[wasm_bindgen(js_name = fromObject)]
+    pub fn from_js_object(obj: js_sys::Object) -> Result<WasmFilterOp, JsValue> {
+        let json = js_sys::JSON::stringify(&obj)?
+            .as_string()
+            .ok_or_else(|| JsValue::from_str("Filter object is not serializable"))?;
+        Self::from_json(&json)
+    }
+
+    /// Copy the filter, e.g. to use it again after passing it to `subscribe`
+    #[wasm_bindgen(js_name = clone)]
+    pub fn clone_filter(&self) -> WasmFilterOp {
+        self.clone()
+    }
+
     /// Convert to JSON string for debugging
     #[wasm_bindgen(js_name = toJSON)]
     pub fn to_json(&self) -> String {
@@ -259,6 +522,49 @@ pub struct WasmOptions {
     pub auth_endpoint: Option<String>,
     #[wasm_bindgen(skip)]
     pub delta_compression: Option<WasmDeltaOptions>,
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub heartbeat_event: Option<String>,
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub channel_event_history_size: Option<usize>,
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub client_id: Option<String>,
+    /// Never serialized, so `toJSON` does not expose the app secret
+    #[wasm_bindgen(skip)]
+    #[serde(skip)]
+    pub auth_signature_mode: AuthSignatureMode,
+}
+
+impl WasmOptions {
+    /// Fill the fields left unset with the values from `defaults`
+    fn or_defaults(self, defaults: &WasmOptions) -> Self {
+        Self {
+            app_key: self.app_key,
+            cluster: self.cluster.or_else(|| defaults.cluster.clone()),
+            ws_host: self.ws_host.or_else(|| defaults.ws_host.clone()),
+            ws_port: self.ws_port.or(defaults.ws_port),
+            use_tls: self.use_tls.or(defaults.use_tls),
+            auth_endpoint: self
+                .auth_endpoint
+                .or_else(|| defaults.auth_endpoint.clone()),
+            delta_compression: self
+                .delta_compression
+                .or_else(|| defaults.delta_compression.clone()),
+            heartbeat_event: self
+                .heartbeat_event
+                .or_else(|| defaults.heartbeat_event.clone()),
+            channel_event_history_size: self
+                .channel_event_history_size
+                .or(defaults.channel_event_history_size),
+            client_id: self.client_id,
+            auth_signature_mode: match self.auth_signature_mode {
+                AuthSignatureMode::None => defaults.auth_signature_mode.clone(),
+                mode => mode,
+            },
+        }
+    }
 }
 
 #[wasm_bindgen]
@@ -273,6 +579,10 @@ impl WasmOptions {
             use_tls: None,
             auth_endpoint: None,
             delta_compression: None,
+            heartbeat_event: None,
+            channel_event_history_size: None,
+            client_id: None,
+            auth_signature_mode: AuthSignatureMode::None,
         }
     }
 
@@ -326,6 +636,45 @@ impl WasmOptions {
         self.auth_endpoint = Some(endpoint.to_string());
     }
 
+    /// ID sent as the `client_id` URL parameter.
+    ///
+    /// Defaults to a random `u32` in hex, picked once per client. Unlike
+    /// the native client, which defaults to its session ID, the WASM client
+    /// has no session ID to reuse.
+    #[wasm_bindgen(getter)]
+    pub fn client_id(&self) -> Option<String> {
+        self.client_id.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_client_id(&mut self, id: &str) {
+        self.client_id = Some(id.to_string());
+    }
+
+    /// Sign auth requests with HMAC-SHA256 of the app secret, as with the
+    /// native `SockudoOptions::hmac_auth`. The auth endpoint must verify
+    /// the `X-Sockudo-Signature` and `X-Sockudo-Timestamp` headers.
+    #[wasm_bindgen(js_name = setAuthHmac)]
+    pub fn set_auth_hmac(&mut self, app_id: &str, app_secret: &str) {
+        self.auth_signature_mode = AuthSignatureMode::Hmac {
+            app_id: app_id.to_string(),
+            app_secret: crate::options::SensitiveString::new(app_secret),
+        };
+    }
+
+    /// Server heartbeat event answered with `pusher:pong` (default: `pusher:ping`)
+    #[wasm_bindgen(js_name = setHeartbeatEvent)]
+    pub fn set_heartbeat_event(&mut self, event: &str) {
+        self.heartbeat_event = Some(event.to_string());
+    }
+
+    /// Number of events of each name every channel keeps for
+    /// `getEventHistory` (default: 0, history off)
+    #[wasm_bindgen(js_name = setChannelEventHistorySize)]
+    pub fn set_channel_event_history_size(&mut self, size: usize) {
+        self.channel_event_history_size = Some(size);
+    }
+
     /// Set delta compression options
     #[wasm_bindgen(js_name = setDeltaCompression)]
     pub fn set_delta_compression(&mut self, options: WasmDeltaOptions) {
@@ -338,6 +687,72 @@ impl WasmOptions {
         self.delta_compression = Some(WasmDeltaOptions::new());
     }
 
+    /// Build options from a plain JS config object, e.g.
+    /// `{ key: "abc", cluster: "mt1", wsHost: "...", wsPort: 6001 }`.
+    ///
+    /// Recognized fields are `key` (required), `cluster`, `wsHost`, `wsPort`,
+    /// `useTLS` (or `forceTLS`), `authEndpoint` and `deltaCompression`
+    /// (`true` for default options). Unknown fields are ignored.
+    #[wasm_bindgen(js_name = fromJsObject)]
+    pub fn from_js_object(obj: &js_sys::Object) -> Result<WasmOptions, JsValue> {
+        let get = |key: &str| {
+            js_sys::Reflect::get(obj, &JsValue::from_str(key))
+                .ok()
+                .filter(|v| !v.is_undefined() && !v.is_null())
+        };
+        let get_string = |key: &str| -> Result<Option<String>, JsValue> {
+            match get(key) {
+                Some(v) => v
+                    .as_string()
+                    .map(Some)
+                    .ok_or_else(|| JsValue::from_str(&format!("'{}' must be a string", key))),
+                None => Ok(None),
+            }
+        };
+        let get_bool = |key: &str| -> Result<Option<bool>, JsValue> {
+            match get(key) {
+                Some(v) => v
+                    .as_bool()
+                    .map(Some)
+                    .ok_or_else(|| JsValue::from_str(&format!("'{}' must be a boolean", key))),
+                None => Ok(None),
+            }
+        };
+
+        let app_key = get_string("key")?
+            .ok_or_else(|| JsValue::from_str("Missing required field 'key' (the app key)"))?;
+
+        let mut options = WasmOptions::new(&app_key);
+        options.cluster = get_string("cluster")?;
+        options.ws_host = get_string("wsHost")?;
+        options.auth_endpoint = get_string("authEndpoint")?;
+        options.use_tls = match get_bool("useTLS")? {
+            Some(tls) => Some(tls),
+            None => get_bool("forceTLS")?,
+        };
+        if let Some(port) = get("wsPort") {
+            let port = port
+                .as_f64()
+                .filter(|p| p.fract() == 0.0 && (1.0..=65535.0).contains(p))
+                .ok_or_else(|| {
+                    JsValue::from_str("'wsPort' must be an integer between 1 and 65535")
+                })?;
+            options.ws_port = Some(port as u16);
+        }
+        if get_bool("deltaCompression")? == Some(true) {
+            options.delta_compression = Some(WasmDeltaOptions::new());
+        }
+
+        Ok(options)
+    }
+
+    /// Serialize the options to JSON. The options hold no credentials; the
+    /// app key is public.
+    #[wasm_bindgen(js_name = toJSON)]
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
+    }
+
     /// Convert to internal SockudoOptions
     pub(crate) fn to_sockudo_options(&self) -> SockudoOptions {
         let mut opts = SockudoOptions::new(&self.app_key);
@@ -350,6 +765,14 @@ impl WasmOptions {
             .delta_compression
             .as_ref()
             .map(|d| d.to_delta_options());
+        if let Some(ref event) = self.heartbeat_event {
+            opts.heartbeat_event = event.clone();
+        }
+        if let Some(size) = self.channel_event_history_size {
+            opts.channel_event_history_size = size;
+        }
+        opts.client_id = self.client_id.clone();
+        opts.auth_signature_mode = self.auth_signature_mode.clone();
         opts
     }
 }
@@ -365,6 +788,8 @@ pub struct WasmSockudo {
 struct WasmSockudoInner {
     key: String,
     options: SockudoOptions,
+    /// Sent as the `client_id` URL parameter on every connect
+    client_id: String,
     socket_id: Option<String>,
     state: String,
     channels: std::collections::HashMap<String, WasmChannel>,
@@ -372,9 +797,71 @@ struct WasmSockudoInner {
     global_callbacks: Vec<Function>,
     ws: Option<web_sys::WebSocket>,
     delta_stats: DeltaStats,
+    /// Delta stats of each channel that received messages
+    channel_delta_stats: std::collections::HashMap<String, DeltaStats>,
     delta_compression_enabled: bool,
+    /// Listener leak warning threshold applied to bindings and channels
+    max_listeners: usize,
+    /// Most recent connection error
+    last_error: Option<String>,
+    /// Events are dropped instead of dispatched while set
+    paused: bool,
+    /// Callbacks fired with `{previous, current}` on every state change
+    state_callbacks: Vec<Function>,
+    /// Callbacks fired only when entering a specific state
+    state_entry_callbacks: Vec<(&'static str, Function)>,
+    /// Number of received events per event name
+    #[cfg(feature = "event-stats")]
+    event_counts: std::collections::HashMap<String, u64>,
     /// Store base messages for delta decoding: channel -> base message string
     delta_base_messages: std::collections::HashMap<String, String>,
+    /// Algorithms used for a channel's deltas whatever the message says
+    channel_delta_algorithms: std::collections::HashMap<String, DeltaAlgorithm>,
+    /// Most recent connection attempts, oldest first
+    reconnect_history: std::collections::VecDeque<crate::ReconnectAttempt>,
+    /// When the state last became `connected`, in JS milliseconds
+    connected_since: Option<f64>,
+    /// Callbacks fired for each server heartbeat
+    heartbeat_callbacks: Vec<Function>,
+    /// Callbacks fired with `{code, message, isFatal}` for each `pusher:error`
+    server_error_callbacks: Vec<Function>,
+    /// Probes waiting for a `pusher:pong`, oldest ping first, with the time
+    /// their ping was sent in JS milliseconds. Each pong answers the oldest
+    /// ping, including those of probes that already timed out.
+    pong_waiters: Vec<(f64, futures::channel::oneshot::Sender<f64>)>,
+    /// Round-trip time of the most recent answered probe, in milliseconds
+    last_ping_rtt_ms: Option<f64>,
+    /// Client event frames kept instead of sent, once `captureSentFrames`
+    /// was called
+    #[cfg(feature = "test-utils")]
+    sent_frames: Option<parking_lot::Mutex<Vec<String>>>,
+}
+
+impl WasmSockudoInner {
+    /// WebSocket URL built from the host, cluster, port and TLS options
+    fn ws_url(&self) -> Result<String, JsValue> {
+        let use_tls = self.options.use_tls.unwrap_or(true);
+        let protocol = if use_tls { "wss" } else { "ws" };
+
+        let host = if let Some(ref h) = self.options.ws_host {
+            h.clone()
+        } else if let Some(ref cluster) = self.options.cluster {
+            format!("ws-{}.pusher.com", cluster)
+        } else {
+            return Err(JsValue::from_str("No host or cluster specified"));
+        };
+
+        let port = self
+            .options
+            .ws_port
+            .unwrap_or(if use_tls { 443 } else { 80 });
+
+        let url = format!(
+            "{}://{}:{}/app/{}?protocol=7&client=sockudo-rust&version=0.1.0",
+            protocol, host, port, self.key
+        );
+        Ok(crate::options::url_with_client_id(&url, &self.client_id))
+    }
 }
 
 #[wasm_bindgen]
@@ -384,19 +871,31 @@ impl WasmSockudo {
     pub fn new(app_key: &str, options: Option<WasmOptions>) -> Result<WasmSockudo, JsValue> {
         console_error_panic_hook::set_once();
 
-        web_sys::console::log_1(&format!("Rust received options: {:?}", options).into());
+        wasm_log!(Debug, "Rust received options: {:?}", options);
+
+        let defaults = GLOBAL_DEFAULTS.with(|defaults| defaults.borrow().clone());
+        let options = match (options, defaults) {
+            (Some(options), Some(defaults)) => Some(options.or_defaults(&defaults)),
+            (None, Some(defaults)) => Some(WasmOptions::new(app_key).or_defaults(&defaults)),
+            (options, None) => options,
+        };
 
         let opts = options
             .map(|o| {
-                web_sys::console::log_1(&format!("Converting options: {:?}", o).into());
+                wasm_log!(Debug, "Converting options: {:?}", o);
                 o.to_sockudo_options()
             })
             .unwrap_or_else(|| SockudoOptions::new(app_key));
+        let client_id = match opts.client_id {
+            Some(ref id) => crate::options::sanitize_client_id(id),
+            None => format!("{:x}", rand::random::<u32>()),
+        };
 
         let client = Self {
             inner: Arc::new(RwLock::new(WasmSockudoInner {
                 key: app_key.to_string(),
                 options: opts,
+                client_id,
                 socket_id: None,
                 state: "initialized".to_string(),
                 channels: std::collections::HashMap::new(),
@@ -404,52 +903,101 @@ impl WasmSockudo {
                 global_callbacks: Vec::new(),
                 ws: None,
                 delta_stats: DeltaStats::new(),
+                channel_delta_stats: std::collections::HashMap::new(),
                 delta_compression_enabled: false,
+                max_listeners: crate::events::DEFAULT_MAX_LISTENERS,
+                last_error: None,
+                paused: false,
+                state_callbacks: Vec::new(),
+                state_entry_callbacks: Vec::new(),
+                #[cfg(feature = "event-stats")]
+                event_counts: std::collections::HashMap::new(),
                 delta_base_messages: std::collections::HashMap::new(),
+                channel_delta_algorithms: std::collections::HashMap::new(),
+                reconnect_history: std::collections::VecDeque::new(),
+                connected_since: None,
+                heartbeat_callbacks: Vec::new(),
+                server_error_callbacks: Vec::new(),
+                pong_waiters: Vec::new(),
+                last_ping_rtt_ms: None,
+                #[cfg(feature = "test-utils")]
+                sent_frames: None,
             })),
         };
 
         Ok(client)
     }
 
+    /// Set options used by every client created afterwards for the fields
+    /// its own options leave unset (the app key and client ID are always
+    /// per client).
+    /// Pass `undefined` to clear them.
+    #[wasm_bindgen(js_name = setGlobalDefaults)]
+    pub fn set_global_defaults(options: Option<WasmOptions>) {
+        GLOBAL_DEFAULTS.with(|defaults| *defaults.borrow_mut() = options);
+    }
+
+    /// Set the lowest console level that is logged: `"debug"` (default),
+    /// `"info"`, `"warn"` or `"error"`
+    #[wasm_bindgen(js_name = setLogLevel)]
+    pub fn set_log_level(level: &str) -> Result<(), JsValue> {
+        let level = LogLevel::parse(level)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown log level: {}", level)))?;
+        LOG_LEVEL.store(level as u8, std::sync::atomic::Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// The current console log level
+    #[wasm_bindgen(js_name = getLogLevel)]
+    pub fn log_level() -> String {
+        LogLevel::current().as_str().to_string()
+    }
+
+    /// Whether messages at `level` are currently logged
+    #[wasm_bindgen(js_name = isLogEnabled)]
+    pub fn is_log_enabled(level: &str) -> bool {
+        LogLevel::parse(level).is_some_and(LogLevel::enabled)
+    }
+
+    /// Create a client from a plain JS config object.
+    ///
+    /// See `WasmOptions.fromJsObject` for the recognized fields.
+    #[wasm_bindgen(js_name = fromJsObject)]
+    pub fn new_from_js_object(config: js_sys::Object) -> Result<WasmSockudo, JsValue> {
+        let options = WasmOptions::from_js_object(&config)?;
+        let app_key = options.app_key.clone();
+        Self::new(&app_key, Some(options))
+    }
+
     /// Connect to the Pusher server
     #[wasm_bindgen]
     pub async fn connect(&self) -> Result<(), JsValue> {
+        transition_state(&self.inner, "connecting");
         let mut inner = self.inner.write();
-        inner.state = "connecting".to_string();
+
+        // Attempts count up until one connects
+        let attempt_number = match inner.reconnect_history.back() {
+            Some(last) if last.connected_at.is_none() => last.attempt_number + 1,
+            _ => 1,
+        };
+        crate::connection::push_reconnect_attempt(
+            &mut inner.reconnect_history,
+            crate::ReconnectAttempt::started(attempt_number, now_system_time(), 0),
+        );
 
         // Debug: log options
-        web_sys::console::log_1(
-            &format!(
-                "Options - cluster: {:?}, ws_host: {:?}, use_tls: {:?}",
-                inner.options.cluster, inner.options.ws_host, inner.options.use_tls
-            )
-            .into(),
+        wasm_log!(
+            Debug,
+            "Options - cluster: {:?}, ws_host: {:?}, use_tls: {:?}",
+            inner.options.cluster,
+            inner.options.ws_host,
+            inner.options.use_tls
         );
 
         // Build WebSocket URL
-        let use_tls = inner.options.use_tls.unwrap_or(true);
-        let protocol = if use_tls { "wss" } else { "ws" };
+        let url = inner.ws_url()?;
 
-        let host = if let Some(ref h) = inner.options.ws_host {
-            h.clone()
-        } else if let Some(ref cluster) = inner.options.cluster {
-            format!("ws-{}.pusher.com", cluster)
-        } else {
-            return Err(JsValue::from_str("No host or cluster specified"));
-        };
-
-        let port = inner
-            .options
-            .ws_port
-            .unwrap_or(if use_tls { 443 } else { 80 });
-
-        let url = format!(
-            "{}://{}:{}/app/{}?protocol=7&client=sockudo-rust&version=0.1.0",
-            protocol, host, port, inner.key
-        );
-
-        web_sys::console::log_1(&format!("Connecting to: {}", url).into());
+        wasm_log!(Info, "Connecting to: {}", url);
 
         // Create WebSocket
         let ws = web_sys::WebSocket::new(&url)
@@ -458,9 +1006,11 @@ impl WasmSockudo {
         // Set up event handlers
         let inner_clone = self.inner.clone();
         let onopen = Closure::wrap(Box::new(move |_event: web_sys::Event| {
-            let mut inner = inner_clone.write();
-            inner.state = "connected".to_string();
-            web_sys::console::log_1(&"WebSocket connected!".into());
+            if let Some(attempt) = inner_clone.write().reconnect_history.back_mut() {
+                attempt.connected_at = Some(now_system_time());
+            }
+            transition_state(&inner_clone, "connected");
+            wasm_log!(Info, "WebSocket connected!");
         }) as Box<dyn FnMut(web_sys::Event)>);
         ws.set_onopen(Some(onopen.as_ref().unchecked_ref()));
         onopen.forget();
@@ -468,301 +1018,50 @@ impl WasmSockudo {
         let inner_clone = self.inner.clone();
         let onmessage = Closure::wrap(Box::new(move |event: web_sys::MessageEvent| {
             if let Ok(text) = event.data().dyn_into::<js_sys::JsString>() {
-                let message: String = text.into();
-                let message_size = message.len();
-                web_sys::console::log_1(&format!("Received: {}", message).into());
-
-                // Parse Pusher message and handle it
-                if let Ok(event_data) = serde_json::from_str::<serde_json::Value>(&message) {
-                    if let Some(event_name) = event_data.get("event").and_then(|v| v.as_str()) {
-                        // Track delta stats for non-internal messages
-                        if !event_name.starts_with("pusher:")
-                            && !event_name.starts_with("pusher_internal:")
-                        {
-                            let mut inner = inner_clone.write();
-                            inner.delta_stats.total_messages += 1;
-
-                            // Check if this is a delta message (has delta field in data)
-                            let is_delta = event_data
-                                .get("data")
-                                .and_then(|d| d.as_str())
-                                .and_then(|s| serde_json::from_str::<serde_json::Value>(s).ok())
-                                .map(|parsed| parsed.get("delta").is_some())
-                                .unwrap_or(false);
-
-                            if is_delta {
-                                inner.delta_stats.delta_messages += 1;
-                                // For delta messages, compressed size is the message size
-                                // decompressed would be larger (estimate 3x for now)
-                                inner.delta_stats.total_bytes_with_compression +=
-                                    message_size as u64;
-                                inner.delta_stats.total_bytes_without_compression +=
-                                    (message_size * 3) as u64;
-                            } else {
-                                inner.delta_stats.full_messages += 1;
-                                inner.delta_stats.total_bytes_with_compression +=
-                                    message_size as u64;
-                                inner.delta_stats.total_bytes_without_compression +=
-                                    message_size as u64;
-                            }
-
-                            inner.delta_stats.calculate_savings();
-                            drop(inner);
-                        }
-                        // Handle pusher:ping - respond with pusher:pong immediately
-                        if event_name == "pusher:ping" {
-                            web_sys::console::log_1(
-                                &"Received pusher:ping, sending pusher:pong".into(),
-                            );
-                            let inner = inner_clone.read();
-                            if let Some(ref ws) = inner.ws {
-                                let pong = serde_json::json!({
-                                    "event": "pusher:pong",
-                                    "data": {}
-                                });
-                                if let Ok(pong_str) = serde_json::to_string(&pong) {
-                                    let _ = ws.send_with_str(&pong_str);
-                                }
-                            }
-                        }
-
-                        // Handle pusher:delta_compression_enabled
-                        if event_name == "pusher:delta_compression_enabled" {
-                            let mut inner = inner_clone.write();
-                            inner.delta_compression_enabled = true;
-                            web_sys::console::log_1(&"Delta compression enabled!".into());
-                        }
-
-                        // Handle pusher:delta - decode and re-emit as original event
-                        if event_name == "pusher:delta" {
-                            if let Some(channel) =
-                                event_data.get("channel").and_then(|v| v.as_str())
-                            {
-                                if let Some(data) = event_data.get("data") {
-                                    // Decode the delta message
-                                    match Self::decode_delta_message(
-                                        &inner_clone,
-                                        channel,
-                                        data.clone(),
-                                    ) {
-                                        Ok(reconstructed_message) => {
-                                            // Parse the reconstructed message and re-emit it
-                                            if let Ok(reconstructed_event) =
-                                                serde_json::from_str::<serde_json::Value>(
-                                                    &reconstructed_message,
-                                                )
-                                            {
-                                                // Store the reconstructed message as new base
-                                                inner_clone.write().delta_base_messages.insert(
-                                                    channel.to_string(),
-                                                    reconstructed_message.clone(),
-                                                );
-
-                                                // Extract the original event name and data
-                                                if let Some(orig_event) = reconstructed_event
-                                                    .get("event")
-                                                    .and_then(|v| v.as_str())
-                                                {
-                                                    // Trigger channel callbacks with the decoded event
-                                                    let inner = inner_clone.read();
-                                                    if let Some(ch) = inner.channels.get(channel) {
-                                                        let callbacks = ch.callbacks.read();
-
-                                                        // Build reconstructed message JSON
-                                                        let reconstructed_msg = serde_json::json!({
-                                                            "event": orig_event,
-                                                            "channel": channel,
-                                                            "data": reconstructed_event.get("data")
-                                                        })
-                                                        .to_string();
-
-                                                        // Trigger event-specific callbacks
-                                                        if let Some(cbs) = callbacks.get(orig_event)
-                                                        {
-                                                            for callback in cbs {
-                                                                let _ = callback.call1(
-                                                                    &JsValue::NULL,
-                                                                    &JsValue::from_str(
-                                                                        &reconstructed_msg,
-                                                                    ),
-                                                                );
-                                                            }
-                                                        }
-
-                                                        // Trigger bind_all callbacks
-                                                        if let Some(all_cbs) =
-                                                            callbacks.get("__all__")
-                                                        {
-                                                            for callback in all_cbs {
-                                                                let event_js =
-                                                                    JsValue::from_str(orig_event);
-                                                                let data_js = reconstructed_event
-                                                                    .get("data")
-                                                                    .and_then(|v| {
-                                                                        serde_json::to_string(v)
-                                                                            .ok()
-                                                                    })
-                                                                    .map(|s| JsValue::from_str(&s))
-                                                                    .unwrap_or(JsValue::NULL);
-                                                                let _ = callback.call2(
-                                                                    &JsValue::NULL,
-                                                                    &event_js,
-                                                                    &data_js,
-                                                                );
-                                                            }
-                                                        }
-                                                    }
-
-                                                    // Trigger global callbacks with decoded event
-                                                    let inner = inner_clone.read();
-                                                    if let Some(callbacks) =
-                                                        inner.callbacks.get(orig_event)
-                                                    {
-                                                        let reconstructed_msg = serde_json::json!({
-                                                            "event": orig_event,
-                                                            "channel": channel,
-                                                            "data": reconstructed_event.get("data")
-                                                        })
-                                                        .to_string();
-
-                                                        for callback in callbacks {
-                                                            let _ = callback.call1(
-                                                                &JsValue::NULL,
-                                                                &JsValue::from_str(
-                                                                    &reconstructed_msg,
-                                                                ),
-                                                            );
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                        Err(e) => {
-                                            web_sys::console::error_1(
-                                                &format!("Delta decode failed: {}", e).into(),
-                                            );
-                                        }
-                                    }
-                                }
-                            }
-                        }
-
-                        // Store base messages for delta compression (non-pusher events with sequence)
-                        if !event_name.starts_with("pusher:")
-                            && !event_name.starts_with("pusher_internal:")
-                        {
-                            if let Some(channel) =
-                                event_data.get("channel").and_then(|v| v.as_str())
-                            {
-                                // Create sanitized base message (without sequence field)
-                                let sanitized = serde_json::json!({
-                                    "event": event_data.get("event"),
-                                    "channel": event_data.get("channel"),
-                                    "data": event_data.get("data"),
-                                });
-                                if let Ok(base_msg) = serde_json::to_string(&sanitized) {
-                                    inner_clone
-                                        .write()
-                                        .delta_base_messages
-                                        .insert(channel.to_string(), base_msg);
-                                }
-                            }
-                        }
-
-                        // Handle pusher:connection_established
-                        if event_name == "pusher:connection_established" {
-                            if let Some(data) = event_data.get("data").and_then(|v| v.as_str()) {
-                                if let Ok(conn_data) =
-                                    serde_json::from_str::<serde_json::Value>(data)
-                                {
-                                    if let Some(socket_id) =
-                                        conn_data.get("socket_id").and_then(|v| v.as_str())
-                                    {
-                                        inner_clone.write().socket_id = Some(socket_id.to_string());
-                                        web_sys::console::log_1(
-                                            &format!("Socket ID: {}", socket_id).into(),
-                                        );
-                                    }
-                                }
-                            }
-                        }
-
-                        // Don't propagate pusher:delta events through normal channels
-                        // (they've already been decoded and re-emitted above)
-                        if event_name != "pusher:delta" {
-                            // Get channel name if present
-                            let channel_name = event_data.get("channel").and_then(|v| v.as_str());
-
-                            // Trigger channel-specific callbacks
-                            if let Some(ch_name) = channel_name {
-                                let inner = inner_clone.read();
-                                if let Some(channel) = inner.channels.get(ch_name) {
-                                    let callbacks = channel.callbacks.read();
-
-                                    // Trigger event-specific callbacks
-                                    if let Some(cbs) = callbacks.get(event_name) {
-                                        for callback in cbs {
-                                            let _ = callback.call1(
-                                                &JsValue::NULL,
-                                                &JsValue::from_str(&message),
-                                            );
-                                        }
-                                    }
-
-                                    // Trigger bind_all callbacks
-                                    if let Some(all_cbs) = callbacks.get("__all__") {
-                                        for callback in all_cbs {
-                                            // Call with event name and data
-                                            let event_js = JsValue::from_str(event_name);
-                                            let data_js = event_data
-                                                .get("data")
-                                                .and_then(|v| v.as_str())
-                                                .map(|s| JsValue::from_str(s))
-                                                .unwrap_or(JsValue::NULL);
-                                            let _ =
-                                                callback.call2(&JsValue::NULL, &event_js, &data_js);
-                                        }
-                                    }
-                                }
-                            }
-
-                            // Trigger global event callbacks
-                            let inner = inner_clone.read();
-                            if let Some(callbacks) = inner.callbacks.get(event_name) {
-                                for callback in callbacks {
-                                    let _ = callback
-                                        .call1(&JsValue::NULL, &JsValue::from_str(&message));
-                                }
-                            }
-
-                            // Trigger global callbacks
-                            for callback in &inner.global_callbacks {
-                                let _ =
-                                    callback.call1(&JsValue::NULL, &JsValue::from_str(&message));
-                            }
-                        }
-                    }
-                }
+                Self::handle_incoming(&inner_clone, text.into());
             }
         }) as Box<dyn FnMut(web_sys::MessageEvent)>);
         ws.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
         onmessage.forget();
 
         let inner_clone = self.inner.clone();
-        let onerror = Closure::wrap(Box::new(move |_event: web_sys::ErrorEvent| {
+        let onerror = Closure::wrap(Box::new(move |event: web_sys::ErrorEvent| {
+            let message = event.message();
+            let message = if message.is_empty() {
+                "WebSocket error".to_string()
+            } else {
+                message
+            };
             let mut inner = inner_clone.write();
-            inner.state = "failed".to_string();
-            web_sys::console::error_1(&"WebSocket error!".into());
+            if let Some(attempt) = inner.reconnect_history.back_mut() {
+                if attempt.connected_at.is_none() {
+                    attempt.error = Some(message.clone());
+                }
+            }
+            inner.last_error = Some(message);
+            drop(inner);
+            transition_state(&inner_clone, "failed");
+            wasm_log!(Error, "WebSocket error!");
         }) as Box<dyn FnMut(web_sys::ErrorEvent)>);
         ws.set_onerror(Some(onerror.as_ref().unchecked_ref()));
         onerror.forget();
 
         let inner_clone = self.inner.clone();
         let onclose = Closure::wrap(Box::new(move |_event: web_sys::CloseEvent| {
-            let mut inner = inner_clone.write();
-            inner.state = "disconnected".to_string();
-            inner.socket_id = None;
-            web_sys::console::log_1(&"WebSocket closed".into());
+            let failed = {
+                let mut inner = inner_clone.write();
+                inner.socket_id = None;
+                inner
+                    .channels
+                    .values()
+                    .for_each(WasmChannel::mark_unsubscribed);
+                inner.state == "failed"
+            };
+            // The server closes the socket after a fatal error
+            if !failed {
+                transition_state(&inner_clone, "disconnected");
+            }
+            wasm_log!(Info, "WebSocket closed");
         }) as Box<dyn FnMut(web_sys::CloseEvent)>);
         ws.set_onclose(Some(onclose.as_ref().unchecked_ref()));
         onclose.forget();
@@ -781,13 +1080,101 @@ impl WasmSockudo {
 
         if let Some(ws) = inner.ws.take() {
             let _ = ws.close();
-            web_sys::console::log_1(&"Disconnecting WebSocket...".into());
+            wasm_log!(Info, "Disconnecting WebSocket...");
         }
 
-        inner.state = "disconnected".to_string();
         inner.socket_id = None;
-    }
-
+        inner
+            .channels
+            .values()
+            .for_each(WasmChannel::mark_unsubscribed);
+        drop(inner);
+        transition_state(&self.inner, "disconnected");
+    }
+
+    /// Disconnect from the server with a close code and reason.
+    ///
+    /// Use 1000 for a normal closure or 4000-4999 for application reasons.
+    #[wasm_bindgen(js_name = disconnectWithReason)]
+    pub fn disconnect_with_reason(&self, code: u16, reason: &str) -> Result<(), JsValue> {
+        let mut inner = self.inner.write();
+
+        if let Some(ws) = inner.ws.take() {
+            ws.close_with_code_and_reason(code, reason)?;
+            wasm_log!(Info, "Disconnecting WebSocket ({}: {})", code, reason);
+        }
+
+        inner.socket_id = None;
+        inner
+            .channels
+            .values()
+            .for_each(WasmChannel::mark_unsubscribed);
+        drop(inner);
+        transition_state(&self.inner, "disconnected");
+        Ok(())
+    }
+
+    /// Pause event dispatch. The browser keeps managing the socket, so
+    /// this only drops incoming events (pings are still answered) until
+    /// `resume` is called.
+    #[wasm_bindgen]
+    pub fn pause(&self) {
+        self.inner.write().paused = true;
+    }
+
+    /// Resume event dispatch after `pause`
+    #[wasm_bindgen]
+    pub fn resume(&self) {
+        self.inner.write().paused = false;
+    }
+
+    /// Call `callback({previous, current})` on every connection state change
+    #[wasm_bindgen(js_name = bindConnectionState)]
+    pub fn bind_connection_state(&self, callback: Function) {
+        self.inner.write().state_callbacks.push(callback);
+    }
+
+    /// Call `callback({previous, current})` whenever the client connects
+    #[wasm_bindgen(js_name = onConnected)]
+    pub fn on_connected(&self, callback: Function) {
+        self.inner
+            .write()
+            .state_entry_callbacks
+            .push(("connected", callback));
+    }
+
+    /// Call `callback({previous, current})` whenever the client disconnects
+    #[wasm_bindgen(js_name = onDisconnected)]
+    pub fn on_disconnected(&self, callback: Function) {
+        self.inner
+            .write()
+            .state_entry_callbacks
+            .push(("disconnected", callback));
+    }
+
+    /// Call `callback({previous, current})` whenever the connection fails
+    #[wasm_bindgen(js_name = onFailed)]
+    pub fn on_failed(&self, callback: Function) {
+        self.inner
+            .write()
+            .state_entry_callbacks
+            .push(("failed", callback));
+    }
+
+    /// Call `callback()` for each heartbeat received from the server
+    #[wasm_bindgen(js_name = onHeartbeat)]
+    pub fn on_heartbeat(&self, callback: Function) {
+        self.inner.write().heartbeat_callbacks.push(callback);
+    }
+
+    /// Call `callback({code, message, isFatal})` for each `pusher:error`
+    /// from the server. Fatal errors (codes 4000-4099) also move the
+    /// connection to `failed`.
+    #[wasm_bindgen(js_name = onServerError)]
+    pub fn on_server_error(&self, callback: Function) {
+        self.inner.write().server_error_callbacks.push(callback);
+    }
+
     /// Get the current connection state
     #[wasm_bindgen(getter)]
     pub fn state(&self) -> String {
@@ -800,6 +1187,12 @@ impl WasmSockudo {
         self.inner.read().socket_id.clone()
     }
 
+    /// Get the ID sent as the `client_id` URL parameter
+    #[wasm_bindgen(getter)]
+    pub fn client_id(&self) -> String {
+        self.inner.read().client_id.clone()
+    }
+
     /// Subscribe to a channel
     #[wasm_bindgen]
     pub fn subscribe(
@@ -807,21 +1200,51 @@ impl WasmSockudo {
         channel_name: &str,
         filter: Option<WasmFilterOp>,
     ) -> Result<WasmChannel, JsValue> {
+        self.subscribe_with(channel_name, filter, std::collections::HashMap::new())
+    }
+
+    /// Subscribe to a channel, sending `metadata` with the subscribe request.
+    ///
+    /// `metadata` must be an object with string values; it is merged over
+    /// the client's global subscription metadata.
+    #[wasm_bindgen(js_name = subscribeWithMetadata)]
+    pub fn subscribe_with_metadata(
+        &self,
+        channel_name: &str,
+        metadata: js_sys::Object,
+    ) -> Result<WasmChannel, JsValue> {
+        let mut entries = std::collections::HashMap::new();
+        for entry in js_sys::Object::entries(&metadata).iter() {
+            let pair = js_sys::Array::from(&entry);
+            let key = pair.get(0).as_string().unwrap_or_default();
+            let value = pair.get(1).as_string().ok_or_else(|| {
+                JsValue::from_str(&format!("Metadata value for '{}' must be a string", key))
+            })?;
+            entries.insert(key, value);
+        }
+        self.subscribe_with(channel_name, None, entries)
+    }
+
+    fn subscribe_with(
+        &self,
+        channel_name: &str,
+        filter: Option<WasmFilterOp>,
+        metadata: std::collections::HashMap<String, String>,
+    ) -> Result<WasmChannel, JsValue> {
+        validate_channel_name(channel_name).map_err(|e| JsValue::from_str(&e.to_string()))?;
+
         let mut inner = self.inner.write();
 
         if inner.channels.contains_key(channel_name) {
             return Ok(inner.channels.get(channel_name).unwrap().clone());
         }
 
-        let channel = WasmChannel::new(channel_name);
-        inner
-            .channels
-            .insert(channel_name.to_string(), channel.clone());
+        let channel = self.register_channel(&mut inner, channel_name);
+
+        let mut metadata_inner = inner.options.global_subscription_metadata.clone();
+        metadata_inner.extend(metadata);
 
-        // Check if this is a private or presence channel that requires authentication
-        let requires_auth = channel_name.starts_with("private-")
-            || channel_name.starts_with("presence-")
-            || channel_name.starts_with("private-encrypted-");
+        let requires_auth = channel_requires_auth(channel_name);
 
         // Send subscribe message if connected
         if let Some(ws) = &inner.ws {
@@ -830,6 +1253,7 @@ impl WasmSockudo {
                 if requires_auth {
                     let socket_id = inner.socket_id.clone();
                     let auth_endpoint = inner.options.auth_endpoint.clone();
+                    let signature_mode = inner.options.auth_signature_mode.clone();
                     let ws_clone = ws.clone();
                     let channel_name_owned = channel_name.to_string();
                     let filter_inner = filter.map(|f| f.inner);
@@ -841,109 +1265,241 @@ impl WasmSockudo {
                     wasm_bindgen_futures::spawn_local(async move {
                         if let Some(socket_id) = socket_id {
                             if let Some(auth_endpoint) = auth_endpoint {
-                                // Call auth endpoint
-                                match Self::authenticate_channel(
+                                if let Err(e) = Self::subscribe_authenticated(
+                                    &ws_clone,
                                     &auth_endpoint,
-                                    &channel_name_owned,
+                                    &signature_mode,
                                     &socket_id,
+                                    &channel_name_owned,
+                                    filter_inner.as_ref(),
+                                    &metadata_inner,
                                 )
                                 .await
                                 {
-                                    Ok(auth_data) => {
-                                        // Build subscribe data with auth
-                                        let mut subscribe_data = serde_json::json!({
-                                            "channel": channel_name_owned,
-                                            "auth": auth_data.auth
-                                        });
-
-                                        // Add channel_data if present (for presence channels)
-                                        if let Some(channel_data) = auth_data.channel_data {
-                                            subscribe_data["channel_data"] =
-                                                serde_json::json!(channel_data);
-                                        }
-
-                                        // Add filter if provided
-                                        if let Some(f) = filter_inner {
-                                            if let Ok(filter_json) = serde_json::to_value(&f) {
-                                                subscribe_data["filter"] = filter_json;
-                                            }
-                                        }
-
-                                        let subscribe_msg = serde_json::json!({
-                                            "event": "pusher:subscribe",
-                                            "data": subscribe_data
-                                        });
-
-                                        if let Ok(msg_str) = serde_json::to_string(&subscribe_msg) {
-                                            let _ = ws_clone.send_with_str(&msg_str);
-                                            web_sys::console::log_1(
-                                                &format!(
-                                                    "Subscribing to authenticated channel: {} with auth: {}",
-                                                    channel_name_owned,
-                                                    auth_data.auth
-                                                )
-                                                .into(),
-                                            );
-                                        }
-                                    }
-                                    Err(e) => {
-                                        web_sys::console::error_1(
-                                            &format!("Failed to authenticate channel: {:?}", e)
-                                                .into(),
-                                        );
-                                    }
+                                    wasm_log!(Error, "{}", e.as_string().unwrap_or_default());
                                 }
                             } else {
-                                web_sys::console::error_1(
-                                    &"No auth_endpoint configured for private/presence channel"
-                                        .into(),
+                                wasm_log!(
+                                    Error,
+                                    "No auth_endpoint configured for private/presence channel"
                                 );
                             }
                         } else {
-                            web_sys::console::error_1(
-                                &"No socket_id available for authentication".into(),
-                            );
+                            wasm_log!(Error, "No socket_id available for authentication");
                         }
                     });
                 } else {
                     // Public channel - subscribe immediately
-                    let mut subscribe_data = serde_json::json!({
-                        "channel": channel_name
-                    });
-
-                    // Add filter if provided
-                    if let Some(f) = filter {
-                        if let Ok(filter_json) = serde_json::to_value(&f.inner) {
-                            subscribe_data["filter"] = filter_json;
+                    let filter = filter.map(|f| f.inner);
+                    let subscribe_msg = Protocol::create_subscribe_event_with_metadata(
+                        channel_name,
+                        None,
+                        None,
+                        filter.as_ref(),
+                        &metadata_inner,
+                    )
+                    .and_then(|event| Protocol::encode_message(&event));
+
+                    match subscribe_msg {
+                        Ok(msg_str) => {
+                            let _ = ws.send_with_str(&msg_str);
+                            wasm_log!(
+                                Info,
+                                "Subscribing to public channel: {} with filter: {:?}",
+                                channel_name,
+                                filter
+                            );
+                        }
+                        Err(e) => {
+                            wasm_log!(Error, "Failed to subscribe: {}", e);
                         }
                     }
+                }
+            }
+        }
 
-                    let subscribe_msg = serde_json::json!({
-                        "event": "pusher:subscribe",
-                        "data": subscribe_data
-                    });
+        Ok(channel)
+    }
 
-                    if let Ok(msg_str) = serde_json::to_string(&subscribe_msg) {
-                        let _ = ws.send_with_str(&msg_str);
-                        web_sys::console::log_1(
-                            &format!(
-                                "Subscribing to public channel: {} with filter: {:?}",
-                                channel_name,
-                                subscribe_data.get("filter")
+    /// Create `channel_name`'s channel and add it to the client, without
+    /// sending a subscribe request
+    fn register_channel(&self, inner: &mut WasmSockudoInner, channel_name: &str) -> WasmChannel {
+        let channel = WasmChannel::new(channel_name, Arc::downgrade(&self.inner));
+        channel.set_max_listeners(inner.max_listeners);
+        channel.set_history_size(inner.options.channel_event_history_size);
+        inner
+            .channels
+            .insert(channel_name.to_string(), channel.clone());
+        channel
+    }
+
+    /// Subscribe to several channels at once.
+    ///
+    /// Subscribe requests for public channels are all sent before any
+    /// authentication starts; private and presence channels are then
+    /// authenticated one at a time, in order. Returns a promise resolving
+    /// to an array with one entry per name: the channel, or `null` when the
+    /// name is invalid or authentication failed (logged as a console
+    /// error). While disconnected, channels are only added to the client,
+    /// as with `subscribe`.
+    #[wasm_bindgen(js_name = subscribeAll)]
+    pub fn subscribe_batch(&self, channel_names: js_sys::Array) -> js_sys::Promise {
+        let mut results: Vec<JsValue> = Vec::new();
+        // Index in `results`, channel name and channel of each channel to
+        // authenticate
+        let mut pending = Vec::new();
+
+        for name in channel_names.iter() {
+            let Some(name) = name.as_string() else {
+                wasm_log!(Error, "Channel names must be strings, got {:?}", name);
+                results.push(JsValue::NULL);
+                continue;
+            };
+            if let Err(e) = validate_channel_name(&name) {
+                wasm_log!(Error, "Cannot subscribe to {}: {}", name, e);
+                results.push(JsValue::NULL);
+                continue;
+            }
+            if !channel_requires_auth(&name) {
+                results.push(match self.subscribe(&name, None) {
+                    Ok(channel) => channel.into(),
+                    Err(_) => JsValue::NULL,
+                });
+                continue;
+            }
+
+            let mut inner = self.inner.write();
+            if let Some(channel) = inner.channels.get(&name) {
+                results.push(channel.clone().into());
+                continue;
+            }
+            if inner.options.auth_endpoint.is_none() {
+                wasm_log!(
+                    Error,
+                    "Cannot subscribe to {}: no auth_endpoint configured",
+                    name
+                );
+                results.push(JsValue::NULL);
+                continue;
+            }
+            let channel = self.register_channel(&mut inner, &name);
+            results.push(channel.clone().into());
+            pending.push((results.len() - 1, name, channel));
+        }
+
+        let shared = self.inner.clone();
+        wasm_bindgen_futures::future_to_promise(async move {
+            for (index, name, channel) in pending {
+                let (ws, socket_id, auth_endpoint, signature_mode, metadata) = {
+                    let inner = shared.read();
+                    match (&inner.ws, &inner.socket_id, &inner.options.auth_endpoint) {
+                        (Some(ws), Some(socket_id), Some(auth_endpoint))
+                            if inner.state == "connected" =>
+                        {
+                            (
+                                ws.clone(),
+                                socket_id.clone(),
+                                auth_endpoint.clone(),
+                                inner.options.auth_signature_mode.clone(),
+                                inner.options.global_subscription_metadata.clone(),
                             )
-                            .into(),
-                        );
+                        }
+                        // Subscribed once connected, as with `subscribe`
+                        _ => continue,
+                    }
+                };
+
+                if let Err(e) = Self::subscribe_authenticated(
+                    &ws,
+                    &auth_endpoint,
+                    &signature_mode,
+                    &socket_id,
+                    &name,
+                    None,
+                    &metadata,
+                )
+                .await
+                {
+                    wasm_log!(Error, "{}", e.as_string().unwrap_or_default());
+                    let mut inner = shared.write();
+                    // Only drop the channel this call added
+                    if inner
+                        .channels
+                        .get(&name)
+                        .is_some_and(|current| Arc::ptr_eq(&current.callbacks, &channel.callbacks))
+                    {
+                        inner.channels.remove(&name);
                     }
+                    results[index] = JsValue::NULL;
                 }
             }
-        }
+            Ok(results.into_iter().collect::<Array>().into())
+        })
+    }
 
-        Ok(channel)
+    /// Authenticate `channel_name` and send its subscribe request on `ws`
+    async fn subscribe_authenticated(
+        ws: &web_sys::WebSocket,
+        auth_endpoint: &str,
+        signature_mode: &AuthSignatureMode,
+        socket_id: &str,
+        channel_name: &str,
+        filter: Option<&InternalFilterOp>,
+        metadata: &std::collections::HashMap<String, String>,
+    ) -> Result<(), JsValue> {
+        let auth_data =
+            Self::authenticate_channel(auth_endpoint, signature_mode, channel_name, socket_id)
+                .await
+                .map_err(|e| {
+                    JsValue::from_str(&format!(
+                        "Failed to authenticate channel {}: {:?}",
+                        channel_name, e
+                    ))
+                })?;
+
+        // Build subscribe message with auth and channel_data
+        let msg_str = Protocol::create_subscribe_event_with_metadata(
+            channel_name,
+            Some(auth_data.auth.as_str()),
+            auth_data.channel_data.as_deref(),
+            filter,
+            metadata,
+        )
+        .and_then(|event| Protocol::encode_message(&event))
+        .map_err(|e| JsValue::from_str(&format!("Failed to subscribe: {}", e)))?;
+
+        let _ = ws.send_with_str(&msg_str);
+        wasm_log!(
+            Info,
+            "Subscribing to authenticated channel: {} with auth: {}",
+            channel_name,
+            auth_data.auth
+        );
+        Ok(())
+    }
+
+    /// Subscribe to a channel and wait for the subscription to succeed.
+    ///
+    /// Returns a promise resolving to the channel once the server confirms
+    /// the subscription.
+    #[wasm_bindgen(js_name = subscribeAndWait)]
+    pub fn subscribe_and_wait(&self, channel_name: &str) -> js_sys::Promise {
+        let channel = match self.subscribe(channel_name, None) {
+            Ok(channel) => channel,
+            Err(e) => return js_sys::Promise::reject(&e),
+        };
+
+        let subscribed = channel.on_subscribed();
+        wasm_bindgen_futures::future_to_promise(async move {
+            wasm_bindgen_futures::JsFuture::from(subscribed).await?;
+            Ok(JsValue::from(channel))
+        })
     }
 
     /// Helper method to authenticate a channel via the auth endpoint
     async fn authenticate_channel(
         auth_endpoint: &str,
+        signature_mode: &AuthSignatureMode,
         channel_name: &str,
         socket_id: &str,
     ) -> Result<AuthData, JsValue> {
@@ -954,13 +1510,21 @@ impl WasmSockudo {
             urlencoding::encode(channel_name)
         );
 
-        web_sys::console::log_1(
-            &format!("Auth request to: {} with body: {}", auth_endpoint, body).into(),
+        wasm_log!(
+            Debug,
+            "Auth request to: {} with body: {}",
+            auth_endpoint,
+            body
         );
 
-        // Make HTTP POST request with form-urlencoded content type
-        let request = gloo_net::http::Request::post(auth_endpoint)
-            .header("Content-Type", "application/x-www-form-urlencoded")
+        // Make HTTP POST request with form-urlencoded content type, signed
+        // when HMAC auth is configured
+        let mut request = gloo_net::http::Request::post(auth_endpoint)
+            .header("Content-Type", "application/x-www-form-urlencoded");
+        for (key, value) in crate::auth::signature_headers(signature_mode, &body) {
+            request = request.header(key, &value);
+        }
+        let request = request
             .body(body)
             .map_err(|e| JsValue::from_str(&format!("Failed to build request: {}", e)))?;
 
@@ -997,23 +1561,284 @@ impl WasmSockudo {
         // Send unsubscribe message if connected
         if let Some(ws) = &inner.ws {
             if inner.state == "connected" {
-                let unsubscribe_msg = serde_json::json!({
-                    "event": "pusher:unsubscribe",
-                    "data": {
-                        "channel": channel_name
-                    }
-                });
+                let unsubscribe_msg = Protocol::create_unsubscribe_event(channel_name);
 
-                if let Ok(msg_str) = serde_json::to_string(&unsubscribe_msg) {
+                if let Ok(msg_str) = Protocol::encode_message(&unsubscribe_msg) {
                     let _ = ws.send_with_str(&msg_str);
-                    web_sys::console::log_1(
-                        &format!("Unsubscribing from channel: {}", channel_name).into(),
-                    );
+                    wasm_log!(Info, "Unsubscribing from channel: {}", channel_name);
+                }
+            }
+        }
+
+        if let Some(channel) = inner.channels.remove(channel_name) {
+            channel.mark_unsubscribed();
+        }
+    }
+
+    /// Get the names of all subscribed channels
+    #[wasm_bindgen(js_name = channelNames)]
+    pub fn channel_names(&self) -> Array {
+        self.inner
+            .read()
+            .channels
+            .keys()
+            .map(|name| JsValue::from_str(name))
+            .collect()
+    }
+
+    /// Get received event counts as a plain `{ eventName: count }` object
+    #[cfg(feature = "event-stats")]
+    #[wasm_bindgen(js_name = getEventStats)]
+    pub fn get_event_stats(&self) -> JsValue {
+        let obj = js_sys::Object::new();
+        for (name, count) in self.inner.read().event_counts.iter() {
+            js_sys::Reflect::set(
+                &obj,
+                &JsValue::from_str(name),
+                &JsValue::from_f64(*count as f64),
+            )
+            .ok();
+        }
+        obj.into()
+    }
+
+    /// Get a snapshot of the connection state, subscriptions and delta stats
+    #[wasm_bindgen(js_name = getStatus)]
+    pub fn get_status(&self) -> JsValue {
+        let inner = self.inner.read();
+        let delta_stats = if inner.delta_compression_enabled {
+            serde_json::to_value(&inner.delta_stats).unwrap_or(serde_json::Value::Null)
+        } else {
+            serde_json::Value::Null
+        };
+        let status = serde_json::json!({
+            "state": inner.state,
+            "socketId": inner.socket_id,
+            "subscribedChannels": inner.channels.keys().collect::<Vec<_>>(),
+            "deltaStats": delta_stats,
+            "lastError": inner.last_error,
+        });
+        js_sys::JSON::parse(&status.to_string()).unwrap_or(JsValue::NULL)
+    }
+
+    /// Milliseconds since the client connected, or `undefined` while not
+    /// connected
+    #[wasm_bindgen(js_name = getUptimeMs)]
+    pub fn get_uptime_ms(&self) -> Option<f64> {
+        self.inner
+            .read()
+            .connected_since
+            .map(|since| (js_sys::Date::now() - since).max(0.0))
+    }
+
+    /// Round-trip time of the most recent `probeConnection`, in
+    /// milliseconds, or `undefined` before the first one
+    #[wasm_bindgen(js_name = getPingRttMs)]
+    pub fn get_ping_rtt_ms(&self) -> Option<f64> {
+        self.inner.read().last_ping_rtt_ms
+    }
+
+    /// Measure the round-trip time of a `pusher:ping` to the server.
+    ///
+    /// Returns a promise resolving to the RTT in milliseconds. When
+    /// connected, the ping goes over the current socket; otherwise a
+    /// separate socket is opened for the probe and closed afterwards, so
+    /// subscriptions are never touched. Rejects if no pong arrives within
+    /// `timeoutMs`.
+    #[wasm_bindgen(js_name = probeConnection)]
+    pub fn probe_connection(&self, timeout_ms: u32) -> js_sys::Promise {
+        use futures::future::{select, Either};
+
+        let shared = self.inner.clone();
+        wasm_bindgen_futures::future_to_promise(async move {
+            let (tx, rx) = futures::channel::oneshot::channel();
+            let ping = Protocol::encode_message(&Protocol::create_ping_event())
+                .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+            let open_socket = {
+                let inner = shared.read();
+                inner.ws.clone().filter(|_| inner.state == "connected")
+            };
+            // Keeps the probe socket and its handler alive until it is closed
+            let probe_socket = match open_socket {
+                Some(ws) => {
+                    shared.write().pong_waiters.push((js_sys::Date::now(), tx));
+                    ws.send_with_str(&ping)?;
+                    None
+                }
+                None => {
+                    let url = shared.read().ws_url()?;
+                    let ws = web_sys::WebSocket::new(&url)?;
+                    let ws_clone = ws.clone();
+                    let mut tx = Some(tx);
+                    let mut sent_at = None;
+                    let onmessage = Closure::wrap(Box::new(move |event: web_sys::MessageEvent| {
+                        let Some(text) = event.data().as_string() else {
+                            return;
+                        };
+                        let Ok(message) = serde_json::from_str::<serde_json::Value>(&text) else {
+                            return;
+                        };
+                        match message.get("event").and_then(|v| v.as_str()) {
+                            Some("pusher:connection_established") => {
+                                sent_at = Some(js_sys::Date::now());
+                                let _ = ws_clone.send_with_str(&ping);
+                            }
+                            Some("pusher:pong") => {
+                                if let (Some(sent_at), Some(tx)) = (sent_at, tx.take()) {
+                                    let _ = tx.send(js_sys::Date::now() - sent_at);
+                                }
+                            }
+                            _ => {}
+                        }
+                    })
+                        as Box<dyn FnMut(web_sys::MessageEvent)>);
+                    ws.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+                    Some((ws, onmessage))
                 }
+            };
+
+            let timeout = gloo_timers::future::TimeoutFuture::new(timeout_ms);
+            let result = match select(rx, timeout).await {
+                Either::Left((Ok(rtt), _)) => Ok(rtt),
+                _ => Err(JsValue::from_str(&format!(
+                    "No pong received within {}ms",
+                    timeout_ms
+                ))),
+            };
+
+            if let Some((ws, _onmessage)) = probe_socket {
+                ws.set_onmessage(None);
+                let _ = ws.close();
             }
+            let rtt = result?;
+            shared.write().last_ping_rtt_ms = Some(rtt);
+            Ok(JsValue::from_f64(rtt))
+        })
+    }
+
+    /// Get the most recent connection attempts, oldest first, as plain
+    /// objects `{ attemptNumber, startedAt, connectedAt, error, delayBeforeMs }`.
+    /// Times are milliseconds since the Unix epoch.
+    #[wasm_bindgen(js_name = getReconnectHistory)]
+    pub fn get_reconnect_history(&self) -> js_sys::Array {
+        self.inner
+            .read()
+            .reconnect_history
+            .iter()
+            .map(|attempt| {
+                js_sys::JSON::parse(&reconnect_attempt_json(attempt).to_string())
+                    .unwrap_or(JsValue::NULL)
+            })
+            .collect()
+    }
+
+    /// Get the client state for a support request, with the socket ID
+    /// masked to its last 4 characters. Paste
+    /// `JSON.stringify(client.debugDump(), null, 2)` into bug reports.
+    #[wasm_bindgen(js_name = debugDump)]
+    pub fn debug_dump(&self) -> JsValue {
+        let inner = self.inner.read();
+        let delta_stats = if inner.delta_compression_enabled {
+            serde_json::to_value(&inner.delta_stats).unwrap_or(serde_json::Value::Null)
+        } else {
+            serde_json::Value::Null
+        };
+        let (subscribed, pending): (Vec<_>, Vec<_>) = inner
+            .channels
+            .iter()
+            .partition(|(_, channel)| channel.subscribed());
+        let subscribed: Vec<&String> = subscribed.into_iter().map(|(name, _)| name).collect();
+        let pending: Vec<&String> = pending.into_iter().map(|(name, _)| name).collect();
+        let dump = serde_json::json!({
+            "version": crate::protocol::CLIENT_VERSION,
+            "state": inner.state,
+            "socketId": inner
+                .socket_id
+                .as_deref()
+                .map(crate::options::redact_socket_id),
+            "channelCount": inner.channels.len(),
+            "subscribedChannels": subscribed,
+            "pendingChannels": pending,
+            "deltaStats": delta_stats,
+            "lastError": inner.last_error,
+            "reconnectHistory": inner
+                .reconnect_history
+                .iter()
+                .map(reconnect_attempt_json)
+                .collect::<Vec<_>>(),
+        });
+        js_sys::JSON::parse(&dump.to_string()).unwrap_or(JsValue::NULL)
+    }
+
+    /// Get channel counts per type, e.g.
+    /// `{ public: 3, private: 2, presence: 1, privateEncrypted: 0 }`
+    #[wasm_bindgen(js_name = getChannelCountsByType)]
+    pub fn get_channel_counts_by_type(&self) -> JsValue {
+        use crate::channels::ChannelType;
+
+        let mut counts = [0u32; 4];
+        for name in self.inner.read().channels.keys() {
+            let index = match ChannelType::from_name(name) {
+                ChannelType::Public => 0,
+                ChannelType::Private => 1,
+                ChannelType::Presence => 2,
+                ChannelType::PrivateEncrypted => 3,
+            };
+            counts[index] += 1;
+        }
+
+        let obj = js_sys::Object::new();
+        for (key, count) in ["public", "private", "presence", "privateEncrypted"]
+            .iter()
+            .zip(counts)
+        {
+            js_sys::Reflect::set(&obj, &JsValue::from_str(key), &JsValue::from(count)).ok();
+        }
+        obj.into()
+    }
+
+    /// Get the number of subscribed channels
+    #[wasm_bindgen(js_name = channelCount)]
+    pub fn channel_count(&self) -> usize {
+        self.inner.read().channels.len()
+    }
+
+    /// Check whether a channel is in the subscribed channel map
+    #[wasm_bindgen(js_name = isSubscribed)]
+    pub fn is_subscribed(&self, channel_name: &str) -> bool {
+        self.inner.read().channels.contains_key(channel_name)
+    }
+
+    /// Get the number of subscribed channels
+    #[wasm_bindgen(js_name = subscriptionCount)]
+    pub fn subscription_count(&self) -> usize {
+        self.inner.read().channels.len()
+    }
+
+    /// Unsubscribe from every channel
+    #[wasm_bindgen(js_name = unsubscribeAll)]
+    pub fn unsubscribe_all(&self) {
+        let names: Vec<String> = self.inner.read().channels.keys().cloned().collect();
+        for name in names {
+            self.unsubscribe(&name);
         }
+    }
 
-        inner.channels.remove(channel_name);
+    /// Unsubscribe from every channel whose name starts with `prefix`
+    #[wasm_bindgen(js_name = unsubscribeByPrefix)]
+    pub fn unsubscribe_by_prefix(&self, prefix: &str) {
+        let names: Vec<String> = self
+            .inner
+            .read()
+            .channels
+            .keys()
+            .filter(|name| name.starts_with(prefix))
+            .cloned()
+            .collect();
+        for name in names {
+            self.unsubscribe(&name);
+        }
     }
 
     /// Get a channel by name
@@ -1022,15 +1847,38 @@ impl WasmSockudo {
         self.inner.read().channels.get(name).cloned()
     }
 
+    /// Get a presence channel by name, with its member list
+    #[wasm_bindgen(js_name = presenceChannel)]
+    pub fn presence_channel(&self, name: &str) -> Option<WasmPresenceChannel> {
+        let channel = self
+            .channel(name)
+            .filter(|_| name.starts_with("presence-"))?;
+        Some(WasmPresenceChannel {
+            members: channel.members.clone(),
+            inner: channel,
+            my_id: Arc::new(RwLock::new(None)),
+        })
+    }
+
     /// Bind a callback to an event
     #[wasm_bindgen]
     pub fn bind(&self, event_name: &str, callback: Function) {
         let mut inner = self.inner.write();
-        inner
-            .callbacks
-            .entry(event_name.to_string())
-            .or_default()
-            .push(callback);
+        let limit = inner.max_listeners;
+        let bound = inner.callbacks.entry(event_name.to_string()).or_default();
+        bound.push(callback);
+        warn_listener_limit(bound.len(), limit, event_name);
+    }
+
+    /// Set the listener leak warning threshold for the client and all of
+    /// its channels, including channels subscribed later (0 disables it)
+    #[wasm_bindgen(js_name = setGlobalMaxListeners)]
+    pub fn set_global_max_listeners(&self, limit: usize) {
+        let mut inner = self.inner.write();
+        inner.max_listeners = limit;
+        for channel in inner.channels.values() {
+            channel.set_max_listeners(limit);
+        }
     }
 
     /// Bind a global callback
@@ -1064,6 +1912,16 @@ impl WasmSockudo {
         inner.global_callbacks.clear();
     }
 
+    /// Send a client event on a subscribed private or presence channel.
+    ///
+    /// Returns `false`, with a console warning, if the event name does not
+    /// start with `client-`, the channel is not subscribed or not private or
+    /// presence, or the client is not connected.
+    #[wasm_bindgen(js_name = sendEventToChannel)]
+    pub fn send_event_to_channel(&self, channel: &str, event_name: &str, data: JsValue) -> bool {
+        send_client_event(&self.inner, channel, event_name, &data)
+    }
+
     /// Send an event
     #[wasm_bindgen]
     pub fn send_event(&self, event_name: &str, data: JsValue, channel: Option<String>) -> bool {
@@ -1093,7 +1951,7 @@ impl WasmSockudo {
 
                 if let Ok(msg_str) = serde_json::to_string(&event_msg) {
                     if ws.send_with_str(&msg_str).is_ok() {
-                        web_sys::console::log_1(&format!("Sent event: {}", event_name).into());
+                        wasm_log!(Debug, "Sent event: {}", event_name);
                         return true;
                     }
                 }
@@ -1103,82 +1961,175 @@ impl WasmSockudo {
         false
     }
 
+    /// Get the delta algorithm the server chose, if it reported one
+    #[wasm_bindgen(js_name = getActiveDeltaAlgorithm)]
+    pub fn get_active_delta_algorithm(&self) -> Option<String> {
+        self.inner.read().delta_stats.active_algorithm.clone()
+    }
+
+    /// Decode deltas on `channel` with `algorithm` ("fossil" or "xdelta3"),
+    /// whatever algorithm the server names in each delta message
+    #[wasm_bindgen(js_name = setChannelDeltaAlgorithm)]
+    pub fn set_channel_delta_algorithm(
+        &self,
+        channel: &str,
+        algorithm: &str,
+    ) -> Result<(), JsValue> {
+        let algorithm = algorithm
+            .parse::<DeltaAlgorithm>()
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.inner
+            .write()
+            .channel_delta_algorithms
+            .insert(channel.to_string(), algorithm);
+        Ok(())
+    }
+
+    /// Names of the delta decoders compiled into this build
+    #[wasm_bindgen(js_name = getAvailableAlgorithms)]
+    pub fn get_available_algorithms() -> Array {
+        decoders::list_available()
+            .into_iter()
+            .map(JsValue::from_str)
+            .collect()
+    }
+
     /// Get delta compression stats
     #[wasm_bindgen]
     pub fn get_delta_stats(&self) -> JsValue {
         let inner = self.inner.read();
-        let stats = &inner.delta_stats;
-
         // Check if delta compression is enabled (runtime flag from server)
-        let enabled = inner.delta_compression_enabled;
+        delta_stats_object(&inner.delta_stats, inner.delta_compression_enabled).into()
+    }
 
-        // Create a JS object with the stats
-        let obj = js_sys::Object::new();
+    /// Merge a stats object in the shape of `get_delta_stats` (e.g. from
+    /// another client) with this client's stats, returning the combined stats
+    #[wasm_bindgen(js_name = mergeDeltaStats)]
+    pub fn merge_delta_stats(&self, other: JsValue) -> JsValue {
+        let inner = self.inner.read();
+        let merged = inner.delta_stats.merged(&delta_stats_from_object(&other));
+        delta_stats_object(&merged, inner.delta_compression_enabled).into()
+    }
 
-        js_sys::Reflect::set(&obj, &"enabled".into(), &JsValue::from_bool(enabled)).ok();
-        js_sys::Reflect::set(
-            &obj,
-            &"totalMessages".into(),
-            &JsValue::from_f64(stats.total_messages as f64),
-        )
-        .ok();
-        js_sys::Reflect::set(
-            &obj,
-            &"deltaMessages".into(),
-            &JsValue::from_f64(stats.delta_messages as f64),
-        )
-        .ok();
-        js_sys::Reflect::set(
-            &obj,
-            &"fullMessages".into(),
-            &JsValue::from_f64(stats.full_messages as f64),
-        )
-        .ok();
-        js_sys::Reflect::set(
-            &obj,
-            &"totalBytesWithoutCompression".into(),
-            &JsValue::from_f64(stats.total_bytes_without_compression as f64),
-        )
-        .ok();
-        js_sys::Reflect::set(
-            &obj,
-            &"totalBytesWithCompression".into(),
-            &JsValue::from_f64(stats.total_bytes_with_compression as f64),
-        )
-        .ok();
-        js_sys::Reflect::set(
-            &obj,
-            &"bandwidthSaved".into(),
-            &JsValue::from_f64(stats.bandwidth_saved as f64),
-        )
-        .ok();
-        js_sys::Reflect::set(
-            &obj,
-            &"bandwidthSavedPercent".into(),
-            &JsValue::from_f64(stats.bandwidth_saved_percent),
-        )
-        .ok();
-        js_sys::Reflect::set(
-            &obj,
-            &"errors".into(),
-            &JsValue::from_f64(stats.errors as f64),
-        )
-        .ok();
-        js_sys::Reflect::set(
-            &obj,
-            &"channelCount".into(),
-            &JsValue::from_f64(stats.channel_count as f64),
-        )
-        .ok();
+    /// Get delta stats of one channel, in the shape of `get_delta_stats`,
+    /// or `null` if the channel has received no messages
+    #[wasm_bindgen]
+    pub fn get_delta_stats_for_channel(&self, channel_name: &str) -> JsValue {
+        let inner = self.inner.read();
+        match inner.channel_delta_stats.get(channel_name) {
+            Some(stats) => delta_stats_object(stats, inner.delta_compression_enabled).into(),
+            None => JsValue::NULL,
+        }
+    }
 
+    /// Route `raw_json` as if it had been received from the socket
+    #[cfg(feature = "test-utils")]
+    #[wasm_bindgen(js_name = injectMessage)]
+    pub fn inject_message(&self, raw_json: &str) {
+        Self::handle_incoming(&self.inner, raw_json.to_string());
+    }
+
+    /// Keep client event frames instead of sending them, as if connected,
+    /// until `takeSentFrames` collects them
+    #[cfg(feature = "test-utils")]
+    #[wasm_bindgen(js_name = captureSentFrames)]
+    pub fn capture_sent_frames(&self) {
+        self.inner
+            .write()
+            .sent_frames
+            .get_or_insert_with(Default::default);
+    }
+
+    /// Client event frames kept since the last call, oldest first
+    #[cfg(feature = "test-utils")]
+    #[wasm_bindgen(js_name = takeSentFrames)]
+    pub fn take_sent_frames(&self) -> Array {
+        self.inner
+            .read()
+            .sent_frames
+            .as_ref()
+            .map(|frames| std::mem::take(&mut *frames.lock()))
+            .unwrap_or_default()
+            .iter()
+            .map(|frame| JsValue::from_str(frame))
+            .collect()
+    }
+
+    /// Get the delta stats of every channel, keyed by channel name
+    #[wasm_bindgen]
+    pub fn get_all_channel_delta_stats(&self) -> JsValue {
+        let inner = self.inner.read();
+        let obj = js_sys::Object::new();
+        for (channel, stats) in &inner.channel_delta_stats {
+            js_sys::Reflect::set(
+                &obj,
+                &JsValue::from_str(channel),
+                &delta_stats_object(stats, inner.delta_compression_enabled),
+            )
+            .ok();
+        }
         obj.into()
     }
 
+    /// Discard the delta base message for a channel and ask the server to
+    /// resend a full message. The server must support the
+    /// `pusher:delta_resync_request` event.
+    #[wasm_bindgen(js_name = forceDeltaResync)]
+    pub fn force_delta_resync(&self, channel_name: &str) {
+        let mut inner = self.inner.write();
+        inner.delta_base_messages.remove(channel_name);
+        inner.delta_stats.resyncs_requested += 1;
+
+        if let Some(ref ws) = inner.ws {
+            let request = serde_json::json!({
+                "event": "pusher:delta_resync_request",
+                "data": { "channel": channel_name }
+            });
+            let _ = ws.send_with_str(&request.to_string());
+        }
+
+        if let Some(channel) = inner.channels.get(channel_name) {
+            let message = serde_json::json!({
+                "event": "sockudo:delta_resync",
+                "channel": channel_name,
+                "data": { "channel": channel_name }
+            })
+            .to_string();
+            if let Some(cbs) = channel.callbacks.read().get("sockudo:delta_resync") {
+                for callback in cbs {
+                    let _ = callback.call1(&JsValue::NULL, &JsValue::from_str(&message));
+                }
+            }
+        }
+    }
+
     /// Reset delta compression stats
     #[wasm_bindgen]
     pub fn reset_delta_stats(&self) {
         let mut inner = self.inner.write();
+        // The negotiated algorithm is connection state, not a counter
+        let active_algorithm = inner.delta_stats.active_algorithm.take();
         inner.delta_stats.reset();
+        inner.delta_stats.active_algorithm = active_algorithm;
+        inner.channel_delta_stats.clear();
+    }
+
+    /// Replace the delta compression options with ones saved by
+    /// `WasmDeltaOptions.toJSON`.
+    ///
+    /// Delta compression is enabled or disabled as saved. Base messages and
+    /// delta stats are discarded, since they belong to the previous
+    /// configuration.
+    #[wasm_bindgen(js_name = restoreDeltaConfig)]
+    pub fn restore_delta_config(&self, json: &str) -> Result<(), JsValue> {
+        let options = WasmDeltaOptions::from_json_string(json)?;
+        let mut inner = self.inner.write();
+        inner.delta_compression_enabled = options.enabled;
+        inner.options.delta_compression = Some(options.to_delta_options());
+        inner.delta_base_messages.clear();
+        inner.delta_stats = DeltaStats::new();
+        inner.channel_delta_stats.clear();
+        Ok(())
     }
 
     /// Update delta stats when a message is received (internal helper)
@@ -1200,6 +2151,334 @@ impl WasmSockudo {
         inner.delta_stats.calculate_savings();
     }
 
+    /// Route a raw frame received from the socket
+    fn handle_incoming(shared: &Arc<RwLock<WasmSockudoInner>>, message: String) {
+        let message_size = message.len();
+        wasm_log!(Debug, "Received: {}", message);
+
+        // Parse Pusher message and handle it
+        if let Ok(event_data) = serde_json::from_str::<serde_json::Value>(&message) {
+            if let Some(event_name) = event_data.get("event").and_then(|v| v.as_str()) {
+                #[cfg(feature = "event-stats")]
+                {
+                    *shared
+                        .write()
+                        .event_counts
+                        .entry(event_name.to_string())
+                        .or_insert(0) += 1;
+                }
+
+                // Track delta stats for non-internal messages and deltas
+                let is_delta_frame = event_name == "pusher:delta";
+                if is_delta_frame
+                    || (!event_name.starts_with("pusher:")
+                        && !event_name.starts_with("pusher_internal:"))
+                {
+                    // Check if this is a delta message (has delta field in data)
+                    let is_delta = is_delta_frame
+                        || event_data
+                            .get("data")
+                            .and_then(|d| d.as_str())
+                            .and_then(|s| serde_json::from_str::<serde_json::Value>(s).ok())
+                            .map(|parsed| parsed.get("delta").is_some())
+                            .unwrap_or(false);
+
+                    let mut inner = shared.write();
+                    record_message_stats(&mut inner.delta_stats, is_delta, message_size);
+                    if let Some(channel) = event_data.get("channel").and_then(|v| v.as_str()) {
+                        let channel_stats = inner
+                            .channel_delta_stats
+                            .entry(channel.to_string())
+                            .or_default();
+                        record_message_stats(channel_stats, is_delta, message_size);
+                        inner.delta_stats.channel_count = inner.channel_delta_stats.len() as u64;
+                    }
+                    drop(inner);
+                }
+                // Answer the server heartbeat (pusher:ping by default) immediately
+                let is_heartbeat = shared.read().options.heartbeat_event == event_name;
+                if is_heartbeat {
+                    let inner = shared.read();
+                    let response = inner.options.heartbeat_response_event.as_str();
+                    wasm_log!(Debug, "Received {}, sending {}", event_name, response);
+                    if let Some(ref ws) = inner.ws {
+                        let pong = crate::protocol::PusherEvent::new(response)
+                            .with_json_data(serde_json::json!({}));
+                        if let Ok(pong_str) = Protocol::encode_message(&pong) {
+                            let _ = ws.send_with_str(&pong_str);
+                        }
+                    }
+                    let callbacks = inner.heartbeat_callbacks.clone();
+                    drop(inner);
+                    for callback in callbacks {
+                        let _ = callback.call0(&JsValue::NULL);
+                    }
+                }
+
+                if event_name == "pusher:error" {
+                    if let Some(server_error) = Protocol::decode_pusher_error(&message) {
+                        let callbacks = {
+                            let mut inner = shared.write();
+                            inner.last_error =
+                                Some(format!("{} ({})", server_error.message, server_error.code));
+                            inner.server_error_callbacks.clone()
+                        };
+                        if server_error.is_fatal {
+                            wasm_log!(
+                                Error,
+                                "Fatal server error {}: {}",
+                                server_error.code,
+                                server_error.message
+                            );
+                            transition_state(shared, "failed");
+                        }
+                        let error = js_sys::Object::new();
+                        let _ = js_sys::Reflect::set(
+                            &error,
+                            &"code".into(),
+                            &JsValue::from(server_error.code),
+                        );
+                        let _ = js_sys::Reflect::set(
+                            &error,
+                            &"message".into(),
+                            &JsValue::from_str(&server_error.message),
+                        );
+                        let _ = js_sys::Reflect::set(
+                            &error,
+                            &"isFatal".into(),
+                            &JsValue::from_bool(server_error.is_fatal),
+                        );
+                        for callback in callbacks {
+                            let _ = callback.call1(&JsValue::NULL, &error);
+                        }
+                    }
+                }
+
+                if event_name == "pusher:pong" {
+                    let mut inner = shared.write();
+                    if !inner.pong_waiters.is_empty() {
+                        let (sent_at, waiter) = inner.pong_waiters.remove(0);
+                        let rtt = js_sys::Date::now() - sent_at;
+                        if waiter.send(rtt).is_ok() {
+                            inner.last_ping_rtt_ms = Some(rtt);
+                        }
+                    }
+                }
+
+                if shared.read().paused {
+                    return;
+                }
+
+                // Handle pusher:delta_compression_enabled
+                if event_name == "pusher:delta_compression_enabled" {
+                    let mut inner = shared.write();
+                    inner.delta_compression_enabled = true;
+                    inner.delta_stats.active_algorithm = event_data
+                        .get("data")
+                        .and_then(|d| match d {
+                            serde_json::Value::String(s) => {
+                                serde_json::from_str::<serde_json::Value>(s).ok()
+                            }
+                            other => Some(other.clone()),
+                        })
+                        .and_then(|d| {
+                            d.get("algorithm")
+                                .and_then(|a| a.as_str())
+                                .map(String::from)
+                        });
+                    wasm_log!(Info, "Delta compression enabled!");
+                }
+
+                // Handle pusher:delta - decode and re-emit as original event
+                if event_name == "pusher:delta" {
+                    if let Some(channel) = event_data.get("channel").and_then(|v| v.as_str()) {
+                        if let Some(data) = event_data.get("data") {
+                            // Decode the delta message
+                            match Self::decode_delta_message(shared, channel, data.clone()) {
+                                Ok(reconstructed_message) => {
+                                    // Parse the reconstructed message and re-emit it
+                                    if let Ok(reconstructed_event) =
+                                        serde_json::from_str::<serde_json::Value>(
+                                            &reconstructed_message,
+                                        )
+                                    {
+                                        // Store the reconstructed message as new base
+                                        shared.write().delta_base_messages.insert(
+                                            channel.to_string(),
+                                            reconstructed_message.clone(),
+                                        );
+
+                                        // Extract the original event name and data
+                                        if let Some(orig_event) = reconstructed_event
+                                            .get("event")
+                                            .and_then(|v| v.as_str())
+                                        {
+                                            // Trigger channel callbacks with the decoded event
+                                            let inner = shared.read();
+                                            if let Some(ch) = inner.channels.get(channel) {
+                                                let callbacks = ch.callbacks.read();
+
+                                                // Build reconstructed message JSON
+                                                let reconstructed_msg = serde_json::json!({
+                                                    "event": orig_event,
+                                                    "channel": channel,
+                                                    "data": reconstructed_event.get("data")
+                                                })
+                                                .to_string();
+
+                                                // Trigger event-specific callbacks
+                                                if let Some(cbs) = callbacks.get(orig_event) {
+                                                    for callback in cbs {
+                                                        let _ = callback.call1(
+                                                            &JsValue::NULL,
+                                                            &JsValue::from_str(&reconstructed_msg),
+                                                        );
+                                                    }
+                                                }
+
+                                                // Trigger bind_all callbacks
+                                                if let Some(all_cbs) = callbacks.get("__all__") {
+                                                    for callback in all_cbs {
+                                                        let event_js =
+                                                            JsValue::from_str(orig_event);
+                                                        let data_js = reconstructed_event
+                                                            .get("data")
+                                                            .and_then(|v| {
+                                                                serde_json::to_string(v).ok()
+                                                            })
+                                                            .map(|s| JsValue::from_str(&s))
+                                                            .unwrap_or(JsValue::NULL);
+                                                        let _ = callback.call2(
+                                                            &JsValue::NULL,
+                                                            &event_js,
+                                                            &data_js,
+                                                        );
+                                                    }
+                                                }
+                                                drop(callbacks);
+                                                ch.record_history(orig_event, &reconstructed_msg);
+                                            }
+
+                                            // Trigger global callbacks with decoded event
+                                            let inner = shared.read();
+                                            if let Some(callbacks) = inner.callbacks.get(orig_event)
+                                            {
+                                                let reconstructed_msg = serde_json::json!({
+                                                    "event": orig_event,
+                                                    "channel": channel,
+                                                    "data": reconstructed_event.get("data")
+                                                })
+                                                .to_string();
+
+                                                for callback in callbacks {
+                                                    let _ = callback.call1(
+                                                        &JsValue::NULL,
+                                                        &JsValue::from_str(&reconstructed_msg),
+                                                    );
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                                Err(e) => {
+                                    wasm_log!(Error, "Delta decode failed: {}", e);
+                                }
+                            }
+                        }
+                    }
+                }
+
+                // Store base messages for delta compression (non-pusher events with sequence)
+                if !event_name.starts_with("pusher:") && !event_name.starts_with("pusher_internal:")
+                {
+                    if let Some(channel) = event_data.get("channel").and_then(|v| v.as_str()) {
+                        // Create sanitized base message (without sequence field)
+                        let sanitized = serde_json::json!({
+                            "event": event_data.get("event"),
+                            "channel": event_data.get("channel"),
+                            "data": event_data.get("data"),
+                        });
+                        if let Ok(base_msg) = serde_json::to_string(&sanitized) {
+                            shared
+                                .write()
+                                .delta_base_messages
+                                .insert(channel.to_string(), base_msg);
+                        }
+                    }
+                }
+
+                // Handle pusher:connection_established
+                if event_name == "pusher:connection_established" {
+                    // Pings sent on an earlier socket are never answered
+                    shared.write().pong_waiters.clear();
+                    if let Some(data) = event_data.get("data").and_then(|v| v.as_str()) {
+                        if let Ok(conn_data) = serde_json::from_str::<serde_json::Value>(data) {
+                            if let Some(socket_id) =
+                                conn_data.get("socket_id").and_then(|v| v.as_str())
+                            {
+                                shared.write().socket_id = Some(socket_id.to_string());
+                                wasm_log!(Info, "Socket ID: {}", socket_id);
+                            }
+                        }
+                    }
+                }
+
+                // Don't propagate pusher:delta events through normal channels
+                // (they've already been decoded and re-emitted above)
+                if event_name != "pusher:delta" {
+                    // Get channel name if present
+                    let channel_name = event_data.get("channel").and_then(|v| v.as_str());
+
+                    // Trigger channel-specific callbacks
+                    if let Some(ch_name) = channel_name {
+                        let inner = shared.read();
+                        if let Some(channel) = inner.channels.get(ch_name) {
+                            channel.track_protocol_event(event_name, &message);
+                            let callbacks = channel.callbacks.read();
+
+                            // Trigger event-specific callbacks
+                            if let Some(cbs) = callbacks.get(event_name) {
+                                for callback in cbs {
+                                    let _ = callback
+                                        .call1(&JsValue::NULL, &JsValue::from_str(&message));
+                                }
+                            }
+
+                            // Trigger bind_all callbacks
+                            if let Some(all_cbs) = callbacks.get("__all__") {
+                                for callback in all_cbs {
+                                    // Call with event name and data
+                                    let event_js = JsValue::from_str(event_name);
+                                    let data_js = event_data
+                                        .get("data")
+                                        .and_then(|v| v.as_str())
+                                        .map(|s| JsValue::from_str(s))
+                                        .unwrap_or(JsValue::NULL);
+                                    let _ = callback.call2(&JsValue::NULL, &event_js, &data_js);
+                                }
+                            }
+                            drop(callbacks);
+                            channel.record_history(event_name, &message);
+                        }
+                    }
+
+                    // Trigger global event callbacks
+                    let inner = shared.read();
+                    if let Some(callbacks) = inner.callbacks.get(event_name) {
+                        for callback in callbacks {
+                            let _ = callback.call1(&JsValue::NULL, &JsValue::from_str(&message));
+                        }
+                    }
+
+                    // Trigger global callbacks
+                    for callback in &inner.global_callbacks {
+                        let _ = callback.call1(&JsValue::NULL, &JsValue::from_str(&message));
+                    }
+                }
+            }
+        }
+    }
+
     /// Decode a delta message
     fn decode_delta_message(
         inner: &Arc<RwLock<WasmSockudoInner>>,
@@ -1207,9 +2486,14 @@ impl WasmSockudo {
         delta_data: serde_json::Value,
     ) -> Result<String, String> {
         // Extract delta fields
-        let algorithm = delta_data
-            .get("algorithm")
-            .and_then(|v| v.as_str())
+        let channel_algorithm = inner
+            .read()
+            .channel_delta_algorithms
+            .get(channel)
+            .map(|a| a.to_string());
+        let algorithm = channel_algorithm
+            .as_deref()
+            .or_else(|| delta_data.get("algorithm").and_then(|v| v.as_str()))
             .unwrap_or("fossil");
         let delta_base64 = delta_data
             .get("delta")
@@ -1229,14 +2513,12 @@ impl WasmSockudo {
             .clone();
         drop(inner_lock);
 
-        web_sys::console::log_1(
-            &format!(
-                "[WASM Delta] Decoding with {}, base length: {}, delta: {}",
-                algorithm,
-                base_message.len(),
-                delta_base64
-            )
-            .into(),
+        wasm_log!(
+            Debug,
+            "[WASM Delta] Decoding with {}, base length: {}, delta: {}",
+            algorithm,
+            base_message.len(),
+            delta_base64
         );
 
         // Decode base64 delta
@@ -1253,12 +2535,10 @@ impl WasmSockudo {
         let reconstructed = String::from_utf8(reconstructed_bytes)
             .map_err(|e| format!("UTF-8 decode failed: {}", e))?;
 
-        web_sys::console::log_1(
-            &format!(
-                "[WASM Delta] Decoded successfully, result length: {}",
-                reconstructed.len()
-            )
-            .into(),
+        wasm_log!(
+            Debug,
+            "[WASM Delta] Decoded successfully, result length: {}",
+            reconstructed.len()
         );
 
         Ok(reconstructed)
@@ -1270,20 +2550,70 @@ impl WasmSockudo {
 #[derive(Clone)]
 pub struct WasmChannel {
     name: String,
-    subscribed: bool,
+    /// Set on `subscription_succeeded`, cleared on unsubscribe and
+    /// disconnect (shared between clones)
+    subscribed: Arc<std::sync::atomic::AtomicBool>,
+    /// Members of a presence channel, kept in sync by the message loop
+    members: Arc<RwLock<Vec<WasmMember>>>,
     callbacks: Arc<RwLock<std::collections::HashMap<String, Vec<Function>>>>,
+    max_listeners: Arc<std::sync::atomic::AtomicUsize>,
+    /// Most recent events received, if enabled
+    history: Arc<RwLock<crate::channels::EventHistory>>,
+    /// Client that owns the channel, used to reach its WebSocket
+    parent: Weak<RwLock<WasmSockudoInner>>,
 }
 
 #[wasm_bindgen]
 impl WasmChannel {
-    fn new(name: &str) -> Self {
+    fn new(name: &str, parent: Weak<RwLock<WasmSockudoInner>>) -> Self {
         Self {
             name: name.to_string(),
-            subscribed: false,
+            subscribed: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            members: Arc::new(RwLock::new(Vec::new())),
             callbacks: Arc::new(RwLock::new(std::collections::HashMap::new())),
+            max_listeners: Arc::new(std::sync::atomic::AtomicUsize::new(
+                crate::events::DEFAULT_MAX_LISTENERS,
+            )),
+            history: Arc::new(RwLock::new(crate::channels::EventHistory::default())),
+            parent,
         }
     }
 
+    /// Warn when more than `limit` callbacks are bound to one event name
+    /// (default 10, 0 disables the warning)
+    #[wasm_bindgen(js_name = setMaxListeners)]
+    pub fn set_max_listeners(&self, limit: usize) {
+        self.max_listeners
+            .store(limit, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Keep the last `size` events of each event name, including
+    /// `pusher:member_added` and `pusher:member_removed` (0 turns history
+    /// off)
+    #[wasm_bindgen(js_name = setHistorySize)]
+    pub fn set_history_size(&self, size: usize) {
+        self.history.write().set_capacity(size);
+    }
+
+    /// Up to `limit` of the most recent events as `{event, channel, data,
+    /// timestamp}` objects, newest first, only of `eventName` when given.
+    /// `timestamp` is when the event was received, in JS milliseconds.
+    #[wasm_bindgen(js_name = getEventHistory)]
+    pub fn get_event_history(&self, event_name: Option<String>, limit: usize) -> js_sys::Array {
+        self.history
+            .read()
+            .newest(event_name.as_deref(), limit)
+            .iter()
+            .filter_map(|event| {
+                let object = js_sys::JSON::parse(&Protocol::encode_message(event).ok()?).ok()?;
+                if let Some(timestamp) = event.received_at_ms() {
+                    let _ = js_sys::Reflect::set(&object, &"timestamp".into(), &timestamp.into());
+                }
+                Some(object)
+            })
+            .collect()
+    }
+
     /// Get channel name
     #[wasm_bindgen(getter)]
     pub fn name(&self) -> String {
@@ -1293,20 +2623,33 @@ impl WasmChannel {
     /// Check if subscribed
     #[wasm_bindgen(getter)]
     pub fn subscribed(&self) -> bool {
-        self.subscribed
+        self.subscribed.load(std::sync::atomic::Ordering::Relaxed)
     }
 
     /// Bind a callback to an event
     #[wasm_bindgen]
     pub fn bind(&self, event_name: &str, callback: Function) -> WasmChannel {
         let mut callbacks = self.callbacks.write();
-        callbacks
-            .entry(event_name.to_string())
-            .or_default()
-            .push(callback);
+        let bound = callbacks.entry(event_name.to_string()).or_default();
+        bound.push(callback);
+        warn_listener_limit(
+            bound.len(),
+            self.max_listeners
+                .load(std::sync::atomic::Ordering::Relaxed),
+            event_name,
+        );
         self.clone()
     }
 
+    /// Check whether any callback would fire for an event, including global bindings
+    #[wasm_bindgen(js_name = hasListeners)]
+    pub fn has_listeners(&self, event_name: &str) -> bool {
+        let callbacks = self.callbacks.read();
+        [event_name, "__all__"]
+            .iter()
+            .any(|name| callbacks.get(*name).is_some_and(|cbs| !cbs.is_empty()))
+    }
+
     /// Bind a callback to all events on this channel (global)
     #[wasm_bindgen(js_name = bind_global)]
     pub fn bind_global(&self, callback: Function) -> WasmChannel {
@@ -1344,6 +2687,78 @@ impl WasmChannel {
         self.clone()
     }
 
+    /// Returns a promise that resolves with the subscription message once the
+    /// subscription succeeds. The internal handler removes itself afterwards.
+    #[wasm_bindgen(js_name = onSubscribed)]
+    pub fn on_subscribed(&self) -> js_sys::Promise {
+        const EVENT: &str = "pusher_internal:subscription_succeeded";
+        let callbacks = self.callbacks.clone();
+
+        js_sys::Promise::new(&mut |resolve: Function, _reject: Function| {
+            let handle: Arc<RwLock<Option<Function>>> = Arc::new(RwLock::new(None));
+            let handle_clone = handle.clone();
+            let callbacks_clone = callbacks.clone();
+
+            let handler = Closure::wrap(Box::new(move |message: JsValue| {
+                let _ = resolve.call1(&JsValue::NULL, &message);
+
+                // Callbacks are read-locked while dispatching, so remove later
+                if let Some(func) = handle_clone.write().take() {
+                    let callbacks = callbacks_clone.clone();
+                    wasm_bindgen_futures::spawn_local(async move {
+                        if let Some(cbs) = callbacks.write().get_mut(EVENT) {
+                            cbs.retain(|cb| !js_sys::Object::is(cb, &func));
+                        }
+                    });
+                }
+            }) as Box<dyn FnMut(JsValue)>);
+
+            let func: Function = handler.into_js_value().unchecked_into();
+            *handle.write() = Some(func.clone());
+            callbacks
+                .write()
+                .entry(EVENT.to_string())
+                .or_default()
+                .push(func);
+        })
+    }
+
+    /// Call `callback` with the error message when the server rejects the subscription
+    #[wasm_bindgen(js_name = onSubscriptionFailed)]
+    pub fn on_subscription_failed(&self, callback: Function) -> WasmChannel {
+        let handler = Closure::wrap(Box::new(move |message: JsValue| {
+            let error = message
+                .as_string()
+                .and_then(|m| serde_json::from_str::<serde_json::Value>(&m).ok())
+                .map(|m| crate::channels::subscription_error_message(&m["data"]))
+                .unwrap_or_default();
+            let _ = callback.call1(&JsValue::NULL, &JsValue::from_str(&error));
+        }) as Box<dyn FnMut(JsValue)>);
+
+        self.bind(
+            "pusher:subscription_error",
+            handler.into_js_value().unchecked_into(),
+        )
+    }
+
+    /// Send a client event on this channel through the owning client's
+    /// WebSocket. Returns `false` if the client is gone or the event could
+    /// not be sent (see `WasmSockudo.sendEventToChannel`).
+    #[wasm_bindgen(js_name = sendEvent)]
+    pub fn send_event(&self, event_name: &str, data: JsValue) -> bool {
+        match self.parent.upgrade() {
+            Some(inner) => send_client_event(&inner, &self.name, event_name, &data),
+            None => {
+                wasm_log!(
+                    Warn,
+                    "Cannot send '{}': client has been dropped",
+                    event_name
+                );
+                false
+            }
+        }
+    }
+
     /// Trigger a client event (private/presence channels only)
     #[wasm_bindgen]
     pub fn trigger(&self, event_name: &str, data: JsValue) -> Result<bool, JsValue> {
@@ -1357,22 +2772,120 @@ impl WasmChannel {
             ));
         }
 
-        // Convert data to JSON string
-        let data_str = js_sys::JSON::stringify(&data)
-            .map(|s| String::from(s))
-            .map_err(|_| JsValue::from_str("Failed to stringify data"))?;
+        Ok(self.send_event(event_name, data))
+    }
 
-        let event_msg = serde_json::json!({
-            "event": event_name,
-            "channel": self.name,
-            "data": data_str
-        });
+    /// Trigger several client events as JSON array frames of up to 10 events.
+    ///
+    /// `events` is an array of `{ event, data }` objects. The promise
+    /// resolves to an array with one boolean per event.
+    #[wasm_bindgen(js_name = triggerBatch)]
+    pub fn trigger_batch(&self, events: Array) -> js_sys::Promise {
+        match self.encode_batch_frames(&events) {
+            Ok(frames) => {
+                for frame in &frames {
+                    wasm_log!(Info, "Would trigger batch: {}", frame);
+                }
+                let results: JsValue = events
+                    .iter()
+                    .map(|_| JsValue::TRUE)
+                    .collect::<Array>()
+                    .into();
+                js_sys::Promise::resolve(&results)
+            }
+            Err(e) => js_sys::Promise::reject(&e),
+        }
+    }
+}
+
+impl WasmChannel {
+    /// Track the subscription and presence members from a received frame
+    fn track_protocol_event(&self, event_name: &str, message: &str) {
+        match event_name {
+            "pusher_internal:subscription_succeeded" => {
+                self.subscribed
+                    .store(true, std::sync::atomic::Ordering::Relaxed);
+                if let Some(members) = parse_presence_members(message) {
+                    *self.members.write() = members;
+                }
+            }
+            "pusher_internal:member_added" => {
+                if let Some(member) = parse_member_message(message) {
+                    let mut members = self.members.write();
+                    members.retain(|m| m.id != member.id);
+                    members.push(member);
+                }
+            }
+            "pusher_internal:member_removed" | "pusher_internal:member_kicked" => {
+                if let Some(member) = parse_member_message(message) {
+                    self.members.write().retain(|m| m.id != member.id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Forget the subscription and members after an unsubscribe or
+    /// disconnect
+    fn mark_unsubscribed(&self) {
+        self.subscribed
+            .store(false, std::sync::atomic::Ordering::Relaxed);
+        self.members.write().clear();
+    }
+
+    /// Add a received frame to the event history if it carries a user or
+    /// member event
+    fn record_history(&self, event_name: &str, message: &str) {
+        let name = match event_name {
+            "pusher_internal:member_added" => "pusher:member_added",
+            "pusher_internal:member_removed" => "pusher:member_removed",
+            "pusher_internal:member_kicked" => "pusher:member_kicked",
+            name if name.starts_with("pusher") => return,
+            name => name,
+        };
+        if let Ok(mut event) = Protocol::decode_message(message) {
+            event.mark_received();
+            event.event = name.to_string();
+            self.history.write().record(&event);
+        }
+    }
+
+    fn encode_batch_frames(&self, events: &Array) -> Result<Vec<String>, JsValue> {
+        if !self.name.starts_with("private-") && !self.name.starts_with("presence-") {
+            return Err(JsValue::from_str(
+                "Client events only work on private/presence channels",
+            ));
+        }
+
+        let mut parsed = Vec::with_capacity(events.length() as usize);
+        for item in events.iter() {
+            let event_name = js_sys::Reflect::get(&item, &JsValue::from_str("event"))?
+                .as_string()
+                .ok_or_else(|| JsValue::from_str("Each event needs an 'event' name"))?;
+            if !event_name.starts_with("client-") {
+                return Err(JsValue::from_str("Client events must start with 'client-'"));
+            }
 
-        // Would need access to parent Pusher's WebSocket
-        // For now, log it
-        web_sys::console::log_1(&format!("Would trigger: {:?}", event_msg).into());
+            let data = js_sys::Reflect::get(&item, &JsValue::from_str("data"))?;
+            let data_str: String = js_sys::JSON::stringify(&data)
+                .map(String::from)
+                .map_err(|_| JsValue::from_str("Failed to stringify data"))?;
+            let data: serde_json::Value =
+                serde_json::from_str(&data_str).map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+            let mut event = crate::protocol::PusherEvent::new(&event_name);
+            event.data = Some(data);
+            event.channel = Some(self.name.clone());
+            parsed.push(event);
+        }
 
-        Ok(true)
+        parsed
+            .chunks(10)
+            .map(|chunk| {
+                crate::protocol::Protocol::encode_batch(chunk)
+                    .map_err(|e| JsValue::from_str(&e.to_string()))
+            })
+            .collect()
     }
 }
 
@@ -1404,6 +2917,48 @@ impl WasmPresenceChannel {
         arr
     }
 
+    /// Get all members sorted by a field of their info, ties broken by ID.
+    ///
+    /// Values are compared as strings (numbers by their JSON text, so `"10"`
+    /// sorts before `"9"`); members without the field come last.
+    #[wasm_bindgen(js_name = sortedMembers)]
+    pub fn sorted_members(&self, field: &str) -> Array {
+        let mut members = self.members.read().clone();
+        members.sort_by_cached_key(|member| {
+            let value = js_sys::Reflect::get(&member.info, &JsValue::from_str(field))
+                .ok()
+                .filter(|value| !value.is_undefined())
+                .and_then(|value| {
+                    value
+                        .as_string()
+                        .or_else(|| js_sys::JSON::stringify(&value).ok().map(String::from))
+                });
+            (value.is_none(), value, member.id.clone())
+        });
+        members.into_iter().map(JsValue::from).collect()
+    }
+
+    /// Get `{ userId: value }` for every member whose info has a string
+    /// `field`
+    #[wasm_bindgen(js_name = getMembersByField)]
+    pub fn get_members_by_field(&self, field: &str) -> js_sys::Object {
+        let result = js_sys::Object::new();
+        for member in self.members.read().iter() {
+            let Some(value) = js_sys::Reflect::get(&member.info, &JsValue::from_str(field))
+                .ok()
+                .and_then(|value| value.as_string())
+            else {
+                continue;
+            };
+            let _ = js_sys::Reflect::set(
+                &result,
+                &JsValue::from_str(&member.id),
+                &JsValue::from_str(&value),
+            );
+        }
+        result
+    }
+
     /// Get current user's member info
     #[wasm_bindgen]
     pub fn me(&self) -> Option<WasmMember> {
@@ -1431,6 +2986,379 @@ impl WasmPresenceChannel {
             .find(|m| m.id == user_id)
             .cloned()
     }
+
+    /// Replace a member's info locally. Returns false if the member is
+    /// unknown or `info_json` is not valid JSON.
+    #[wasm_bindgen(js_name = updateMemberInfo)]
+    pub fn update_member_info(&self, user_id: &str, info_json: &str) -> bool {
+        let info = match js_sys::JSON::parse(info_json) {
+            Ok(info) => info,
+            Err(_) => return false,
+        };
+
+        match self.members.write().iter_mut().find(|m| m.id == user_id) {
+            Some(member) => {
+                member.info = info;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Register a callback invoked with a `WasmMember` when a member joins
+    #[wasm_bindgen(js_name = onMemberAdded)]
+    pub fn on_member_added(&self, callback: Function) {
+        self.bind_member_event("pusher_internal:member_added", callback);
+    }
+
+    /// Register a callback invoked with a `WasmMember` when a member leaves
+    #[wasm_bindgen(js_name = onMemberRemoved)]
+    pub fn on_member_removed(&self, callback: Function) {
+        self.bind_member_event("pusher_internal:member_removed", callback);
+    }
+
+    /// Register a callback invoked with a `WasmMember` when the server
+    /// evicts a member after a `kick`
+    #[wasm_bindgen(js_name = onMemberKicked)]
+    pub fn on_member_kicked(&self, callback: Function) {
+        self.bind_member_event("pusher_internal:member_kicked", callback);
+    }
+
+    /// Ask the server to evict a member with a `client-kick` event.
+    /// Rejects if the channel is not subscribed or the member is unknown.
+    #[wasm_bindgen]
+    pub fn kick(&self, user_id: &str, reason: Option<String>) -> js_sys::Promise {
+        if !self.inner.subscribed() {
+            return js_sys::Promise::reject(&JsValue::from_str(
+                "Channel must be subscribed to kick members",
+            ));
+        }
+        if self.get(user_id).is_none() {
+            return js_sys::Promise::reject(&JsValue::from_str(&format!(
+                "Member not found: {}",
+                user_id
+            )));
+        }
+
+        let payload = serde_json::json!({
+            "user_id": user_id,
+            "reason": reason,
+        });
+        let data = match js_sys::JSON::parse(&payload.to_string()) {
+            Ok(data) => data,
+            Err(e) => return js_sys::Promise::reject(&e),
+        };
+
+        match self.inner.trigger("client-kick", data) {
+            Ok(sent) => js_sys::Promise::resolve(&JsValue::from_bool(sent)),
+            Err(e) => js_sys::Promise::reject(&e),
+        }
+    }
+}
+
+impl WasmPresenceChannel {
+    /// Bind a handler forwarding the affected member to `callback`. The
+    /// member list itself is kept in sync by the message loop.
+    fn bind_member_event(&self, event_name: &'static str, callback: Function) {
+        let handler = Closure::wrap(Box::new(move |message: JsValue| {
+            let Some(member) = message.as_string().and_then(|m| parse_member_message(&m)) else {
+                wasm_log!(Warn, "Invalid {} message", event_name);
+                return;
+            };
+
+            let _ = callback.call1(&JsValue::NULL, &JsValue::from(member));
+        }) as Box<dyn FnMut(JsValue)>);
+
+        self.inner
+            .callbacks
+            .write()
+            .entry(event_name.to_string())
+            .or_default()
+            .push(handler.into_js_value().unchecked_into());
+    }
+}
+
+/// Whether subscribing to `channel_name` needs the auth endpoint
+fn channel_requires_auth(channel_name: &str) -> bool {
+    channel_name.starts_with("private-") || channel_name.starts_with("presence-")
+}
+
+/// Validate and send a client event on `channel` over the client's socket
+fn send_client_event(
+    inner: &RwLock<WasmSockudoInner>,
+    channel: &str,
+    event_name: &str,
+    data: &JsValue,
+) -> bool {
+    let warn = |reason: &str| {
+        wasm_log!(Warn, "Cannot send '{}': {}", event_name, reason);
+        false
+    };
+
+    if !event_name.starts_with("client-") {
+        return warn("client events must start with 'client-'");
+    }
+    if !channel.starts_with("private-") && !channel.starts_with("presence-") {
+        return warn("client events only work on private/presence channels");
+    }
+
+    let inner = inner.read();
+    if !inner.channels.contains_key(channel) {
+        return warn(&format!("not subscribed to '{}'", channel));
+    }
+    #[cfg(feature = "test-utils")]
+    let capturing = inner.sent_frames.is_some();
+    #[cfg(not(feature = "test-utils"))]
+    let capturing = false;
+    let ws = match inner.ws {
+        Some(ref ws) if inner.state == "connected" => Some(ws),
+        _ if capturing => None,
+        _ => return warn("not connected"),
+    };
+
+    let data_str = match js_sys::JSON::stringify(data) {
+        Ok(s) => String::from(s),
+        Err(_) => return warn("failed to stringify data"),
+    };
+    let frame = serde_json::json!({
+        "event": event_name,
+        "channel": channel,
+        "data": data_str
+    })
+    .to_string();
+
+    #[cfg(feature = "test-utils")]
+    if let Some(ref frames) = inner.sent_frames {
+        frames.lock().push(frame);
+        return true;
+    }
+    ws.is_some_and(|ws| ws.send_with_str(&frame).is_ok())
+}
+
+/// Render a reconnect attempt with camelCase keys and epoch-millisecond times
+fn reconnect_attempt_json(attempt: &crate::ReconnectAttempt) -> serde_json::Value {
+    let epoch_ms = |time: std::time::SystemTime| {
+        time.duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0)
+    };
+    serde_json::json!({
+        "attemptNumber": attempt.attempt_number,
+        "startedAt": epoch_ms(attempt.started_at),
+        "connectedAt": attempt.connected_at.map(epoch_ms),
+        "error": attempt.error,
+        "delayBeforeMs": attempt.delay_before_ms,
+    })
+}
+
+/// Current wall-clock time; `SystemTime::now` is unavailable in the browser
+fn now_system_time() -> std::time::SystemTime {
+    std::time::UNIX_EPOCH + std::time::Duration::from_millis(js_sys::Date::now() as u64)
+}
+
+/// Move to state `current` and notify state callbacks.
+///
+/// Callbacks run after the lock is released so they can call back into the
+/// client. Setting the state it already has is a no-op.
+fn transition_state(inner: &RwLock<WasmSockudoInner>, current: &str) {
+    let (previous, callbacks) = {
+        let mut inner = inner.write();
+        if inner.state == current {
+            return;
+        }
+        let previous = std::mem::replace(&mut inner.state, current.to_string());
+        inner.connected_since = (current == "connected").then(js_sys::Date::now);
+        let mut callbacks = inner.state_callbacks.clone();
+        callbacks.extend(
+            inner
+                .state_entry_callbacks
+                .iter()
+                .filter(|(state, _)| *state == current)
+                .map(|(_, callback)| callback.clone()),
+        );
+        (previous, callbacks)
+    };
+
+    let change = js_sys::Object::new();
+    let _ = js_sys::Reflect::set(&change, &"previous".into(), &previous.into());
+    let _ = js_sys::Reflect::set(&change, &"current".into(), &current.into());
+    for callback in callbacks {
+        let _ = callback.call1(&JsValue::NULL, &change);
+    }
+}
+
+/// Warn once when the bindings for an event first exceed `limit`
+fn warn_listener_limit(count: usize, limit: usize, event_name: &str) {
+    if limit > 0 && count == limit + 1 {
+        wasm_log!(Warn, "Possible listener leak: {} listeners bound for '{}' (limit {}). Use setMaxListeners to raise the limit.", count, event_name, limit);
+    }
+}
+
+/// Parse a raw member_added/member_removed message into a `WasmMember`.
+/// The member data may be a JSON string or an object.
+/// Build the JS object returned by `get_delta_stats`
+fn delta_stats_object(stats: &DeltaStats, enabled: bool) -> js_sys::Object {
+    // Create a JS object with the stats
+    let obj = js_sys::Object::new();
+
+    js_sys::Reflect::set(&obj, &"enabled".into(), &JsValue::from_bool(enabled)).ok();
+    js_sys::Reflect::set(
+        &obj,
+        &"totalMessages".into(),
+        &JsValue::from_f64(stats.total_messages as f64),
+    )
+    .ok();
+    js_sys::Reflect::set(
+        &obj,
+        &"deltaMessages".into(),
+        &JsValue::from_f64(stats.delta_messages as f64),
+    )
+    .ok();
+    js_sys::Reflect::set(
+        &obj,
+        &"fullMessages".into(),
+        &JsValue::from_f64(stats.full_messages as f64),
+    )
+    .ok();
+    js_sys::Reflect::set(
+        &obj,
+        &"totalBytesWithoutCompression".into(),
+        &JsValue::from_f64(stats.total_bytes_without_compression as f64),
+    )
+    .ok();
+    js_sys::Reflect::set(
+        &obj,
+        &"totalBytesWithCompression".into(),
+        &JsValue::from_f64(stats.total_bytes_with_compression as f64),
+    )
+    .ok();
+    js_sys::Reflect::set(
+        &obj,
+        &"bandwidthSaved".into(),
+        &JsValue::from_f64(stats.bandwidth_saved as f64),
+    )
+    .ok();
+    js_sys::Reflect::set(
+        &obj,
+        &"bandwidthSavedPercent".into(),
+        &JsValue::from_f64(stats.bandwidth_saved_percent),
+    )
+    .ok();
+    js_sys::Reflect::set(
+        &obj,
+        &"errors".into(),
+        &JsValue::from_f64(stats.errors as f64),
+    )
+    .ok();
+    js_sys::Reflect::set(
+        &obj,
+        &"channelCount".into(),
+        &JsValue::from_f64(stats.channel_count as f64),
+    )
+    .ok();
+    js_sys::Reflect::set(
+        &obj,
+        &"resyncsRequested".into(),
+        &JsValue::from_f64(stats.resyncs_requested as f64),
+    )
+    .ok();
+    js_sys::Reflect::set(
+        &obj,
+        &"algorithmFallbacks".into(),
+        &JsValue::from_f64(stats.algorithm_fallbacks as f64),
+    )
+    .ok();
+    if let Some(ref algorithm) = stats.active_algorithm {
+        js_sys::Reflect::set(
+            &obj,
+            &"activeAlgorithm".into(),
+            &JsValue::from_str(algorithm),
+        )
+        .ok();
+    }
+
+    obj
+}
+
+/// Read a JS object in the shape built by `delta_stats_object`; missing
+/// or non-numeric fields count as zero
+fn delta_stats_from_object(obj: &JsValue) -> DeltaStats {
+    let number = |key: &str| {
+        js_sys::Reflect::get(obj, &JsValue::from_str(key))
+            .ok()
+            .and_then(|value| value.as_f64())
+            .unwrap_or(0.0) as u64
+    };
+    let mut stats = DeltaStats {
+        total_messages: number("totalMessages"),
+        delta_messages: number("deltaMessages"),
+        full_messages: number("fullMessages"),
+        total_bytes_without_compression: number("totalBytesWithoutCompression"),
+        total_bytes_with_compression: number("totalBytesWithCompression"),
+        bandwidth_saved: number("bandwidthSaved"),
+        errors: number("errors"),
+        channel_count: number("channelCount"),
+        resyncs_requested: number("resyncsRequested"),
+        algorithm_fallbacks: number("algorithmFallbacks"),
+        active_algorithm: js_sys::Reflect::get(obj, &"activeAlgorithm".into())
+            .ok()
+            .and_then(|value| value.as_string()),
+        ..Default::default()
+    };
+    stats.calculate_savings();
+    stats
+}
+
+/// Count one received message in `stats`
+fn record_message_stats(stats: &mut DeltaStats, is_delta: bool, message_size: usize) {
+    stats.total_messages += 1;
+    if is_delta {
+        stats.delta_messages += 1;
+        // For delta messages, compressed size is the message size
+        // decompressed would be larger (estimate 3x for now)
+        stats.total_bytes_with_compression += message_size as u64;
+        stats.total_bytes_without_compression += (message_size * 3) as u64;
+    } else {
+        stats.full_messages += 1;
+        stats.total_bytes_with_compression += message_size as u64;
+        stats.total_bytes_without_compression += message_size as u64;
+    }
+    stats.calculate_savings();
+}
+
+/// Parse the members of a presence `subscription_succeeded` frame
+fn parse_presence_members(message: &str) -> Option<Vec<WasmMember>> {
+    let message: serde_json::Value = serde_json::from_str(message).ok()?;
+    let data = match message.get("data")? {
+        serde_json::Value::String(s) => serde_json::from_str(s).ok()?,
+        other => other.clone(),
+    };
+
+    let hash = data.get("presence")?.get("hash")?.as_object()?;
+    Some(
+        hash.iter()
+            .map(|(id, info)| WasmMember {
+                id: id.clone(),
+                info: js_sys::JSON::parse(&info.to_string()).unwrap_or(JsValue::NULL),
+            })
+            .collect(),
+    )
+}
+
+fn parse_member_message(message: &str) -> Option<WasmMember> {
+    let message: serde_json::Value = serde_json::from_str(message).ok()?;
+    let data = match message.get("data")? {
+        serde_json::Value::String(s) => serde_json::from_str(s).ok()?,
+        other => other.clone(),
+    };
+
+    let id = data.get("user_id")?.as_str()?.to_string();
+    let info = match data.get("user_info") {
+        Some(info) => js_sys::JSON::parse(&info.to_string()).unwrap_or(JsValue::NULL),
+        None => JsValue::UNDEFINED,
+    };
+
+    Some(WasmMember { id, info })
 }
 
 /// WebAssembly-friendly member info
@@ -1454,10 +3382,13 @@ impl WasmMember {
     }
 }
 
-/// Initialize console error panic hook for better error messages
+/// Initialize console error panic hook for better error messages and reset
+/// the global client defaults and log level
 #[wasm_bindgen(start)]
 pub fn main() {
     console_error_panic_hook::set_once();
+    LOG_LEVEL.store(LogLevel::Debug as u8, std::sync::atomic::Ordering::Relaxed);
+    GLOBAL_DEFAULTS.with(|defaults| *defaults.borrow_mut() = None);
 }
 
 // Additional helper for console_error_panic_hook
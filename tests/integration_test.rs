@@ -256,10 +256,12 @@ async fn test_event_binding() {
         Ok(Ok(())) => {
             let channel = client.subscribe("event-test-channel").unwrap();
 
-            let binding_id = channel.bind("test-event", move |event| {
-                println!("Received event: {:?}", event);
-                event_received_clone.store(true, Ordering::SeqCst);
-            });
+            let binding_id = channel
+                .bind("test-event", move |event| {
+                    println!("Received event: {:?}", event);
+                    event_received_clone.store(true, Ordering::SeqCst);
+                })
+                .unwrap();
 
             assert!(binding_id > 0);
             println!("✓ Event binding created with ID: {}", binding_id);
@@ -573,9 +575,11 @@ async fn test_delta_compression_market_data_channel() {
         Ok(Ok(())) => {
             match client.subscribe("market-data") {
                 Ok(channel) => {
-                    channel.bind("price-update", |event| {
-                        println!("Price update: {:?}", event.data);
-                    });
+                    channel
+                        .bind("price-update", |event| {
+                            println!("Price update: {:?}", event.data);
+                        })
+                        .unwrap();
                     println!("✓ Subscribed to market-data with delta compression");
                 }
                 Err(e) => {
@@ -747,6 +751,22 @@ async fn test_xdelta3_decoder_with_vcdiff() {
     println!("✓ Xdelta3/VCDIFF decoder (using vcdiff-decoder) works correctly");
 }
 
+#[tokio::test]
+async fn test_decoder_algorithm_names() {
+    use sockudo_client::delta::{list_available, DeltaDecoder, FossilDeltaDecoder, Xdelta3Decoder};
+
+    let fossil = FossilDeltaDecoder::new();
+    let xdelta3 = Xdelta3Decoder::new();
+    assert_eq!(fossil.algorithm_name(), fossil.algorithm());
+    assert_eq!(xdelta3.algorithm_name(), xdelta3.algorithm());
+
+    let available = list_available();
+    assert!(available.contains(&"fossil"));
+    assert_eq!(available.contains(&"xdelta3"), xdelta3.is_available());
+
+    println!("✓ Decoder algorithm names match the available decoders");
+}
+
 #[tokio::test]
 async fn test_base64_encoding() {
     use sockudo_client::delta::{decode_base64, encode_base64};
@@ -102,6 +102,131 @@ fn test_delta_compression_options() {
     delta_opts.set_algorithms("fossil,xdelta3");
 }
 
+#[wasm_bindgen_test]
+fn test_delta_options_json_round_trip() {
+    console::log_1(&"Test: Delta options JSON round trip".into());
+
+    for enabled in [true, false] {
+        for debug in [true, false] {
+            for algorithms in ["fossil", "xdelta3", "fossil,xdelta3", "xdelta3,fossil"] {
+                for preferred in [None, Some("fossil"), Some("xdelta3")] {
+                    let mut delta_opts = WasmDeltaOptions::new();
+                    delta_opts.set_enabled(enabled);
+                    delta_opts.set_debug(debug);
+                    delta_opts.set_max_messages_per_key(42);
+                    delta_opts.set_algorithms(algorithms);
+                    if let Some(algorithm) = preferred {
+                        delta_opts.set_preferred_algorithm(algorithm);
+                    }
+
+                    let json = delta_opts.to_json_string();
+                    let restored = WasmDeltaOptions::from_json_string(&json).unwrap();
+                    assert_eq!(restored.enabled(), enabled);
+                    assert_eq!(restored.debug(), debug);
+                    assert_eq!(restored.max_messages_per_key(), 42);
+                    assert_eq!(restored.algorithms, delta_opts.algorithms);
+                    assert_eq!(restored.preferred_algorithm.as_deref(), preferred);
+                    assert_eq!(restored.to_json_string(), json);
+                }
+            }
+        }
+    }
+
+    assert!(WasmDeltaOptions::from_json_string("{\"enabled\":1}").is_err());
+
+    let mut options = WasmOptions::new("test-app-key");
+    options.set_cluster("mt1");
+    options.enable_delta_compression();
+    let json: serde_json::Value = serde_json::from_str(&options.to_json_string()).unwrap();
+    assert_eq!(json["app_key"], "test-app-key");
+    assert_eq!(json["delta_compression"]["enabled"], true);
+
+    let client = WasmSockudo::new("test-app-key", Some(options)).unwrap();
+    let mut saved = WasmDeltaOptions::new();
+    saved.set_algorithms("xdelta3");
+    assert!(client.restore_delta_config(&saved.to_json_string()).is_ok());
+    assert!(client.restore_delta_config("not json").is_err());
+
+    // The enabled flag is restored both ways
+    let delta_enabled = |client: &WasmSockudo| {
+        js_sys::Reflect::get(&client.get_delta_stats(), &"enabled".into())
+            .unwrap()
+            .as_bool()
+    };
+    saved.set_enabled(false);
+    client
+        .restore_delta_config(&saved.to_json_string())
+        .unwrap();
+    assert_eq!(delta_enabled(&client), Some(false));
+    saved.set_enabled(true);
+    client
+        .restore_delta_config(&saved.to_json_string())
+        .unwrap();
+    assert_eq!(delta_enabled(&client), Some(true));
+}
+
+#[wasm_bindgen_test]
+fn test_auth_hmac_option() {
+    let mut options = WasmOptions::new("test-app-key");
+    options.set_auth_endpoint("http://localhost:3000/pusher/auth");
+    options.set_auth_hmac("app-1", "s3cret");
+    // The secret is used to sign auth requests but never serialized
+    assert!(!options.to_json_string().contains("s3cret"));
+    assert!(WasmSockudo::new("test-app-key", Some(options)).is_ok());
+}
+
+#[wasm_bindgen_test]
+fn test_subscribe_with_metadata() {
+    console::log_1(&"Test: Subscribe with metadata".into());
+
+    let client = WasmSockudo::new("test-app-key", Some(WasmOptions::new("test-app-key"))).unwrap();
+
+    let metadata = js_sys::Object::new();
+    js_sys::Reflect::set(&metadata, &"device_id".into(), &"abc".into()).unwrap();
+    let channel = client.subscribe_with_metadata("news", metadata).unwrap();
+    assert_eq!(channel.name(), "news");
+
+    let invalid = js_sys::Object::new();
+    js_sys::Reflect::set(&invalid, &"retries".into(), &JsValue::from(3)).unwrap();
+    assert!(client.subscribe_with_metadata("sports", invalid).is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_log_level_and_global_defaults() {
+    console::log_1(&"Test: Log level and global defaults".into());
+
+    assert!(WasmSockudo::set_log_level("verbose").is_err());
+    WasmSockudo::set_log_level("error").unwrap();
+    assert_eq!(WasmSockudo::log_level(), "error");
+    assert!(!WasmSockudo::is_log_enabled("debug"));
+    assert!(!WasmSockudo::is_log_enabled("info"));
+    assert!(!WasmSockudo::is_log_enabled("warn"));
+    assert!(WasmSockudo::is_log_enabled("error"));
+
+    let mut defaults = WasmOptions::new("ignored-key");
+    defaults.set_cluster("eu");
+    WasmSockudo::set_global_defaults(Some(defaults));
+    let client = WasmSockudo::new("test-app-key", None).unwrap();
+    assert!(client.socket_id().is_none());
+    WasmSockudo::set_global_defaults(None);
+
+    WasmSockudo::set_log_level("debug").unwrap();
+    assert!(WasmSockudo::is_log_enabled("info"));
+}
+
+#[wasm_bindgen_test]
+fn test_heartbeat_options() {
+    console::log_1(&"Test: Heartbeat options".into());
+
+    let mut options = WasmOptions::new("test-app-key");
+    options.set_heartbeat_event("app:heartbeat");
+    let json: serde_json::Value = serde_json::from_str(&options.to_json_string()).unwrap();
+    assert_eq!(json["heartbeat_event"], "app:heartbeat");
+
+    let client = WasmSockudo::new("test-app-key", Some(options)).unwrap();
+    client.on_heartbeat(js_sys::Function::new_no_args(""));
+}
+
 #[wasm_bindgen_test]
 fn test_client_with_delta_compression() {
     console::log_1(&"Test: Client with delta compression".into());
@@ -323,6 +448,34 @@ fn test_disconnect() {
     );
 }
 
+#[wasm_bindgen_test]
+fn test_connection_state_callbacks() {
+    console::log_1(&"Test: Connection state callbacks".into());
+
+    let options = create_test_options("test-app-key");
+    let client = WasmSockudo::new("test-app-key", Some(options)).unwrap();
+
+    let record = js_sys::Function::new_with_args("e", "this.push(e.previous + '->' + e.current)");
+    let changes = js_sys::Array::new();
+    let disconnects = js_sys::Array::new();
+    let connects = js_sys::Array::new();
+    client.bind_connection_state(record.bind(&changes));
+    client.on_disconnected(record.bind(&disconnects));
+    client.on_connected(record.bind(&connects));
+
+    client.disconnect();
+    // Repeating the current state is not a change
+    client.disconnect();
+
+    assert_eq!(changes.length(), 1);
+    assert_eq!(
+        changes.get(0).as_string().as_deref(),
+        Some("initialized->disconnected")
+    );
+    assert_eq!(disconnects.length(), 1);
+    assert_eq!(connects.length(), 0);
+}
+
 #[wasm_bindgen_test]
 fn test_options_getters_setters() {
     console::log_1(&"Test: Options getters and setters".into());
@@ -362,6 +515,37 @@ fn test_options_getters_setters() {
     );
 }
 
+#[wasm_bindgen_test]
+fn test_options_from_js_object() {
+    console::log_1(&"Test: Options from JS object".into());
+
+    let config = js_sys::Object::new();
+    let set = |key: &str, value: JsValue| {
+        js_sys::Reflect::set(&config, &JsValue::from_str(key), &value).unwrap();
+    };
+    set("key", JsValue::from_str("abc"));
+    set("cluster", JsValue::from_str("mt1"));
+    set("wsHost", JsValue::from_str("localhost"));
+    set("wsPort", JsValue::from_f64(6001.0));
+    set("forceTLS", JsValue::from_bool(false));
+    set("unknownField", JsValue::from_str("ignored"));
+
+    let options = WasmOptions::from_js_object(&config).expect("Config should be accepted");
+    assert_eq!(options.cluster(), Some("mt1".to_string()));
+    assert_eq!(options.ws_host(), Some("localhost".to_string()));
+    assert_eq!(options.ws_port(), Some(6001));
+    assert_eq!(options.use_tls(), Some(false));
+    assert!(options.auth_endpoint().is_none());
+
+    let client = WasmSockudo::new_from_js_object(config).expect("Client should be created");
+    assert_eq!(client.state(), "initialized");
+
+    let missing_key = js_sys::Object::new();
+    js_sys::Reflect::set(&missing_key, &"cluster".into(), &"mt1".into()).unwrap();
+    let err = WasmOptions::from_js_object(&missing_key).unwrap_err();
+    assert!(err.as_string().unwrap().contains("key"));
+}
+
 #[wasm_bindgen_test]
 fn test_enable_delta_compression_shorthand() {
     console::log_1(&"Test: Enable delta compression shorthand".into());
@@ -465,6 +649,30 @@ fn test_send_event_to_channel() {
     );
 }
 
+#[wasm_bindgen_test]
+fn test_available_algorithms() {
+    let algorithms = WasmSockudo::get_available_algorithms();
+    assert!(algorithms
+        .iter()
+        .any(|name| name.as_string().as_deref() == Some("fossil")));
+}
+
+#[wasm_bindgen_test]
+fn test_set_channel_delta_algorithm() {
+    let options = create_test_options("test-app-key");
+    let client = WasmSockudo::new("test-app-key", Some(options)).unwrap();
+
+    assert!(client
+        .set_channel_delta_algorithm("ticker", "fossil")
+        .is_ok());
+    assert!(client
+        .set_channel_delta_algorithm("ticker", "vcdiff")
+        .is_ok());
+    assert!(client
+        .set_channel_delta_algorithm("ticker", "brotli")
+        .is_err());
+}
+
 #[wasm_bindgen_test]
 fn test_delta_stats() {
     console::log_1(&"Test: Delta stats".into());
@@ -483,6 +691,35 @@ fn test_delta_stats() {
     client.reset_delta_stats();
 }
 
+#[wasm_bindgen_test]
+fn test_merge_delta_stats() {
+    console::log_1(&"Test: Merge delta stats".into());
+
+    let options = create_test_options("test-app-key");
+    let client = WasmSockudo::new("test-app-key", Some(options)).unwrap();
+
+    let other = js_sys::Object::new();
+    let set = |key: &str, value: f64| {
+        js_sys::Reflect::set(&other, &key.into(), &JsValue::from_f64(value)).unwrap();
+    };
+    set("totalMessages", 4.0);
+    set("totalBytesWithoutCompression", 1000.0);
+    set("totalBytesWithCompression", 250.0);
+    set("channelCount", 2.0);
+
+    let merged = client.merge_delta_stats(other.into());
+    let get = |key: &str| {
+        js_sys::Reflect::get(&merged, &key.into())
+            .unwrap()
+            .as_f64()
+            .unwrap()
+    };
+    assert_eq!(get("totalMessages"), 4.0);
+    assert_eq!(get("bandwidthSaved"), 750.0);
+    assert_eq!(get("bandwidthSavedPercent"), 75.0);
+    assert_eq!(get("channelCount"), 2.0);
+}
+
 #[wasm_bindgen_test]
 fn test_unbind_operations() {
     console::log_1(&"Test: Unbind operations".into());
@@ -511,3 +748,399 @@ fn test_channel_unbind_operations() {
     let channel = channel.unbind_global();
     let _channel = channel.unbind_all();
 }
+
+#[wasm_bindgen_test]
+fn test_channel_on_subscription_failed() {
+    console::log_1(&"Test: Channel onSubscriptionFailed".into());
+
+    let options = create_test_options("test-app-key");
+    let client = WasmSockudo::new("test-app-key", Some(options)).unwrap();
+    let channel = client.subscribe("test-channel", None).unwrap();
+
+    let callback = js_sys::Function::new_with_args("message", "return message;");
+    let channel = channel.on_subscription_failed(callback);
+    assert!(channel.has_listeners("pusher:subscription_error"));
+}
+
+#[wasm_bindgen_test]
+fn test_reconnect_history_starts_empty() {
+    console::log_1(&"Test: Reconnect history".into());
+
+    let options = create_test_options("test-app-key");
+    let client = WasmSockudo::new("test-app-key", Some(options)).unwrap();
+
+    assert_eq!(client.get_reconnect_history().length(), 0);
+}
+
+#[wasm_bindgen_test]
+async fn test_probe_connection_times_out_without_server() {
+    console::log_1(&"Test: probeConnection timeout".into());
+
+    let mut options = WasmOptions::new("test-app-key");
+    options.set_ws_host("127.0.0.1");
+    options.set_ws_port(1);
+    options.set_use_tls(false);
+    let client = WasmSockudo::new("test-app-key", Some(options)).unwrap();
+
+    let result = wasm_bindgen_futures::JsFuture::from(client.probe_connection(200)).await;
+    assert!(result.is_err());
+    assert_eq!(client.get_ping_rtt_ms(), None);
+    assert_eq!(client.state(), "initialized");
+}
+
+#[wasm_bindgen_test]
+async fn test_subscribe_all() {
+    console::log_1(&"Test: subscribeAll".into());
+
+    // No auth endpoint, so the private channel cannot be authenticated
+    let options = create_test_options("test-app-key");
+    let client = WasmSockudo::new("test-app-key", Some(options)).unwrap();
+    let names: js_sys::Array = ["news", "private-orders", "prices", "sports"]
+        .into_iter()
+        .map(JsValue::from_str)
+        .collect();
+
+    let result = wasm_bindgen_futures::JsFuture::from(client.subscribe_batch(names))
+        .await
+        .unwrap();
+    let channels = js_sys::Array::from(&result);
+    assert_eq!(channels.length(), 4);
+    assert!(channels.get(1).is_null());
+    assert_eq!(channels.iter().filter(|c| !c.is_null()).count(), 3);
+    assert_eq!(client.channel_names().length(), 3);
+}
+
+#[wasm_bindgen_test]
+fn test_send_event_to_channel_requires_subscription() {
+    console::log_1(&"Test: sendEventToChannel".into());
+
+    let options = create_test_options("test-app-key");
+    let client = WasmSockudo::new("test-app-key", Some(options)).unwrap();
+    let data = JsValue::from_str("hello");
+
+    // Not subscribed
+    assert!(!client.send_event_to_channel("private-chat", "client-typing", data.clone()));
+
+    let channel = client.subscribe("private-chat", None).unwrap();
+    // Wrong event prefix
+    assert!(!client.send_event_to_channel("private-chat", "typing", data.clone()));
+    // Subscribed but not connected
+    assert!(!client.send_event_to_channel("private-chat", "client-typing", data.clone()));
+    assert!(!channel.send_event("client-typing", data.clone()));
+
+    // Public channels never carry client events
+    client.subscribe("public-chat", None).unwrap();
+    assert!(!client.send_event_to_channel("public-chat", "client-typing", data));
+}
+
+#[cfg(feature = "test-utils")]
+#[wasm_bindgen_test]
+fn test_send_event_to_channel_frame() {
+    let options = create_test_options("test-app-key");
+    let client = WasmSockudo::new("test-app-key", Some(options)).unwrap();
+    let channel = client.subscribe("private-chat", None).unwrap();
+    client.capture_sent_frames();
+
+    let data = js_sys::JSON::parse(r#"{"text":"hi"}"#).unwrap();
+    assert!(client.send_event_to_channel("private-chat", "client-typing", data.clone()));
+    assert!(channel.send_event("client-typing", data));
+
+    let frames = client.take_sent_frames();
+    assert_eq!(frames.length(), 2);
+    for frame in frames.iter() {
+        let frame: serde_json::Value = serde_json::from_str(&frame.as_string().unwrap()).unwrap();
+        assert_eq!(
+            frame,
+            serde_json::json!({
+                "event": "client-typing",
+                "channel": "private-chat",
+                "data": r#"{"text":"hi"}"#,
+            })
+        );
+    }
+    assert_eq!(client.take_sent_frames().length(), 0);
+}
+
+#[wasm_bindgen_test]
+fn test_debug_dump() {
+    console::log_1(&"Test: debugDump".into());
+
+    let options = create_test_options("test-app-key");
+    let client = WasmSockudo::new("test-app-key", Some(options)).unwrap();
+    client.subscribe("dump-channel", None).unwrap();
+
+    let dump = client.debug_dump();
+    let state = js_sys::Reflect::get(&dump, &"state".into()).unwrap();
+    assert_eq!(state.as_string().as_deref(), Some("initialized"));
+    let count = js_sys::Reflect::get(&dump, &"channelCount".into()).unwrap();
+    assert_eq!(count.as_f64(), Some(1.0));
+    let socket_id = js_sys::Reflect::get(&dump, &"socketId".into()).unwrap();
+    assert!(socket_id.is_null());
+}
+
+#[cfg(feature = "test-utils")]
+#[wasm_bindgen_test]
+fn test_debug_dump_channel_lists() {
+    console::log_1(&"Test: debugDump subscribed and pending channels".into());
+
+    let options = create_test_options("test-app-key");
+    let client = WasmSockudo::new("test-app-key", Some(options)).unwrap();
+    client.subscribe("news", None).unwrap();
+    client.subscribe("sports", None).unwrap();
+    client.inject_message(
+        r#"{"event":"pusher_internal:subscription_succeeded","channel":"news","data":"{}"}"#,
+    );
+
+    let list = |dump: &JsValue, key: &str| -> Vec<String> {
+        js_sys::Array::from(&js_sys::Reflect::get(dump, &key.into()).unwrap())
+            .iter()
+            .filter_map(|name| name.as_string())
+            .collect()
+    };
+    let dump = client.debug_dump();
+    assert_eq!(list(&dump, "subscribedChannels"), vec!["news".to_string()]);
+    assert_eq!(list(&dump, "pendingChannels"), vec!["sports".to_string()]);
+    let count = js_sys::Reflect::get(&dump, &"channelCount".into()).unwrap();
+    assert_eq!(count.as_f64(), Some(2.0));
+}
+
+#[cfg(feature = "test-utils")]
+#[wasm_bindgen_test]
+fn test_delta_stats_for_channel() {
+    console::log_1(&"Test: get_delta_stats_for_channel".into());
+
+    let options = create_test_options("test-app-key");
+    let client = WasmSockudo::new("test-app-key", Some(options)).unwrap();
+    client.inject_message(r#"{"event":"price","channel":"foo","data":"{\"p\":1}"}"#);
+    client.inject_message(
+        r#"{"event":"pusher:delta","channel":"foo","data":{"event":"price","delta":"AAAA","seq":1}}"#,
+    );
+
+    let get = |stats: &JsValue, key: &str| {
+        js_sys::Reflect::get(stats, &key.into())
+            .unwrap()
+            .as_f64()
+            .unwrap()
+    };
+    let stats = client.get_delta_stats_for_channel("foo");
+    assert_eq!(get(&stats, "totalMessages"), 2.0);
+    assert_eq!(get(&stats, "deltaMessages"), 1.0);
+    assert_eq!(get(&stats, "fullMessages"), 1.0);
+    assert!(client.get_delta_stats_for_channel("bar").is_null());
+
+    let all = client.get_all_channel_delta_stats();
+    let foo = js_sys::Reflect::get(&all, &"foo".into()).unwrap();
+    assert_eq!(get(&foo, "deltaMessages"), 1.0);
+    assert_eq!(get(&client.get_delta_stats(), "channelCount"), 1.0);
+}
+
+#[wasm_bindgen_test]
+fn test_filter_query_string() {
+    console::log_1(&"Test: WasmFilterOp::fromQueryString".into());
+
+    let filter =
+        WasmFilterOp::from_query_string("type:eq:goal,league:in:premier,champions").unwrap();
+    assert_eq!(
+        filter.to_query_string(),
+        "type:eq:goal,league:in:premier|champions"
+    );
+    let reparsed = WasmFilterOp::from_query_string(&filter.to_query_string()).unwrap();
+    assert_eq!(reparsed.to_json(), filter.to_json());
+
+    assert!(WasmFilterOp::from_query_string("type:like:goal").is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_filter_from_json() {
+    use wasm_bindgen::JsCast;
+
+    console::log_1(&"Test: WasmFilterOp::fromJSON".into());
+
+    let filters = vec![
+        WasmFilterOp::eq("status", "active"),
+        WasmFilterOp::neq("status", "inactive"),
+        WasmFilterOp::lt("age", "18"),
+        WasmFilterOp::lte("age", "18"),
+        WasmFilterOp::gt("score", "100"),
+        WasmFilterOp::gte("score", "100"),
+        WasmFilterOp::in_set("role", vec!["admin".to_string(), "moderator".to_string()]),
+        WasmFilterOp::not_in("role", vec!["banned".to_string()]),
+        WasmFilterOp::exists("premium"),
+        WasmFilterOp::not_exists("banned"),
+        WasmFilterOp::json_path("user.role", "eq", "admin").unwrap(),
+        WasmFilterOp::timestamp_after("created_at", 1_700_000_000.0),
+        WasmFilterOp::and(vec![
+            WasmFilterOp::eq("a", "1"),
+            WasmFilterOp::or(vec![WasmFilterOp::eq("b", "2"), WasmFilterOp::exists("c")]),
+        ]),
+        WasmFilterOp::not(WasmFilterOp::eq("d", "4")),
+        WasmFilterOp::nand(vec![WasmFilterOp::eq("e", "5"), WasmFilterOp::eq("f", "6")]),
+        WasmFilterOp::nor(vec![WasmFilterOp::eq("g", "7"), WasmFilterOp::eq("h", "8")]),
+        WasmFilterOp::xor(WasmFilterOp::eq("i", "9"), WasmFilterOp::exists("j")),
+    ];
+    for filter in filters {
+        let parsed = WasmFilterOp::from_json(&filter.to_json()).unwrap();
+        assert_eq!(parsed.to_json(), filter.to_json());
+        assert_eq!(parsed.clone_filter().to_json(), filter.to_json());
+
+        let object: js_sys::Object = js_sys::JSON::parse(&filter.to_json())
+            .unwrap()
+            .unchecked_into();
+        let from_object = WasmFilterOp::from_js_object(object).unwrap();
+        assert_eq!(from_object.to_json(), filter.to_json());
+    }
+
+    let error = WasmFilterOp::from_json("{\"op\": ").unwrap_err();
+    assert!(error
+        .as_string()
+        .unwrap()
+        .starts_with("Invalid filter JSON"));
+    assert!(WasmFilterOp::from_json(r#"{"unknown": true}"#).is_err());
+}
+
+#[cfg(feature = "test-utils")]
+#[wasm_bindgen_test]
+fn test_on_server_error() {
+    console::log_1(&"Test: onServerError".into());
+
+    let options = create_test_options("test-app-key");
+    let client = WasmSockudo::new("test-app-key", Some(options)).unwrap();
+
+    let record = js_sys::Function::new_with_args("e", "this.push(e.code + ':' + e.isFatal)");
+    let errors = js_sys::Array::new();
+    client.on_server_error(record.bind(&errors));
+
+    client.inject_message(
+        r#"{"event":"pusher:error","data":{"code":4201,"message":"Pong reply not received"}}"#,
+    );
+    assert_ne!(client.state(), "failed");
+
+    client
+        .inject_message(r#"{"event":"pusher:error","data":{"code":4004,"message":"Over quota"}}"#);
+    assert_eq!(client.state(), "failed");
+
+    assert_eq!(errors.length(), 2);
+    assert_eq!(errors.get(0).as_string().as_deref(), Some("4201:false"));
+    assert_eq!(errors.get(1).as_string().as_deref(), Some("4004:true"));
+}
+
+#[cfg(feature = "test-utils")]
+#[wasm_bindgen_test]
+fn test_channel_event_history() {
+    console::log_1(&"Test: Channel event history".into());
+
+    let options = create_test_options("test-app-key");
+    let client = WasmSockudo::new("test-app-key", Some(options)).unwrap();
+    let channel = client.subscribe("prices", None).unwrap();
+    channel.set_history_size(2);
+
+    let start = js_sys::Date::now();
+    for i in 0..3 {
+        client.inject_message(&format!(
+            r#"{{"event":"tick","channel":"prices","data":{{"i":{}}}}}"#,
+            i
+        ));
+    }
+    client.inject_message(r#"{"event":"close","channel":"prices","data":{}}"#);
+
+    let ticks = channel.get_event_history(Some("tick".to_string()), 10);
+    assert_eq!(ticks.length(), 2);
+    let data = js_sys::Reflect::get(&ticks.get(0), &"data".into()).unwrap();
+    let newest = js_sys::Reflect::get(&data, &"i".into()).unwrap();
+    assert_eq!(newest.as_f64(), Some(2.0));
+    // Events are stamped when the message loop receives them
+    let received = js_sys::Reflect::get(&ticks.get(0), &"timestamp".into())
+        .unwrap()
+        .as_f64()
+        .unwrap();
+    assert!(received >= start && received <= js_sys::Date::now());
+
+    assert_eq!(channel.get_event_history(None, 10).length(), 3);
+    assert_eq!(channel.get_event_history(None, 1).length(), 1);
+}
+
+#[wasm_bindgen_test]
+fn test_client_id() {
+    console::log_1(&"Test: Client ID".into());
+
+    let mut options = create_test_options("test-app-key");
+    assert_eq!(options.client_id(), None);
+    options.set_client_id("device 42");
+    assert_eq!(options.client_id().as_deref(), Some("device 42"));
+    let client = WasmSockudo::new("test-app-key", Some(options)).unwrap();
+    assert_eq!(client.client_id(), "device-42");
+
+    // Without one, each client gets a random hex ID
+    let client =
+        WasmSockudo::new("test-app-key", Some(create_test_options("test-app-key"))).unwrap();
+    assert!(!client.client_id().is_empty());
+    assert!(client.client_id().chars().all(|c| c.is_ascii_hexdigit()));
+}
+
+#[wasm_bindgen_test]
+fn test_filter_set_operations() {
+    console::log_1(&"Test: WasmFilterOp set operations".into());
+
+    let a = || WasmFilterOp::eq("a", "1");
+    let b = || WasmFilterOp::exists("b");
+
+    assert_eq!(
+        a().intersection(&b()).to_json(),
+        WasmFilterOp::and(vec![a(), b()]).to_json()
+    );
+    assert_eq!(
+        a().union(&b()).to_json(),
+        WasmFilterOp::or(vec![a(), b()]).to_json()
+    );
+    assert_eq!(
+        WasmFilterOp::difference(a(), b()).to_json(),
+        WasmFilterOp::and(vec![a(), WasmFilterOp::not(b())]).to_json()
+    );
+    assert_eq!(
+        WasmFilterOp::xor(a(), b()).to_json(),
+        WasmFilterOp::or(vec![
+            WasmFilterOp::and(vec![a(), WasmFilterOp::not(b())]),
+            WasmFilterOp::and(vec![WasmFilterOp::not(a()), b()]),
+        ])
+        .to_json()
+    );
+}
+
+#[cfg(feature = "test-utils")]
+#[wasm_bindgen_test]
+async fn test_presence_kick_after_subscription() {
+    console::log_1(&"Test: WasmPresenceChannel::kick".into());
+
+    let options = create_test_options("test-app-key");
+    let client = WasmSockudo::new("test-app-key", Some(options)).unwrap();
+    client.subscribe("presence-room", None).unwrap();
+    let presence = client.presence_channel("presence-room").unwrap();
+    assert!(client.presence_channel("news").is_none());
+
+    // Not subscribed yet
+    let result = wasm_bindgen_futures::JsFuture::from(presence.kick("bob", None)).await;
+    assert!(result.is_err());
+
+    client.inject_message(
+        r#"{"event":"pusher_internal:subscription_succeeded","channel":"presence-room","data":"{\"presence\":{\"count\":2,\"ids\":[\"alice\",\"bob\"],\"hash\":{\"alice\":{},\"bob\":{\"name\":\"Bob\"}}}}"}"#,
+    );
+    assert!(client.channel("presence-room").unwrap().subscribed());
+    assert_eq!(presence.count(), 2);
+
+    // Resolves; there is no socket, so nothing is actually sent
+    let sent = wasm_bindgen_futures::JsFuture::from(presence.kick("bob", Some("spam".into())))
+        .await
+        .unwrap();
+    assert_eq!(sent.as_bool(), Some(false));
+
+    // The server re-broadcasts the kick
+    client.inject_message(
+        r#"{"event":"pusher_internal:member_kicked","channel":"presence-room","data":"{\"user_id\":\"bob\",\"reason\":\"spam\"}"}"#,
+    );
+    assert_eq!(presence.count(), 1);
+    assert!(presence.get("bob").is_none());
+
+    client.disconnect();
+    assert!(!client.channel("presence-room").unwrap().subscribed());
+    assert_eq!(presence.count(), 0);
+}